@@ -0,0 +1,164 @@
+//! Failover between matrix engines when the AI provider is unhealthy.
+//!
+//! Order: primary provider (`ANTHROPIC_API_KEY`), then the secondary provider
+//! when `AI_FALLBACK_MODEL` is set, then the [`heuristic`](super::heuristic)
+//! generator. Only transient errors (5xx, 429, timeouts) trigger a failover;
+//! after one, the primary is skipped for [`PRIMARY_COOLDOWN`].
+
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::heuristic::heuristic_matrix;
+use super::{AiClient, AiError};
+use crate::transform::dsl::TransformationMatrix;
+
+/// How long the primary provider is skipped after it failed
+pub const PRIMARY_COOLDOWN: Duration = Duration::from_secs(300);
+
+/// Engine that produced a matrix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Engine {
+    Primary,
+    Secondary,
+    Heuristic,
+}
+
+/// Which engine generated the matrix, and why the primary was not used
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineReport {
+    pub engine: Engine,
+    /// Model name (None for the heuristic generator)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Why the previous engine(s) were skipped (None without failover)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failover_reason: Option<String>,
+}
+
+impl EngineReport {
+    pub fn is_failover(&self) -> bool {
+        self.engine != Engine::Primary
+    }
+}
+
+/// Health of the primary provider, shared by all requests of the process
+#[derive(Debug, Default)]
+pub struct ProviderHealth {
+    down_until: Option<Instant>,
+}
+
+impl ProviderHealth {
+    pub const fn new() -> Self {
+        Self { down_until: None }
+    }
+
+    /// Whether the provider should be skipped at `now`
+    pub fn is_down(&self, now: Instant) -> bool {
+        self.down_until.is_some_and(|until| now < until)
+    }
+
+    pub fn mark_down(&mut self, now: Instant) {
+        self.down_until = Some(now + PRIMARY_COOLDOWN);
+    }
+
+    pub fn mark_up(&mut self) {
+        self.down_until = None;
+    }
+}
+
+static PRIMARY_HEALTH: Mutex<ProviderHealth> = Mutex::new(ProviderHealth::new());
+
+fn primary_is_down() -> bool {
+    PRIMARY_HEALTH.lock().map(|h| h.is_down(Instant::now())).unwrap_or(false)
+}
+
+fn set_primary_health(up: bool) {
+    if let Ok(mut health) = PRIMARY_HEALTH.lock() {
+        if up { health.mark_up() } else { health.mark_down(Instant::now()) }
+    }
+}
+
+/// Generate a matrix with the first healthy engine.
+///
+/// `generate` runs one AI client (with its own retries). Non-transient errors
+/// (missing key, unparsable matrix) are returned as-is, without failover.
+pub async fn generate_with_failover<F, Fut>(
+    headers: &[String],
+    generate: F,
+) -> Result<(TransformationMatrix, EngineReport), AiError>
+where
+    F: Fn(AiClient) -> Fut,
+    Fut: Future<Output = Result<TransformationMatrix, AiError>>,
+{
+    let primary = AiClient::from_env()?;
+    let mut reasons = Vec::new();
+
+    if primary_is_down() {
+        reasons.push("primary provider failed recently, skipped during cooldown".to_string());
+    } else {
+        let model = primary.model().to_string();
+        match generate(primary).await {
+            Ok(matrix) => {
+                set_primary_health(true);
+                return Ok((matrix, EngineReport { engine: Engine::Primary, model: Some(model), failover_reason: None }));
+            }
+            Err(e) if e.is_transient() => {
+                set_primary_health(false);
+                reasons.push(format!("primary provider unavailable ({})", e));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    if let Some(secondary) = AiClient::secondary_from_env() {
+        let model = secondary.model().to_string();
+        match generate(secondary).await {
+            Ok(matrix) => {
+                return Ok((matrix, EngineReport {
+                    engine: Engine::Secondary,
+                    model: Some(model),
+                    failover_reason: Some(reasons.join("; ")),
+                }));
+            }
+            Err(e) if e.is_transient() => reasons.push(format!("secondary provider unavailable ({})", e)),
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok((heuristic_matrix(headers), EngineReport {
+        engine: Engine::Heuristic,
+        model: None,
+        failover_reason: Some(reasons.join("; ")),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_health_cooldown() {
+        let now = Instant::now();
+        let mut health = ProviderHealth::new();
+        assert!(!health.is_down(now));
+
+        health.mark_down(now);
+        assert!(health.is_down(now + Duration::from_secs(10)));
+        assert!(!health.is_down(now + PRIMARY_COOLDOWN));
+
+        health.mark_down(now);
+        health.mark_up();
+        assert!(!health.is_down(now));
+    }
+
+    #[test]
+    fn test_transient_errors() {
+        assert!(AiError::Unavailable("HTTP 529".to_string()).is_transient());
+        assert!(!AiError::ParseError("bad matrix".to_string()).is_transient());
+        assert!(!AiError::MissingApiKey("ANTHROPIC_API_KEY not set".to_string()).is_transient());
+    }
+}
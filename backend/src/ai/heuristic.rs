@@ -0,0 +1,130 @@
+//! Offline matrix generator: maps columns from their names alone.
+//!
+//! Used as the last failover engine when no AI provider answers. Columns are
+//! matched on usual header names (French, English, German exports) and the
+//! role codes listed in the system prompt; anything else is left unmapped.
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::transform::dsl::matrix::{FieldTransform, TransformationMatrix};
+use crate::transform::dsl::operations::Operation;
+
+/// Header name hints per target field, checked in this order.
+///
+/// Headers are compared lowercased with non-alphanumeric characters removed;
+/// a hint matches when the header equals it or contains it.
+const FIELD_HINTS: &[(&str, &[&str])] = &[
+    ("iswc", &["iswc"]),
+    ("creatorIsni", &["isni"]),
+    ("creatorIpi", &["ipi", "cae"]),
+    ("creatorRole", &["role", "qualite", "qualité", "fonction", "funktion"]),
+    ("title", &["titre", "title", "titel", "oeuvre", "œuvre", "werk"]),
+    // Before `creatorShare`: "Performance share" also contains "share"
+    ("creatorPerformanceShare", &["performanceshare", "prshare", "execution", "exécution", "auffuhrung", "aufführung"]),
+    ("creatorMechanicalShare", &["mechanicalshare", "mrshare", "reproduction", "mecanique", "mécanique", "mechanisch"]),
+    ("creatorShare", &["quotepart", "share", "anteil", "pourcentage"]),
+    ("creatorName", &["ayantdroit", "nom", "name"]),
+    ("creationYear", &["annee", "année", "year", "jahr", "datecreation", "datedecreation"]),
+    // Before `language`: "Langue du titre" also contains "langue"
+    ("titleLanguage", &["languedutitre", "languetitre", "titlelanguage", "titelsprache"]),
+    ("language", &["langue", "language", "sprache"]),
+    ("bpm", &["bpm", "tempo"]),
+    ("catalogNumber", &["catalogue", "catalog", "katalog"]),
+    ("opus", &["opus"]),
+];
+
+/// Role codes mapped to MIDDS roles (see the system prompt)
+const ROLE_CODES: &[(&str, &str)] = &[
+    ("CA", "Composer"), ("C+A", "Composer"), ("C", "Composer"), ("COMP", "Composer"),
+    ("COMPOSER", "Composer"), ("COMPOSITEUR", "Composer"), ("KOMPONIST", "Composer"),
+    ("A", "Author"), ("AUTEUR", "Author"), ("AUTHOR", "Author"), ("AUTOR", "Author"),
+    ("LYRICIST", "Author"), ("TEXTDICHTER", "Author"),
+    ("AR", "Arranger"), ("ARR", "Arranger"), ("ARRANGER", "Arranger"), ("ARRANGEUR", "Arranger"),
+    ("AD", "Adapter"), ("ADAPT", "Adapter"), ("ADAPTER", "Adapter"), ("ADAPTATEUR", "Adapter"),
+    ("E", "Publisher"), ("ED", "Publisher"), ("PUB", "Publisher"), ("PUBLISHER", "Publisher"),
+    ("EDITEUR", "Publisher"), ("ÉDITEUR", "Publisher"), ("VERLAG", "Publisher"),
+];
+
+/// Build a matrix by matching header names, each column used at most once
+pub fn heuristic_matrix(headers: &[String]) -> TransformationMatrix {
+    let normalized: Vec<String> = headers.iter().map(|h| normalize(h)).collect();
+    let mut used = vec![false; headers.len()];
+    let mut matrix = TransformationMatrix::new();
+    matrix.description = "Generated from column names (no AI provider available)".to_string();
+
+    for (field, hints) in FIELD_HINTS {
+        let found = hints.iter().find_map(|hint| {
+            (0..headers.len()).find(|&i| !used[i] && normalized[i] == *hint)
+                .or_else(|| (0..headers.len()).find(|&i| !used[i] && normalized[i].contains(hint)))
+        });
+        if let Some(i) = found {
+            used[i] = true;
+            matrix.transforms.insert(field.to_string(), field_transform(field, &headers[i]));
+        }
+    }
+    matrix
+}
+
+/// Transformation of one column into a target field
+pub(crate) fn field_transform(field: &str, column: &str) -> FieldTransform {
+    let transform = FieldTransform::from_source(column).with_operation(Operation::Trim);
+    match field {
+        "iswc" => transform
+            .with_operation(Operation::Uppercase)
+            .with_operation(Operation::Replace { pattern: "[-. ]".to_string(), value: String::new() })
+            .with_operation(Operation::EnsurePrefix { value: "T".to_string() })
+            .required(),
+        "title" => transform.required(),
+        "creatorIpi" => transform
+            .with_operation(Operation::DigitsOnly)
+            .with_operation(Operation::ToNumber)
+            .required(),
+        "creatorRole" => transform
+            .with_operation(Operation::Uppercase)
+            .with_operation(Operation::Map {
+                mapping: ROLE_CODES.iter().map(|(code, role)| (code.to_string(), role.to_string())).collect::<HashMap<_, _>>(),
+                case_insensitive: true,
+                default_unmapped: None,
+            })
+            .with_default(Value::String("Composer".to_string()))
+            .required(),
+        "creatorIsni" => transform
+            .with_operation(Operation::Alphanumeric)
+            .with_operation(Operation::Uppercase),
+        "creationYear" => transform
+            .with_operation(Operation::ExtractYear)
+            .with_operation(Operation::ToNumber)
+            .with_operation(Operation::ClampYear { min: crate::validation::DEFAULT_MIN_YEAR, max: None }),
+        "creatorShare" | "creatorPerformanceShare" | "creatorMechanicalShare" => transform.with_operation(Operation::ToDecimal),
+        "bpm" => transform
+            .with_operation(Operation::DigitsOnly)
+            .with_operation(Operation::ToNumber),
+        _ => transform,
+    }
+}
+
+/// Lowercase a header and keep only its letters and digits
+fn normalize(header: &str) -> String {
+    header.to_lowercase().chars().filter(|c| c.is_alphanumeric()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heuristic_matrix_maps_usual_headers() {
+        let headers: Vec<String> = ["Code ISWC", "Titre de l'oeuvre", "IPI", "Qualité", "Année", "Commentaire"]
+            .iter().map(|h| h.to_string()).collect();
+        let matrix = heuristic_matrix(&headers);
+
+        let source = |field: &str| matrix.transforms.get(field).and_then(|t| t.source.clone());
+        assert_eq!(source("iswc").as_deref(), Some("Code ISWC"));
+        assert_eq!(source("title").as_deref(), Some("Titre de l'oeuvre"));
+        assert_eq!(source("creatorIpi").as_deref(), Some("IPI"));
+        assert_eq!(source("creatorRole").as_deref(), Some("Qualité"));
+        assert_eq!(source("creationYear").as_deref(), Some("Année"));
+        assert_eq!(matrix.transforms.len(), 5);
+    }
+}
@@ -16,16 +16,22 @@
 //! let matrix = client.generate_matrix(&csv_data[..10]).await?;
 //! ```
 
+pub mod failover;
+pub mod heuristic;
 pub mod prompt;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::env;
+use std::time::Duration;
 use thiserror::Error;
 
 use crate::transform::dsl::TransformationMatrix;
 
-pub use prompt::{system_prompt, user_prompt_with_all_data};
+pub use prompt::{column_stats_note, duplicate_columns_note, system_prompt, user_prompt_with_all_data};
+pub use crate::parser::sampling::{stratified_sample, varied_sample};
+pub use failover::{generate_with_failover, Engine, EngineReport};
+pub use heuristic::heuristic_matrix;
 
 /// AI-related errors
 #[derive(Error, Debug)]
@@ -44,6 +50,17 @@ pub enum AiError {
 
     #[error("Failed to parse matrix: {0}")]
     ParseError(String),
+
+    /// Server error (5xx), rate limit (429) or timeout: worth failing over
+    #[error("Provider unavailable: {0}")]
+    Unavailable(String),
+}
+
+impl AiError {
+    /// Whether the provider itself is failing (as opposed to a bad request or response)
+    pub fn is_transient(&self) -> bool {
+        matches!(self, AiError::Unavailable(_))
+    }
 }
 
 /// Anthropic API client
@@ -52,6 +69,8 @@ pub struct AiClient {
     api_key: String,
     model: String,
     max_tokens: u32,
+    /// File-specific remarks appended to the user prompt (e.g. renamed columns)
+    file_notes: Vec<String>,
 }
 
 /// Anthropic API response structure
@@ -93,13 +112,72 @@ const DEFAULT_MAX_RETRIES: u32 = 3;
 /// Delay between retries in milliseconds
 const RETRY_DELAY_MS: u64 = 1000;
 
+/// Timeout of one API request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Default maximum output tokens of a matrix generation
+const DEFAULT_MAX_TOKENS: u32 = 2048;
+
+/// API pricing in USD per million tokens (input, output)
+const INPUT_COST_PER_MTOK: f64 = 3.0;
+const OUTPUT_COST_PER_MTOK: f64 = 15.0;
+
+/// Average characters per token, for estimates made before calling the API
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Estimated size and cost of one matrix generation request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AiCostEstimate {
+    /// Characters of the system prompt and messages
+    pub prompt_chars: usize,
+    pub input_tokens: u32,
+    /// Output token budget (the actual answer is usually shorter)
+    pub max_output_tokens: u32,
+    /// Upper bound in USD for one attempt
+    pub cost_usd: f64,
+}
+
+impl AiCostEstimate {
+    fn for_messages(messages: &[Value]) -> Self {
+        let prompt_chars = prompt::system_prompt().chars().count()
+            + messages.iter().map(|m| m.to_string().chars().count()).sum::<usize>();
+        let input_tokens = prompt_chars.div_ceil(CHARS_PER_TOKEN) as u32;
+        Self {
+            prompt_chars,
+            input_tokens,
+            max_output_tokens: DEFAULT_MAX_TOKENS,
+            cost_usd: token_cost(input_tokens, DEFAULT_MAX_TOKENS),
+        }
+    }
+
+    /// Estimate for [`AiClient::generate_matrix_full`]
+    pub fn full(csv_preview: &[Value], all_records: &[Value], file_notes: &[String]) -> Result<Self, AiError> {
+        let messages = prompt::build_messages_with_all_data(csv_preview, all_records, &load_flat_schema()?);
+        Ok(Self::for_messages(&prompt::append_file_notes(messages, file_notes)))
+    }
+
+    /// Estimate for [`AiClient::generate_matrix_structure_only`]
+    pub fn structure_only(headers: &[String], all_records: &[Value], file_notes: &[String]) -> Result<Self, AiError> {
+        let messages = prompt::build_messages_structure_only(headers, all_records, &load_flat_schema()?);
+        Ok(Self::for_messages(&prompt::append_file_notes(messages, file_notes)))
+    }
+}
+
+/// Cost in USD of a request
+fn token_cost(input_tokens: u32, output_tokens: u32) -> f64 {
+    (input_tokens as f64 / 1_000_000.0) * INPUT_COST_PER_MTOK
+        + (output_tokens as f64 / 1_000_000.0) * OUTPUT_COST_PER_MTOK
+}
+
 impl AiClient {
     /// Create a new client with explicit API key
     pub fn new(api_key: String) -> Self {
         Self {
             api_key,
             model: "claude-sonnet-4-20250514".to_string(),
-            max_tokens: 2048,
+            max_tokens: DEFAULT_MAX_TOKENS,
+            file_notes: Vec::new(),
         }
     }
 
@@ -114,6 +192,24 @@ impl AiClient {
         Ok(Self::new(api_key))
     }
 
+    /// Secondary client for failover, when `AI_FALLBACK_MODEL` is set.
+    ///
+    /// Uses `AI_FALLBACK_API_KEY`, or `ANTHROPIC_API_KEY` when unset.
+    pub fn secondary_from_env() -> Option<Self> {
+        let _ = dotenvy::dotenv();
+
+        let model = env::var("AI_FALLBACK_MODEL").ok().filter(|m| !m.trim().is_empty())?;
+        let api_key = env::var("AI_FALLBACK_API_KEY")
+            .or_else(|_| env::var("ANTHROPIC_API_KEY"))
+            .ok()?;
+        Some(Self::new(api_key).with_model(model.trim()))
+    }
+
+    /// Model used by this client
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
     /// Set the model to use
     pub fn with_model(mut self, model: &str) -> Self {
         self.model = model.to_string();
@@ -126,6 +222,12 @@ impl AiClient {
         self
     }
 
+    /// Add remarks about the file to every prompt (e.g. renamed duplicate columns)
+    pub fn with_file_notes(mut self, notes: Vec<String>) -> Self {
+        self.file_notes = notes;
+        self
+    }
+
     /// Generate a transformation matrix from CSV data
     ///
     /// # Arguments
@@ -139,6 +241,18 @@ impl AiClient {
         self.generate_matrix_with_schema_full(csv_preview, all_records, &schema).await
     }
 
+    /// Generate a matrix in structure-only (privacy) mode
+    ///
+    /// Only column names, inferred types and value patterns are sent to the API,
+    /// never raw cell values. Mappings of codes (roles, languages) are guessed
+    /// from the standard lists, so expect lower matrix quality.
+    pub async fn generate_matrix_structure_only(&self, headers: &[String], all_records: &[Value]) -> Result<TransformationMatrix, AiError> {
+        let schema = load_flat_schema()?;
+        println!("   📡 Structure-only mode: {} columns, {} rows summarized (no values sent)", headers.len(), all_records.len());
+        let messages = prompt::build_messages_structure_only(headers, all_records, &schema);
+        self.generate_matrix_with_messages(&messages).await
+    }
+
     /// Generate matrix with custom schema (with retries)
     pub async fn generate_matrix_with_schema_full(
         &self,
@@ -146,10 +260,19 @@ impl AiClient {
         all_records: &[Value],
         schema: &Value,
     ) -> Result<TransformationMatrix, AiError> {
+        println!("   📡 Preview rows: {}, Total rows for unique values: {}", csv_preview.len(), all_records.len());
+        let messages = prompt::build_messages_with_all_data(csv_preview, all_records, schema);
+        self.generate_matrix_with_messages(&messages).await
+    }
+
+    /// Send prepared messages to the API (with retries)
+    async fn generate_matrix_with_messages(&self, messages: &[Value]) -> Result<TransformationMatrix, AiError> {
+        let messages = prompt::append_file_notes(messages.to_vec(), &self.file_notes);
+        let messages = messages.as_slice();
         let mut last_error = None;
         
         for attempt in 1..=DEFAULT_MAX_RETRIES {
-            match self.try_generate_matrix(csv_preview, all_records, schema).await {
+            match self.try_generate_matrix(messages).await {
                 Ok(matrix) => return Ok(matrix),
                 Err(e) => {
                     eprintln!("   ⚠️  Attempt {}/{} failed: {}", attempt, DEFAULT_MAX_RETRIES, e);
@@ -167,26 +290,22 @@ impl AiClient {
     }
 
     /// Single attempt to generate matrix
-    async fn try_generate_matrix(
-        &self,
-        csv_preview: &[Value],
-        all_records: &[Value],
-        schema: &Value,
-    ) -> Result<TransformationMatrix, AiError> {
-        let response = self.call_api(csv_preview, all_records, schema).await?;
+    async fn try_generate_matrix(&self, messages: &[Value]) -> Result<TransformationMatrix, AiError> {
+        let response = self.call_api(messages).await?;
         parse_matrix_from_response(&response)
     }
 
     /// Call Anthropic API
-    async fn call_api(&self, csv_preview: &[Value], all_records: &[Value], schema: &Value) -> Result<String, AiError> {
+    async fn call_api(&self, messages: &[Value]) -> Result<String, AiError> {
         println!("   📡 Calling Anthropic API...");
         println!("      Model: {}", self.model);
         println!("      Max tokens: {}", self.max_tokens);
-        println!("      Preview rows: {}, Total rows for unique values: {}", csv_preview.len(), all_records.len());
         
-        let client = reqwest::Client::new();
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| AiError::RequestFailed(e.to_string()))?;
 
-        let messages = prompt::build_messages_with_all_data(csv_preview, all_records, schema);
         let system = prompt::system_prompt();
 
         let request_body = serde_json::json!({
@@ -206,9 +325,14 @@ impl AiClient {
             .json(&request_body)
             .send()
             .await
-            .map_err(|e| AiError::RequestFailed(e.to_string()))?;
+            .map_err(|e| if e.is_timeout() || e.is_connect() {
+                AiError::Unavailable(e.to_string())
+            } else {
+                AiError::RequestFailed(e.to_string())
+            })?;
 
         let status = response.status();
+        let unavailable = status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
         println!("      Response status: {}", status);
         
         let body = response
@@ -218,12 +342,17 @@ impl AiClient {
 
         if !status.is_success() {
             // Try to parse error
-            if let Ok(error) = serde_json::from_str::<AnthropicError>(&body) {
-                println!("      ✗ API error: {}", error.error.message);
-                return Err(AiError::ApiError(error.error.message));
-            }
-            println!("      ✗ HTTP error: {}", status);
-            return Err(AiError::ApiError(format!("HTTP {}: {}", status, body)));
+            let message = match serde_json::from_str::<AnthropicError>(&body) {
+                Ok(error) => {
+                    println!("      ✗ API error: {}", error.error.message);
+                    error.error.message
+                }
+                Err(_) => {
+                    println!("      ✗ HTTP error: {}", status);
+                    format!("HTTP {}: {}", status, body)
+                }
+            };
+            return Err(if unavailable { AiError::Unavailable(message) } else { AiError::ApiError(message) });
         }
 
         let response: AnthropicResponse =
@@ -239,8 +368,8 @@ impl AiClient {
         
         // Log token usage for cost estimation
         if let Some(ref usage) = response.usage {
-            let input_cost = (usage.input_tokens as f64 / 1_000_000.0) * 3.0;
-            let output_cost = (usage.output_tokens as f64 / 1_000_000.0) * 15.0;
+            let input_cost = token_cost(usage.input_tokens, 0);
+            let output_cost = token_cost(0, usage.output_tokens);
             let total_cost = input_cost + output_cost;
             println!("      📊 Tokens: {} input + {} output = {} total", 
                 usage.input_tokens, usage.output_tokens, 
@@ -366,6 +495,19 @@ Done!"#;
         assert_eq!(json, response);
     }
 
+    #[test]
+    fn test_cost_estimate() {
+        let records: Vec<Value> = (0..20)
+            .map(|i| serde_json::json!({"Titre": format!("Song {}", i), "Role": "CA"}))
+            .collect();
+        let estimate = AiCostEstimate::full(&records[..5], &records, &[]).unwrap();
+        assert_eq!(estimate.input_tokens as usize, estimate.prompt_chars.div_ceil(CHARS_PER_TOKEN));
+        assert_eq!(estimate.cost_usd, token_cost(estimate.input_tokens, DEFAULT_MAX_TOKENS));
+
+        let noted = AiCostEstimate::full(&records[..5], &records, &["Renamed columns".to_string()]).unwrap();
+        assert!(noted.prompt_chars > estimate.prompt_chars);
+    }
+
     #[test]
     fn test_load_schema() {
         let schema = load_flat_schema().unwrap();
@@ -4,6 +4,8 @@
 
 use serde_json::Value;
 
+use crate::parser::{ColumnProfile, ParseReport};
+
 /// The transformation matrix JSON schema (embedded at compile time)
 const MATRIX_SCHEMA: &str = include_str!("../../schemas/transformation-matrix-schema.json");
 
@@ -32,19 +34,23 @@ You MUST return ONLY valid JSON matching this schema EXACTLY:
 ## MIDDS Field Requirements
 
 ### Required fields (must be mapped):
-- `iswc`: International Standard Musical Work Code. Format: T + 10 digits (e.g., "T1234567890")
-- `title`: Title of the work (string, max 256 chars)
+- `iswc`: International Standard Musical Work Code. Format: T + 10 digits (e.g., "T1234567890"). Use `fix_iswc` to reformat it and reject codes with a wrong check digit
+- `title`: Title of the work (string, max 256 chars). Keep its case as in the file; `title_case` is only added by users who want readable titles
 - `creatorIpi`: IPI code (integer, 9-11 digits)
 - `creatorRole`: Must be one of: "Author", "Composer", "Arranger", "Adapter", "Publisher"
 
 ### Optional fields:
-- `creationYear`: 4-digit year (integer)
-- `instrumental`: boolean
+- `creationYear`: 4-digit year (integer), then `clamp_year`. For full dates use `date_parse` with the format of the column (e.g. `{{"type": "date_parse", "format": "%d/%m/%Y", "output": "year"}}`), so day/month/year order is never guessed; use `extract_year` only for free text holding a year
+- `instrumental`: boolean. Without a dedicated column, derive it with `when` from a lyrics author column (e.g. `{{"constant": false, "operations": [{{"type": "when", "column": "Parolier", "empty": true, "then": true}}]}}`). `when` only sees the current row: with one row per creator (a role column, no lyrics author column), leave `instrumental` out
+- `titleLanguage`: language the title is written in, same values as `language`. Map it only from a column that tags the title (e.g. "Langue du titre", "Title Language", "Titelsprache"), never from the lyrics language column; use `map` to translate codes ("FR", "fr", "Français" → "French")
 - `language`: Must be one of: "English", "French", "Spanish", "German", "Italian", "Portuguese", "Russian", "Chinese", "Japanese", "Korean", "Arabic", "Hindi", "Dutch", "Swedish", "Norwegian", "Finnish", "Polish", "Turkish", "Hebrew", "Greek", "Latin", "Esperanto"
 - `bpm`: beats per minute (integer)
 - `key`: Musical key (e.g., "Am", "C", "Fs", "Bb", "Dm", etc.)
 - `workType`: Type of work - MUST be "Original" or null. Map any column containing work type info.
 - `creatorIsni`: 16-character ISNI code (format: 16 digits/X)
+- `creatorName`: creator name as written in the file (string)
+- `creatorShare`: creator ownership share in percent (number, 0-100), use `to_decimal`. Normalize other scales: fractions (0.5) with `multiply` by 100, per-mille (500) with `divide` by 10, then `round` with 2 decimals
+- `creatorPerformanceShare`, `creatorMechanicalShare`: performance (PR, exécution) and mechanical (MR, reproduction) shares in percent, use `to_decimal`. Map each distinct share column to its own field, never add them up into `creatorShare`
 - `opus`, `catalogNumber`, `numberOfVoices`: For classical works
 
 ## Work Type Mapping
@@ -66,7 +72,7 @@ Common role codes to map:
 
 ## Rules
 
-1. Use ONLY operations defined in the schema: trim, uppercase, lowercase, replace, pad_start, pad_end, extract_year, ensure_prefix, ensure_suffix, map, split, to_boolean, to_number, substring, alphanumeric, digits_only
+1. Use ONLY operations defined in the schema: trim, uppercase, lowercase, title_case, replace, pad_start, pad_end, extract_year, clamp_year, date_parse, ensure_prefix, ensure_suffix, map, split, to_boolean, to_number, to_decimal, multiply, divide, round, substring, alphanumeric, digits_only, fix_iswc, hash_id, when
 2. Do NOT invent new operations
 3. Use exact CSV column names from the preview (case-sensitive)
 4. Always use `trim` for text fields
@@ -85,7 +91,7 @@ Common role codes to map:
 /// Generate the user prompt with CSV data and schema
 /// 
 /// # Arguments
-/// * `csv_preview` - First N rows for the AI to see the structure
+/// * `csv_preview` - Sampled rows for the AI to see the structure (see [`super::sampling`])
 /// * `all_records` - All records (for extracting unique values)
 /// * `schema` - Target MIDDS schema
 pub fn user_prompt_with_all_data(csv_preview: &[Value], all_records: &[Value], schema: &Value) -> String {
@@ -172,6 +178,231 @@ fn extract_unique_values(rows: &[Value]) -> String {
     result
 }
 
+/// Maximum distinct value patterns shown per column in structure-only mode
+const MAX_PATTERNS_PER_COLUMN: usize = 5;
+
+/// Values longer than this are summarized by length only
+const MAX_PATTERN_SOURCE_LEN: usize = 40;
+
+/// Generate the user prompt in structure-only (privacy) mode.
+///
+/// No raw cell value is included: only column names, inferred types and
+/// regex-like value patterns (e.g. `[A-Z]-\d{3}\.\d{3}`).
+pub fn user_prompt_structure_only(headers: &[String], all_records: &[Value], schema: &Value) -> String {
+    let schema_json = serde_json::to_string_pretty(schema).unwrap_or_default();
+    let structure = describe_structure(headers, all_records);
+    let total_count = all_records.len();
+
+    format!(
+        r#"## CSV Structure ({total_count} rows, values redacted)
+
+The data owner does not allow sharing cell values. For each column you get its
+inferred type and the most frequent value patterns (`[A-Z]` uppercase letter,
+`[a-z]` lowercase letter, `\d` digit, `{{n}}` repetitions, other characters literal).
+
+{structure}
+
+## Target MIDDS Flat Schema
+
+```json
+{schema_json}
+```
+
+## Task
+
+Analyze the column names, types and patterns and generate a transformation matrix.
+You cannot see actual codes: for `map` operations use the standard codes listed in your instructions.
+
+Return ONLY the JSON object matching the transformation matrix schema. No explanations."#
+    )
+}
+
+/// Describe each column by inferred type and value patterns
+fn describe_structure(headers: &[String], rows: &[Value]) -> String {
+    use std::collections::HashMap;
+
+    let mut result = String::new();
+
+    for header in headers {
+        let values: Vec<&str> = rows
+            .iter()
+            .filter_map(|r| r.get(header))
+            .filter_map(|v| v.as_str())
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        let empty_count = rows.len() - values.len();
+
+        let mut patterns: HashMap<String, usize> = HashMap::new();
+        for v in &values {
+            *patterns.entry(value_pattern(v)).or_default() += 1;
+        }
+        let mut patterns: Vec<(String, usize)> = patterns.into_iter().collect();
+        patterns.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let shown: Vec<String> = patterns
+            .iter()
+            .take(MAX_PATTERNS_PER_COLUMN)
+            .map(|(p, n)| format!("`{}` ×{}", p, n))
+            .collect();
+        let more = if patterns.len() > MAX_PATTERNS_PER_COLUMN {
+            format!(", ... (+{} patterns)", patterns.len() - MAX_PATTERNS_PER_COLUMN)
+        } else {
+            String::new()
+        };
+
+        result.push_str(&format!(
+            "- **{}** ({}, {} empty): {}{}\n",
+            header,
+            infer_type(&values),
+            empty_count,
+            if shown.is_empty() { "-".to_string() } else { shown.join(", ") },
+            more
+        ));
+    }
+
+    result
+}
+
+/// Infer a coarse type from non-empty values
+fn infer_type(values: &[&str]) -> &'static str {
+    if values.is_empty() {
+        return "empty";
+    }
+    let all = |f: fn(&str) -> bool| values.iter().all(|v| f(v));
+
+    if all(|v| v.chars().all(|c| c.is_ascii_digit())) {
+        "integer"
+    } else if all(|v| v.replace(',', ".").parse::<f64>().is_ok()) {
+        "decimal"
+    } else if all(|v| {
+        let digits = v.chars().filter(|c| c.is_ascii_digit()).count();
+        digits >= 6 && v.chars().all(|c| c.is_ascii_digit() || "-/.: ".contains(c))
+    }) {
+        "date-like"
+    } else if all(|v| v.chars().count() <= 3) {
+        "short code"
+    } else {
+        "text"
+    }
+}
+
+/// Summarize a value as a regex-like pattern without revealing its content
+pub fn value_pattern(value: &str) -> String {
+    let len = value.chars().count();
+    if len > MAX_PATTERN_SOURCE_LEN {
+        return format!("text({} chars)", len);
+    }
+
+    let mut result = String::new();
+    let mut last: Option<String> = None;
+    let mut run = 0;
+
+    let flush = |result: &mut String, class: &Option<String>, run: usize| {
+        if let Some(c) = class {
+            result.push_str(c);
+            if run > 1 {
+                result.push_str(&format!("{{{}}}", run));
+            }
+        }
+    };
+
+    for ch in value.chars() {
+        let class = if ch.is_ascii_digit() {
+            "\\d".to_string()
+        } else if ch.is_uppercase() {
+            "[A-Z]".to_string()
+        } else if ch.is_alphabetic() {
+            "[a-z]".to_string()
+        } else if ch.is_whitespace() {
+            " ".to_string()
+        } else if ".+*?()[]{}|^$\\".contains(ch) {
+            format!("\\{}", ch)
+        } else {
+            ch.to_string()
+        };
+
+        if last.as_ref() == Some(&class) {
+            run += 1;
+        } else {
+            flush(&mut result, &last, run);
+            last = Some(class);
+            run = 1;
+        }
+    }
+    flush(&mut result, &last, run);
+
+    result
+}
+
+/// Build the structure-only prompt messages (privacy mode)
+pub fn build_messages_structure_only(headers: &[String], all_records: &[Value], schema: &Value) -> Vec<serde_json::Value> {
+    vec![serde_json::json!({
+        "role": "user",
+        "content": user_prompt_structure_only(headers, all_records, schema)
+    })]
+}
+
+/// Describe renamed duplicate headers so mappings can target each copy
+pub fn duplicate_columns_note(renamed: &[(String, String)]) -> Option<String> {
+    if renamed.is_empty() {
+        return None;
+    }
+    let list: Vec<String> = renamed
+        .iter()
+        .map(|(original, new)| format!("\"{}\" is another column named \"{}\"", new, original))
+        .collect();
+    Some(format!(
+        "The file has duplicate column names, later copies were renamed: {}. \
+They usually hold different data (e.g. composer IPI then author IPI): map each copy separately.",
+        list.join(", ")
+    ))
+}
+
+/// Point out empty and high-cardinality columns (no cell values, safe in structure-only mode)
+pub fn column_stats_note(report: &ParseReport) -> Option<String> {
+    let names = |columns: Vec<&ColumnProfile>| {
+        columns.iter().map(|c| format!("\"{}\"", c.name)).collect::<Vec<_>>().join(", ")
+    };
+    let empty: Vec<&ColumnProfile> = report.empty_columns().collect();
+    let distinct: Vec<&ColumnProfile> = report.high_cardinality_columns().collect();
+    let mut notes = Vec::new();
+    if !empty.is_empty() {
+        notes.push(format!("These columns are empty in every row, do not map them: {}.", names(empty)));
+    }
+    if !distinct.is_empty() {
+        notes.push(format!(
+            "These columns hold a different value on nearly every row (identifiers, titles or names), \
+never use them in `map` operations: {}.",
+            names(distinct)
+        ));
+    }
+    (!notes.is_empty()).then(|| notes.join(" "))
+}
+
+/// Append file-specific notes to the last user message
+pub fn append_file_notes(mut messages: Vec<Value>, notes: &[String]) -> Vec<Value> {
+    if notes.is_empty() {
+        return messages;
+    }
+    let section = format!(
+        "\n\n## Notes About This File\n\n{}",
+        notes.iter().map(|n| format!("- {}", n)).collect::<Vec<_>>().join("\n")
+    );
+    if let Some(content) = messages
+        .iter_mut()
+        .rev()
+        .find(|m| m["role"] == "user")
+        .and_then(|m| m.get_mut("content"))
+    {
+        if let Some(text) = content.as_str() {
+            *content = Value::String(format!("{}{}", text, section));
+        }
+    }
+    messages
+}
+
 /// Build the complete prompt for streaming (with all data for unique values)
 pub fn build_messages_with_all_data(csv_preview: &[Value], all_records: &[Value], schema: &Value) -> Vec<serde_json::Value> {
     vec![serde_json::json!({
@@ -205,6 +436,51 @@ mod tests {
         assert!(prompt.contains("TITRE"));
     }
 
+    #[test]
+    fn test_file_notes_appended() {
+        let note = duplicate_columns_note(&[("IPI".to_string(), "IPI_2".to_string())]).unwrap();
+        assert!(note.contains("\"IPI_2\" is another column named \"IPI\""));
+        assert!(duplicate_columns_note(&[]).is_none());
+
+        let messages = append_file_notes(vec![json!({"role": "user", "content": "Data"})], &[note]);
+        let content = messages[0]["content"].as_str().unwrap();
+        assert!(content.starts_with("Data"));
+        assert!(content.contains("## Notes About This File"));
+        assert!(content.contains("IPI_2"));
+
+        let rows: Vec<Value> = (0..10).map(|i| json!({"Titre": format!("Song {}", i), "Genre": "", "Role": "CA"})).collect();
+        let headers = ["Titre", "Genre", "Role"].map(String::from);
+        let note = column_stats_note(&ParseReport::new(&headers, &rows)).unwrap();
+        assert!(note.contains("empty in every row, do not map them: \"Genre\"."));
+        assert!(note.contains("`map` operations: \"Titre\"."));
+        assert!(column_stats_note(&ParseReport::new(&headers[2..], &rows)).is_none());
+    }
+
+    #[test]
+    fn test_value_pattern() {
+        assert_eq!(value_pattern("T-123.456.789-0"), "[A-Z]-\\d{3}\\.\\d{3}\\.\\d{3}-\\d");
+        assert_eq!(value_pattern("Jean Dupont"), "[A-Z][a-z]{3} [A-Z][a-z]{5}");
+        assert_eq!(value_pattern(&"x".repeat(60)), "text(60 chars)");
+    }
+
+    #[test]
+    fn test_structure_only_prompt_hides_values() {
+        let headers = vec!["ISWC".to_string(), "TITRE".to_string(), "IPI".to_string()];
+        let rows = vec![
+            json!({"ISWC": "T1234567890", "TITRE": "Secret Song", "IPI": "123456789"}),
+            json!({"ISWC": "T0987654321", "TITRE": "Other", "IPI": ""}),
+        ];
+        let schema = json!({"type": "object"});
+
+        let prompt = user_prompt_structure_only(&headers, &rows, &schema);
+        assert!(prompt.contains("TITRE"));
+        assert!(prompt.contains("[A-Z]\\d{10}"));
+        assert!(prompt.contains("**IPI** (integer, 1 empty)"));
+        assert!(!prompt.contains("T1234567890"));
+        assert!(!prompt.contains("Secret Song"));
+        assert!(!prompt.contains("123456789"));
+    }
+
     #[test]
     fn test_matrix_schema_is_valid_json() {
         let schema: Value = serde_json::from_str(MATRIX_SCHEMA).expect("Schema should be valid JSON");
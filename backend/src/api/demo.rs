@@ -0,0 +1,189 @@
+//! Public demo mode (`massload serve --demo`).
+//!
+//! Uploads are capped (file size, row count, uploads per minute and client), matrices
+//! come from the heuristic generator (no AI call, no cost), templates and
+//! catalog snapshots are never written, and every response is watermarked.
+
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::request::Parts;
+use once_cell::sync::{Lazy, OnceCell};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Response header set on every response of a demo instance
+pub const DEMO_HEADER: &str = "x-massload-demo";
+
+/// Notice added to upload responses of a demo instance
+pub const DEMO_WATERMARK: &str = "Massload public demo: matrix guessed from column names, nothing is saved";
+
+/// Limits of a demo instance
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DemoLimits {
+    pub max_file_bytes: usize,
+    pub max_rows: usize,
+    /// Uploads accepted per minute from each client
+    pub uploads_per_minute: u32,
+    /// Reverse proxies whose `X-Forwarded-For` header names the client
+    #[serde(skip)]
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl Default for DemoLimits {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: 1024 * 1024,
+            max_rows: 500,
+            uploads_per_minute: 10,
+            trusted_proxies: Vec::new(),
+        }
+    }
+}
+
+impl DemoLimits {
+    pub fn check_size(&self, bytes: usize) -> Result<(), String> {
+        if bytes > self.max_file_bytes {
+            return Err(format!("Demo mode: file too large ({} bytes, max {})", bytes, self.max_file_bytes));
+        }
+        Ok(())
+    }
+
+    pub fn check_rows(&self, rows: usize) -> Result<(), String> {
+        if rows > self.max_rows {
+            return Err(format!("Demo mode: too many rows ({}, max {})", rows, self.max_rows));
+        }
+        Ok(())
+    }
+}
+
+static DEMO: OnceCell<DemoLimits> = OnceCell::new();
+
+/// Turn demo mode on for this process (first call wins)
+pub fn enable_demo(limits: DemoLimits) {
+    let _ = DEMO.set(limits);
+}
+
+/// Demo limits, None when the server is not a demo instance
+pub fn demo_limits() -> Option<&'static DemoLimits> {
+    DEMO.get()
+}
+
+/// Fixed one-minute window counting uploads
+#[derive(Debug)]
+pub struct UploadWindow {
+    start: Instant,
+    count: u32,
+}
+
+impl UploadWindow {
+    pub fn new(now: Instant) -> Self {
+        Self { start: now, count: 0 }
+    }
+
+    /// The window ended (a new upload starts a new one)
+    pub fn expired(&self, now: Instant) -> bool {
+        now.duration_since(self.start) >= Duration::from_secs(60)
+    }
+
+    /// Count an upload at `now`, false when the window is already full
+    pub fn allow(&mut self, now: Instant, per_minute: u32) -> bool {
+        if self.expired(now) {
+            *self = Self::new(now);
+        }
+        if self.count >= per_minute {
+            return false;
+        }
+        self.count += 1;
+        true
+    }
+}
+
+/// Upload window of each client (`None`: address unknown)
+static UPLOADS: Lazy<Mutex<HashMap<Option<IpAddr>, UploadWindow>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Client of a request: the peer address, or behind a trusted proxy the last
+/// `X-Forwarded-For` address that is not itself a trusted proxy
+pub fn client_ip(peer: Option<IpAddr>, forwarded_for: Option<&str>, trusted_proxies: &[IpAddr]) -> Option<IpAddr> {
+    let peer = peer?;
+    if !trusted_proxies.contains(&peer) {
+        return Some(peer);
+    }
+    let forwarded = forwarded_for.into_iter()
+        .flat_map(|header| header.split(','))
+        .rev()
+        .filter_map(|address| address.trim().parse::<IpAddr>().ok())
+        .find(|ip| !trusted_proxies.contains(ip));
+    Some(forwarded.unwrap_or(peer))
+}
+
+/// Client address of a request, as counted by the demo rate limit
+#[derive(Debug, Clone, Copy)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientIp {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let peer = parts.extensions.get::<ConnectInfo<SocketAddr>>().map(|info| info.0.ip());
+        let forwarded_for = parts.headers.get("x-forwarded-for").and_then(|v| v.to_str().ok());
+        let trusted = demo_limits().map(|limits| limits.trusted_proxies.as_slice()).unwrap_or_default();
+        Ok(ClientIp(client_ip(peer, forwarded_for, trusted)))
+    }
+}
+
+/// Count an upload of `client` against the demo rate limit
+pub fn allow_upload(limits: &DemoLimits, client: Option<IpAddr>) -> bool {
+    let now = Instant::now();
+    let Ok(mut windows) = UPLOADS.lock() else {
+        return false;
+    };
+    windows.retain(|_, window| !window.expired(now));
+    windows.entry(client)
+        .or_insert_with(|| UploadWindow::new(now))
+        .allow(now, limits.uploads_per_minute)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demo_limits_and_rate_window() {
+        let limits = DemoLimits { max_file_bytes: 100, max_rows: 10, uploads_per_minute: 2, trusted_proxies: Vec::new() };
+        assert!(limits.check_size(100).is_ok());
+        assert!(limits.check_size(101).is_err());
+        assert!(limits.check_rows(11).unwrap_err().contains("max 10"));
+
+        let now = Instant::now();
+        let mut window = UploadWindow::new(now);
+        assert!(window.allow(now, 2));
+        assert!(window.allow(now + Duration::from_secs(1), 2));
+        assert!(!window.allow(now + Duration::from_secs(59), 2));
+        assert!(window.allow(now + Duration::from_secs(60), 2));
+
+        // Each client has its own window
+        let (a, b): (IpAddr, IpAddr) = ("203.0.113.1".parse().unwrap(), "203.0.113.2".parse().unwrap());
+        assert!(allow_upload(&limits, Some(a)));
+        assert!(allow_upload(&limits, Some(a)));
+        assert!(!allow_upload(&limits, Some(a)));
+        assert!(allow_upload(&limits, Some(b)));
+    }
+
+    #[test]
+    fn test_client_ip_behind_trusted_proxy() {
+        let proxy: IpAddr = "10.0.0.1".parse().unwrap();
+        let client: IpAddr = "203.0.113.7".parse().unwrap();
+        let spoofed = "198.51.100.1, 203.0.113.7";
+
+        // Direct clients cannot choose their address
+        assert_eq!(client_ip(Some(client), Some("198.51.100.1"), &[proxy]), Some(client));
+        // Behind the proxy: the address it appended, not the one sent by the client
+        assert_eq!(client_ip(Some(proxy), Some(spoofed), &[proxy]), Some(client));
+        assert_eq!(client_ip(Some(proxy), None, &[proxy]), Some(proxy));
+        assert_eq!(client_ip(None, Some(spoofed), &[proxy]), None);
+    }
+}
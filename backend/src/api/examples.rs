@@ -0,0 +1,49 @@
+//! Example CSV files bundled with the server.
+//!
+//! New users can try the whole flow (preflight, transformation, preview,
+//! signing) without exporting their own catalog first. The files are
+//! anonymized: fictional titles, names, ISWCs and IPI numbers.
+
+/// A bundled example file
+#[derive(Debug, Clone, Copy)]
+pub struct Example {
+    /// Name used in `/api/v1/examples/{name}`
+    pub name: &'static str,
+    /// File name offered to the browser
+    pub file_name: &'static str,
+    pub content: &'static str,
+}
+
+/// Examples served by `/api/v1/examples/{name}`
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "sacem",
+    file_name: "catalogue-sacem-exemple.csv",
+    content: include_str!("../../samples/catalogue-sacem.csv"),
+}];
+
+/// Example named `name`, if bundled
+pub fn example(name: &str) -> Option<&'static Example> {
+    EXAMPLES.iter().find(|e| e.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::heuristic::heuristic_matrix;
+    use crate::parser::parse_bytes_auto;
+    use crate::transform::pipeline::transform_with_matrix;
+    use crate::validation::ValidationContext;
+
+    /// Examples go through without AI (as on a demo instance), every row valid
+    #[test]
+    fn test_examples_transform_without_ai() {
+        assert!(example("unknown").is_none());
+        let validator = ValidationContext::new().unwrap();
+        for example in EXAMPLES {
+            let parsed = parse_bytes_auto(example.content.as_bytes(), None).unwrap();
+            let result = transform_with_matrix(&parsed.records, &heuristic_matrix(&parsed.headers), Some(&validator));
+            assert_eq!(result.valid_count, parsed.records.len(), "{}", example.name);
+            assert_eq!(result.skipped, 0, "{}", example.name);
+        }
+    }
+}
@@ -0,0 +1,403 @@
+//! In-memory store of recent transformation jobs.
+//!
+//! Keeps the flat records and skipped rows of recent uploads so follow-up
+//! requests (e.g. amending skipped rows) can refer to them by job id.
+//! Jobs live in memory only and are lost on restart.
+//!
+//! Uploads started with a job id are also registered while they run, so the
+//! download can stream their grouped works before they complete.
+//!
+//! Lifecycle events (see [`crate::transform::events`]) are kept for the
+//! same number of recent jobs, failed ones included.
+//!
+//! Jobs belong to the tenant that uploaded them (see [`super::tenant`]): a
+//! job id is unknown to every other tenant.
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use super::tenant::Tenant;
+use super::usage::ResourceUsage;
+use crate::models::CatalogPeriod;
+use crate::parser::ColumnProfile;
+use crate::sinks::Delivery;
+use crate::transform::dsl::SkippedRow;
+use crate::transform::{EventLog, LiveWorks};
+use crate::validation::validate_musical_work_flat;
+
+/// Maximum number of jobs kept in memory (oldest are evicted first)
+const MAX_JOBS: usize = 100;
+
+/// Flat fields holding integers in the MIDDS flat schema
+const INTEGER_FIELDS: &[&str] = &["creatorIpi", "creationYear", "bpm", "numberOfVoices"];
+
+/// Global job store
+pub static JOB_STORE: Lazy<JobStore> = Lazy::new(JobStore::new);
+
+/// Outcome of one upload, kept for follow-up requests
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub id: String,
+    /// Tenant that uploaded the file
+    pub tenant: Tenant,
+    pub created_at: DateTime<Utc>,
+    /// Flat records (one per creator)
+    pub flat: Vec<Value>,
+    /// Rows skipped because required fields were missing
+    pub skipped: Vec<SkippedRow>,
+    /// Number of source rows (after parsing)
+    pub source_rows: usize,
+    /// Statistics of each source column
+    pub columns: Vec<ColumnProfile>,
+    /// Delivery to each output sink chosen for the job
+    pub deliveries: Vec<Delivery>,
+    /// Memory, CPU time and temporary disk used by the job
+    pub usage: Option<ResourceUsage>,
+    /// SHA-256 of the uploaded file the job was built from
+    pub sha256: Option<String>,
+    /// Accounting period of the catalog (`period` upload field)
+    pub period: Option<CatalogPeriod>,
+}
+
+impl Job {
+    pub fn new(id: impl Into<String>, flat: Vec<Value>, skipped: Vec<SkippedRow>) -> Self {
+        Self {
+            id: id.into(),
+            tenant: Tenant::default(),
+            created_at: Utc::now(),
+            flat,
+            skipped,
+            source_rows: 0,
+            columns: Vec::new(),
+            deliveries: Vec::new(),
+            usage: None,
+            sha256: None,
+            period: None,
+        }
+    }
+
+    /// Scope the job to a tenant
+    pub fn with_tenant(mut self, tenant: Tenant) -> Self {
+        self.tenant = tenant;
+        self
+    }
+
+    /// Record the deliveries to the output sinks
+    pub fn with_deliveries(mut self, deliveries: Vec<Delivery>) -> Self {
+        self.deliveries = deliveries;
+        self
+    }
+
+    /// Record the checksum of the source file
+    pub fn with_sha256(mut self, sha256: Option<String>) -> Self {
+        self.sha256 = sha256;
+        self
+    }
+
+    /// Record the accounting period of the catalog
+    pub fn with_period(mut self, period: Option<CatalogPeriod>) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// Record the resources used by the job
+    pub fn with_usage(mut self, usage: ResourceUsage) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    /// Attach the source column statistics computed during parsing
+    pub fn with_columns(mut self, source_rows: usize, columns: Vec<ColumnProfile>) -> Self {
+        self.source_rows = source_rows;
+        self.columns = columns;
+        self
+    }
+}
+
+/// Values supplied by the user for a skipped row
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Amendment {
+    /// Row index, as reported in the skipped rows
+    pub row: usize,
+    /// Flat field name → value (e.g. `{"creatorIpi": "123456789"}`)
+    pub values: Map<String, Value>,
+}
+
+/// Result of applying amendments to a job
+#[derive(Debug, Default)]
+pub struct AmendOutcome {
+    /// Number of records added to the job
+    pub amended: usize,
+    /// Rows that still fail, with their errors
+    pub errors: Vec<(usize, Vec<String>)>,
+}
+
+/// Thread-safe, bounded job store
+pub struct JobStore {
+    jobs: Mutex<VecDeque<Job>>,
+    /// Jobs still running, with their works published so far
+    running: Mutex<HashMap<(Tenant, String), Arc<LiveWorks>>>,
+    /// Event logs of recent jobs, oldest first
+    events: Mutex<VecDeque<(Tenant, Arc<EventLog>)>>,
+}
+
+impl JobStore {
+    pub fn new() -> Self {
+        Self {
+            jobs: Mutex::new(VecDeque::new()),
+            running: Mutex::new(HashMap::new()),
+            events: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Keep the event log of a job (replacing an older log with the same id)
+    pub fn track_events(&self, tenant: &Tenant, log: Arc<EventLog>) {
+        let mut events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+        events.retain(|(t, l)| l.job_id() != log.job_id() || t != tenant);
+        if events.len() >= MAX_JOBS {
+            events.pop_front();
+        }
+        events.push_back((tenant.clone(), log));
+    }
+
+    /// Event log of a job of `tenant`
+    pub fn events(&self, tenant: &Tenant, id: &str) -> Option<Arc<EventLog>> {
+        let events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+        events.iter().find(|(t, l)| l.job_id() == id && t == tenant).map(|(_, l)| l.clone())
+    }
+
+    /// Register a running job; false if a job with this id already exists
+    pub fn start(&self, tenant: &Tenant, id: &str, live: Arc<LiveWorks>) -> bool {
+        let key = (tenant.clone(), id.to_string());
+        let mut running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        if running.contains_key(&key) || self.get(tenant, id).is_some() {
+            return false;
+        }
+        running.insert(key, live);
+        true
+    }
+
+    /// Works of a running job
+    pub fn running(&self, tenant: &Tenant, id: &str) -> Option<Arc<LiveWorks>> {
+        let key = (tenant.clone(), id.to_string());
+        self.running.lock().unwrap_or_else(|e| e.into_inner()).get(&key).cloned()
+    }
+
+    /// Unregister a running job (once stored with [`JobStore::insert`] or failed)
+    pub fn stop(&self, tenant: &Tenant, id: &str) {
+        let key = (tenant.clone(), id.to_string());
+        self.running.lock().unwrap_or_else(|e| e.into_inner()).remove(&key);
+    }
+
+    /// Store a job, evicting the oldest one when full
+    pub fn insert(&self, job: Job) {
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        jobs.retain(|j| j.id != job.id || j.tenant != job.tenant);
+        if jobs.len() >= MAX_JOBS {
+            jobs.pop_front();
+        }
+        jobs.push_back(job);
+    }
+
+    /// Get a copy of a job of `tenant`
+    pub fn get(&self, tenant: &Tenant, id: &str) -> Option<Job> {
+        let jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        jobs.iter().find(|j| j.id == id && &j.tenant == tenant).cloned()
+    }
+
+    /// Jobs of `tenant`, newest first, optionally only those of a period
+    /// (or, for a year such as `2025`, of any period of that year)
+    pub fn list(&self, tenant: &Tenant, period: Option<&str>) -> Vec<Job> {
+        let jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        jobs.iter()
+            .rev()
+            .filter(|j| &j.tenant == tenant)
+            .filter(|j| period.is_none_or(|filter| j.period.as_ref().is_some_and(|p| p.matches(filter))))
+            .cloned()
+            .collect()
+    }
+
+    /// Apply amendments to a stored job and return the updated job
+    pub fn amend(&self, tenant: &Tenant, id: &str, amendments: &[Amendment]) -> Option<(Job, AmendOutcome)> {
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        let job = jobs.iter_mut().find(|j| j.id == id && &j.tenant == tenant)?;
+        let outcome = amend_job(job, amendments);
+        Some((job.clone(), outcome))
+    }
+}
+
+impl Default for JobStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Complete skipped rows with user values and move the valid ones into `flat`.
+///
+/// Every skipped entry of the amended row is completed (a row can expand into
+/// several records). Entries that still fail validation stay skipped.
+pub fn amend_job(job: &mut Job, amendments: &[Amendment]) -> AmendOutcome {
+    let mut outcome = AmendOutcome::default();
+
+    for amendment in amendments {
+        let mut still_skipped = Vec::new();
+        let mut found = false;
+
+        for skip in job.skipped.drain(..) {
+            if skip.row != amendment.row {
+                still_skipped.push(skip);
+                continue;
+            }
+            found = true;
+
+            let mut record = skip.partial.clone();
+            for (field, raw) in &amendment.values {
+                if let Some(value) = coerce_flat_value(field, raw) {
+                    record.insert(field.clone(), value);
+                }
+            }
+
+            let record = Value::Object(record);
+            match validate_musical_work_flat(&record) {
+                Ok(()) => {
+                    job.flat.push(record);
+                    outcome.amended += 1;
+                }
+                Err(errs) => {
+                    outcome.errors.push((amendment.row, errs));
+                    still_skipped.push(skip);
+                }
+            }
+        }
+
+        job.skipped = still_skipped;
+        if !found {
+            outcome.errors.push((amendment.row, vec!["Row is not in the skipped rows".to_string()]));
+        }
+    }
+
+    outcome
+}
+
+/// Convert a user-typed value to the type expected by the flat schema.
+///
+/// Returns `None` for empty input so the field stays missing.
+pub fn coerce_flat_value(field: &str, raw: &Value) -> Option<Value> {
+    let text = match raw {
+        Value::String(s) => s.trim().to_string(),
+        Value::Null => return None,
+        other => return Some(other.clone()),
+    };
+    if text.is_empty() {
+        return None;
+    }
+
+    if INTEGER_FIELDS.contains(&field) {
+        let digits: String = text.chars().filter(|c| c.is_ascii_digit()).collect();
+        return digits.parse::<i64>().ok().map(Value::from).or(Some(Value::String(text)));
+    }
+    if field == "instrumental" {
+        let truthy = ["true", "1", "yes", "oui", "o", "y", "x"];
+        return Some(Value::Bool(truthy.contains(&text.to_lowercase().as_str())));
+    }
+
+    Some(Value::String(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn skipped_without_ipi(row: usize) -> SkippedRow {
+        let partial = json!({"iswc": "T1234567890", "title": "Song", "creatorRole": "Composer"});
+        SkippedRow {
+            row,
+            line: None,
+            reason: "Missing required fields".to_string(),
+            missing_fields: vec!["creatorIpi".to_string()],
+            partial: partial.as_object().unwrap().clone(),
+        }
+    }
+
+    #[test]
+    fn test_amend_moves_valid_rows() {
+        let mut job = Job::new("job-1", Vec::new(), vec![skipped_without_ipi(3), skipped_without_ipi(7)]);
+        let mut values = Map::new();
+        values.insert("creatorIpi".to_string(), json!("123 456 789"));
+
+        let outcome = amend_job(&mut job, &[Amendment { row: 3, values }]);
+
+        assert_eq!(outcome.amended, 1);
+        assert!(outcome.errors.is_empty());
+        assert_eq!(job.flat[0]["creatorIpi"], 123456789);
+        assert_eq!(job.skipped.len(), 1);
+        assert_eq!(job.skipped[0].row, 7);
+    }
+
+    #[test]
+    fn test_list_jobs_by_period() {
+        let store = JobStore::new();
+        let period = |label: &str| Some(CatalogPeriod::parse(label).unwrap());
+        store.insert(Job::new("q1", Vec::new(), Vec::new()).with_period(period("2025-Q1")));
+        store.insert(Job::new("q2", Vec::new(), Vec::new()).with_period(period("2025-Q2")));
+        store.insert(Job::new("none", Vec::new(), Vec::new()));
+        store.insert(Job::new("other", Vec::new(), Vec::new()).with_period(period("2025-Q1"))
+            .with_tenant(Tenant::named("acme").unwrap()));
+
+        let ids = |period: Option<&str>| -> Vec<String> {
+            store.list(&Tenant::default(), period).into_iter().map(|j| j.id).collect()
+        };
+        assert_eq!(ids(None), ["none", "q2", "q1"]);
+        assert_eq!(ids(Some("2025-q1")), ["q1"]);
+        assert_eq!(ids(Some("2025")), ["q2", "q1"]);
+    }
+
+    #[test]
+    fn test_amend_keeps_invalid_rows() {
+        let mut job = Job::new("job-2", Vec::new(), vec![skipped_without_ipi(1)]);
+
+        let outcome = amend_job(&mut job, &[Amendment { row: 1, values: Map::new() }]);
+
+        assert_eq!(outcome.amended, 0);
+        assert_eq!(outcome.errors.len(), 1);
+        assert_eq!(job.skipped.len(), 1);
+    }
+
+    #[test]
+    fn test_store_evicts_oldest() {
+        let store = JobStore::new();
+        for i in 0..=MAX_JOBS {
+            store.insert(Job::new(format!("job-{}", i), Vec::new(), Vec::new()));
+        }
+
+        assert!(store.get(&Tenant::default(), "job-0").is_none());
+        assert!(store.get(&Tenant::default(), &format!("job-{}", MAX_JOBS)).is_some());
+    }
+
+    #[test]
+    fn test_store_isolates_tenants() {
+        let store = JobStore::new();
+        let (sacem, gema) = (Tenant::named("sacem").unwrap(), Tenant::named("gema").unwrap());
+        store.insert(Job::new("job-1", Vec::new(), Vec::new()).with_tenant(sacem.clone()));
+
+        assert!(store.get(&sacem, "job-1").is_some());
+        assert!(store.get(&gema, "job-1").is_none());
+        assert!(store.get(&Tenant::default(), "job-1").is_none());
+        assert!(store.amend(&gema, "job-1", &[]).is_none());
+
+        // The same id can be used by another tenant without touching the first job
+        assert!(store.start(&gema, "job-1", Arc::new(LiveWorks::new())));
+        assert!(store.running(&sacem, "job-1").is_none());
+        assert!(!store.start(&sacem, "job-1", Arc::new(LiveWorks::new())));
+
+        store.track_events(&sacem, Arc::new(EventLog::new("job-1")));
+        assert!(store.events(&sacem, "job-1").is_some());
+        assert!(store.events(&gema, "job-1").is_none());
+    }
+}
@@ -2,10 +2,39 @@
 //!
 //! This module provides a broadcast channel for pipeline logs
 //! that can be streamed to frontend clients via SSE.
+//!
+//! A slow client lags behind the channel on busy runs. Its subscription then
+//! moves to a channel twice as large and reports the lost entries with a
+//! synthetic warning instead of dropping them silently.
+//!
+//! With `MASSLOAD_MASK_PII=1`, identifiers and creator names are masked in
+//! every entry before it is printed or sent (see [`super::masking`]).
+//!
+//! Entries logged inside [`with_log_tenant`] belong to that tenant, and a
+//! subscription opened with [`LogBroadcaster::subscribe_as`] only receives
+//! the entries of its tenant. The pipeline stage is kept per task there too,
+//! so concurrent jobs do not stamp each other's entries.
 
+use chrono::{SecondsFormat, Utc};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
 use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+
+use super::masking::{self, PiiMask};
+use super::tenant::Tenant;
+
+tokio::task_local! {
+    /// Tenant of the entries logged by the current task
+    static LOG_TENANT: Tenant;
+    /// Pipeline stage of the current task
+    static LOG_STAGE: Cell<Option<LogStage>>;
+}
 
 /// Log level for frontend display
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +44,26 @@ pub enum LogLevel {
     Success,
     Warning,
     Error,
+    /// Verbose per-row details (only emitted for verbose jobs)
+    Debug,
+}
+
+/// Pipeline stage a log entry belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStage {
+    /// Reading the file (encoding, delimiter, columns)
+    Parse,
+    /// Looking up and scoring cached templates
+    Template,
+    /// Generating a matrix with the AI
+    Ai,
+    /// Executing the matrix
+    Transform,
+    /// Validating flat and grouped records
+    Validate,
+    /// Grouping records by work
+    Group,
 }
 
 /// A single log entry
@@ -28,64 +77,258 @@ pub struct LogEntry {
     /// Optional indentation level (for nested logs)
     #[serde(default)]
     pub indent: u8,
+    /// Emission time (RFC3339, UTC)
+    #[serde(default)]
+    pub timestamp: String,
+    /// Pipeline stage (filled from the broadcaster's current stage when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stage: Option<LogStage>,
+    /// Broadcast order, to skip entries received twice after a channel switch
+    #[serde(skip)]
+    seq: u64,
+    /// Tenant whose job logged this entry (never sent)
+    #[serde(skip)]
+    tenant: Tenant,
 }
 
 impl LogEntry {
+    fn new(level: LogLevel, message: impl Into<String>) -> Self {
+        Self {
+            level,
+            message: message.into(),
+            indent: 0,
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            stage: None,
+            seq: 0,
+            tenant: Tenant::default(),
+        }
+    }
+
     pub fn info(message: impl Into<String>) -> Self {
-        Self { level: LogLevel::Info, message: message.into(), indent: 0 }
+        Self::new(LogLevel::Info, message)
     }
     
     pub fn success(message: impl Into<String>) -> Self {
-        Self { level: LogLevel::Success, message: message.into(), indent: 0 }
+        Self::new(LogLevel::Success, message)
     }
     
     pub fn warning(message: impl Into<String>) -> Self {
-        Self { level: LogLevel::Warning, message: message.into(), indent: 0 }
+        Self::new(LogLevel::Warning, message)
     }
     
     pub fn error(message: impl Into<String>) -> Self {
-        Self { level: LogLevel::Error, message: message.into(), indent: 0 }
+        Self::new(LogLevel::Error, message)
+    }
+
+    pub fn debug(message: impl Into<String>) -> Self {
+        Self::new(LogLevel::Debug, message)
     }
     
     pub fn with_indent(mut self, indent: u8) -> Self {
         self.indent = indent;
         self
     }
+
+    pub fn with_stage(mut self, stage: LogStage) -> Self {
+        self.stage = Some(stage);
+        self
+    }
 }
 
 /// Global log broadcaster
 pub static LOG_BROADCASTER: Lazy<LogBroadcaster> = Lazy::new(LogBroadcaster::new);
 
+/// Capacity of the first log channel
+const INITIAL_CAPACITY: usize = 256;
+
+/// Lagging subscribers are not moved to channels larger than this
+const MAX_CAPACITY: usize = 16_384;
+
 /// Broadcasts log entries to all connected SSE clients
 pub struct LogBroadcaster {
-    sender: broadcast::Sender<LogEntry>,
+    /// Channels by increasing capacity; every entry goes to each of them
+    channels: Mutex<Vec<(usize, broadcast::Sender<LogEntry>)>>,
+    /// Sequence number of the last entry sent
+    seq: AtomicU64,
+    /// Stage attached to entries logged without an explicit one, outside
+    /// [`with_log_tenant`] (which keeps a stage per task)
+    stage: Mutex<Option<LogStage>>,
+    /// Personal data masking (`MASSLOAD_MASK_PII`)
+    mask: Option<PiiMask>,
 }
 
 impl LogBroadcaster {
     pub fn new() -> Self {
-        let (sender, _) = broadcast::channel(100);
-        Self { sender }
+        Self::with_capacity(INITIAL_CAPACITY)
+    }
+
+    /// Broadcaster whose first channel holds `capacity` entries
+    pub fn with_capacity(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            channels: Mutex::new(vec![(capacity, sender)]),
+            seq: AtomicU64::new(0),
+            stage: Mutex::new(None),
+            mask: masking::is_enabled().then(PiiMask::default),
+        }
+    }
+
+    /// Mask identifiers and creator names in every entry, whatever `MASSLOAD_MASK_PII`
+    pub fn masking_pii(mut self) -> Self {
+        self.mask = Some(PiiMask::default());
+        self
+    }
+
+    /// Whether entries are masked
+    pub fn masks_pii(&self) -> bool {
+        self.mask.is_some()
+    }
+
+    /// Creator names to mask in the following entries (ignored without masking)
+    pub fn register_names<'a>(&self, names: impl IntoIterator<Item = &'a str>) {
+        if let Some(mask) = &self.mask {
+            mask.register_names(names);
+        }
+    }
+
+    fn channels(&self) -> std::sync::MutexGuard<'_, Vec<(usize, broadcast::Sender<LogEntry>)>> {
+        self.channels.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Set the stage attached to the following entries of the current task
+    pub fn set_stage(&self, stage: LogStage) {
+        if LOG_STAGE.try_with(|current| current.set(Some(stage))).is_err() {
+            *self.stage.lock().unwrap_or_else(|e| e.into_inner()) = Some(stage);
+        }
     }
     
     /// Send a log entry to all subscribers
-    pub fn log(&self, entry: LogEntry) {
+    pub fn log(&self, mut entry: LogEntry) {
+        if entry.stage.is_none() {
+            entry.stage = LOG_STAGE.try_with(Cell::get)
+                .unwrap_or_else(|_| *self.stage.lock().unwrap_or_else(|e| e.into_inner()));
+        }
+        if let Some(mask) = &self.mask {
+            entry.message = mask.apply(&entry.message);
+        }
+        if let Ok(tenant) = LOG_TENANT.try_with(Tenant::clone) {
+            entry.tenant = tenant;
+        }
+
         // Also print to stdout
         let prefix = match entry.level {
             LogLevel::Info => "   ",
             LogLevel::Success => "   ✓",
             LogLevel::Warning => "   ⚠️",
             LogLevel::Error => "   ❌",
+            LogLevel::Debug => "   🔍",
         };
         let indent = "   ".repeat(entry.indent as usize);
         println!("{}{} {}", indent, prefix, entry.message);
         
         // Broadcast to SSE clients (ignore if no receivers)
-        let _ = self.sender.send(entry);
+        let mut channels = self.channels();
+        entry.seq = self.seq.fetch_add(1, Ordering::Relaxed) + 1;
+        // Drop the smaller channels nobody listens to anymore (the largest is kept)
+        let largest = channels.len() - 1;
+        let mut index = 0;
+        channels.retain(|(_, sender)| {
+            index += 1;
+            index - 1 == largest || sender.receiver_count() > 0
+        });
+        for (_, sender) in channels.iter() {
+            let _ = sender.send(entry.clone());
+        }
     }
     
-    /// Get a receiver for SSE streaming
-    pub fn subscribe(&self) -> broadcast::Receiver<LogEntry> {
-        self.sender.subscribe()
+    /// Subscribe to the log stream (all tenants)
+    pub fn subscribe(&self) -> LogSubscription<'_> {
+        let channels = self.channels();
+        let (capacity, sender) = channels.last().expect("log broadcaster has a channel");
+        LogSubscription {
+            broadcaster: self,
+            rx: sender.subscribe(),
+            capacity: *capacity,
+            last_seq: 0,
+            pending: VecDeque::new(),
+            tenant: None,
+        }
+    }
+
+    /// Subscribe to the entries of one tenant (for SSE streaming)
+    pub fn subscribe_as(&self, tenant: Tenant) -> LogSubscription<'_> {
+        LogSubscription { tenant: Some(tenant), ..self.subscribe() }
+    }
+
+    /// Receiver on a channel larger than `capacity`, created when needed
+    fn subscribe_larger(&self, capacity: usize) -> (usize, broadcast::Receiver<LogEntry>) {
+        let mut channels = self.channels();
+        if let Some((size, sender)) = channels.iter().find(|(size, _)| *size > capacity) {
+            return (*size, sender.subscribe());
+        }
+        let size = (capacity * 2).min(MAX_CAPACITY);
+        if size <= capacity {
+            let (size, sender) = channels.last().expect("log broadcaster has a channel");
+            return (*size, sender.subscribe());
+        }
+        let (sender, rx) = broadcast::channel(size);
+        channels.push((size, sender));
+        (size, rx)
+    }
+}
+
+/// A log stream subscriber that survives lagging behind the channel
+pub struct LogSubscription<'a> {
+    broadcaster: &'a LogBroadcaster,
+    rx: broadcast::Receiver<LogEntry>,
+    capacity: usize,
+    /// Sequence number of the last entry returned
+    last_seq: u64,
+    /// Entries drained from the previous channel after a switch
+    pending: VecDeque<LogEntry>,
+    /// Only entries of this tenant are returned (`None`: all of them)
+    tenant: Option<Tenant>,
+}
+
+impl LogSubscription<'_> {
+    /// Next entry, or a warning counting the entries lost when this
+    /// subscriber lagged. `None` once the broadcaster is gone.
+    pub async fn recv(&mut self) -> Option<LogEntry> {
+        loop {
+            let entry = match self.pending.pop_front() {
+                Some(entry) => entry,
+                None => match self.rx.recv().await {
+                    Ok(entry) => entry,
+                    Err(RecvError::Lagged(skipped)) => {
+                        self.move_to_larger_channel();
+                        return Some(LogEntry::warning(format!(
+                            "{} log entries skipped (connection too slow)", skipped
+                        )));
+                    }
+                    Err(RecvError::Closed) => return None,
+                },
+            };
+            if entry.seq > self.last_seq {
+                self.last_seq = entry.seq;
+                if self.tenant.as_ref().is_none_or(|tenant| *tenant == entry.tenant) {
+                    return Some(entry);
+                }
+            }
+        }
+    }
+
+    /// Switch to a larger channel, keeping what the current one still holds
+    fn move_to_larger_channel(&mut self) {
+        let (capacity, rx) = self.broadcaster.subscribe_larger(self.capacity);
+        loop {
+            match self.rx.try_recv() {
+                Ok(entry) => self.pending.push_back(entry),
+                Err(TryRecvError::Lagged(_)) => continue,
+                Err(_) => break,
+            }
+        }
+        self.rx = rx;
+        self.capacity = capacity;
     }
 }
 
@@ -95,6 +338,16 @@ impl Default for LogBroadcaster {
     }
 }
 
+/// Run `future` with its log entries attributed to `tenant`, and its own stage
+pub async fn with_log_tenant<F: Future>(tenant: Tenant, future: F) -> F::Output {
+    LOG_TENANT.scope(tenant, LOG_STAGE.scope(Cell::new(None), future)).await
+}
+
+/// Set the pipeline stage of the following log entries
+pub fn set_log_stage(stage: LogStage) {
+    LOG_BROADCASTER.set_stage(stage);
+}
+
 /// Convenient logging functions
 pub fn log_info(msg: impl Into<String>) {
     LOG_BROADCASTER.log(LogEntry::info(msg));
@@ -112,6 +365,15 @@ pub fn log_error(msg: impl Into<String>) {
     LOG_BROADCASTER.log(LogEntry::error(msg));
 }
 
+pub fn log_debug(msg: impl Into<String>) {
+    LOG_BROADCASTER.log(LogEntry::debug(msg));
+}
+
+/// Creator names to mask in the logs when `MASSLOAD_MASK_PII` is set
+pub fn register_pii_names<'a>(names: impl IntoIterator<Item = &'a str>) {
+    LOG_BROADCASTER.register_names(names);
+}
+
 pub fn log_info_indent(msg: impl Into<String>, indent: u8) {
     LOG_BROADCASTER.log(LogEntry::info(msg).with_indent(indent));
 }
@@ -120,3 +382,123 @@ pub fn log_success_indent(msg: impl Into<String>, indent: u8) {
     LOG_BROADCASTER.log(LogEntry::success(msg).with_indent(indent));
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_carries_timestamp_and_stage() {
+        let broadcaster = LogBroadcaster::new();
+        let mut subscription = broadcaster.subscribe();
+        broadcaster.set_stage(LogStage::Template);
+        broadcaster.log(LogEntry::info("hello"));
+
+        let entry = futures::executor::block_on(subscription.recv()).unwrap();
+        assert_eq!(entry.stage, Some(LogStage::Template));
+        assert!(chrono::DateTime::parse_from_rfc3339(&entry.timestamp).is_ok());
+
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["stage"], "template");
+        assert_eq!(json["level"], "info");
+    }
+
+    #[test]
+    fn test_lagging_subscriber_gets_skip_notice_then_larger_channel() {
+        let broadcaster = LogBroadcaster::with_capacity(4);
+        let mut subscription = broadcaster.subscribe();
+        for i in 0..10 {
+            broadcaster.log(LogEntry::info(format!("entry {}", i)));
+        }
+
+        let notice = futures::executor::block_on(subscription.recv()).unwrap();
+        assert!(matches!(notice.level, LogLevel::Warning));
+        assert_eq!(notice.message, "6 log entries skipped (connection too slow)");
+        assert_eq!(subscription.capacity, 8);
+
+        // Later entries fit in the larger channel, none lost nor repeated
+        for i in 10..16 {
+            broadcaster.log(LogEntry::info(format!("entry {}", i)));
+        }
+        let messages: Vec<String> = (0..10)
+            .map(|_| futures::executor::block_on(subscription.recv()).unwrap().message)
+            .collect();
+        let expected: Vec<String> = (6..16).map(|i| format!("entry {}", i)).collect();
+        assert_eq!(messages, expected);
+    }
+
+    #[test]
+    fn test_masking_broadcaster() {
+        let broadcaster = LogBroadcaster::new().masking_pii();
+        let mut subscription = broadcaster.subscribe();
+        broadcaster.register_names(["Jean Dupont"]);
+        broadcaster.log(LogEntry::debug(r#"Record 2: invalid, "Jean Dupont" is not of type "null", IPI 00123456789"#));
+
+        let entry = futures::executor::block_on(subscription.recv()).unwrap();
+        assert_eq!(entry.message, r#"Record 2: invalid, "Jea…" is not of type "null", IPI •••789"#);
+    }
+
+    #[test]
+    fn test_tenant_subscription_only_gets_its_entries() {
+        let broadcaster = LogBroadcaster::new();
+        let (sacem, gema) = (Tenant::named("sacem").unwrap(), Tenant::named("gema").unwrap());
+        let mut subscriptions = [sacem.clone(), Tenant::default(), gema.clone()]
+            .map(|tenant| broadcaster.subscribe_as(tenant));
+
+        futures::executor::block_on(async {
+            with_log_tenant(sacem, async { broadcaster.log(LogEntry::info("sacem job")) }).await;
+            broadcaster.log(LogEntry::info("default job"));
+            with_log_tenant(gema, async { broadcaster.log(LogEntry::info("gema job")) }).await;
+
+            // Entries of other tenants are skipped, not delivered
+            let mut messages = Vec::new();
+            for subscription in subscriptions.iter_mut() {
+                messages.push(subscription.recv().await.unwrap().message);
+            }
+            assert_eq!(messages, ["sacem job", "default job", "gema job"]);
+        });
+    }
+
+    #[test]
+    fn test_concurrent_jobs_keep_their_own_stage() {
+        let broadcaster = LogBroadcaster::new();
+        let mut subscription = broadcaster.subscribe();
+        let (sacem, gema) = (Tenant::named("sacem").unwrap(), Tenant::named("gema").unwrap());
+        let job = |stage: LogStage, message: &'static str| {
+            let broadcaster = &broadcaster;
+            async move {
+                broadcaster.set_stage(stage);
+                // Let the other job set its stage in between
+                tokio::task::yield_now().await;
+                broadcaster.log(LogEntry::info(message));
+            }
+        };
+
+        futures::executor::block_on(async {
+            futures::join!(
+                with_log_tenant(sacem, job(LogStage::Parse, "sacem")),
+                with_log_tenant(gema, job(LogStage::Ai, "gema")),
+            );
+            let mut stages = Vec::new();
+            for _ in 0..2 {
+                let entry = subscription.recv().await.unwrap();
+                stages.push((entry.message, entry.stage));
+            }
+            stages.sort_by(|a, b| a.0.cmp(&b.0));
+            assert_eq!(stages, [("gema".to_string(), Some(LogStage::Ai)), ("sacem".to_string(), Some(LogStage::Parse))]);
+        });
+    }
+
+    #[test]
+    fn test_debug_level_serialization() {
+        let json = serde_json::to_value(LogEntry::debug("row 3")).unwrap();
+        assert_eq!(json["level"], "debug");
+    }
+
+    #[test]
+    fn test_entry_without_new_fields_deserializes() {
+        let entry: LogEntry = serde_json::from_str(r#"{"level":"success","message":"ok"}"#).unwrap();
+        assert!(entry.timestamp.is_empty());
+        assert!(entry.stage.is_none());
+    }
+}
@@ -0,0 +1,135 @@
+//! Masking of personal data in logs (`MASSLOAD_MASK_PII=1`).
+//!
+//! Some societies' data-handling policies forbid creator identifiers and
+//! names in logs. With the variable set, the log broadcaster masks them
+//! before printing and streaming each entry: identifiers (IPI, ISNI, any run
+//! of 9 digits or more) keep their last 3 digits, and quoted creator names
+//! registered by the pipeline keep their first 3 letters.
+
+use once_cell::sync::Lazy;
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+/// Environment variable enabling the masking (`1`, `true`)
+pub const MASK_PII_ENV: &str = "MASSLOAD_MASK_PII";
+
+/// Shortest digit run masked as an identifier (IPI name numbers have 9 to 11 digits)
+const MIN_IDENTIFIER_DIGITS: usize = 9;
+
+/// Names kept for masking; the set is emptied past this (older jobs are done)
+const MAX_NAMES: usize = 100_000;
+
+/// Read once: the policy can't change during a run
+static ENABLED: Lazy<bool> = Lazy::new(|| {
+    std::env::var(MASK_PII_ENV)
+        .map(|v| !matches!(v.trim(), "" | "0" | "false"))
+        .unwrap_or(false)
+});
+
+/// Whether `MASSLOAD_MASK_PII` is set
+pub fn is_enabled() -> bool {
+    *ENABLED
+}
+
+/// Masks identifiers and registered creator names in log messages
+#[derive(Debug, Default)]
+pub struct PiiMask {
+    names: Mutex<HashSet<String>>,
+}
+
+impl PiiMask {
+    /// Remember creator names to mask when they appear quoted in a message
+    pub fn register_names<'a>(&self, names: impl IntoIterator<Item = &'a str>) {
+        let mut known = self.names.lock().unwrap_or_else(|e| e.into_inner());
+        for name in names.into_iter().map(str::trim).filter(|n| !n.is_empty()) {
+            if known.len() >= MAX_NAMES {
+                known.clear();
+            }
+            known.insert(name.to_string());
+        }
+    }
+
+    /// The message with its identifiers and known names masked
+    pub fn apply(&self, message: &str) -> String {
+        let known = self.names.lock().unwrap_or_else(|e| e.into_inner());
+        mask_identifiers(&mask_quoted_names(message, &known))
+    }
+}
+
+/// `•••` followed by the last 3 characters
+pub fn mask_identifier(id: &str) -> String {
+    let chars: Vec<char> = id.chars().collect();
+    let last: String = chars[chars.len().saturating_sub(3)..].iter().collect();
+    format!("•••{}", last)
+}
+
+/// First 3 letters followed by `…`
+pub fn mask_name(name: &str) -> String {
+    format!("{}…", name.trim().chars().take(3).collect::<String>())
+}
+
+/// Mask every run of at least [`MIN_IDENTIFIER_DIGITS`] digits
+fn mask_identifiers(message: &str) -> String {
+    let mut masked = String::with_capacity(message.len());
+    let mut digits = String::new();
+    let flush = |masked: &mut String, digits: &mut String| {
+        if digits.len() >= MIN_IDENTIFIER_DIGITS {
+            masked.push_str(&mask_identifier(digits));
+        } else {
+            masked.push_str(digits);
+        }
+        digits.clear();
+    };
+    for c in message.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+        } else {
+            flush(&mut masked, &mut digits);
+            masked.push(c);
+        }
+    }
+    flush(&mut masked, &mut digits);
+    masked
+}
+
+/// Mask the double-quoted segments that are known names
+fn mask_quoted_names(message: &str, names: &HashSet<String>) -> String {
+    if names.is_empty() {
+        return message.to_string();
+    }
+    let mut masked = String::with_capacity(message.len());
+    let mut rest = message;
+    while let Some(open) = rest.find('"') {
+        let after = &rest[open + 1..];
+        let Some(close) = after.find('"') else { break };
+        let quoted = &after[..close];
+        masked.push_str(&rest[..=open]);
+        if names.contains(quoted.trim()) {
+            masked.push_str(&mask_name(quoted));
+        } else {
+            masked.push_str(quoted);
+        }
+        masked.push('"');
+        rest = &after[close + 1..];
+    }
+    masked.push_str(rest);
+    masked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_identifiers_and_names() {
+        let mask = PiiMask::default();
+        mask.register_names(["Dupont Jean ", "Martin"]);
+
+        assert_eq!(
+            mask.apply(r#"Record 4: invalid, "Dupont Jean" is not of type "integer", 00123456789 is not a valid IPI"#),
+            r#"Record 4: invalid, "Dup…" is not of type "integer", •••789 is not a valid IPI"#
+        );
+        // Short numbers, unknown quoted values and ISNI suffixes
+        assert_eq!(mask.apply(r#"12 rows, column "Nom", ISNI 000000012103268X"#), r#"12 rows, column "Nom", ISNI •••268X"#);
+    }
+}
@@ -5,6 +5,12 @@
 pub mod server;
 pub mod types;
 pub mod logs;
+pub mod jobs;
+pub mod demo;
+pub mod examples;
+pub mod masking;
+pub mod tenant;
+pub mod usage;
 
 pub use server::start_server;
 pub use types::*;
@@ -8,84 +8,301 @@
 //! | Method | Path              | Description                          |
 //! |--------|-------------------|--------------------------------------|
 //! | GET    | `/health`         | Health check                         |
-//! | POST   | `/api/upload`     | Upload CSV for transformation        |
-//! | GET    | `/api/logs`       | SSE stream for real-time logs        |
+//! | GET    | `/metrics`        | Job resource usage (Prometheus)      |
+//! | POST   | `/api/v1/preflight`  | Parse CSV only (columns, encoding)   |
+//! | POST   | `/api/v1/upload`     | Upload CSV for transformation        |
+//! | GET    | `/api/v1/jobs`       | Recent jobs (`?period=2025-Q1`)      |
+//! | POST   | `/api/v1/jobs/{id}/amend` | Complete skipped rows of a job  |
+//! | GET    | `/api/v1/jobs/{id}/download` | Grouped works as NDJSON stream |
+//! | GET    | `/api/v1/jobs/{id}/columns` | Source column statistics      |
+//! | GET    | `/api/v1/jobs/{id}/flat` | Flat records (JSON, or `?format=csv`) |
+//! | GET    | `/api/v1/jobs/{id}/events` | Lifecycle events of a job     |
+//! | GET    | `/api/v1/jobs/{id}/iswc-requests` | ISWC allocation requests (CSV) |
+//! | POST   | `/api/v1/validate`   | Validate MIDDS JSON built elsewhere  |
+//! | GET    | `/api/v1/templates`  | List stored templates (`?archived=true`: archived ones) |
+//! | POST   | `/api/v1/templates/evaluate` | Compare compatible templates  |
+//! | POST   | `/api/v1/templates/{id}/rename` | Rename a template          |
+//! | POST   | `/api/v1/templates/{id}/archive` | Archive a template (out of matching) |
+//! | POST   | `/api/v1/templates/{id}/restore` | Restore an archived template |
+//! | GET    | `/api/v1/examples/{name}` | Bundled example CSV file        |
+//! | GET    | `/api/v1/logs`       | SSE stream for real-time logs        |
+//!
+//! Every endpoint above also answers under `/api/...` without the version:
+//! those aliases are deprecated and their responses carry `Deprecation: true`
+//! and a `Link` to the versioned path. JSON bodies include `apiVersion`.
+//!
+//! Requests are scoped by the `X-Org-Id` header (or `X-Api-Key`, or
+//! `?orgId=`): jobs, logs, templates and catalog snapshots of one tenant are
+//! invisible to the others (see [`super::tenant`]).
+//!
+//! With `--demo` (see [`super::demo`]) uploads are capped, no AI provider is
+//! called, nothing is written to the template registry and every response
+//! carries the `x-massload-demo` header.
 
 use axum::{
-    extract::Multipart,
-    http::{header, Method, StatusCode},
-    response::{Json, Sse, sse::Event},
+    body::Body,
+    extract::{Multipart, Path, Query, Request},
+    http::{header, HeaderName, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Json, Response, Sse, sse::Event},
     routing::{get, post},
     Router,
 };
-use futures::stream::Stream;
+use futures::stream::{Stream, StreamExt};
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
 use serde_json::{json, Value};
-use std::{convert::Infallible, net::SocketAddr, time::Duration};
-use tokio_stream::StreamExt as _;
-use tokio_stream::wrappers::BroadcastStream;
+use std::{convert::Infallible, net::SocketAddr, sync::Arc, time::Duration};
+use tokio_stream::wrappers::ReceiverStream;
 use tower_http::cors::CorsLayer;
 
-use super::types::{error_response, UploadResponse};
-use super::logs::LOG_BROADCASTER;
-use crate::transform::pipeline::{transform_bytes, TransformOptions};
+use super::types::{
+    ensure_midds_format, ApiVersion, API_VERSION, error_response, musical_works_from_flat, AmendRequest, AmendResponse, ColumnsResponse, CsvMetadata, JobEventsResponse, JobSummary, RenameTemplateRequest, TemplateSummary, ValidateResponse, ValidationStats,
+    PreflightResponse, TemplateEvaluationResponse, UploadResponse, ValidationError,
+};
+use super::examples::{example, EXAMPLES};
+use super::demo::{allow_upload, demo_limits, ClientIp, enable_demo, DemoLimits, DEMO_HEADER, DEMO_WATERMARK};
+use super::jobs::{Job, JOB_STORE};
+use super::usage::{UsageMonitor, JOB_METRICS};
+use super::logs::{with_log_tenant, LOG_BROADCASTER};
+use super::tenant::{Tenant, API_KEY_HEADER, ORG_HEADER};
+use crate::parser::{check_delimiter, check_encoding, checksum_sha256, parse_bytes_with_options, parse_cwr, parse_json_with_encoding, parse_xlsx, unpack_within, CsvError, FixedWidthColumn, InputFormat, ParseOptions, ParseResult, RaggedRows, Unpacked, MAX_UNPACKED_BYTES, MAX_UPLOAD_INFLATION};
+use crate::cache::{catalog_key, CatalogSnapshot};
+use crate::deterministic;
+use crate::models::CatalogPeriod;
+use crate::notify::{post_webhook, WebhookConfig};
+use crate::sinks::{deliver_all, select_sinks, SinksConfig};
+use crate::transform::allocation::{allocation_csv, works_lacking_iswc};
+use crate::transform::diff::diff_works;
+use crate::transform::events::{EventKind, EventLog};
+use crate::transform::flat_csv;
+use crate::transform::streaming::{flat_to_grouped_streaming, LiveWorks, DEFAULT_RUN_SIZE};
+use crate::transform::pipeline::{evaluate_templates, transform_bytes, PipelineError, TransformOptions, DEFAULT_EVALUATION_ROWS};
+use crate::transform::role_inference::RoleInference;
+use crate::transform::roles::ChainNetwork;
+use crate::validation::{check_creation_years, ValidationContext, YearBounds};
+
+/// Response header flagging the unversioned `/api/...` aliases
+const DEPRECATION_HEADER: &str = "deprecation";
+
+/// Response header of job downloads: SHA-256 of the file the job was built from
+const SOURCE_SHA256_HEADER: &str = "x-massload-source-sha256";
+
+/// Estimated AI cost (USD) above which uploads need `confirm_ai`
+static AI_COST_THRESHOLD: OnceCell<f64> = OnceCell::new();
+
+/// Webhook receiving every job lifecycle event
+static EVENTS_WEBHOOK: OnceCell<WebhookConfig> = OnceCell::new();
+
+/// Output sinks that uploads can choose (`sink` form field)
+static SINKS: OnceCell<SinksConfig> = OnceCell::new();
+
+/// Role inference applied to uploads of catalogs without a role column
+static ROLE_INFERENCE: OnceCell<RoleInference> = OnceCell::new();
+
+/// Start the HTTP server (a public demo instance when `demo` is set).
+///
+/// With `ai_cost_threshold`, uploads whose AI request is estimated above it
+/// are answered `409` with the estimate until sent again with `confirm_ai`.
+/// With `events_webhook`, the lifecycle events of every job are POSTed to it.
+/// Uploads can have their grouped works delivered to any of `sinks`.
+/// `role_inference` gives roles to catalogs lacking a role column.
+pub async fn start_server(
+    port: u16,
+    demo: Option<DemoLimits>,
+    ai_cost_threshold: Option<f64>,
+    events_webhook: Option<WebhookConfig>,
+    sinks: SinksConfig,
+    role_inference: RoleInference,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(limits) = demo {
+        enable_demo(limits);
+    }
+    if let Some(threshold) = ai_cost_threshold {
+        let _ = AI_COST_THRESHOLD.set(threshold);
+    }
+    if let Some(webhook) = events_webhook {
+        let _ = EVENTS_WEBHOOK.set(webhook);
+    }
+    let _ = SINKS.set(sinks);
+    let _ = ROLE_INFERENCE.set(role_inference);
 
-/// Start the HTTP server
-pub async fn start_server(port: u16) -> Result<(), Box<dyn std::error::Error>> {
     // CORS permissif pour le développement
     let cors = CorsLayer::new()
         .allow_origin(tower_http::cors::Any)
         .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
-        .allow_headers([header::CONTENT_TYPE, header::ACCEPT])
-        .expose_headers([header::CONTENT_TYPE]);
+        .allow_headers([
+            header::CONTENT_TYPE,
+            header::ACCEPT,
+            HeaderName::from_static(ORG_HEADER),
+            HeaderName::from_static(API_KEY_HEADER),
+        ])
+        .expose_headers([
+            header::CONTENT_TYPE,
+            header::CONTENT_DISPOSITION,
+            header::LINK,
+            HeaderName::from_static(DEMO_HEADER),
+            HeaderName::from_static(DEPRECATION_HEADER),
+            HeaderName::from_static(SOURCE_SHA256_HEADER),
+        ]);
 
-    let app = Router::new()
-        .route("/", get(health))
-        .route("/health", get(health))
-        .route("/api/upload", post(upload_csv))
-        .route("/api/logs", get(sse_logs))
-        .layer(cors);
+    let app = router().layer(cors);
 
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
     println!("🚀 Massload server running on http://localhost:{}", port);
-    println!("   POST /api/upload - Upload CSV file");
-    println!("   GET  /api/logs   - SSE log stream");
+    println!("   POST /api/v1/preflight - Parse CSV only");
+    println!("   POST /api/v1/upload - Upload CSV file");
+    println!("   GET  /api/v1/jobs - Recent jobs (filter by period)");
+    println!("   POST /api/v1/jobs/{{id}}/amend - Complete skipped rows");
+    println!("   GET  /api/v1/jobs/{{id}}/download - Grouped works (NDJSON)");
+    println!("   GET  /api/v1/jobs/{{id}}/columns - Source column statistics");
+    println!("   GET  /api/v1/jobs/{{id}}/flat - Flat records (JSON or CSV)");
+    println!("   GET  /api/v1/jobs/{{id}}/events - Job lifecycle events");
+    println!("   GET  /api/v1/jobs/{{id}}/iswc-requests - ISWC allocation requests (CSV)");
+    println!("   POST /api/v1/validate - Validate external MIDDS JSON");
+    println!("   GET  /api/v1/templates - List stored templates");
+    println!("   POST /api/v1/templates/evaluate - Compare cached templates");
+    println!("   POST /api/v1/templates/{{id}}/rename - Rename a template");
+    println!("   POST /api/v1/templates/{{id}}/archive - Archive a template");
+    println!("   POST /api/v1/templates/{{id}}/restore - Restore an archived template");
+    println!("   GET  /api/v1/logs   - SSE log stream");
     println!("   GET  /health     - Health check");
+    println!("   GET  /metrics    - Job resource usage (Prometheus)");
+    println!("   (unversioned /api/... aliases still answer, with a Deprecation header)");
     println!();
     println!("📝 Blockchain submission via frontend SDK (@allfeat/client)");
+    if let Some(threshold) = AI_COST_THRESHOLD.get() {
+        println!("💰 AI requests estimated above ${:.4} need confirmation", threshold);
+    }
+    if let Some(webhook) = EVENTS_WEBHOOK.get() {
+        println!("📡 Job events pushed to {}", webhook.url);
+    }
+    if let Some(sinks) = SINKS.get().filter(|sinks| !sinks.is_empty()) {
+        println!("📤 Output sinks: {}", sinks.keys().cloned().collect::<Vec<_>>().join(", "));
+    }
+    if ROLE_INFERENCE.get().is_some_and(|inference| inference.enabled) {
+        println!("🧭 Roles inferred for catalogs without a role column");
+    }
+    if let Some(limits) = demo_limits() {
+        println!();
+        println!("🧪 DEMO MODE: no AI calls, nothing saved");
+        println!("   Max file size: {} bytes, max rows: {}, {} upload(s)/minute per client",
+            limits.max_file_bytes, limits.max_rows, limits.uploads_per_minute);
+    }
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
 
     Ok(())
 }
 
+/// Application routes: the API under `/api/v1`, and its deprecated
+/// unversioned aliases under `/api`
+fn router() -> Router {
+    Router::new()
+        .route("/", get(health))
+        .route("/health", get(health))
+        .route("/metrics", get(metrics))
+        .nest(&format!("/api/{}", API_VERSION), api_routes())
+        .nest("/api", api_routes().layer(axum::middleware::from_fn(deprecated_alias)))
+        .layer(axum::middleware::map_response(demo_header))
+}
+
+/// API endpoints, relative to the version prefix
+fn api_routes() -> Router {
+    Router::new()
+        .route("/preflight", post(preflight_csv))
+        .route("/upload", post(upload_csv))
+        .route("/jobs", get(list_jobs))
+        .route("/jobs/{id}/amend", post(amend_job))
+        .route("/jobs/{id}/download", get(download_job))
+        .route("/jobs/{id}/columns", get(job_columns))
+        .route("/jobs/{id}/flat", get(job_flat))
+        .route("/jobs/{id}/events", get(job_events))
+        .route("/jobs/{id}/iswc-requests", get(job_iswc_requests))
+        .route("/validate", post(validate_midds))
+        .route("/templates", get(list_templates))
+        .route("/templates/evaluate", post(evaluate_templates_csv))
+        .route("/templates/{id}/rename", post(rename_template))
+        .route("/templates/{id}/archive", post(archive_template))
+        .route("/templates/{id}/restore", post(restore_template))
+        .route("/examples/{name}", get(example_csv))
+        .route("/logs", get(sse_logs))
+}
+
+/// Flag responses of the unversioned aliases as deprecated, pointing to the
+/// versioned endpoint (`Link: </api/v1/...>; rel="successor-version"`)
+async fn deprecated_alias(request: Request, next: Next) -> Response {
+    // Nested routes see the path without the `/api` prefix
+    let successor = format!("/api/{}{}", API_VERSION, request.uri().path());
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert(HeaderName::from_static(DEPRECATION_HEADER), HeaderValue::from_static("true"));
+    if let Ok(link) = HeaderValue::from_str(&format!("<{}>; rel=\"successor-version\"", successor)) {
+        headers.insert(header::LINK, link);
+    }
+    response
+}
+
+/// Mark responses of a demo instance
+async fn demo_header(mut response: Response) -> Response {
+    if demo_limits().is_some() {
+        response.headers_mut().insert(HeaderName::from_static(DEMO_HEADER), HeaderValue::from_static("true"));
+    }
+    response
+}
+
+/// Reject files over the demo size limit
+fn check_demo_size(bytes: &[u8]) -> Result<(), (StatusCode, Json<Value>)> {
+    match demo_limits().map(|limits| limits.check_size(bytes.len())) {
+        Some(Err(e)) => Err((StatusCode::PAYLOAD_TOO_LARGE, Json(error_response(&e)))),
+        _ => Ok(()),
+    }
+}
+
 /// Health check endpoint
 async fn health() -> Json<Value> {
     Json(json!({
+        "apiVersion": API_VERSION,
         "status": "ok",
         "service": "massload",
         "version": env!("CARGO_PKG_VERSION"),
+        "demo": demo_limits(),
         "endpoints": {
-            "upload": "POST /api/upload",
-            "logs": "GET /api/logs (SSE)"
+            "preflight": "POST /api/v1/preflight",
+            "upload": "POST /api/v1/upload",
+            "jobs": "GET /api/v1/jobs?period=YYYY|YYYY-Qn",
+            "amend": "POST /api/v1/jobs/{id}/amend",
+            "download": "GET /api/v1/jobs/{id}/download",
+            "columns": "GET /api/v1/jobs/{id}/columns",
+            "flat": "GET /api/v1/jobs/{id}/flat?format=json|csv",
+            "events": "GET /api/v1/jobs/{id}/events",
+            "iswcRequests": "GET /api/v1/jobs/{id}/iswc-requests",
+            "validate": "POST /api/v1/validate",
+            "templates": "GET /api/v1/templates",
+            "evaluateTemplates": "POST /api/v1/templates/evaluate",
+            "renameTemplate": "POST /api/v1/templates/{id}/rename",
+            "archiveTemplate": "POST /api/v1/templates/{id}/archive",
+            "restoreTemplate": "POST /api/v1/templates/{id}/restore",
+            "logs": "GET /api/v1/logs (SSE)",
+            "metrics": "GET /metrics (Prometheus)"
         }
     }))
 }
 
+/// Prometheus metrics: job counts and resource usage since the server started
+async fn metrics() -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], JOB_METRICS.render())
+}
+
 /// SSE endpoint for real-time log streaming
-async fn sse_logs() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    let rx = LOG_BROADCASTER.subscribe();
-    
-    let stream = BroadcastStream::new(rx)
-        .filter_map(|result| {
-            match result {
-                Ok(entry) => {
-                    let json = serde_json::to_string(&entry).ok()?;
-                    Some(Ok(Event::default().data(json)))
-                }
-                Err(_) => None,
-            }
-        });
+async fn sse_logs(tenant: Tenant) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    // Lagging clients get a "N log entries skipped" warning, see `LogSubscription`
+    let stream = futures::stream::unfold(LOG_BROADCASTER.subscribe_as(tenant), |mut subscription| async move {
+        let entry = subscription.recv().await?;
+        let event = Event::default().json_data(&entry).unwrap_or_default();
+        Some((Ok(event), subscription))
+    });
     
     Sse::new(stream).keep_alive(
         axum::response::sse::KeepAlive::new()
@@ -94,27 +311,501 @@ async fn sse_logs() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
     )
 }
 
-/// Upload CSV endpoint
-async fn upload_csv(mut multipart: Multipart) -> Result<Json<UploadResponse>, (StatusCode, Json<Value>)> {
-    let mut file_data: Option<Vec<u8>> = None;
-    let mut file_name: Option<String> = None;
+/// Fields extracted from an upload multipart form
+#[derive(Default)]
+struct UploadForm {
+    file_data: Option<Vec<u8>>,
+    file_name: Option<String>,
+    /// Format implied by the file part's content type and name
+    input_format: InputFormat,
+    exclude_columns: Vec<String>,
+    structure_only: bool,
+    /// The user accepted the estimated AI cost (after a `409`)
+    confirm_ai: bool,
+    encoding: Option<String>,
+    /// Delimiter forced when detection picks the wrong one (`delimiter`)
+    delimiter: Option<char>,
+    /// Banner lines above the header (`skip_rows`)
+    skip_rows: Option<usize>,
+    /// 1-based line of the header (`header_row`, overrides `skip_rows`)
+    header_row: Option<usize>,
+    /// Header lines merged into composite column names (`header_rows`)
+    header_rows: Option<usize>,
+    /// Lines dropped at the end of the file, besides detected footers (`skip_footer_rows`)
+    skip_footer_rows: Option<usize>,
+    /// Rows with more or fewer cells than the header (`ragged_rows`: pad, warn or reject)
+    ragged_rows: RaggedRows,
+    /// Layout of a headerless fixed-width file (`fixed_width`: "name:start:len,...")
+    fixed_width: Option<Vec<FixedWidthColumn>>,
+    sample_rows: Option<usize>,
+    catalog: Option<String>,
+    /// Accounting period of the catalog (`period`, e.g. `2025-Q1`)
+    period: Option<CatalogPeriod>,
+    /// Output sinks receiving the grouped works (`sink`)
+    sinks: Vec<String>,
+}
+
+impl UploadForm {
+    /// Parser options from the form fields
+    fn parse_options(&self) -> ParseOptions {
+        let options = ParseOptions {
+            skip_first: self.skip_rows.unwrap_or(0),
+            header_rows: self.header_rows.unwrap_or(1),
+            skip_last: self.skip_footer_rows.unwrap_or(0),
+            ragged_rows: self.ragged_rows,
+            fixed_width: self.fixed_width.clone(),
+            encoding: self.encoding.clone(),
+            delimiter: self.delimiter,
+            ..ParseOptions::default()
+        };
+        match self.header_row {
+            Some(line) => options.with_header_row(line),
+            None => options,
+        }
+    }
+}
+
+/// Decompress an uploaded file off the async workers. The content may grow
+/// [`MAX_UPLOAD_INFLATION`] times the upload, and no larger than the demo size limit.
+async fn unpack_upload(bytes: Vec<u8>, file_name: Option<String>) -> Result<Unpacked, (StatusCode, Json<Value>)> {
+    let max_bytes = match demo_limits() {
+        Some(limits) => limits.max_file_bytes as u64,
+        None => (bytes.len() as u64).saturating_mul(MAX_UPLOAD_INFLATION).min(MAX_UNPACKED_BYTES),
+    };
+    tokio::task::spawn_blocking(move || unpack_within(bytes, file_name.as_deref(), max_bytes))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response(&format!("Decompression failed: {}", e)))))?
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(error_response(&e.message))))
+}
+
+/// Read the multipart form (file + optional option fields)
+async fn read_upload_form(mut multipart: Multipart) -> Result<UploadForm, (StatusCode, Json<Value>)> {
+    let mut form = UploadForm::default();
 
     while let Some(field) = multipart.next_field().await.map_err(|e| {
         (StatusCode::BAD_REQUEST, Json(error_response(&format!("Multipart error: {}", e))))
     })? {
         let name = field.name().unwrap_or("").to_string();
         
-        if name == "file" {
-            file_name = field.file_name().map(|s| s.to_string());
-            file_data = Some(field.bytes().await.map_err(|e| {
-                (StatusCode::BAD_REQUEST, Json(error_response(&format!("Read error: {}", e))))
-            })?.to_vec());
+        match name.as_str() {
+            "file" => {
+                let file_name = field.file_name().map(|s| s.to_string());
+                let content_type = field.content_type().map(|s| s.to_string());
+                let bytes = field.bytes().await.map_err(|e| {
+                    (StatusCode::BAD_REQUEST, Json(error_response(&format!("Read error: {}", e))))
+                })?.to_vec();
+                // The compressed file counts against the demo limit before being unpacked
+                check_demo_size(&bytes)?;
+                // Zipped or gzipped exports: the file inside names the template and hints the format
+                let unpacked = unpack_upload(bytes, file_name.clone()).await?;
+                let content_type = content_type.filter(|_| unpacked.compression.is_none());
+                form.input_format = InputFormat::from_hints(content_type.as_deref(), unpacked.file_name.as_deref());
+                form.file_name = unpacked.file_name;
+                form.file_data = Some(unpacked.bytes);
+            }
+            "exclude_columns" | "sink" => {
+                let text = field.text().await.map_err(|e| {
+                    (StatusCode::BAD_REQUEST, Json(error_response(&format!("Read error: {}", e))))
+                })?;
+                // Accept both repeated fields and a comma-separated list
+                let values = text.split(',')
+                    .map(|c| c.trim().to_string())
+                    .filter(|c| !c.is_empty());
+                match name.as_str() {
+                    "sink" => form.sinks.extend(values),
+                    _ => form.exclude_columns.extend(values),
+                }
+            }
+            "structure_only" => {
+                let text = field.text().await.map_err(|e| {
+                    (StatusCode::BAD_REQUEST, Json(error_response(&format!("Read error: {}", e))))
+                })?;
+                form.structure_only = matches!(text.trim(), "true" | "1" | "on");
+            }
+            "confirm_ai" => {
+                let text = field.text().await.map_err(|e| {
+                    (StatusCode::BAD_REQUEST, Json(error_response(&format!("Read error: {}", e))))
+                })?;
+                form.confirm_ai = matches!(text.trim(), "true" | "1" | "on");
+            }
+            "encoding" => {
+                let text = field.text().await.map_err(|e| {
+                    (StatusCode::BAD_REQUEST, Json(error_response(&format!("Read error: {}", e))))
+                })?;
+                if !text.trim().is_empty() {
+                    form.encoding = Some(check_encoding(&text).map_err(|e| {
+                        (StatusCode::BAD_REQUEST, Json(error_response(&e.message)))
+                    })?);
+                }
+            }
+            "delimiter" => {
+                let text = field.text().await.map_err(|e| {
+                    (StatusCode::BAD_REQUEST, Json(error_response(&format!("Read error: {}", e))))
+                })?;
+                // A lone tab is a delimiter, not blank space to trim
+                let value = if text.trim().is_empty() { text.as_str() } else { text.trim() };
+                if !value.is_empty() {
+                    form.delimiter = Some(check_delimiter(value).map_err(|e| {
+                        (StatusCode::BAD_REQUEST, Json(error_response(&e.message)))
+                    })?);
+                }
+            }
+            "skip_rows" | "header_row" | "header_rows" | "skip_footer_rows" => {
+                let text = field.text().await.map_err(|e| {
+                    (StatusCode::BAD_REQUEST, Json(error_response(&format!("Read error: {}", e))))
+                })?;
+                let value = text.trim().parse::<usize>().ok();
+                match name.as_str() {
+                    "skip_rows" => form.skip_rows = value,
+                    "header_row" => form.header_row = value.filter(|n| *n > 0),
+                    "skip_footer_rows" => form.skip_footer_rows = value,
+                    _ => form.header_rows = value.filter(|n| *n > 0),
+                }
+            }
+            "ragged_rows" => {
+                let text = field.text().await.map_err(|e| {
+                    (StatusCode::BAD_REQUEST, Json(error_response(&format!("Read error: {}", e))))
+                })?;
+                if !text.trim().is_empty() {
+                    form.ragged_rows = text.trim().parse().map_err(|e: String| {
+                        (StatusCode::BAD_REQUEST, Json(error_response(&e)))
+                    })?;
+                }
+            }
+            "fixed_width" => {
+                let text = field.text().await.map_err(|e| {
+                    (StatusCode::BAD_REQUEST, Json(error_response(&format!("Read error: {}", e))))
+                })?;
+                if !text.trim().is_empty() {
+                    form.fixed_width = Some(FixedWidthColumn::parse_layout(&text).map_err(|e| {
+                        (StatusCode::BAD_REQUEST, Json(error_response(&e)))
+                    })?);
+                }
+            }
+            "sample_rows" => {
+                let text = field.text().await.map_err(|e| {
+                    (StatusCode::BAD_REQUEST, Json(error_response(&format!("Read error: {}", e))))
+                })?;
+                form.sample_rows = text.trim().parse().ok().filter(|n| *n > 0);
+            }
+            "catalog" => {
+                let text = field.text().await.map_err(|e| {
+                    (StatusCode::BAD_REQUEST, Json(error_response(&format!("Read error: {}", e))))
+                })?;
+                form.catalog = Some(text.trim().to_string()).filter(|c| !c.is_empty());
+            }
+            "period" => {
+                let text = field.text().await.map_err(|e| {
+                    (StatusCode::BAD_REQUEST, Json(error_response(&format!("Read error: {}", e))))
+                })?;
+                if !text.trim().is_empty() {
+                    form.period = Some(CatalogPeriod::parse(&text).map_err(|e| {
+                        (StatusCode::BAD_REQUEST, Json(error_response(&e)))
+                    })?);
+                }
+            }
+            _ => {}
         }
     }
 
-    let bytes = file_data.ok_or_else(|| {
+    Ok(form)
+}
+
+/// Parse an uploaded CSV file, XLSX workbook, CWR file or JSON records (values as text, like CSV cells)
+fn parse_upload(bytes: &[u8], form: &UploadForm) -> Result<ParseResult, CsvError> {
+    let format = form.input_format.resolve(bytes);
+    if format == InputFormat::Json {
+        let mut parse_result = parse_json_with_encoding(bytes, form.encoding.as_deref())?;
+        parse_result.stringify_values();
+        return Ok(parse_result);
+    }
+    if format == InputFormat::Xlsx {
+        return parse_xlsx(bytes, &form.parse_options());
+    }
+    if format == InputFormat::Cwr {
+        return parse_cwr(bytes, form.encoding.as_deref());
+    }
+    parse_bytes_with_options(bytes, &form.parse_options())
+}
+
+/// Preflight endpoint: parse the file and return its columns.
+/// Nothing is sent to the AI and nothing is persisted.
+async fn preflight_csv(multipart: Multipart) -> Result<Json<PreflightResponse>, (StatusCode, Json<Value>)> {
+    let mut form = read_upload_form(multipart).await?;
+
+    let bytes = form.file_data.take().ok_or_else(|| {
+        (StatusCode::BAD_REQUEST, Json(error_response("No file provided")))
+    })?;
+    check_demo_size(&bytes)?;
+
+    let parse_result = parse_upload(&bytes, &form).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(error_response(&e.to_string())))
+    })?;
+
+    Ok(Json(PreflightResponse {
+        api_version: ApiVersion::default(),
+        file_name: form.file_name,
+        csv_info: CsvMetadata {
+            sha256: Some(checksum_sha256(&bytes)),
+            ..CsvMetadata::from(&parse_result)
+        },
+    }))
+}
+
+/// Template trial endpoint: run every compatible cached template on a sample
+/// of the file and return the comparison. No AI call, template stats untouched.
+async fn evaluate_templates_csv(tenant: Tenant, multipart: Multipart) -> Result<Json<TemplateEvaluationResponse>, (StatusCode, Json<Value>)> {
+    let mut form = read_upload_form(multipart).await?;
+
+    let bytes = form.file_data.take().ok_or_else(|| {
+        (StatusCode::BAD_REQUEST, Json(error_response("No file provided")))
+    })?;
+    check_demo_size(&bytes)?;
+
+    let mut parse_result = parse_upload(&bytes, &form).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(error_response(&e.to_string())))
+    })?;
+    parse_result.exclude_columns(&form.exclude_columns);
+
+    let sample_rows = form.sample_rows.unwrap_or(DEFAULT_EVALUATION_ROWS).min(parse_result.records.len());
+    let validator = ValidationContext::shared().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response(&e.to_string())))
+    })?;
+    let templates = evaluate_templates(&tenant.registry(), &parse_result, sample_rows, validator);
+
+    Ok(Json(TemplateEvaluationResponse {
+        api_version: ApiVersion::default(),
+        file_name: form.file_name,
+        csv_info: CsvMetadata {
+            sha256: Some(checksum_sha256(&bytes)),
+            ..CsvMetadata::from(&parse_result)
+        },
+        sampled_rows: sample_rows,
+        templates,
+    }))
+}
+
+/// Validation endpoint for MIDDS records produced by other tools: a JSON
+/// array of grouped works (with `creators`) or flat rows, in any mix. Every
+/// invalid record is reported, with the same statistics as an upload.
+async fn validate_midds(Json(body): Json<Value>) -> Result<Json<ValidateResponse>, (StatusCode, Json<Value>)> {
+    let Value::Array(records) = body else {
+        return Err((StatusCode::BAD_REQUEST, Json(error_response("Expected a JSON array of MIDDS records"))));
+    };
+    let validator = ValidationContext::shared().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response(&e.to_string())))
+    })?;
+
+    let mut grouped = 0;
+    let mut errors = Vec::new();
+    for (i, record) in records.iter().enumerate() {
+        let result = if record.get("creators").is_some() {
+            grouped += 1;
+            validator.validate_grouped(record)
+        } else {
+            validator.validate_flat(record)
+        };
+        if let Err(errs) = result {
+            errors.push(ValidationError { record_index: i, line: None, errors: errs });
+        }
+    }
+
+    Ok(Json(ValidateResponse {
+        api_version: ApiVersion::default(),
+        grouped,
+        validation: ValidationStats {
+            valid: records.len() - errors.len(),
+            invalid: errors.len(),
+            errors,
+            warnings: check_creation_years(&records, &YearBounds::default()),
+        },
+    }))
+}
+
+/// Template list endpoint, most recently created first
+async fn list_templates(tenant: Tenant, Query(query): Query<TemplateListQuery>) -> Json<Vec<TemplateSummary>> {
+    let registry = tenant.registry();
+    let stored = if query.archived { registry.list_archived() } else { registry.list() };
+    let mut templates: Vec<TemplateSummary> = stored.into_iter()
+        .filter(|t| query.period.as_deref().is_none_or(|period| t.used_for_period(period)))
+        .map(TemplateSummary::from)
+        .collect();
+    templates.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Json(templates)
+}
+
+/// Template rename endpoint (the ID is kept)
+async fn rename_template(
+    tenant: Tenant,
+    Path(id): Path<String>,
+    Json(request): Json<RenameTemplateRequest>,
+) -> Result<Json<TemplateSummary>, (StatusCode, Json<Value>)> {
+    if demo_limits().is_some() {
+        return Err((StatusCode::FORBIDDEN, Json(error_response("Templates are read-only in demo mode"))));
+    }
+    let mut registry = tenant.registry();
+    if registry.get(&id).is_none() {
+        return Err((StatusCode::NOT_FOUND, Json(error_response(&format!("Unknown template: {}", id)))));
+    }
+    let stored = registry.rename(&id, &request.name).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(error_response(&e)))
+    })?;
+    println!("🏷️  Template {} renamed to \"{}\"", id, stored.name);
+    Ok(Json(TemplateSummary::from(stored)))
+}
+
+/// Template archive endpoint: the template is kept (audit records and jobs
+/// may refer to it) but no longer matched
+async fn archive_template(tenant: Tenant, Path(id): Path<String>) -> Result<Json<TemplateSummary>, (StatusCode, Json<Value>)> {
+    set_template_archived(tenant, &id, true)
+}
+
+/// Template restore endpoint: an archived template is matched again
+async fn restore_template(tenant: Tenant, Path(id): Path<String>) -> Result<Json<TemplateSummary>, (StatusCode, Json<Value>)> {
+    set_template_archived(tenant, &id, false)
+}
+
+fn set_template_archived(tenant: Tenant, id: &str, archived: bool) -> Result<Json<TemplateSummary>, (StatusCode, Json<Value>)> {
+    if demo_limits().is_some() {
+        return Err((StatusCode::FORBIDDEN, Json(error_response("Templates are read-only in demo mode"))));
+    }
+    let mut registry = tenant.registry();
+    if registry.get(id).is_none() {
+        return Err((StatusCode::NOT_FOUND, Json(error_response(&format!("Unknown template: {}", id)))));
+    }
+    let stored = match archived {
+        true => registry.archive(id),
+        false => registry.restore(id),
+    }
+    .map_err(|e| (StatusCode::CONFLICT, Json(error_response(&e))))?;
+    println!("🗄️  Template {} {}", id, if archived { "archived" } else { "restored" });
+    Ok(Json(TemplateSummary::from(stored)))
+}
+
+/// Name of a template generated for an uploaded file: the file name without extension
+fn template_stem(file_name: &str) -> String {
+    std::path::Path::new(file_name)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(file_name)
+        .to_string()
+}
+
+/// Query options of the upload endpoint
+#[derive(Debug, Default, Deserialize)]
+struct UploadQuery {
+    /// Emit per-row debug logs (`?verbose=true`)
+    #[serde(default)]
+    verbose: bool,
+    /// Job id chosen by the client (`?jobId=...`), so the grouped works can be
+    /// streamed with `GET /api/jobs/{id}/download?stream=true` while the job runs
+    #[serde(default, rename = "jobId")]
+    job_id: Option<String>,
+    /// Network whose accepted creator roles are checked (`?network=mainnet`, default devnet)
+    #[serde(default)]
+    network: ChainNetwork,
+}
+
+/// Template list query
+#[derive(Debug, Default, Deserialize)]
+struct TemplateListQuery {
+    /// List archived templates instead of active ones (`?archived=true`)
+    #[serde(default)]
+    archived: bool,
+    /// Only templates used for this period, or a period of this year (`?period=2025-Q1`)
+    #[serde(default)]
+    period: Option<String>,
+}
+
+/// Job list query
+#[derive(Debug, Default, Deserialize)]
+struct JobListQuery {
+    /// Only jobs of this period, or of a period of this year (`?period=2025-Q1`)
+    #[serde(default)]
+    period: Option<String>,
+}
+
+/// Download query
+#[derive(Debug, Deserialize)]
+struct DownloadQuery {
+    /// Follow a running job: works are sent as soon as they are grouped (`?stream=true`)
+    #[serde(default)]
+    stream: bool,
+}
+
+/// Flat records query
+#[derive(Debug, Default, Deserialize)]
+struct FlatQuery {
+    /// `json` (default) or `csv`
+    #[serde(default)]
+    format: Option<String>,
+}
+
+/// Client-chosen job ids: 1 to 64 letters, digits, `-` or `_`
+fn is_valid_job_id(id: &str) -> bool {
+    (1..=64).contains(&id.len()) && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Upload CSV endpoint
+async fn upload_csv(
+    tenant: Tenant,
+    ClientIp(client): ClientIp,
+    Query(query): Query<UploadQuery>,
+    multipart: Multipart,
+) -> Result<Json<UploadResponse>, (StatusCode, Json<Value>)> {
+    let demo = demo_limits();
+    if demo.is_some_and(|limits| !allow_upload(limits, client)) {
+        return Err((StatusCode::TOO_MANY_REQUESTS, Json(error_response("Demo mode: too many uploads, retry in a minute"))));
+    }
+
+    let mut form = read_upload_form(multipart).await?;
+
+    let bytes = form.file_data.take().ok_or_else(|| {
         (StatusCode::BAD_REQUEST, Json(error_response("No file provided")))
     })?;
+    if let Some(limits) = demo {
+        check_demo_size(&bytes)?;
+        let rows = parse_upload(&bytes, &form).map(|p| p.records.len()).unwrap_or(0);
+        limits.check_rows(rows).map_err(|e| (StatusCode::PAYLOAD_TOO_LARGE, Json(error_response(&e))))?;
+    }
+    let file_name = form.file_name;
+    let sinks = match demo {
+        Some(_) if !form.sinks.is_empty() => {
+            return Err((StatusCode::FORBIDDEN, Json(error_response("Output sinks are disabled in demo mode"))));
+        }
+        _ => select_sinks(SINKS.get().unwrap_or(&SinksConfig::new()), &form.sinks)
+            .map_err(|e| (StatusCode::BAD_REQUEST, Json(error_response(&e))))?,
+    };
+
+    // Sort runs of the job land in its own directory, measured with the memory and CPU time.
+    // Job ids are chosen by clients and only unique per tenant: the directory gets a random name.
+    // Created before the job is started so this failure never leaves it running.
+    let temp_dir = tempfile::Builder::new().prefix("massload-job-").tempdir().map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response(&format!("Cannot create job directory: {}", e))))
+    })?;
+
+    let live = match query.job_id {
+        Some(ref id) if !is_valid_job_id(id) => {
+            return Err((StatusCode::BAD_REQUEST, Json(error_response(&format!("Invalid job id: {}", id)))));
+        }
+        Some(ref id) => {
+            let live = Arc::new(LiveWorks::new());
+            if !JOB_STORE.start(&tenant, id, live.clone()) {
+                return Err((StatusCode::CONFLICT, Json(error_response(&format!("Job already exists: {}", id)))));
+            }
+            Some(live)
+        }
+        None => None,
+    };
+    let job_id = query.job_id.clone().unwrap_or_else(|| deterministic::new_uuid().to_string());
+    let events = new_event_log(&job_id);
+    JOB_STORE.track_events(&tenant, events.clone());
+    events.emit(EventKind::JobCreated, json!({
+        "fileName": file_name,
+        "bytes": bytes.len(),
+        "network": query.network,
+        "period": form.period,
+    }));
 
     println!("\n{}", "=".repeat(70));
     println!("📄 NEW UPLOAD: {} ({} bytes)", 
@@ -123,11 +814,66 @@ async fn upload_csv(mut multipart: Multipart) -> Result<Json<UploadResponse>, (S
     );
     println!("{}\n", "=".repeat(70));
 
-    let options = TransformOptions::default();
+    let monitor = UsageMonitor::start(Some(temp_dir.path().to_path_buf()));
+
+    let options = TransformOptions {
+        exclude_columns: form.exclude_columns,
+        structure_only: form.structure_only,
+        encoding: form.encoding,
+        delimiter: form.delimiter,
+        skip_first: form.skip_rows,
+        header_row: form.header_row,
+        header_rows: form.header_rows,
+        skip_last: form.skip_footer_rows,
+        ragged_rows: form.ragged_rows,
+        fixed_width: form.fixed_width,
+        period: form.period.clone(),
+        input_format: form.input_format,
+        template_name: file_name.as_deref().map(template_stem),
+        verbose: query.verbose,
+        // Demo: no AI cost, no registry reads or writes
+        no_ai: demo.is_some(),
+        no_cache: demo.is_some(),
+        no_save: demo.is_some(),
+        ai_cost_limit: AI_COST_THRESHOLD.get().copied().filter(|_| !form.confirm_ai),
+        registry_dir: tenant.registry_dir(),
+        network: query.network,
+        role_inference: ROLE_INFERENCE.get().cloned().unwrap_or_default(),
+        temp_dir: Some(temp_dir.path().to_string_lossy().to_string()),
+        live: live.clone(),
+        events: Some(events.clone()),
+        ..TransformOptions::default()
+    };
     
-    let result = transform_bytes(&bytes, options).await.map_err(|e| {
-        eprintln!("❌ Transform error: {}", e);
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response(&e.to_string())))
+    let result = with_log_tenant(tenant.clone(), transform_bytes(&bytes, options)).await;
+    let usage = monitor.finish();
+    drop(temp_dir);
+    JOB_METRICS.record(&usage, result.is_err());
+    println!("📈 Job resources: {} ms elapsed, CPU {}, peak memory {}, peak temp files {} bytes",
+        usage.wall_ms,
+        usage.cpu_ms.map(|ms| format!("{} ms", ms)).unwrap_or_else(|| "n/a".to_string()),
+        usage.peak_memory_bytes.map(|b| format!("{} MB", b / (1024 * 1024))).unwrap_or_else(|| "n/a".to_string()),
+        usage.peak_temp_bytes);
+    if let (Some(live), Some(id)) = (&live, &query.job_id) {
+        live.finish(result.as_ref().err().map(ToString::to_string));
+        if result.is_err() {
+            JOB_STORE.stop(&tenant, id);
+        }
+    }
+    if let Err(ref e) = result {
+        events.emit(EventKind::Failed, json!({ "error": e.to_string(), "usage": usage }));
+    }
+    let result = result.map_err(|e| match e {
+        PipelineError::AiCostNotConfirmed(ref estimate) => {
+            println!("💰 Upload stopped: AI request estimated at ${:.4}, waiting for confirmation", estimate.cost_usd);
+            let mut body = error_response(&e.to_string());
+            body["aiEstimate"] = json!(estimate);
+            (StatusCode::CONFLICT, Json(body))
+        }
+        e => {
+            eprintln!("❌ Transform error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response(&e.to_string())))
+        }
     })?;
 
     println!("\n{}", "=".repeat(70));
@@ -142,7 +888,388 @@ async fn upload_csv(mut multipart: Multipart) -> Result<Json<UploadResponse>, (S
     }
     println!("{}\n", "=".repeat(70));
 
-    let response = UploadResponse::from(result);
+    // Keep the job so skipped rows can be amended later
+    let (flat, skipped) = (result.flat.clone(), result.skipped.clone());
+    let (source_rows, columns) = (result.csv_info.row_count, result.csv_info.report.columns.clone());
+    let missing_iswc = works_lacking_iswc(&flat, &skipped).len();
+    let mut response = UploadResponse::from(result);
+    response.metadata.missing_iswc = missing_iswc;
+    response.job_id = job_id;
+
+    // Output sinks, once validated
+    if !sinks.is_empty() {
+        let valid = response.metadata.validation.valid;
+        response.metadata.deliveries = deliver_all(&sinks, &response.job_id, &response.musical_works, valid).await;
+        for delivery in &response.metadata.deliveries {
+            match delivery.error {
+                None => println!("📤 Delivered to {} ({})", delivery.sink, delivery.target),
+                Some(ref e) => eprintln!("⚠️  Delivery to {} {:?}: {}", delivery.sink, delivery.status, e),
+            }
+        }
+    }
+    JOB_STORE.insert(Job::new(response.job_id.clone(), flat, skipped)
+        .with_tenant(tenant.clone())
+        .with_columns(source_rows, columns)
+        .with_sha256(response.metadata.csv_info.sha256.clone())
+        .with_period(response.metadata.period.clone())
+        .with_deliveries(response.metadata.deliveries.clone())
+        .with_usage(usage.clone()));
+    if let Some(ref id) = query.job_id {
+        JOB_STORE.stop(&tenant, id);
+    }
+    if demo.is_some() {
+        response.metadata.watermark = Some(DEMO_WATERMARK.to_string());
+    }
+
+    // Review mode: diff against the previous version of the same catalog (no snapshots kept in demo)
+    let catalog = form.catalog.as_deref().or(file_name.as_deref()).map(catalog_key).unwrap_or_default();
+    if !catalog.is_empty() && demo.is_none() {
+        let store = tenant.snapshots();
+        let sha256 = response.metadata.csv_info.sha256.clone();
+        if let Some(previous) = store.latest(&catalog, sha256.as_deref()) {
+            let mut diff = diff_works(&previous.works, &response.musical_works);
+            println!("🔁 Catalog '{}': {} new, {} changed, {} removed, {} unchanged since job {}",
+                catalog, diff.added.len(), diff.changed.len(), diff.removed.len(), diff.unchanged, previous.job_id);
+            if !diff.ambiguous.is_empty() {
+                println!("⚠️  {} work key(s) shared by several works, submitted again: {}",
+                    diff.ambiguous.len(), diff.ambiguous.join(", "));
+            }
+            diff.catalog = catalog.clone();
+            diff.previous_job_id = Some(previous.job_id);
+            diff.previous_created_at = Some(previous.created_at);
+            response.metadata.diff = Some(diff);
+        }
+
+        let snapshot = CatalogSnapshot {
+            job_id: response.job_id.clone(),
+            catalog,
+            created_at: deterministic::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            sha256,
+            period: response.metadata.period.clone(),
+            works: response.musical_works.clone(),
+        };
+        if let Err(e) = store.save(&snapshot) {
+            eprintln!("⚠️  Could not save catalog snapshot: {}", e);
+        }
+    }
     
+    events.emit(EventKind::Completed, json!({
+        "works": response.musical_works.len(),
+        "valid": response.metadata.validation.valid,
+        "invalid": response.metadata.validation.invalid,
+        "templateId": response.metadata.matrix_id,
+        "period": response.metadata.period,
+        "deliveries": response.metadata.deliveries,
+        "usage": usage,
+    }));
     Ok(Json(response))
 }
+
+/// Event log of a new job; events are also forwarded, in order, to the
+/// configured webhook (failed pushes are logged, not retried)
+fn new_event_log(job_id: &str) -> Arc<EventLog> {
+    let log = EventLog::new(job_id);
+    let Some(webhook) = EVENTS_WEBHOOK.get() else {
+        return Arc::new(log);
+    };
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(event) = receiver.recv().await {
+            if let Err(e) = post_webhook(webhook, &event).await {
+                eprintln!("⚠️  Event webhook {}: {}", webhook.url, e);
+            }
+        }
+    });
+    Arc::new(log.with_sink(sender))
+}
+
+/// Lifecycle events endpoint: what happened to a job so far, in order
+/// (also for failed jobs, which keep no records).
+async fn job_events(tenant: Tenant, Path(job_id): Path<String>) -> Result<Json<JobEventsResponse>, (StatusCode, Json<Value>)> {
+    let events = JOB_STORE.events(&tenant, &job_id).ok_or_else(|| {
+        (StatusCode::NOT_FOUND, Json(error_response(&format!("Unknown job: {}", job_id))))
+    })?;
+
+    Ok(Json(JobEventsResponse {
+        api_version: ApiVersion::default(),
+        sha256: JOB_STORE.get(&tenant, &job_id).and_then(|job| job.sha256),
+        job_id,
+        events: events.events(),
+    }))
+}
+
+/// Job list: the recent jobs of the tenant, newest first (`?period=2025-Q1`:
+/// only jobs of that period or, for `?period=2025`, of that year)
+async fn list_jobs(tenant: Tenant, Query(query): Query<JobListQuery>) -> Json<Vec<JobSummary>> {
+    Json(JOB_STORE.list(&tenant, query.period.as_deref()).into_iter().map(JobSummary::from).collect())
+}
+
+/// Column statistics endpoint: profile of each source column of a job
+/// (distinct values, empty rate, lengths, value patterns).
+async fn job_columns(tenant: Tenant, Path(job_id): Path<String>) -> Result<Json<ColumnsResponse>, (StatusCode, Json<Value>)> {
+    let job = JOB_STORE.get(&tenant, &job_id).ok_or_else(|| {
+        (StatusCode::NOT_FOUND, Json(error_response(&format!("Unknown job: {}", job_id))))
+    })?;
+
+    Ok(Json(ColumnsResponse {
+        api_version: ApiVersion::default(),
+        job_id: job.id,
+        sha256: job.sha256,
+        period: job.period,
+        row_count: job.source_rows,
+        columns: job.columns,
+    }))
+}
+
+/// Flat records endpoint: the normalized records of a job before grouping,
+/// as a JSON array or (`?format=csv`) a CSV with the flat schema columns.
+async fn job_flat(
+    tenant: Tenant,
+    Path(job_id): Path<String>,
+    Query(query): Query<FlatQuery>,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    let job = JOB_STORE.get(&tenant, &job_id).ok_or_else(|| {
+        (StatusCode::NOT_FOUND, Json(error_response(&format!("Unknown job: {}", job_id))))
+    })?;
+
+    let mut response = match query.format.as_deref().unwrap_or("json") {
+        "json" => Json(job.flat).into_response(),
+        "csv" => {
+            let csv = flat_csv(&job.flat).map_err(|e| {
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response(&format!("Cannot write CSV: {}", e))))
+            })?;
+            let disposition = format!("attachment; filename=\"{}-flat.csv\"", job_id);
+            (
+                [(header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()), (header::CONTENT_DISPOSITION, disposition)],
+                csv,
+            ).into_response()
+        }
+        other => return Err((StatusCode::BAD_REQUEST, Json(error_response(&format!(
+            "Unknown format: {} (expected json or csv)", other))))),
+    };
+    source_sha256_header(&mut response, job.sha256.as_deref());
+    Ok(response)
+}
+
+/// Name the source file of a job download by its checksum
+fn source_sha256_header(response: &mut Response, sha256: Option<&str>) {
+    if let Some(value) = sha256.and_then(|s| HeaderValue::from_str(s).ok()) {
+        response.headers_mut().insert(HeaderName::from_static(SOURCE_SHA256_HEADER), value);
+    }
+}
+
+/// ISWC allocation requests of a job: works without ISWC as a CSV file
+/// (title, creators, IPI, role, share) to send to a society.
+async fn job_iswc_requests(tenant: Tenant, Path(job_id): Path<String>) -> Result<Response, (StatusCode, Json<Value>)> {
+    let job = JOB_STORE.get(&tenant, &job_id).ok_or_else(|| {
+        (StatusCode::NOT_FOUND, Json(error_response(&format!("Unknown job: {}", job_id))))
+    })?;
+
+    let requests = works_lacking_iswc(&job.flat, &job.skipped);
+    let csv = allocation_csv(&requests).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response(&format!("Cannot write CSV: {}", e))))
+    })?;
+    println!("📝 Job {}: ISWC allocation requests for {} work(s)", job.id, requests.len());
+
+    let disposition = format!("attachment; filename=\"{}-iswc-requests.csv\"", job_id);
+    let mut response = (
+        [(header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()), (header::CONTENT_DISPOSITION, disposition)],
+        csv,
+    ).into_response();
+    source_sha256_header(&mut response, job.sha256.as_deref());
+    Ok(response)
+}
+
+/// Example endpoint: a bundled anonymized CSV, to try the flow without a catalog
+async fn example_csv(Path(name): Path<String>) -> Result<Response, (StatusCode, Json<Value>)> {
+    let example = example(&name).ok_or_else(|| {
+        let names: Vec<&str> = EXAMPLES.iter().map(|e| e.name).collect();
+        (StatusCode::NOT_FOUND, Json(error_response(&format!(
+            "Unknown example: {} (available: {})", name, names.join(", ")))))
+    })?;
+
+    let disposition = format!("attachment; filename=\"{}\"", example.file_name);
+    Ok((
+        [(header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()), (header::CONTENT_DISPOSITION, disposition)],
+        example.content,
+    ).into_response())
+}
+
+/// Download endpoint: grouped works of a job as NDJSON (one work per line).
+///
+/// Grouping goes through the on-disk external sort and each work is sent as
+/// soon as it is built, so the whole grouped catalog is never materialized.
+/// With `?stream=true`, a job still running (uploaded with `?jobId=`) is
+/// followed: works are sent as the pipeline groups them, until it completes.
+async fn download_job(
+    tenant: Tenant,
+    Path(job_id): Path<String>,
+    Query(query): Query<DownloadQuery>,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    if let Some(live) = JOB_STORE.running(&tenant, &job_id) {
+        if !query.stream {
+            return Err((StatusCode::CONFLICT, Json(error_response(&format!(
+                "Job {} is still running: use ?stream=true to follow its works", job_id)))));
+        }
+        println!("📡 Job {}: streaming works while the job runs", job_id);
+        let lines = live.stream().map(|work| work.map(|w| format!("{}\n", ensure_midds_format(w))));
+        return Ok(ndjson_response(&job_id, Body::from_stream(lines)));
+    }
+
+    let job = JOB_STORE.get(&tenant, &job_id).ok_or_else(|| {
+        (StatusCode::NOT_FOUND, Json(error_response(&format!("Unknown job: {}", job_id))))
+    })?;
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<Result<String, std::io::Error>>(64);
+    tokio::task::spawn_blocking(move || {
+        let works = match flat_to_grouped_streaming(job.flat.into_iter().map(Ok), std::env::temp_dir(), DEFAULT_RUN_SIZE) {
+            Ok(works) => works,
+            Err(e) => {
+                let _ = tx.blocking_send(Err(e));
+                return;
+            }
+        };
+        for work in works {
+            let line = work.map(|w| format!("{}\n", ensure_midds_format(w.to_value())));
+            if tx.blocking_send(line).is_err() {
+                break; // client went away
+            }
+        }
+    });
+
+    Ok(ndjson_response(&job_id, Body::from_stream(ReceiverStream::new(rx))))
+}
+
+/// NDJSON attachment named after the job
+fn ndjson_response(job_id: &str, body: Body) -> Response {
+    let disposition = format!("attachment; filename=\"{}.ndjson\"", job_id);
+    (
+        [(header::CONTENT_TYPE, "application/x-ndjson".to_string()), (header::CONTENT_DISPOSITION, disposition)],
+        body,
+    ).into_response()
+}
+
+/// Amend endpoint: complete skipped rows with user-supplied values,
+/// re-validate them and return the regrouped works
+async fn amend_job(
+    tenant: Tenant,
+    Path(job_id): Path<String>,
+    Json(request): Json<AmendRequest>,
+) -> Result<Json<AmendResponse>, (StatusCode, Json<Value>)> {
+    let (job, outcome) = JOB_STORE.amend(&tenant, &job_id, &request.amendments).ok_or_else(|| {
+        (StatusCode::NOT_FOUND, Json(error_response(&format!("Unknown job: {}", job_id))))
+    })?;
+
+    println!("✏️  Job {}: {} row(s) amended, {} rejected, {} still skipped",
+        job.id, outcome.amended, outcome.errors.len(), job.skipped.len());
+
+    let errors = outcome.errors.into_iter()
+        .map(|(row, errors)| ValidationError {
+            record_index: row,
+            line: job.skipped.iter().find(|s| s.row == row).and_then(|s| s.line),
+            errors,
+        })
+        .collect();
+    Ok(Json(AmendResponse {
+        api_version: ApiVersion::default(),
+        job_id: job.id,
+        sha256: job.sha256,
+        musical_works: musical_works_from_flat(job.flat),
+        amended: outcome.amended,
+        skipped: job.skipped,
+        errors,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_unversioned_alias_is_deprecated() {
+        let get = |uri: &str| Request::get(uri).body(Body::empty()).unwrap();
+
+        let response = router().oneshot(get("/api/v1/examples/sacem")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get(DEPRECATION_HEADER).is_none());
+
+        let response = router().oneshot(get("/api/examples/sacem")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[DEPRECATION_HEADER], "true");
+        assert_eq!(response.headers()[header::LINK], "</api/v1/examples/sacem>; rel=\"successor-version\"");
+
+        let response = router().oneshot(get("/api/v1/examples/unknown")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["apiVersion"], API_VERSION);
+    }
+
+    #[tokio::test]
+    async fn test_job_events_endpoint() {
+        let get = |uri: &str| Request::get(uri).body(Body::empty()).unwrap();
+        let events = Arc::new(EventLog::new("events-test"));
+        events.emit(EventKind::JobCreated, json!({ "fileName": "catalog.csv" }));
+        events.emit(EventKind::Failed, json!({ "error": "CSV file is empty" }));
+        JOB_STORE.track_events(&Tenant::default(), events);
+
+        let response = router().oneshot(get("/api/v1/jobs/events-test/events")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["events"][0]["event"], "job.created");
+        assert_eq!(body["events"][1]["data"]["error"], "CSV file is empty");
+        assert!(body.get("sha256").is_none());
+
+        // The job record names its source file by checksum
+        let period = CatalogPeriod::parse("2025-Q1").ok();
+        JOB_STORE.insert(Job::new("events-test", Vec::new(), Vec::new()).with_sha256(Some("ab12".to_string())).with_period(period));
+        let response = router().oneshot(get("/api/v1/jobs/events-test/events")).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(serde_json::from_slice::<Value>(&body).unwrap()["sha256"], "ab12");
+        let response = router().oneshot(get("/api/v1/jobs/events-test/flat?format=csv")).await.unwrap();
+        assert_eq!(response.headers()[SOURCE_SHA256_HEADER], "ab12");
+        let response = router().oneshot(get("/api/v1/jobs?period=2025")).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let jobs: Vec<JobSummary> = serde_json::from_slice(&body).unwrap();
+        assert!(jobs.iter().any(|j| j.id == "events-test" && j.sha256.as_deref() == Some("ab12")));
+        let response = router().oneshot(get("/api/v1/jobs?period=2024")).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(!serde_json::from_slice::<Vec<JobSummary>>(&body).unwrap().iter().any(|j| j.id == "events-test"));
+
+        let response = router().oneshot(get("/api/v1/jobs/unknown-job/events")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_validate_endpoint() {
+        let post = |body: Value| Request::post("/api/v1/validate")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let records = json!([
+            {
+                "iswc": "T1234567890",
+                "title": "My Song",
+                "creationYear": 1200,
+                "creators": [{ "id": { "type": "Ipi", "value": 123456789 }, "role": "Composer" }],
+                "participants": []
+            },
+            { "iswc": "T1234567890", "title": "My Song", "creatorIpi": 123456789, "creatorRole": "Composer" },
+            { "iswc": "BAD", "title": "Test", "creatorIpi": 123456789, "creatorRole": "Composer" }
+        ]);
+
+        let response = router().oneshot(post(records)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: ValidateResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body.grouped, 1);
+        assert_eq!((body.validation.valid, body.validation.invalid), (2, 1));
+        assert_eq!(body.validation.errors[0].record_index, 2);
+        assert_eq!(body.validation.warnings[0].record_index, 0);
+
+        let response = router().oneshot(post(json!({ "title": "My Song" }))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,144 @@
+//! Tenant isolation for a hosted instance shared by several societies.
+//!
+//! Each request is scoped by an `X-Org-Id` header, or by a tenant derived
+//! from its `X-Api-Key`. Links and `EventSource` streams cannot send headers,
+//! so `?orgId=` is accepted too. A tenant only sees its own jobs and logs,
+//! and its templates and catalog snapshots live under
+//! `.massload/tenants/<id>/`. Requests without a tenant use the default
+//! directories, as a single-tenant instance does.
+
+use axum::{
+    extract::FromRequestParts,
+    http::{request::Parts, StatusCode},
+    response::Json,
+};
+use serde_json::Value;
+use std::path::PathBuf;
+
+use super::types::error_response;
+use crate::cache::{MatrixRegistry, SnapshotStore};
+use crate::parser::checksum_sha256;
+
+/// Request header naming the tenant
+pub const ORG_HEADER: &str = "x-org-id";
+
+/// Request header whose key is hashed into a tenant id (when `X-Org-Id` is absent)
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Query parameter naming the tenant, for requests that cannot set headers
+const ORG_QUERY: &str = "orgId";
+
+/// Directory holding one data directory per tenant
+const TENANTS_DIR: &str = ".massload/tenants";
+
+/// Tenant of a request (`None`: the default, single-tenant data)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Tenant(Option<String>);
+
+impl Tenant {
+    /// Tenant named `id`; `None` if the id is not 1 to 64 letters, digits, `-` or `_`
+    pub fn named(id: &str) -> Option<Self> {
+        is_valid_tenant_id(id).then(|| Self(Some(id.to_string())))
+    }
+
+    /// Tenant derived from an API key: `key-` and the start of its SHA-256
+    pub fn from_api_key(key: &str) -> Self {
+        Self(Some(format!("key-{}", &checksum_sha256(key.as_bytes())[..16])))
+    }
+
+    /// Tenant id (`None` for the default tenant)
+    pub fn id(&self) -> Option<&str> {
+        self.0.as_deref()
+    }
+
+    /// Data directory of this tenant, `None` for the default directories
+    pub fn data_dir(&self) -> Option<PathBuf> {
+        self.0.as_ref().map(|id| PathBuf::from(TENANTS_DIR).join(id))
+    }
+
+    /// Template registry directory of this tenant (`None`: the default registry)
+    pub fn registry_dir(&self) -> Option<String> {
+        self.data_dir().map(|dir| dir.join("matrices").to_string_lossy().into_owned())
+    }
+
+    /// Template registry of this tenant
+    pub fn registry(&self) -> MatrixRegistry {
+        match self.registry_dir() {
+            Some(dir) => MatrixRegistry::with_dir(dir),
+            None => MatrixRegistry::new(),
+        }
+    }
+
+    /// Catalog snapshot store of this tenant
+    pub fn snapshots(&self) -> SnapshotStore {
+        match self.data_dir() {
+            Some(dir) => SnapshotStore::with_dir(dir.join("snapshots")),
+            None => SnapshotStore::new(),
+        }
+    }
+}
+
+/// Tenant ids: 1 to 64 letters, digits, `-` or `_` (they name a directory)
+fn is_valid_tenant_id(id: &str) -> bool {
+    (1..=64).contains(&id.len()) && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Tenant id from the `orgId` query parameter
+fn query_org_id(query: &str) -> Option<String> {
+    query.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(name, _)| *name == ORG_QUERY)
+        .map(|(_, value)| value.to_string())
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Tenant {
+    type Rejection = (StatusCode, Json<Value>);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let header = |name: &str| parts.headers.get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+
+        let org_id = header(ORG_HEADER).or_else(|| parts.uri.query().and_then(query_org_id));
+        match org_id {
+            Some(id) => Tenant::named(&id).ok_or_else(|| {
+                (StatusCode::BAD_REQUEST, Json(error_response(&format!("Invalid organization id: {}", id))))
+            }),
+            None => Ok(header(API_KEY_HEADER).map(|key| Tenant::from_api_key(&key)).unwrap_or_default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+
+    async fn tenant_of(request: Request<()>) -> Result<Tenant, StatusCode> {
+        let (mut parts, _) = request.into_parts();
+        Tenant::from_request_parts(&mut parts, &()).await.map_err(|(status, _)| status)
+    }
+
+    #[tokio::test]
+    async fn test_tenant_from_request() {
+        let org = Request::get("/api/logs").header(ORG_HEADER, "sacem").body(()).unwrap();
+        assert_eq!(tenant_of(org).await.unwrap().id(), Some("sacem"));
+
+        let query = Request::get("/api/logs?orgId=gema").body(()).unwrap();
+        assert_eq!(tenant_of(query).await.unwrap().id(), Some("gema"));
+
+        let key = Request::get("/api/templates").header(API_KEY_HEADER, "secret").body(()).unwrap();
+        let tenant = tenant_of(key).await.unwrap();
+        assert_eq!(tenant, Tenant::from_api_key("secret"));
+        assert!(tenant.id().unwrap().starts_with("key-"));
+
+        let none = Request::get("/api/templates").body(()).unwrap();
+        assert_eq!(tenant_of(none).await.unwrap(), Tenant::default());
+        assert_eq!(Tenant::default().registry_dir(), None);
+
+        // Ids name a directory: no path traversal
+        let invalid = Request::get("/api/templates").header(ORG_HEADER, "../other").body(()).unwrap();
+        assert_eq!(tenant_of(invalid).await.unwrap_err(), StatusCode::BAD_REQUEST);
+    }
+}
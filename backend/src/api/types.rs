@@ -5,19 +5,52 @@
 
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use uuid::Uuid;
+use std::collections::BTreeMap;
 
-use crate::transform::pipeline::PipelineResult;
+use super::jobs::Job;
+use crate::ai::{AiCostEstimate, EngineReport};
+use crate::deterministic;
+use crate::models::CatalogPeriod;
+use crate::cache::{suggest_name, StoredMatrix};
+use crate::parser::{ColumnProfile, ParseDiagnostics, ParseReport, ParseResult, RaggedRow};
+use crate::sinks::Delivery;
+use crate::transform::diagnosis::{diagnose_failure, FailureReport};
+use crate::transform::diff::CatalogDiff;
+use crate::transform::roles::RoleReport;
+use crate::transform::dsl::{FieldStats, SkippedRow};
+use crate::transform::events::JobEvent;
+use crate::transform::{flat_to_grouped, to_values};
+use crate::transform::pipeline::{ColumnCoverage, PipelineResult, TemplateEvaluation};
+use crate::validation::ValidationWarning;
+use super::jobs::Amendment;
+
+/// Current API version: endpoints live under `/api/v1`
+pub const API_VERSION: &str = "v1";
+
+/// Version tag of every response envelope (`"apiVersion": "v1"`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ApiVersion(pub String);
+
+impl Default for ApiVersion {
+    fn default() -> Self {
+        Self(API_VERSION.to_string())
+    }
+}
 
 /// Response sent to frontend after CSV upload and transformation.
 /// `musical_works` contains MIDDS format ready for blockchain.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UploadResponse {
+    /// API version of this response
+    #[serde(default)]
+    pub api_version: ApiVersion,
+
     /// Unique job identifier
     pub job_id: String,
     
-    /// Status: "ready", "warning", "error"
+    /// Status: "ready", "warning", "all_invalid", "error"
     pub status: String,
     
     /// Musical works in MIDDS format - ready for blockchain
@@ -48,6 +81,69 @@ pub struct ResponseMetadata {
     
     /// Validation stats
     pub validation: ValidationStats,
+
+    /// Rows skipped because required fields were missing (can be amended)
+    #[serde(default)]
+    pub skipped: Vec<SkippedRow>,
+
+    /// Changes since the previous upload of the same catalog
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<CatalogDiff>,
+
+    /// Columns matched, missing and unused by the cached template
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_columns: Option<ColumnCoverage>,
+
+    /// Top error causes and next steps when no record is valid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure: Option<FailureReport>,
+
+    /// Template saved from the AI matrix of this upload, to be named by the user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_template: Option<NewTemplate>,
+
+    /// Engine that generated the matrix (primary, secondary or heuristic) and failover reason
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matrix_engine: Option<EngineReport>,
+
+    /// Estimated size and cost of the AI request, when the AI was called
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ai_estimate: Option<AiCostEstimate>,
+
+    /// Works without ISWC, listed by `GET /api/v1/jobs/{id}/iswc-requests`
+    #[serde(default)]
+    pub missing_iswc: usize,
+
+    /// Operation failures per target field (regex, number and mapping fallbacks)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub field_stats: BTreeMap<String, FieldStats>,
+
+    /// Creators per role and works without composer
+    #[serde(default)]
+    pub roles: RoleReport,
+
+    /// Demo instance notice (see `massload serve --demo`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watermark: Option<String>,
+
+    /// Accounting period of the catalog (`period` form field)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub period: Option<CatalogPeriod>,
+
+    /// Delivery of the grouped works to each output sink chosen for the job
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deliveries: Vec<Delivery>,
+}
+
+/// A template created by an upload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewTemplate {
+    pub id: String,
+    /// Name it was saved with (the file name)
+    pub name: String,
+    /// Human-friendly name to propose
+    pub suggested_name: String,
 }
 
 /// CSV file metadata
@@ -55,9 +151,63 @@ pub struct ResponseMetadata {
 #[serde(rename_all = "camelCase")]
 pub struct CsvMetadata {
     pub encoding: String,
+    /// Decode-quality score of the detected encoding (below 0.9 = uncertain)
+    pub encoding_confidence: f32,
     pub delimiter: String,
     pub row_count: usize,
     pub columns: Vec<String>,
+    /// SHA-256 of the uploaded bytes, proves which file version was processed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Trailing lines dropped as a footer (totals, end-of-file markers)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub footer_lines: Vec<String>,
+    /// Rows whose cell count differs from the header (`ragged_rows` warn or reject)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ragged_rows: Vec<RaggedRow>,
+    /// NUL characters removed and mixed line endings normalized, with data-quality notes
+    #[serde(default, skip_serializing_if = "ParseDiagnostics::is_clean")]
+    pub diagnostics: ParseDiagnostics,
+    /// Column names changed by normalization (trim, whitespace, accents): normalized → original
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub original_headers: BTreeMap<String, String>,
+    /// Statistics of each column: fill rate, distinct values, sample values
+    #[serde(default)]
+    pub report: ParseReport,
+}
+
+impl From<&ParseResult> for CsvMetadata {
+    fn from(result: &ParseResult) -> Self {
+        CsvMetadata {
+            encoding: result.encoding.clone(),
+            encoding_confidence: result.encoding_confidence,
+            delimiter: result.delimiter.to_string(),
+            row_count: result.records.len(),
+            columns: result.headers.clone(),
+            sha256: None,
+            footer_lines: result.footer_lines.clone(),
+            ragged_rows: result.ragged_rows.clone(),
+            diagnostics: result.diagnostics.clone(),
+            original_headers: result.original_headers.clone(),
+            report: result.report(),
+        }
+    }
+}
+
+/// Response of the preflight endpoint: parsing only, no AI, nothing persisted.
+/// Lets the frontend show the detected columns before the real upload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightResponse {
+    /// API version of this response
+    #[serde(default)]
+    pub api_version: ApiVersion,
+
+    /// Original file name (if provided)
+    pub file_name: Option<String>,
+
+    /// Detected CSV info
+    pub csv_info: CsvMetadata,
 }
 
 /// Validation statistics
@@ -67,6 +217,9 @@ pub struct ValidationStats {
     pub valid: usize,
     pub invalid: usize,
     pub errors: Vec<ValidationError>,
+    /// Non-blocking findings (implausible creation years): the works are still submitted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<ValidationWarning>,
 }
 
 /// A validation error
@@ -74,6 +227,9 @@ pub struct ValidationStats {
 #[serde(rename_all = "camelCase")]
 pub struct ValidationError {
     pub record_index: usize,
+    /// 1-based line of the record in the source file, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
     pub errors: Vec<String>,
 }
 
@@ -81,6 +237,7 @@ pub struct ValidationError {
 /// The grouped works are already in MIDDS format!
 impl From<PipelineResult> for UploadResponse {
     fn from(result: PipelineResult) -> Self {
+        let failure = diagnose_failure(&result);
         // grouped is already in MIDDS format from grouper.rs
         // Just need to ensure camelCase naming
         let musical_works: Vec<Value> = result
@@ -93,20 +250,41 @@ impl From<PipelineResult> for UploadResponse {
         let cost_per_work = 0.05;
         let estimated_cost = format!("{:.2} AFT", total as f64 * cost_per_work);
 
+        let new_template = match (&result.template_id, result.created_template) {
+            (Some(id), Some(name)) => Some(NewTemplate {
+                id: id.clone(),
+                suggested_name: suggest_name(&name),
+                name,
+            }),
+            _ => None,
+        };
+
         UploadResponse {
-            job_id: Uuid::new_v4().to_string(),
-            status: if result.invalid_count == 0 { "ready" } else { "warning" }.to_string(),
+            api_version: ApiVersion::default(),
+            job_id: deterministic::new_uuid().to_string(),
+            status: match (&failure, result.invalid_count) {
+                (Some(_), _) => "all_invalid",
+                (None, 0) => "ready",
+                (None, _) => "warning",
+            }.to_string(),
             musical_works,
             metadata: ResponseMetadata {
                 total_works: total,
                 estimated_cost,
-                cached: result.template_id.is_some(),
+                cached: result.template_id.is_some() && new_template.is_none(),
                 matrix_id: result.template_id,
                 csv_info: CsvMetadata {
                     encoding: result.csv_info.encoding,
+                    encoding_confidence: result.csv_info.encoding_confidence,
                     delimiter: result.csv_info.delimiter.to_string(),
                     row_count: result.csv_info.row_count,
                     columns: result.csv_info.headers,
+                    sha256: result.csv_info.sha256,
+                    footer_lines: result.csv_info.footer_lines,
+                    ragged_rows: result.csv_info.ragged_rows,
+                    diagnostics: result.csv_info.diagnostics,
+                    original_headers: result.csv_info.original_headers,
+                    report: result.csv_info.report,
                 },
                 validation: ValidationStats {
                     valid: result.valid_count,
@@ -114,18 +292,224 @@ impl From<PipelineResult> for UploadResponse {
                     errors: result.validation_errors.into_iter()
                         .map(|(idx, errs)| ValidationError {
                             record_index: idx,
+                            line: result.flat_lines.get(idx).copied(),
                             errors: errs,
                         })
                         .collect(),
+                    warnings: result.validation_warnings,
                 },
+                skipped: result.skipped,
+                diff: None,
+                template_columns: result.template_columns,
+                failure,
+                new_template,
+                matrix_engine: result.matrix_engine,
+                ai_estimate: result.ai_estimate,
+                missing_iswc: 0,
+                field_stats: result.field_stats,
+                roles: result.roles,
+                watermark: None,
+                period: result.period,
+                deliveries: Vec::new(),
             },
         }
     }
 }
 
+/// Body of `POST /api/v1/jobs/{id}/amend`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmendRequest {
+    pub amendments: Vec<Amendment>,
+}
+
+/// Response after amending skipped rows of a job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmendResponse {
+    /// API version of this response
+    #[serde(default)]
+    pub api_version: ApiVersion,
+
+    pub job_id: String,
+
+    /// SHA-256 of the uploaded file the job was built from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+
+    /// All musical works of the job, regrouped with the amended rows
+    pub musical_works: Vec<Value>,
+
+    /// Number of rows merged into the job
+    pub amended: usize,
+
+    /// Rows still skipped
+    pub skipped: Vec<SkippedRow>,
+
+    /// Amendments rejected by validation (record_index = row)
+    pub errors: Vec<ValidationError>,
+}
+
+/// Response of `POST /api/v1/templates/evaluate`: every compatible template
+/// tried on a sample of the file, best first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateEvaluationResponse {
+    /// API version of this response
+    #[serde(default)]
+    pub api_version: ApiVersion,
+
+    /// Original file name (if provided)
+    pub file_name: Option<String>,
+
+    /// Detected CSV info
+    pub csv_info: CsvMetadata,
+
+    /// Number of rows each template was run on
+    pub sampled_rows: usize,
+
+    /// Comparison table, best template first
+    pub templates: Vec<TemplateEvaluation>,
+}
+
+/// Response of `GET /api/v1/jobs/{id}/columns`: profile of each source column
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnsResponse {
+    /// API version of this response
+    #[serde(default)]
+    pub api_version: ApiVersion,
+
+    pub job_id: String,
+
+    /// SHA-256 of the uploaded file the job was built from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+
+    /// Accounting period of the catalog
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub period: Option<CatalogPeriod>,
+
+    /// Number of source rows
+    pub row_count: usize,
+
+    /// One entry per source column, in file order
+    pub columns: Vec<ColumnProfile>,
+}
+
+/// Response of `GET /api/v1/jobs/{id}/events`: lifecycle events of a job, in order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobEventsResponse {
+    /// API version of this response
+    #[serde(default)]
+    pub api_version: ApiVersion,
+
+    pub job_id: String,
+
+    /// SHA-256 of the uploaded file the job was built from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+
+    pub events: Vec<JobEvent>,
+}
+
+/// A stored job as listed by `GET /api/v1/jobs`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobSummary {
+    pub id: String,
+    /// RFC 3339
+    pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub period: Option<CatalogPeriod>,
+    /// Number of source rows
+    pub source_rows: usize,
+    /// Flat records (one per creator)
+    pub records: usize,
+    /// Rows skipped because required fields were missing
+    pub skipped: usize,
+}
+
+impl From<Job> for JobSummary {
+    fn from(job: Job) -> Self {
+        Self {
+            created_at: job.created_at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            records: job.flat.len(),
+            skipped: job.skipped.len(),
+            source_rows: job.source_rows,
+            sha256: job.sha256,
+            period: job.period,
+            id: job.id,
+        }
+    }
+}
+
+/// Response of `POST /api/v1/validate`: schema check of MIDDS records built elsewhere
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidateResponse {
+    /// API version of this response
+    #[serde(default)]
+    pub api_version: ApiVersion,
+
+    /// Records checked against the grouped schema (the others are flat)
+    pub grouped: usize,
+
+    pub validation: ValidationStats,
+}
+
+/// A stored template as listed by `GET /api/v1/templates`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateSummary {
+    pub id: String,
+    pub name: String,
+    pub csv_columns: Vec<String>,
+    pub created_at: String,
+    pub last_used: Option<String>,
+    pub success_rate: f64,
+    pub use_count: u32,
+    /// Uses per catalog accounting period
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub periods: BTreeMap<String, u32>,
+    /// Set for archived templates (left out of matching)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<String>,
+}
+
+impl From<&StoredMatrix> for TemplateSummary {
+    fn from(stored: &StoredMatrix) -> Self {
+        Self {
+            id: stored.id.clone(),
+            name: stored.name.clone(),
+            csv_columns: stored.csv_columns.clone(),
+            created_at: stored.created_at.clone(),
+            last_used: stored.last_used.clone(),
+            success_rate: stored.success_rate,
+            use_count: stored.use_count,
+            periods: stored.periods.clone(),
+            archived_at: stored.archived_at.clone(),
+        }
+    }
+}
+
+/// Body of `POST /api/v1/templates/{id}/rename`
+#[derive(Debug, Clone, Deserialize)]
+pub struct RenameTemplateRequest {
+    pub name: String,
+}
+
+/// Group flat records into MIDDS musical works
+pub fn musical_works_from_flat(flat: Vec<Value>) -> Vec<Value> {
+    to_values(&flat_to_grouped(flat)).into_iter().map(ensure_midds_format).collect()
+}
+
 /// Ensure the work is in exact MIDDS format for blockchain
 /// grouper.rs already produces this format, just ensure consistency
-fn ensure_midds_format(work: Value) -> Value {
+pub(crate) fn ensure_midds_format(work: Value) -> Value {
     // The grouped format from grouper.rs is already MIDDS-compatible:
     // {
     //   "iswc": "T1234567890",
@@ -147,7 +531,8 @@ fn ensure_midds_format(work: Value) -> Value {
 /// Create an error response
 pub fn error_response(error: &str) -> Value {
     json!({
-        "jobId": Uuid::new_v4().to_string(),
+        "apiVersion": API_VERSION,
+        "jobId": deterministic::new_uuid().to_string(),
         "status": "error",
         "error": error,
         "musicalWorks": [],
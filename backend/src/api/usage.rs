@@ -0,0 +1,274 @@
+//! Resource usage of upload jobs, for capacity planning.
+//!
+//! While a job runs, a [`UsageMonitor`] thread samples the resident memory of
+//! the process and the size of the job's temporary directory (external sort
+//! runs of streamed downloads). CPU time is the process time spent between
+//! start and finish. Figures are read from `/proc` and are process-wide:
+//! jobs running at the same time are counted in each other's usage. On
+//! systems without `/proc`, memory and CPU time are left out.
+//!
+//! Finished jobs are added to [`JOB_METRICS`], served in the Prometheus text
+//! format by `GET /metrics`.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Interval between two memory and temp-disk samples
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Clock ticks per second of `/proc/self/stat` times (`USER_HZ`, 100 on Linux)
+const CLOCK_TICKS: u64 = 100;
+
+/// Histogram buckets of peak memory (bytes): 64 MB to 8 GB
+const MEMORY_BUCKETS: &[f64] = &[
+    64e6, 128e6, 256e6, 512e6, 1e9, 2e9, 4e9, 8e9,
+];
+
+/// Histogram buckets of CPU time (seconds)
+const CPU_BUCKETS: &[f64] = &[0.1, 0.5, 1.0, 5.0, 15.0, 60.0, 300.0, 900.0];
+
+/// Histogram buckets of temporary disk usage (bytes): 1 MB to 16 GB
+const TEMP_BUCKETS: &[f64] = &[1e6, 16e6, 128e6, 512e6, 1e9, 4e9, 16e9];
+
+/// Global metrics of finished jobs
+pub static JOB_METRICS: Lazy<JobMetrics> = Lazy::new(JobMetrics::default);
+
+/// Resources used by one job
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceUsage {
+    /// Elapsed time
+    pub wall_ms: u64,
+    /// CPU time (user + system) of the process during the job
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_ms: Option<u64>,
+    /// Highest resident memory of the process seen during the job
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_memory_bytes: Option<u64>,
+    /// Largest size reached by the job's temporary directory
+    pub peak_temp_bytes: u64,
+}
+
+/// Samples resources until [`UsageMonitor::finish`]
+pub struct UsageMonitor {
+    started: Instant,
+    cpu_start: Option<u64>,
+    stop: Arc<AtomicBool>,
+    sampler: JoinHandle<(Option<u64>, u64)>,
+}
+
+impl UsageMonitor {
+    /// Start sampling; `temp_dir` is the job's own temporary directory, if any
+    pub fn start(temp_dir: Option<PathBuf>) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = stop.clone();
+        let sampler = thread::spawn(move || {
+            let (mut peak_memory, mut peak_temp) = (None, 0);
+            loop {
+                // Read before sampling: the last sample is taken after `finish`
+                let stopping = flag.load(Ordering::Relaxed);
+                if let Some(rss) = resident_memory() {
+                    peak_memory = Some(peak_memory.unwrap_or(0).max(rss));
+                }
+                if let Some(ref dir) = temp_dir {
+                    peak_temp = peak_temp.max(dir_size(dir));
+                }
+                if stopping {
+                    return (peak_memory, peak_temp);
+                }
+                thread::park_timeout(SAMPLE_INTERVAL);
+            }
+        });
+        Self { started: Instant::now(), cpu_start: cpu_ticks(), stop, sampler }
+    }
+
+    /// Stop sampling and return the usage (after a last sample)
+    pub fn finish(self) -> ResourceUsage {
+        self.stop.store(true, Ordering::Relaxed);
+        self.sampler.thread().unpark();
+        let (peak_memory_bytes, peak_temp_bytes) = self.sampler.join().unwrap_or_default();
+        let cpu_ms = self.cpu_start
+            .zip(cpu_ticks())
+            .map(|(start, end)| end.saturating_sub(start) * 1000 / CLOCK_TICKS);
+        ResourceUsage {
+            wall_ms: self.started.elapsed().as_millis() as u64,
+            cpu_ms,
+            peak_memory_bytes,
+            peak_temp_bytes,
+        }
+    }
+}
+
+/// Resident memory of the process (`VmRSS` of `/proc/self/status`)
+fn resident_memory() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// User + system time of the process, in clock ticks (`/proc/self/stat`)
+fn cpu_ticks() -> Option<u64> {
+    let stat = fs::read_to_string("/proc/self/stat").ok()?;
+    // Fields after the command name, which may hold spaces: `utime` and `stime` are 14th and 15th
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(utime + stime)
+}
+
+/// Total size of the files under `dir` (0 when missing)
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Prometheus histogram (cumulative buckets, sum, count)
+#[derive(Debug, Clone)]
+struct Histogram {
+    bounds: &'static [f64],
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self { bounds, counts: vec![0; bounds.len()], sum: 0.0, count: 0 }
+    }
+
+    fn observe(&mut self, value: f64) {
+        for (bound, count) in self.bounds.iter().zip(&mut self.counts) {
+            if value <= *bound {
+                *count += 1;
+            }
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, count);
+        }
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, self.count);
+        let _ = writeln!(out, "{}_sum {}", name, self.sum);
+        let _ = writeln!(out, "{}_count {}", name, self.count);
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MetricsState {
+    completed: u64,
+    failed: u64,
+    wall_seconds: f64,
+    cpu_seconds: Histogram,
+    peak_memory: Histogram,
+    peak_temp: Histogram,
+}
+
+/// Usage of finished jobs, aggregated since the server started
+#[derive(Debug)]
+pub struct JobMetrics {
+    state: Mutex<MetricsState>,
+}
+
+impl Default for JobMetrics {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(MetricsState {
+                completed: 0,
+                failed: 0,
+                wall_seconds: 0.0,
+                cpu_seconds: Histogram::new(CPU_BUCKETS),
+                peak_memory: Histogram::new(MEMORY_BUCKETS),
+                peak_temp: Histogram::new(TEMP_BUCKETS),
+            }),
+        }
+    }
+}
+
+impl JobMetrics {
+    /// Add a finished job
+    pub fn record(&self, usage: &ResourceUsage, failed: bool) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if failed {
+            state.failed += 1;
+        } else {
+            state.completed += 1;
+        }
+        state.wall_seconds += usage.wall_ms as f64 / 1000.0;
+        if let Some(cpu_ms) = usage.cpu_ms {
+            state.cpu_seconds.observe(cpu_ms as f64 / 1000.0);
+        }
+        if let Some(memory) = usage.peak_memory_bytes {
+            state.peak_memory.observe(memory as f64);
+        }
+        state.peak_temp.observe(usage.peak_temp_bytes as f64);
+    }
+
+    /// Metrics in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let mut out = String::new();
+        out.push_str("# HELP massload_jobs_total Upload jobs finished, by outcome\n");
+        out.push_str("# TYPE massload_jobs_total counter\n");
+        let _ = writeln!(out, "massload_jobs_total{{status=\"completed\"}} {}", state.completed);
+        let _ = writeln!(out, "massload_jobs_total{{status=\"failed\"}} {}", state.failed);
+        out.push_str("# HELP massload_job_wall_seconds_total Elapsed time of finished jobs\n");
+        out.push_str("# TYPE massload_job_wall_seconds_total counter\n");
+        let _ = writeln!(out, "massload_job_wall_seconds_total {}", state.wall_seconds);
+        state.cpu_seconds.render(&mut out, "massload_job_cpu_seconds", "CPU time of the process during each job");
+        state.peak_memory.render(&mut out, "massload_job_peak_memory_bytes", "Peak resident memory of the process during each job");
+        state.peak_temp.render(&mut out, "massload_job_peak_temp_bytes", "Peak size of the temporary files of each job");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monitor_and_metrics() {
+        let dir = tempfile::tempdir().unwrap();
+        let monitor = UsageMonitor::start(Some(dir.path().to_path_buf()));
+        fs::write(dir.path().join("run-0.ndjson"), vec![b'x'; 4096]).unwrap();
+        let usage = monitor.finish();
+        assert_eq!(usage.peak_temp_bytes, 4096);
+        if cfg!(target_os = "linux") {
+            assert!(usage.peak_memory_bytes.unwrap() > 0);
+            assert!(usage.cpu_ms.is_some());
+        }
+
+        let metrics = JobMetrics::default();
+        metrics.record(&ResourceUsage { wall_ms: 1500, cpu_ms: Some(700), peak_memory_bytes: Some(300_000_000), peak_temp_bytes: 0 }, false);
+        metrics.record(&ResourceUsage { wall_ms: 500, ..ResourceUsage::default() }, true);
+        let text = metrics.render();
+        assert!(text.contains("massload_jobs_total{status=\"completed\"} 1\n"));
+        assert!(text.contains("massload_jobs_total{status=\"failed\"} 1\n"));
+        assert!(text.contains("massload_job_wall_seconds_total 2\n"));
+        assert!(text.contains("massload_job_cpu_seconds_bucket{le=\"0.5\"} 0\n"));
+        assert!(text.contains("massload_job_cpu_seconds_bucket{le=\"1\"} 1\n"));
+        assert!(text.contains("massload_job_peak_memory_bytes_bucket{le=\"512000000\"} 1\n"));
+        assert!(text.contains("massload_job_peak_temp_bytes_count 2\n"));
+    }
+}
@@ -0,0 +1,145 @@
+//! Audit of stored templates against the current flat schema
+//!
+//! A template saved before a schema change may target a field that no longer
+//! exists, miss a newly required one or map to a value the schema dropped; a
+//! template using a removed operation doesn't even load. The audit lists, per
+//! template, the edits needed to make it compatible again.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+use crate::transform::dsl::matrix::{ExpandConfig, FieldTransform, TransformationMatrix};
+use crate::transform::dsl::operations::Operation;
+
+/// Audit result of one stored template
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateAudit {
+    /// Template ID (file stem)
+    pub id: String,
+    /// Template name (None when the file no longer loads)
+    pub name: Option<String>,
+    /// Required edits, one message each (empty: compatible)
+    pub issues: Vec<String>,
+    /// The file no longer loads (removed operation, broken JSON): it is
+    /// already ignored by the registry and can only be fixed by hand
+    pub unreadable: bool,
+}
+
+impl TemplateAudit {
+    pub fn is_compatible(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// The embedded flat MIDDS schema templates are audited against
+pub fn flat_schema() -> Value {
+    serde_json::from_str(crate::validation::FLAT_SCHEMA).unwrap_or_default()
+}
+
+/// Required edits for `matrix` to produce records valid against the flat `schema`
+pub fn audit_matrix(matrix: &TransformationMatrix, schema: &Value) -> Vec<String> {
+    let properties = schema.get("properties").and_then(Value::as_object);
+    let known = |field: &str| properties.is_some_and(|p| p.contains_key(field));
+    let mut issues = Vec::new();
+
+    let mut targets: Vec<(&String, &FieldTransform)> = matrix.transforms.iter().collect();
+    if let Some(ExpandConfig::MultipleColumns { variants }) = &matrix.expand {
+        targets.extend(variants.iter().flat_map(|v| v.overrides.iter()));
+    }
+    targets.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut produced = BTreeSet::new();
+    for (field, transform) in targets {
+        if !known(field) {
+            let hint = properties
+                .and_then(|p| p.keys().find(|k| same_field(k, field)))
+                .map(|k| format!("rename it to `{}`", k))
+                .unwrap_or_else(|| "remove it".to_string());
+            issues.push(format!("field `{}` is not in the schema: {}", field, hint));
+            continue;
+        }
+        produced.insert(field.as_str());
+
+        let allowed = properties.and_then(|p| p[field].get("enum")).and_then(Value::as_array);
+        if let Some(allowed) = allowed {
+            for value in literal_outputs(transform) {
+                if !allowed.iter().any(|a| a.as_str() == Some(value)) {
+                    issues.push(format!("field `{}` produces \"{}\", no longer an allowed value: update the mapping", field, value));
+                }
+            }
+        }
+    }
+
+    if let Some(ExpandConfig::SplitRole { mapping, .. }) = &matrix.expand {
+        produced.insert("creatorRole");
+        let allowed = properties.and_then(|p| p.get("creatorRole")?.get("enum")?.as_array());
+        for value in mapping.values().collect::<BTreeSet<_>>() {
+            if allowed.is_some_and(|a| !a.iter().any(|a| a.as_str() == Some(value))) {
+                issues.push(format!("role split produces \"{}\", no longer an allowed `creatorRole`: update the mapping", value));
+            }
+        }
+    }
+
+    let required = schema.get("required").and_then(Value::as_array).into_iter().flatten();
+    for field in required.filter_map(Value::as_str) {
+        if !produced.contains(field) {
+            issues.push(format!("required field `{}` has no transform: add one", field));
+        }
+    }
+    issues
+}
+
+/// Same field name up to case and separators (`creator_ipi` ~ `creatorIpi`)
+fn same_field(a: &str, b: &str) -> bool {
+    let key = |s: &str| s.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase();
+    key(a) == key(b)
+}
+
+/// String values a transform can write regardless of the source (constant, default, mappings)
+fn literal_outputs(transform: &FieldTransform) -> BTreeSet<&str> {
+    let mut values: BTreeSet<&str> = [&transform.constant, &transform.default]
+        .into_iter()
+        .filter_map(|v| v.as_ref()?.as_str())
+        .collect();
+    for op in &transform.operations {
+        if let Operation::Map { mapping, default_unmapped, .. } = op {
+            values.extend(mapping.values().map(String::as_str));
+            values.extend(default_unmapped.as_deref());
+        }
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_audit_after_schema_change() {
+        let matrix = TransformationMatrix::from_value(&json!({
+            "transforms": {
+                "iswc": { "source": "ISWC" },
+                "creator_ipi": { "source": "IPI" },
+                "creatorRole": { "source": "Role", "operations": [
+                    { "type": "map", "mapping": { "CA": "Composer", "AR": "Arranger" } }
+                ]}
+            }
+        })).unwrap();
+        let schema = json!({
+            "required": ["iswc", "title", "creatorRole"],
+            "properties": {
+                "iswc": {}, "title": {}, "creatorIpi": {},
+                "creatorRole": { "enum": ["Author", "Composer"] }
+            }
+        });
+
+        assert_eq!(audit_matrix(&matrix, &schema), vec![
+            "field `creatorRole` produces \"Arranger\", no longer an allowed value: update the mapping",
+            "field `creator_ipi` is not in the schema: rename it to `creatorIpi`",
+            "required field `title` has no transform: add one",
+        ]);
+        assert!(audit_matrix(&crate::transform::dsl::example_matrix(), &flat_schema()).is_empty());
+    }
+}
@@ -0,0 +1,305 @@
+//! Import of mappings from the legacy massdrop tooling
+//!
+//! A massdrop mapping (YAML or JSON) describes, per target field, the CSV
+//! column(s) to read and the cleaning steps to apply:
+//!
+//! ```yaml
+//! name: sacem-2019
+//! csv:
+//!   separator: ";"
+//!   encoding: windows-1252
+//!   skip_lines: 2        # banner lines before the header
+//!   skip_footer: 1       # totals line
+//! fields:
+//!   iswc:
+//!     from: Code ISWC
+//!     clean: [trim, upper, strip_punctuation]
+//!     required: true
+//!   title:
+//!     from: [Titre, Sous-titre]
+//!     join: " - "
+//!   creator_ipi:
+//!     from: IPI
+//!     clean: [digits_only]
+//!     pad_left: 11
+//!   creator_role:
+//!     from: Role
+//!     translate: { CA: Composer, A: Author }
+//!     translate_ignore_case: true
+//!   instrumental:
+//!     value: false
+//! split_roles:           # one row per role of "C+A" values
+//!   from: Role
+//!   separator: "+"
+//!   translate: { C: Composer, A: Author }
+//! ```
+//!
+//! Field names are converted from snake_case (`creator_ipi` → `creatorIpi`).
+//! Steps are applied in this order: `clean`, `regex_replace`, `substring`,
+//! `pad_left`, `prefix`, `translate`; `fallback` is the default value.
+//! Constructs without an equivalent (`script`, `lookup`, `when`, unknown keys
+//! or cleaning steps) are left out and listed in the import report.
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::transform::dsl::matrix::{ExpandConfig, FieldTransform, SourceFormat, TransformationMatrix};
+use crate::transform::dsl::operations::Operation;
+
+/// A legacy mapping converted to a matrix
+#[derive(Debug, Clone)]
+pub struct LegacyImport {
+    pub matrix: TransformationMatrix,
+    /// Mapping name, if set in the file
+    pub name: Option<String>,
+    /// Constructs left out of the matrix (one message each)
+    pub unsupported: Vec<String>,
+}
+
+/// Field keys understood by the converter
+const FIELD_KEYS: &[&str] = &[
+    "from", "join", "value", "fallback", "required", "clean", "regex_replace",
+    "substring", "pad_left", "pad_with", "prefix", "translate", "translate_ignore_case",
+];
+
+/// Convert a massdrop mapping (YAML or JSON) into a transformation matrix
+pub fn from_legacy(content: &str) -> Result<LegacyImport, String> {
+    let doc: Value = serde_yaml::from_str(content)
+        .map_err(|e| format!("Invalid legacy mapping: {}", e))?;
+    let root = doc.as_object().ok_or("Legacy mapping must be a map")?;
+    let fields = root.get("fields")
+        .and_then(Value::as_object)
+        .ok_or("Legacy mapping has no `fields` section")?;
+
+    let mut unsupported = Vec::new();
+    let mut matrix = TransformationMatrix::new();
+    matrix.description = "Imported from a massdrop mapping".to_string();
+
+    for key in root.keys() {
+        if !["name", "csv", "fields", "split_roles"].contains(&key.as_str()) {
+            unsupported.push(format!("top-level `{}` is not supported", key));
+        }
+    }
+
+    if let Some(csv) = root.get("csv").and_then(Value::as_object) {
+        matrix.source_format = Some(SourceFormat {
+            delimiter: csv.get("separator").and_then(Value::as_str).and_then(|s| s.chars().next()),
+            encoding: csv.get("encoding").and_then(Value::as_str).map(str::to_string),
+            header_rows: 1,
+            skip_first: csv.get("skip_lines").and_then(Value::as_u64).unwrap_or(0) as usize,
+            skip_last: csv.get("skip_footer").and_then(Value::as_u64).unwrap_or(0) as usize,
+            fixed_width: None,
+        });
+        for key in csv.keys() {
+            if !["separator", "encoding", "skip_lines", "skip_footer"].contains(&key.as_str()) {
+                unsupported.push(format!("csv `{}` is not supported", key));
+            }
+        }
+    }
+
+    for (name, rule) in fields {
+        let field = camel_case(name);
+        match field_transform(&field, rule, &mut unsupported) {
+            Some(transform) => {
+                matrix.transforms.insert(field, transform);
+            }
+            None => unsupported.push(format!("field `{}` has no `from` nor `value`, skipped", name)),
+        }
+    }
+
+    if let Some(split) = root.get("split_roles") {
+        match (split.get("from").and_then(Value::as_str), split.get("translate").and_then(Value::as_object)) {
+            (Some(source), Some(translate)) => {
+                matrix.expand = Some(ExpandConfig::SplitRole {
+                    source: source.to_string(),
+                    separator: split.get("separator").and_then(Value::as_str).unwrap_or("+").to_string(),
+                    mapping: string_map(translate),
+                });
+            }
+            _ => unsupported.push("`split_roles` needs `from` and `translate`, skipped".to_string()),
+        }
+    }
+
+    unsupported.sort();
+    Ok(LegacyImport {
+        matrix,
+        name: root.get("name").and_then(Value::as_str).map(str::to_string),
+        unsupported,
+    })
+}
+
+/// Convert one field rule (None when it reads nothing)
+fn field_transform(field: &str, rule: &Value, unsupported: &mut Vec<String>) -> Option<FieldTransform> {
+    // Shorthand: `title: Titre`
+    if let Some(column) = rule.as_str() {
+        return Some(FieldTransform::from_source(column));
+    }
+    let rule = rule.as_object()?;
+
+    let mut transform = match (rule.get("from"), rule.get("value")) {
+        (Some(Value::String(column)), _) => FieldTransform::from_source(column),
+        (Some(Value::Array(columns)), _) => FieldTransform::from_sources(
+            columns.iter().filter_map(Value::as_str).map(str::to_string).collect(),
+            rule.get("join").and_then(Value::as_str).unwrap_or(" "),
+        ),
+        (_, Some(value)) => FieldTransform::from_constant(value.clone()),
+        _ => return None,
+    };
+    transform.default = rule.get("fallback").cloned();
+    transform.required = rule.get("required").and_then(Value::as_bool).unwrap_or(false);
+
+    for step in rule.get("clean").and_then(Value::as_array).into_iter().flatten() {
+        match step.as_str().and_then(clean_step) {
+            Some(op) => transform.operations.push(op),
+            None => unsupported.push(format!("field `{}`: cleaning step {} is not supported", field, step)),
+        }
+    }
+    if let Some(replace) = rule.get("regex_replace") {
+        match replace.get("pattern").and_then(Value::as_str) {
+            Some(pattern) => transform.operations.push(Operation::Replace {
+                pattern: pattern.to_string(),
+                value: replace.get("with").and_then(Value::as_str).unwrap_or("").to_string(),
+            }),
+            None => unsupported.push(format!("field `{}`: `regex_replace` needs a `pattern`", field)),
+        }
+    }
+    if let Some(range) = rule.get("substring").and_then(Value::as_array) {
+        transform.operations.push(Operation::Substring {
+            start: range.first().and_then(Value::as_u64).unwrap_or(0) as usize,
+            length: range.get(1).and_then(Value::as_u64).map(|l| l as usize),
+        });
+    }
+    if let Some(length) = rule.get("pad_left").and_then(Value::as_u64) {
+        transform.operations.push(Operation::PadStart {
+            length: length as usize,
+            char: rule.get("pad_with").and_then(Value::as_str).unwrap_or("0").to_string(),
+        });
+    }
+    if let Some(prefix) = rule.get("prefix").and_then(Value::as_str) {
+        transform.operations.push(Operation::EnsurePrefix { value: prefix.to_string() });
+    }
+    if let Some(translate) = rule.get("translate").and_then(Value::as_object) {
+        transform.operations.push(Operation::Map {
+            mapping: string_map(translate),
+            case_insensitive: rule.get("translate_ignore_case").and_then(Value::as_bool).unwrap_or(false),
+            default_unmapped: None,
+        });
+    }
+
+    for key in rule.keys().filter(|k| !FIELD_KEYS.contains(&k.as_str())) {
+        unsupported.push(format!("field `{}`: `{}` is not supported", field, key));
+    }
+    Some(transform)
+}
+
+/// Operation of a `clean` step
+fn clean_step(step: &str) -> Option<Operation> {
+    Some(match step {
+        "trim" => Operation::Trim,
+        "upper" => Operation::Uppercase,
+        "lower" => Operation::Lowercase,
+        "digits_only" => Operation::DigitsOnly,
+        "alphanumeric" => Operation::Alphanumeric,
+        "strip_punctuation" => Operation::Replace { pattern: r"[\s.\-/]".to_string(), value: String::new() },
+        "year" => Operation::ExtractYear,
+        "number" => Operation::ToNumber,
+        "decimal" => Operation::ToDecimal,
+        "bool" => Operation::ToBoolean { true_values: ["true", "1", "yes", "oui", "o", "y"].map(String::from).to_vec() },
+        _ => return None,
+    })
+}
+
+fn string_map(map: &serde_json::Map<String, Value>) -> HashMap<String, String> {
+    map.iter()
+        .map(|(k, v)| {
+            let value = match v {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            (k.clone(), value)
+        })
+        .collect()
+}
+
+/// `creator_ipi` → `creatorIpi` (names without `_` are kept as is)
+pub(crate) fn camel_case(name: &str) -> String {
+    let mut parts = name.split('_').filter(|p| !p.is_empty());
+    let mut out = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            out.extend(first.to_uppercase());
+            out.push_str(chars.as_str());
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAPPING: &str = r#"
+name: sacem-2019
+csv:
+  separator: ";"
+  skip_lines: 2
+fields:
+  iswc:
+    from: Code ISWC
+    clean: [trim, upper, strip_punctuation]
+    required: true
+  title: Titre
+  creator_ipi:
+    from: IPI
+    clean: [digits_only, soundex]
+    pad_left: 11
+  creator_role:
+    from: Role
+    translate: { CA: Composer, A: Author }
+    script: "role.toUpperCase()"
+  instrumental:
+    value: false
+split_roles:
+  from: Role
+  translate: { C: Composer, A: Author }
+"#;
+
+    #[test]
+    fn test_from_legacy_yaml() {
+        let import = from_legacy(MAPPING).unwrap();
+        let matrix = &import.matrix;
+
+        assert_eq!(import.name.as_deref(), Some("sacem-2019"));
+        assert_eq!(matrix.source_format.as_ref().unwrap().delimiter, Some(';'));
+        assert_eq!(matrix.source_format.as_ref().unwrap().skip_first, 2);
+        assert!(matrix.transforms["iswc"].required);
+        assert_eq!(matrix.transforms["iswc"].operations.len(), 3);
+        assert_eq!(matrix.transforms["title"].source.as_deref(), Some("Titre"));
+        assert_eq!(matrix.transforms["creatorIpi"].operations.len(), 2);
+        assert_eq!(matrix.transforms["instrumental"].constant, Some(Value::Bool(false)));
+        assert!(matches!(matrix.expand, Some(ExpandConfig::SplitRole { .. })));
+
+        assert_eq!(import.unsupported, vec![
+            "field `creatorIpi`: cleaning step \"soundex\" is not supported".to_string(),
+            "field `creatorRole`: `script` is not supported".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn test_from_legacy_converts_rows() {
+        let import = from_legacy(MAPPING).unwrap();
+        let row = serde_json::json!({"Code ISWC": " t-123.456.789-0 ", "Titre": "Song", "IPI": "IPI 123", "Role": "CA"});
+        let result = crate::transform::dsl::execute(&[row], &import.matrix);
+
+        assert_eq!(result.records[0]["iswc"], "T1234567890");
+        assert_eq!(result.records[0]["creatorIpi"], "00000000123");
+    }
+
+    #[test]
+    fn test_from_legacy_rejects_missing_fields() {
+        assert!(from_legacy("name: x").is_err());
+        assert_eq!(camel_case("creator_ipi"), "creatorIpi");
+        assert_eq!(camel_case("iswc"), "iswc");
+    }
+}
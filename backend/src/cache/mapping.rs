@@ -0,0 +1,99 @@
+//! Skeleton templates from a mapping spreadsheet
+//!
+//! Business analysts describe a catalog format as a two-column sheet (CSV or
+//! XLSX): the export column, then the MIDDS field it feeds.
+//!
+//! ```text
+//! Colonne;Champ MIDDS
+//! Code ISWC;iswc
+//! Titre;title
+//! IPI;creator_ipi
+//! ```
+//!
+//! Each line becomes a field transform with the standard operations of the
+//! field (trim, ISWC cleanup, IPI digits and `to_number`, role codes, year,
+//! shares), the same ones the offline generator uses. Field names may be
+//! camelCase or snake_case; unknown fields, duplicates and lines with an empty
+//! cell are left out and listed in the import report.
+
+use crate::ai::heuristic::field_transform;
+use crate::parser::parse_bytes_auto;
+use crate::transform::dsl::matrix::TransformationMatrix;
+
+use super::import::{camel_case, LegacyImport};
+
+/// Convert a mapping spreadsheet into a skeleton matrix
+pub fn from_mapping(bytes: &[u8]) -> Result<LegacyImport, String> {
+    let parsed = parse_bytes_auto(bytes, None).map_err(|e| format!("Invalid mapping file: {}", e))?;
+    let [column_header, field_header, ..] = &parsed.headers[..] else {
+        return Err("Mapping file needs two columns: CSV column, MIDDS field".to_string());
+    };
+    let properties = super::flat_schema()
+        .get("properties")
+        .and_then(|p| p.as_object())
+        .map(|p| p.keys().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let mut unsupported = Vec::new();
+    let mut matrix = TransformationMatrix::new();
+    matrix.description = "Imported from a mapping spreadsheet".to_string();
+
+    for (i, record) in parsed.records.iter().enumerate() {
+        let cell = |header: &str| record.get(header).and_then(|v| v.as_str()).unwrap_or("").trim().to_string();
+        let (column, target) = (cell(column_header), cell(field_header));
+        // Line 1 is the header
+        let line = i + 2;
+        if column.is_empty() || target.is_empty() {
+            if !column.is_empty() || !target.is_empty() {
+                unsupported.push(format!("line {}: needs both a column and a field, skipped", line));
+            }
+            continue;
+        }
+        let field = camel_case(&target);
+        if !properties.contains(&field) {
+            unsupported.push(format!("line {}: `{}` is not a MIDDS field, skipped", line, target));
+        } else if let Some(existing) = matrix.transforms.get(&field) {
+            unsupported.push(format!(
+                "line {}: `{}` is already read from `{}`, skipped",
+                line, field, existing.source.as_deref().unwrap_or_default(),
+            ));
+        } else {
+            matrix.transforms.insert(field.clone(), field_transform(&field, &column));
+        }
+    }
+
+    if matrix.transforms.is_empty() {
+        return Err("Mapping file maps no MIDDS field".to_string());
+    }
+    Ok(LegacyImport { matrix, name: None, unsupported })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::dsl::operations::Operation;
+
+    #[test]
+    fn test_from_mapping_csv() {
+        let csv = "Colonne;Champ MIDDS\nCode ISWC;iswc\nTitre;title\nIPI;creator_ipi\nGenre;genre\nNom;\nTitre original;title\n";
+        let import = from_mapping(csv.as_bytes()).unwrap();
+        let matrix = &import.matrix;
+
+        assert_eq!(matrix.transforms.len(), 3);
+        assert_eq!(matrix.transforms["title"].source.as_deref(), Some("Titre"));
+        assert!(matches!(matrix.transforms["iswc"].operations[0], Operation::Trim));
+        assert!(matrix.transforms["creatorIpi"].operations.iter().any(|op| matches!(op, Operation::ToNumber)));
+        assert_eq!(import.unsupported, vec![
+            "line 5: `genre` is not a MIDDS field, skipped".to_string(),
+            "line 6: needs both a column and a field, skipped".to_string(),
+            "line 7: `title` is already read from `Titre`, skipped".to_string(),
+        ]);
+
+        let row = serde_json::json!({"Code ISWC": " t-123.456.789-0 ", "Titre": "Song", "IPI": "IPI 00123"});
+        let result = crate::transform::dsl::execute(&[row], matrix);
+        assert_eq!(result.records[0]["iswc"], "T1234567890");
+        assert_eq!(result.records[0]["creatorIpi"], 123);
+
+        assert!(from_mapping(b"Colonne\nTitre\n").is_err());
+    }
+}
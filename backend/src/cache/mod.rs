@@ -1,17 +1,38 @@
 //! Matrix Registry - Store and reuse transformation matrices
 //!
 //! Saves matrices to disk and automatically matches them to CSV formats based on columns.
+//!
+//! Templates are never deleted: archiving moves them to `archived/` (audit
+//! records and jobs may still refer to them) and leaves them out of matching
+//! until they are restored.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::transform::dsl::matrix::TransformationMatrix;
+use crate::deterministic;
+use crate::parser::{normalize_header, FixedWidthColumn};
+use crate::models::CatalogPeriod;
+use crate::transform::dsl::matrix::{SourceFormat, TransformationMatrix};
+
+pub mod audit;
+pub mod import;
+pub mod mapping;
+pub mod snapshots;
+
+pub use audit::{audit_matrix, flat_schema, TemplateAudit};
+pub use import::{from_legacy, LegacyImport};
+pub use mapping::from_mapping;
+pub use snapshots::{catalog_key, CatalogSnapshot, SnapshotStore};
 
 /// Directory where matrices are stored (relative to current dir)
 const DEFAULT_REGISTRY_DIR: &str = ".massload/matrices";
 
+/// Subdirectory of archived matrices
+const ARCHIVE_DIR: &str = "archived";
+
 /// A stored matrix with metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoredMatrix {
@@ -31,6 +52,28 @@ pub struct StoredMatrix {
     pub success_rate: f64,
     /// Number of times used
     pub use_count: u32,
+    /// Edits required by the last `template audit` (non-empty: left out of automatic matching)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub incompatible: Vec<String>,
+    /// Uses per catalog accounting period (`2025-Q1` → 3)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub periods: BTreeMap<String, u32>,
+    /// Archive timestamp (set: stored in `archived/` and left out of matching)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<String>,
+}
+
+impl StoredMatrix {
+    pub fn is_archived(&self) -> bool {
+        self.archived_at.is_some()
+    }
+
+    /// Whether the template was used for a period matching `filter` (see [`CatalogPeriod::matches`])
+    pub fn used_for_period(&self, filter: &str) -> bool {
+        self.periods.keys()
+            .filter_map(|label| CatalogPeriod::parse(label).ok())
+            .any(|period| period.matches(filter))
+    }
 }
 
 /// Registry for managing transformation matrices
@@ -58,13 +101,18 @@ impl MatrixRegistry {
         registry
     }
 
-    /// Load all matrices from the registry directory
+    /// Load all matrices from the registry directory, archived ones included
     fn load_all(&mut self) {
-        if !self.registry_dir.exists() {
+        self.load_dir(&self.registry_dir.clone());
+        self.load_dir(&self.registry_dir.join(ARCHIVE_DIR));
+    }
+
+    fn load_dir(&mut self, dir: &Path) {
+        if !dir.exists() {
             return;
         }
 
-        let entries = match fs::read_dir(&self.registry_dir) {
+        let entries = match fs::read_dir(dir) {
             Ok(e) => e,
             Err(_) => return,
         };
@@ -81,12 +129,17 @@ impl MatrixRegistry {
         }
     }
 
-    /// Get all stored matrices
+    /// Get all active (not archived) matrices
     pub fn list(&self) -> Vec<&StoredMatrix> {
-        self.matrices.values().collect()
+        self.matrices.values().filter(|m| !m.is_archived()).collect()
+    }
+
+    /// Get all archived matrices
+    pub fn list_archived(&self) -> Vec<&StoredMatrix> {
+        self.matrices.values().filter(|m| m.is_archived()).collect()
     }
 
-    /// Get a matrix by ID
+    /// Get a matrix by ID (archived ones included)
     pub fn get(&self, id: &str) -> Option<&StoredMatrix> {
         self.matrices.get(id)
     }
@@ -97,6 +150,7 @@ impl MatrixRegistry {
         let mut compatible: Vec<_> = self
             .matrices
             .values()
+            .filter(|m| m.incompatible.is_empty() && !m.is_archived())
             .filter_map(|m| {
                 let score = self.calculate_compatibility(&m.csv_columns, csv_columns);
                 if score > 0.5 {
@@ -111,7 +165,9 @@ impl MatrixRegistry {
         compatible.sort_by(|a, b| {
             let score_a = a.1 * a.0.success_rate;
             let score_b = b.1 * b.0.success_rate;
-            score_b.partial_cmp(&score_a).unwrap_or(std::cmp::Ordering::Equal)
+            score_b.partial_cmp(&score_a)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.0.id.cmp(&b.0.id))
         });
 
         compatible
@@ -123,10 +179,12 @@ impl MatrixRegistry {
             return 0.0;
         }
 
-        let csv_lower: Vec<String> = csv.iter().map(|c| c.to_lowercase()).collect();
+        // Cosmetic variations (case, spaces, accents) do not count
+        let key = |col: &String| normalize_header(col).to_lowercase();
+        let csv_keys: Vec<String> = csv.iter().map(key).collect();
         let match_count = stored
             .iter()
-            .filter(|col| csv_lower.contains(&col.to_lowercase()))
+            .filter(|col| csv_keys.contains(&key(col)))
             .count();
 
         match_count as f64 / stored.len() as f64
@@ -148,14 +206,17 @@ impl MatrixRegistry {
             name: name.to_string(),
             matrix,
             csv_columns,
-            created_at: chrono::Utc::now().to_rfc3339(),
+            created_at: deterministic::now().to_rfc3339(),
             last_used: None,
             success_rate: 1.0,
             use_count: 0,
+            incompatible: Vec::new(),
+            periods: BTreeMap::new(),
+            archived_at: None,
         };
 
         // Save to disk
-        let path = self.registry_dir.join(format!("{}.json", id));
+        let path = self.path(&stored);
         let content = serde_json::to_string_pretty(&stored)?;
         fs::write(&path, content)?;
 
@@ -188,8 +249,47 @@ impl MatrixRegistry {
             .map_err(|e| format!("Failed to save: {}", e))
     }
 
-    /// Update statistics after using a matrix
-    pub fn update_stats(&mut self, id: &str, success: bool) {
+    /// Import a mapping from the legacy massdrop tooling (YAML or JSON).
+    ///
+    /// Returns the template ID and the constructs left out of the matrix.
+    pub fn import_legacy(&mut self, path: &Path, name: Option<&str>) -> Result<(String, Vec<String>), String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        let import = from_legacy(&content)?;
+
+        let matrix_name = name
+            .or(import.name.as_deref())
+            .or_else(|| path.file_stem().and_then(|s| s.to_str()))
+            .unwrap_or("imported")
+            .to_string();
+        let csv_columns = import.matrix.source_columns();
+
+        let id = self.save(import.matrix, &matrix_name, csv_columns)
+            .map_err(|e| format!("Failed to save: {}", e))?;
+        Ok((id, import.unsupported))
+    }
+
+    /// Import a two-column mapping spreadsheet (CSV column → MIDDS field) as a skeleton template.
+    ///
+    /// Returns the template ID and the lines left out of the matrix.
+    pub fn import_mapping(&mut self, path: &Path, name: Option<&str>) -> Result<(String, Vec<String>), String> {
+        let bytes = fs::read(path)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        let import = from_mapping(&bytes)?;
+
+        let matrix_name = name
+            .or_else(|| path.file_stem().and_then(|s| s.to_str()))
+            .unwrap_or("imported")
+            .to_string();
+        let csv_columns = import.matrix.source_columns();
+
+        let id = self.save(import.matrix, &matrix_name, csv_columns)
+            .map_err(|e| format!("Failed to save: {}", e))?;
+        Ok((id, import.unsupported))
+    }
+
+    /// Update statistics after using a matrix (for a catalog of `period`, when known)
+    pub fn update_stats(&mut self, id: &str, success: bool, period: Option<&CatalogPeriod>) {
         if let Some(matrix) = self.matrices.get_mut(id) {
             // Exponential moving average
             matrix.success_rate = if success {
@@ -197,26 +297,148 @@ impl MatrixRegistry {
             } else {
                 matrix.success_rate * 0.9
             };
-            matrix.last_used = Some(chrono::Utc::now().to_rfc3339());
+            matrix.last_used = Some(deterministic::now().to_rfc3339());
             matrix.use_count += 1;
+            if let Some(period) = period {
+                *matrix.periods.entry(period.to_string()).or_default() += 1;
+            }
 
             // Save updated stats
-            let path = self.registry_dir.join(format!("{}.json", id));
+            let path = registry_path(&self.registry_dir, matrix);
             if let Ok(content) = serde_json::to_string_pretty(matrix) {
                 let _ = fs::write(&path, content);
             }
         }
     }
 
-    /// Delete a matrix from the registry
-    pub fn delete(&mut self, id: &str) -> Result<(), String> {
-        if self.matrices.remove(id).is_some() {
-            let path = self.registry_dir.join(format!("{}.json", id));
-            fs::remove_file(&path).map_err(|e| format!("Failed to delete file: {}", e))?;
-            Ok(())
-        } else {
-            Err(format!("Matrix not found: {}", id))
+    /// Rename a template (its ID is kept)
+    pub fn rename(&mut self, id: &str, name: &str) -> Result<&StoredMatrix, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Template name cannot be empty".to_string());
+        }
+        let matrix = self.matrices.get_mut(id)
+            .ok_or_else(|| format!("Matrix not found: {}", id))?;
+        matrix.name = name.to_string();
+
+        let path = registry_path(&self.registry_dir, matrix);
+        let content = serde_json::to_string_pretty(matrix)
+            .map_err(|e| format!("Failed to serialize: {}", e))?;
+        fs::write(&path, content).map_err(|e| format!("Failed to save: {}", e))?;
+        Ok(matrix)
+    }
+
+    /// Store or remove the fixed-width layout of a template, applied when
+    /// the template reads a file (`template use`, matrix files)
+    pub fn set_layout(&mut self, id: &str, layout: Option<Vec<FixedWidthColumn>>) -> Result<&StoredMatrix, String> {
+        if layout.as_ref().is_some_and(Vec::is_empty) {
+            return Err("Empty fixed-width layout".to_string());
+        }
+        let matrix = self.matrices.get_mut(id)
+            .ok_or_else(|| format!("Matrix not found: {}", id))?;
+        match layout {
+            Some(columns) => matrix.matrix.source_format.get_or_insert_with(SourceFormat::default).fixed_width = Some(columns),
+            None => if let Some(ref mut format) = matrix.matrix.source_format {
+                format.fixed_width = None;
+            },
+        }
+
+        let path = registry_path(&self.registry_dir, matrix);
+        let content = serde_json::to_string_pretty(matrix)
+            .map_err(|e| format!("Failed to serialize: {}", e))?;
+        fs::write(&path, content).map_err(|e| format!("Failed to save: {}", e))?;
+        Ok(matrix)
+    }
+
+    /// Re-check every stored template file against `schema` (see [`audit_matrix`]).
+    ///
+    /// Templates with issues are marked [`StoredMatrix::incompatible`] on disk,
+    /// fixed ones are unmarked. Files that no longer load are reported as is.
+    pub fn audit(&mut self, schema: &Value) -> Vec<TemplateAudit> {
+        let mut paths: Vec<PathBuf> = fs::read_dir(&self.registry_dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|e| e == "json"))
+            .collect();
+        paths.sort();
+
+        let mut audits = Vec::new();
+        for path in paths {
+            let file_id = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+            let stored = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| serde_json::from_str::<StoredMatrix>(&content).map_err(|e| e.to_string()));
+            let mut stored = match stored {
+                Ok(stored) => stored,
+                Err(e) => {
+                    audits.push(TemplateAudit {
+                        id: file_id,
+                        name: None,
+                        issues: vec![format!("template no longer loads ({}): fix or remove the file {}", e, path.display())],
+                        unreadable: true,
+                    });
+                    continue;
+                }
+            };
+
+            let issues = audit_matrix(&stored.matrix, schema);
+            if issues != stored.incompatible {
+                stored.incompatible = issues.clone();
+                if let Ok(content) = serde_json::to_string_pretty(&stored) {
+                    let _ = fs::write(&path, content);
+                }
+            }
+            audits.push(TemplateAudit { id: stored.id.clone(), name: Some(stored.name.clone()), issues, unreadable: false });
+            self.matrices.insert(stored.id.clone(), stored);
+        }
+        audits
+    }
+
+    /// Archive a matrix: flagged, moved to `archived/` and left out of matching
+    pub fn archive(&mut self, id: &str) -> Result<&StoredMatrix, String> {
+        let matrix = self.matrices.get(id)
+            .ok_or_else(|| format!("Matrix not found: {}", id))?;
+        if matrix.is_archived() {
+            return Err(format!("Template already archived: {}", id));
+        }
+        self.set_archived(id, Some(deterministic::now().to_rfc3339()))
+    }
+
+    /// Restore an archived matrix into automatic matching
+    pub fn restore(&mut self, id: &str) -> Result<&StoredMatrix, String> {
+        let matrix = self.matrices.get(id)
+            .ok_or_else(|| format!("Matrix not found: {}", id))?;
+        if !matrix.is_archived() {
+            return Err(format!("Template is not archived: {}", id));
+        }
+        self.set_archived(id, None)
+    }
+
+    /// Flag or unflag a matrix and move its file accordingly
+    fn set_archived(&mut self, id: &str, archived_at: Option<String>) -> Result<&StoredMatrix, String> {
+        let matrix = self.matrices.get_mut(id)
+            .ok_or_else(|| format!("Matrix not found: {}", id))?;
+        let old_path = registry_path(&self.registry_dir, matrix);
+        matrix.archived_at = archived_at;
+        let new_path = registry_path(&self.registry_dir, matrix);
+
+        if let Some(dir) = new_path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
         }
+        let content = serde_json::to_string_pretty(matrix)
+            .map_err(|e| format!("Failed to serialize: {}", e))?;
+        fs::write(&new_path, content).map_err(|e| format!("Failed to save: {}", e))?;
+        if old_path.exists() {
+            fs::remove_file(&old_path).map_err(|e| format!("Failed to move file: {}", e))?;
+        }
+        Ok(matrix)
+    }
+
+    /// File of a stored matrix
+    fn path(&self, matrix: &StoredMatrix) -> PathBuf {
+        registry_path(&self.registry_dir, matrix)
     }
 
     /// Generate a unique ID from a name
@@ -231,11 +453,44 @@ impl MatrixRegistry {
             .collect::<Vec<_>>()
             .join("-");
 
-        let timestamp = chrono::Utc::now().timestamp_millis();
+        let timestamp = deterministic::now().timestamp_millis();
         format!("{}-{}", slug, timestamp)
     }
 }
 
+/// File of a matrix in `registry_dir` (`archived/` for archived ones)
+fn registry_path(registry_dir: &Path, matrix: &StoredMatrix) -> PathBuf {
+    let file = format!("{}.json", matrix.id);
+    match matrix.archived_at {
+        Some(_) => registry_dir.join(ARCHIVE_DIR).join(file),
+        None => registry_dir.join(file),
+    }
+}
+
+/// Human-friendly template name from a file or template name:
+/// `export_sacem_mars-1712345` → `Export sacem mars`
+pub fn suggest_name(raw: &str) -> String {
+    let stem = raw.rsplit_once('.')
+        .filter(|(_, ext)| ["csv", "json", "ndjson", "jsonl", "txt"].contains(&ext.to_lowercase().as_str()))
+        .map_or(raw, |(stem, _)| stem);
+
+    // Drop trailing numeric suffixes (timestamps, export counters)
+    let mut words: Vec<&str> = stem
+        .split(|c: char| c == '_' || c == '-' || c == '.' || c.is_whitespace())
+        .filter(|w| !w.is_empty())
+        .collect();
+    while words.len() > 1 && words.last().is_some_and(|w| w.len() >= 6 && w.chars().all(|c| c.is_ascii_digit())) {
+        words.pop();
+    }
+
+    let name = words.join(" ");
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => "Template".to_string(),
+    }
+}
+
 impl Default for MatrixRegistry {
     fn default() -> Self {
         Self::new()
@@ -267,6 +522,116 @@ mod tests {
         
         let score = registry.calculate_compatibility(&stored, &csv);
         assert!((score - 1.0).abs() < 0.01); // 100% match (case insensitive)
+
+        let stored = vec!["Rôle ".to_string(), "Titre\u{a0}original".to_string()];
+        let csv = vec!["Role".to_string(), "Titre original".to_string()];
+        assert!((registry.calculate_compatibility(&stored, &csv) - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_rename_keeps_id() {
+        let dir = tempdir().unwrap();
+        let mut registry = MatrixRegistry::with_dir(dir.path());
+        let id = registry.save(crate::transform::dsl::example_matrix(), "export_sacem-1712345", vec![]).unwrap();
+
+        assert_eq!(registry.rename(&id, "  SACEM mensuel ").unwrap().name, "SACEM mensuel");
+        assert!(registry.rename(&id, " ").is_err());
+        assert!(registry.rename("unknown", "x").is_err());
+
+        // Persisted on disk
+        let reloaded = MatrixRegistry::with_dir(dir.path());
+        assert_eq!(reloaded.get(&id).unwrap().name, "SACEM mensuel");
+    }
+
+    #[test]
+    fn test_layout_stored_with_template() {
+        let dir = tempdir().unwrap();
+        let mut registry = MatrixRegistry::with_dir(dir.path());
+        let id = registry.save(crate::transform::dsl::example_matrix(), "sacem-1998", vec![]).unwrap();
+
+        let layout = FixedWidthColumn::parse_layout("iswc:0:11,titre:11:12").unwrap();
+        assert_eq!(FixedWidthColumn::layout_spec(&layout), "iswc:0:11,titre:11:12");
+        registry.set_layout(&id, Some(layout)).unwrap();
+        assert!(registry.set_layout(&id, Some(vec![])).is_err());
+
+        // Reloaded from disk, the template reads the headerless file itself
+        let reloaded = MatrixRegistry::with_dir(dir.path());
+        let format = reloaded.get(&id).unwrap().matrix.source_format.clone().unwrap();
+        let text = "T1234567890Mon titre\nT0987654321Autre\n";
+        let parsed = crate::parser::parse_bytes_with_options(text.as_bytes(), &format.parse_options()).unwrap();
+        assert_eq!(parsed.records.len(), 2);
+        assert_eq!(parsed.records[1]["titre"], "Autre");
+
+        registry.set_layout(&id, None).unwrap();
+        assert!(registry.get(&id).unwrap().matrix.source_format.as_ref().unwrap().fixed_width.is_none());
+    }
+
+    #[test]
+    fn test_archive_and_restore() {
+        let dir = tempdir().unwrap();
+        let mut registry = MatrixRegistry::with_dir(dir.path());
+        let columns = vec!["Titre".to_string()];
+        let id = registry.save(crate::transform::dsl::example_matrix(), "sacem", columns.clone()).unwrap();
+
+        assert!(registry.archive(&id).unwrap().is_archived());
+        assert!(registry.archive(&id).is_err());
+        assert!(dir.path().join("archived").join(format!("{}.json", id)).exists());
+        assert!(!dir.path().join(format!("{}.json", id)).exists());
+
+        // Kept (audit records may refer to it) but out of listing and matching
+        let mut reloaded = MatrixRegistry::with_dir(dir.path());
+        assert!(reloaded.get(&id).unwrap().is_archived());
+        assert!(reloaded.list().is_empty());
+        assert_eq!(reloaded.list_archived().len(), 1);
+        assert!(reloaded.find_compatible(&columns).is_empty());
+
+        assert!(!reloaded.restore(&id).unwrap().is_archived());
+        assert!(reloaded.restore(&id).is_err());
+        assert_eq!(MatrixRegistry::with_dir(dir.path()).find_compatible(&columns).len(), 1);
     }
-}
 
+    #[test]
+    fn test_stats_per_period() {
+        let dir = tempdir().unwrap();
+        let mut registry = MatrixRegistry::with_dir(dir.path());
+        let id = registry.save(crate::transform::dsl::example_matrix(), "sacem", vec![]).unwrap();
+        let q1 = CatalogPeriod::parse("2025-Q1").unwrap();
+        registry.update_stats(&id, true, Some(&q1));
+        registry.update_stats(&id, true, Some(&q1));
+        registry.update_stats(&id, false, None);
+
+        let reloaded = MatrixRegistry::with_dir(dir.path());
+        let stored = reloaded.get(&id).unwrap();
+        assert_eq!(stored.use_count, 3);
+        assert_eq!(stored.periods.get("2025-Q1"), Some(&2));
+        assert!(stored.used_for_period("2025") && !stored.used_for_period("2025-Q2"));
+    }
+
+    #[test]
+    fn test_suggest_name() {
+        assert_eq!(suggest_name("export_sacem_mars-1712345"), "Export sacem mars");
+        assert_eq!(suggest_name("catalogue-2024.csv"), "Catalogue 2024");
+        assert_eq!(suggest_name("20240301"), "20240301");
+        assert_eq!(suggest_name(""), "Template");
+    }
+
+    #[test]
+    fn test_audit_marks_incompatible_templates() {
+        let dir = tempdir().unwrap();
+        let mut registry = MatrixRegistry::with_dir(dir.path());
+        let id = registry.save(crate::transform::dsl::example_matrix(), "sacem", vec!["Titre".to_string()]).unwrap();
+        fs::write(dir.path().join("old.json"), r#"{"id": "old", "matrix": {"transforms": {}}}"#).unwrap();
+
+        let schema = serde_json::json!({"required": ["iswc", "workType"], "properties": {}});
+        let audits = registry.audit(&schema);
+        assert!(audits.iter().any(|a| a.id == "old" && a.unreadable));
+        let audit = audits.iter().find(|a| a.id == id).unwrap();
+        assert!(audit.issues.contains(&"required field `workType` has no transform: add one".to_string()));
+
+        // Marked on disk and left out of matching until a new audit passes
+        let reloaded = MatrixRegistry::with_dir(dir.path());
+        assert!(reloaded.find_compatible(&["Titre".to_string()]).is_empty());
+        assert!(registry.audit(&flat_schema()).iter().find(|a| a.id == id).unwrap().is_compatible());
+        assert_eq!(MatrixRegistry::with_dir(dir.path()).find_compatible(&["Titre".to_string()]).len(), 1);
+    }
+}
@@ -0,0 +1,163 @@
+//! Catalog snapshots - Keep the grouped works of previous uploads
+//!
+//! Used to diff a re-sent catalog against its last transformed version.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::models::CatalogPeriod;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory where snapshots are stored (relative to current dir)
+const DEFAULT_SNAPSHOT_DIR: &str = ".massload/snapshots";
+
+/// Snapshots kept per catalog (oldest are pruned)
+pub const MAX_SNAPSHOTS_PER_CATALOG: usize = 10;
+
+/// Grouped works of one transformed version of a catalog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogSnapshot {
+    /// Job that produced this version
+    pub job_id: String,
+    /// Catalog key (see [`catalog_key`])
+    pub catalog: String,
+    /// Creation timestamp (RFC3339)
+    pub created_at: String,
+    /// SHA-256 of the source file
+    pub sha256: Option<String>,
+    /// Accounting period the catalog was delivered for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub period: Option<CatalogPeriod>,
+    /// Grouped works
+    pub works: Vec<Value>,
+}
+
+/// Store of catalog snapshots, one directory per catalog
+pub struct SnapshotStore {
+    dir: PathBuf,
+}
+
+impl SnapshotStore {
+    /// Create a store in the default directory
+    pub fn new() -> Self {
+        Self::with_dir(DEFAULT_SNAPSHOT_DIR)
+    }
+
+    /// Create a store with a custom directory
+    pub fn with_dir(dir: impl AsRef<Path>) -> Self {
+        Self { dir: PathBuf::from(dir.as_ref()) }
+    }
+
+    /// Save a snapshot and prune old ones of the same catalog
+    pub fn save(&self, snapshot: &CatalogSnapshot) -> Result<(), std::io::Error> {
+        let dir = self.dir.join(&snapshot.catalog);
+        fs::create_dir_all(&dir)?;
+
+        let path = dir.join(format!("{}.json", snapshot.job_id));
+        fs::write(&path, serde_json::to_string(snapshot)?)?;
+
+        let mut snapshots = self.load_catalog(&snapshot.catalog);
+        if snapshots.len() > MAX_SNAPSHOTS_PER_CATALOG {
+            snapshots.truncate(snapshots.len() - MAX_SNAPSHOTS_PER_CATALOG);
+            for old in snapshots {
+                let _ = fs::remove_file(dir.join(format!("{}.json", old.job_id)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Most recent snapshot of a catalog.
+    ///
+    /// Snapshots of the same file (`exclude_sha256`) are skipped so that
+    /// re-uploading an unchanged file still diffs against the previous version.
+    pub fn latest(&self, catalog: &str, exclude_sha256: Option<&str>) -> Option<CatalogSnapshot> {
+        self.load_catalog(catalog)
+            .into_iter()
+            .rev()
+            .find(|s| exclude_sha256.is_none() || s.sha256.as_deref() != exclude_sha256)
+    }
+
+    /// All snapshots of a catalog, oldest first
+    fn load_catalog(&self, catalog: &str) -> Vec<CatalogSnapshot> {
+        let entries = match fs::read_dir(self.dir.join(catalog)) {
+            Ok(e) => e,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut snapshots: Vec<CatalogSnapshot> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|e| e == "json"))
+            .filter_map(|path| fs::read_to_string(path).ok())
+            .filter_map(|content| serde_json::from_str(&content).ok())
+            .collect();
+        snapshots.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        snapshots
+    }
+}
+
+impl Default for SnapshotStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Catalog key from a name or file name (`Catalogue SACEM 2025.csv` → `catalogue-sacem-2025`)
+pub fn catalog_key(name: &str) -> String {
+    let stem = Path::new(name.trim())
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(name);
+
+    stem.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn snapshot(job_id: &str, created_at: &str, sha256: &str) -> CatalogSnapshot {
+        CatalogSnapshot {
+            job_id: job_id.to_string(),
+            catalog: "sacem".to_string(),
+            created_at: created_at.to_string(),
+            sha256: Some(sha256.to_string()),
+            period: None,
+            works: vec![],
+        }
+    }
+
+    #[test]
+    fn test_catalog_key() {
+        assert_eq!(catalog_key("Catalogue SACEM 2025.csv"), "catalogue-sacem-2025");
+        assert_eq!(catalog_key("  export_v2 "), "export-v2");
+    }
+
+    #[test]
+    fn test_latest_skips_same_file_and_prunes() {
+        let dir = tempdir().unwrap();
+        let store = SnapshotStore::with_dir(dir.path());
+
+        store.save(&snapshot("a", "2025-01-01T00:00:00Z", "aaa")).unwrap();
+        store.save(&snapshot("b", "2025-02-01T00:00:00Z", "bbb")).unwrap();
+
+        assert_eq!(store.latest("sacem", None).unwrap().job_id, "b");
+        assert_eq!(store.latest("sacem", Some("bbb")).unwrap().job_id, "a");
+        assert!(store.latest("other", None).is_none());
+
+        for i in 0..MAX_SNAPSHOTS_PER_CATALOG {
+            store.save(&snapshot(&format!("c{i}"), &format!("2025-03-{:02}T00:00:00Z", i + 1), "ccc")).unwrap();
+        }
+        assert_eq!(store.load_catalog("sacem").len(), MAX_SNAPSHOTS_PER_CATALOG);
+        assert!(store.latest("sacem", Some("ccc")).is_none());
+    }
+}
@@ -0,0 +1,83 @@
+//! Configuration file (`massload.yaml`)
+//!
+//! Optional settings that do not fit on the command line. Read from the
+//! `--config` path, else `MASSLOAD_CONFIG`, else `massload.yaml` in the
+//! working directory when it exists.
+//!
+//! ```yaml
+//! notifications:
+//!   on: always            # or: failure
+//!   webhook:
+//!     url: https://hooks.example.com/massload
+//!   smtp:
+//!     host: localhost
+//!     port: 25
+//!     from: massload@example.com
+//!     to: [ops@example.com]
+//! events:                 # job lifecycle events of `massload serve`
+//!   webhook:
+//!     url: https://orchestrator.example.com/massload-events
+//! sinks:                  # output sinks, chosen per job (see `crate::sinks`)
+//!   erp:
+//!     type: http
+//!     url: https://erp.example.com/midds
+//!     auth_env: ERP_TOKEN
+//! role_inference:         # catalogs without a role column (see `crate::transform::role_inference`)
+//!   enabled: true
+//!   default_role: Composer
+//! ```
+
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use crate::notify::{EventsConfig, NotifyConfig};
+use crate::sinks::SinksConfig;
+use crate::transform::role_inference::RoleInference;
+
+/// Default configuration file, looked up in the working directory
+pub const DEFAULT_CONFIG_FILE: &str = "massload.yaml";
+
+/// Settings read from the configuration file
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// End-of-run notifications (webhook, email)
+    #[serde(default)]
+    pub notifications: NotifyConfig,
+    /// Job lifecycle events pushed by the HTTP server
+    #[serde(default)]
+    pub events: EventsConfig,
+    /// Named output sinks receiving the grouped works of a job
+    #[serde(default)]
+    pub sinks: SinksConfig,
+    /// Roles of catalogs without a role column
+    #[serde(default)]
+    pub role_inference: RoleInference,
+}
+
+impl Config {
+    /// Load the configuration.
+    ///
+    /// An explicit path (argument or `MASSLOAD_CONFIG`) must exist; the
+    /// default file is optional and an empty configuration is used without it.
+    pub fn load(path: Option<&Path>) -> Result<Self, String> {
+        let explicit = path.map(Path::to_path_buf)
+            .or_else(|| std::env::var_os("MASSLOAD_CONFIG").map(PathBuf::from));
+        let path = match explicit {
+            Some(path) => path,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => PathBuf::from(DEFAULT_CONFIG_FILE),
+            None => return Ok(Self::default()),
+        };
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Cannot read config {}: {}", path.display(), e))?;
+        Self::from_yaml(&content).map_err(|e| format!("Invalid config {}: {}", path.display(), e))
+    }
+
+    /// Parse a configuration from YAML (an empty document is an empty configuration)
+    pub fn from_yaml(content: &str) -> Result<Self, String> {
+        if content.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        serde_yaml::from_str(content).map_err(|e| e.to_string())
+    }
+}
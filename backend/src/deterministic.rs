@@ -0,0 +1,71 @@
+//! Reproducible runs for golden-file tests (`MASSLOAD_DETERMINISTIC=1`).
+//!
+//! With the variable set, job ids come from a sequence seeded by its value
+//! and timestamps (registry entries, template ids, catalog snapshots) are
+//! pinned, so the same input always gives the same `UploadResponse`.
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicU64, Ordering};
+use uuid::Uuid;
+
+/// Environment variable enabling the mode; its value seeds the ids (`1`, `42`...)
+pub const DETERMINISTIC_ENV: &str = "MASSLOAD_DETERMINISTIC";
+
+/// Instant returned by [`now`] in deterministic mode (2024-01-01T00:00:00Z)
+const FIXED_TIMESTAMP: i64 = 1_704_067_200;
+
+/// Read once: the mode can't change during a run
+static SEED: Lazy<Option<u64>> = Lazy::new(|| parse_seed(std::env::var(DETERMINISTIC_ENV).ok().as_deref()));
+
+static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// Seed from the variable value: unset, empty, `0` or `false` disable the mode,
+/// any other non-numeric value seeds with 1
+fn parse_seed(value: Option<&str>) -> Option<u64> {
+    match value.map(str::trim) {
+        None | Some("" | "0" | "false") => None,
+        Some(value) => Some(value.parse().unwrap_or(1)),
+    }
+}
+
+/// Whether `MASSLOAD_DETERMINISTIC` is set
+pub fn is_enabled() -> bool {
+    SEED.is_some()
+}
+
+/// A random v4 UUID, or the next id of the seeded sequence
+pub fn new_uuid() -> Uuid {
+    match *SEED {
+        Some(seed) => Uuid::from_u64_pair(seed, SEQUENCE.fetch_add(1, Ordering::Relaxed) + 1),
+        None => Uuid::new_v4(),
+    }
+}
+
+/// Current time, or a fixed instant in deterministic mode
+pub fn now() -> DateTime<Utc> {
+    if is_enabled() {
+        DateTime::from_timestamp(FIXED_TIMESTAMP, 0).unwrap_or_default()
+    } else {
+        Utc::now()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_seed() {
+        assert_eq!(parse_seed(None), None);
+        assert_eq!(parse_seed(Some("0")), None);
+        assert_eq!(parse_seed(Some(" ")), None);
+        assert_eq!(parse_seed(Some("1")), Some(1));
+        assert_eq!(parse_seed(Some("42")), Some(42));
+        assert_eq!(parse_seed(Some("yes")), Some(1));
+        assert_eq!(
+            Uuid::from_u64_pair(1, 1).to_string(),
+            "00000000-0000-0001-0000-000000000001"
+        );
+    }
+}
@@ -126,8 +126,12 @@ pub enum RegistryError {
 // =============================================================================
 
 /// Errors during MIDDS validation.
-#[derive(Debug, Error)]
+#[derive(Debug, Clone, Error)]
 pub enum ValidationError {
+    /// An embedded schema could not be parsed or compiled.
+    #[error("Invalid schema '{schema}': {message}")]
+    InvalidSchema { schema: String, message: String },
+
     /// Schema validation failed.
     #[error("Validation failed: {errors:?}")]
     SchemaError { errors: Vec<String> },
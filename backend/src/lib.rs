@@ -34,6 +34,11 @@
 //! - [`cache`] - Template caching
 //! - [`ai`] - AI-powered matrix generation
 //! - [`api`] - HTTP API server
+//! - [`progress`] - Per-stage progress reporting (CLI progress bars)
+//! - [`config`] - Configuration file (`massload.yaml`)
+//! - [`notify`] - End-of-run notifications (webhook, email)
+//! - [`sinks`] - Output sinks (HTTP POST, S3-compatible bucket) chosen per job
+//! - [`deterministic`] - Reproducible ids and timestamps for golden-file tests
 
 // Core modules
 pub mod error;
@@ -57,6 +62,17 @@ pub mod ai;
 // HTTP API
 pub mod api;
 
+// Progress reporting
+pub mod progress;
+
+// Configuration file, end-of-run notifications and output sinks
+pub mod config;
+pub mod notify;
+pub mod sinks;
+
+// Reproducible runs (MASSLOAD_DETERMINISTIC)
+pub mod deterministic;
+
 // =============================================================================
 // Re-exports - Error types
 // =============================================================================
@@ -77,10 +93,15 @@ pub use error::{
 
 pub use models::{
     PartyId,
+    CatalogPeriod,
     CreatorRole,
     Creator,
+    CreatorShares,
     MusicalWorkType,
     GroupedWork,
+    ClassicalInfo,
+    WorkTitle,
+    title_text,
 };
 
 // =============================================================================
@@ -94,13 +115,23 @@ pub use validation::{
     validate_musical_work_grouped,
     is_valid_musical_work_flat,
     validate_musical_work_flat,
+    ValidationContext,
+    ValidationWarning,
+    YearBounds,
+    DEFAULT_MIN_YEAR,
 };
 
 // =============================================================================
 // Re-exports - Grouper
 // =============================================================================
 
-pub use transform::flat_to_grouped;
+pub use transform::{flat_to_grouped, to_values};
+pub use transform::{flat_csv, grouped_to_flat, write_flat_csv};
+pub use transform::{diff_works, CatalogDiff, WorkChange};
+pub use transform::{diagnose_failure, ErrorCause, FailureReport, Suggestion};
+pub use transform::{allocation_csv, works_lacking_iswc, AllocationCreator, AllocationRequest};
+pub use transform::{role_report, ChainNetwork, RoleReport, UnsupportedRole, WorkRef};
+pub use transform::{InferredRole, RoleInference, RolePlan};
 
 // =============================================================================
 // Re-exports - CSV Parsing
@@ -112,11 +143,46 @@ pub use parser::{
     parse_csv_file, 
     parse_csv_file_auto,
     parse_bytes_auto,
+    parse_bytes_with_options,
+    merge_header_rows,
+    normalize_header,
+    parse_csv_stream,
+    checksum_sha256,
+    detect_bom,
+    check_delimiter,
+    check_encoding,
     detect_encoding,
+    detect_encoding_with_confidence,
     detect_delimiter,
+    detect_fixed_width,
+    parse_fixed_width,
+    parse_json_records,
+    parse_json_auto,
+    parse_json_with_encoding,
+    parse_jsonl_auto,
+    parse_xlsx,
+    unpack,
+    Unpacked,
+    Compression,
+    parse_cwr,
+    profile_columns,
+    sample_rows,
+    sanitize_text,
     decode_content,
     CsvError,
+    CsvStream,
+    EncodingGuess,
+    FixedWidthColumn,
+    InputFormat,
+    ColumnProfile,
+    PatternCount,
+    ParseDiagnostics,
+    RaggedRow,
+    RaggedRows,
+    SampleOptions,
+    ParseOptions,
     ParseResult,
+    ParseReport,
 };
 
 // =============================================================================
@@ -127,11 +193,14 @@ pub use transform::dsl::{
     TransformationMatrix,
     FieldTransform,
     Operation,
+    DateComponent,
+    CaseStyle,
     execute,
     execute_hashmap,
     TransformResult,
     TransformError,
     SkippedRow,
+    FieldStats,
     operations_description,
     example_matrix,
 };
@@ -140,13 +209,13 @@ pub use transform::dsl::{
 // Re-exports - AI Client
 // =============================================================================
 
-pub use ai::{AiClient, AiError, generate_matrix};
+pub use ai::{AiClient, AiCostEstimate, AiError, Engine, EngineReport, generate_matrix};
 
 // =============================================================================
 // Re-exports - Registry (Cache)
 // =============================================================================
 
-pub use cache::{MatrixRegistry, StoredMatrix};
+pub use cache::{audit_matrix, catalog_key, flat_schema, from_legacy, suggest_name, CatalogSnapshot, LegacyImport, MatrixRegistry, SnapshotStore, StoredMatrix, TemplateAudit};
 
 // =============================================================================
 // Re-exports - Pipeline
@@ -156,12 +225,21 @@ pub use transform::pipeline::{
     transform_csv,
     transform_bytes,
     transform_records,
+    stream_csv,
     transform_with_matrix,
+    evaluate_templates,
+    column_coverage,
+    is_midds_flat,
     TransformOptions,
     PipelineResult,
     PipelineError,
     CsvInfo,
     TransformWithMatrixResult,
+    TemplateEvaluation,
+    ColumnCoverage,
+    MatrixScore,
+    DEFAULT_EVALUATION_ROWS,
+    DEFAULT_AI_ATTEMPTS,
 };
 
 // =============================================================================
@@ -172,12 +250,18 @@ pub use api::types::{
     UploadResponse,
     ResponseMetadata,
     CsvMetadata,
+    PreflightResponse,
     ValidationStats,
     ValidationError,
+    ValidateResponse,
+    ColumnsResponse,
+    NewTemplate,
+    TemplateSummary,
     error_response,
 };
 
 // Server
 pub mod server {
+    pub use crate::api::demo::DemoLimits;
     pub use crate::api::server::start_server;
 }
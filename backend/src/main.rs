@@ -4,6 +4,7 @@
 //!
 //! ```bash
 //! massload serve                    # Start HTTP server (port 3000)
+//! massload serve --demo             # Public demo instance (capped, no AI, nothing saved)
 //! massload transform input.csv     # Transform CSV to MIDDS JSON
 //! massload template list           # Manage transformation templates
 //! ```
@@ -14,19 +15,31 @@
 //! massload parse input.csv         # Just parse CSV to JSON
 //! massload validate input.json     # Validate JSON against schema
 //! massload group input.json        # Group flat records by ISWC
+//! massload ungroup works.json      # Expand grouped works back to flat CSV rows
 //! massload operations              # Show available DSL operations
 //! massload example-matrix          # Show example transformation matrix
 //! ```
 
 use clap::{Parser, Subcommand};
+use indicatif::{ProgressBar, ProgressStyle};
+use massload::api::demo::DemoLimits;
+use massload::config::Config;
+use massload::deterministic;
+use massload::notify::{notify, NotifyConfig, RunSummary, WebhookConfig};
+use massload::sinks::{deliver_all, select_sinks, SinkConfig, SinksConfig};
+use massload::api::logs::LogStage;
+use massload::progress::{set_progress_observer, ProgressObserver};
 use massload::{
-    flat_to_grouped, validate_musical_work_flat,
-    parse_csv_file_auto, MatrixRegistry,
-    transform_csv, transform_with_matrix, TransformOptions,
+    diagnose_failure, flat_csv, flat_schema, ChainNetwork, RoleInference, flat_to_grouped, grouped_to_flat, validate_musical_work_flat, GroupedWork,
+    parse_bytes_with_options, CatalogPeriod, check_delimiter, check_encoding, parse_cwr, parse_json_with_encoding, parse_xlsx, suggest_name, unpack, ParseOptions, AiCostEstimate, FixedWidthColumn, InputFormat, MatrixRegistry, RaggedRows, PipelineError, PipelineResult,
+    transform_csv, transform_with_matrix, write_flat_csv, TransformOptions, ValidationContext, YearBounds, DEFAULT_MIN_YEAR, DEFAULT_AI_ATTEMPTS,
 };
 use serde_json::Value;
 use std::fs;
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 #[derive(Parser)]
 #[command(name = "massload")]
@@ -34,31 +47,58 @@ use std::path::{Path, PathBuf};
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Configuration file (default: massload.yaml in the working directory, if any)
+    #[arg(long, global = true, value_name = "FILE")]
+    config: Option<PathBuf>,
 }
 
+// Parsed once at startup, the size of the transform variant does not matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 enum Commands {
     /// Parse a CSV file and output JSON
     Parse {
-        /// Input CSV file
+        /// Input file (CSV, XLSX or CWR)
         input: PathBuf,
 
-        /// CSV delimiter (auto-detect if not specified)
-        #[arg(short, long)]
+        /// CSV delimiter, one character or "tab" (auto-detect if not specified)
+        #[arg(short, long, value_parser = parse_delimiter)]
         delimiter: Option<char>,
 
         /// Output file (default: stdout)
         #[arg(short, long)]
         output: Option<PathBuf>,
+
+        /// Lines to skip before the header row (export banners)
+        #[arg(long, alias = "skip-rows", conflicts_with = "header_row")]
+        skip_first: Option<usize>,
+
+        /// Line number (from 1) of the header row; the lines above it are skipped
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        header_row: Option<u64>,
+
+        /// Header lines merged into composite column names (e.g. "Rights / Share")
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        header_rows: Option<u64>,
+
+        /// Lines to drop at the end of the file (footer detection runs anyway)
+        #[arg(long, alias = "skip-footer-rows")]
+        skip_last: Option<usize>,
+
+        /// Rows with more or fewer cells than the header: pad (silently), warn (list them
+        /// with their line numbers) or reject (leave them out)
+        #[arg(long, default_value = "pad")]
+        ragged_rows: RaggedRows,
     },
 
     /// Full transformation pipeline: CSV → AI Matrix → MIDDS JSON
     Transform {
-        /// Input CSV file
+        /// Input file (CSV, XLSX or CWR)
         input: PathBuf,
 
-        /// CSV delimiter (auto-detect if not specified)
-        #[arg(short, long)]
+        /// CSV delimiter, one character or "tab" (auto-detect if not specified)
+        #[arg(short, long, value_parser = parse_delimiter)]
         delimiter: Option<char>,
 
         /// Use existing matrix file instead of AI
@@ -77,13 +117,119 @@ enum Commands {
         #[arg(short, long)]
         grouped: Option<PathBuf>,
 
-        /// Number of preview rows for AI (default: 10)
+        /// Also write the flat records as CSV (flat schema columns), to review them before grouping
+        #[arg(long, value_name = "FILE")]
+        flat_csv: Option<PathBuf>,
+
+        /// Number of sampled preview rows for AI (default: 10, max: 50)
         #[arg(long, default_value = "10")]
         preview_rows: usize,
 
         /// Skip validation
         #[arg(long)]
         no_validate: bool,
+
+        /// Columns to strip before AI analysis (comma-separated, e.g. "Email,Adresse")
+        #[arg(long, value_delimiter = ',')]
+        exclude_columns: Vec<String>,
+
+        /// Send only column names and value patterns to the AI (no raw values)
+        #[arg(long)]
+        structure_only: bool,
+
+        /// Never call the AI: guess the matrix from column names
+        #[arg(long)]
+        no_ai: bool,
+
+        /// Ask before calling the AI when its estimated cost exceeds --ai-cost-threshold
+        #[arg(long)]
+        confirm_ai: bool,
+
+        /// Cost (USD) above which --confirm-ai asks for confirmation
+        #[arg(long, value_name = "USD", default_value = "0.10", requires = "confirm_ai")]
+        ai_cost_threshold: f64,
+
+        /// Lines to skip before the header row (export banners)
+        #[arg(long, alias = "skip-rows", conflicts_with = "header_row")]
+        skip_first: Option<usize>,
+
+        /// Line number (from 1) of the header row; the lines above it are skipped
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        header_row: Option<u64>,
+
+        /// Header lines merged into composite column names (e.g. "Rights / Share")
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        header_rows: Option<u64>,
+
+        /// Lines to drop at the end of the file (footer detection runs anyway)
+        #[arg(long, alias = "skip-footer-rows")]
+        skip_last: Option<usize>,
+
+        /// Rows with more or fewer cells than the header: pad (silently), warn (list them
+        /// with their line numbers) or reject (leave them out)
+        #[arg(long, default_value = "pad")]
+        ragged_rows: RaggedRows,
+
+        /// Force the source encoding (e.g. windows-1252, iso-8859-1) instead of detecting it
+        #[arg(long, value_parser = parse_encoding)]
+        encoding: Option<String>,
+
+        /// Input format: auto, csv, json, xlsx or cwr (JSON array or NDJSON; flat MIDDS records and CWR skip the matrix)
+        #[arg(long, default_value = "auto")]
+        input_format: InputFormat,
+
+        /// Read the input as fixed-width text: "name:start:len,..." (0-based offsets, no header row)
+        #[arg(long, value_name = "LAYOUT")]
+        fixed_width: Option<String>,
+
+        /// Minimum share of valid records for a cached template to be used (0.0 to 1.0)
+        #[arg(long, default_value = "0.8")]
+        min_valid_ratio: f64,
+
+        /// AI generations tried when no cached template fits, each on another preview sample
+        #[arg(long, default_value_t = DEFAULT_AI_ATTEMPTS)]
+        ai_attempts: usize,
+
+        /// Stop the AI attempts once a matrix reaches this valid ratio (default: --min-valid-ratio)
+        #[arg(long)]
+        ai_target_ratio: Option<f64>,
+
+        /// Earliest plausible creation year (older ones are reported as warnings)
+        #[arg(long, default_value_t = DEFAULT_MIN_YEAR)]
+        min_year: i64,
+
+        /// Latest plausible creation year (default: current year)
+        #[arg(long)]
+        max_year: Option<i64>,
+
+        /// Network whose runtime roles the creators are checked against: devnet, testnet or mainnet
+        #[arg(long, default_value = "devnet")]
+        network: ChainNetwork,
+
+        /// Infer creator roles when the catalog has no role column (settings: `role_inference` in the config)
+        #[arg(long)]
+        infer_roles: bool,
+
+        /// Accounting period of the catalog (2025, 2025-Q1, 2025-03 or 2025-W07),
+        /// recorded in template stats and notifications
+        #[arg(long)]
+        period: Option<CatalogPeriod>,
+
+        /// Print per-row details (skipped rows, transform and validation errors)
+        #[arg(short, long)]
+        verbose: bool,
+
+        /// Spill flat records to a temporary NDJSON file in DIR and group from disk (huge catalogs)
+        #[arg(long, value_name = "DIR")]
+        spill_dir: Option<PathBuf>,
+
+        /// Write validation errors, skipped rows and diagnosis to this JSON file
+        #[arg(long, value_name = "FILE")]
+        error_report: Option<PathBuf>,
+
+        /// Deliver the grouped works to this output sink of the configuration file (repeatable)
+        #[arg(long = "sink", value_name = "NAME")]
+        sinks: Vec<String>,
     },
 
     /// Validate JSON records against MIDDS flat schema
@@ -102,6 +248,16 @@ enum Commands {
         output: Option<PathBuf>,
     },
 
+    /// Expand grouped works back to flat CSV rows (one per creator)
+    Ungroup {
+        /// Input JSON file (array of grouped works, or NDJSON)
+        input: PathBuf,
+
+        /// Output CSV file (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
     /// Show example transformation matrix
     ExampleMatrix,
 
@@ -113,6 +269,30 @@ enum Commands {
         /// Port to listen on
         #[arg(short, long, default_value = "3000")]
         port: u16,
+
+        /// Public demo instance: capped uploads, no AI calls, nothing saved
+        #[arg(long)]
+        demo: bool,
+
+        /// Demo mode: maximum rows per file
+        #[arg(long, default_value = "500", requires = "demo")]
+        demo_max_rows: usize,
+
+        /// Demo mode: maximum file size in bytes
+        #[arg(long, default_value = "1048576", requires = "demo")]
+        demo_max_bytes: usize,
+
+        /// Demo mode: uploads accepted per minute from each client
+        #[arg(long, default_value = "10", requires = "demo")]
+        demo_uploads_per_minute: u32,
+
+        /// Demo mode: reverse proxy whose X-Forwarded-For header names the client (repeatable)
+        #[arg(long, value_name = "IP", requires = "demo")]
+        demo_trusted_proxy: Vec<std::net::IpAddr>,
+
+        /// Ask the user to confirm uploads whose AI request is estimated above this cost (USD)
+        #[arg(long, value_name = "USD")]
+        ai_cost_threshold: Option<f64>,
     },
 
     /// Manage transformation matrix templates
@@ -125,7 +305,14 @@ enum Commands {
 #[derive(Subcommand)]
 enum TemplateAction {
     /// List all stored templates
-    List,
+    List {
+        /// List archived templates instead
+        #[arg(long)]
+        archived: bool,
+        /// Only templates used for this period, or a period of this year
+        #[arg(long)]
+        period: Option<String>,
+    },
 
     /// Import a matrix JSON file as template
     Import {
@@ -134,6 +321,19 @@ enum TemplateAction {
         /// Name for the template
         #[arg(short, long)]
         name: Option<String>,
+        /// The file is a mapping from the legacy massdrop tooling (YAML or JSON)
+        #[arg(long)]
+        legacy: bool,
+    },
+
+    /// Create a skeleton template from a two-column mapping spreadsheet
+    /// (CSV column → MIDDS field, CSV or XLSX), with the standard cleanup operations
+    FromMapping {
+        /// Mapping file
+        file: PathBuf,
+        /// Name for the template (default: file name)
+        #[arg(short, long)]
+        name: Option<String>,
     },
 
     /// Show details of a template
@@ -142,12 +342,42 @@ enum TemplateAction {
         id: String,
     },
 
-    /// Delete a template
-    Delete {
+    /// Archive a template: kept in `archived/` for audit records, no longer matched
+    #[command(alias = "delete")]
+    Archive {
         /// Template ID
         id: String,
     },
 
+    /// Restore an archived template
+    Restore {
+        /// Template ID
+        id: String,
+    },
+
+    /// Rename a template (its ID is kept)
+    Rename {
+        /// Template ID
+        id: String,
+        /// New name
+        name: String,
+    },
+
+    /// Show, set or remove the fixed-width layout stored with a template
+    Layout {
+        /// Template ID
+        id: String,
+        /// Layout "name:start:len,..." (0-based offsets, no header row); shown when omitted
+        spec: Option<String>,
+        /// Remove the stored layout
+        #[arg(long, conflicts_with = "spec")]
+        clear: bool,
+    },
+
+    /// Re-check all stored templates against the current schema and operations,
+    /// marking incompatible ones (left out of automatic matching)
+    Audit,
+
     /// Use a specific template to transform a CSV
     Use {
         /// Template ID
@@ -169,13 +399,33 @@ async fn main() {
     dotenvy::dotenv().ok();
     
     let cli = Cli::parse();
+    let config_path = cli.config;
 
     let result = match cli.command {
         Commands::Parse {
             input,
             delimiter,
             output,
-        } => cmd_parse(&input, delimiter, output.as_deref()),
+            skip_first,
+            header_row,
+            header_rows,
+            skip_last,
+            ragged_rows,
+        } => {
+            let options = ParseOptions {
+                skip_first: skip_first.unwrap_or(0),
+                header_rows: header_rows.map_or(1, |n| n as usize),
+                skip_last: skip_last.unwrap_or(0),
+                ragged_rows,
+                delimiter,
+                ..ParseOptions::default()
+            };
+            let options = match header_row {
+                Some(line) => options.with_header_row(line as usize),
+                None => options,
+            };
+            cmd_parse(&input, output.as_deref(), &options)
+        }
 
         Commands::Transform {
             input,
@@ -184,31 +434,99 @@ async fn main() {
             save_matrix,
             output,
             grouped,
+            flat_csv,
             preview_rows,
             no_validate,
-        } => {
-            cmd_transform(
+            exclude_columns,
+            structure_only,
+            no_ai,
+            confirm_ai,
+            ai_cost_threshold,
+            skip_first,
+            header_row,
+            header_rows,
+            skip_last,
+            ragged_rows,
+            encoding,
+            input_format,
+            fixed_width,
+            min_valid_ratio,
+            ai_attempts,
+            ai_target_ratio,
+            min_year,
+            max_year,
+            network,
+            infer_roles,
+            period,
+            verbose,
+            spill_dir,
+            error_report,
+            sinks,
+        } => match Config::load(config_path.as_deref()).and_then(|config| {
+            let sinks = select_sinks(&config.sinks, &sinks)?;
+            Ok((config, sinks))
+        }) {
+            Ok((config, sinks)) => cmd_transform(
                 &input,
                 delimiter,
                 matrix.as_deref(),
                 save_matrix.as_deref(),
                 output.as_deref(),
                 grouped.as_deref(),
+                flat_csv.as_deref(),
                 preview_rows,
                 no_validate,
+                exclude_columns,
+                structure_only,
+                no_ai,
+                confirm_ai.then_some(ai_cost_threshold),
+                skip_first,
+                header_row.map(|line| line as usize),
+                header_rows.map(|n| n as usize),
+                skip_last,
+                ragged_rows,
+                encoding,
+                input_format,
+                fixed_width.as_deref(),
+                min_valid_ratio,
+                ai_attempts,
+                ai_target_ratio,
+                YearBounds { min: min_year, max: max_year },
+                network,
+                RoleInference { enabled: infer_roles || config.role_inference.enabled, ..config.role_inference.clone() },
+                period,
+                verbose,
+                spill_dir.as_deref(),
+                error_report.as_deref(),
+                &sinks,
+                &config.notifications,
             )
-            .await
-        }
+            .await,
+            Err(e) => Err(e.into()),
+        },
 
         Commands::Validate { input } => cmd_validate(&input),
 
         Commands::Group { input, output } => cmd_group(&input, output.as_deref()),
 
+        Commands::Ungroup { input, output } => cmd_ungroup(&input, output.as_deref()),
+
         Commands::ExampleMatrix => cmd_example_matrix(),
 
         Commands::Operations => cmd_operations(),
 
-        Commands::Serve { port } => cmd_serve(port).await,
+        Commands::Serve { port, demo, demo_max_rows, demo_max_bytes, demo_uploads_per_minute, demo_trusted_proxy, ai_cost_threshold } => {
+            let demo = demo.then_some(DemoLimits {
+                max_file_bytes: demo_max_bytes,
+                max_rows: demo_max_rows,
+                uploads_per_minute: demo_uploads_per_minute,
+                trusted_proxies: demo_trusted_proxy,
+            });
+            match Config::load(config_path.as_deref()) {
+                Ok(config) => cmd_serve(port, demo, ai_cost_threshold, config.events.webhook, config.sinks, config.role_inference).await,
+                Err(e) => Err(e.into()),
+            }
+        }
 
         Commands::Template { action } => cmd_template(action).await,
     };
@@ -221,23 +539,35 @@ async fn main() {
 
 fn cmd_parse(
     input: &Path,
-    delimiter: Option<char>,
     output: Option<&Path>,
+    options: &ParseOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    eprintln!("📄 Parsing CSV: {}", input.display());
-
-    let result = parse_csv_file_auto(input)?;
+    eprintln!("📄 Parsing: {}", input.display());
+
+    let unpacked = unpack(fs::read(input)?, input.file_name().and_then(|n| n.to_str()))?;
+    let bytes = unpacked.bytes;
+    let result = match InputFormat::from_hints(None, unpacked.file_name.as_deref()).resolve(&bytes) {
+        InputFormat::Xlsx => parse_xlsx(&bytes, options)?,
+        InputFormat::Cwr => parse_cwr(&bytes, options.encoding.as_deref())?,
+        InputFormat::Json => parse_json_with_encoding(&bytes, options.encoding.as_deref())?,
+        _ => parse_bytes_with_options(&bytes, options)?,
+    };
     
-    let used_delimiter = delimiter.unwrap_or(result.delimiter);
     eprintln!("   Encoding: {}", result.encoding);
-    eprintln!("   Delimiter: '{}'{}", 
-        match used_delimiter {
-            '\t' => "\\t".to_string(),
-            c => c.to_string(),
-        },
-        if delimiter.is_none() { " (auto-detected)" } else { "" }
+    eprintln!("   Delimiter: '{}'{}",
+        format_delimiter(result.delimiter),
+        if options.delimiter.is_none() { " (auto-detected)" } else { "" }
     );
     eprintln!("   Columns: {}", result.headers.join(", "));
+    if !result.footer_lines.is_empty() {
+        eprintln!("   Footer lines ignored: {}", result.footer_lines.join(" | "));
+    }
+    for note in &result.diagnostics.notes {
+        eprintln!("   ⚠️  Data quality: {}", note);
+    }
+    for row in &result.ragged_rows {
+        eprintln!("   ⚠️  Ragged row, {}", row);
+    }
     eprintln!("✅ Parsed {} records", result.records.len());
 
     let json = serde_json::to_string_pretty(&result.records)?;
@@ -246,17 +576,45 @@ fn cmd_parse(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn cmd_transform(
     input: &Path,
-    _delimiter: Option<char>,
+    delimiter: Option<char>,
     matrix_path: Option<&Path>,
     save_matrix: Option<&Path>,
     output: Option<&Path>,
     grouped_output: Option<&Path>,
+    flat_csv_output: Option<&Path>,
     preview_rows: usize,
     no_validate: bool,
+    exclude_columns: Vec<String>,
+    structure_only: bool,
+    no_ai: bool,
+    ai_cost_limit: Option<f64>,
+    skip_first: Option<usize>,
+    header_row: Option<usize>,
+    header_rows: Option<usize>,
+    skip_last: Option<usize>,
+    ragged_rows: RaggedRows,
+    encoding: Option<String>,
+    input_format: InputFormat,
+    fixed_width: Option<&str>,
+    min_valid_ratio: f64,
+    ai_attempts: usize,
+    ai_target_ratio: Option<f64>,
+    year_bounds: YearBounds,
+    network: ChainNetwork,
+    role_inference: RoleInference,
+    period: Option<CatalogPeriod>,
+    verbose: bool,
+    spill_dir: Option<&Path>,
+    error_report: Option<&Path>,
+    sinks: &[(String, SinkConfig)],
+    notifications: &NotifyConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
     eprintln!("📄 Processing: {}", input.display());
+    install_progress();
+    let fixed_width = fixed_width.map(FixedWidthColumn::parse_layout).transpose()?;
 
     // Build options
     let options = TransformOptions {
@@ -265,22 +623,75 @@ async fn cmd_transform(
         skip_validation: no_validate,
         no_cache: false,
         no_save: false,
+        exclude_columns,
+        structure_only,
+        no_ai,
+        ai_cost_limit,
+        skip_first,
+        header_row,
+        header_rows,
+        skip_last,
+        ragged_rows,
+        encoding,
+        delimiter,
+        input_format,
+        template_name: None,
+        fixed_width,
+        min_valid_ratio,
+        ai_attempts,
+        ai_target_ratio,
+        verbose,
+        spill_dir: spill_dir.map(|p| p.to_string_lossy().to_string()),
+        temp_dir: None,
+        year_bounds,
+        registry_dir: None,
+        network,
+        role_inference,
+        period,
+        live: None,
+        events: None,
     };
 
     // Run pipeline
-    let result = transform_csv(input, options).await?;
+    let started = Instant::now();
+    let result = match transform_csv(input, options.clone()).await {
+        Err(PipelineError::AiCostNotConfirmed(estimate)) if confirm_ai_cost(&estimate)? => {
+            transform_csv(input, TransformOptions { ai_cost_limit: None, ..options }).await
+        }
+        result => result,
+    };
+    let result = match result {
+        Ok(result) => result,
+        Err(e) => {
+            send_notifications(notifications, &RunSummary::failed(input, &e.to_string(), started.elapsed())).await;
+            return Err(e.into());
+        }
+    };
 
     // Display info
     eprintln!("   Encoding: {}", result.csv_info.encoding);
     eprintln!("   Delimiter: '{}'", format_delimiter(result.csv_info.delimiter));
     eprintln!("   Rows: {}", result.csv_info.row_count);
+    if let Some(ref sha256) = result.csv_info.sha256 {
+        eprintln!("   SHA-256: {}", sha256);
+    }
     eprintln!("   Columns: {}", result.csv_info.headers.join(", "));
 
     if let Some(ref tid) = result.template_id {
         eprintln!("   Template: {}", tid);
     }
+    if let Some(ref columns) = result.template_columns {
+        eprintln!("   Matched columns: {}", columns.matched.join(", "));
+        if !columns.missing.is_empty() {
+            eprintln!("   ⚠️  Missing columns: {}", columns.missing.join(", "));
+        }
+        if !columns.unused.is_empty() {
+            eprintln!("   Unused columns: {}", columns.unused.join(", "));
+        }
+    }
 
-    eprintln!("\n⚙️  Transformed: {} flat records", result.flat.len());
+    let flat_count = result.flat_spill.as_ref().map_or(result.flat.len(), |s| s.len());
+    eprintln!("\n⚙️  Transformed: {} flat records", flat_count);
 
     // Validation results
     if !no_validate {
@@ -289,7 +700,10 @@ async fn cmd_transform(
             eprintln!("   ✅ Valid: {}", result.valid_count);
             eprintln!("   ❌ Invalid: {}", result.invalid_count);
             for (i, errors) in result.validation_errors.iter().take(5) {
-                eprintln!("\n   Record {}:", i);
+                match result.flat_lines.get(*i) {
+                    Some(line) => eprintln!("\n   Record {} (line {}):", i, line),
+                    None => eprintln!("\n   Record {}:", i),
+                }
                 for err in errors.iter().take(3) {
                     eprintln!("     - {}", err);
                 }
@@ -297,6 +711,16 @@ async fn cmd_transform(
         } else {
             eprintln!("   ✅ All {} records valid!", result.valid_count);
         }
+
+        if let Some(report) = diagnose_failure(&result) {
+            eprintln!("\n❌ No valid record. Most frequent errors:");
+            for cause in &report.top_causes {
+                eprintln!("   - {} ({} records)", cause.message, cause.count);
+            }
+            for suggestion in &report.suggestions {
+                eprintln!("   💡 {}", suggestion.hint());
+            }
+        }
     }
 
     // Save matrix if requested
@@ -306,9 +730,29 @@ async fn cmd_transform(
         eprintln!("   💾 Matrix saved to: {}", save_path.display());
     }
 
-    // Output flat records
-    let flat_json = serde_json::to_string_pretty(&result.flat)?;
-    write_output(&flat_json, output)?;
+    // Output flat records (streamed from the spill file when spilled)
+    match result.flat_spill {
+        Some(ref spill) => match output {
+            Some(p) => {
+                spill.write_json_array(std::io::BufWriter::new(fs::File::create(p)?))?;
+                eprintln!("💾 Output written to: {}", p.display());
+            }
+            None => spill.write_json_array(std::io::stdout().lock())?,
+        },
+        None => {
+            let flat_json = serde_json::to_string_pretty(&result.flat)?;
+            write_output(&flat_json, output)?;
+        }
+    }
+
+    if let Some(csv_path) = flat_csv_output {
+        let out = std::io::BufWriter::new(fs::File::create(csv_path)?);
+        match result.flat_spill {
+            Some(ref spill) => write_flat_csv(spill.records()?, out)?,
+            None => write_flat_csv(result.flat.iter().cloned().map(Ok), out)?,
+        }
+        eprintln!("   💾 Flat CSV written to: {}", csv_path.display());
+    }
 
     // Grouped output
     if let Some(grouped_path) = grouped_output {
@@ -318,13 +762,223 @@ async fn cmd_transform(
         eprintln!("   💾 Saved to: {}", grouped_path.display());
     }
 
+    if let Some(report_path) = error_report {
+        write_error_report(input, &result, report_path)?;
+        eprintln!("   📝 Error report: {}", report_path.display());
+    }
+
+    if !sinks.is_empty() {
+        let valid = if no_validate { result.grouped.len() } else { result.valid_count };
+        let job_id = deterministic::new_uuid().to_string();
+        for delivery in deliver_all(sinks, &job_id, &result.grouped, valid).await {
+            match delivery.error {
+                None => eprintln!("   📤 Delivered to {} ({})", delivery.sink, delivery.target),
+                Some(ref e) => eprintln!("   ⚠️  Delivery to {} {:?}: {}", delivery.sink, delivery.status, e),
+            }
+        }
+    }
+
+    send_notifications(notifications, &RunSummary::completed(input, &result, started.elapsed(), error_report)).await;
+
+    if let (Some(ref id), Some(ref name)) = (&result.template_id, &result.created_template) {
+        prompt_template_name(id, name)?;
+    }
+
     eprintln!("\n✨ Done!");
     Ok(())
 }
 
+/// Write the errors of a run: invalid records, skipped rows and the failure diagnosis
+fn write_error_report(input: &Path, result: &PipelineResult, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let report = serde_json::json!({
+        "input": input.display().to_string(),
+        "valid": result.valid_count,
+        "invalid": result.invalid_count,
+        "validationErrors": result.validation_errors.iter()
+            .map(|(record, errors)| serde_json::json!({
+                "recordIndex": record,
+                "line": result.flat_lines.get(*record),
+                "errors": errors,
+            }))
+            .collect::<Vec<_>>(),
+        "validationWarnings": result.validation_warnings,
+        "skipped": result.skipped,
+        "failure": diagnose_failure(result),
+    });
+    fs::write(path, serde_json::to_string_pretty(&report)?)?;
+    Ok(())
+}
+
+/// Send the end-of-run notifications, reporting (not propagating) failures
+async fn send_notifications(config: &NotifyConfig, summary: &RunSummary) {
+    if !config.is_enabled() {
+        return;
+    }
+    let errors = notify(config, summary).await;
+    if errors.is_empty() {
+        eprintln!("   📨 Notification sent ({})", summary.event);
+    }
+    for error in errors {
+        eprintln!("   ⚠️  Notification failed: {}", error);
+    }
+}
+
+/// Offer a human-friendly name for a template the AI just created.
+///
+/// Interactive terminals get a prompt (Enter accepts the suggestion, `-`
+/// keeps the current name); otherwise the rename command is printed.
+fn prompt_template_name(id: &str, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let suggestion = suggest_name(name);
+    eprintln!("\n🏷️  New template saved as \"{}\" ({})", name, id);
+
+    if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        eprintln!("   Rename it with: massload template rename {} \"{}\"", id, suggestion);
+        return Ok(());
+    }
+
+    eprint!("   Name it [{}] (- to keep): ", suggestion);
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    let answer = answer.trim();
+    if answer == "-" {
+        return Ok(());
+    }
+
+    let new_name = if answer.is_empty() { suggestion.as_str() } else { answer };
+    MatrixRegistry::new().rename(id, new_name)?;
+    eprintln!("   ✅ Template renamed to \"{}\"", new_name);
+    Ok(())
+}
+
+/// Ask whether to call the AI despite its estimated cost (no on a non-interactive terminal)
+fn confirm_ai_cost(estimate: &AiCostEstimate) -> Result<bool, Box<dyn std::error::Error>> {
+    eprintln!("\n💰 The AI request is estimated at up to ${:.4} (~{} input tokens, up to {} output tokens)",
+        estimate.cost_usd, estimate.input_tokens, estimate.max_output_tokens);
+
+    if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        eprintln!("   Not confirmed: rerun interactively, raise --ai-cost-threshold or drop --confirm-ai");
+        return Ok(false);
+    }
+
+    eprint!("   Call the AI anyway? [y/N] ");
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes" | "o" | "oui"))
+}
+
+/// Progress bars when stderr is a terminal, periodic lines otherwise
+fn install_progress() {
+    let observer: Arc<dyn ProgressObserver> = if std::io::stderr().is_terminal() {
+        Arc::new(BarProgress::default())
+    } else {
+        Arc::new(LineProgress::default())
+    };
+    set_progress_observer(Some(observer));
+}
+
+fn stage_label(stage: LogStage) -> &'static str {
+    match stage {
+        LogStage::Parse => "parsing",
+        LogStage::Template => "templates",
+        LogStage::Ai => "ai",
+        LogStage::Transform => "transforming",
+        LogStage::Validate => "validating",
+        LogStage::Group => "grouping",
+    }
+}
+
+/// One indicatif bar per stage
+#[derive(Default)]
+struct BarProgress {
+    bar: Mutex<Option<ProgressBar>>,
+}
+
+impl ProgressObserver for BarProgress {
+    fn start(&self, stage: LogStage, total: u64) {
+        let bar = ProgressBar::new(total).with_prefix(stage_label(stage));
+        if let Ok(style) = ProgressStyle::with_template(
+            "{prefix:>12} [{bar:40.cyan/blue}] {human_pos}/{human_len} ({per_sec}, ETA {eta})",
+        ) {
+            bar.set_style(style.progress_chars("=> "));
+        }
+        if let Ok(mut current) = self.bar.lock() {
+            *current = Some(bar);
+        }
+    }
+
+    fn update(&self, done: u64) {
+        if let Some(bar) = self.bar.lock().ok().as_ref().and_then(|b| b.as_ref()) {
+            bar.set_position(done);
+        }
+    }
+
+    fn finish(&self) {
+        if let Some(bar) = self.bar.lock().ok().and_then(|mut b| b.take()) {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+/// Interval between two progress lines when stderr is not a terminal
+const PROGRESS_LINE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Periodic progress lines (logs, CI); fast stages print nothing
+#[derive(Default)]
+struct LineProgress {
+    state: Mutex<Option<LineState>>,
+}
+
+struct LineState {
+    stage: LogStage,
+    total: u64,
+    started: Instant,
+    last_line: Instant,
+    printed: bool,
+}
+
+impl ProgressObserver for LineProgress {
+    fn start(&self, stage: LogStage, total: u64) {
+        let now = Instant::now();
+        if let Ok(mut state) = self.state.lock() {
+            *state = Some(LineState { stage, total, started: now, last_line: now, printed: false });
+        }
+    }
+
+    fn update(&self, done: u64) {
+        let Ok(mut state) = self.state.lock() else { return };
+        let Some(state) = state.as_mut() else { return };
+        if state.last_line.elapsed() < PROGRESS_LINE_INTERVAL {
+            return;
+        }
+        let percent = done * 100 / state.total.max(1);
+        eprintln!("   ⏳ {}: {}/{} ({}%)", stage_label(state.stage), done, state.total, percent);
+        state.last_line = Instant::now();
+        state.printed = true;
+    }
+
+    fn finish(&self) {
+        let Some(state) = self.state.lock().ok().and_then(|mut s| s.take()) else { return };
+        if state.printed {
+            eprintln!("   ⏳ {}: done in {:.1}s", stage_label(state.stage), state.started.elapsed().as_secs_f64());
+        }
+    }
+}
+
+/// `--encoding` value: a known encoding, normalized (an unknown one would decode as UTF-8)
+fn parse_encoding(label: &str) -> Result<String, String> {
+    check_encoding(label).map_err(|e| e.message)
+}
+
+/// `--delimiter` value: one ASCII character, or `tab`
+fn parse_delimiter(value: &str) -> Result<char, String> {
+    check_delimiter(value).map_err(|e| e.message)
+}
+
 fn format_delimiter(d: char) -> String {
     match d {
         '\t' => "\\t".to_string(),
+        ' ' => "fixed-width".to_string(),
+        '\0' => "none (JSON)".to_string(),
         c => c.to_string(),
     }
 }
@@ -379,6 +1033,29 @@ fn cmd_group(input: &Path, output: Option<&Path>) -> Result<(), Box<dyn std::err
     Ok(())
 }
 
+fn cmd_ungroup(input: &Path, output: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+    eprintln!("📂 Ungrouping: {}", input.display());
+
+    let content = fs::read_to_string(input)?;
+    // A JSON array, or NDJSON as streamed by the download endpoint
+    let works: Vec<GroupedWork> = if content.trim_start().starts_with('[') {
+        serde_json::from_str(&content)?
+    } else {
+        content.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?
+    };
+    eprintln!("   {} works", works.len());
+
+    let records = grouped_to_flat(&works);
+    eprintln!("   {} flat rows", records.len());
+
+    write_output(flat_csv(&records)?.trim_end(), output)?;
+
+    Ok(())
+}
+
 fn cmd_example_matrix() -> Result<(), Box<dyn std::error::Error>> {
     let matrix = massload::example_matrix();
     let json = matrix.to_json()?;
@@ -391,8 +1068,15 @@ fn cmd_operations() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-async fn cmd_serve(port: u16) -> Result<(), Box<dyn std::error::Error>> {
-    massload::server::start_server(port).await
+async fn cmd_serve(
+    port: u16,
+    demo: Option<DemoLimits>,
+    ai_cost_threshold: Option<f64>,
+    events_webhook: Option<WebhookConfig>,
+    sinks: SinksConfig,
+    role_inference: RoleInference,
+) -> Result<(), Box<dyn std::error::Error>> {
+    massload::server::start_server(port, demo, ai_cost_threshold, events_webhook, sinks, role_inference).await
 }
 
 fn write_output(content: &str, path: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
@@ -412,15 +1096,23 @@ async fn cmd_template(action: TemplateAction) -> Result<(), Box<dyn std::error::
     let mut registry = MatrixRegistry::new();
 
     match action {
-        TemplateAction::List => {
-            let templates = registry.list();
+        TemplateAction::List { archived, period } => {
+            let mut templates = if archived { registry.list_archived() } else { registry.list() };
+            if let Some(ref period) = period {
+                templates.retain(|t| t.used_for_period(period));
+            }
+            if templates.is_empty() && archived {
+                eprintln!("📋 No archived templates.");
+                return Ok(());
+            }
             if templates.is_empty() {
                 eprintln!("📋 No templates stored yet.");
                 eprintln!("   Use 'massload template import <file>' to add one.");
                 return Ok(());
             }
 
-            eprintln!("📋 Stored templates ({}):\n", templates.len());
+            let label = if archived { "Archived" } else { "Stored" };
+            eprintln!("📋 {} templates ({}):\n", label, templates.len());
             for t in templates {
                 println!("  📄 {} ({})", t.name, t.id);
                 println!("     Columns: {}", t.csv_columns.join(", "));
@@ -429,11 +1121,33 @@ async fn cmd_template(action: TemplateAction) -> Result<(), Box<dyn std::error::
                 if let Some(ref last) = t.last_used {
                     println!("     Last used: {}", last);
                 }
+                if !t.periods.is_empty() {
+                    let periods: Vec<String> = t.periods.iter().map(|(p, n)| format!("{} ({})", p, n)).collect();
+                    println!("     Periods: {}", periods.join(", "));
+                }
+                if let Some(ref at) = t.archived_at {
+                    println!("     Archived: {} (restore with 'massload template restore {}')", at, t.id);
+                }
+                if !t.incompatible.is_empty() {
+                    println!("     ⚠️  Incompatible with the current schema (see 'massload template audit')");
+                }
                 println!();
             }
         }
 
-        TemplateAction::Import { file, name } => {
+        TemplateAction::Import { file, name, legacy: true } => {
+            eprintln!("📥 Importing legacy mapping from: {}", file.display());
+            let (id, unsupported) = registry.import_legacy(&file, name.as_deref())?;
+            for construct in &unsupported {
+                eprintln!("   ⚠️  Left out: {}", construct);
+            }
+            eprintln!("✅ Template saved with ID: {}", id);
+            if !unsupported.is_empty() {
+                eprintln!("   {} construct(s) not converted: check the template with 'massload template show {}'", unsupported.len(), id);
+            }
+        }
+
+        TemplateAction::Import { file, name, legacy: false } => {
             let template_name = name.as_deref().unwrap_or_else(|| {
                 file.file_stem()
                     .and_then(|s| s.to_str())
@@ -445,6 +1159,16 @@ async fn cmd_template(action: TemplateAction) -> Result<(), Box<dyn std::error::
             eprintln!("✅ Template saved with ID: {}", id);
         }
 
+        TemplateAction::FromMapping { file, name } => {
+            eprintln!("📥 Building template from mapping: {}", file.display());
+            let (id, skipped) = registry.import_mapping(&file, name.as_deref())?;
+            for line in &skipped {
+                eprintln!("   ⚠️  Left out: {}", line);
+            }
+            eprintln!("✅ Template saved with ID: {}", id);
+            eprintln!("   Review it with 'massload template show {}'", id);
+        }
+
         TemplateAction::Show { id } => {
             match registry.get(&id) {
                 Some(t) => {
@@ -453,6 +1177,9 @@ async fn cmd_template(action: TemplateAction) -> Result<(), Box<dyn std::error::
                     println!("Created: {}", t.created_at);
                     println!("Success rate: {:.0}%", t.success_rate * 100.0);
                     println!("Uses: {}", t.use_count);
+                    if let Some(ref at) = t.archived_at {
+                        println!("Archived: {}", at);
+                    }
                     println!("\nMatrix:");
                     println!("{}", serde_json::to_string_pretty(&t.matrix)?);
                 }
@@ -462,9 +1189,68 @@ async fn cmd_template(action: TemplateAction) -> Result<(), Box<dyn std::error::
             }
         }
 
-        TemplateAction::Delete { id } => {
-            registry.delete(&id)?;
-            eprintln!("🗑️  Template deleted: {}", id);
+        TemplateAction::Archive { id } => {
+            registry.archive(&id)?;
+            eprintln!("🗄️  Template archived: {} (no longer matched, restore with 'massload template restore {}')", id, id);
+        }
+
+        TemplateAction::Restore { id } => {
+            let stored = registry.restore(&id)?;
+            eprintln!("♻️  Template restored: {} ({})", stored.name, stored.id);
+        }
+
+        TemplateAction::Rename { id, name } => {
+            let stored = registry.rename(&id, &name)?;
+            eprintln!("🏷️  Template {} renamed to \"{}\"", stored.id, stored.name);
+        }
+
+        TemplateAction::Layout { id, spec, clear } => {
+            let layout = match spec {
+                Some(spec) => Some(FixedWidthColumn::parse_layout(&spec)?),
+                None if clear => None,
+                None => {
+                    let template = registry.get(&id)
+                        .ok_or_else(|| format!("Template not found: {}", id))?;
+                    match template.matrix.source_format.as_ref().and_then(|f| f.fixed_width.as_ref()) {
+                        Some(columns) => println!("{}", FixedWidthColumn::layout_spec(columns)),
+                        None => eprintln!("📋 Template {} has no fixed-width layout", id),
+                    }
+                    return Ok(());
+                }
+            };
+            let stored = registry.set_layout(&id, layout)?;
+            match stored.matrix.source_format.as_ref().and_then(|f| f.fixed_width.as_ref()) {
+                Some(columns) => eprintln!("📐 Template {} reads fixed-width files: {}", stored.id, FixedWidthColumn::layout_spec(columns)),
+                None => eprintln!("📐 Fixed-width layout removed from template {}", stored.id),
+            }
+        }
+
+        TemplateAction::Audit => {
+            let audits = registry.audit(&flat_schema());
+            if audits.is_empty() {
+                eprintln!("📋 No templates stored yet.");
+                return Ok(());
+            }
+
+            let incompatible = audits.iter().filter(|a| !a.is_compatible()).count();
+            for audit in &audits {
+                let label = match audit.name {
+                    Some(ref name) => format!("{} ({})", name, audit.id),
+                    None => audit.id.clone(),
+                };
+                if audit.is_compatible() {
+                    println!("  ✅ {}", label);
+                    continue;
+                }
+                println!("  ❌ {}", label);
+                for issue in &audit.issues {
+                    println!("     - {}", issue);
+                }
+            }
+            eprintln!("\n🔎 {} template(s) audited, {} incompatible", audits.len(), incompatible);
+            if incompatible > 0 {
+                eprintln!("   Incompatible templates are no longer matched automatically: fix them and re-import, or archive them.");
+            }
         }
 
         TemplateAction::Use { id, input, output, grouped } => {
@@ -473,16 +1259,20 @@ async fn cmd_template(action: TemplateAction) -> Result<(), Box<dyn std::error::
 
             eprintln!("📄 Using template: {} ({})", template.name, template.id);
             
-            // Parse and transform
-            let parse_result = parse_csv_file_auto(&input)?;
+            // Parse like the file the template was made for (rows to skip, fixed-width layout)
+            let options = template.matrix.source_format.as_ref()
+                .map(|format| format.parse_options())
+                .unwrap_or_default();
+            let bytes = unpack(fs::read(&input)?, None)?.bytes;
+            let parse_result = parse_bytes_with_options(&bytes, &options)?;
             eprintln!("   Found {} rows", parse_result.records.len());
 
-            let result = transform_with_matrix(&parse_result.records, &template.matrix, true);
+            let result = transform_with_matrix(&parse_result.records, &template.matrix, Some(ValidationContext::shared()?));
             eprintln!("   Transformed: {} records", result.flat.len());
 
             // Update stats
             let mut registry_mut = MatrixRegistry::new();
-            registry_mut.update_stats(&id, result.invalid_count == 0);
+            registry_mut.update_stats(&id, result.invalid_count == 0, None);
 
             if result.invalid_count == 0 {
                 eprintln!("   ✅ All {} records valid!", result.valid_count);
@@ -4,11 +4,15 @@
 //!
 //! - [`GroupedWork`] - Complete MIDDS musical work with all creators
 //! - [`Creator`] - Creator information with ID and role
+//! - [`CreatorShares`] - Ownership, performance and mechanical shares of a creator
 //! - [`PartyId`] - IPI or ISNI identifier for a creator
 //! - [`CreatorRole`] - CISAC role codes (Composer, Author, etc.)
 //! - [`MusicalWorkType`] - Type of work (Original, Arrangement, etc.)
+//! - [`ClassicalInfo`] - Opus, catalog number and voices of classical works
+//! - [`CatalogPeriod`] - Accounting period a catalog is delivered for (`2025-Q1`)
 
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 // =============================================================================
 // Party Identification
@@ -18,6 +22,10 @@ use serde::{Deserialize, Serialize};
 ///
 /// Can be either an IPI (Interested Party Identifier) or ISNI
 /// (International Standard Name Identifier), or both.
+///
+/// Serializes to the dedot SDK format used in grouped works:
+/// `{"type":"Ipi","value":123}`, `{"type":"Isni","value":"…"}`,
+/// `{"type":"Both","value":{"ipi":123,"isni":"…"}}`.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type", content = "value")]
 pub enum PartyId {
@@ -135,7 +143,7 @@ impl CreatorRole {
 // =============================================================================
 
 /// A creator (interested party) of a musical work.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Creator {
     /// Unique identifier (IPI, ISNI, or both).
     pub id: PartyId,
@@ -144,9 +152,33 @@ pub struct Creator {
     /// Display name (optional, for UI).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
-    /// Share percentage (0-100).
+    /// Shares by type, in percent (0-100).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub share: Option<f64>,
+    pub shares: Option<CreatorShares>,
+}
+
+/// Shares of a creator in a work, in percent (0-100).
+///
+/// Catalogs often split the ownership share into performance and mechanical
+/// rights; each type is kept apart instead of collapsed into one number.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct CreatorShares {
+    /// Ownership share (flat `creatorShare`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ownership: Option<f64>,
+    /// Performance rights share (flat `creatorPerformanceShare`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub performance: Option<f64>,
+    /// Mechanical rights share (flat `creatorMechanicalShare`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mechanical: Option<f64>,
+}
+
+impl CreatorShares {
+    /// `None` when no share type is known.
+    pub fn non_empty(self) -> Option<Self> {
+        (self != Self::default()).then_some(self)
+    }
 }
 
 // =============================================================================
@@ -170,6 +202,164 @@ pub enum MusicalWorkType {
     Unspecified,
 }
 
+// =============================================================================
+// Classical Info
+// =============================================================================
+
+/// Classical music details of a work.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassicalInfo {
+    /// Opus number (e.g. "Op. 27 No. 2").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opus: Option<String>,
+    /// Catalog number (e.g. "BWV 1007").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub catalog_number: Option<String>,
+    /// Number of voices.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number_of_voices: Option<u32>,
+}
+
+// =============================================================================
+// Work Title
+// =============================================================================
+
+/// Title of a work, tagged with its language when known.
+///
+/// Serializes as a bare string, or as `{ "title": "...", "language": "French" }`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum WorkTitle {
+    /// Title with the language it is written in.
+    Tagged { title: String, language: String },
+    /// Title of unknown language.
+    Plain(String),
+}
+
+impl WorkTitle {
+    /// Title, tagged when `language` is given.
+    pub fn new(title: String, language: Option<String>) -> Self {
+        match language {
+            Some(language) => Self::Tagged { title, language },
+            None => Self::Plain(title),
+        }
+    }
+
+    /// Text of the title.
+    pub fn text(&self) -> &str {
+        match self {
+            Self::Tagged { title, .. } | Self::Plain(title) => title,
+        }
+    }
+
+    /// Language of the title, if tagged.
+    pub fn language(&self) -> Option<&str> {
+        match self {
+            Self::Tagged { language, .. } => Some(language),
+            Self::Plain(_) => None,
+        }
+    }
+}
+
+impl From<String> for WorkTitle {
+    fn from(title: String) -> Self {
+        Self::Plain(title)
+    }
+}
+
+/// Text of a title in SDK JSON, bare or language-tagged.
+pub fn title_text(title: &serde_json::Value) -> Option<&str> {
+    title.as_str().or_else(|| title.get("title")?.as_str())
+}
+
+// =============================================================================
+// Catalog Period
+// =============================================================================
+
+/// Accounting period a catalog is delivered for.
+///
+/// A year (`2025`), quarter (`2025-Q1`), month (`2025-03`) or ISO week
+/// (`2025-W07`). Parsing is case-insensitive and pads week numbers.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CatalogPeriod(String);
+
+impl CatalogPeriod {
+    /// Parse and normalize a period label.
+    pub fn parse(label: &str) -> Result<Self, String> {
+        let label = label.trim().to_uppercase();
+        let invalid = || format!("Invalid period '{}': expected 2025, 2025-Q1, 2025-03 or 2025-W07", label);
+        let (year, rest) = label.split_once('-').unwrap_or((&label, ""));
+        if year.len() != 4 || !year.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let number = |digits: &str, max: u32| {
+            digits.parse::<u32>().ok().filter(|n| (1..=max).contains(n) && digits.len() <= 2)
+        };
+
+        let normalized = match rest.chars().next() {
+            None if !label.contains('-') => year.to_string(),
+            Some('Q') => format!("{}-Q{}", year, number(&rest[1..], 4).ok_or_else(invalid)?),
+            Some('W') => format!("{}-W{:02}", year, number(&rest[1..], 53).ok_or_else(invalid)?),
+            Some(c) if c.is_ascii_digit() && rest.len() == 2 => format!("{}-{:02}", year, number(rest, 12).ok_or_else(invalid)?),
+            _ => return Err(invalid()),
+        };
+        Ok(Self(normalized))
+    }
+
+    /// Quarter of a date (`2025-Q1`).
+    pub fn quarter_of(date: chrono::NaiveDate) -> Self {
+        use chrono::Datelike;
+        Self(format!("{}-Q{}", date.year(), date.month0() / 3 + 1))
+    }
+
+    /// ISO week of a date (`2025-W07`); the ISO year may differ from the calendar year.
+    pub fn iso_week_of(date: chrono::NaiveDate) -> Self {
+        use chrono::Datelike;
+        let week = date.iso_week();
+        Self(format!("{}-W{:02}", week.year(), week.week()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether this period is `filter`, or falls in `filter` when it is a year.
+    pub fn matches(&self, filter: &str) -> bool {
+        let filter = filter.trim().to_uppercase();
+        self.0 == filter || (filter.len() == 4 && self.0.starts_with(&filter))
+    }
+}
+
+impl fmt::Display for CatalogPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::str::FromStr for CatalogPeriod {
+    type Err = String;
+
+    fn from_str(label: &str) -> Result<Self, Self::Err> {
+        Self::parse(label)
+    }
+}
+
+impl TryFrom<String> for CatalogPeriod {
+    type Error = String;
+
+    fn try_from(label: String) -> Result<Self, Self::Error> {
+        Self::parse(&label)
+    }
+}
+
+impl From<CatalogPeriod> for String {
+    fn from(period: CatalogPeriod) -> Self {
+        period.0
+    }
+}
+
 // =============================================================================
 // Grouped Musical Work (MIDDS format)
 // =============================================================================
@@ -177,13 +367,15 @@ pub enum MusicalWorkType {
 /// A complete musical work in MIDDS format.
 ///
 /// This is the final output format, with all creators grouped together.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Serializes to the @allfeat/client SDK (dedot) format: optional fields are
+/// omitted rather than `null`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct GroupedWork {
     /// ISWC (International Standard Musical Work Code).
     pub iswc: String,
     /// Main title of the work.
-    pub title: String,
+    pub title: WorkTitle,
     /// Alternative titles.
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub alternative_titles: Vec<String>,
@@ -192,18 +384,30 @@ pub struct GroupedWork {
     /// Participants (performers, etc.) - currently empty, reserved for future use.
     #[serde(default)]
     pub participants: Vec<serde_json::Value>,
-    /// Type of work.
-    #[serde(default)]
-    pub work_type: MusicalWorkType,
+    /// Type of work (`{ "type": "Original" }`).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub work_type: Option<MusicalWorkType>,
     /// Year of creation.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
     pub creation_year: Option<u16>,
     /// Musical genre.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
     pub genre: Option<String>,
     /// Whether the work is instrumental (no lyrics).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
     pub instrumental: Option<bool>,
+    /// Language of the lyrics.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub language: Option<String>,
+    /// Tempo in beats per minute.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub bpm: Option<u16>,
+    /// Musical key (e.g. "Am").
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub key: Option<String>,
+    /// Classical music details.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub classical_info: Option<ClassicalInfo>,
 }
 
 impl GroupedWork {
@@ -211,14 +415,18 @@ impl GroupedWork {
     pub fn new(iswc: String, title: String) -> Self {
         Self {
             iswc,
-            title,
+            title: title.into(),
             alternative_titles: Vec::new(),
             creators: Vec::new(),
             participants: Vec::new(),
-            work_type: MusicalWorkType::Original,
+            work_type: None,
             creation_year: None,
             genre: None,
             instrumental: None,
+            language: None,
+            bpm: None,
+            key: None,
+            classical_info: None,
         }
     }
 
@@ -226,6 +434,11 @@ impl GroupedWork {
     pub fn add_creator(&mut self, creator: Creator) {
         self.creators.push(creator);
     }
+
+    /// Convert to the SDK JSON value.
+    pub fn to_value(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
 }
 
 // =============================================================================
@@ -236,6 +449,27 @@ impl GroupedWork {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_catalog_period() {
+        assert_eq!(CatalogPeriod::parse(" 2025-q1 ").unwrap().as_str(), "2025-Q1");
+        assert_eq!(CatalogPeriod::parse("2025-w7").unwrap().as_str(), "2025-W07");
+        assert_eq!(CatalogPeriod::parse("2025-03").unwrap().as_str(), "2025-03");
+        assert_eq!(CatalogPeriod::parse("2025").unwrap().as_str(), "2025");
+        for invalid in ["2025-Q5", "2025-W54", "2025-13", "25-Q1", "2025-", "Q1", "2025-Q1x"] {
+            assert!(CatalogPeriod::parse(invalid).is_err(), "{invalid} accepted");
+        }
+
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 12, 30).unwrap();
+        assert_eq!(CatalogPeriod::quarter_of(date).as_str(), "2024-Q4");
+        assert_eq!(CatalogPeriod::iso_week_of(date).as_str(), "2025-W01");
+
+        let period = CatalogPeriod::parse("2025-Q1").unwrap();
+        assert!(period.matches("2025-q1") && period.matches("2025"));
+        assert!(!period.matches("2025-Q2") && !period.matches("2024"));
+        assert_eq!(serde_json::to_value(&period).unwrap(), "2025-Q1");
+        assert!(serde_json::from_value::<CatalogPeriod>(serde_json::json!("2025-Q9")).is_err());
+    }
+
     #[test]
     fn test_party_id_from_optional() {
         assert!(matches!(
@@ -275,5 +509,38 @@ mod tests {
         assert!(json.contains("T1234567890"));
         assert!(json.contains("Test Song"));
     }
-}
 
+    #[test]
+    fn test_party_id_dedot_format() {
+        use serde_json::json;
+
+        let cases = [
+            (PartyId::Ipi(123456789), json!({"type": "Ipi", "value": 123456789})),
+            (PartyId::Isni("0000000121464388".into()), json!({"type": "Isni", "value": "0000000121464388"})),
+            (
+                PartyId::Both { ipi: 123456789, isni: "0000000121464388".into() },
+                json!({"type": "Both", "value": {"ipi": 123456789, "isni": "0000000121464388"}}),
+            ),
+        ];
+
+        for (id, expected) in cases {
+            assert_eq!(serde_json::to_value(&id).unwrap(), expected);
+            assert_eq!(serde_json::from_value::<PartyId>(expected).unwrap(), id);
+        }
+    }
+
+    #[test]
+    fn test_party_ids_pass_grouped_schema() {
+        let ids = [
+            PartyId::Ipi(123456789),
+            PartyId::Isni("0000000121464388".into()),
+            PartyId::Both { ipi: 123456789, isni: "0000000121464388".into() },
+        ];
+
+        for id in ids {
+            let mut work = GroupedWork::new("T1234567890".into(), "Test Song".into());
+            work.add_creator(Creator { id, role: CreatorRole::Composer, name: None, shares: None });
+            crate::validation::validate_musical_work_grouped(&work.to_value()).unwrap();
+        }
+    }
+}
@@ -0,0 +1,329 @@
+//! End-of-run notifications (webhook, email)
+//!
+//! Long runs post a [`RunSummary`] when they complete or fail, so nobody has
+//! to watch the terminal. Configured in the `notifications` section of the
+//! configuration file (see [`crate::config`]). A failed notification is
+//! reported but never fails the run.
+//!
+//! Emails are sent in plain SMTP without authentication nor TLS, meant for a
+//! local relay (Postfix, msmtp) that forwards them.
+
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::models::CatalogPeriod;
+use crate::transform::pipeline::PipelineResult;
+
+/// Timeout of each notification (HTTP request, SMTP exchange)
+const NOTIFY_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// When to notify
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifyOn {
+    /// Every run, completed or failed
+    #[default]
+    Always,
+    /// Failed runs and runs without any valid record
+    Failure,
+}
+
+/// `notifications` section of the configuration file
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NotifyConfig {
+    #[serde(default)]
+    pub on: NotifyOn,
+    /// JSON summary POSTed to this URL
+    #[serde(default)]
+    pub webhook: Option<WebhookConfig>,
+    /// Summary emailed through this relay
+    #[serde(default)]
+    pub smtp: Option<SmtpConfig>,
+}
+
+/// `events` section of the configuration file: job lifecycle events of the
+/// HTTP server, each POSTed to the webhook as it happens
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventsConfig {
+    #[serde(default)]
+    pub webhook: Option<WebhookConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SmtpConfig {
+    pub host: String,
+    #[serde(default = "default_smtp_port")]
+    pub port: u16,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+fn default_smtp_port() -> u16 {
+    25
+}
+
+impl NotifyConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.webhook.is_some() || self.smtp.is_some()
+    }
+}
+
+/// Outcome of a run, as sent to the webhook (and summarized in emails)
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunSummary {
+    /// `run.completed` or `run.failed`
+    pub event: String,
+    /// One-line summary (shown by chat webhooks such as Slack or Teams)
+    pub text: String,
+    pub input: String,
+    pub duration_secs: f64,
+    pub rows: usize,
+    pub works: usize,
+    pub valid: usize,
+    pub invalid: usize,
+    pub skipped: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<String>,
+    /// Accounting period of the catalog
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period: Option<CatalogPeriod>,
+    /// Path of the error report written for this run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_report: Option<String>,
+    /// Error that stopped the run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl RunSummary {
+    /// Summary of a completed run
+    pub fn completed(input: &Path, result: &PipelineResult, duration: Duration, error_report: Option<&Path>) -> Self {
+        let mut summary = Self {
+            event: "run.completed".to_string(),
+            input: input.display().to_string(),
+            duration_secs: duration.as_secs_f64(),
+            rows: result.csv_info.row_count,
+            works: result.grouped.len(),
+            valid: result.valid_count,
+            invalid: result.invalid_count,
+            skipped: result.skipped.len(),
+            template_id: result.template_id.clone(),
+            period: result.period.clone(),
+            error_report: error_report.map(|p| p.display().to_string()),
+            ..Self::default()
+        };
+        let period = summary.period.as_ref().map(|p| format!(" [{}]", p)).unwrap_or_default();
+        summary.text = format!(
+            "massload: {}{} done in {:.0}s, {} works ({} valid, {} invalid, {} rows skipped)",
+            summary.input, period, summary.duration_secs, summary.works, summary.valid, summary.invalid, summary.skipped
+        );
+        summary
+    }
+
+    /// Summary of a run stopped by an error
+    pub fn failed(input: &Path, error: &str, duration: Duration) -> Self {
+        let input = input.display().to_string();
+        Self {
+            event: "run.failed".to_string(),
+            text: format!("massload: {} failed after {:.0}s: {}", input, duration.as_secs_f64(), error),
+            input,
+            duration_secs: duration.as_secs_f64(),
+            error: Some(error.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// Failed run, or rows read without any valid work (same rule as the API's `all_invalid`)
+    pub fn is_failure(&self) -> bool {
+        self.error.is_some() || (self.rows > 0 && self.valid == 0)
+    }
+
+    /// Email subject: a run without any valid work is reported as a failure too
+    pub fn subject(&self) -> String {
+        let outcome = match (self.error.is_some(), self.is_failure()) {
+            (true, _) => "Run failed",
+            (false, true) => "Run failed: no valid work",
+            (false, false) => "Run completed",
+        };
+        format!("[massload] {}", outcome)
+    }
+
+    /// Plain-text email body
+    pub fn email_body(&self) -> String {
+        let mut body = format!("{}\r\n\r\nInput: {}\r\nDuration: {:.1}s\r\n", self.text, self.input, self.duration_secs);
+        match self.error {
+            Some(ref error) => body.push_str(&format!("Error: {}\r\n", error)),
+            None => body.push_str(&format!(
+                "Rows: {}\r\nWorks: {}\r\nValid: {}\r\nInvalid: {}\r\nSkipped rows: {}\r\n",
+                self.rows, self.works, self.valid, self.invalid, self.skipped
+            )),
+        }
+        if let Some(ref id) = self.template_id {
+            body.push_str(&format!("Template: {}\r\n", id));
+        }
+        if let Some(ref period) = self.period {
+            body.push_str(&format!("Period: {}\r\n", period));
+        }
+        if let Some(ref report) = self.error_report {
+            body.push_str(&format!("Error report: {}\r\n", report));
+        }
+        body
+    }
+}
+
+/// Send the summary to every configured target; returns the errors
+pub async fn notify(config: &NotifyConfig, summary: &RunSummary) -> Vec<String> {
+    let mut errors = Vec::new();
+    if config.on == NotifyOn::Failure && !summary.is_failure() {
+        return errors;
+    }
+
+    if let Some(ref webhook) = config.webhook {
+        if let Err(e) = post_webhook(webhook, summary).await {
+            errors.push(format!("webhook {}: {}", webhook.url, e));
+        }
+    }
+
+    if let Some(smtp) = config.smtp.clone() {
+        let subject = summary.subject();
+        let body = summary.email_body();
+        let host = smtp.host.clone();
+        let sent = tokio::task::spawn_blocking(move || send_email(&smtp, &subject, &body)).await;
+        match sent {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => errors.push(format!("smtp {}: {}", host, e)),
+            Err(e) => errors.push(format!("smtp {}: {}", host, e)),
+        }
+    }
+    errors
+}
+
+/// POST a JSON body to the webhook
+pub async fn post_webhook<T: Serialize>(webhook: &WebhookConfig, body: &T) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .timeout(NOTIFY_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let response = client.post(&webhook.url).json(body).send().await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    Ok(())
+}
+
+/// Minimal SMTP exchange (RFC 5321) with a local relay
+fn send_email(smtp: &SmtpConfig, subject: &str, body: &str) -> Result<(), String> {
+    let stream = TcpStream::connect((smtp.host.as_str(), smtp.port)).map_err(|e| e.to_string())?;
+    stream.set_read_timeout(Some(NOTIFY_TIMEOUT)).map_err(|e| e.to_string())?;
+    stream.set_write_timeout(Some(NOTIFY_TIMEOUT)).map_err(|e| e.to_string())?;
+    let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+    let mut writer = stream;
+
+    expect_reply(&mut reader, 220)?;
+    command(&mut writer, &mut reader, "HELO massload", 250)?;
+    command(&mut writer, &mut reader, &format!("MAIL FROM:<{}>", smtp.from), 250)?;
+    for to in &smtp.to {
+        command(&mut writer, &mut reader, &format!("RCPT TO:<{}>", to), 250)?;
+    }
+    command(&mut writer, &mut reader, "DATA", 354)?;
+    let message = format!(
+        "From: {}\r\nTo: {}\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}",
+        smtp.from, smtp.to.join(", "), subject, dot_stuff(&crlf_lines(body))
+    );
+    command(&mut writer, &mut reader, &format!("{}\r\n.", message), 250)?;
+    command(&mut writer, &mut reader, "QUIT", 221)
+}
+
+fn command(writer: &mut TcpStream, reader: &mut BufReader<TcpStream>, line: &str, expected: u16) -> Result<(), String> {
+    writer.write_all(format!("{}\r\n", line).as_bytes()).map_err(|e| e.to_string())?;
+    expect_reply(reader, expected)
+}
+
+/// Read a (possibly multi-line) reply and check its code
+fn expect_reply(reader: &mut BufReader<TcpStream>, expected: u16) -> Result<(), String> {
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).map_err(|e| e.to_string())?;
+        let code: u16 = line.get(..3).and_then(|c| c.parse().ok())
+            .ok_or_else(|| format!("unexpected reply: {}", line.trim()))?;
+        if line.as_bytes().get(3) == Some(&b'-') {
+            continue;
+        }
+        if code != expected {
+            return Err(format!("expected {}, got: {}", expected, line.trim()));
+        }
+        return Ok(());
+    }
+}
+
+/// Turn bare `\n` and `\r` (e.g. in a multi-line error) into `\r\n` line endings
+fn crlf_lines(body: &str) -> String {
+    body.replace("\r\n", "\n").replace('\r', "\n").replace('\n', "\r\n")
+}
+
+/// Escape lines starting with a dot (RFC 5321 §4.5.2)
+fn dot_stuff(body: &str) -> String {
+    body.split("\r\n")
+        .map(|line| if line.starts_with('.') { format!(".{}", line) } else { line.to_string() })
+        .collect::<Vec<_>>()
+        .join("\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_notify_config_and_summary() {
+        let config = Config::from_yaml(
+            "notifications:\n  on: failure\n  webhook:\n    url: http://localhost/hook\n  smtp:\n    host: localhost\n    from: a@example.com\n    to: [b@example.com]\n",
+        ).unwrap();
+        let notifications = config.notifications;
+        assert_eq!(notifications.on, NotifyOn::Failure);
+        assert_eq!(notifications.smtp.as_ref().unwrap().port, 25);
+        assert!(notifications.is_enabled());
+        assert!(Config::from_yaml("").unwrap().notifications.webhook.is_none());
+        assert!(Config::from_yaml("notifications:\n  slack: x\n").is_err());
+
+        let failed = RunSummary::failed(Path::new("catalog.csv"), "AI error", Duration::from_secs(3));
+        assert!(failed.is_failure());
+        assert!(failed.email_body().contains("Error: AI error"));
+        let json = serde_json::to_value(&failed).unwrap();
+        assert_eq!(json["event"], "run.failed");
+        assert!(json.get("templateId").is_none());
+
+        assert_eq!(dot_stuff("a\r\n.b"), "a\r\n..b");
+        assert_eq!(failed.subject(), "[massload] Run failed");
+
+        // A multi-line error cannot end the DATA section early
+        let failed = RunSummary::failed(Path::new("catalog.csv"), "line 1\n.\nline 3", Duration::from_secs(1));
+        let data = dot_stuff(&crlf_lines(&failed.email_body()));
+        assert!(data.contains("Error: line 1\r\n..\r\nline 3\r\n"));
+        assert!(!data.replace("\r\n", "").contains('\n'));
+
+        let all_invalid = RunSummary { rows: 3, works: 3, invalid: 3, ..RunSummary::default() };
+        assert!(all_invalid.is_failure());
+        assert_eq!(all_invalid.subject(), "[massload] Run failed: no valid work");
+        assert_eq!(RunSummary { rows: 3, works: 3, valid: 3, ..RunSummary::default() }.subject(), "[massload] Run completed");
+
+        // Every row skipped: no work at all, still a failure
+        let all_skipped = RunSummary { rows: 3, skipped: 3, ..RunSummary::default() };
+        assert!(all_skipped.is_failure());
+        assert_eq!(all_skipped.subject(), "[massload] Run failed: no valid work");
+        assert!(!RunSummary::default().is_failure());
+    }
+}
@@ -0,0 +1,169 @@
+//! Compressed inputs: gzip files and single-entry zip archives.
+//!
+//! Catalog exports often arrive zipped. The archive is unpacked before
+//! anything else reads the content (format sniffing, encoding detection), and
+//! the name of the file inside replaces the archive name, so templates are
+//! still named after `catalogue.csv`, not `catalogue.zip`.
+//!
+//! An `.xlsx` workbook is itself a zip archive: it is recognized by its
+//! `[Content_Types].xml` entry and left untouched.
+
+use flate2::read::GzDecoder;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use zip::ZipArchive;
+
+use super::CsvError;
+
+/// Largest decompressed size accepted (guards against zip bombs)
+pub const MAX_UNPACKED_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Largest decompressed size of an upload, as a multiple of its compressed size
+pub const MAX_UPLOAD_INFLATION: u64 = 50;
+
+/// Compression of an input file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zip,
+}
+
+/// Input content once decompressed
+#[derive(Debug, Clone)]
+pub struct Unpacked {
+    pub bytes: Vec<u8>,
+    /// Name of the file inside the archive (the given name when not compressed)
+    pub file_name: Option<String>,
+    /// Compression removed, if any
+    pub compression: Option<Compression>,
+}
+
+/// Whether `bytes` start with the gzip magic number
+pub fn is_gzip(bytes: &[u8]) -> bool {
+    bytes.starts_with(&[0x1f, 0x8b])
+}
+
+/// Decompress a gzip file or a single-entry zip archive; other content
+/// (workbooks included) is returned as is.
+///
+/// The inner name comes from the zip entry, the gzip header, or the outer
+/// name without its `.gz` extension.
+pub fn unpack(bytes: Vec<u8>, file_name: Option<&str>) -> Result<Unpacked, CsvError> {
+    unpack_within(bytes, file_name, MAX_UNPACKED_BYTES)
+}
+
+/// [`unpack`] refusing more than `max_bytes` of decompressed content: the
+/// limit is checked while inflating, so a small bomb never fills memory
+pub fn unpack_within(bytes: Vec<u8>, file_name: Option<&str>, max_bytes: u64) -> Result<Unpacked, CsvError> {
+    if is_gzip(&bytes) {
+        let mut decoder = GzDecoder::new(&bytes[..]);
+        let content = read_limited(&mut decoder, max_bytes)?;
+        let inner = decoder.header()
+            .and_then(|h| h.filename())
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+            .or_else(|| file_name.map(strip_gz_extension));
+        return Ok(Unpacked { bytes: content, file_name: inner, compression: Some(Compression::Gzip) });
+    }
+
+    if bytes.starts_with(b"PK\x03\x04") {
+        let mut archive = ZipArchive::new(Cursor::new(&bytes[..]))
+            .map_err(|e| CsvError::new(0, format!("Cannot read zip archive: {}", e)))?;
+        if archive.index_for_name("[Content_Types].xml").is_none() {
+            let entries: Vec<usize> = (0..archive.len())
+                .filter(|&i| archive.name_for_index(i).is_some_and(is_content_entry))
+                .collect();
+            let [index] = entries[..] else {
+                return Err(CsvError::new(0, format!(
+                    "Zip archive holds {} files: upload a single file per archive", entries.len())));
+            };
+            let mut entry = archive.by_index(index)
+                .map_err(|e| CsvError::new(0, format!("Cannot read zip archive: {}", e)))?;
+            let inner = Path::new(entry.name()).file_name().map(|n| n.to_string_lossy().into_owned());
+            let content = read_limited(&mut entry, max_bytes)?;
+            return Ok(Unpacked { bytes: content, file_name: inner, compression: Some(Compression::Zip) });
+        }
+    }
+
+    Ok(Unpacked { bytes, file_name: file_name.map(String::from), compression: None })
+}
+
+/// Entries worth reading: files, without macOS metadata (`__MACOSX/`, `.DS_Store`)
+fn is_content_entry(name: &str) -> bool {
+    let base = name.rsplit('/').next().unwrap_or(name);
+    !name.ends_with('/') && !name.starts_with("__MACOSX/") && !base.is_empty() && !base.starts_with('.')
+}
+
+/// `catalogue.csv.gz` → `catalogue.csv`
+fn strip_gz_extension(name: &str) -> String {
+    let lower = name.to_lowercase();
+    for ext in [".gz", ".gzip"] {
+        if lower.ends_with(ext) {
+            return name[..name.len() - ext.len()].to_string();
+        }
+    }
+    name.to_string()
+}
+
+/// Read decompressed content, refusing more than `max_bytes`
+fn read_limited(reader: &mut impl Read, max_bytes: u64) -> Result<Vec<u8>, CsvError> {
+    let mut content = Vec::new();
+    reader.take(max_bytes + 1).read_to_end(&mut content)
+        .map_err(|e| CsvError::new(0, format!("Cannot decompress file: {}", e)))?;
+    if content.len() as u64 > max_bytes {
+        return Err(CsvError::new(0, format!("Decompressed file exceeds {} bytes", max_bytes)));
+    }
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+    use zip::write::{SimpleFileOptions, ZipWriter};
+
+    fn zip(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in entries {
+            writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_unpack_gzip_and_single_entry_zip() {
+        let csv = "Titre;ISWC\nLa Vie en rose;T1234567890\n";
+
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(csv.as_bytes()).unwrap();
+        let unpacked = unpack(encoder.finish().unwrap(), Some("catalogue.CSV.gz")).unwrap();
+        assert_eq!(unpacked.bytes, csv.as_bytes());
+        assert_eq!(unpacked.file_name.as_deref(), Some("catalogue.CSV"));
+        assert_eq!(unpacked.compression, Some(Compression::Gzip));
+
+        let archive = zip(&[("export/sacem-2024.csv", csv), ("__MACOSX/export/._sacem-2024.csv", "x")]);
+        let unpacked = unpack(archive, Some("export.zip")).unwrap();
+        assert_eq!(unpacked.bytes, csv.as_bytes());
+        assert_eq!(unpacked.file_name.as_deref(), Some("sacem-2024.csv"));
+
+        let err = unpack(zip(&[("a.csv", csv), ("b.csv", csv)]), Some("two.zip")).unwrap_err();
+        assert!(err.to_string().contains("2 files"));
+
+        // Workbooks are zip archives too: left for the xlsx reader
+        let workbook = zip(&[("[Content_Types].xml", "<Types/>"), ("xl/workbook.xml", "<workbook/>")]);
+        let unpacked = unpack(workbook.clone(), Some("catalogue.xlsx")).unwrap();
+        assert_eq!(unpacked.bytes, workbook);
+        assert_eq!(unpacked.compression, None);
+
+        // Bomb: refused as soon as the limit is passed
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::best());
+        encoder.write_all(&vec![b';'; 1024 * 1024]).unwrap();
+        let err = unpack_within(encoder.finish().unwrap(), None, 64 * 1024).unwrap_err();
+        assert!(err.to_string().contains("exceeds 65536 bytes"));
+
+        let plain = unpack(csv.as_bytes().to_vec(), Some("catalogue.csv")).unwrap();
+        assert_eq!(plain.file_name.as_deref(), Some("catalogue.csv"));
+        assert_eq!(plain.compression, None);
+    }
+}
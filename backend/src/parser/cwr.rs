@@ -0,0 +1,244 @@
+//! CWR (Common Works Registration) v2.1 / v2.2 files.
+//!
+//! CWR is the fixed-width interchange format publishers send to societies.
+//! Each work transaction (`NWR`, `REV`, `ISW`, `EXC`) is followed by its
+//! publishers (`SPU`, `OPU`) and writers (`SWR`, `OWR`); every interested
+//! party becomes one flat MIDDS record, the work fields repeated on each, so
+//! the file skips the matrix step like flat JSON records do.
+//!
+//! Only the fields the flat schema knows are read. Income participants
+//! (`PA`) are not creators and are left out.
+
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+use super::{decode_content, resolve_encoding, CsvError, EncodingGuess, ParseDiagnostics, ParseResult};
+
+/// Work transactions: a new work starts at each of these records
+const WORK_RECORDS: [&str; 4] = ["NWR", "REV", "ISW", "EXC"];
+
+/// Publisher records (controlled, then other publishers)
+const PUBLISHER_RECORDS: [&str; 2] = ["SPU", "OPU"];
+
+/// Writer records (controlled, then other writers)
+const WRITER_RECORDS: [&str; 2] = ["SWR", "OWR"];
+
+/// Columns of the records produced, in flat schema order
+const CWR_COLUMNS: [&str; 14] = [
+    "iswc", "title", "instrumental", "language", "workType", "creatorIpi", "creatorRole",
+    "creatorName", "creatorShare", "creatorPerformanceShare", "creatorMechanicalShare",
+    "opus", "catalogNumber", "workKey",
+];
+
+/// Writer designation codes mapped to flat MIDDS roles (`PA` is not a creator)
+const WRITER_ROLES: &[(&str, &str)] = &[
+    ("C", "Composer"), ("CA", "Composer"), ("A", "Author"), ("SA", "Author"),
+    ("AR", "Arranger"), ("SR", "Arranger"), ("AD", "Adapter"), ("TR", "Adapter"),
+];
+
+/// Publisher types mapped to flat MIDDS roles (`PA` is not a creator)
+const PUBLISHER_ROLES: &[(&str, &str)] = &[
+    ("E", "Publisher"), ("SE", "Publisher"), ("ES", "Publisher"), ("AM", "Publisher"), ("AQ", "Publisher"),
+];
+
+/// ISO 639-1 codes of the languages the flat schema accepts
+const LANGUAGES: &[(&str, &str)] = &[
+    ("EN", "English"), ("FR", "French"), ("ES", "Spanish"), ("DE", "German"), ("IT", "Italian"),
+    ("PT", "Portuguese"), ("RU", "Russian"), ("ZH", "Chinese"), ("JA", "Japanese"), ("KO", "Korean"),
+    ("AR", "Arabic"), ("HI", "Hindi"), ("NL", "Dutch"), ("SV", "Swedish"), ("NO", "Norwegian"),
+    ("FI", "Finnish"), ("PL", "Polish"), ("TR", "Turkish"), ("HE", "Hebrew"), ("EL", "Greek"),
+    ("LA", "Latin"), ("EO", "Esperanto"),
+];
+
+/// Whether the content starts with a CWR transmission header
+pub fn is_cwr(bytes: &[u8]) -> bool {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    bytes.starts_with(b"HDR") && bytes.get(59..64) == Some(b"01.10".as_slice())
+}
+
+/// CWR version of a transmission header (`2.1` unless a v2.2 header says otherwise)
+fn cwr_version(header: &str) -> String {
+    match field(header, 101, 3).as_str() {
+        "" => "2.1".to_string(),
+        version => version.to_string(),
+    }
+}
+
+/// Parse CWR bytes into flat MIDDS records (encoding detected unless forced)
+pub fn parse_cwr(bytes: &[u8], encoding: Option<&str>) -> Result<ParseResult, CsvError> {
+    let EncodingGuess { encoding, confidence } = resolve_encoding(bytes, encoding)?;
+    let content = decode_content(bytes, &encoding)?;
+    let mut lines = content.lines().enumerate().filter(|(_, l)| !l.trim().is_empty());
+
+    let header = lines.next().map(|(_, l)| l).filter(|l| l.starts_with("HDR"))
+        .ok_or_else(|| CsvError::new(1, "Not a CWR file: missing HDR record"))?;
+    let version = cwr_version(header);
+    if !["2.1", "2.2"].contains(&version.as_str()) {
+        return Err(CsvError::new(1, format!("Unsupported CWR version: {}", version)));
+    }
+
+    let mut records = Vec::new();
+    let mut record_lines = Vec::new();
+    let mut work: Option<Map<String, Value>> = None;
+    for (index, line) in lines {
+        let record_type = line.get(..3).unwrap_or(line);
+        if WORK_RECORDS.contains(&record_type) {
+            work = Some(work_fields(line));
+        } else if PUBLISHER_RECORDS.contains(&record_type) || WRITER_RECORDS.contains(&record_type) {
+            let base = work.as_ref().ok_or_else(|| {
+                CsvError::new(index + 1, format!("{} record outside of a work transaction", record_type))
+                    .with_value(record_type)
+            })?;
+            let party = if PUBLISHER_RECORDS.contains(&record_type) { publisher(line) } else { writer(line) };
+            if let Some(party) = party {
+                let mut record = base.clone();
+                record.extend(party);
+                records.push(Value::Object(record));
+                record_lines.push(index + 1);
+            }
+        } else if record_type == "GRT" || record_type == "TRL" {
+            work = None;
+        }
+    }
+
+    Ok(ParseResult {
+        records,
+        lines: record_lines,
+        encoding,
+        encoding_confidence: confidence,
+        delimiter: ' ',
+        headers: CWR_COLUMNS.iter().map(|c| c.to_string()).collect(),
+        footer_lines: Vec::new(),
+        ragged_rows: Vec::new(),
+        diagnostics: ParseDiagnostics::default(),
+        renamed_columns: Vec::new(),
+        original_headers: BTreeMap::new(),
+        fixed_width: None,
+    })
+}
+
+/// Trimmed field at a 0-based character offset (empty past the end of the line)
+fn field(line: &str, start: usize, len: usize) -> String {
+    line.chars().skip(start).take(len).collect::<String>().trim().to_string()
+}
+
+/// Work fields of an `NWR`/`REV`/`ISW`/`EXC` record
+fn work_fields(line: &str) -> Map<String, Value> {
+    let mut work = Map::new();
+    work.insert("iswc".into(), field(line, 95, 11).into());
+    work.insert("title".into(), field(line, 19, 60).into());
+    // Submitter work number: groups the parties of a work without ISWC
+    let submitter_id = field(line, 81, 14);
+    if !submitter_id.is_empty() {
+        work.insert("workKey".into(), submitter_id.into());
+    }
+    if let Some((_, language)) = LANGUAGES.iter().find(|(code, _)| *code == field(line, 79, 2)) {
+        work.insert("language".into(), (*language).into());
+    }
+    match field(line, 136, 3).as_str() {
+        "MUS" => { work.insert("instrumental".into(), true.into()); }
+        "MTX" | "TXT" => { work.insert("instrumental".into(), false.into()); }
+        _ => {}
+    }
+    if field(line, 142, 3) == "ORI" {
+        work.insert("workType".into(), "Original".into());
+    }
+    for (name, start) in [("opus", 209), ("catalogNumber", 234)] {
+        let value = field(line, start, 25);
+        if !value.is_empty() {
+            work.insert(name.into(), value.into());
+        }
+    }
+    work
+}
+
+/// Party fields shared by writers and publishers (the PR share doubles as
+/// the ownership share)
+fn party(role: &str, name: String, ipi: String, pr_share: String, mr_share: String) -> Map<String, Value> {
+    let mut party = Map::new();
+    party.insert("creatorRole".into(), role.into());
+    if let Ok(ipi) = ipi.parse::<u64>() {
+        party.insert("creatorIpi".into(), ipi.into());
+    }
+    if !name.is_empty() {
+        party.insert("creatorName".into(), name.into());
+    }
+    // Shares are 5 digits with 2 implied decimals (05000 = 50.00 %)
+    let percent = |share: &str| share.parse::<u32>().ok().map(|share| f64::from(share) / 100.0);
+    if let Some(share) = percent(&pr_share) {
+        party.insert("creatorShare".into(), share.into());
+        party.insert("creatorPerformanceShare".into(), share.into());
+    }
+    if let Some(share) = percent(&mr_share) {
+        party.insert("creatorMechanicalShare".into(), share.into());
+    }
+    party
+}
+
+/// Writer of an `SWR`/`OWR` record (`None` for income participants)
+fn writer(line: &str) -> Option<Map<String, Value>> {
+    let designation = field(line, 104, 2);
+    let (_, role) = WRITER_ROLES.iter().find(|(code, _)| *code == designation)?;
+    let name = format!("{} {}", field(line, 73, 30), field(line, 28, 45)).trim().to_string();
+    Some(party(role, name, field(line, 115, 11), field(line, 129, 5), field(line, 137, 5)))
+}
+
+/// Publisher of an `SPU`/`OPU` record (`None` for income participants)
+fn publisher(line: &str) -> Option<Map<String, Value>> {
+    let publisher_type = field(line, 76, 2);
+    let (_, role) = PUBLISHER_ROLES.iter().find(|(code, _)| *code == publisher_type)?;
+    Some(party(role, field(line, 30, 45), field(line, 87, 11), field(line, 115, 5), field(line, 123, 5)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A CWR record: fields placed at their 0-based offsets, padded with spaces
+    fn record(fields: &[(usize, &str)]) -> String {
+        let mut line = String::new();
+        for (start, value) in fields {
+            while line.chars().count() < *start {
+                line.push(' ');
+            }
+            line.push_str(value);
+        }
+        line
+    }
+
+    #[test]
+    fn test_parse_cwr_parties_to_flat_records() {
+        let cwr = [
+            record(&[(0, "HDRPB123456789ALLFEAT PUBLISHING"), (59, "01.1020240101120000")]),
+            record(&[(0, "GRHNWR0000102.10")]),
+            record(&[(0, "NWR0000000000000000"), (19, "LA VIE EN ROSE"), (79, "FR"), (81, "WRK-001"),
+                (95, "T1234567890"), (136, "MTX"), (142, "ORI")]),
+            record(&[(0, "SPU0000000000000001"), (19, "01"), (21, "P001"), (30, "EDITIONS ROSE"),
+                (76, "E"), (87, "00123456789"), (112, "05805000"), (120, "05810000")]),
+            record(&[(0, "SWR0000000000000002"), (19, "W001"), (28, "PIAF"), (73, "EDITH"),
+                (104, "CA"), (115, "00987654321"), (126, "05802500")]),
+            record(&[(0, "SWR0000000000000003"), (19, "W002"), (28, "MONNOT"), (73, "MARGUERITE"),
+                (104, "PA"), (115, "00111111111")]),
+            record(&[(0, "GRT000010000000100000005")]),
+            record(&[(0, "TRL000010000000100000007")]),
+        ].join("\r\n");
+        assert!(is_cwr(cwr.as_bytes()));
+
+        let result = parse_cwr(cwr.as_bytes(), None).unwrap();
+        assert_eq!(result.records.len(), 2);
+        assert_eq!(result.records[0], json!({
+            "iswc": "T1234567890", "title": "LA VIE EN ROSE", "workKey": "WRK-001", "language": "French",
+            "instrumental": false, "workType": "Original",
+            "creatorRole": "Publisher", "creatorIpi": 123456789, "creatorName": "EDITIONS ROSE", "creatorShare": 50.0,
+            "creatorPerformanceShare": 50.0, "creatorMechanicalShare": 100.0,
+        }));
+        assert_eq!(result.records[1]["creatorName"], "EDITH PIAF");
+        assert_eq!(result.records[1]["creatorRole"], "Composer");
+        assert_eq!(result.records[1]["creatorShare"], 25.0);
+        assert!(result.records[1].get("creatorMechanicalShare").is_none());
+
+        let orphan = [cwr.lines().next().unwrap(), &record(&[(0, "SWR0000000000000001")])].join("\n");
+        assert!(parse_cwr(orphan.as_bytes(), None).is_err());
+    }
+}
@@ -1,11 +1,33 @@
 //! Generic CSV to JSON parser with encoding and delimiter auto-detection.
 //!
 //! Converts CSV rows into JSON objects. No MIDDS-specific logic here.
+//!
+//! Delimited files are read as RFC 4180 records (through the `csv` crate):
+//! quoted fields may hold the delimiter, `""` escaped quotes and line breaks.
 
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Map, Value};
-use std::io::{BufRead, BufReader, Read};
+use std::collections::BTreeMap;
+use std::io::Read;
 use std::path::Path;
 
+use crate::api::logs::LogStage;
+use crate::progress::StageProgress;
+
+pub mod archive;
+pub mod cwr;
+pub mod profile;
+pub mod sampling;
+pub mod stream;
+pub mod xlsx;
+
+pub use archive::{is_gzip, unpack, unpack_within, Compression, Unpacked, MAX_UNPACKED_BYTES, MAX_UPLOAD_INFLATION};
+pub use cwr::{is_cwr, parse_cwr};
+pub use profile::{profile_columns, ColumnProfile, ParseReport, PatternCount};
+pub use sampling::{sample_rows, SampleOptions};
+pub use stream::{parse_csv_stream, CsvStream};
+pub use xlsx::{is_xlsx, parse_xlsx, XLSX_DELIMITER};
+
 /// CSV parsing error with context
 #[derive(Debug, Clone)]
 pub struct CsvError {
@@ -59,61 +81,726 @@ impl CsvError {
 pub struct ParseResult {
     /// Parsed records as JSON objects
     pub records: Vec<Value>,
+    /// 1-based line of each record in the file, parallel to `records`
+    /// (empty when the source has no lines, e.g. a JSON array)
+    pub lines: Vec<usize>,
     /// Detected or used encoding
     pub encoding: String,
-    /// Detected or used delimiter
+    /// Decode-quality score of the detected encoding (1.0 = certain or forced)
+    pub encoding_confidence: f32,
+    /// Detected or used delimiter (`' '` for fixed-width text, [`JSON_DELIMITER`] for JSON)
     pub delimiter: char,
     /// Column headers
     pub headers: Vec<String>,
+    /// Trailing summary lines dropped by footer detection or `skip_last`
+    pub footer_lines: Vec<String>,
+    /// Rows whose cell count differs from the header (strict modes only)
+    pub ragged_rows: Vec<RaggedRow>,
+    /// Input repairs made before parsing (NUL characters, mixed line endings)
+    pub diagnostics: ParseDiagnostics,
+    /// Duplicate headers that were renamed: (original name, new name)
+    pub renamed_columns: Vec<(String, String)>,
+    /// Headers changed by [`normalize_header`]: normalized name → name in the file
+    pub original_headers: BTreeMap<String, String>,
+    /// Column layout when the file was read as fixed-width text
+    pub fixed_width: Option<Vec<FixedWidthColumn>>,
+}
+
+/// A column of a fixed-width (positional) text file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixedWidthColumn {
+    pub name: String,
+    /// Offset of the first character (0-based, in characters)
+    pub start: usize,
+    /// Width in characters
+    pub len: usize,
+}
+
+impl FixedWidthColumn {
+    pub fn new(name: impl Into<String>, start: usize, len: usize) -> Self {
+        Self { name: name.into(), start, len }
+    }
+
+    /// Parse a layout spec: `"iswc:0:15,title:15:40"` (name:start:len, comma-separated)
+    pub fn parse_layout(spec: &str) -> Result<Vec<Self>, String> {
+        let columns: Vec<Self> = spec
+            .split(',')
+            .filter(|c| !c.trim().is_empty())
+            .map(|column| {
+                let parts: Vec<&str> = column.trim().rsplitn(3, ':').collect();
+                match parts.as_slice() {
+                    [len, start, name] if !name.trim().is_empty() => Ok(Self::new(
+                        name.trim(),
+                        start.trim().parse().map_err(|_| format!("Invalid start in \"{}\"", column))?,
+                        len.trim().parse().map_err(|_| format!("Invalid length in \"{}\"", column))?,
+                    )),
+                    _ => Err(format!("Expected name:start:len, got \"{}\"", column)),
+                }
+            })
+            .collect::<Result<_, _>>()?;
+
+        if columns.is_empty() {
+            return Err("Empty fixed-width layout".to_string());
+        }
+        Ok(columns)
+    }
+
+    /// Layout spec read back by [`Self::parse_layout`]
+    pub fn layout_spec(columns: &[Self]) -> String {
+        columns.iter()
+            .map(|c| format!("{}:{}:{}", c.name, c.start, c.len))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Trimmed value of this column in a line (empty past the end of the line)
+    fn slice(&self, line: &str) -> String {
+        line.chars().skip(self.start).take(self.len).collect::<String>().trim().to_string()
+    }
+}
+
+/// Delimiter reported for JSON input, which has none
+pub const JSON_DELIMITER: char = '\0';
+
+/// Format of an input file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputFormat {
+    /// JSON when the content starts with `[` or `{`, CSV otherwise
+    #[default]
+    Auto,
+    Csv,
+    /// JSON array of objects or NDJSON (one object per line)
+    Json,
+    /// Excel workbook (`.xlsx`), first worksheet
+    Xlsx,
+    /// CWR v2.1 / v2.2 registration file (parties become flat MIDDS records)
+    Cwr,
+}
+
+impl InputFormat {
+    /// Format implied by a MIME type or file name (Auto when neither tells)
+    pub fn from_hints(content_type: Option<&str>, file_name: Option<&str>) -> Self {
+        let content_type = content_type.unwrap_or("").to_lowercase();
+        let extension = file_name
+            .and_then(|n| n.rsplit_once('.'))
+            .map(|(_, ext)| ext.to_lowercase())
+            .unwrap_or_default();
+
+        if content_type.contains("json") || ["json", "ndjson", "jsonl"].contains(&extension.as_str()) {
+            Self::Json
+        } else if content_type.contains("spreadsheetml") || extension == "xlsx" {
+            Self::Xlsx
+        } else if ["cwr", "v21", "v22"].contains(&extension.as_str()) {
+            Self::Cwr
+        } else if content_type.contains("csv") || ["csv", "tsv", "txt"].contains(&extension.as_str()) {
+            Self::Csv
+        } else {
+            Self::Auto
+        }
+    }
+
+    /// Resolve `Auto` by sniffing the content: a ZIP archive is a workbook, a
+    /// CWR header a CWR file, else the first non-blank character tells JSON from CSV
+    pub fn resolve(self, bytes: &[u8]) -> Self {
+        if self != Self::Auto {
+            return self;
+        }
+        if is_xlsx(bytes) {
+            return Self::Xlsx;
+        }
+        if is_cwr(bytes) {
+            return Self::Cwr;
+        }
+        let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+        match bytes.iter().find(|b| !b.is_ascii_whitespace()) {
+            Some(b'[') | Some(b'{') => Self::Json,
+            _ => Self::Csv,
+        }
+    }
+}
+
+impl std::str::FromStr for InputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "csv" => Ok(Self::Csv),
+            "json" | "ndjson" => Ok(Self::Json),
+            "xlsx" => Ok(Self::Xlsx),
+            "cwr" => Ok(Self::Cwr),
+            other => Err(format!("Unknown input format \"{}\" (expected auto, csv, json, xlsx or cwr)", other)),
+        }
+    }
+}
+
+/// Handling of rows with more or fewer cells than the header
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RaggedRows {
+    /// Pad missing cells with empty strings and drop extra ones, silently
+    #[default]
+    Pad,
+    /// Pad or truncate, and report each row with its line number
+    Warn,
+    /// Leave the rows out of the records, and report them
+    Reject,
+}
+
+impl std::str::FromStr for RaggedRows {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pad" => Ok(Self::Pad),
+            "warn" => Ok(Self::Warn),
+            "reject" => Ok(Self::Reject),
+            other => Err(format!("Unknown ragged rows mode \"{}\" (expected pad, warn or reject)", other)),
+        }
+    }
+}
+
+/// A data row whose cell count differs from the header
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RaggedRow {
+    /// 1-based line of the file where the row starts
+    pub line: usize,
+    /// Cells in the row (trailing empty cells past the header not counted)
+    pub cells: usize,
+    /// Columns of the header
+    pub expected: usize,
+    /// Left out of the records (`reject` mode)
+    pub rejected: bool,
+}
+
+impl std::fmt::Display for RaggedRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let outcome = match (self.rejected, self.cells < self.expected) {
+            (true, _) => "rejected",
+            (false, true) => "padded with empty cells",
+            (false, false) => "extra cells dropped",
+        };
+        write!(f, "line {}: {} cells instead of {}, {}", self.line, self.cells, self.expected, outcome)
+    }
+}
+
+/// Options controlling which lines of the file are treated as data
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Lines to skip before the header row (titles, export banners)
+    pub skip_first: usize,
+    /// Header rows merged into composite column names (group row above the field row)
+    pub header_rows: usize,
+    /// Lines to drop at the end of the file
+    pub skip_last: usize,
+    /// Drop trailing rows that are summaries ("Total: 1234 œuvres") or whose
+    /// cell count differs from the header
+    pub detect_footer: bool,
+    /// Force the encoding instead of detecting it
+    pub encoding: Option<String>,
+    /// Force the delimiter instead of detecting it (no fixed-width detection either)
+    pub delimiter: Option<char>,
+    /// Read the file as fixed-width text with this layout (every line is data)
+    pub fixed_width: Option<Vec<FixedWidthColumn>>,
+    /// Rows with more or fewer cells than the header (delimited files)
+    pub ragged_rows: RaggedRows,
+}
+
+impl ParseOptions {
+    /// Read the header from 1-based line `line`: the lines above it are skipped
+    pub fn with_header_row(mut self, line: usize) -> Self {
+        self.skip_first = line.saturating_sub(1);
+        self
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            skip_first: 0,
+            header_rows: 1,
+            skip_last: 0,
+            detect_footer: true,
+            encoding: None,
+            delimiter: None,
+            fixed_width: None,
+            ragged_rows: RaggedRows::default(),
+        }
+    }
+}
+
+/// Maximum number of trailing lines footer detection may drop
+const MAX_FOOTER_LINES: usize = 5;
+
+/// First words of a summary row ("Total", "Nombre d'œuvres: 1234")
+const SUMMARY_WORDS: &[&str] = &[
+    "total", "totals", "totaux", "sous-total", "subtotal", "nombre", "nb", "count", "somme", "summe", "gesamt", "anzahl",
+];
+
+impl ParseResult {
+    /// Column statistics of the records (fill rate, distinct values, samples)
+    pub fn report(&self) -> ParseReport {
+        ParseReport::new(&self.headers, &self.records)
+    }
+
+    /// Turn every value into text, as CSV cells are (null → empty string,
+    /// numbers, booleans and nested values → their JSON text)
+    pub fn stringify_values(&mut self) {
+        for record in &mut self.records {
+            if let Value::Object(obj) = record {
+                for value in obj.values_mut() {
+                    *value = match value.take() {
+                        Value::Null => json!(""),
+                        Value::String(s) => Value::String(s),
+                        other => Value::String(other.to_string()),
+                    };
+                }
+            }
+        }
+    }
+
+    /// Remove the given columns from headers and every record.
+    ///
+    /// Matching is case-insensitive and ignores surrounding whitespace.
+    /// Returns the header names that were actually removed.
+    pub fn exclude_columns(&mut self, columns: &[String]) -> Vec<String> {
+        let wanted: Vec<String> = columns
+            .iter()
+            .map(|c| c.trim().to_lowercase())
+            .filter(|c| !c.is_empty())
+            .collect();
+
+        let removed: Vec<String> = self
+            .headers
+            .iter()
+            .filter(|h| wanted.contains(&h.trim().to_lowercase()))
+            .cloned()
+            .collect();
+
+        if removed.is_empty() {
+            return removed;
+        }
+
+        self.headers.retain(|h| !removed.contains(h));
+        for record in &mut self.records {
+            if let Some(obj) = record.as_object_mut() {
+                for col in &removed {
+                    obj.remove(col);
+                }
+            }
+        }
+
+        removed
+    }
+}
+
+/// SHA-256 of the raw file bytes, as lowercase hex.
+///
+/// Identifies exactly which file version produced a given output.
+pub fn checksum_sha256(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Below this decode-quality score the detected encoding is reported as uncertain
+pub const LOW_ENCODING_CONFIDENCE: f32 = 0.9;
+
+/// Encoding chosen for a file, with a decode-quality score in [0, 1]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncodingGuess {
+    pub encoding: String,
+    pub confidence: f32,
 }
 
 /// Detect the encoding of raw bytes using chardet
 pub fn detect_encoding(bytes: &[u8]) -> String {
-    let result = chardet::detect(bytes);
-    let charset = result.0;
-    
-    // Normalize charset names
+    detect_encoding_with_confidence(bytes).encoding
+}
+
+/// Byte order mark at the start of `bytes`: its encoding and length
+pub fn detect_bom(bytes: &[u8]) -> Option<(&'static str, usize)> {
+    match bytes {
+        [0xEF, 0xBB, 0xBF, ..] => Some(("utf-8", 3)),
+        [0xFF, 0xFE, ..] => Some(("utf-16le", 2)),
+        [0xFE, 0xFF, ..] => Some(("utf-16be", 2)),
+        _ => None,
+    }
+}
+
+/// UTF-16 without BOM: most code units of a catalog are ASCII, so one byte of
+/// each pair is zero (the odd ones in little-endian, the even ones in big-endian)
+fn detect_utf16_without_bom(bytes: &[u8]) -> Option<&'static str> {
+    let pairs = bytes.len() / 2;
+    if pairs < 2 {
+        return None;
+    }
+    let zeros = |offset: usize| bytes.iter().skip(offset).step_by(2).take(pairs).filter(|&&b| b == 0).count();
+    let (even, odd) = (zeros(0), zeros(1));
+    if odd * 2 > pairs && even * 10 < pairs {
+        Some("utf-16le")
+    } else if even * 2 > pairs && odd * 10 < pairs {
+        Some("utf-16be")
+    } else {
+        None
+    }
+}
+
+/// Detect the encoding and score how cleanly the bytes decode with it.
+///
+/// A byte order mark decides (Excel's UTF-8 CSV and "Unicode text" exports),
+/// then UTF-16 is recognized from its zero bytes. Valid UTF-8 always wins. Otherwise Windows-1252, ISO-8859-1 and the chardet
+/// guess are all tried and the best decode-quality score is kept: bytes in
+/// 0x80–0x9F (€, œ, smart quotes in Windows-1252) are C1 control characters
+/// in ISO-8859-1, so they naturally disambiguate the two.
+pub fn detect_encoding_with_confidence(bytes: &[u8]) -> EncodingGuess {
+    if let Some(encoding) = detect_bom(bytes).map(|(encoding, _)| encoding).or_else(|| detect_utf16_without_bom(bytes)) {
+        return EncodingGuess { encoding: encoding.to_string(), confidence: 1.0 };
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return EncodingGuess { encoding: "utf-8".to_string(), confidence: 1.0 };
+    }
+
+    let mut candidates = vec!["windows-1252".to_string(), "iso-8859-1".to_string()];
+    let chardet_guess = normalize_charset(&chardet::detect(bytes).0);
+    if !candidates.contains(&chardet_guess) {
+        candidates.push(chardet_guess);
+    }
+
+    let mut best = EncodingGuess { encoding: candidates[0].clone(), confidence: -1.0 };
+    for encoding in candidates {
+        let confidence = decode_content(bytes, &encoding)
+            .map(|content| decode_quality(&content))
+            .unwrap_or(0.0);
+        if confidence > best.confidence {
+            best = EncodingGuess { encoding, confidence };
+        }
+    }
+
+    best
+}
+
+/// Normalize charset names
+fn normalize_charset(charset: &str) -> String {
     match charset.to_lowercase().as_str() {
         "ascii" | "utf-8" | "utf8" => "utf-8".to_string(),
-        "iso-8859-1" | "iso-8859-15" | "latin-1" | "latin1" => "iso-8859-1".to_string(),
+        "iso-8859-1" | "latin-1" | "latin1" => "iso-8859-1".to_string(),
+        "iso-8859-15" | "latin-9" | "latin9" => "iso-8859-15".to_string(),
         "windows-1252" | "cp1252" => "windows-1252".to_string(),
-        _ => charset,
+        "utf-16" | "utf-16le" | "utf16" | "utf16le" | "ucs-2" => "utf-16le".to_string(),
+        "utf-16be" | "utf16be" => "utf-16be".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Check an encoding given by the user (`--encoding`, `encoding` form field)
+/// and return its normalized name. Unknown labels are an error rather than
+/// a silent UTF-8 fallback that would destroy accented names.
+pub fn check_encoding(label: &str) -> Result<String, CsvError> {
+    let encoding = normalize_charset(label.trim());
+    match encoding.as_str() {
+        "utf-8" | "iso-8859-1" | "iso-8859-15" | "windows-1252" | "utf-16le" | "utf-16be" => Ok(encoding),
+        other if encoding_rs::Encoding::for_label(other.as_bytes()).is_some() => Ok(encoding),
+        _ => Err(CsvError::new(0, format!("Unknown encoding: {}", label.trim()))),
+    }
+}
+
+/// Check a delimiter given by the user (`--delimiter`, `delimiter` form field):
+/// a single ASCII character, or `tab` / `\t` for tabs
+pub fn check_delimiter(value: &str) -> Result<char, CsvError> {
+    let unsupported = || CsvError::new(0, format!("Unsupported delimiter: '{}' (expected one ASCII character or \"tab\")", value));
+    match value {
+        "\t" | "tab" | "TAB" => return Ok('\t'),
+        _ => {}
+    }
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii() && !c.is_ascii_alphanumeric() && c != '"' && c != '\n' && c != '\r' => Ok(c),
+        _ => Err(unsupported()),
     }
 }
 
-/// Decode bytes to string using the specified encoding
+/// Encoding of `bytes`: the forced one when given (detection skipped), else detected
+pub fn resolve_encoding(bytes: &[u8], forced: Option<&str>) -> Result<EncodingGuess, CsvError> {
+    match forced {
+        Some(label) => Ok(EncodingGuess { encoding: check_encoding(label)?, confidence: 1.0 }),
+        None => Ok(detect_encoding_with_confidence(bytes)),
+    }
+}
+
+/// Share of non-ASCII characters that look like real text.
+///
+/// Letters and common typographic signs count fully, control characters and
+/// replacement characters not at all, anything else counts half.
+pub fn decode_quality(content: &str) -> f32 {
+    const TYPOGRAPHIC: &str = "€‚„…†‡‰‹›‘’“”•–—™«»°§©®±·¿¡ ²³µ¼½¾×÷£¥¢";
+
+    let mut total = 0usize;
+    let mut score = 0.0f32;
+    for ch in content.chars().filter(|c| !c.is_ascii()) {
+        total += 1;
+        if ch == '\u{FFFD}' || ch.is_control() {
+            continue;
+        }
+        score += if ch.is_alphabetic() || TYPOGRAPHIC.contains(ch) { 1.0 } else { 0.5 };
+    }
+
+    if total == 0 { 1.0 } else { score / total as f32 }
+}
+
+/// Decode bytes to string using the specified encoding.
+///
+/// A byte order mark is stripped and its encoding wins over `encoding`.
 pub fn decode_content(bytes: &[u8], encoding: &str) -> Result<String, CsvError> {
-    match encoding.to_lowercase().as_str() {
+    if let Some((bom_encoding, len)) = detect_bom(bytes) {
+        return decode_content(&bytes[len..], bom_encoding);
+    }
+    match normalize_charset(encoding).as_str() {
         "utf-8" | "utf8" | "ascii" => {
             String::from_utf8(bytes.to_vec())
                 .or_else(|_| Ok(String::from_utf8_lossy(bytes).to_string()))
         }
         "iso-8859-1" | "latin-1" | "latin1" => {
+            // True Latin-1: every byte is the code point of the same value
+            Ok(bytes.iter().map(|&b| b as char).collect())
+        }
+        "iso-8859-15" | "latin-9" | "latin9" => {
             Ok(encoding_rs::ISO_8859_15.decode(bytes).0.to_string())
         }
         "windows-1252" | "cp1252" => {
             Ok(encoding_rs::WINDOWS_1252.decode(bytes).0.to_string())
         }
-        _ => {
-            // Fallback: try UTF-8 with lossy conversion
-            Ok(String::from_utf8_lossy(bytes).to_string())
+        "utf-16le" => Ok(encoding_rs::UTF_16LE.decode_without_bom_handling(bytes).0.to_string()),
+        "utf-16be" => Ok(encoding_rs::UTF_16BE.decode_without_bom_handling(bytes).0.to_string()),
+        label => {
+            // Any other label known to encoding_rs, else UTF-8 with lossy conversion
+            match encoding_rs::Encoding::for_label(label.as_bytes()) {
+                Some(enc) => Ok(enc.decode(bytes).0.to_string()),
+                None => Ok(String::from_utf8_lossy(bytes).to_string()),
+            }
         }
     }
     .map_err(|e: std::string::FromUtf8Error| CsvError::new(0, format!("Encoding error: {}", e)))
 }
 
-/// Detect the delimiter by counting occurrences in the first line
+/// Repairs made to the decoded text before parsing.
+///
+/// Files assembled from several systems mix `\r\n` and `\n` (or old Mac `\r`)
+/// line endings, or hold NUL characters: both would corrupt rows, so NULs are
+/// removed and line endings turned into `\n`, and the counts are reported.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseDiagnostics {
+    /// NUL characters removed
+    pub nul_chars: usize,
+    /// Lines ending with `\r\n`
+    pub crlf_lines: usize,
+    /// Lines ending with a lone `\n`
+    pub lf_lines: usize,
+    /// Lines ending with a lone `\r`
+    pub cr_lines: usize,
+    /// Data-quality notes, one per repair made
+    pub notes: Vec<String>,
+}
+
+impl ParseDiagnostics {
+    /// Whether the text needed no repair
+    pub fn is_clean(&self) -> bool {
+        self.notes.is_empty()
+    }
+}
+
+/// Remove NUL characters and turn `\r\n` and lone `\r` line endings into `\n`
+pub fn sanitize_text(content: String) -> (String, ParseDiagnostics) {
+    let mut diagnostics = ParseDiagnostics::default();
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\0' => diagnostics.nul_chars += 1,
+            '\r' if chars.peek() == Some(&'\n') => {
+                chars.next();
+                diagnostics.crlf_lines += 1;
+            }
+            '\r' => diagnostics.cr_lines += 1,
+            '\n' => diagnostics.lf_lines += 1,
+            _ => {}
+        }
+    }
+
+    if diagnostics.nul_chars > 0 {
+        diagnostics.notes.push(format!("{} NUL character(s) removed", diagnostics.nul_chars));
+    }
+    let endings = [("\\r\\n", diagnostics.crlf_lines), ("\\n", diagnostics.lf_lines), ("\\r", diagnostics.cr_lines)];
+    let used: Vec<String> = endings.iter().filter(|(_, n)| *n > 0).map(|(e, n)| format!("{} {}", n, e)).collect();
+    if used.len() > 1 {
+        diagnostics.notes.push(format!("Mixed line endings ({}) normalized to \\n", used.join(", ")));
+    } else if diagnostics.cr_lines > 0 {
+        diagnostics.notes.push(format!("{} old Mac line ending(s) (\\r) normalized to \\n", diagnostics.cr_lines));
+    }
+
+    if diagnostics.nul_chars == 0 && diagnostics.cr_lines == 0 && used.len() <= 1 {
+        return (content, diagnostics);
+    }
+    let content = content.replace('\0', "").replace("\r\n", "\n").replace('\r', "\n");
+    (content, diagnostics)
+}
+
+/// Separator between the parts of a merged column name ("Ayant droit / IPI")
+pub const HEADER_SEPARATOR: &str = " / ";
+
+/// Merge several header rows into one column name per column.
+///
+/// Upper rows hold group names spanning several columns (merged cells export
+/// the name once, then empty cells), so their empty cells take the group on
+/// their left; the last row holds the field names. Empty and repeated parts
+/// are left out: `["Ayant droit", ""]` over `["IPI", "Rôle"]` gives
+/// `Ayant droit / IPI` and `Ayant droit / Rôle`.
+pub fn merge_header_rows(mut rows: Vec<Vec<String>>) -> Vec<String> {
+    let Some(fields) = rows.pop() else {
+        return Vec::new();
+    };
+    if rows.is_empty() {
+        return fields;
+    }
+    let width = rows.iter().map(Vec::len).chain([fields.len()]).max().unwrap_or(0);
+    let groups: Vec<Vec<String>> = rows.into_iter()
+        .map(|row| {
+            let mut current = String::new();
+            (0..width).map(|i| {
+                match row.get(i).map(|c| c.trim()).filter(|c| !c.is_empty()) {
+                    Some(cell) => current = cell.to_string(),
+                    None if i >= row.len() => current.clear(),
+                    None => {}
+                }
+                current.clone()
+            }).collect()
+        })
+        .collect();
+
+    (0..width).map(|i| {
+        let mut parts: Vec<&str> = Vec::new();
+        let field = fields.get(i).map(|f| f.trim()).unwrap_or("");
+        for part in groups.iter().map(|g| g[i].as_str()).chain([field]) {
+            if !part.is_empty() && parts.last() != Some(&part) {
+                parts.push(part);
+            }
+        }
+        parts.join(HEADER_SEPARATOR)
+    }).collect()
+}
+
+/// Accented letters and ligatures folded by [`normalize_header`]
+const ACCENT_FOLDS: &[(&str, &str)] = &[
+    ("ÀÁÂÃÄÅ", "A"), ("àáâãäå", "a"), ("Ç", "C"), ("ç", "c"),
+    ("ÈÉÊË", "E"), ("èéêë", "e"), ("ÌÍÎÏ", "I"), ("ìíîï", "i"),
+    ("Ñ", "N"), ("ñ", "n"), ("ÒÓÔÕÖØ", "O"), ("òóôõöø", "o"),
+    ("ÙÚÛÜ", "U"), ("ùúûü", "u"), ("ÝŸ", "Y"), ("ýÿ", "y"),
+    ("Œ", "OE"), ("œ", "oe"), ("Æ", "AE"), ("æ", "ae"), ("ß", "ss"),
+];
+
+/// Header without cosmetic variations: trimmed, whitespace runs (NBSP
+/// included) collapsed to one space, accents removed. Case is kept.
+///
+/// `" Rôle\u{a0} ayant  droit "` gives `"Role ayant droit"`; decomposed
+/// accents (a letter followed by a combining mark) are removed too.
+pub fn normalize_header(header: &str) -> String {
+    let mut folded = String::with_capacity(header.len());
+    for c in header.chars() {
+        match c {
+            // Combining diacritical marks, zero-width space, BOM
+            '\u{300}'..='\u{36f}' | '\u{200b}' | '\u{feff}' => {}
+            _ => match ACCENT_FOLDS.iter().find(|(accented, _)| accented.contains(c)) {
+                Some((_, plain)) => folded.push_str(plain),
+                None => folded.push(c),
+            },
+        }
+    }
+    folded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Normalize headers in place (see [`normalize_header`]), then rename duplicates.
+///
+/// Returns the headers changed by normalization as final name → name in the
+/// file, and the renamings as (name in the file, new name). Both are keyed per
+/// column, so headers that only collide once normalized ("Role", "Rôle")
+/// keep their own raw name.
+pub fn normalize_headers(headers: &mut [String]) -> (BTreeMap<String, String>, Vec<(String, String)>) {
+    let raw: Vec<String> = headers.to_vec();
+    let normalized: Vec<String> = raw.iter().map(|h| normalize_header(h)).collect();
+    headers.clone_from_slice(&normalized);
+    disambiguate_headers(headers);
+
+    let mut originals = BTreeMap::new();
+    let mut renamed = Vec::new();
+    for ((header, raw), normalized) in headers.iter().zip(&raw).zip(&normalized) {
+        if normalized != raw {
+            originals.insert(header.clone(), raw.clone());
+        }
+        if header != normalized {
+            renamed.push((raw.clone(), header.clone()));
+        }
+    }
+    (originals, renamed)
+}
+
+/// Rename duplicate headers deterministically ("IPI", "IPI_2", "IPI_3").
+///
+/// Returns the renamings as (original name, new name).
+pub fn disambiguate_headers(headers: &mut [String]) -> Vec<(String, String)> {
+    let original: Vec<String> = headers.to_vec();
+    let mut renamed = Vec::new();
+    let mut seen: Vec<String> = Vec::new();
+
+    for header in headers.iter_mut() {
+        if seen.contains(header) {
+            // Never reuse a name that appears anywhere in the original header
+            let mut n = 2;
+            let mut candidate = format!("{}_{}", header, n);
+            while seen.contains(&candidate) || original.contains(&candidate) {
+                n += 1;
+                candidate = format!("{}_{}", header, n);
+            }
+            renamed.push((header.clone(), candidate.clone()));
+            *header = candidate;
+        }
+        seen.push(header.clone());
+    }
+
+    renamed
+}
+
+/// Number of non-empty lines sampled for delimiter detection
+const DELIMITER_SAMPLE_LINES: usize = 20;
+
+/// Detect the delimiter from the first lines of the file.
+///
+/// Each candidate is scored on how many sampled lines have the same field
+/// count as the header (quoted fields are respected). Ties are broken by the
+/// number of columns, then by candidate order (`;` first).
 pub fn detect_delimiter(content: &str) -> char {
-    let first_line = content.lines().next().unwrap_or("");
-    
+    let sample: Vec<&str> = content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .take(DELIMITER_SAMPLE_LINES)
+        .collect();
+
     let separators = [';', ',', '\t', '|'];
     let mut best_sep = ';';
-    let mut best_count = 0;
-    
+    let mut best_score = (0, 0);
+
     for &sep in &separators {
-        let count = first_line.matches(sep).count();
-        if count > best_count {
-            best_count = count;
+        let counts: Vec<usize> = sample.iter().map(|l| count_fields(l, sep)).collect();
+        let header_count = counts.first().copied().unwrap_or(0);
+        if header_count <= 1 {
+            continue;
+        }
+
+        let consistent = counts.iter().filter(|&&c| c == header_count).count();
+        let score = (consistent, header_count);
+        if score > best_score {
+            best_score = score;
             best_sep = sep;
         }
     }
@@ -121,6 +808,67 @@ pub fn detect_delimiter(content: &str) -> char {
     best_sep
 }
 
+/// Detect a fixed-width layout, with column names taken from the first line.
+///
+/// Only applies to files none of the usual delimiters splits. A column
+/// starts after a "gutter", a position that is blank on every sampled line,
+/// under a header name; the layout is rejected unless it has at least two
+/// columns and two data lines.
+pub fn detect_fixed_width(content: &str) -> Option<Vec<FixedWidthColumn>> {
+    let sample: Vec<Vec<char>> = content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .take(DELIMITER_SAMPLE_LINES)
+        .map(|l| l.trim_end().chars().collect())
+        .collect();
+
+    let header = sample.first()?;
+    let header_line: String = header.iter().collect();
+    if sample.len() < 3 || [';', ',', '\t', '|'].iter().any(|&sep| count_fields(&header_line, sep) > 1) {
+        return None;
+    }
+
+    let sample_width = sample.iter().map(Vec::len).max().unwrap_or(0);
+    let blank = |i: usize| sample.iter().all(|line| line.get(i).is_none_or(|c| c.is_whitespace()));
+    let starts: Vec<usize> = (0..sample_width)
+        .filter(|&i| !blank(i) && (i == 0 || blank(i - 1)))
+        .collect();
+    if starts.len() < 2 {
+        return None;
+    }
+
+    // The last column runs to the end of the longest line of the file. A
+    // gutter under no header name (a blank inside values) does not split.
+    let width = content.lines().map(|l| l.trim_end().chars().count()).max().unwrap_or(0);
+    let mut columns: Vec<FixedWidthColumn> = Vec::new();
+    for (i, &start) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).copied().unwrap_or(width);
+        let name = FixedWidthColumn::new("", start, end - start).slice(&header_line);
+        match columns.last_mut() {
+            Some(previous) if name.is_empty() => previous.len = end - previous.start,
+            _ => columns.push(FixedWidthColumn::new(name, start, end - start)),
+        }
+    }
+
+    (columns.len() >= 2 && !columns[0].name.is_empty()).then_some(columns)
+}
+
+/// Count fields in a line, ignoring separators inside double quotes
+fn count_fields(line: &str, delimiter: char) -> usize {
+    let mut in_quotes = false;
+    let mut count = 1;
+
+    for ch in line.chars() {
+        if ch == '"' {
+            in_quotes = !in_quotes;
+        } else if ch == delimiter && !in_quotes {
+            count += 1;
+        }
+    }
+
+    count
+}
+
 /// Parse CSV into JSON objects with explicit delimiter.
 ///
 /// Each row becomes a JSON object where keys are column headers.
@@ -141,55 +889,93 @@ pub fn csv_to_json(csv: &str, delimiter: char) -> Result<Vec<Value>, CsvError> {
 }
 
 /// Parse CSV from a reader into JSON objects.
-pub fn parse_csv<R: Read>(reader: R, delimiter: char) -> Result<Vec<Value>, CsvError> {
-    let buf = BufReader::new(reader);
-    let mut lines = buf.lines();
+pub fn parse_csv<R: Read>(mut reader: R, delimiter: char) -> Result<Vec<Value>, CsvError> {
+    let mut content = String::new();
+    reader.read_to_string(&mut content)
+        .map_err(|e| CsvError::new(1, format!("Cannot read CSV: {}", e)))?;
 
-    // Get headers from first line
-    let header_line = lines.next()
+    let mut records = read_records(&content, delimiter)?.into_iter();
+    let mut headers = records.next()
         .ok_or_else(|| CsvError::new(1, "Empty CSV file"))?
-        .map_err(|e| CsvError::new(1, format!("Cannot read header: {}", e)))?;
+        .fields;
+    disambiguate_headers(&mut headers);
 
-    let headers: Vec<String> = header_line
-        .split(delimiter)
-        .map(|s| s.trim().trim_matches('"').to_string())
-        .collect();
-
-    if headers.is_empty() {
-        return Err(CsvError::new(1, "No headers found"));
-    }
+    Ok(records.map(|record| record_object(&headers, &record.fields)).collect())
+}
 
-    // Parse data rows
-    let mut rows = Vec::new();
+/// One CSV record, with its raw text (several lines for multi-line fields)
+struct CsvRecord {
+    fields: Vec<String>,
+    text: String,
+    /// 1-based line where the record starts
+    line: usize,
+}
 
-    for (line_idx, line_result) in lines.enumerate() {
-        let line_num = line_idx + 2; // +1 for 0-index, +1 for header
+/// Split delimited content into RFC 4180 records: quoted fields keep their
+/// delimiters, `""` becomes `"` and line breaks inside quotes stay in the
+/// field. Cells are trimmed; blank lines are skipped.
+fn read_records(content: &str, delimiter: char) -> Result<Vec<CsvRecord>, CsvError> {
+    let delimiter = u8::try_from(delimiter)
+        .ok()
+        .filter(u8::is_ascii)
+        .ok_or_else(|| CsvError::new(1, format!("Unsupported delimiter: '{}'", delimiter)))?;
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(content.as_bytes());
 
-        let line = line_result
-            .map_err(|e| CsvError::new(line_num, format!("Cannot read line: {}", e)))?;
-        
-        if line.trim().is_empty() {
-            continue;
+    // Record start offsets, to cut the raw text of each record
+    let mut parsed = Vec::new();
+    let mut record = csv::StringRecord::new();
+    loop {
+        let start = reader.position().byte() as usize;
+        let line = reader.position().line() as usize;
+        match reader.read_record(&mut record) {
+            Ok(true) => parsed.push((start, line, record.iter().map(clean_cell).collect::<Vec<_>>())),
+            Ok(false) => break,
+            Err(e) => return Err(CsvError::new(line, format!("Cannot read record: {}", e))),
         }
+    }
 
-        let values: Vec<&str> = line.split(delimiter).collect();
-        let mut obj = Map::new();
-
-        for (i, header) in headers.iter().enumerate() {
-            let raw_value = values.get(i)
-                .map(|s| s.trim().trim_matches('"'))
-                .unwrap_or("");
-            
-            obj.insert(header.clone(), json!(raw_value));
-        }
+    let ends: Vec<usize> = parsed.iter().skip(1).map(|(start, _, _)| *start).chain([content.len()]).collect();
+    Ok(parsed.into_iter()
+        .zip(ends)
+        .map(|((start, line, fields), end)| {
+            // The reader's position is still before the blank lines it skips
+            let text = &content[start..end];
+            let record = text.trim_start_matches(['\r', '\n']);
+            CsvRecord {
+                fields,
+                text: record.trim_end_matches(['\r', '\n']).to_string(),
+                line: line + text[..text.len() - record.len()].matches('\n').count(),
+            }
+        })
+        .filter(|record| !record.text.trim().is_empty())
+        .collect())
+}
 
-        rows.push(Value::Object(obj));
+/// Trim a cell, and unquote one whose quotes follow spaces (`a; "b"`)
+fn clean_cell(cell: &str) -> String {
+    let cell = cell.trim();
+    match cell.strip_prefix('"').and_then(|c| c.strip_suffix('"')) {
+        Some(inner) => inner.replace("\"\"", "\"").trim().to_string(),
+        None => cell.to_string(),
     }
+}
 
-    Ok(rows)
+/// JSON object of a record, empty strings for missing trailing cells
+fn record_object(headers: &[String], fields: &[String]) -> Value {
+    let obj: Map<String, Value> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, header)| (header.clone(), json!(fields.get(i).map(String::as_str).unwrap_or(""))))
+        .collect();
+    Value::Object(obj)
 }
 
-/// Parse CSV file with auto-detection of encoding and delimiter.
+/// Parse CSV file with auto-detection of encoding and delimiter
+/// (gzip files and single-entry zip archives are decompressed first).
 ///
 /// # Example
 /// ```ignore
@@ -201,72 +987,390 @@ pub fn parse_csv_file_auto<P: AsRef<Path>>(path: P) -> Result<ParseResult, CsvEr
     let bytes = std::fs::read(path.as_ref())
         .map_err(|e| CsvError::new(0, format!("Cannot read file: {}", e)))?;
     
-    parse_bytes_auto(&bytes)
+    parse_bytes_auto(&unpack(bytes, None)?.bytes, None)
+}
+
+/// Parse CSV bytes with auto-detection of encoding and, unless forced, delimiter.
+pub fn parse_bytes_auto(bytes: &[u8], delimiter: Option<char>) -> Result<ParseResult, CsvError> {
+    parse_bytes_with_options(bytes, &ParseOptions { delimiter, ..ParseOptions::default() })
 }
 
-/// Parse CSV bytes with auto-detection of encoding and delimiter.
-pub fn parse_bytes_auto(bytes: &[u8]) -> Result<ParseResult, CsvError> {
-    // Detect encoding
-    let encoding = detect_encoding(bytes);
+/// Parse CSV bytes with auto-detection and explicit skip/footer options.
+pub fn parse_bytes_with_options(bytes: &[u8], options: &ParseOptions) -> Result<ParseResult, CsvError> {
+    // Detect encoding (unless forced)
+    let guess = resolve_encoding(bytes, options.encoding.as_deref())?;
+    let encoding = guess.encoding;
     
-    // Decode content
-    let content = decode_content(bytes, &encoding)?;
+    // Decode content, then repair NULs and mixed line endings
+    let (content, diagnostics) = sanitize_text(decode_content(bytes, &encoding)?);
+
+    // Drop preamble lines before detecting the delimiter on the header
+    let content: String = if options.skip_first > 0 {
+        content.lines().skip(options.skip_first).collect::<Vec<_>>().join("\n")
+    } else {
+        content
+    };
     
-    // Detect delimiter
-    let delimiter = detect_delimiter(&content);
+    // Explicit fixed-width layout, or one detected on an undelimited file
+    // (its first line is then the header); a forced delimiter rules both out
+    let layout = match options.fixed_width {
+        Some(ref columns) => Some((columns.clone(), 0)),
+        None if options.delimiter.is_some() => None,
+        None => detect_fixed_width(&content).map(|columns| (columns, 1)),
+    };
+    let skipped = options.skip_first;
+    if let Some((columns, header_rows)) = layout {
+        let options = ParseOptions { skip_first: header_rows, ..options.clone() };
+        let mut result = parse_fixed_width(&content, &columns, encoding, &options)?;
+        result.encoding_confidence = guess.confidence;
+        result.diagnostics = diagnostics;
+        for line in &mut result.lines {
+            *line += skipped;
+        }
+        return Ok(result);
+    }
+
+    // Detect delimiter (unless forced)
+    let delimiter = options.delimiter.unwrap_or_else(|| detect_delimiter(&content));
     
     // Parse with detected settings
-    parse_string_with_metadata(&content, delimiter, encoding)
+    let options = ParseOptions { skip_first: 0, ..options.clone() };
+    let mut result = parse_string_with_options(&content, delimiter, encoding, &options)?;
+    result.encoding_confidence = guess.confidence;
+    result.diagnostics = diagnostics;
+    for row in &mut result.ragged_rows {
+        row.line += skipped;
+    }
+    for line in &mut result.lines {
+        *line += skipped;
+    }
+    Ok(result)
+}
+
+/// The row at `line` when its cell count differs from the header's `columns`
+/// (always `None` in [`RaggedRows::Pad`] mode)
+pub(crate) fn ragged_row(fields: &[String], columns: usize, line: usize, mode: RaggedRows) -> Option<RaggedRow> {
+    if mode == RaggedRows::Pad {
+        return None;
+    }
+    // Trailing empty cells past the header (`a;b;c;`) lose nothing
+    let cells = fields.iter().rposition(|f| !f.is_empty()).map_or(0, |i| i + 1).max(columns.min(fields.len()));
+    (cells != columns).then_some(RaggedRow { line, cells, expected: columns, rejected: mode == RaggedRows::Reject })
 }
 
 /// Parse CSV string with explicit delimiter and return metadata.
 pub fn parse_string_with_metadata(content: &str, delimiter: char, encoding: String) -> Result<ParseResult, CsvError> {
-    let mut lines = content.lines();
+    parse_string_with_options(content, delimiter, encoding, &ParseOptions::default())
+}
+
+/// Parse CSV string with explicit delimiter and skip/footer options.
+pub fn parse_string_with_options(
+    content: &str,
+    delimiter: char,
+    encoding: String,
+    options: &ParseOptions,
+) -> Result<ParseResult, CsvError> {
+    let preamble: usize = content.split_inclusive('\n').take(options.skip_first).map(str::len).sum();
+    let mut data_records = read_records(content.get(preamble..).unwrap_or(""), delimiter)
+        .map_err(|e| CsvError { line: e.line + options.skip_first, ..e })?;
+    if data_records.is_empty() {
+        return Err(CsvError::new(options.skip_first + 1, "Empty CSV file"));
+    }
 
-    // Get headers from first line
-    let header_line = lines.next()
-        .ok_or_else(|| CsvError::new(1, "Empty CSV file"))?;
+    // Headers from the first record(s)
+    let header_rows = options.header_rows.clamp(1, data_records.len());
+    let mut headers = merge_header_rows(data_records.drain(..header_rows).map(|r| r.fields).collect());
+    let (original_headers, renamed_columns) = normalize_headers(&mut headers);
 
-    let headers: Vec<String> = header_line
-        .split(delimiter)
-        .map(|s| s.trim().trim_matches('"').to_string())
-        .collect();
+    // Explicit trailing records first, then heuristic footer detection
+    let explicit = options.skip_last.min(data_records.len());
+    let mut footer: Vec<CsvRecord> = data_records.split_off(data_records.len() - explicit);
 
-    if headers.is_empty() {
-        return Err(CsvError::new(1, "No headers found"));
+    if options.detect_footer {
+        let detected = detect_footer(&data_records, headers.len());
+        let mut dropped = data_records.split_off(data_records.len() - detected);
+        dropped.append(&mut footer);
+        footer = dropped;
     }
+    let footer_lines: Vec<String> = footer.into_iter().map(|record| record.text).collect();
 
     // Parse data rows
     let mut records = Vec::new();
+    let mut lines = Vec::with_capacity(data_records.len());
+    let mut ragged_rows = Vec::new();
+    let mut progress = StageProgress::start(LogStage::Parse, data_records.len() as u64);
 
-    for line in lines {
-        
-        if line.trim().is_empty() {
-            continue;
+    for (row, record) in data_records.iter().enumerate() {
+        progress.set(row as u64 + 1);
+        if let Some(ragged) = ragged_row(&record.fields, headers.len(), record.line + options.skip_first, options.ragged_rows) {
+            let rejected = ragged.rejected;
+            ragged_rows.push(ragged);
+            if rejected {
+                continue;
+            }
         }
+        records.push(record_object(&headers, &record.fields));
+        lines.push(record.line + options.skip_first);
+    }
+
+    Ok(ParseResult {
+        records,
+        lines,
+        encoding,
+        encoding_confidence: 1.0,
+        delimiter,
+        headers,
+        footer_lines,
+        ragged_rows,
+        diagnostics: ParseDiagnostics::default(),
+        renamed_columns,
+        original_headers,
+        fixed_width: None,
+    })
+}
 
-        let values: Vec<&str> = line.split(delimiter).collect();
-        let mut obj = Map::new();
+/// Parse fixed-width text: every line after `skip_first` is a data row, cut
+/// into the given columns. The delimiter is reported as `' '`.
+pub fn parse_fixed_width(
+    content: &str,
+    columns: &[FixedWidthColumn],
+    encoding: String,
+    options: &ParseOptions,
+) -> Result<ParseResult, CsvError> {
+    if columns.is_empty() {
+        return Err(CsvError::new(1, "Empty fixed-width layout"));
+    }
+    let mut headers: Vec<String> = columns.iter().map(|c| c.name.clone()).collect();
+    let (original_headers, renamed_columns) = normalize_headers(&mut headers);
 
-        for (i, header) in headers.iter().enumerate() {
-            let raw_value = values.get(i)
-                .map(|s| s.trim().trim_matches('"'))
-                .unwrap_or("");
-            
-            obj.insert(header.clone(), json!(raw_value));
-        }
+    // (1-based line number, text) of each data line
+    let mut data_lines: Vec<(usize, &str)> = content
+        .lines()
+        .enumerate()
+        .skip(options.skip_first)
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| (i + 1, l))
+        .collect();
 
+    let explicit = options.skip_last.min(data_lines.len());
+    let mut footer_lines: Vec<String> = data_lines
+        .split_off(data_lines.len() - explicit)
+        .into_iter()
+        .map(|(_, l)| l.to_string())
+        .collect();
+
+    if options.detect_footer {
+        // Every line has all the layout's cells: only summary rows are footers
+        let summary = |(_, line): &(usize, &str)| is_summary_row(&columns.iter().map(|c| c.slice(line)).collect::<Vec<_>>());
+        let detected = count_footer_lines(&data_lines, columns.len(), |_| columns.len(), summary);
+        let mut dropped: Vec<String> = data_lines
+            .split_off(data_lines.len() - detected)
+            .into_iter()
+            .map(|(_, l)| l.to_string())
+            .collect();
+        dropped.append(&mut footer_lines);
+        footer_lines = dropped;
+    }
+
+    let mut records = Vec::new();
+    let mut lines = Vec::with_capacity(data_lines.len());
+    let mut progress = StageProgress::start(LogStage::Parse, data_lines.len() as u64);
+
+    for (row, (number, line)) in data_lines.into_iter().enumerate() {
+        progress.set(row as u64 + 1);
+        lines.push(number);
+        let obj: Map<String, Value> = headers
+            .iter()
+            .zip(columns)
+            .map(|(header, column)| (header.clone(), json!(column.slice(line))))
+            .collect();
         records.push(Value::Object(obj));
     }
 
     Ok(ParseResult {
         records,
+        lines,
         encoding,
-        delimiter,
+        encoding_confidence: 1.0,
+        delimiter: ' ',
         headers,
+        footer_lines,
+        ragged_rows: Vec::new(),
+        diagnostics: ParseDiagnostics::default(),
+        renamed_columns,
+        original_headers,
+        fixed_width: Some(columns.to_vec()),
     })
 }
 
+/// Parse JSON records: an array of objects, or NDJSON (one object per line).
+///
+/// Values are kept as they are, nested objects flattened to dotted keys
+/// (`{"work": {"title": ..}}` → `work.title`). Keys of a record come out in
+/// alphabetical order (serde_json does not keep the file order); headers list
+/// them record after record, each at its first appearance.
+pub fn parse_json_records(content: &str) -> Result<ParseResult, CsvError> {
+    let (records, lines) = json_records(content)?;
+    json_result(records, lines, "utf-8".to_string(), 1.0)
+}
+
+/// Parse JSON bytes with encoding detection: an array of objects, or
+/// newline-delimited objects (see [`parse_jsonl_auto`]) for any other content
+pub fn parse_json_auto(bytes: &[u8]) -> Result<ParseResult, CsvError> {
+    parse_json_with_encoding(bytes, None)
+}
+
+/// Parse JSON bytes like [`parse_json_auto`], decoded with `encoding` when given
+pub fn parse_json_with_encoding(bytes: &[u8], encoding: Option<&str>) -> Result<ParseResult, CsvError> {
+    let guess = resolve_encoding(bytes, encoding)?;
+    let content = decode_content(bytes, &guess.encoding)?;
+    let (records, lines) = json_records(&content)?;
+    json_result(records, lines, guess.encoding, guess.confidence)
+}
+
+/// Parse newline-delimited JSON bytes (JSONL / NDJSON) with encoding detection.
+///
+/// Blank lines are ignored; an invalid line fails with its line number.
+pub fn parse_jsonl_auto(bytes: &[u8]) -> Result<ParseResult, CsvError> {
+    let guess = detect_encoding_with_confidence(bytes);
+    let content = decode_content(bytes, &guess.encoding)?;
+    let (records, lines) = json_lines(content.trim_start_matches('\u{feff}'))?;
+    json_result(records, lines, guess.encoding, guess.confidence)
+}
+
+/// Objects of a JSON array, or of newline-delimited JSON (with line numbers).
+///
+/// Leading blank lines are kept so NDJSON line numbers match the file.
+fn json_records(content: &str) -> Result<(Vec<Value>, Vec<usize>), CsvError> {
+    let content = content.trim_start_matches('\u{feff}').trim_end();
+    if content.trim_start().starts_with('[') {
+        Ok((json_array(content)?, Vec::new()))
+    } else {
+        json_lines(content)
+    }
+}
+
+fn json_array(content: &str) -> Result<Vec<Value>, CsvError> {
+    serde_json::from_str(content)
+        .map_err(|e| CsvError::new(e.line(), format!("Invalid JSON: {}", e)))
+}
+
+/// Objects of newline-delimited JSON, with their 1-based line numbers
+fn json_lines(content: &str) -> Result<(Vec<Value>, Vec<usize>), CsvError> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| serde_json::from_str(l)
+            .map(|record| (record, i + 1))
+            .map_err(|e| CsvError::new(i + 1, format!("Invalid JSON line: {}", e))))
+        .collect()
+}
+
+/// Records as a `ParseResult`: nested objects flattened, headers collected
+fn json_result(records: Vec<Value>, lines: Vec<usize>, encoding: String, encoding_confidence: f32) -> Result<ParseResult, CsvError> {
+    let mut headers: Vec<String> = Vec::new();
+    let mut flat_records = Vec::with_capacity(records.len());
+    for (i, record) in records.into_iter().enumerate() {
+        let Value::Object(obj) = record else {
+            return Err(CsvError::new(i + 1, format!("Record {} is not a JSON object", i + 1)));
+        };
+        let mut flat = Map::new();
+        flatten_object(None, obj, &mut flat);
+        for key in flat.keys() {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+        flat_records.push(Value::Object(flat));
+    }
+
+    Ok(ParseResult {
+        records: flat_records,
+        lines,
+        encoding,
+        encoding_confidence,
+        delimiter: JSON_DELIMITER,
+        headers,
+        footer_lines: Vec::new(),
+        ragged_rows: Vec::new(),
+        diagnostics: ParseDiagnostics::default(),
+        renamed_columns: Vec::new(),
+        original_headers: BTreeMap::new(),
+        fixed_width: None,
+    })
+}
+
+/// Move the fields of `obj` into `out`, nested objects as `parent.child` keys
+fn flatten_object(prefix: Option<&str>, obj: Map<String, Value>, out: &mut Map<String, Value>) {
+    for (key, value) in obj {
+        let key = match prefix {
+            Some(prefix) => format!("{}.{}", prefix, key),
+            None => key,
+        };
+        match value {
+            Value::Object(nested) => flatten_object(Some(&key), nested, out),
+            value => { out.insert(key, value); }
+        }
+    }
+}
+
+/// Count trailing lines that look like a summary footer.
+///
+/// A trailing line is a footer when it is a summary row (see
+/// [`is_summary_row`]), or when its cell count differs from the header while
+/// most rows match it. Sparse rows with the right cell count (a creator-only
+/// continuation row) are data. At most `MAX_FOOTER_LINES` are dropped, and
+/// never when the file has too few rows to compare.
+fn detect_footer(records: &[CsvRecord], column_count: usize) -> usize {
+    count_footer_lines(
+        records,
+        column_count,
+        |record: &CsvRecord| record.fields.len(),
+        |record: &CsvRecord| is_summary_row(&record.fields),
+    )
+}
+
+/// Whether a row is a totals line: its first cell opens with a summary word
+/// and gives a count ("Nombre d'œuvres: 1234"), or the other cells hold numbers only
+/// ("Total;;1234"). "Total Eclipse of the Heart;T0701234567" is a work.
+pub(crate) fn is_summary_row<S: AsRef<str>>(cells: &[S]) -> bool {
+    let mut cells = cells.iter().map(|c| c.as_ref().trim()).filter(|c| !c.is_empty());
+    let Some(first) = cells.next() else {
+        return false;
+    };
+    let lower = first.to_lowercase();
+    let word: String = lower.chars().take_while(|c| c.is_alphanumeric() || *c == '-').collect();
+    if !SUMMARY_WORDS.contains(&word.as_str()) && !lower.starts_with("fin de fichier") && !lower.starts_with("end of file") {
+        return false;
+    }
+    let counted = lower.split_once(':')
+        .is_some_and(|(_, count)| count.trim_start().starts_with(|c: char| c.is_ascii_digit()));
+    counted || cells.all(|c| c.chars().all(|ch| ch.is_ascii_digit() || " .,%".contains(ch)))
+}
+
+/// Footer detection given the number of cells of a line and whether it is a summary row
+fn count_footer_lines<T>(
+    lines: &[T],
+    column_count: usize,
+    width: impl Fn(&T) -> usize,
+    summary: impl Fn(&T) -> bool,
+) -> usize {
+    if column_count < 2 || lines.len() < 3 {
+        return 0;
+    }
+
+    let mut counts: Vec<usize> = lines.iter().map(&width).collect();
+    counts.sort_unstable();
+    let regular = counts[counts.len() / 2] == column_count;
+
+    lines.iter()
+        .rev()
+        .take(MAX_FOOTER_LINES.min(lines.len() - 1))
+        .take_while(|l| summary(l) || (regular && width(l) != column_count))
+        .count()
+}
+
 /// Parse CSV file with explicit delimiter.
 pub fn parse_csv_file(path: &str, delimiter: char) -> Result<Vec<Value>, CsvError> {
     let file = std::fs::File::open(path)
@@ -310,6 +1414,28 @@ mod tests {
         assert_eq!(rows[0]["value"], "Hello World");
     }
 
+    #[test]
+    fn test_rfc4180_quoted_fields() {
+        let csv = "Titre;ISWC;Notes\n\"Ballade; op. 2\";T1;\"Dit \"\"la\"\" ballade\"\n\"Chanson\nsur deux lignes\";T2;ok\nFin;T3; \"espacé\"\n";
+        let result = parse_string_with_metadata(csv, ';', "utf-8".to_string()).unwrap();
+
+        assert_eq!(result.headers, vec!["Titre", "ISWC", "Notes"]);
+        assert_eq!(result.records.len(), 3);
+        // Quoted delimiter, escaped quotes, embedded line break
+        assert_eq!(result.records[0]["Titre"], "Ballade; op. 2");
+        assert_eq!(result.records[0]["Notes"], "Dit \"la\" ballade");
+        assert_eq!(result.records[1]["Titre"], "Chanson\nsur deux lignes");
+        assert_eq!(result.records[1]["ISWC"], "T2");
+        assert_eq!(result.records[2]["Notes"], "espacé");
+
+        // Same through detection, and a multi-line record is never taken for a footer
+        let result = parse_bytes_auto(format!("{}Total;;\n", csv).as_bytes(), None).unwrap();
+        assert_eq!(result.delimiter, ';');
+        assert_eq!(result.records[1]["Titre"], "Chanson\nsur deux lignes");
+        assert_eq!(result.footer_lines, vec!["Total;;"]);
+        assert_eq!(csv_to_json(csv, ';').unwrap()[0]["Titre"], "Ballade; op. 2");
+    }
+
     #[test]
     fn test_empty_lines_skipped() {
         let csv = "a;b\n1;2\n\n3;4\n";
@@ -381,16 +1507,288 @@ mod tests {
         assert_eq!(detect_delimiter(content), '|');
     }
 
+    #[test]
+    fn test_detect_delimiter_quoted_commas_in_header() {
+        // The header holds more commas (inside quotes) than semicolons
+        let content = "\"Titre, sous-titre, version\";\"Auteurs, compositeurs\";IPI\nA;B;1\nC;D;2";
+        assert_eq!(detect_delimiter(content), ';');
+    }
+
+    #[test]
+    fn test_detect_delimiter_prefers_stable_columns() {
+        // Commas appear in free text, only ';' gives a stable column count
+        let content = "Titre;Notes\nA;un, deux, trois\nB;quatre\nC;cinq, six";
+        assert_eq!(detect_delimiter(content), ';');
+    }
+
     #[test]
     fn test_auto_parse() {
         let csv = "name;age\nAlice;30\nBob;25";
-        let result = parse_bytes_auto(csv.as_bytes()).unwrap();
+        let result = parse_bytes_auto(csv.as_bytes(), None).unwrap();
         
         assert_eq!(result.delimiter, ';');
         assert_eq!(result.records.len(), 2);
         assert_eq!(result.headers, vec!["name", "age"]);
     }
 
+    #[test]
+    fn test_exclude_columns() {
+        let csv = "Titre;Email;IPI\nSong;jane@example.com;123";
+        let mut result = parse_bytes_auto(csv.as_bytes(), None).unwrap();
+
+        let removed = result.exclude_columns(&["email ".to_string(), "Missing".to_string()]);
+
+        assert_eq!(removed, vec!["Email"]);
+        assert_eq!(result.headers, vec!["Titre", "IPI"]);
+        assert!(result.records[0].get("Email").is_none());
+        assert_eq!(result.records[0]["IPI"], "123");
+    }
+
+    #[test]
+    fn test_duplicate_headers_renamed() {
+        let csv = "Titre;IPI;IPI;IPI_2;IPI\nSong;111;222;x;333";
+        let result = parse_bytes_auto(csv.as_bytes(), None).unwrap();
+
+        assert_eq!(result.headers, vec!["Titre", "IPI", "IPI_3", "IPI_2", "IPI_4"]);
+        assert_eq!(result.records[0]["IPI"], "111");
+        assert_eq!(result.records[0]["IPI_3"], "222");
+        assert_eq!(result.records[0]["IPI_2"], "x");
+        assert_eq!(result.records[0]["IPI_4"], "333");
+        assert_eq!(result.renamed_columns, vec![
+            ("IPI".to_string(), "IPI_3".to_string()),
+            ("IPI".to_string(), "IPI_4".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_footer_detection() {
+        let csv = "Titre;ISWC;IPI\nA;T1;1\nB;T2;2\nC;T3;3\nTotal: 3 oeuvres;;\n";
+        let result = parse_bytes_auto(csv.as_bytes(), None).unwrap();
+
+        assert_eq!(result.records.len(), 3);
+        assert_eq!(result.footer_lines, vec!["Total: 3 oeuvres;;"]);
+
+        // SACEM trailer filling as many cells as a data row of a two-column file
+        let csv = "Titre;ISWC\nA;T1\nB;T2\nTotal Eclipse;T3\nNombre d'œuvres: 3;\n";
+        let result = parse_bytes_auto(csv.as_bytes(), None).unwrap();
+        assert_eq!(result.records.len(), 3);
+        assert_eq!(result.footer_lines, vec!["Nombre d'œuvres: 3;"]);
+        assert!(is_summary_row(&["Total", "", "1234"]));
+
+        // A creator-only continuation row is data, a short trailer is a footer
+        let csv = "Titre;ISWC;IPI;Part\nA;T1;1;50\nB;T2;2;100\nC;T3;3;50\n;;4;50\nExporté le 01/02/2024\n";
+        let result = parse_bytes_auto(csv.as_bytes(), None).unwrap();
+        assert_eq!(result.records.len(), 4);
+        assert_eq!(result.records[3]["IPI"], "4");
+        assert_eq!(result.footer_lines, vec!["Exporté le 01/02/2024"]);
+        assert!(!is_summary_row(&["Total Eclipse of the Heart", "T0701234567"]));
+        assert!(!is_summary_row(&["Country Roads", "12"]));
+    }
+
+    #[test]
+    fn test_skip_first_and_last() {
+        let csv = "Export SACEM\n\nTitre;IPI\nA;1\nB;2\nC;3\nFin de fichier;42";
+        let options = ParseOptions { skip_first: 2, skip_last: 1, detect_footer: false, ..ParseOptions::default() };
+        let result = parse_bytes_with_options(csv.as_bytes(), &options).unwrap();
+
+        assert_eq!(result.headers, vec!["Titre", "IPI"]);
+        assert_eq!(result.records.len(), 3);
+        assert_eq!(result.footer_lines, vec!["Fin de fichier;42"]);
+    }
+
+    #[test]
+    fn test_normalize_headers() {
+        // NBSP, trailing and doubled spaces, precomposed and decomposed accents
+        let csv = "Titre\u{a0}original ;Rôle  ayant droit;Re\u{301}fe\u{301}rence;IPI\nA;CA;R1;1\n";
+        let result = parse_bytes_auto(csv.as_bytes(), None).unwrap();
+
+        assert_eq!(result.headers, vec!["Titre original", "Role ayant droit", "Reference", "IPI"]);
+        assert_eq!(result.records[0]["Role ayant droit"], "CA");
+        assert_eq!(result.original_headers.len(), 3);
+        assert_eq!(result.original_headers["Role ayant droit"], "Rôle  ayant droit");
+        assert_eq!(normalize_header("Œuvre / Société"), "OEuvre / Societe");
+
+        // A template saved from the accented header still reads the column
+        let matrix: crate::transform::dsl::matrix::TransformationMatrix = serde_json::from_value(json!({
+            "transforms": {"creatorRole": {"source": "Rôle ayant droit", "operations": []}}
+        })).unwrap();
+        let output = crate::transform::dsl::execute(&result.records, &matrix);
+        assert_eq!(output.records[0]["creatorRole"], "CA");
+
+        // Headers that only collide once normalized keep their own raw name
+        let result = parse_bytes_auto("Role;Rôle\nA;B\n".as_bytes(), None).unwrap();
+        assert_eq!(result.headers, vec!["Role", "Role_2"]);
+        assert_eq!(result.records[0]["Role_2"], "B");
+        assert_eq!(result.original_headers, BTreeMap::from([("Role_2".to_string(), "Rôle".to_string())]));
+        assert_eq!(result.renamed_columns, vec![("Rôle".to_string(), "Role_2".to_string())]);
+    }
+
+    #[test]
+    fn test_mixed_line_endings_and_nuls() {
+        // Header from one system (CRLF), rows appended by another (LF, lone CR), NUL padding
+        let csv = "Titre;ISWC\r\nA;T1\nB;T2\rC;T3\0\0\n";
+        let result = parse_bytes_auto(csv.as_bytes(), None).unwrap();
+
+        assert_eq!(result.records.len(), 3);
+        assert_eq!(result.records[1]["Titre"], "B");
+        assert_eq!(result.records[2]["ISWC"], "T3");
+        let diagnostics = &result.diagnostics;
+        assert_eq!((diagnostics.nul_chars, diagnostics.crlf_lines, diagnostics.lf_lines, diagnostics.cr_lines), (2, 1, 2, 1));
+        assert_eq!(diagnostics.notes, vec![
+            "2 NUL character(s) removed".to_string(),
+            "Mixed line endings (1 \\r\\n, 2 \\n, 1 \\r) normalized to \\n".to_string(),
+        ]);
+
+        let (text, diagnostics) = sanitize_text("Titre;ISWC\r\nA;T1\r\n".to_string());
+        assert_eq!(text, "Titre;ISWC\r\nA;T1\r\n");
+        assert!(diagnostics.is_clean());
+    }
+
+    #[test]
+    fn test_ragged_rows() {
+        let csv = "Export SACEM\nTitre;ISWC;IPI\nA;T1;1\nB;T2\nC;T3;3;\"x\"\nD;T4;4;\n";
+        let options = ParseOptions { skip_first: 1, ..ParseOptions::default() };
+        let result = parse_bytes_with_options(csv.as_bytes(), &options).unwrap();
+        assert_eq!(result.records.len(), 4);
+        assert!(result.ragged_rows.is_empty());
+
+        let options = ParseOptions { ragged_rows: RaggedRows::Warn, ..options };
+        let result = parse_bytes_with_options(csv.as_bytes(), &options).unwrap();
+        assert_eq!(result.records.len(), 4);
+        assert_eq!(result.records[1]["IPI"], "");
+        // A trailing empty cell (`D;T4;4;`) is not ragged
+        assert_eq!(result.ragged_rows, vec![
+            RaggedRow { line: 4, cells: 2, expected: 3, rejected: false },
+            RaggedRow { line: 5, cells: 4, expected: 3, rejected: false },
+        ]);
+        assert_eq!(result.ragged_rows[0].to_string(), "line 4: 2 cells instead of 3, padded with empty cells");
+
+        let options = ParseOptions { ragged_rows: RaggedRows::Reject, ..options };
+        let result = parse_bytes_with_options(csv.as_bytes(), &options).unwrap();
+        assert_eq!(result.records.len(), 2);
+        assert!(result.ragged_rows.iter().all(|row| row.rejected));
+        assert_eq!("warn".parse::<RaggedRows>(), Ok(RaggedRows::Warn));
+    }
+
+    #[test]
+    fn test_merge_header_rows() {
+        let csv = "Œuvre;;Ayant droit;;\nTitre;ISWC;Nom;IPI;Notes\nA;T1;X;1;\nB;T2;Y;2;ok";
+        let options = ParseOptions { header_rows: 2, ..ParseOptions::default() };
+        let result = parse_bytes_with_options(csv.as_bytes(), &options).unwrap();
+
+        assert_eq!(result.headers, vec!["OEuvre / Titre", "OEuvre / ISWC", "Ayant droit / Nom", "Ayant droit / IPI", "Ayant droit / Notes"]);
+        assert_eq!(result.original_headers["OEuvre / Titre"], "Œuvre / Titre");
+        assert_eq!(result.records.len(), 2);
+        assert_eq!(result.records[1]["Ayant droit / IPI"], "2");
+        // A group repeating the field name is not doubled
+        let rows = vec![vec!["Date".to_string(), "".to_string()], vec!["Date".to_string(), "Heure".to_string()]];
+        assert_eq!(merge_header_rows(rows), vec!["Date", "Date / Heure"]);
+    }
+
+    #[test]
+    fn test_header_row() {
+        let csv = "Export SACEM\nPériode : 2024-T1\nÉditeur : X\nTitre;IPI\nA;1\nB;2";
+        let options = ParseOptions::default().with_header_row(4);
+        assert_eq!(options.skip_first, 3);
+        let result = parse_bytes_with_options(csv.as_bytes(), &options).unwrap();
+
+        assert_eq!(result.headers, vec!["Titre", "IPI"]);
+        assert_eq!(result.records.len(), 2);
+    }
+
+    #[test]
+    fn test_checksum_sha256() {
+        assert_eq!(
+            checksum_sha256(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_windows_1252_detected_from_c1_bytes() {
+        // "Prix: 5€ — cœur" in Windows-1252 (0x80 = €, 0x97 = —, 0x9C = œ)
+        let bytes: &[u8] = b"Prix: 5\x80 \x97 c\x9Cur";
+        let guess = detect_encoding_with_confidence(bytes);
+
+        assert_eq!(guess.encoding, "windows-1252");
+        assert!(guess.confidence >= LOW_ENCODING_CONFIDENCE);
+        assert_eq!(decode_content(bytes, &guess.encoding).unwrap(), "Prix: 5€ — cœur");
+    }
+
+    #[test]
+    fn test_bom_and_utf16() {
+        // Excel "CSV UTF-8": BOM before the header
+        let bytes: &[u8] = b"\xEF\xBB\xBFISWC;Titre\nT1234567890;Caf\xC3\xA9\n";
+        let result = parse_bytes_auto(bytes, None).unwrap();
+        assert_eq!(result.encoding, "utf-8");
+        assert_eq!(result.headers, vec!["ISWC", "Titre"]);
+        assert_eq!(result.records[0]["Titre"], "Café");
+
+        // Excel "Unicode text": UTF-16LE with BOM, tab-separated, CRLF
+        let text = "ISWC\tTitre\r\nT1234567890\tCœur\r\n";
+        let mut utf16le = vec![0xFF, 0xFE];
+        utf16le.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        let result = parse_bytes_auto(&utf16le, None).unwrap();
+        assert_eq!(result.encoding, "utf-16le");
+        assert_eq!(result.headers, vec!["ISWC", "Titre"]);
+        assert_eq!(result.records[0]["Titre"], "Cœur");
+
+        // Without BOM (detected from the zero bytes), and big-endian
+        assert_eq!(detect_encoding(&utf16le[2..]), "utf-16le");
+        assert_eq!(decode_content(&utf16le[2..], "utf-16le").unwrap(), text);
+        let utf16be: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        assert_eq!(detect_encoding(&utf16be), "utf-16be");
+        assert_eq!(decode_content(&utf16be, "UTF-16BE").unwrap(), text);
+
+        // The BOM wins over a forced encoding and never reaches the text
+        assert_eq!(decode_content(b"\xEF\xBB\xBFCaf\xC3\xA9", "windows-1252").unwrap(), "Café");
+    }
+
+    #[test]
+    fn test_decode_quality_flags_control_chars() {
+        assert_eq!(decode_quality("plain ascii"), 1.0);
+        assert_eq!(decode_quality("Société"), 1.0);
+        assert!(decode_quality("c\u{9c}ur \u{80}") < LOW_ENCODING_CONFIDENCE);
+    }
+
+    #[test]
+    fn test_forced_encoding() {
+        let bytes: &[u8] = b"Titre;Prix\nA;5\xA4";
+        let options = ParseOptions { encoding: Some("iso-8859-15".to_string()), ..ParseOptions::default() };
+        let result = parse_bytes_with_options(bytes, &options).unwrap();
+
+        assert_eq!(result.encoding, "iso-8859-15");
+        assert_eq!(result.encoding_confidence, 1.0);
+        assert_eq!(result.records[0]["Prix"], "5€");
+
+        // JSON sources honour the forced encoding too
+        let json: &[u8] = b"[{\"Nom\": \"H\xE9l\xE8ne\"}]";
+        assert_eq!(parse_json_with_encoding(json, Some("Latin1")).unwrap().records[0]["Nom"], "Hélène");
+
+        // A mistyped encoding is refused instead of decoding as lossy UTF-8
+        assert_eq!(check_encoding(" CP1252 ").unwrap(), "windows-1252");
+        assert_eq!(check_encoding("shift_jis").unwrap(), "shift_jis");
+        let options = ParseOptions { encoding: Some("latin-one".to_string()), ..ParseOptions::default() };
+        assert!(parse_bytes_with_options(bytes, &options).unwrap_err().message.contains("Unknown encoding"));
+    }
+
+    #[test]
+    fn test_forced_delimiter() {
+        let csv = "Titre;ISWC\nUn, deux, trois;T1234567890\nA, B, C;T2222222222\n";
+        let result = parse_bytes_auto(csv.as_bytes(), Some(';')).unwrap();
+        assert_eq!(result.delimiter, ';');
+        assert_eq!(result.records[0]["Titre"], "Un, deux, trois");
+
+        // The forced delimiter wins over detection
+        let result = parse_bytes_auto(csv.as_bytes(), Some(',')).unwrap();
+        assert_eq!(result.headers, vec!["Titre;ISWC"]);
+
+        assert_eq!(check_delimiter("tab").unwrap(), '\t');
+        assert_eq!(check_delimiter("|").unwrap(), '|');
+        assert!(check_delimiter(";;").is_err());
+        assert!(check_delimiter("a").is_err());
+    }
+
     #[test]
     fn test_latin1_decoding() {
         // "Société" in ISO-8859-1
@@ -398,4 +1796,83 @@ mod tests {
         let decoded = decode_content(bytes, "iso-8859-1").unwrap();
         assert!(decoded.contains("Soci"));
     }
+
+    #[test]
+    fn test_fixed_width_detected() {
+        let text = "\
+ISWC           Titre               IPI
+T1234567890    Ma chanson          00123456789
+T2345678901    Autre titre long    00987654321
+T3456789012    Court               00111111111
+Total: 3
+";
+        let result = parse_bytes_auto(text.as_bytes(), None).unwrap();
+
+        assert_eq!(result.headers, vec!["ISWC", "Titre", "IPI"]);
+        assert_eq!(result.records.len(), 3);
+        assert_eq!(result.records[1]["Titre"], "Autre titre long");
+        assert_eq!(result.records[2]["IPI"], "00111111111");
+        assert_eq!(result.footer_lines, vec!["Total: 3"]);
+        assert_eq!(result.lines, vec![2, 3, 4]);
+        assert_eq!(result.fixed_width.unwrap()[1], FixedWidthColumn::new("Titre", 15, 20));
+
+        // Delimited files are never read as fixed-width
+        assert!(detect_fixed_width("a;b\n1;2\n3;4").is_none());
+    }
+
+    #[test]
+    fn test_fixed_width_explicit_layout() {
+        let layout = FixedWidthColumn::parse_layout("iswc:0:11, title:11:10").unwrap();
+        let options = ParseOptions { fixed_width: Some(layout), ..ParseOptions::default() };
+        let result = parse_bytes_with_options(b"T1234567890Song\nT2345678901Other song", &options).unwrap();
+
+        assert_eq!(result.records.len(), 2);
+        assert_eq!(result.records[0]["title"], "Song");
+        assert_eq!(result.records[1]["title"], "Other song");
+        assert!(FixedWidthColumn::parse_layout("iswc:0").is_err());
+    }
+
+    #[test]
+    fn test_parse_json_records() {
+        let array = r#"[{"Titre": "Song", "Duree": 245}, {"Titre": "Other", "ISWC": null}]"#;
+        let mut result = parse_json_records(array).unwrap();
+        assert_eq!(result.headers, vec!["Duree", "Titre", "ISWC"]);
+        assert_eq!(result.records[0]["Duree"], 245);
+
+        result.stringify_values();
+        assert_eq!(result.records[0]["Duree"], "245");
+        assert_eq!(result.records[1]["ISWC"], "");
+
+        let ndjson = "{\"Titre\": \"Song\"}\n\n{\"Titre\": \"Other\"}\n";
+        assert_eq!(parse_json_records(ndjson).unwrap().lines, vec![1, 3]);
+        assert_eq!(parse_json_records("{\"a\": 1}\n[1]").unwrap_err().line, 2);
+        assert_eq!(parse_json_records("\n\n{\"a\": 1}\n").unwrap().lines, vec![3]);
+        assert_eq!(parse_json_records("\n{\"a\": 1}\n{").unwrap_err().line, 3);
+    }
+
+    #[test]
+    fn test_parse_json_auto_and_jsonl() {
+        // Latin-1 export with a nested object
+        let array = b"[{\"Titre\": \"Caf\xe9\", \"Ayant droit\": {\"IPI\": 123, \"R\xf4le\": \"C\"}}]";
+        let result = parse_json_auto(array).unwrap();
+        assert_eq!(result.records[0]["Titre"], "Café");
+        assert_eq!(result.records[0]["Ayant droit.IPI"], 123);
+        assert_eq!(result.headers, vec!["Ayant droit.IPI", "Ayant droit.Rôle", "Titre"]);
+
+        let jsonl = b"{\"Titre\": \"Song\"}\n\n{\"Titre\": \"Other\", \"ISWC\": \"T1234567890\"}\n";
+        assert_eq!(parse_jsonl_auto(jsonl).unwrap().records.len(), 2);
+        assert_eq!(parse_json_auto(jsonl).unwrap().headers, vec!["Titre", "ISWC"]);
+        assert_eq!(parse_jsonl_auto(b"{\"a\": 1}\n{oops}").unwrap_err().line, 2);
+    }
+
+    #[test]
+    fn test_input_format_resolution() {
+        assert_eq!(InputFormat::Auto.resolve(b"\xEF\xBB\xBF [{\"a\":1}]"), InputFormat::Json);
+        assert_eq!(InputFormat::Auto.resolve(b"a;b\n1;2"), InputFormat::Csv);
+        assert_eq!(InputFormat::Csv.resolve(b"{}"), InputFormat::Csv);
+        assert_eq!(InputFormat::from_hints(Some("application/x-ndjson"), None), InputFormat::Json);
+        assert_eq!(InputFormat::from_hints(None, Some("export.JSONL")), InputFormat::Json);
+        assert_eq!(InputFormat::from_hints(Some("application/octet-stream"), Some("data")), InputFormat::Auto);
+        assert_eq!("ndjson".parse::<InputFormat>(), Ok(InputFormat::Json));
+    }
 }
@@ -0,0 +1,187 @@
+//! Per-column profiling of parsed records, to explore unfamiliar exports.
+//!
+//! Computed once right after parsing (excluded columns are already gone) as a
+//! [`ParseReport`]: returned in `csvInfo.report`, summarized for the AI prompt
+//! and kept with the job for `GET /api/v1/jobs/{id}/columns`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+use crate::ai::prompt::value_pattern;
+
+/// Maximum value patterns reported per column
+pub const MAX_PROFILE_PATTERNS: usize = 5;
+
+/// Maximum sample values reported per column
+pub const MAX_PROFILE_SAMPLES: usize = 3;
+
+/// Sample values longer than this (in characters) are cut
+const MAX_SAMPLE_LEN: usize = 40;
+
+/// Share of distinct values above which a column is high-cardinality
+pub const HIGH_CARDINALITY_RATIO: f64 = 0.9;
+
+/// Filled rows below which cardinality says nothing
+const MIN_CARDINALITY_ROWS: usize = 10;
+
+/// Column statistics of a parsed file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseReport {
+    /// Data rows the statistics were computed on
+    pub rows: usize,
+    /// One profile per column, in header order
+    pub columns: Vec<ColumnProfile>,
+}
+
+impl ParseReport {
+    pub fn new(headers: &[String], records: &[Value]) -> Self {
+        Self { rows: records.len(), columns: profile_columns(headers, records) }
+    }
+
+    /// Columns without any value
+    pub fn empty_columns(&self) -> impl Iterator<Item = &ColumnProfile> {
+        self.columns.iter().filter(|c| c.filled == 0)
+    }
+
+    /// Columns whose values are nearly all different (identifiers, free text)
+    pub fn high_cardinality_columns(&self) -> impl Iterator<Item = &ColumnProfile> {
+        self.columns.iter().filter(|c| c.is_high_cardinality())
+    }
+}
+
+/// Statistics of one source column
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnProfile {
+    pub name: String,
+    /// Rows with a non-empty value
+    pub filled: usize,
+    /// Share of rows with no value (0.0 to 1.0)
+    pub null_rate: f64,
+    /// Distinct non-empty values
+    pub distinct: usize,
+    /// Shortest and longest non-empty value, in characters (0 when the column is empty)
+    pub min_length: usize,
+    pub max_length: usize,
+    /// Most frequent value patterns (see [`value_pattern`]), most frequent first
+    pub patterns: Vec<PatternCount>,
+    /// First distinct values, in file order (long values cut)
+    #[serde(default)]
+    pub samples: Vec<String>,
+}
+
+impl ColumnProfile {
+    /// Share of rows with a value (0.0 to 1.0)
+    pub fn fill_rate(&self) -> f64 {
+        1.0 - self.null_rate
+    }
+
+    /// Nearly every filled row holds a different value
+    pub fn is_high_cardinality(&self) -> bool {
+        self.filled >= MIN_CARDINALITY_ROWS && self.distinct as f64 >= self.filled as f64 * HIGH_CARDINALITY_RATIO
+    }
+}
+
+/// A value pattern and the number of values matching it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PatternCount {
+    pub pattern: String,
+    pub count: usize,
+}
+
+/// Profile every column of the records, in header order
+pub fn profile_columns(headers: &[String], records: &[Value]) -> Vec<ColumnProfile> {
+    headers.iter().map(|header| profile_column(header, records)).collect()
+}
+
+fn profile_column(header: &str, records: &[Value]) -> ColumnProfile {
+    let values: Vec<String> = records
+        .iter()
+        .filter_map(|r| r.get(header))
+        .filter_map(cell_text)
+        .collect();
+
+    let mut distinct: HashSet<&str> = HashSet::new();
+    let mut samples = Vec::new();
+    for value in &values {
+        if distinct.insert(value) && samples.len() < MAX_PROFILE_SAMPLES {
+            samples.push(match value.char_indices().nth(MAX_SAMPLE_LEN) {
+                Some((end, _)) => format!("{}…", &value[..end]),
+                None => value.clone(),
+            });
+        }
+    }
+    let lengths = values.iter().map(|v| v.chars().count());
+
+    let mut patterns: HashMap<String, usize> = HashMap::new();
+    for value in &values {
+        *patterns.entry(value_pattern(value)).or_default() += 1;
+    }
+    let mut patterns: Vec<PatternCount> = patterns
+        .into_iter()
+        .map(|(pattern, count)| PatternCount { pattern, count })
+        .collect();
+    patterns.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.pattern.cmp(&b.pattern)));
+    patterns.truncate(MAX_PROFILE_PATTERNS);
+
+    ColumnProfile {
+        name: header.to_string(),
+        filled: values.len(),
+        null_rate: if records.is_empty() { 0.0 } else { 1.0 - values.len() as f64 / records.len() as f64 },
+        distinct: distinct.len(),
+        min_length: lengths.clone().min().unwrap_or(0),
+        max_length: lengths.max().unwrap_or(0),
+        patterns,
+        samples,
+    }
+}
+
+/// Trimmed text of a cell, None when empty
+fn cell_text(value: &Value) -> Option<String> {
+    let text = match value {
+        Value::Null => return None,
+        Value::String(s) => s.trim().to_string(),
+        other => other.to_string(),
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_profile_columns() {
+        let records = vec![
+            json!({"ISWC": "T-123.456.789-0", "Role": "CA", "Duree": 245}),
+            json!({"ISWC": "T-987.654.321-0", "Role": "CA", "Duree": null}),
+            json!({"ISWC": "", "Role": "A"}),
+            json!({"ISWC": "T1234567890", "Role": " CA "}),
+        ];
+        let headers: Vec<String> = ["ISWC", "Role", "Duree"].iter().map(|h| h.to_string()).collect();
+        let profiles = profile_columns(&headers, &records);
+
+        let iswc = &profiles[0];
+        assert_eq!(iswc.filled, 3);
+        assert_eq!(iswc.null_rate, 0.25);
+        assert_eq!(iswc.distinct, 3);
+        assert_eq!((iswc.min_length, iswc.max_length), (11, 15));
+        assert_eq!(iswc.patterns[0], PatternCount { pattern: value_pattern("T-123.456.789-0"), count: 2 });
+
+        assert_eq!(iswc.samples, vec!["T-123.456.789-0", "T-987.654.321-0", "T1234567890"]);
+
+        assert_eq!(profiles[1].distinct, 2);
+        assert_eq!(profiles[1].samples, vec!["CA", "A"]);
+        assert_eq!(profiles[2].filled, 1);
+        assert_eq!(profiles[2].null_rate, 0.75);
+
+        let codes: Vec<Value> = (0..12).map(|i| json!({"Code": format!("W{}", i), "Vide": ""})).collect();
+        let report = ParseReport::new(&["Code".to_string(), "Vide".to_string()], &codes);
+        assert_eq!(report.high_cardinality_columns().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["Code"]);
+        assert_eq!(report.empty_columns().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["Vide"]);
+        assert!(!profiles[0].is_high_cardinality());
+    }
+}
@@ -0,0 +1,234 @@
+//! Row sampling for huge files
+//!
+//! The first N rows often come from a single publisher and miss edge cases
+//! (role codes that only appear late in the file). A sample keeps the first
+//! rows, adds rows with rare codes and missing values, and fills the rest with
+//! one random row per slice of the file. The random part is seeded: the same
+//! file and seed always give the same sample.
+
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+
+/// Columns with at most this many distinct values are treated as codes
+const MAX_CODE_VALUES: usize = 30;
+
+/// Rows always taken from the start of the file by [`stratified_sample`]
+pub const DEFAULT_HEAD_ROWS: usize = 3;
+
+/// How [`sample_rows`] picks rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleOptions {
+    /// First rows of the file, always kept (capped at a quarter of the sample)
+    pub head: usize,
+    /// Seed of the random rows
+    pub seed: u64,
+}
+
+impl Default for SampleOptions {
+    fn default() -> Self {
+        Self { head: DEFAULT_HEAD_ROWS, seed: 0 }
+    }
+}
+
+/// Select `n` representative rows, in file order.
+///
+/// After the first rows, up to half of the budget goes to rows holding rare
+/// code values (rarest first), up to a quarter to rows with an empty column;
+/// the rest is one random row per equal slice of the file.
+pub fn sample_rows(records: &[Value], n: usize, options: &SampleOptions) -> Vec<Value> {
+    if records.len() <= n {
+        return records.to_vec();
+    }
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let mut picked: BTreeSet<usize> = (0..options.head.min(n / 4).max(1)).collect();
+
+    // Rare code values: first row holding each value, rarest first
+    let rare_budget = n / 2;
+    for row in rare_value_rows(records) {
+        if picked.len() >= rare_budget.max(1) {
+            break;
+        }
+        picked.insert(row);
+    }
+
+    // One row per column with a missing value
+    let missing_budget = picked.len() + n / 4;
+    for row in missing_value_rows(records) {
+        if picked.len() >= missing_budget {
+            break;
+        }
+        picked.insert(row);
+    }
+
+    // One random row per slice of the file
+    let remaining = n.saturating_sub(picked.len());
+    let mut rng = SplitMix64(options.seed);
+    let step = records.len() as f64 / remaining.max(1) as f64;
+    for slice in 0..remaining {
+        let start = (slice as f64 * step) as usize;
+        let end = (((slice + 1) as f64 * step) as usize).clamp(start + 1, records.len());
+        let row = start + (rng.next() % (end - start) as u64) as usize;
+        if !picked.insert(row) {
+            // Already picked: take the next free row
+            if let Some(free) = (row..records.len()).chain(0..row).find(|r| !picked.contains(r)) {
+                picked.insert(free);
+            }
+        }
+    }
+
+    picked.into_iter().take(n).map(|i| records[i].clone()).collect()
+}
+
+/// AI preview sample: the first rows, rare codes, missing values and random
+/// rows spread across the file
+pub fn stratified_sample(records: &[Value], n: usize) -> Vec<Value> {
+    sample_rows(records, n, &SampleOptions::default())
+}
+
+/// Sample of a later AI attempt: same first, rare-code and missing-value rows,
+/// other random rows (attempt 0 is [`stratified_sample`])
+pub fn varied_sample(records: &[Value], n: usize, attempt: usize) -> Vec<Value> {
+    sample_rows(records, n, &SampleOptions { seed: attempt as u64, ..SampleOptions::default() })
+}
+
+/// Small seeded generator (SplitMix64): reproducible samples without a dependency
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// Text of a cell, None when empty or missing
+fn cell(record: &Value, column: &str) -> Option<String> {
+    match record.get(column)? {
+        Value::Null => None,
+        Value::String(s) if s.trim().is_empty() => None,
+        Value::String(s) => Some(s.trim().to_string()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// Column names, in first-seen order
+fn columns(records: &[Value]) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::new();
+    for record in records {
+        if let Some(obj) = record.as_object() {
+            for key in obj.keys() {
+                if !columns.contains(key) {
+                    columns.push(key.clone());
+                }
+            }
+        }
+    }
+    columns
+}
+
+/// First row of each value of code-like columns, rarest values first
+fn rare_value_rows(records: &[Value]) -> Vec<usize> {
+    let mut candidates: Vec<(usize, usize)> = Vec::new(); // (count, first row)
+
+    for column in columns(records) {
+        let mut values: HashMap<String, (usize, usize)> = HashMap::new();
+        for (i, record) in records.iter().enumerate() {
+            if let Some(value) = cell(record, &column) {
+                let entry = values.entry(value).or_insert((0, i));
+                entry.0 += 1;
+            }
+            if values.len() > MAX_CODE_VALUES {
+                break;
+            }
+        }
+        if values.len() > 1 && values.len() <= MAX_CODE_VALUES {
+            candidates.extend(values.into_values());
+        }
+    }
+
+    candidates.sort();
+    candidates.into_iter().map(|(_, row)| row).collect()
+}
+
+/// First row with an empty value, for each column that has some
+fn missing_value_rows(records: &[Value]) -> Vec<usize> {
+    columns(records)
+        .iter()
+        .filter_map(|column| records.iter().position(|r| cell(r, column).is_none()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn catalog() -> Vec<Value> {
+        (0..1000)
+            .map(|i| {
+                let role = match i {
+                    700 => "AR",
+                    900 => "SE",
+                    _ if i % 2 == 0 => "C",
+                    _ => "A",
+                };
+                let ipi = if i == 850 { "" } else { "123456789" };
+                json!({"Title": format!("Song {i}"), "Role": role, "IPI": ipi})
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sample_includes_rare_and_missing_values() {
+        let records = catalog();
+        let sample = stratified_sample(&records, 10);
+
+        assert_eq!(sample.len(), 10);
+        let titles: Vec<&str> = sample.iter().map(|r| r["Title"].as_str().unwrap()).collect();
+        assert!(titles.contains(&"Song 700"), "rare role AR missing: {titles:?}");
+        assert!(titles.contains(&"Song 900"), "rare role SE missing: {titles:?}");
+        assert!(titles.contains(&"Song 850"), "row with empty IPI missing: {titles:?}");
+        // In file order and spread across the file, not only the first rows
+        assert_eq!(titles[0], "Song 0");
+        let rows: Vec<usize> = titles.iter().map(|t| t[5..].parse().unwrap()).collect();
+        assert!(rows.windows(2).all(|w| w[0] < w[1]));
+        assert!(rows.iter().filter(|&&r| r >= 100).count() > 5);
+    }
+
+    #[test]
+    fn test_sample_keeps_head_and_is_seeded() {
+        let records = catalog();
+        let options = SampleOptions { head: 5, seed: 7 };
+        let sample = sample_rows(&records, 20, &options);
+
+        assert_eq!(sample.len(), 20);
+        let titles: Vec<&str> = sample.iter().map(|r| r["Title"].as_str().unwrap()).collect();
+        assert_eq!(titles[..5], ["Song 0", "Song 1", "Song 2", "Song 3", "Song 4"]);
+        assert_eq!(sample_rows(&records, 20, &options), sample);
+        assert_ne!(sample_rows(&records, 20, &SampleOptions { seed: 8, ..options }), sample);
+    }
+
+    #[test]
+    fn test_small_file_is_returned_as_is() {
+        let records = catalog()[..5].to_vec();
+        assert_eq!(stratified_sample(&records, 10), records);
+        assert!(stratified_sample(&records, 0).is_empty());
+    }
+
+    #[test]
+    fn test_varied_sample_keeps_rare_rows() {
+        let records = catalog();
+        assert_eq!(varied_sample(&records, 10, 0), stratified_sample(&records, 10));
+
+        let second = varied_sample(&records, 10, 1);
+        assert_eq!(second.len(), 10);
+        assert_ne!(second, stratified_sample(&records, 10));
+        assert!(second.iter().any(|r| r["Title"] == "Song 700"));
+    }
+}
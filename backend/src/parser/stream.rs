@@ -0,0 +1,361 @@
+//! Streaming CSV parser for catalogs too large to hold in memory.
+//!
+//! [`parse_csv_stream`] detects the encoding and the delimiter on the first
+//! [`SNIFF_BYTES`], then decodes and parses the rest lazily: records are
+//! yielded one at a time, only the last few are held back for footer
+//! detection. Records are the same as [`parse_bytes_with_options`] returns.
+//!
+//! [`parse_bytes_with_options`]: super::parse_bytes_with_options
+
+use serde_json::Value;
+use std::collections::{BTreeMap, VecDeque};
+use std::io::{self, BufRead, BufReader, Chain, Cursor, Read};
+
+use super::{
+    clean_cell, decode_content, detect_delimiter, detect_fixed_width, merge_header_rows, normalize_headers,
+    is_summary_row, ragged_row, record_object, resolve_encoding, CsvError, ParseOptions, RaggedRow, MAX_FOOTER_LINES,
+};
+
+/// Bytes read ahead to detect the encoding and the delimiter
+pub const SNIFF_BYTES: usize = 64 * 1024;
+
+/// Size of the chunks read from the source
+const CHUNK_BYTES: usize = 8 * 1024;
+
+/// Decoded source: the sniffed bytes, then the rest of the reader
+type Source<R> = BufReader<DecodeReader<Chain<Cursor<Vec<u8>>, R>>>;
+
+/// CSV records read lazily from a reader (see [`parse_csv_stream`])
+pub struct CsvStream<R: Read> {
+    pub headers: Vec<String>,
+    pub encoding: String,
+    /// Decode-quality score of the encoding, on the sniffed bytes
+    pub encoding_confidence: f32,
+    pub delimiter: char,
+    /// Duplicate headers renamed (original name, new name)
+    pub renamed_columns: Vec<(String, String)>,
+    /// Headers changed by normalization: normalized name → name in the file
+    pub original_headers: BTreeMap<String, String>,
+    /// Dropped trailing rows, cells joined by the delimiter (known once the stream is exhausted)
+    pub footer_lines: Vec<String>,
+    /// Rows whose cell count differs from the header (strict modes only), as they are read;
+    /// rejected rows are not yielded
+    pub ragged_rows: Vec<RaggedRow>,
+    reader: csv::Reader<Source<R>>,
+    options: ParseOptions,
+    /// Records read but not yielded yet, with their line: they may turn out to be footer rows
+    pending: VecDeque<(usize, Vec<String>)>,
+    /// Number of data records having each count of cells
+    width_counts: Vec<usize>,
+    finished: bool,
+}
+
+/// Parse delimited text lazily from `reader`.
+///
+/// Options are the same as for whole files; fixed-width text is not
+/// supported (it is rejected, whether forced or detected).
+pub fn parse_csv_stream<R: Read>(mut reader: R, options: &ParseOptions) -> Result<CsvStream<R>, CsvError> {
+    if options.fixed_width.is_some() {
+        return Err(CsvError::new(0, "Fixed-width files cannot be streamed"));
+    }
+
+    let mut sample = Vec::with_capacity(SNIFF_BYTES);
+    (&mut reader).take(SNIFF_BYTES as u64).read_to_end(&mut sample)
+        .map_err(|e| CsvError::new(0, format!("Cannot read file: {}", e)))?;
+    // Detection works on whole lines (never on a character cut in half)
+    let complete = if sample.len() < SNIFF_BYTES {
+        &sample[..]
+    } else {
+        sample.iter().rposition(|&b| b == b'\n').map_or(&sample[..], |end| &sample[..=end])
+    };
+
+    let guess = resolve_encoding(complete, options.encoding.as_deref())?;
+    let text = decode_content(complete, &guess.encoding)?;
+    let text: String = text.split_inclusive('\n').skip(options.skip_first).collect();
+    if options.delimiter.is_none() && detect_fixed_width(&text).is_some() {
+        return Err(CsvError::new(0, "Fixed-width files cannot be streamed"));
+    }
+    let delimiter = options.delimiter.unwrap_or_else(|| detect_delimiter(&text));
+    let delimiter_byte = u8::try_from(delimiter).ok().filter(u8::is_ascii)
+        .ok_or_else(|| CsvError::new(1, format!("Unsupported delimiter: '{}'", delimiter)))?;
+
+    let decoded = DecodeReader::new(Cursor::new(sample).chain(reader), &guess.encoding);
+    let mut lines = BufReader::new(decoded);
+    for line in 0..options.skip_first {
+        lines.read_until(b'\n', &mut Vec::new())
+            .map_err(|e| CsvError::new(line + 1, format!("Cannot read line: {}", e)))?;
+    }
+
+    let mut stream = CsvStream {
+        headers: Vec::new(),
+        encoding: guess.encoding,
+        encoding_confidence: guess.confidence,
+        delimiter,
+        renamed_columns: Vec::new(),
+        original_headers: BTreeMap::new(),
+        footer_lines: Vec::new(),
+        ragged_rows: Vec::new(),
+        reader: csv::ReaderBuilder::new()
+            .delimiter(delimiter_byte)
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(lines),
+        options: options.clone(),
+        pending: VecDeque::new(),
+        width_counts: Vec::new(),
+        finished: false,
+    };
+
+    let mut header_rows = Vec::new();
+    for _ in 0..options.header_rows.max(1) {
+        match stream.read_fields()? {
+            Some((_, fields)) => header_rows.push(fields),
+            None => break,
+        }
+    }
+    if header_rows.is_empty() {
+        return Err(CsvError::new(options.skip_first + 1, "Empty CSV file"));
+    }
+    stream.headers = merge_header_rows(header_rows);
+    (stream.original_headers, stream.renamed_columns) = normalize_headers(&mut stream.headers);
+    Ok(stream)
+}
+
+impl<R: Read> CsvStream<R> {
+    /// 1-based line and cells of the next non-blank record
+    fn read_fields(&mut self) -> Result<Option<(usize, Vec<String>)>, CsvError> {
+        let mut record = csv::ByteRecord::new();
+        loop {
+            let line = self.reader.position().line() as usize + self.options.skip_first;
+            match self.reader.read_byte_record(&mut record) {
+                Ok(true) => {
+                    let fields: Vec<String> = record.iter()
+                        .map(|cell| clean_cell(&String::from_utf8_lossy(cell)))
+                        .collect();
+                    if fields.len() > 1 || fields.first().is_some_and(|f| !f.is_empty()) {
+                        let start = record.position().map_or(line, |p| p.line() as usize + self.options.skip_first);
+                        return Ok(Some((start, fields)));
+                    }
+                }
+                Ok(false) => return Ok(None),
+                Err(e) => return Err(CsvError::new(line, format!("Cannot read record: {}", e))),
+            }
+        }
+    }
+
+    /// Records held back for footer detection
+    fn hold_back(&self) -> usize {
+        self.options.skip_last + if self.options.detect_footer { MAX_FOOTER_LINES } else { 0 }
+    }
+
+    fn count_width(&mut self, fields: &[String], delta: isize) {
+        let width = fields.len();
+        if self.width_counts.len() <= width {
+            self.width_counts.resize(width + 1, 0);
+        }
+        self.width_counts[width] = self.width_counts[width].saturating_add_signed(delta);
+    }
+
+    /// Move the trailing rows to `footer_lines`: `skip_last` rows, then the
+    /// detected summary rows (same rule as for whole files)
+    fn finish(&mut self) {
+        self.finished = true;
+        let explicit = self.options.skip_last.min(self.pending.len());
+        let mut footer: Vec<(usize, Vec<String>)> = self.pending.split_off(self.pending.len() - explicit).into();
+        for (_, fields) in &footer {
+            self.count_width(fields, -1);
+        }
+
+        let total: usize = self.width_counts.iter().sum();
+        let columns = self.headers.len();
+        if self.options.detect_footer && columns >= 2 && total >= 3 {
+            // Median number of cells of a data row
+            let mut seen = 0;
+            let typical = self.width_counts.iter()
+                .position(|&count| {
+                    seen += count;
+                    seen > total / 2
+                })
+                .unwrap_or(0);
+            let regular = typical == columns;
+            let detected = self.pending.iter()
+                .rev()
+                .take(MAX_FOOTER_LINES.min(total - 1))
+                .take_while(|(_, fields)| is_summary_row(fields) || (regular && fields.len() != columns))
+                .count();
+            let mut dropped: Vec<(usize, Vec<String>)> = self.pending.split_off(self.pending.len() - detected).into();
+            dropped.append(&mut footer);
+            footer = dropped;
+        }
+
+        let delimiter = self.delimiter.to_string();
+        self.footer_lines = footer.into_iter().map(|(_, fields)| fields.join(&delimiter)).collect();
+    }
+}
+
+impl<R: Read> Iterator for CsvStream<R> {
+    type Item = Result<Value, CsvError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.finished || self.pending.len() > self.hold_back() {
+                let (line, fields) = self.pending.pop_front()?;
+                // Same check as for whole files, once the row is known not to be a footer
+                if let Some(ragged) = ragged_row(&fields, self.headers.len(), line, self.options.ragged_rows) {
+                    let rejected = ragged.rejected;
+                    self.ragged_rows.push(ragged);
+                    if rejected {
+                        continue;
+                    }
+                }
+                return Some(Ok(record_object(&self.headers, &fields)));
+            }
+            match self.read_fields() {
+                Ok(Some((line, fields))) => {
+                    self.count_width(&fields, 1);
+                    self.pending.push_back((line, fields));
+                }
+                Ok(None) => self.finish(),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// How source bytes become UTF-8 (mirrors [`decode_content`])
+enum Decoding {
+    /// Already UTF-8 (invalid sequences are replaced cell by cell), the BOM is dropped
+    Utf8,
+    /// True Latin-1: every byte is the code point of the same value
+    Latin1,
+    Other(encoding_rs::Decoder),
+}
+
+/// Reader decoding its source to UTF-8 chunk by chunk
+struct DecodeReader<R> {
+    inner: R,
+    decoding: Decoding,
+    output: Vec<u8>,
+    position: usize,
+    /// No chunk decoded yet (a UTF-8 BOM may come first)
+    start: bool,
+    done: bool,
+}
+
+impl<R: Read> DecodeReader<R> {
+    fn new(inner: R, encoding: &str) -> Self {
+        let decoding = match encoding.to_lowercase().as_str() {
+            "utf-8" | "utf8" | "ascii" => Decoding::Utf8,
+            "iso-8859-1" | "latin-1" | "latin1" => Decoding::Latin1,
+            label => match encoding_rs::Encoding::for_label(label.as_bytes()) {
+                Some(enc) if enc != encoding_rs::UTF_8 => Decoding::Other(enc.new_decoder_with_bom_removal()),
+                _ => Decoding::Utf8,
+            },
+        };
+        Self { inner, decoding, output: Vec::new(), position: 0, start: true, done: false }
+    }
+
+    /// Decode the next chunk into `output`
+    fn fill(&mut self) -> io::Result<()> {
+        let mut chunk = [0u8; CHUNK_BYTES];
+        let read = self.inner.read(&mut chunk)?;
+        let last = read == 0;
+        self.output.clear();
+        self.position = 0;
+        match &mut self.decoding {
+            Decoding::Utf8 => {
+                let bytes = &chunk[..read];
+                let bytes = if self.start { bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes) } else { bytes };
+                self.output.extend_from_slice(bytes);
+            }
+            Decoding::Latin1 => {
+                let text: String = chunk[..read].iter().map(|&b| b as char).collect();
+                self.output.extend_from_slice(text.as_bytes());
+            }
+            Decoding::Other(decoder) => {
+                let capacity = decoder.max_utf8_buffer_length(read).unwrap_or(read * 4 + 16);
+                let mut text = String::with_capacity(capacity);
+                let _ = decoder.decode_to_string(&chunk[..read], &mut text, last);
+                self.output.extend_from_slice(text.as_bytes());
+            }
+        }
+        self.start = false;
+        self.done = last;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecodeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.output.len() {
+            if self.done {
+                return Ok(0);
+            }
+            self.fill()?;
+        }
+        let n = buf.len().min(self.output.len() - self.position);
+        buf[..n].copy_from_slice(&self.output[self.position..self.position + n]);
+        self.position += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_bytes_with_options, RaggedRows};
+
+    #[test]
+    fn test_stream_matches_whole_file_parser() {
+        let mut csv = b"Export catalogue\nTitre;Compositeur;IPI\n".to_vec();
+        for i in 0..3000 {
+            csv.extend_from_slice(b"Op\xe9ra n\xb0");
+            csv.extend_from_slice(format!("{};\"Dupont; J.\";{}\n", i, 100000000 + i).as_bytes());
+        }
+        csv.extend_from_slice(b"\"Chanson\nsur deux lignes\";\"Dit \"\"X\"\"\";1\nTotal : 3001 \x9cuvres;;\n");
+        let options = ParseOptions { skip_first: 1, ..ParseOptions::default() };
+
+        let whole = parse_bytes_with_options(&csv, &options).unwrap();
+        let mut stream = parse_csv_stream(&csv[..], &options).unwrap();
+        assert_eq!(stream.encoding, "windows-1252");
+        assert_eq!(stream.delimiter, ';');
+        assert_eq!(stream.headers, whole.headers);
+
+        let records: Vec<Value> = stream.by_ref().collect::<Result<_, _>>().unwrap();
+        assert_eq!(records.len(), 3001);
+        assert_eq!(records, whole.records);
+        assert_eq!(records[0]["Titre"], "Opéra n°0");
+        assert_eq!(records[3000]["Titre"], "Chanson\nsur deux lignes");
+        assert_eq!(stream.footer_lines, vec!["Total : 3001 œuvres;;"]);
+        assert_eq!(stream.footer_lines, whole.footer_lines);
+    }
+
+    #[test]
+    fn test_stream_ragged_rows() {
+        let csv = "Export SACEM\nTitre;ISWC;IPI\nA;T1;1\nB;T2\nC;T3;3;\"x\"\nD;T4;4;\n";
+        for mode in [RaggedRows::Pad, RaggedRows::Warn, RaggedRows::Reject] {
+            let options = ParseOptions { skip_first: 1, ragged_rows: mode, ..ParseOptions::default() };
+            let whole = parse_bytes_with_options(csv.as_bytes(), &options).unwrap();
+            let mut stream = parse_csv_stream(csv.as_bytes(), &options).unwrap();
+            let records: Vec<Value> = stream.by_ref().collect::<Result<_, _>>().unwrap();
+            assert_eq!(records, whole.records);
+            assert_eq!(stream.ragged_rows, whole.ragged_rows);
+        }
+    }
+
+    #[test]
+    fn test_stream_strips_bom_and_decodes_utf16() {
+        let text = "ISWC\tTitre\r\nT1234567890\tCœur\r\n";
+        let mut utf8 = b"\xEF\xBB\xBF".to_vec();
+        utf8.extend_from_slice(text.as_bytes());
+        let mut utf16le = vec![0xFF, 0xFE];
+        utf16le.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+
+        for bytes in [utf8, utf16le] {
+            let mut stream = parse_csv_stream(&bytes[..], &ParseOptions::default()).unwrap();
+            assert_eq!(stream.headers, vec!["ISWC", "Titre"]);
+            let records: Vec<Value> = stream.by_ref().collect::<Result<_, _>>().unwrap();
+            assert_eq!(records[0]["Titre"], "Cœur");
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Excel workbooks (`.xlsx`), as exported by many rights societies.
+//!
+//! The first worksheet is read like a CSV file: `skip_first` rows are
+//! dropped, the next non-blank row is the header (`header_rows` rows merged
+//! into composite names), and trailing summary rows are detected the same
+//! way. Cells become text as a spreadsheet shows them (integers without `.0`,
+//! dates as `YYYY-MM-DD`).
+
+use calamine::{Data, Reader, Xlsx};
+use std::io::Cursor;
+
+use super::{count_footer_lines, is_summary_row, merge_header_rows, normalize_headers, record_object, CsvError, ParseDiagnostics, ParseOptions, ParseResult};
+
+/// Delimiter reported for workbooks, which have none (cells of footer rows are joined by it)
+pub const XLSX_DELIMITER: char = '\t';
+
+/// Whether `bytes` look like an `.xlsx` workbook (a ZIP archive)
+pub fn is_xlsx(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"PK\x03\x04")
+}
+
+/// Parse the first worksheet of an `.xlsx` workbook
+pub fn parse_xlsx(bytes: &[u8], options: &ParseOptions) -> Result<ParseResult, CsvError> {
+    let mut workbook = Xlsx::new(Cursor::new(bytes))
+        .map_err(|e| CsvError::new(0, format!("Cannot read workbook: {}", e)))?;
+    let range = workbook.worksheet_range_at(0)
+        .ok_or_else(|| CsvError::new(0, "Workbook has no worksheet"))?
+        .map_err(|e| CsvError::new(0, format!("Cannot read worksheet: {}", e)))?;
+
+    // Sheet row number (1-based, as shown by spreadsheet software) of each kept row
+    let first_row = range.start().map_or(0, |(row, _)| row as usize);
+    let (mut rows, mut numbers): (Vec<Vec<String>>, Vec<usize>) = range.rows()
+        .enumerate()
+        .skip(options.skip_first)
+        .map(|(i, row)| (row.iter().map(cell_text).collect::<Vec<String>>(), first_row + i + 1))
+        .filter(|(cells, _)| cells.iter().any(|c| !c.is_empty()))
+        .unzip();
+    if rows.is_empty() {
+        return Err(CsvError::new(options.skip_first + 1, "Empty worksheet"));
+    }
+
+    let header_rows = options.header_rows.clamp(1, rows.len());
+    let mut headers = merge_header_rows(rows.drain(..header_rows).collect());
+    numbers.drain(..header_rows);
+    // Trailing empty header cells are formatting, not columns
+    while headers.last().is_some_and(String::is_empty) {
+        headers.pop();
+    }
+    let (original_headers, renamed_columns) = normalize_headers(&mut headers);
+
+    let explicit = options.skip_last.min(rows.len());
+    let mut footer = rows.split_off(rows.len() - explicit);
+    if options.detect_footer {
+        // Sheet rows span the whole range: only summary rows are footers
+        let summary = |cells: &Vec<String>| is_summary_row(cells);
+        let detected = count_footer_lines(&rows, headers.len(), |_| headers.len(), summary);
+        let mut dropped = rows.split_off(rows.len() - detected);
+        dropped.append(&mut footer);
+        footer = dropped;
+    }
+    numbers.truncate(rows.len());
+    let delimiter = XLSX_DELIMITER.to_string();
+
+    Ok(ParseResult {
+        records: rows.iter().map(|cells| record_object(&headers, cells)).collect(),
+        lines: numbers,
+        encoding: "utf-8".to_string(),
+        encoding_confidence: 1.0,
+        delimiter: XLSX_DELIMITER,
+        headers,
+        footer_lines: footer.iter()
+            .map(|cells| cells.join(&delimiter).trim_end_matches(XLSX_DELIMITER).to_string())
+            .collect(),
+        ragged_rows: Vec::new(),
+        diagnostics: ParseDiagnostics::default(),
+        renamed_columns,
+        original_headers,
+        fixed_width: None,
+    })
+}
+
+/// Cell as text: whole numbers without decimals (IPI numbers are often
+/// stored as numbers), dates as ISO dates
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Empty | Data::Error(_) => String::new(),
+        Data::String(s) | Data::DateTimeIso(s) | Data::DurationIso(s) => s.trim().to_string(),
+        Data::Float(f) if f.fract() == 0.0 && f.abs() < 1e15 => format!("{}", *f as i64),
+        Data::Float(f) => f.to_string(),
+        Data::Int(i) => i.to_string(),
+        Data::Bool(b) => b.to_string(),
+        Data::DateTime(date) => match date.as_datetime() {
+            Some(dt) if date.is_datetime() && dt.time() == chrono::NaiveTime::MIN => dt.date().to_string(),
+            Some(dt) if date.is_datetime() => dt.to_string(),
+            _ => date.as_f64().to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::InputFormat;
+
+    #[test]
+    fn test_parse_xlsx_like_csv() {
+        let bytes = include_bytes!("../../tests/format-xlsx.xlsx");
+        assert_eq!(InputFormat::Auto.resolve(bytes), InputFormat::Xlsx);
+
+        let options = ParseOptions { skip_first: 1, ..ParseOptions::default() };
+        let result = parse_xlsx(bytes, &options).unwrap();
+        assert_eq!(result.headers, vec!["ISWC", "Titre", "IPI", "Role", "Date de depot"]);
+        assert_eq!(result.records.len(), 2);
+        assert_eq!(result.records[0]["Titre"], "Opéra n°1");
+        assert_eq!(result.records[0]["Date de depot"], "2023-03-15");
+        // Numbers stored as floats keep their digits only
+        assert_eq!(result.records[1]["IPI"], "987654321");
+        assert_eq!(result.footer_lines, vec!["Total : 2 œuvres"]);
+    }
+}
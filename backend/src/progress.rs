@@ -0,0 +1,116 @@
+//! Per-stage progress reporting for long runs
+//!
+//! The pipeline reports progress (parsing, transforming, validating, grouping)
+//! to a global observer. Nothing is reported until one is installed with
+//! [`set_progress_observer`], which the CLI does for `massload transform`.
+
+use once_cell::sync::Lazy;
+use std::sync::{Arc, RwLock};
+
+use crate::api::logs::LogStage;
+
+/// Receives progress updates from the pipeline
+pub trait ProgressObserver: Send + Sync {
+    /// A stage starts with `total` units of work (rows or bytes)
+    fn start(&self, stage: LogStage, total: u64);
+    /// Units done so far in the current stage
+    fn update(&self, done: u64);
+    /// The current stage is over
+    fn finish(&self);
+}
+
+/// Installed observer (None = no progress reporting)
+static OBSERVER: Lazy<RwLock<Option<Arc<dyn ProgressObserver>>>> = Lazy::new(|| RwLock::new(None));
+
+/// Number of updates sent per stage at most (keeps the hot loops cheap)
+const UPDATES_PER_STAGE: u64 = 200;
+
+/// Install (or remove with `None`) the global progress observer
+pub fn set_progress_observer(observer: Option<Arc<dyn ProgressObserver>>) {
+    if let Ok(mut current) = OBSERVER.write() {
+        *current = observer;
+    }
+}
+
+/// Progress of one stage, reported to the observer if any.
+///
+/// Updates are throttled; the stage is finished when dropped.
+pub struct StageProgress {
+    observer: Option<Arc<dyn ProgressObserver>>,
+    step: u64,
+    next: u64,
+}
+
+impl StageProgress {
+    /// Start a stage of `total` units
+    pub fn start(stage: LogStage, total: u64) -> Self {
+        let observer = OBSERVER.read().ok().and_then(|o| o.clone());
+        Self::with_observer(observer, stage, total)
+    }
+
+    /// Start a stage reported to a given observer
+    fn with_observer(observer: Option<Arc<dyn ProgressObserver>>, stage: LogStage, total: u64) -> Self {
+        if let Some(ref observer) = observer {
+            observer.start(stage, total);
+        }
+        let step = (total / UPDATES_PER_STAGE).max(1);
+        Self { observer, step, next: step }
+    }
+
+    /// Report `done` units (only every `total / 200` units reach the observer)
+    pub fn set(&mut self, done: u64) {
+        if done < self.next {
+            return;
+        }
+        self.next = done + self.step;
+        if let Some(ref observer) = self.observer {
+            observer.update(done);
+        }
+    }
+}
+
+impl Drop for StageProgress {
+    fn drop(&mut self) {
+        if let Some(ref observer) = self.observer {
+            observer.finish();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl ProgressObserver for Recorder {
+        fn start(&self, stage: LogStage, total: u64) {
+            self.0.lock().unwrap().push(format!("start {:?} {}", stage, total));
+        }
+        fn update(&self, done: u64) {
+            self.0.lock().unwrap().push(format!("update {}", done));
+        }
+        fn finish(&self) {
+            self.0.lock().unwrap().push("finish".to_string());
+        }
+    }
+
+    #[test]
+    fn test_stage_progress_is_throttled() {
+        let recorder = Arc::new(Recorder::default());
+        {
+            let mut progress = StageProgress::with_observer(Some(recorder.clone()), LogStage::Transform, 1000);
+            for done in 1..=1000 {
+                progress.set(done);
+            }
+        }
+
+        let events = recorder.0.lock().unwrap();
+        assert_eq!(events.first().map(String::as_str), Some("start Transform 1000"));
+        assert_eq!(events.last().map(String::as_str), Some("finish"));
+        // One update every 5 rows
+        assert_eq!(events.len(), 2 + 200);
+    }
+}
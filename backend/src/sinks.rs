@@ -0,0 +1,328 @@
+//! Output sinks: the grouped MIDDS works pushed to the user's own system
+//!
+//! Sinks are named in the `sinks` section of the configuration file (see
+//! [`crate::config`]) and chosen per job: `--sink` on `transform`, `sink`
+//! form field of the upload endpoint. Once validation is done, each chosen
+//! sink receives the grouped works as one JSON array. The outcome of every
+//! delivery is reported with the job; a failed delivery never fails it.
+//!
+//! Secrets are read from environment variables, so the configuration file
+//! can be shared:
+//!
+//! ```yaml
+//! sinks:
+//!   erp:
+//!     type: http                      # POST to the URL
+//!     url: https://erp.example.com/midds
+//!     auth_header: Authorization      # default
+//!     auth_env: ERP_TOKEN             # header value, e.g. "Bearer ..."
+//!   archive:
+//!     type: s3                        # PUT <prefix><job id>.json (SigV4)
+//!     endpoint: https://s3.eu-west-3.amazonaws.com
+//!     bucket: midds-exports
+//!     region: eu-west-3
+//!     prefix: massload/
+//!     access_key_env: AWS_ACCESS_KEY_ID       # default
+//!     secret_key_env: AWS_SECRET_ACCESS_KEY   # default
+//! ```
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::deterministic;
+use crate::parser::checksum_sha256;
+
+/// Timeout of each delivery
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Header carrying the job id on HTTP deliveries
+pub const JOB_ID_HEADER: &str = "x-massload-job-id";
+
+/// `sinks` section of the configuration file: sink name → sink
+pub type SinksConfig = BTreeMap<String, SinkConfig>;
+
+/// Where the grouped works of a job are delivered
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SinkConfig {
+    /// JSON array POSTed to a URL
+    Http(HttpSink),
+    /// JSON object written to an S3-compatible bucket
+    S3(S3Sink),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HttpSink {
+    pub url: String,
+    #[serde(default = "default_auth_header")]
+    pub auth_header: String,
+    /// Environment variable holding the header value (no header without it)
+    #[serde(default)]
+    pub auth_env: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct S3Sink {
+    /// Service URL; objects are addressed path-style (`<endpoint>/<bucket>/<key>`)
+    pub endpoint: String,
+    pub bucket: String,
+    #[serde(default = "default_region")]
+    pub region: String,
+    /// Prepended to the object key (`<prefix><job id>.json`)
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default = "default_access_key_env")]
+    pub access_key_env: String,
+    #[serde(default = "default_secret_key_env")]
+    pub secret_key_env: String,
+}
+
+fn default_auth_header() -> String {
+    "Authorization".to_string()
+}
+
+fn default_region() -> String {
+    "us-east-1".to_string()
+}
+
+fn default_access_key_env() -> String {
+    "AWS_ACCESS_KEY_ID".to_string()
+}
+
+fn default_secret_key_env() -> String {
+    "AWS_SECRET_ACCESS_KEY".to_string()
+}
+
+/// Outcome of a delivery
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    Delivered,
+    Failed,
+    /// Nothing sent: the job has no valid work
+    Skipped,
+}
+
+/// Delivery of a job to one sink, reported with the job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Delivery {
+    pub sink: String,
+    pub status: DeliveryStatus,
+    /// URL posted to, or `s3://<bucket>/<key>`
+    pub target: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// RFC 3339 timestamp
+    pub at: String,
+}
+
+impl SinkConfig {
+    /// Where the works of `job_id` go
+    pub fn target(&self, job_id: &str) -> String {
+        match self {
+            SinkConfig::Http(http) => http.url.clone(),
+            SinkConfig::S3(s3) => format!("s3://{}/{}", s3.bucket, s3.object_key(job_id)),
+        }
+    }
+}
+
+impl S3Sink {
+    pub fn object_key(&self, job_id: &str) -> String {
+        format!("{}{}.json", self.prefix, job_id)
+    }
+}
+
+/// Sinks chosen for a job, in the order given; unknown names are an error
+pub fn select_sinks(config: &SinksConfig, names: &[String]) -> Result<Vec<(String, SinkConfig)>, String> {
+    names.iter()
+        .map(|name| match config.get(name) {
+            Some(sink) => Ok((name.clone(), sink.clone())),
+            None if config.is_empty() => Err(format!("Unknown sink '{}': no sink configured", name)),
+            None => Err(format!(
+                "Unknown sink '{}' (configured: {})",
+                name, config.keys().cloned().collect::<Vec<_>>().join(", ")
+            )),
+        })
+        .collect()
+}
+
+/// Deliver the grouped works of a job to every chosen sink, one after the other.
+///
+/// Without any valid work nothing is sent and each delivery is `skipped`.
+pub async fn deliver_all<T: Serialize + ?Sized>(
+    sinks: &[(String, SinkConfig)],
+    job_id: &str,
+    works: &T,
+    valid: usize,
+) -> Vec<Delivery> {
+    let body = match serde_json::to_vec(works) {
+        Ok(body) => body,
+        Err(e) => {
+            return sinks.iter()
+                .map(|(name, sink)| delivery(name, sink, job_id, DeliveryStatus::Failed, Some(e.to_string())))
+                .collect();
+        }
+    };
+
+    let mut deliveries = Vec::with_capacity(sinks.len());
+    for (name, sink) in sinks {
+        let outcome = if valid == 0 {
+            delivery(name, sink, job_id, DeliveryStatus::Skipped, Some("No valid work".to_string()))
+        } else {
+            match deliver(sink, job_id, body.clone()).await {
+                Ok(()) => delivery(name, sink, job_id, DeliveryStatus::Delivered, None),
+                Err(e) => delivery(name, sink, job_id, DeliveryStatus::Failed, Some(e)),
+            }
+        };
+        deliveries.push(outcome);
+    }
+    deliveries
+}
+
+fn delivery(name: &str, sink: &SinkConfig, job_id: &str, status: DeliveryStatus, error: Option<String>) -> Delivery {
+    Delivery {
+        sink: name.to_string(),
+        status,
+        target: sink.target(job_id),
+        error,
+        at: deterministic::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+    }
+}
+
+/// Send the serialized works to one sink
+async fn deliver(sink: &SinkConfig, job_id: &str, body: Vec<u8>) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .timeout(DELIVERY_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let request = match sink {
+        SinkConfig::Http(http) => {
+            let mut request = client.post(&http.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(JOB_ID_HEADER, job_id);
+            if let Some(ref var) = http.auth_env {
+                request = request.header(http.auth_header.as_str(), env_var(var)?);
+            }
+            request.body(body)
+        }
+        SinkConfig::S3(s3) => {
+            let url = format!("{}/{}/{}", s3.endpoint.trim_end_matches('/'), s3.bucket, s3.object_key(job_id));
+            let url = reqwest::Url::parse(&url).map_err(|e| format!("Invalid S3 URL {}: {}", url, e))?;
+            let credentials = (env_var(&s3.access_key_env)?, env_var(&s3.secret_key_env)?);
+            let signed = sign_put(&url, &s3.region, &credentials, &body, Utc::now());
+            let mut request = client.put(url).header(reqwest::header::CONTENT_TYPE, "application/json");
+            for (name, value) in signed {
+                request = request.header(name, value);
+            }
+            request.body(body)
+        }
+    };
+
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    Ok(())
+}
+
+fn env_var(name: &str) -> Result<String, String> {
+    std::env::var(name).map_err(|_| format!("Environment variable {} is not set", name))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// AWS Signature Version 4 signing key for a day, region and service
+fn signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), date);
+    let key = hmac_sha256(&key, region);
+    let key = hmac_sha256(&key, service);
+    hmac_sha256(&key, "aws4_request")
+}
+
+/// Headers of a SigV4-signed S3 `PUT` of `body` to `url` (path-style, no query)
+fn sign_put(url: &reqwest::Url, region: &str, (access_key, secret_key): &(String, String), body: &[u8], now: DateTime<Utc>) -> Vec<(&'static str, String)> {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = &amz_date[..8];
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+    let payload_hash = checksum_sha256(body);
+
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+        "PUT\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        url.path(), host, payload_hash, amz_date, signed_headers, payload_hash
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{}", amz_date, scope, checksum_sha256(canonical_request.as_bytes()));
+    let signature = hex(&hmac_sha256(&signing_key(secret_key, date, region, "s3"), &string_to_sign));
+
+    vec![
+        ("x-amz-date", amz_date.clone()),
+        ("x-amz-content-sha256", payload_hash),
+        ("authorization", format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            access_key, scope, signed_headers, signature
+        )),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_sink_config_and_signing() {
+        let config = Config::from_yaml(
+            "sinks:\n  erp:\n    type: http\n    url: http://localhost/midds\n    auth_env: ERP_TOKEN\n  archive:\n    type: s3\n    endpoint: http://localhost:9000\n    bucket: midds\n    prefix: exports/\n",
+        ).unwrap();
+        let SinkConfig::Http(ref erp) = config.sinks["erp"] else { panic!("http sink expected") };
+        assert_eq!(erp.auth_header, "Authorization");
+        assert_eq!(config.sinks["archive"].target("job-1"), "s3://midds/exports/job-1.json");
+        assert!(Config::from_yaml("sinks:\n  x:\n    type: ftp\n    url: ftp://host\n").is_err());
+
+        let names = vec!["archive".to_string(), "erp".to_string()];
+        assert_eq!(select_sinks(&config.sinks, &names).unwrap()[0].0, "archive");
+        assert!(select_sinks(&config.sinks, &["crm".to_string()]).unwrap_err().contains("archive, erp"));
+
+        // Signing key example of the AWS SigV4 documentation
+        let key = signing_key("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY", "20120215", "us-east-1", "iam");
+        assert_eq!(hex(&key), "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d");
+
+        let url = reqwest::Url::parse("http://localhost:9000/midds/exports/job-1.json").unwrap();
+        let now = DateTime::parse_from_rfc3339("2025-03-01T12:00:00Z").unwrap().with_timezone(&Utc);
+        let headers = sign_put(&url, "us-east-1", &("AK".to_string(), "SK".to_string()), b"[]", now);
+        assert_eq!(headers[0], ("x-amz-date", "20250301T120000Z".to_string()));
+        assert!(headers[2].1.starts_with("AWS4-HMAC-SHA256 Credential=AK/20250301/us-east-1/s3/aws4_request, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature="));
+    }
+
+    #[tokio::test]
+    async fn test_no_valid_work_skips_delivery() {
+        let sinks = vec![("erp".to_string(), SinkConfig::Http(HttpSink {
+            url: "http://127.0.0.1:9/unreachable".to_string(),
+            auth_header: default_auth_header(),
+            auth_env: None,
+        }))];
+        let deliveries = deliver_all(&sinks, "job-1", &Vec::<serde_json::Value>::new(), 0).await;
+        assert_eq!(deliveries[0].status, DeliveryStatus::Skipped);
+        assert_eq!(deliveries[0].target, "http://127.0.0.1:9/unreachable");
+    }
+}
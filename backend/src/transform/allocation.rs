@@ -0,0 +1,147 @@
+//! ISWC allocation requests for works lacking a code.
+//!
+//! Works without ISWC can't be registered on-chain. Societies allocate one
+//! from the title and the creators (IPI name number, role, share), so this
+//! collects those works from a job (valid records and skipped rows alike)
+//! and writes them as a CSV file, one line per creator.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use crate::models::CreatorRole;
+use crate::transform::dsl::SkippedRow;
+
+/// Header of the allocation request file
+const CSV_HEADER: [&str; 6] = ["Work ref", "Title", "Creator name", "IPI name number", "Role", "Share (%)"];
+
+/// A work to request an ISWC for
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllocationRequest {
+    pub title: String,
+    pub creators: Vec<AllocationCreator>,
+}
+
+/// A creator of a work, as known from the source rows
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllocationCreator {
+    pub name: Option<String>,
+    pub ipi: Option<u64>,
+    /// CISAC role code (`C`, `A`, `AR`, `AD`, `E`...)
+    pub role: Option<String>,
+    pub share: Option<f64>,
+}
+
+/// Works without ISWC, grouped by `workKey` (or title) in source order.
+///
+/// Rows without a title are left out: a society can't allocate a code to them.
+pub fn works_lacking_iswc(flat: &[Value], skipped: &[SkippedRow]) -> Vec<AllocationRequest> {
+    let records = flat.iter()
+        .filter_map(Value::as_object)
+        .chain(skipped.iter().map(|s| &s.partial));
+
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut requests: Vec<AllocationRequest> = Vec::new();
+    for record in records {
+        if text(record, "iswc").is_some() {
+            continue;
+        }
+        let Some(title) = text(record, "title") else { continue };
+        let key = text(record, "workKey")
+            .map(|k| format!("key:{}", k))
+            .unwrap_or_else(|| format!("title:{}", title.to_lowercase()));
+        let position = *index.entry(key).or_insert_with(|| {
+            requests.push(AllocationRequest { title: title.to_string(), creators: Vec::new() });
+            requests.len() - 1
+        });
+
+        let creator = creator_of(record);
+        let creators = &mut requests[position].creators;
+        if (creator.name.is_some() || creator.ipi.is_some()) && !creators.contains(&creator) {
+            creators.push(creator);
+        }
+    }
+    requests
+}
+
+/// Write the requests as CSV, the work columns repeated on each creator line
+pub fn allocation_csv(requests: &[AllocationRequest]) -> Result<String, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(CSV_HEADER)?;
+    for (i, request) in requests.iter().enumerate() {
+        let work_ref = format!("W{:04}", i + 1);
+        if request.creators.is_empty() {
+            writer.write_record([work_ref.as_str(), &request.title, "", "", "", ""])?;
+        }
+        for creator in &request.creators {
+            writer.write_record([
+                work_ref.as_str(),
+                &request.title,
+                creator.name.as_deref().unwrap_or(""),
+                // IPI name numbers are 11 digits, zero-padded
+                &creator.ipi.map(|ipi| format!("{:011}", ipi)).unwrap_or_default(),
+                creator.role.as_deref().unwrap_or(""),
+                &creator.share.map(|share| share.to_string()).unwrap_or_default(),
+            ])?;
+        }
+    }
+    let bytes = writer.into_inner().map_err(|e| e.into_error())?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn creator_of(record: &Map<String, Value>) -> AllocationCreator {
+    let role = record.get("creatorRole").map(|role| {
+        serde_json::from_value::<CreatorRole>(role.clone())
+            .map(|r| r.to_code().to_string())
+            .unwrap_or_else(|_| role.as_str().unwrap_or_default().to_string())
+    });
+    AllocationCreator {
+        name: text(record, "creatorName").map(String::from),
+        ipi: record.get("creatorIpi").and_then(Value::as_u64),
+        role: role.filter(|r| !r.is_empty()),
+        share: record.get("creatorShare").and_then(Value::as_f64),
+    }
+}
+
+/// Non-empty trimmed string field
+fn text<'a>(record: &'a Map<String, Value>, field: &str) -> Option<&'a str> {
+    record.get(field).and_then(Value::as_str).map(str::trim).filter(|s| !s.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_works_lacking_iswc_to_csv() {
+        let flat = vec![
+            json!({"iswc": "T1234567890", "title": "Registered", "creatorIpi": 1, "creatorRole": "Composer"}),
+            json!({"title": "Sans code", "creatorName": "Dupont, Jean", "creatorIpi": 123456789, "creatorRole": "Composer", "creatorShare": 50.0}),
+        ];
+        let partial = json!({"title": "sans code", "creatorName": "Martin", "creatorRole": "Author", "creatorShare": 50});
+        let skipped = vec![
+            SkippedRow {
+                row: 4,
+                line: None,
+                reason: "Missing required fields".to_string(),
+                missing_fields: vec!["iswc".to_string(), "creatorIpi".to_string()],
+                partial: partial.as_object().unwrap().clone(),
+            },
+            SkippedRow { row: 5, line: None, reason: String::new(), missing_fields: vec!["title".to_string()], partial: Map::new() },
+        ];
+
+        let requests = works_lacking_iswc(&flat, &skipped);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].title, "Sans code");
+        assert_eq!(requests[0].creators[1].role.as_deref(), Some("A"));
+
+        let csv = allocation_csv(&requests).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "Work ref,Title,Creator name,IPI name number,Role,Share (%)");
+        assert_eq!(lines[1], "W0001,Sans code,\"Dupont, Jean\",00123456789,C,50");
+        assert_eq!(lines[2], "W0001,Sans code,Martin,,A,50");
+    }
+}
@@ -0,0 +1,178 @@
+//! Diagnosis of a transformation where no record is valid.
+//!
+//! Aggregates the most frequent error causes and suggests what to check
+//! (template, delimiter, encoding, required columns).
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::pipeline::PipelineResult;
+use crate::parser::LOW_ENCODING_CONFIDENCE;
+use crate::validation::validate_musical_work_flat;
+
+/// Records re-validated to aggregate error causes
+const DIAGNOSIS_SAMPLE: usize = 1000;
+
+/// Error causes reported
+const MAX_CAUSES: usize = 5;
+
+/// An error cause and the number of records it affects
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorCause {
+    pub message: String,
+    pub count: usize,
+}
+
+/// Next step suggested to the user
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Suggestion {
+    /// The cached template (or generated matrix) does not fit this file
+    CheckTemplate,
+    /// A single column was detected: the delimiter is probably wrong
+    CheckDelimiter,
+    /// Encoding detection was uncertain
+    CheckEncoding,
+    /// Rows were skipped for missing required fields
+    CheckRequiredColumns,
+}
+
+impl Suggestion {
+    /// Short hint printed by the CLI
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Suggestion::CheckTemplate => "check that the file matches the transformation template",
+            Suggestion::CheckDelimiter => "only one column detected: check the delimiter",
+            Suggestion::CheckEncoding => "encoding detection was uncertain: try --encoding",
+            Suggestion::CheckRequiredColumns => "rows lack required fields: check the title, ISWC and IPI columns",
+        }
+    }
+}
+
+/// Why every record failed, and what to check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailureReport {
+    /// Most frequent causes first
+    pub top_causes: Vec<ErrorCause>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// Build a failure report when the file has rows but no valid record
+pub fn diagnose_failure(result: &PipelineResult) -> Option<FailureReport> {
+    if result.csv_info.row_count == 0 || result.valid_count > 0 {
+        return None;
+    }
+
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for record in result.flat.iter().take(DIAGNOSIS_SAMPLE) {
+        if let Err(errors) = validate_musical_work_flat(record) {
+            for error in errors {
+                *counts.entry(error_cause(&error)).or_default() += 1;
+            }
+        }
+    }
+    for skipped in &result.skipped {
+        let cause = format!("missing required field(s): {}", skipped.missing_fields.join(", "));
+        *counts.entry(cause).or_default() += 1;
+    }
+
+    let mut top_causes: Vec<ErrorCause> = counts.into_iter()
+        .map(|(message, count)| ErrorCause { message, count })
+        .collect();
+    top_causes.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.message.cmp(&b.message)));
+    top_causes.truncate(MAX_CAUSES);
+
+    let mut suggestions = Vec::new();
+    if result.csv_info.headers.len() <= 1 {
+        suggestions.push(Suggestion::CheckDelimiter);
+    }
+    if result.csv_info.encoding_confidence < LOW_ENCODING_CONFIDENCE {
+        suggestions.push(Suggestion::CheckEncoding);
+    }
+    if !result.skipped.is_empty() {
+        suggestions.push(Suggestion::CheckRequiredColumns);
+    }
+    suggestions.push(Suggestion::CheckTemplate);
+
+    Some(FailureReport { top_causes, suggestions })
+}
+
+/// Error message without the offending value, so that records aggregate
+/// (`"abc" is not of type "integer"` → `value is not of type "integer"`).
+/// Missing-property messages keep the field name.
+fn error_cause(message: &str) -> String {
+    match message.split_once(" is ") {
+        Some((value, rest)) if !rest.starts_with("a required property") && serde_json::from_str::<Value>(value).is_ok() => {
+            format!("value is {}", rest)
+        }
+        _ => message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::dsl::example_matrix;
+    use crate::transform::pipeline::CsvInfo;
+    use serde_json::json;
+
+    fn result(flat: Vec<Value>, valid_count: usize, headers: &[&str]) -> PipelineResult {
+        PipelineResult {
+            flat,
+            flat_spill: None,
+            flat_lines: vec![],
+            grouped: vec![],
+            valid_count,
+            invalid_count: 0,
+            validation_errors: vec![],
+            skipped: vec![],
+            matrix: example_matrix(),
+            template_id: None,
+            template_columns: None,
+            created_template: None,
+            matrix_engine: None,
+            ai_estimate: None,
+            csv_info: CsvInfo {
+                encoding: "utf-8".to_string(),
+                encoding_confidence: 1.0,
+                delimiter: ';',
+                headers: headers.iter().map(|h| h.to_string()).collect(),
+                row_count: 2,
+                sha256: None,
+                footer_lines: vec![],
+                ragged_rows: vec![],
+                diagnostics: Default::default(),
+                original_headers: Default::default(),
+                report: Default::default(),
+            },
+            period: None,
+            field_stats: Default::default(),
+            roles: Default::default(),
+            validation_warnings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_error_cause_strips_value() {
+        assert_eq!(error_cause(r#""abc" is not of type "integer""#), r#"value is not of type "integer""#);
+        assert_eq!(error_cause("12 is less than the minimum of 100"), "value is less than the minimum of 100");
+        assert_eq!(error_cause(r#""title" is a required property"#), r#""title" is a required property"#);
+        assert_eq!(error_cause("something else"), "something else");
+    }
+
+    #[test]
+    fn test_diagnose_all_invalid() {
+        let flat = vec![
+            json!({"iswc": "bad", "title": "A", "creatorIpi": 1, "creatorRole": "Composer"}),
+            json!({"iswc": "worse", "title": "B", "creatorIpi": 2, "creatorRole": "Composer"}),
+        ];
+        let report = diagnose_failure(&result(flat, 0, &["Titre;ISWC;IPI"])).unwrap();
+
+        assert_eq!(report.top_causes[0].count, 2);
+        assert_eq!(report.suggestions, vec![Suggestion::CheckDelimiter, Suggestion::CheckTemplate]);
+        assert!(diagnose_failure(&result(vec![], 1, &["Titre", "ISWC"])).is_none());
+    }
+}
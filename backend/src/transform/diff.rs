@@ -0,0 +1,222 @@
+//! Diff between two versions of the same catalog.
+//!
+//! When a society re-sends an updated catalog, only new and changed works
+//! need to be registered. Works are matched by the grouping key (ISWC, else
+//! work key) or, when missing, by title. A key shared by several works of a
+//! version cannot be matched: it is reported as ambiguous and its works are
+//! submitted again rather than compared.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+
+use super::grouper::grouping_key;
+use crate::models::title_text;
+
+/// A work present in both versions with different content
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkChange {
+    pub iswc: String,
+    pub title: String,
+    /// Top-level fields that differ (e.g. `creators`, `title`)
+    pub fields: Vec<String>,
+}
+
+/// Changes between a previous and a current set of grouped works
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogDiff {
+    /// Catalog the versions belong to
+    #[serde(default)]
+    pub catalog: String,
+    /// Job that produced the previous version
+    #[serde(default)]
+    pub previous_job_id: Option<String>,
+    /// When the previous version was transformed (RFC3339)
+    #[serde(default)]
+    pub previous_created_at: Option<String>,
+    /// Keys of works only in the current version
+    pub added: Vec<String>,
+    /// Keys of works only in the previous version
+    pub removed: Vec<String>,
+    /// Works present in both versions with different content
+    pub changed: Vec<WorkChange>,
+    /// Number of identical works
+    pub unchanged: usize,
+    /// Keys shared by several works of a version, or empty (no ISWC nor title):
+    /// their works are not compared and are submitted again
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ambiguous: Vec<String>,
+}
+
+impl CatalogDiff {
+    /// Whether the current version differs from the previous one
+    pub fn has_changes(&self) -> bool {
+        !self.added.is_empty() || !self.removed.is_empty() || !self.changed.is_empty() || !self.ambiguous.is_empty()
+    }
+
+    /// Works of the current version to submit: added, changed and ambiguous ones only
+    pub fn works_to_submit<'a>(&self, current: &'a [Value]) -> Vec<&'a Value> {
+        let keys: BTreeSet<&str> = self.added.iter()
+            .map(String::as_str)
+            .chain(self.changed.iter().map(|c| c.iswc.as_str()))
+            .chain(self.ambiguous.iter().map(String::as_str))
+            .collect();
+        current.iter().filter(|w| keys.contains(work_key(w).as_str())).collect()
+    }
+}
+
+/// Key identifying a work across versions: the grouping key (ISWC, else
+/// `key:{workKey}`), else `title:{title}`, else empty
+pub fn work_key(work: &Value) -> String {
+    // Titles may be language-tagged (`{ "title": ..., "language": ... }`)
+    grouping_key(work)
+        .or_else(|| {
+            work.get("title")
+                .and_then(title_text)
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|t| format!("title:{}", t.to_lowercase()))
+        })
+        .unwrap_or_default()
+}
+
+/// Works of a version by key
+fn works_by_key(works: &[Value]) -> HashMap<String, Vec<&Value>> {
+    let mut by_key: HashMap<String, Vec<&Value>> = HashMap::new();
+    for work in works {
+        by_key.entry(work_key(work)).or_default().push(work);
+    }
+    by_key
+}
+
+/// Compare two versions of a catalog (grouped works).
+///
+/// Creator order is ignored: a reordered creators list is not a change.
+pub fn diff_works(previous: &[Value], current: &[Value]) -> CatalogDiff {
+    let previous_by_key = works_by_key(previous);
+    let current_by_key = works_by_key(current);
+    let ambiguous: BTreeSet<String> = previous_by_key.iter()
+        .chain(current_by_key.iter())
+        .filter(|(key, works)| key.is_empty() || works.len() > 1)
+        .map(|(key, _)| key.clone())
+        .collect();
+
+    let mut diff = CatalogDiff::default();
+    for work in current {
+        let key = work_key(work);
+        if ambiguous.contains(&key) {
+            continue;
+        }
+        match previous_by_key.get(&key).and_then(|works| works.first()) {
+            None => diff.added.push(key),
+            Some(old) => {
+                let fields = changed_fields(old, work);
+                if fields.is_empty() {
+                    diff.unchanged += 1;
+                } else {
+                    diff.changed.push(WorkChange {
+                        iswc: key,
+                        title: work.get("title").and_then(title_text).unwrap_or("").to_string(),
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+
+    diff.removed = previous_by_key.into_keys()
+        .filter(|key| !current_by_key.contains_key(key) && !ambiguous.contains(key))
+        .collect();
+    diff.removed.sort();
+    diff.ambiguous = ambiguous.into_iter().collect();
+    diff
+}
+
+/// Top-level fields whose values differ between two works
+fn changed_fields(old: &Value, new: &Value) -> Vec<String> {
+    let (Some(old), Some(new)) = (old.as_object(), new.as_object()) else {
+        return vec!["work".to_string()];
+    };
+
+    let fields: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    fields.into_iter()
+        .filter(|field| normalized(field, old.get(*field)) != normalized(field, new.get(*field)))
+        .cloned()
+        .collect()
+}
+
+/// Field value with order-insensitive arrays (creators, participants)
+fn normalized(field: &str, value: Option<&Value>) -> Option<Value> {
+    match value {
+        Some(Value::Array(items)) if field == "creators" || field == "participants" => {
+            let mut items = items.clone();
+            items.sort_by_key(|item| item.to_string());
+            Some(Value::Array(items))
+        }
+        other => other.cloned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn work(iswc: &str, title: &str, ipis: &[u64]) -> Value {
+        let creators: Vec<Value> = ipis.iter()
+            .map(|ipi| json!({"id": {"type": "Ipi", "value": ipi}, "role": "Composer"}))
+            .collect();
+        json!({"iswc": iswc, "title": title, "creators": creators, "participants": []})
+    }
+
+    #[test]
+    fn test_diff_works() {
+        let previous = vec![
+            work("T0000000001", "Same", &[1, 2]),
+            work("T0000000002", "Creators", &[1]),
+            work("T0000000003", "Gone", &[1]),
+        ];
+        let current = vec![
+            work("T0000000001", "Same", &[2, 1]),
+            work("T0000000002", "Creators", &[1, 3]),
+            work("T0000000004", "New", &[1]),
+        ];
+
+        let diff = diff_works(&previous, &current);
+
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(diff.added, vec!["T0000000004".to_string()]);
+        assert_eq!(diff.removed, vec!["T0000000003".to_string()]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].fields, vec!["creators".to_string()]);
+
+        let to_submit: Vec<String> = diff.works_to_submit(&current).into_iter().map(work_key).collect();
+        assert_eq!(to_submit, vec!["T0000000002".to_string(), "T0000000004".to_string()]);
+    }
+
+    #[test]
+    fn test_ambiguous_titles_are_not_matched() {
+        let untitled = json!({"creators": [], "participants": []});
+        let previous = vec![work("", "Intro", &[1]), work("", "Solo", &[1]), untitled.clone()];
+        let current = vec![work("", "Intro", &[1]), work("", "Intro", &[2]), work("", "Solo", &[1]), untitled];
+
+        let diff = diff_works(&previous, &current);
+
+        // Two works titled "Intro" now: neither is compared to the previous one
+        assert_eq!(diff.ambiguous, vec!["".to_string(), "title:intro".to_string()]);
+        assert_eq!(diff.unchanged, 1);
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.changed.is_empty());
+        assert_eq!(diff.works_to_submit(&current).len(), 3);
+        assert_eq!(work_key(&json!({"workKey": "W-1", "title": "Intro"})), "key:W-1");
+    }
+
+    #[test]
+    fn test_identical_catalog_has_no_changes() {
+        let works = vec![work("T0000000001", "Same", &[1])];
+        let diff = diff_works(&works, &works);
+        assert!(!diff.has_changes());
+        assert!(diff.works_to_submit(&works).is_empty());
+    }
+}
@@ -1,21 +1,65 @@
 //! DSL Executor
 //! 
 //! Executes transformation matrices on CSV data to produce MIDDS flat records.
-
+//!
+//! A field's value is read from its source, replaced by `default` when empty,
+//! then run through the operations in order. An empty intermediate value
+//! goes on through the chain, unless the field sets `stop_if_empty` (the
+//! chain stops and `default` is used as-is) or `fail_if_empty` (the row is
+//! skipped). A value still empty after the chain falls back to `default`.
+
+use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use super::matrix::{ExpandConfig, FieldTransform, TransformationMatrix};
+use super::operations::Operation;
+use crate::parser::normalize_header;
 
 /// Result of executing a transformation
 #[derive(Debug)]
 pub struct TransformResult {
     /// Successfully transformed records
     pub records: Vec<Value>,
+    /// Index of the input row each record comes from, parallel to `records`
+    pub source_rows: Vec<usize>,
+    /// 1-based source file line of each record, parallel to `records`
+    /// (set by the pipeline from the parsed file; empty when unknown)
+    pub source_lines: Vec<usize>,
     /// Errors encountered (row index, field, error message)
     pub errors: Vec<TransformError>,
     /// Rows skipped due to missing required fields
     pub skipped: Vec<SkippedRow>,
+    /// Operation failures per target field (fields with operations only)
+    pub field_stats: BTreeMap<String, FieldStats>,
+}
+
+/// Share of failed values above which a field is reported
+pub const FIELD_FAILURE_WARN_RATE: f64 = 0.1;
+
+/// Operation counters of one target field
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldStats {
+    /// Non-empty values the operations ran on
+    pub values: usize,
+    /// Values where at least one operation fell back (see [`Operation::apply_checked`])
+    ///
+    /// [`Operation::apply_checked`]: super::Operation::apply_checked
+    pub failures: usize,
+    /// Failures per operation name (`to_number`, `map`...)
+    pub by_operation: BTreeMap<String, usize>,
+}
+
+impl FieldStats {
+    /// Share of values with a failed operation (0.0 to 1.0)
+    pub fn failure_rate(&self) -> f64 {
+        if self.values == 0 {
+            0.0
+        } else {
+            self.failures as f64 / self.values as f64
+        }
+    }
 }
 
 /// An error during transformation
@@ -27,20 +71,46 @@ pub struct TransformError {
 }
 
 /// A row that was skipped
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct SkippedRow {
     pub row: usize,
+    /// 1-based line of the row in the source file, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
     pub reason: String,
     pub missing_fields: Vec<String>,
+    /// Fields that did transform, so the row can be completed later
+    #[serde(default)]
+    pub partial: Map<String, Value>,
 }
 
 impl TransformResult {
     pub fn new() -> Self {
         Self {
             records: Vec::new(),
+            source_rows: Vec::new(),
+            source_lines: Vec::new(),
             errors: Vec::new(),
             skipped: Vec::new(),
+            field_stats: BTreeMap::new(),
+        }
+    }
+
+    /// Fill `source_lines` and the lines of skipped rows from the line of each input row
+    pub fn locate(&mut self, row_lines: &[usize]) {
+        if row_lines.is_empty() {
+            return;
         }
+        self.source_lines = self.source_rows.iter().filter_map(|&row| row_lines.get(row).copied()).collect();
+        for skip in &mut self.skipped {
+            skip.line = row_lines.get(skip.row).copied();
+        }
+    }
+
+    /// Fields whose failure rate exceeds [`FIELD_FAILURE_WARN_RATE`]
+    pub fn failing_fields(&self) -> impl Iterator<Item = (&String, &FieldStats)> {
+        self.field_stats.iter().filter(|(_, stats)| stats.failure_rate() > FIELD_FAILURE_WARN_RATE)
     }
 
     /// Check if transformation completed without errors
@@ -59,6 +129,16 @@ impl TransformResult {
     }
 }
 
+impl SkippedRow {
+    /// `line N` in the source file when known, else `row N` (data row index)
+    pub fn location(&self) -> String {
+        match self.line {
+            Some(line) => format!("line {}", line),
+            None => format!("row {}", self.row),
+        }
+    }
+}
+
 impl Default for TransformResult {
     fn default() -> Self {
         Self::new()
@@ -74,6 +154,15 @@ impl Default for TransformResult {
 /// # Returns
 /// A TransformResult containing the transformed records and any errors
 pub fn execute(csv_data: &[Value], matrix: &TransformationMatrix) -> TransformResult {
+    execute_with_progress(csv_data, matrix, |_| {})
+}
+
+/// Same as [`execute`], calling `on_row` with the number of rows done
+pub fn execute_with_progress(
+    csv_data: &[Value],
+    matrix: &TransformationMatrix,
+    mut on_row: impl FnMut(usize),
+) -> TransformResult {
     let mut result = TransformResult::new();
 
     for (row_idx, row) in csv_data.iter().enumerate() {
@@ -81,14 +170,18 @@ pub fn execute(csv_data: &[Value], matrix: &TransformationMatrix) -> TransformRe
         let expanded_rows = expand_row(row, matrix, row_idx);
         
         for (expanded_row, variant_overrides) in expanded_rows {
-            match transform_row_with_overrides(&expanded_row, matrix, row_idx, variant_overrides.as_ref()) {
-                Ok(Some(record)) => result.records.push(record),
+            match transform_row_with_overrides(&expanded_row, matrix, row_idx, variant_overrides.as_ref(), &mut result.field_stats) {
+                Ok(Some(record)) => {
+                    result.records.push(record);
+                    result.source_rows.push(row_idx);
+                }
                 Ok(None) => {
                     // Row was intentionally skipped (e.g., missing required fields)
                 }
                 Err(skip) => result.skipped.push(skip),
             }
         }
+        on_row(row_idx + 1);
     }
 
     result
@@ -109,7 +202,7 @@ fn expand_row(
         None => vec![(row.clone(), None)],
         
         Some(ExpandConfig::SplitRole { source, separator, mapping }) => {
-            let role_value = row_obj.get(source)
+            let role_value = column(row_obj, source)
                 .and_then(|v| v.as_str())
                 .unwrap_or("");
             
@@ -150,7 +243,7 @@ fn expand_row(
             for variant in variants {
                 // Check condition
                 if let Some(ref cond_col) = variant.condition_column {
-                    let cond_value = row_obj.get(cond_col)
+                    let cond_value = column(row_obj, cond_col)
                         .and_then(|v| v.as_str())
                         .unwrap_or("");
                     
@@ -197,20 +290,25 @@ fn transform_row_with_overrides(
     matrix: &TransformationMatrix,
     row_idx: usize,
     overrides: Option<&HashMap<String, FieldTransform>>,
+    field_stats: &mut BTreeMap<String, FieldStats>,
 ) -> Result<Option<Value>, SkippedRow> {
     let row_obj = match row.as_object() {
         Some(obj) => obj,
         None => {
             return Err(SkippedRow {
                 row: row_idx,
+                line: None,
                 reason: "Row is not a JSON object".to_string(),
                 missing_fields: Vec::new(),
+                partial: Map::new(),
             });
         }
     };
 
     let mut output = Map::new();
     let mut missing_required = Vec::new();
+    // `fail_if_empty` fields that came out empty, with where they did
+    let mut empty_failures = Vec::new();
 
     for (target_field, transform) in &matrix.transforms {
         // Check if there's an override for this field
@@ -218,10 +316,19 @@ fn transform_row_with_overrides(
             .and_then(|o| o.get(target_field))
             .unwrap_or(transform);
         
-        let value = apply_transform(row_obj, effective_transform);
+        let value = if effective_transform.operations.is_empty() {
+            apply_transform(row_obj, effective_transform, None)
+        } else {
+            let stats = field_stats.entry(target_field.clone()).or_default();
+            apply_transform(row_obj, effective_transform, Some(stats))
+        };
 
         match value {
-            Some(v) if !is_empty(&v) => {
+            Err(step) => {
+                missing_required.push(target_field.clone());
+                empty_failures.push(format!("{}: {}", target_field, step));
+            }
+            Ok(Some(v)) if !is_empty(&v) => {
                 output.insert(target_field.clone(), v);
             }
             _ => {
@@ -235,10 +342,17 @@ fn transform_row_with_overrides(
     }
 
     if !missing_required.is_empty() {
+        let reason = if empty_failures.is_empty() {
+            "Missing required fields".to_string()
+        } else {
+            format!("Empty values: {}", empty_failures.join(", "))
+        };
         return Err(SkippedRow {
             row: row_idx,
-            reason: "Missing required fields".to_string(),
+            line: None,
+            reason,
             missing_fields: missing_required,
+            partial: output,
         });
     }
 
@@ -250,16 +364,22 @@ fn transform_row_with_overrides(
     Ok(Some(Value::Object(output)))
 }
 
-/// Apply a field transformation
-fn apply_transform(row: &Map<String, Value>, transform: &FieldTransform) -> Option<Value> {
+/// Apply a field transformation, counting operation failures in `stats`.
+///
+/// `Err` tells where the value of a `fail_if_empty` field became empty.
+fn apply_transform(
+    row: &Map<String, Value>,
+    transform: &FieldTransform,
+    stats: Option<&mut FieldStats>,
+) -> Result<Option<Value>, String> {
     // Get initial value from source column(s) or constant
     let mut value = if let Some(source) = &transform.source {
         // Single source
-        row.get(source).cloned()
+        column(row, source).cloned()
     } else if let Some(sources) = &transform.sources {
         // Multiple sources - concatenate them
         let parts: Vec<String> = sources.iter()
-            .filter_map(|s| row.get(s))
+            .filter_map(|s| column(row, s))
             .filter_map(|v| v.as_str())
             .map(|s| s.trim())
             .filter(|s| !s.is_empty())
@@ -277,6 +397,12 @@ fn apply_transform(row: &Map<String, Value>, transform: &FieldTransform) -> Opti
 
     // If no value and we have a default, use it
     if value.is_none() || is_empty(value.as_ref().unwrap()) {
+        if transform.fail_if_empty {
+            return Err("empty source value".to_string());
+        }
+        if transform.stop_if_empty {
+            return Ok(transform.default.clone());
+        }
         if let Some(default) = &transform.default {
             value = Some(default.clone());
         }
@@ -284,22 +410,52 @@ fn apply_transform(row: &Map<String, Value>, transform: &FieldTransform) -> Opti
 
     // Apply operations in sequence
     if let Some(mut v) = value {
+        let counted = stats.filter(|_| !is_empty(&v));
+        let mut failed_ops = Vec::new();
+        let mut emptied_by = None;
         for op in &transform.operations {
-            v = op.apply(&v);
+            let (result, failed) = match op {
+                Operation::When { column: Some(name), .. } => op.apply_when(&v, column(row, name)),
+                _ => op.apply_checked(&v),
+            };
+            if failed {
+                failed_ops.push(op.name());
+            }
+            v = result;
+            if is_empty(&v) && (transform.stop_if_empty || transform.fail_if_empty) {
+                emptied_by = Some(op.name());
+                break;
+            }
+        }
+        if let Some(stats) = counted {
+            stats.values += 1;
+            if !failed_ops.is_empty() {
+                stats.failures += 1;
+                for name in failed_ops {
+                    *stats.by_operation.entry(name.to_string()).or_default() += 1;
+                }
+            }
+        }
+
+        if let Some(name) = emptied_by.filter(|_| transform.fail_if_empty) {
+            return Err(format!("empty after {}", name));
         }
 
         // If result is empty after operations, try default again
         if is_empty(&v) {
-            if let Some(default) = &transform.default {
-                return Some(default.clone());
-            }
-            return None;
+            return Ok(transform.default.clone());
         }
 
-        return Some(v);
+        return Ok(Some(v));
     }
 
-    None
+    Ok(None)
+}
+
+/// Value of a source column; matrices saved before header normalization may
+/// name it with accents or extra spaces
+fn column<'a>(row: &'a Map<String, Value>, name: &str) -> Option<&'a Value> {
+    row.get(name).or_else(|| row.get(&normalize_header(name)))
 }
 
 /// Check if a value is "empty" (null, empty string, etc.)
@@ -317,6 +473,7 @@ fn is_empty(value: &Value) -> bool {
 mod tests {
     use super::*;
     use crate::transform::dsl::matrix::example_matrix;
+    use crate::transform::dsl::Operation;
 
     #[test]
     fn test_execute_simple() {
@@ -389,6 +546,36 @@ mod tests {
         assert_eq!(result.records[0]["language"], "French");
     }
 
+    #[test]
+    fn test_field_stats_count_operation_failures() {
+        let mut matrix = TransformationMatrix::new();
+        matrix.transforms.insert(
+            "creatorIpi".to_string(),
+            FieldTransform::from_source("IPI").with_operation(Operation::ToNumber),
+        );
+        matrix.transforms.insert(
+            "title".to_string(),
+            FieldTransform::from_source("Titre")
+                .with_operation(Operation::Replace { pattern: "(".to_string(), value: String::new() }),
+        );
+
+        let csv_data = vec![
+            serde_json::json!({"IPI": "123", "Titre": "Un"}),
+            serde_json::json!({"IPI": "n/a", "Titre": "Deux"}),
+            serde_json::json!({"IPI": "", "Titre": ""}),
+        ];
+
+        let result = execute(&csv_data, &matrix);
+        let ipi = &result.field_stats["creatorIpi"];
+        assert_eq!((ipi.values, ipi.failures), (2, 1));
+        assert_eq!(ipi.by_operation["to_number"], 1);
+        assert_eq!(ipi.failure_rate(), 0.5);
+        // The invalid regex keeps the original title
+        assert_eq!(result.records[1]["title"], "Deux");
+        assert_eq!(result.field_stats["title"].failures, 2);
+        assert_eq!(result.failing_fields().count(), 2);
+    }
+
     #[test]
     fn test_default_value() {
         let mut matrix = TransformationMatrix::new();
@@ -448,5 +635,54 @@ mod tests {
         assert_eq!(result.records.len(), 1);
         assert_eq!(result.records[0]["title"], "Solo Title");
     }
-}
 
+    #[test]
+    fn test_chain_short_circuit_on_empty_values() {
+        let fallback_role = || Operation::Map {
+            mapping: HashMap::from([("CA".to_string(), "Composer".to_string())]),
+            case_insensitive: false,
+            default_unmapped: Some("Author".to_string()),
+        };
+        let matrix_with = |transform: FieldTransform| {
+            let mut matrix = TransformationMatrix::new();
+            matrix.transforms.insert("title".to_string(), FieldTransform::from_source("Titre"));
+            matrix.transforms.insert("creatorRole".to_string(), transform);
+            matrix
+        };
+        let csv_data = vec![
+            serde_json::json!({"Titre": "Vide", "Role": ""}),
+            serde_json::json!({"Titre": "Sans code", "Role": "n/a"}),
+        ];
+        let role = FieldTransform::from_source("Role")
+            .with_operation(Operation::Alphanumeric)
+            .with_operation(Operation::Trim)
+            .with_operation(fallback_role());
+
+        // Default behaviour: the empty values still reach the map and get its fallback
+        let result = execute(&csv_data, &matrix_with(role.clone()));
+        assert_eq!(result.records[0]["creatorRole"], "Author");
+
+        // stop_if_empty: the chain stops, the field gets `default` untouched
+        let stopped = role.clone().with_default(Value::String("Performer".to_string())).stop_if_empty();
+        let result = execute(&csv_data, &matrix_with(stopped.clone()));
+        assert_eq!(result.records[0]["creatorRole"], "Performer");
+        assert_eq!(result.records[1]["creatorRole"], "Author");
+        let mut no_default = stopped;
+        no_default.default = None;
+        let result = execute(&csv_data, &matrix_with(no_default));
+        assert!(result.records[0].get("creatorRole").is_none());
+
+        // fail_if_empty: the row is skipped, telling where the value became empty
+        let failing = FieldTransform::from_source("Role")
+            .with_operation(Operation::DigitsOnly)
+            .with_operation(fallback_role())
+            .with_default(Value::String("Performer".to_string()))
+            .fail_if_empty();
+        let result = execute(&csv_data, &matrix_with(failing));
+        assert!(result.records.is_empty());
+        assert_eq!(result.skipped[0].reason, "Empty values: creatorRole: empty source value");
+        assert_eq!(result.skipped[1].reason, "Empty values: creatorRole: empty after digits_only");
+        assert_eq!(result.skipped[1].missing_fields, vec!["creatorRole".to_string()]);
+        assert_eq!(result.skipped[1].partial["title"], "Sans code");
+    }
+}
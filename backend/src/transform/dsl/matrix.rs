@@ -7,6 +7,7 @@ use serde_json::Value;
 use std::collections::HashMap;
 
 use super::operations::Operation;
+use crate::parser::{FixedWidthColumn, ParseOptions};
 
 /// A complete transformation matrix defining all field transformations
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -83,12 +84,51 @@ pub struct SourceFormat {
     /// Number of header rows
     #[serde(default = "default_header_rows")]
     pub header_rows: usize,
+
+    /// Lines to skip before the header row (export banners, titles)
+    #[serde(default)]
+    pub skip_first: usize,
+
+    /// Lines to drop at the end of the file (totals, summaries)
+    #[serde(default)]
+    pub skip_last: usize,
+
+    /// Column layout of a fixed-width text source (no header row is read)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fixed_width: Option<Vec<FixedWidthColumn>>,
 }
 
 fn default_header_rows() -> usize {
     1
 }
 
+impl Default for SourceFormat {
+    fn default() -> Self {
+        Self {
+            delimiter: None,
+            encoding: None,
+            header_rows: default_header_rows(),
+            skip_first: 0,
+            skip_last: 0,
+            fixed_width: None,
+        }
+    }
+}
+
+impl SourceFormat {
+    /// Parser options reading a file like the one the matrix was made for
+    /// (rows to skip, header rows, fixed-width layout)
+    pub fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            skip_first: self.skip_first,
+            skip_last: self.skip_last,
+            header_rows: self.header_rows,
+            fixed_width: self.fixed_width.clone(),
+            ..ParseOptions::default()
+        }
+    }
+}
+
 /// Transformation rule for a single field
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FieldTransform {
@@ -119,6 +159,17 @@ pub struct FieldTransform {
     /// Whether this field is required
     #[serde(default)]
     pub required: bool,
+
+    /// Stop the operation chain at the first empty value (source or
+    /// intermediate result); the field then gets `default` as-is, never run
+    /// through the remaining operations
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stop_if_empty: bool,
+
+    /// Skip the row when the value is empty (source or intermediate result),
+    /// without falling back to `default`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fail_if_empty: bool,
 }
 
 fn default_concat_separator() -> String {
@@ -222,6 +273,8 @@ impl FieldTransform {
             operations: Vec::new(),
             default: None,
             required: false,
+            stop_if_empty: false,
+            fail_if_empty: false,
         }
     }
 
@@ -235,6 +288,8 @@ impl FieldTransform {
             operations: Vec::new(),
             default: None,
             required: false,
+            stop_if_empty: false,
+            fail_if_empty: false,
         }
     }
 
@@ -248,6 +303,8 @@ impl FieldTransform {
             operations: Vec::new(),
             default: None,
             required: false,
+            stop_if_empty: false,
+            fail_if_empty: false,
         }
     }
 
@@ -268,6 +325,18 @@ impl FieldTransform {
         self.required = true;
         self
     }
+
+    /// Stop the operation chain at the first empty value
+    pub fn stop_if_empty(mut self) -> Self {
+        self.stop_if_empty = true;
+        self
+    }
+
+    /// Skip the row when the value is, or becomes, empty
+    pub fn fail_if_empty(mut self) -> Self {
+        self.fail_if_empty = true;
+        self
+    }
     
     /// Get all source columns referenced by this transform
     pub fn get_sources(&self) -> Vec<String> {
@@ -278,6 +347,11 @@ impl FieldTransform {
         if let Some(ref ss) = self.sources {
             result.extend(ss.clone());
         }
+        for op in &self.operations {
+            if let Operation::When { column: Some(column), .. } = op {
+                result.push(column.clone());
+            }
+        }
         result
     }
 }
@@ -361,6 +435,9 @@ pub fn example_matrix() -> TransformationMatrix {
             delimiter: Some(';'),
             encoding: Some("utf-8".to_string()),
             header_rows: 1,
+            skip_first: 0,
+            skip_last: 0,
+            fixed_width: None,
         }),
         transforms,
         expand: None,
@@ -41,7 +41,10 @@ pub mod matrix;
 pub mod operations;
 
 // Re-exports for convenience
-pub use executor::{execute, execute_hashmap, SkippedRow, TransformError, TransformResult};
+pub use executor::{
+    execute, execute_hashmap, execute_with_progress, FieldStats, SkippedRow, TransformError, TransformResult,
+    FIELD_FAILURE_WARN_RATE,
+};
 pub use matrix::{example_matrix, FieldTransform, SourceFormat, TransformationMatrix, ExpandConfig, ColumnVariant};
-pub use operations::{operations_description, Operation};
+pub use operations::{operations_description, CaseStyle, DateComponent, Operation};
 
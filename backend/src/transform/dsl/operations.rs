@@ -2,9 +2,12 @@
 //! 
 //! Available operations that can be applied to transform CSV values into MIDDS-compliant data.
 
+use chrono::Datelike;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::Mutex;
 
 /// All available transformation operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +21,20 @@ pub enum Operation {
     
     /// Convert to lowercase
     Lowercase,
+
+    /// Human-readable capitalization for ALL CAPS titles. Known acronyms
+    /// and roman numerals stay uppercase; with a `locale`, its articles and
+    /// prepositions stay lowercase inside the title.
+    TitleCase {
+        #[serde(default)]
+        style: CaseStyle,
+        /// Language code or name ("fr", "French"...)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        locale: Option<String>,
+        /// Extra words written as given (`"AC/DC"`, `"McCartney"`)
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        acronyms: Vec<String>,
+    },
     
     /// Replace using regex pattern
     Replace {
@@ -42,6 +59,24 @@ pub enum Operation {
     
     /// Extract year (4 digits) from a date string
     ExtractYear,
+
+    /// Drop years outside `[min, max]` (date-parsing mistakes such as 0201 or 2301):
+    /// the field is left empty rather than moved to a bound
+    ClampYear {
+        #[serde(default = "default_min_year")]
+        min: i64,
+        /// Latest accepted year (default: current year)
+        #[serde(default)]
+        max: Option<i64>,
+    },
+
+    /// Parse a date with a `strftime` format (`%d/%m/%Y`, `%Y-%m-%d`) and keep one
+    /// part of it. Text after the date (a time) is ignored; unparsable dates become null
+    DateParse {
+        format: String,
+        #[serde(default)]
+        output: DateComponent,
+    },
     
     /// Ensure string starts with given prefix
     EnsurePrefix {
@@ -77,6 +112,26 @@ pub enum Operation {
     
     /// Convert to number (integer)
     ToNumber,
+
+    /// Convert to a decimal number (`,` or `.` as decimal separator, `%` ignored).
+    /// Thousands separators are not supported: `"1.234,56"` becomes null.
+    ToDecimal,
+
+    /// Multiply a number (e.g. fractions `0.5` → percent with `by: 100`)
+    Multiply {
+        by: f64,
+    },
+
+    /// Divide a number (e.g. per-mille → percent with `by: 10`); null when `by` is 0
+    Divide {
+        by: f64,
+    },
+
+    /// Round a number to `decimals` places
+    Round {
+        #[serde(default)]
+        decimals: u32,
+    },
     
     /// Take first N characters
     Substring {
@@ -90,12 +145,121 @@ pub enum Operation {
     
     /// Remove all non-digit characters
     DigitsOnly,
+
+    /// Canonical ISWC (`T-123.456.789-0` → `T1234567890`); null when the
+    /// check digit does not match, to catch typos
+    #[serde(alias = "validate_iswc")]
+    FixIswc,
+
+    /// Derive a stable identifier (BLAKE2b hex digest) from the value.
+    /// Combine several columns with `sources` to hash e.g. title + first IPI.
+    HashId {
+        /// Number of hex characters to keep (max 128)
+        #[serde(default = "default_hash_length")]
+        length: usize,
+        #[serde(default)]
+        prefix: String,
+    },
+
+    /// Output `then` or `else` depending on a condition on the value, or on
+    /// `column` of the same row. Every condition given must hold; with none,
+    /// the condition is "not empty". A missing branch keeps the value.
+    When {
+        /// Column to test instead of the current value
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        column: Option<String>,
+        /// Equal to this text (trimmed, case-insensitive)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        equals: Option<String>,
+        /// Matches this regex
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        matches: Option<String>,
+        /// Empty (`true`) or filled (`false`)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        empty: Option<bool>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        then: Option<Value>,
+        #[serde(rename = "else", default, skip_serializing_if = "Option::is_none")]
+        otherwise: Option<Value>,
+    },
+}
+
+/// Part of a date kept by [`Operation::DateParse`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DateComponent {
+    /// Year as an integer
+    #[default]
+    Year,
+    /// Month as an integer (1 to 12)
+    Month,
+    /// ISO 8601 date (`2024-03-15`)
+    Iso,
+}
+
+/// Capitalization of [`Operation::TitleCase`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaseStyle {
+    /// Every word capitalized ("Le Temps des Cerises")
+    #[default]
+    Title,
+    /// First word only ("Le temps des cerises")
+    Sentence,
+}
+
+/// Acronyms kept uppercase by [`Operation::TitleCase`]
+const TITLE_ACRONYMS: &[&str] = &["DJ", "MC", "USA", "UK", "TV", "R&B"];
+
+/// Regexes of `when` and `replace` operations, compiled once per pattern
+/// (`None`: invalid pattern)
+static REGEX_CACHE: Lazy<Mutex<HashMap<String, Option<regex::Regex>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Patterns kept in [`REGEX_CACHE`] before it is emptied
+const REGEX_CACHE_SIZE: usize = 256;
+
+/// Compiled regex of `pattern`, None when it is invalid
+fn cached_regex(pattern: &str) -> Option<regex::Regex> {
+    let mut cache = REGEX_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(compiled) = cache.get(pattern) {
+        return compiled.clone();
+    }
+    if cache.len() >= REGEX_CACHE_SIZE {
+        cache.clear();
+    }
+    let compiled = regex::Regex::new(pattern).ok();
+    cache.insert(pattern.to_string(), compiled.clone());
+    compiled
+}
+
+/// Roman numerals up to 39 (`II`, `XIV`); no `M`/`D`/`L`/`C` to spare words like "MIX"
+static ROMAN_NUMERAL: Lazy<regex::Regex> =
+    Lazy::new(|| regex::Regex::new(r"^X{0,3}(IX|IV|V?I{0,3})$").unwrap());
+
+/// Articles, conjunctions and prepositions left lowercase inside a title
+fn small_words(locale: &str) -> &'static [&'static str] {
+    match locale.to_lowercase().as_str() {
+        "en" | "english" => &["a", "an", "the", "and", "but", "or", "nor", "for", "of", "on", "in", "at", "to", "by", "with", "from", "as", "vs"],
+        "fr" | "french" => &["le", "la", "les", "l", "un", "une", "des", "de", "du", "d", "et", "ou", "à", "au", "aux", "en", "sur", "par", "pour", "dans", "avec"],
+        "es" | "spanish" => &["el", "la", "los", "las", "un", "una", "de", "del", "y", "o", "en", "a", "con", "por", "para"],
+        "it" | "italian" => &["il", "lo", "la", "gli", "le", "l", "un", "una", "di", "d", "del", "della", "e", "o", "in", "a", "da", "con", "per"],
+        "pt" | "portuguese" => &["o", "a", "os", "as", "um", "uma", "de", "do", "da", "dos", "das", "e", "ou", "em", "no", "na", "com", "por", "para"],
+        _ => &[],
+    }
 }
 
 fn default_pad_char() -> String {
     "0".to_string()
 }
 
+fn default_min_year() -> i64 {
+    crate::validation::DEFAULT_MIN_YEAR
+}
+
+fn default_hash_length() -> usize {
+    16
+}
+
 fn default_split_separator() -> String {
     ",".to_string()
 }
@@ -118,12 +282,17 @@ impl Operation {
             Operation::Trim => self.apply_trim(value),
             Operation::Uppercase => self.apply_uppercase(value),
             Operation::Lowercase => self.apply_lowercase(value),
+            Operation::TitleCase { style, locale, acronyms } => {
+                self.apply_title_case(value, *style, locale.as_deref(), acronyms)
+            }
             Operation::Replace { pattern, value: replacement } => {
-                self.apply_replace(value, pattern, replacement)
+                self.apply_replace(value, pattern, replacement).unwrap_or(value.clone())
             }
             Operation::PadStart { length, char } => self.apply_pad_start(value, *length, char),
             Operation::PadEnd { length, char } => self.apply_pad_end(value, *length, char),
             Operation::ExtractYear => self.apply_extract_year(value),
+            Operation::ClampYear { min, max } => self.apply_clamp_year(value, *min, *max),
+            Operation::DateParse { format, output } => self.apply_date_parse(value, format, *output),
             Operation::EnsurePrefix { value: prefix } => self.apply_ensure_prefix(value, prefix),
             Operation::EnsureSuffix { value: suffix } => self.apply_ensure_suffix(value, suffix),
             Operation::Map { mapping, case_insensitive, default_unmapped } => {
@@ -132,9 +301,113 @@ impl Operation {
             Operation::Split { separator } => self.apply_split(value, separator),
             Operation::ToBoolean { true_values } => self.apply_to_boolean(value, true_values),
             Operation::ToNumber => self.apply_to_number(value),
+            Operation::ToDecimal => self.apply_to_decimal(value),
+            Operation::Multiply { by } => Self::arithmetic(value, |n| Some(n * by)),
+            Operation::Divide { by } => Self::arithmetic(value, |n| (*by != 0.0).then(|| n / by)),
+            Operation::Round { decimals } => {
+                let factor = 10f64.powi(*decimals as i32);
+                Self::arithmetic(value, |n| Some((n * factor).round() / factor))
+            }
             Operation::Substring { start, length } => self.apply_substring(value, *start, *length),
             Operation::Alphanumeric => self.apply_alphanumeric(value),
             Operation::DigitsOnly => self.apply_digits_only(value),
+            Operation::FixIswc => self.apply_fix_iswc(value),
+            Operation::HashId { length, prefix } => self.apply_hash_id(value, *length, prefix),
+            Operation::When { .. } => self.apply_when(value, Some(value)).0,
+        }
+    }
+
+    /// Apply this operation and tell whether it failed.
+    ///
+    /// [`apply`](Self::apply) never errors: an invalid regex keeps the value,
+    /// an unparsable number or year becomes null and an unmapped value (no
+    /// `default_unmapped`) empties the field. Those fallbacks count as failures
+    /// here when the input had a value.
+    pub fn apply_checked(&self, value: &Value) -> (Value, bool) {
+        let has_value = Self::as_string(value).is_some_and(|s| !s.trim().is_empty());
+        match self {
+            Operation::Replace { pattern, value: replacement } => {
+                match self.apply_replace(value, pattern, replacement) {
+                    Some(result) => (result, false),
+                    None => (value.clone(), has_value),
+                }
+            }
+            Operation::ExtractYear | Operation::ClampYear { .. } | Operation::DateParse { .. }
+            | Operation::ToNumber | Operation::ToDecimal
+            | Operation::Multiply { .. } | Operation::Divide { .. } | Operation::Round { .. }
+            | Operation::FixIswc => {
+                let result = self.apply(value);
+                let failed = has_value && result.is_null();
+                (result, failed)
+            }
+            Operation::Map { default_unmapped: None, .. } => {
+                let result = self.apply(value);
+                let failed = has_value && result.as_str().is_some_and(str::is_empty);
+                (result, failed)
+            }
+            Operation::When { .. } => self.apply_when(value, Some(value)),
+            _ => (self.apply(value), false),
+        }
+    }
+
+    /// Apply a `when` operation whose condition reads `subject` (the value
+    /// itself, or its `column` looked up by the caller). An invalid `matches`
+    /// regex keeps the value and counts as a failure.
+    ///
+    /// The condition only sees the current row: in a file with one row per
+    /// creator, it cannot tell that a work has no author *row*.
+    pub fn apply_when(&self, value: &Value, subject: Option<&Value>) -> (Value, bool) {
+        let Operation::When { equals, matches, empty, then, otherwise, .. } = self else {
+            return self.apply_checked(value);
+        };
+        let text = subject.and_then(Self::as_string).unwrap_or_default();
+        let text = text.trim();
+        let mut holds = match empty {
+            Some(empty) => text.is_empty() == *empty,
+            None => equals.is_some() || matches.is_some() || !text.is_empty(),
+        };
+        if let Some(expected) = equals {
+            holds &= text.to_lowercase() == expected.trim().to_lowercase();
+        }
+        if let Some(pattern) = matches {
+            match cached_regex(pattern) {
+                Some(re) => holds &= re.is_match(text),
+                None => return (value.clone(), true),
+            }
+        }
+        let branch = if holds { then } else { otherwise };
+        (branch.clone().unwrap_or_else(|| value.clone()), false)
+    }
+
+    /// Operation name as written in matrices (`to_number`, `replace`...)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operation::Trim => "trim",
+            Operation::Uppercase => "uppercase",
+            Operation::Lowercase => "lowercase",
+            Operation::TitleCase { .. } => "title_case",
+            Operation::Replace { .. } => "replace",
+            Operation::PadStart { .. } => "pad_start",
+            Operation::PadEnd { .. } => "pad_end",
+            Operation::ExtractYear => "extract_year",
+            Operation::ClampYear { .. } => "clamp_year",
+            Operation::DateParse { .. } => "date_parse",
+            Operation::EnsurePrefix { .. } => "ensure_prefix",
+            Operation::EnsureSuffix { .. } => "ensure_suffix",
+            Operation::Map { .. } => "map",
+            Operation::Split { .. } => "split",
+            Operation::ToBoolean { .. } => "to_boolean",
+            Operation::ToNumber => "to_number",
+            Operation::ToDecimal => "to_decimal",
+            Operation::Multiply { .. } => "multiply",
+            Operation::Divide { .. } => "divide",
+            Operation::Round { .. } => "round",
+            Operation::Substring { .. } => "substring",
+            Operation::Alphanumeric => "alphanumeric",
+            Operation::DigitsOnly => "digits_only",
+            Operation::FixIswc => "fix_iswc",
+            Operation::HashId { .. } => "hash_id",
+            Operation::When { .. } => "when",
         }
     }
 
@@ -165,14 +438,53 @@ impl Operation {
             .unwrap_or(value.clone())
     }
 
-    fn apply_replace(&self, value: &Value, pattern: &str, replacement: &str) -> Value {
-        Self::as_string(value)
-            .and_then(|s| {
-                regex::Regex::new(pattern)
-                    .ok()
-                    .map(|re| Value::String(re.replace_all(&s, replacement).to_string()))
-            })
-            .unwrap_or(value.clone())
+    fn apply_title_case(&self, value: &Value, style: CaseStyle, locale: Option<&str>, acronyms: &[String]) -> Value {
+        let Some(s) = Self::as_string(value) else {
+            return value.clone();
+        };
+        let small = locale.map(small_words).unwrap_or_default();
+        let words: Vec<String> = s.split(' ').enumerate().map(|(index, word)| {
+            let core = word.trim_matches(|c: char| !c.is_alphanumeric() && c != '&' && c != '/');
+            let upper = core.to_uppercase();
+            let kept = acronyms.iter().map(String::as_str)
+                .chain(TITLE_ACRONYMS.iter().copied())
+                .find(|acronym| acronym.to_uppercase() == upper)
+                .map(str::to_string)
+                .or_else(|| (!upper.is_empty() && ROMAN_NUMERAL.is_match(&upper)).then_some(upper));
+            if let Some(kept) = kept {
+                return word.replacen(core, &kept, 1);
+            }
+            let title = style == CaseStyle::Title;
+            word.split('-').enumerate().map(|(part_index, part)| {
+                let first = index == 0 && part_index == 0;
+                // French/Italian elision: "L'AMOUR" → "L'Amour"
+                let elided = part.find(['\'', '’']).filter(|&at| {
+                    let article = part[..at].to_lowercase();
+                    small.contains(&article.as_str()) && at + 1 < part.len()
+                });
+                match elided {
+                    Some(at) => {
+                        let (article, rest) = part.split_at(at);
+                        let quote_len = rest.chars().next().map_or(0, char::len_utf8);
+                        format!("{}{}{}", capitalized(article, first), &rest[..quote_len], capitalized(&rest[quote_len..], title))
+                    }
+                    None => {
+                        let bare = part.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase();
+                        capitalized(part, first || (title && !small.contains(&bare.as_str())))
+                    }
+                }
+            }).collect::<Vec<_>>().join("-")
+        }).collect();
+        Value::String(words.join(" "))
+    }
+
+    /// None when the pattern is not a valid regex
+    fn apply_replace(&self, value: &Value, pattern: &str, replacement: &str) -> Option<Value> {
+        match Self::as_string(value) {
+            Some(s) => cached_regex(pattern)
+                .map(|re| Value::String(re.replace_all(&s, replacement).to_string())),
+            None => Some(value.clone()),
+        }
     }
 
     fn apply_pad_start(&self, value: &Value, length: usize, pad_char: &str) -> Value {
@@ -216,6 +528,46 @@ impl Operation {
             .unwrap_or(Value::Null)
     }
 
+    fn apply_clamp_year(&self, value: &Value, min: i64, max: Option<i64>) -> Value {
+        let max = max.unwrap_or_else(crate::validation::current_year);
+        let year = match value {
+            Value::Number(n) => n.as_i64(),
+            _ => Self::as_string(value).and_then(|s| s.trim().parse::<i64>().ok()),
+        };
+        year.filter(|y| (min..=max).contains(y))
+            .map(|y| Value::Number(y.into()))
+            .unwrap_or(Value::Null)
+    }
+
+    fn apply_date_parse(&self, value: &Value, format: &str, output: DateComponent) -> Value {
+        let Some(text) = Self::as_string(value) else {
+            return Value::Null;
+        };
+        let (mut text, mut format) = (text.trim().to_string(), format.to_string());
+        let mut parsed = chrono::NaiveDate::parse_and_remainder(&text, &format);
+        // Dates without a day (`%m/%Y`) or month (`%Y`) start on the first. Only
+        // padded when chrono lacks fields, so `%F` or `%D` are read as they are.
+        if parsed.as_ref().is_err_and(|e| e.kind() == chrono::format::ParseErrorKind::NotEnough) {
+            if !["%d", "%e", "%j"].iter().any(|d| format.contains(d)) {
+                text.insert_str(0, "01 ");
+                format.insert_str(0, "%d ");
+            }
+            if !["%m", "%b", "%B", "%h", "%j"].iter().any(|m| format.contains(m)) {
+                text.insert_str(0, "01 ");
+                format.insert_str(0, "%m ");
+            }
+            parsed = chrono::NaiveDate::parse_and_remainder(&text, &format);
+        }
+        match parsed {
+            Ok((date, _)) => match output {
+                DateComponent::Year => Value::Number(i64::from(date.year()).into()),
+                DateComponent::Month => Value::Number(date.month().into()),
+                DateComponent::Iso => Value::String(date.format("%Y-%m-%d").to_string()),
+            },
+            Err(_) => Value::Null,
+        }
+    }
+
     fn apply_ensure_prefix(&self, value: &Value, prefix: &str) -> Value {
         Self::as_string(value)
             .map(|s| {
@@ -312,6 +664,40 @@ impl Operation {
         }
     }
 
+    fn apply_to_decimal(&self, value: &Value) -> Value {
+        match value {
+            Value::Number(_) => value.clone(),
+            _ => Self::as_decimal(value)
+                .and_then(serde_json::Number::from_f64)
+                .map(Value::Number)
+                .unwrap_or(Value::Null),
+        }
+    }
+
+    /// Number read as [`Operation::ToDecimal`] does
+    fn as_decimal(value: &Value) -> Option<f64> {
+        match value {
+            Value::Number(n) => n.as_f64(),
+            _ => Self::as_string(value).and_then(|s| {
+                let cleaned: String = s.trim().trim_end_matches('%').trim()
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .map(|c| if c == ',' { '.' } else { c })
+                    .collect();
+                cleaned.parse::<f64>().ok()
+            }),
+        }
+    }
+
+    /// Decimal result of `op`, null when the value is not a number or `op` gives none
+    fn arithmetic(value: &Value, op: impl Fn(f64) -> Option<f64>) -> Value {
+        Self::as_decimal(value)
+            .and_then(op)
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number)
+            .unwrap_or(Value::Null)
+    }
+
     fn apply_substring(&self, value: &Value, start: usize, length: Option<usize>) -> Value {
         Self::as_string(value)
             .map(|s| {
@@ -342,6 +728,58 @@ impl Operation {
             })
             .unwrap_or(value.clone())
     }
+
+    fn apply_fix_iswc(&self, value: &Value) -> Value {
+        let Some(s) = Self::as_string(value) else {
+            return Value::Null;
+        };
+        let cleaned: String = s.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_uppercase();
+        let digits = cleaned.strip_prefix('T').unwrap_or(&cleaned);
+        if digits.len() != 10 || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Value::Null;
+        }
+        // Check digit: 1 + sum of the 9 digits weighted 1..9, mod 10, complemented
+        let values: Vec<u32> = digits.chars().filter_map(|c| c.to_digit(10)).collect();
+        let sum: u32 = 1 + values[..9].iter().zip(1..).map(|(d, weight)| d * weight).sum::<u32>();
+        if (10 - sum % 10) % 10 != values[9] {
+            return Value::Null;
+        }
+        Value::String(format!("T{}", digits))
+    }
+
+    fn apply_hash_id(&self, value: &Value, length: usize, prefix: &str) -> Value {
+        use blake2::{Blake2b512, Digest};
+
+        Self::as_string(value)
+            .map(|s| {
+                // Normalize so casing and spacing differences give the same id
+                let normalized = s.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+                if normalized.is_empty() {
+                    return Value::String(String::new());
+                }
+                let digest = Blake2b512::digest(normalized.as_bytes());
+                let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+                Value::String(format!("{}{}", prefix, &hex[..length.clamp(1, hex.len())]))
+            })
+            .unwrap_or(value.clone())
+    }
+}
+
+/// Lowercase `word`, with its first letter uppercase when `capitalize`
+fn capitalized(word: &str, capitalize: bool) -> String {
+    let lower = word.to_lowercase();
+    if !capitalize {
+        return lower;
+    }
+    match lower.find(char::is_alphabetic) {
+        Some(at) => {
+            let (head, tail) = lower.split_at(at);
+            let mut chars = tail.chars();
+            let letter = chars.next().map(|c| c.to_uppercase().collect::<String>()).unwrap_or_default();
+            format!("{}{}{}", head, letter, chars.as_str())
+        }
+        None => lower,
+    }
 }
 
 /// Get a description of all available operations for AI prompts
@@ -353,19 +791,29 @@ pub fn operations_description() -> String {
 | trim | Remove leading/trailing whitespace | - |
 | uppercase | Convert to uppercase | - |
 | lowercase | Convert to lowercase | - |
+| title_case | Readable capitalization of ALL CAPS text, acronyms and roman numerals kept | style: "title" (default) or "sentence", locale: e.g. "fr" (articles/prepositions kept lowercase), acronyms: extra words written as given |
 | replace | Regex pattern replacement | pattern: regex, value: replacement |
 | pad_start | Pad string at start | length: target length, char: pad character (default "0") |
 | pad_end | Pad string at end | length: target length, char: pad character (default "0") |
 | extract_year | Extract 4-digit year from date | - |
+| clamp_year | Drop years outside a plausible range (field left empty) | min: earliest year (default 1700), max: latest year (default current year) |
+| date_parse | Parse a date with a strftime format, keep one part (null if unparsable) | format: e.g. "%d/%m/%Y", output: "year" (default, integer), "month" (integer) or "iso" ("2024-03-15") |
 | ensure_prefix | Add prefix if not present | value: prefix string |
 | ensure_suffix | Add suffix if not present | value: suffix string |
 | map | Map values using lookup table | mapping: {source: target}, case_insensitive: bool |
 | split | Split into array | separator: split char (default ",") |
 | to_boolean | Convert to boolean | true_values: list of truthy strings |
 | to_number | Convert to integer | - |
+| to_decimal | Convert to decimal number ("33,33 %" → 33.33; "1.234,56" → null) | - |
+| multiply | Multiply a number (fraction 0.5 → 50 percent) | by: factor |
+| divide | Divide a number (per-mille 500 → 50 percent), null if by is 0 | by: divisor |
+| round | Round a number | decimals: places (default 0) |
 | substring | Extract substring | start: start index, length: optional length |
 | alphanumeric | Keep only alphanumeric chars | - |
 | digits_only | Keep only digits | - |
+| fix_iswc | Canonical ISWC ("T-034.524.680-1" → "T0345246801"), null if the check digit is wrong | - |
+| hash_id | Stable id from the (normalized) value, BLAKE2b hex | length: hex chars (default 16), prefix: optional string |
+| when | Output one of two values depending on a condition | column: test another column (default: the value), equals / matches: regex / empty: bool, then, else: output values (missing = keep the value) |
 
 Example operations in JSON:
 [
@@ -373,7 +821,10 @@ Example operations in JSON:
   {"type": "replace", "pattern": "[-. ]", "value": ""},
   {"type": "map", "mapping": {"CA": "Composer", "A": "Author"}, "case_insensitive": true},
   {"type": "to_number"},
-  {"type": "ensure_prefix", "value": "T"}
+  {"type": "multiply", "by": 100},
+  {"type": "date_parse", "format": "%d/%m/%Y", "output": "year"},
+  {"type": "ensure_prefix", "value": "T"},
+  {"type": "when", "column": "Lyricist", "empty": true, "then": true, "else": false}
 ]"#.to_string()
 }
 
@@ -387,6 +838,23 @@ mod tests {
         assert_eq!(op.apply(&Value::String("  hello  ".to_string())), Value::String("hello".to_string()));
     }
 
+    #[test]
+    fn test_title_case() {
+        let case = |json: serde_json::Value, text: &str| {
+            let op: Operation = serde_json::from_value(json).unwrap();
+            op.apply(&Value::String(text.to_string())).as_str().unwrap().to_string()
+        };
+        let title = serde_json::json!({"type": "title_case", "locale": "fr"});
+        assert_eq!(case(title.clone(), "LE TEMPS DES CERISES"), "Le Temps des Cerises");
+        assert_eq!(case(title.clone(), "L'AMOUR À LA PLAGE"), "L'Amour à la Plage");
+        assert_eq!(case(title, "ROCKY II (DJ MIX)"), "Rocky II (DJ Mix)");
+        assert_eq!(
+            case(serde_json::json!({"type": "title_case", "style": "sentence", "acronyms": ["AC/DC"]}), "HOMMAGE À AC/DC"),
+            "Hommage à AC/DC"
+        );
+        assert_eq!(case(serde_json::json!({"type": "title_case"}), "DON'T STOP"), "Don't Stop");
+    }
+
     #[test]
     fn test_map() {
         let mut mapping = HashMap::new();
@@ -411,6 +879,25 @@ mod tests {
         assert_eq!(op.apply(&Value::String("123-456-789".to_string())), Value::Number(123456789.into()));
     }
 
+    #[test]
+    fn test_to_decimal() {
+        let op = Operation::ToDecimal;
+        assert_eq!(op.apply(&Value::String("33,33 %".to_string())), serde_json::json!(33.33));
+        assert_eq!(op.apply(&Value::String("50.00".to_string())), serde_json::json!(50.0));
+        assert_eq!(op.apply(&Value::String("n/a".to_string())), Value::Null);
+        assert_eq!(op.apply(&Value::String("1.234,56".to_string())), Value::Null);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let text = |s: &str| Value::String(s.to_string());
+        assert_eq!(Operation::Multiply { by: 100.0 }.apply(&text("0,5")), serde_json::json!(50.0));
+        assert_eq!(Operation::Divide { by: 10.0 }.apply(&serde_json::json!(333)), serde_json::json!(33.3));
+        assert_eq!(Operation::Divide { by: 0.0 }.apply_checked(&text("12")), (Value::Null, true));
+        assert_eq!(Operation::Round { decimals: 2 }.apply(&serde_json::json!(33.3333)), serde_json::json!(33.33));
+        assert_eq!(Operation::Round { decimals: 0 }.apply_checked(&text("n/a")), (Value::Null, true));
+    }
+
     #[test]
     fn test_extract_year() {
         let op = Operation::ExtractYear;
@@ -418,11 +905,100 @@ mod tests {
         assert_eq!(op.apply(&Value::String("2023-12-25".to_string())), Value::Number(2023.into()));
     }
 
+    #[test]
+    fn test_date_parse() {
+        let parse = |format: &str, output: DateComponent, text: &str| {
+            Operation::DateParse { format: format.to_string(), output }.apply(&Value::String(text.to_string()))
+        };
+        assert_eq!(parse("%d/%m/%Y", DateComponent::Year, " 15/03/2024 "), serde_json::json!(2024));
+        assert_eq!(parse("%d/%m/%Y", DateComponent::Month, "15/03/2024"), serde_json::json!(3));
+        assert_eq!(parse("%d/%m/%Y", DateComponent::Iso, "5/3/2024"), serde_json::json!("2024-03-05"));
+        // Time after the date, day or month missing from the format
+        assert_eq!(parse("%Y-%m-%d", DateComponent::Iso, "2023-12-25 10:30:00"), serde_json::json!("2023-12-25"));
+        assert_eq!(parse("%m/%Y", DateComponent::Iso, "07/1999"), serde_json::json!("1999-07-01"));
+        assert_eq!(parse("%Y", DateComponent::Iso, "1985"), serde_json::json!("1985-01-01"));
+        // Composite specifiers hold the day and month already
+        assert_eq!(parse("%F", DateComponent::Iso, "2024-03-15"), serde_json::json!("2024-03-15"));
+        assert_eq!(parse("%D", DateComponent::Iso, "03/15/24"), serde_json::json!("2024-03-15"));
+
+        // Month first: 15 is not a month
+        let op = Operation::DateParse { format: "%m/%d/%Y".to_string(), output: DateComponent::Year };
+        assert_eq!(op.apply_checked(&Value::String("15/03/2024".to_string())), (Value::Null, true));
+        let op: Operation = serde_json::from_value(serde_json::json!({"type": "date_parse", "format": "%Y"})).unwrap();
+        assert!(matches!(op, Operation::DateParse { output: DateComponent::Year, .. }));
+    }
+
+    #[test]
+    fn test_when() {
+        let op: Operation = serde_json::from_value(serde_json::json!(
+            {"type": "when", "equals": "instr", "then": true, "else": false}
+        )).unwrap();
+        assert_eq!(op.apply(&Value::String(" INSTR ".to_string())), Value::Bool(true));
+        assert_eq!(op.apply(&Value::String("vocal".to_string())), Value::Bool(false));
+
+        // Condition on another column, value kept when no branch is given
+        let op: Operation = serde_json::from_value(serde_json::json!(
+            {"type": "when", "column": "Author", "empty": true, "then": "Instrumental"}
+        )).unwrap();
+        let value = Value::String("Song".to_string());
+        assert_eq!(op.apply_when(&value, None), (Value::String("Instrumental".to_string()), false));
+        assert_eq!(op.apply_when(&value, Some(&Value::String("Doe".to_string()))), (value.clone(), false));
+
+        let op = Operation::When {
+            column: None, equals: None, matches: Some("[".to_string()), empty: None,
+            then: Some(Value::Bool(true)), otherwise: None,
+        };
+        assert_eq!(op.apply_checked(&value), (value, true));
+
+        // Patterns are compiled once, invalid ones remembered as such
+        assert!(cached_regex("[").is_none());
+        assert!(REGEX_CACHE.lock().unwrap().contains_key("["));
+        assert!(cached_regex(r"^\d+$").is_some_and(|re| re.is_match("42")));
+    }
+
+    #[test]
+    fn test_clamp_year() {
+        let op = Operation::ClampYear { min: 1700, max: Some(2024) };
+        assert_eq!(op.apply(&Value::Number(1985.into())), Value::Number(1985.into()));
+        assert_eq!(op.apply(&Value::String(" 2024 ".to_string())), Value::Number(2024.into()));
+        assert_eq!(op.apply(&Value::Number(201.into())), Value::Null);
+        assert_eq!(op.apply_checked(&Value::String("2301".to_string())), (Value::Null, true));
+    }
+
     #[test]
     fn test_ensure_prefix() {
         let op = Operation::EnsurePrefix { value: "T".to_string() };
         assert_eq!(op.apply(&Value::String("1234567890".to_string())), Value::String("T1234567890".to_string()));
         assert_eq!(op.apply(&Value::String("T1234567890".to_string())), Value::String("T1234567890".to_string()));
     }
+
+    #[test]
+    fn test_fix_iswc() {
+        let op: Operation = serde_json::from_value(serde_json::json!({"type": "validate_iswc"})).unwrap();
+        assert_eq!(op.name(), "fix_iswc");
+        let text = |s: &str| Value::String(s.to_string());
+        assert_eq!(op.apply(&text(" t-034.524.680-1 ")), text("T0345246801"));
+        assert_eq!(op.apply(&text("0345246801")), text("T0345246801"));
+        // Typo: the check digit no longer matches
+        assert_eq!(op.apply_checked(&text("T-034.524.860-1")), (Value::Null, true));
+        assert_eq!(op.apply(&text("T12345")), Value::Null);
+    }
+
+    #[test]
+    fn test_hash_id() {
+        let op = Operation::HashId { length: 16, prefix: "W-".to_string() };
+        let a = op.apply(&Value::String("Ma Chanson 123456789".to_string()));
+        let b = op.apply(&Value::String("  ma   chanson 123456789 ".to_string()));
+        let c = op.apply(&Value::String("Autre Chanson 123456789".to_string()));
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        let id = a.as_str().unwrap();
+        assert!(id.starts_with("W-"));
+        assert_eq!(id.len(), 18);
+
+        // Empty input stays empty so required checks still apply
+        assert_eq!(op.apply(&Value::String("  ".to_string())), Value::String(String::new()));
+    }
 }
 
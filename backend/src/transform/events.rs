@@ -0,0 +1,115 @@
+//! Lifecycle events of a transformation job.
+//!
+//! A job records structured events as it goes through the pipeline (created,
+//! matrix selected, transform done, validation done, completed or failed) so
+//! integrators can follow it without parsing logs: they are served by
+//! `GET /api/v1/jobs/{id}/events` and, when configured, pushed to a webhook
+//! (see [`crate::config`]).
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Mutex;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::deterministic;
+
+/// Kind of lifecycle event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EventKind {
+    #[serde(rename = "job.created")]
+    JobCreated,
+    #[serde(rename = "matrix.selected")]
+    MatrixSelected,
+    #[serde(rename = "transform.done")]
+    TransformDone,
+    #[serde(rename = "validation.done")]
+    ValidationDone,
+    #[serde(rename = "job.completed")]
+    Completed,
+    #[serde(rename = "job.failed")]
+    Failed,
+}
+
+/// One event of a job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobEvent {
+    pub job_id: String,
+    /// Position of the event in the job (from 1)
+    pub sequence: usize,
+    pub event: EventKind,
+    /// RFC 3339 timestamp
+    pub at: String,
+    /// Details of the event (template, counts, error...)
+    pub data: Value,
+}
+
+/// Events of one job, in order, optionally forwarded as they are emitted
+#[derive(Debug)]
+pub struct EventLog {
+    job_id: String,
+    events: Mutex<Vec<JobEvent>>,
+    /// Receives every event (e.g. a webhook forwarder)
+    sink: Option<UnboundedSender<JobEvent>>,
+}
+
+impl EventLog {
+    pub fn new(job_id: impl Into<String>) -> Self {
+        Self { job_id: job_id.into(), events: Mutex::new(Vec::new()), sink: None }
+    }
+
+    /// Forward every event to `sink` as well
+    pub fn with_sink(mut self, sink: UnboundedSender<JobEvent>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    pub fn job_id(&self) -> &str {
+        &self.job_id
+    }
+
+    /// Record an event
+    pub fn emit(&self, event: EventKind, data: Value) {
+        let mut events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+        let event = JobEvent {
+            job_id: self.job_id.clone(),
+            sequence: events.len() + 1,
+            event,
+            at: deterministic::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            data,
+        };
+        if let Some(ref sink) = self.sink {
+            // A closed forwarder only loses the push, the event is still recorded
+            let _ = sink.send(event.clone());
+        }
+        events.push(event);
+    }
+
+    /// Events recorded so far
+    pub fn events(&self) -> Vec<JobEvent> {
+        self.events.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_events_are_numbered_and_forwarded() {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let log = EventLog::new("job-1").with_sink(sender);
+        log.emit(EventKind::JobCreated, json!({ "fileName": "catalog.csv" }));
+        log.emit(EventKind::Completed, json!({ "works": 3 }));
+
+        let events = log.events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].sequence, 2);
+        let value = serde_json::to_value(&events[0]).unwrap();
+        assert_eq!(value["event"], "job.created");
+        assert_eq!(value["jobId"], "job-1");
+        assert_eq!(receiver.try_recv().unwrap().event, EventKind::JobCreated);
+        assert_eq!(receiver.try_recv().unwrap().data["works"], 3);
+    }
+}
@@ -22,33 +22,84 @@
 //!
 //! - `creators[].id`: `{ "type": "Ipi", "value": 123 }` (dedot enum format)
 //! - `creators[].role`: `"Composer"` (simple string)
+//! - `creators[].shares`: `{ "ownership": 50, "performance": 50, "mechanical": 100 }`
+//!   from the `creatorShare`, `creatorPerformanceShare` and `creatorMechanicalShare` columns
 //! - Optional fields are OMITTED if null (SDK doesn't like null)
+//!
+//! Works are returned as typed [`GroupedWork`]s; [`to_values`] gives the JSON payload.
 
-use serde_json::{json, Map, Value};
+use serde_json::{json, Value};
 use std::collections::HashMap;
 
+use crate::models::{ClassicalInfo, Creator, CreatorRole, CreatorShares, GroupedWork, MusicalWorkType, PartyId, WorkTitle};
+
 /// Transform a set of flat rows into grouped musical works.
 ///
 /// Output format is compatible with @allfeat/client SDK (dedot).
-pub fn flat_to_grouped(flat_rows: Vec<Value>) -> Vec<Value> {
-    let mut works: HashMap<String, WorkBuilder> = HashMap::new();
+pub fn flat_to_grouped(flat_rows: Vec<Value>) -> Vec<GroupedWork> {
+    // Works keep the order of their first row
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut works: Vec<WorkBuilder> = Vec::new();
 
     for row in flat_rows {
-        if let Some(iswc) = row.get("iswc").and_then(|v| v.as_str()) {
-            let builder = works.entry(iswc.to_string()).or_insert_with(|| {
-                WorkBuilder::new(&row)
+        if let Some(key) = grouping_key(&row) {
+            let position = *index.entry(key).or_insert_with(|| {
+                works.push(WorkBuilder::new(&row));
+                works.len() - 1
             });
-            builder.add_creator(&row);
+            works[position].add_creator(&row);
         }
     }
 
-    works.into_values().map(|b| b.build()).collect()
+    works.into_iter().map(|b| b.build()).collect()
+}
+
+/// Source lines of the rows of each work (`lines` parallel to `flat_rows`),
+/// in the order works are built: first row order as [`flat_to_grouped`], or
+/// sorted by key (`sorted`) as the streaming grouper yields them.
+pub fn work_lines(flat_rows: &[Value], lines: &[usize], sorted: bool) -> Vec<Vec<usize>> {
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut works: Vec<(String, Vec<usize>)> = Vec::new();
+
+    for (row, &line) in flat_rows.iter().zip(lines) {
+        if let Some(key) = grouping_key(row) {
+            let position = *index.entry(key.clone()).or_insert_with(|| {
+                works.push((key, Vec::new()));
+                works.len() - 1
+            });
+            works[position].1.push(line);
+        }
+    }
+    if sorted {
+        works.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+    works.into_iter().map(|(_, lines)| lines).collect()
+}
+
+/// Serialize grouped works to SDK JSON values.
+pub fn to_values(works: &[GroupedWork]) -> Vec<Value> {
+    works.iter().map(GroupedWork::to_value).collect()
+}
+
+/// Key identifying the work a row belongs to: the ISWC, or the `workKey`
+/// (e.g. a `hash_id`) for catalogs lacking work identifiers.
+pub(crate) fn grouping_key(row: &Value) -> Option<String> {
+    let non_empty = |field: &str| {
+        row.get(field)
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.trim().is_empty())
+    };
+
+    non_empty("iswc")
+        .map(|iswc| iswc.to_string())
+        .or_else(|| non_empty("workKey").map(|key| format!("key:{}", key)))
 }
 
 /// Builder for accumulating creators while grouping.
-struct WorkBuilder {
+pub(crate) struct WorkBuilder {
     iswc: String,
     title: String,
+    title_language: Option<String>,
     creation_year: Option<i64>,
     instrumental: Option<bool>,
     language: Option<String>,
@@ -58,14 +109,15 @@ struct WorkBuilder {
     opus: Option<String>,
     catalog_number: Option<String>,
     number_of_voices: Option<i64>,
-    creators: Vec<Value>,
+    creators: Vec<Creator>,
 }
 
 impl WorkBuilder {
-    fn new(row: &Value) -> Self {
+    pub(crate) fn new(row: &Value) -> Self {
         Self {
             iswc: row.get("iswc").and_then(|v| v.as_str()).unwrap_or("").to_string(),
             title: row.get("title").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            title_language: row.get("titleLanguage").and_then(|v| v.as_str()).map(String::from),
             creation_year: row.get("creationYear").and_then(|v| v.as_i64()),
             instrumental: row.get("instrumental").and_then(|v| v.as_bool()),
             language: row.get("language").and_then(|v| v.as_str()).map(String::from),
@@ -79,86 +131,51 @@ impl WorkBuilder {
         }
     }
 
-    fn add_creator(&mut self, row: &Value) {
-        let ipi = row.get("creatorIpi").and_then(|v| v.as_i64());
-        let isni = row.get("creatorIsni").and_then(|v| v.as_str());
-        let role = row.get("creatorRole").and_then(|v| v.as_str());
-
-        if let Some(role) = role {
-            // Format SDK dedot: { "type": "Ipi", "value": 123 }
-            let id = match (ipi, isni) {
-                (Some(ipi), Some(isni)) => json!({
-                    "type": "Both",
-                    "value": { "ipi": ipi, "isni": isni }
-                }),
-                (Some(ipi), None) => json!({
-                    "type": "Ipi",
-                    "value": ipi
-                }),
-                (None, Some(isni)) => json!({
-                    "type": "Isni",
-                    "value": isni
-                }),
-                (None, None) => return, // Skip if no ID
-            };
-
-            // Role: simple string (SDK accepts this)
-            self.creators.push(json!({
-                "id": id,
-                "role": role
-            }));
+    pub(crate) fn add_creator(&mut self, row: &Value) {
+        let ipi = row.get("creatorIpi").and_then(|v| v.as_i64()).and_then(|v| u64::try_from(v).ok());
+        let isni = row.get("creatorIsni").and_then(|v| v.as_str()).map(String::from);
+        // Roles outside `CreatorRole` can't be sent on-chain: the creator is skipped
+        let role = row.get("creatorRole")
+            .and_then(|v| serde_json::from_value::<CreatorRole>(v.clone()).ok());
+        let shares = CreatorShares {
+            ownership: row.get("creatorShare").and_then(|v| v.as_f64()),
+            performance: row.get("creatorPerformanceShare").and_then(|v| v.as_f64()),
+            mechanical: row.get("creatorMechanicalShare").and_then(|v| v.as_f64()),
+        };
+
+        // Skip if no ID (serialized by the model as { "type": "Ipi", "value": 123 })
+        if let (Some(id), Some(role)) = (PartyId::from_optional(ipi, isni), role) {
+            self.creators.push(Creator { id, role, name: None, shares: shares.non_empty() });
         }
     }
 
-    fn build(self) -> Value {
-        let mut obj = Map::new();
-        
-        // Required fields
-        obj.insert("iswc".to_string(), json!(self.iswc));
-        obj.insert("title".to_string(), json!(self.title));
-        obj.insert("creators".to_string(), json!(self.creators));
-        
-        // participants: required by Melodie runtime (empty array for now)
-        // This field is for performers/interpreters, not creators
-        obj.insert("participants".to_string(), json!([]));
-        
-        // Optional fields - ONLY include if present (SDK doesn't like null)
-        if let Some(v) = self.creation_year {
-            obj.insert("creationYear".to_string(), json!(v));
-        }
-        if let Some(v) = self.instrumental {
-            obj.insert("instrumental".to_string(), json!(v));
-        }
-        if let Some(ref v) = self.language {
-            obj.insert("language".to_string(), json!(v));
-        }
-        if let Some(v) = self.bpm {
-            obj.insert("bpm".to_string(), json!(v));
-        }
-        if let Some(ref v) = self.key {
-            obj.insert("key".to_string(), json!(v));
-        }
-        if let Some(ref v) = self.work_type {
+    pub(crate) fn build(self) -> GroupedWork {
+        let classical_info = (self.opus.is_some() || self.catalog_number.is_some() || self.number_of_voices.is_some())
+            .then(|| ClassicalInfo {
+                opus: self.opus,
+                catalog_number: self.catalog_number,
+                number_of_voices: self.number_of_voices.and_then(|v| u32::try_from(v).ok()),
+            });
+
+        GroupedWork {
+            iswc: self.iswc,
+            title: WorkTitle::new(self.title, self.title_language),
+            alternative_titles: Vec::new(),
+            creators: self.creators,
+            // participants: required by Melodie runtime (empty array for now)
+            // This field is for performers/interpreters, not creators
+            participants: Vec::new(),
             // workType: { type: "Original" } format for SDK
-            obj.insert("workType".to_string(), json!({ "type": v }));
-        }
-        
-        // Classical info - only if any field present
-        if self.opus.is_some() || self.catalog_number.is_some() || self.number_of_voices.is_some() {
-            let mut classical = Map::new();
-            if let Some(v) = self.opus {
-                classical.insert("opus".to_string(), json!(v));
-            }
-            if let Some(v) = self.catalog_number {
-                classical.insert("catalogNumber".to_string(), json!(v));
-            }
-            if let Some(v) = self.number_of_voices {
-                classical.insert("numberOfVoices".to_string(), json!(v));
-            }
-            obj.insert("classicalInfo".to_string(), Value::Object(classical));
+            work_type: self.work_type
+                .and_then(|v| serde_json::from_value::<MusicalWorkType>(json!({ "type": v })).ok()),
+            creation_year: self.creation_year.and_then(|v| u16::try_from(v).ok()),
+            genre: None,
+            instrumental: self.instrumental,
+            language: self.language,
+            bpm: self.bpm.and_then(|v| u16::try_from(v).ok()),
+            key: self.key,
+            classical_info,
         }
-
-        Value::Object(obj)
     }
 }
 
@@ -167,6 +184,10 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    fn grouped_values(rows: Vec<Value>) -> Vec<Value> {
+        to_values(&flat_to_grouped(rows))
+    }
+
     #[test]
     fn test_single_work_multiple_creators() {
         let rows = vec![
@@ -188,7 +209,7 @@ mod tests {
             }),
         ];
 
-        let grouped = flat_to_grouped(rows);
+        let grouped = grouped_values(rows);
         
         assert_eq!(grouped.len(), 1);
         let work = &grouped[0];
@@ -208,7 +229,7 @@ mod tests {
             }),
         ];
 
-        let grouped = flat_to_grouped(rows);
+        let grouped = grouped_values(rows);
         let creator = &grouped[0]["creators"][0];
         
         // SDK format: { type: "Ipi", value: 123 }
@@ -229,7 +250,7 @@ mod tests {
             }),
         ];
 
-        let grouped = flat_to_grouped(rows);
+        let grouped = grouped_values(rows);
         let creator = &grouped[0]["creators"][0];
         
         // SDK format: { type: "Both", value: { ipi: ..., isni: ... } }
@@ -249,7 +270,7 @@ mod tests {
             }),
         ];
 
-        let grouped = flat_to_grouped(rows);
+        let grouped = grouped_values(rows);
         let work = &grouped[0];
         
         // These optional fields should NOT be present (not even as null)
@@ -272,9 +293,118 @@ mod tests {
             }),
         ];
 
-        let grouped = flat_to_grouped(rows);
+        let grouped = grouped_values(rows);
         
         // SDK format: { type: "Original" }
         assert_eq!(grouped[0]["workType"]["type"], "Original");
     }
+
+    #[test]
+    fn test_group_by_work_key_without_iswc() {
+        let rows = vec![
+            json!({ "workKey": "W-abc", "title": "Sans ISWC", "creatorIpi": 1, "creatorRole": "Composer" }),
+            json!({ "workKey": "W-abc", "title": "Sans ISWC", "creatorIpi": 2, "creatorRole": "Author" }),
+            json!({ "workKey": "W-def", "title": "Autre", "creatorIpi": 3, "creatorRole": "Composer" }),
+        ];
+
+        let grouped = grouped_values(rows);
+
+        assert_eq!(grouped.len(), 2);
+        let work = grouped.iter().find(|w| w["title"] == "Sans ISWC").unwrap();
+        assert_eq!(work["creators"].as_array().unwrap().len(), 2);
+        // workKey is internal and never part of the SDK payload
+        assert!(work.get("workKey").is_none());
+    }
+
+    #[test]
+    fn test_round_trip_through_grouped_schema() {
+        let rows = vec![
+            json!({
+                "iswc": "T1234567890",
+                "title": "Sonate",
+                "creationYear": 1801,
+                "instrumental": true,
+                "language": "German",
+                "bpm": 60,
+                "key": "C#m",
+                "workType": "Original",
+                "opus": "Op. 27 No. 2",
+                "catalogNumber": "LvB 27",
+                "numberOfVoices": 1,
+                "creatorIpi": 123456789,
+                "creatorRole": "Composer"
+            }),
+        ];
+
+        let works = flat_to_grouped(rows);
+        let value = works[0].to_value();
+        crate::validation::validate_musical_work_grouped(&value).unwrap();
+
+        let back: GroupedWork = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(back, works[0]);
+        assert_eq!(value["classicalInfo"]["opus"], "Op. 27 No. 2");
+        assert_eq!(value["workType"]["type"], "Original");
+        assert_eq!(value["bpm"], 60);
+    }
+
+    #[test]
+    fn test_title_language_tags_title() {
+        let rows = vec![
+            json!({ "iswc": "T1234567890", "title": "La Vie en rose", "titleLanguage": "French", "creatorIpi": 1, "creatorRole": "Composer" }),
+            json!({ "iswc": "T2222222222", "title": "Plain", "creatorIpi": 2, "creatorRole": "Composer" }),
+        ];
+
+        let grouped = grouped_values(rows);
+
+        assert_eq!(grouped[0]["title"], json!({ "title": "La Vie en rose", "language": "French" }));
+        assert_eq!(grouped[1]["title"], "Plain");
+        crate::validation::validate_musical_work_grouped(&grouped[0]).unwrap();
+        assert_eq!(crate::models::title_text(&grouped[0]["title"]), Some("La Vie en rose"));
+    }
+
+    #[test]
+    fn test_schema_example_deserializes() {
+        let example = json!({
+            "iswc": "T1234567890",
+            "title": "My Song",
+            "creationYear": 2024,
+            "creators": [
+                { "id": { "type": "Ipi", "value": 123456789 }, "role": "Composer" }
+            ],
+            "participants": []
+        });
+
+        let work: GroupedWork = serde_json::from_value(example.clone()).unwrap();
+        assert_eq!(work.creation_year, Some(2024));
+        assert_eq!(work.to_value(), example);
+    }
+
+    #[test]
+    fn test_creator_share_types_kept_apart() {
+        let rows = vec![
+            json!({ "iswc": "T1234567890", "title": "Test", "creatorIpi": 1, "creatorRole": "Composer",
+                    "creatorShare": 50.0, "creatorPerformanceShare": 50.0, "creatorMechanicalShare": 100.0 }),
+            json!({ "iswc": "T1234567890", "title": "Test", "creatorIpi": 2, "creatorRole": "Author", "creatorPerformanceShare": 50.0 }),
+            json!({ "iswc": "T1234567890", "title": "Test", "creatorIpi": 3, "creatorRole": "Arranger" }),
+        ];
+
+        let grouped = grouped_values(rows);
+        let creators = grouped[0]["creators"].as_array().unwrap();
+
+        assert_eq!(creators[0]["shares"], json!({ "ownership": 50.0, "performance": 50.0, "mechanical": 100.0 }));
+        assert_eq!(creators[1]["shares"], json!({ "performance": 50.0 }));
+        assert!(creators[2].get("shares").is_none());
+        crate::validation::validate_musical_work_grouped(&grouped[0]).unwrap();
+    }
+
+    #[test]
+    fn test_unknown_role_creator_skipped() {
+        let rows = vec![
+            json!({ "iswc": "T1234567890", "title": "Test", "creatorIpi": 1, "creatorRole": "Composer" }),
+            json!({ "iswc": "T1234567890", "title": "Test", "creatorIpi": 2, "creatorRole": "Drummer" }),
+        ];
+
+        let works = flat_to_grouped(rows);
+        assert_eq!(works[0].creators.len(), 1);
+    }
 }
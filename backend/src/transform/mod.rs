@@ -3,13 +3,39 @@
 //! This module handles CSV to MIDDS transformation:
 //! - DSL: Transformation operations and matrix
 //! - Grouper: Flat rows to grouped works
+//! - Diff: Changes between two versions of a catalog
+//! - Diagnosis: Top error causes when no record is valid
+//! - Allocation: ISWC allocation requests for works lacking a code
+//! - Roles: Creators per role and works without composer
+//! - Role inference: Roles of catalogs without a role column
+//! - Spill: Disk-backed flat records for very large catalogs
+//! - Streaming: External-sort grouping with bounded memory
+//! - Events: Lifecycle events of a job (served and pushed to webhooks)
 //! - Pipeline: Main transformation pipeline
 
+pub mod allocation;
+pub mod diagnosis;
+pub mod diff;
 pub mod dsl;
+pub mod events;
 pub mod grouper;
 pub mod pipeline;
+pub mod role_inference;
+pub mod roles;
+pub mod spill;
+pub mod streaming;
+pub mod ungroup;
 
+pub use allocation::{allocation_csv, works_lacking_iswc, AllocationCreator, AllocationRequest};
+pub use diagnosis::{diagnose_failure, ErrorCause, FailureReport, Suggestion};
+pub use diff::{diff_works, CatalogDiff, WorkChange};
 pub use dsl::*;
-pub use grouper::flat_to_grouped;
+pub use events::{EventKind, EventLog, JobEvent};
+pub use grouper::{flat_to_grouped, to_values, work_lines};
 pub use pipeline::*;
+pub use role_inference::{InferredRole, RoleInference, RolePlan};
+pub use roles::{role_report, ChainNetwork, RoleReport, UnsupportedRole, WorkRef};
+pub use spill::{group_spilled, SpillFile};
+pub use streaming::{flat_to_grouped_streaming, GroupedStream, LiveWorks, DEFAULT_RUN_SIZE};
+pub use ungroup::{flat_csv, grouped_to_flat, write_flat_csv};
 
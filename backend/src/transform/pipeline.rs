@@ -7,7 +7,7 @@
 //!
 //! ```rust,ignore
 //! use massload::pipeline::{transform_csv, TransformOptions};
-//! use std::path::Path;
+//! use std::path::{Path, PathBuf};
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -22,17 +22,26 @@
 //! ```
 
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
-use std::path::Path;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use thiserror::Error;
 
-use crate::parser::{parse_csv_file_auto, parse_bytes_auto, CsvError, ParseResult};
-use crate::transform::dsl::{execute, TransformationMatrix};
-use super::grouper::flat_to_grouped;
-use crate::api::logs::{log_info, log_success, log_warning, log_error};
+use crate::parser::{checksum_sha256, parse_bytes_with_options, parse_csv_stream, parse_cwr, parse_json_with_encoding, parse_xlsx, normalize_header, unpack, ColumnProfile, CsvError, CsvStream, FixedWidthColumn, InputFormat, ParseDiagnostics, ParseOptions, RaggedRow, RaggedRows, JSON_DELIMITER, ParseReport, ParseResult, LOW_ENCODING_CONFIDENCE};
+use crate::transform::dsl::{execute, execute_with_progress, FieldStats, SourceFormat, TransformationMatrix};
+use super::events::{EventKind, EventLog};
+use super::grouper::{flat_to_grouped, to_values, work_lines};
+use super::role_inference::{apply_plan, inferred_role_warnings, InferredRole, RoleInference, RolePlan};
+use super::roles::{role_report, ChainNetwork, RoleReport};
+use super::spill::SpillFile;
+use super::streaming::{flat_to_grouped_streaming, LiveWorks, DEFAULT_RUN_SIZE};
+use crate::api::logs::{log_debug, log_info, log_success, log_warning, log_error, register_pii_names, set_log_stage, LogStage, LOG_BROADCASTER};
 use crate::cache::MatrixRegistry;
-use crate::validation::{validate_musical_work_flat, validate_musical_work_grouped};
-use crate::ai::{AiClient, AiError};
+use crate::models::CatalogPeriod;
+use crate::progress::StageProgress;
+use crate::validation::{check_creation_years, ValidationContext, ValidationWarning, YearBounds};
+use crate::ai::{column_stats_note, duplicate_columns_note, generate_with_failover, heuristic_matrix, stratified_sample, varied_sample, AiCostEstimate, AiError, Engine, EngineReport};
 
 /// Pipeline errors
 #[derive(Error, Debug)]
@@ -43,12 +52,18 @@ pub enum PipelineError {
     #[error("AI error: {0}")]
     AiError(#[from] AiError),
 
+    #[error("Estimated AI cost of up to ${:.4} exceeds the confirmation threshold", .0.cost_usd)]
+    AiCostNotConfirmed(AiCostEstimate),
+
     #[error("Matrix error: {0}")]
     MatrixError(String),
 
     #[error("Validation error: {0}")]
     ValidationError(String),
 
+    #[error(transparent)]
+    Schema(#[from] crate::error::ValidationError),
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 }
@@ -59,7 +74,7 @@ pub struct TransformOptions {
     /// Use a specific matrix file instead of AI/cache
     pub matrix_path: Option<String>,
 
-    /// Number of rows to send to AI for analysis
+    /// Number of rows to send to AI for analysis (capped at [`MAX_PREVIEW_ROWS`])
     pub preview_rows: usize,
 
     /// Skip validation step
@@ -70,6 +85,137 @@ pub struct TransformOptions {
 
     /// Don't save generated matrix to cache
     pub no_save: bool,
+
+    /// Columns stripped right after parsing (never sent to the AI nor persisted)
+    #[serde(default)]
+    pub exclude_columns: Vec<String>,
+
+    /// Send only column names and value patterns to the AI, never raw values
+    #[serde(default)]
+    pub structure_only: bool,
+
+    /// Never call an AI provider: guess the matrix from column names
+    #[serde(default)]
+    pub no_ai: bool,
+
+    /// Stop with [`PipelineError::AiCostNotConfirmed`] instead of calling the AI
+    /// when the estimated cost (USD) exceeds this threshold
+    #[serde(default)]
+    pub ai_cost_limit: Option<f64>,
+
+    /// Lines to skip before the header (overrides the matrix `source_format`)
+    #[serde(default)]
+    pub skip_first: Option<usize>,
+
+    /// 1-based line of the header row, for banner lines above it (overrides `skip_first`)
+    #[serde(default)]
+    pub header_row: Option<usize>,
+
+    /// Header lines merged into composite column names (overrides the matrix `source_format`)
+    #[serde(default)]
+    pub header_rows: Option<usize>,
+
+    /// Lines to drop at the end of the file (overrides the matrix `source_format`)
+    #[serde(default, alias = "skip_footer_rows")]
+    pub skip_last: Option<usize>,
+
+    /// Rows with more or fewer cells than the header: `pad` (silently), `warn` or `reject`
+    #[serde(default)]
+    pub ragged_rows: RaggedRows,
+
+    /// Force the source encoding instead of detecting it
+    #[serde(default)]
+    pub encoding: Option<String>,
+
+    /// Force the CSV delimiter instead of detecting it
+    #[serde(default)]
+    pub delimiter: Option<char>,
+
+    /// Input format (JSON records skip CSV parsing; flat MIDDS records also skip the matrix)
+    #[serde(default)]
+    pub input_format: InputFormat,
+
+    /// Name of the template saved from an AI matrix (default: source file name)
+    #[serde(default)]
+    pub template_name: Option<String>,
+
+    /// Read the source as fixed-width text with this layout (overrides the matrix `source_format`)
+    #[serde(default)]
+    pub fixed_width: Option<Vec<FixedWidthColumn>>,
+
+    /// Minimum valid ratio for a cached template to be accepted (0.0 to 1.0)
+    #[serde(default = "default_min_valid_ratio")]
+    pub min_valid_ratio: f64,
+
+    /// AI generations tried when no cached template fits, each on another
+    /// preview sample; the best matrix is kept
+    #[serde(default = "default_ai_attempts")]
+    pub ai_attempts: usize,
+
+    /// Stop the AI attempts once a matrix reaches this valid ratio (default: `min_valid_ratio`)
+    #[serde(default)]
+    pub ai_target_ratio: Option<f64>,
+
+    /// Emit per-row debug logs (skipped rows, transform and validation errors)
+    #[serde(default)]
+    pub verbose: bool,
+
+    /// Spill flat records to an NDJSON file in this directory and group
+    /// from disk (large catalogs on modest RAM)
+    #[serde(default)]
+    pub spill_dir: Option<String>,
+
+    /// Directory of the sort runs of a live grouping (default: system temp dir)
+    #[serde(default)]
+    pub temp_dir: Option<String>,
+
+    /// Accepted `creationYear` range; years outside are warnings, not errors
+    #[serde(default)]
+    pub year_bounds: YearBounds,
+
+    /// Template registry directory (default `.massload/matrices`)
+    #[serde(default)]
+    pub registry_dir: Option<String>,
+
+    /// Network whose runtime roles the creators are checked against
+    #[serde(default)]
+    pub network: ChainNetwork,
+
+    /// Infer creator roles when the matrix reads no role column
+    #[serde(default)]
+    pub role_inference: RoleInference,
+
+    /// Accounting period of the catalog, recorded in template stats and the result
+    #[serde(default)]
+    pub period: Option<CatalogPeriod>,
+
+    /// Publish grouped works here as they are built (streamed download of a running job)
+    #[serde(skip)]
+    pub live: Option<Arc<LiveWorks>>,
+
+    /// Record the job's lifecycle events here (matrix selected, transform and validation done)
+    #[serde(skip)]
+    pub events: Option<Arc<EventLog>>,
+}
+
+/// Default minimum valid ratio for cached templates
+pub const DEFAULT_MIN_VALID_RATIO: f64 = 0.8;
+
+/// Ragged rows logged one by one (all are listed in `CsvInfo`)
+const MAX_RAGGED_LOGS: usize = 20;
+
+/// Maximum preview rows sent to the AI (keeps the prompt size bounded)
+pub const MAX_PREVIEW_ROWS: usize = 50;
+
+fn default_min_valid_ratio() -> f64 {
+    DEFAULT_MIN_VALID_RATIO
+}
+
+/// Default number of AI generations per file
+pub const DEFAULT_AI_ATTEMPTS: usize = 1;
+
+fn default_ai_attempts() -> usize {
+    DEFAULT_AI_ATTEMPTS
 }
 
 impl Default for TransformOptions {
@@ -80,6 +226,50 @@ impl Default for TransformOptions {
             skip_validation: false,
             no_cache: false,
             no_save: false,
+            exclude_columns: Vec::new(),
+            structure_only: false,
+            no_ai: false,
+            ai_cost_limit: None,
+            skip_first: None,
+            header_row: None,
+            header_rows: None,
+            skip_last: None,
+            ragged_rows: RaggedRows::default(),
+            encoding: None,
+            delimiter: None,
+            input_format: InputFormat::Auto,
+            template_name: None,
+            fixed_width: None,
+            min_valid_ratio: DEFAULT_MIN_VALID_RATIO,
+            ai_attempts: DEFAULT_AI_ATTEMPTS,
+            ai_target_ratio: None,
+            verbose: false,
+            spill_dir: None,
+            temp_dir: None,
+            year_bounds: YearBounds::default(),
+            registry_dir: None,
+            network: ChainNetwork::default(),
+            role_inference: RoleInference::default(),
+            period: None,
+            live: None,
+            events: None,
+        }
+    }
+}
+
+impl TransformOptions {
+    /// Record a lifecycle event when the job has an event log
+    fn emit(&self, event: EventKind, data: Value) {
+        if let Some(ref events) = self.events {
+            events.emit(event, data);
+        }
+    }
+
+    /// Template registry of these options
+    fn registry(&self) -> MatrixRegistry {
+        match &self.registry_dir {
+            Some(dir) => MatrixRegistry::with_dir(dir),
+            None => MatrixRegistry::new(),
         }
     }
 }
@@ -87,9 +277,16 @@ impl Default for TransformOptions {
 /// Result of a complete transformation pipeline
 #[derive(Debug, Clone, Serialize)]
 pub struct PipelineResult {
-    /// Flat records (one per creator)
+    /// Flat records (one per creator), empty when spilled to disk
     pub flat: Vec<Value>,
 
+    /// Flat records spilled to disk (`spill_dir` option)
+    #[serde(skip)]
+    pub flat_spill: Option<Arc<SpillFile>>,
+
+    /// 1-based source file line of each flat record (empty when the input has no lines)
+    pub flat_lines: Vec<usize>,
+
     /// Grouped records (one per work, with creators array)
     pub grouped: Vec<Value>,
 
@@ -102,23 +299,81 @@ pub struct PipelineResult {
     /// Validation errors (record index, errors)
     pub validation_errors: Vec<(usize, Vec<String>)>,
 
+    /// Non-blocking findings on grouped works (implausible creation years)
+    pub validation_warnings: Vec<ValidationWarning>,
+
+    /// Rows skipped because required fields were missing
+    pub skipped: Vec<super::dsl::SkippedRow>,
+
     /// Matrix used for transformation
     pub matrix: TransformationMatrix,
 
     /// Template ID if a cached template was used or created
     pub template_id: Option<String>,
 
+    /// Columns matched, missing and unused by the cached template (None if not from cache)
+    pub template_columns: Option<ColumnCoverage>,
+
+    /// Name of the template saved from the AI matrix (None when reused or not saved)
+    pub created_template: Option<String>,
+
+    /// Engine that generated the matrix (None when a cached or provided matrix was used)
+    pub matrix_engine: Option<EngineReport>,
+
+    /// Estimated size and cost of the AI request, when the AI was called
+    pub ai_estimate: Option<AiCostEstimate>,
+
     /// CSV parsing metadata
     pub csv_info: CsvInfo,
+
+    /// Operation failures per target field (empty when the matrix step was skipped)
+    pub field_stats: BTreeMap<String, FieldStats>,
+
+    /// Creators per role and works without composer
+    pub roles: RoleReport,
+
+    /// Accounting period of the catalog (`period` option)
+    pub period: Option<CatalogPeriod>,
 }
 
 /// CSV file information
 #[derive(Debug, Clone, Serialize)]
 pub struct CsvInfo {
     pub encoding: String,
+    pub encoding_confidence: f32,
     pub delimiter: char,
     pub headers: Vec<String>,
     pub row_count: usize,
+    /// SHA-256 of the source bytes (None for in-memory records)
+    pub sha256: Option<String>,
+    /// Trailing summary lines dropped by footer detection or `skip_last`
+    pub footer_lines: Vec<String>,
+    /// Rows whose cell count differs from the header (`ragged_rows` warn or reject)
+    pub ragged_rows: Vec<RaggedRow>,
+    /// NUL characters removed and line endings normalized before parsing
+    pub diagnostics: ParseDiagnostics,
+    /// Headers changed by normalization: normalized name → name in the file
+    pub original_headers: BTreeMap<String, String>,
+    /// Statistics of each column (after excluded columns are removed)
+    pub report: ParseReport,
+}
+
+impl CsvInfo {
+    fn new(parse_result: &ParseResult, sha256: Option<String>) -> Self {
+        Self {
+            encoding: parse_result.encoding.clone(),
+            encoding_confidence: parse_result.encoding_confidence,
+            delimiter: parse_result.delimiter,
+            headers: parse_result.headers.clone(),
+            row_count: parse_result.records.len(),
+            sha256,
+            footer_lines: parse_result.footer_lines.clone(),
+            ragged_rows: parse_result.ragged_rows.clone(),
+            diagnostics: parse_result.diagnostics.clone(),
+            original_headers: parse_result.original_headers.clone(),
+            report: parse_result.report(),
+        }
+    }
 }
 
 /// Transform a CSV file to MIDDS format.
@@ -131,7 +386,8 @@ pub struct CsvInfo {
 /// 5. Groups records by ISWC
 ///
 /// # Arguments
-/// * `path` - Path to the CSV file
+/// * `path` - Path to the CSV file (gzip or single-entry zip archives are
+///   decompressed, the template then named after the file inside)
 /// * `options` - Transformation options
 ///
 /// # Returns
@@ -141,8 +397,13 @@ pub async fn transform_csv(
     options: TransformOptions,
 ) -> Result<PipelineResult, PipelineError> {
     // 1. Parse CSV
-    let parse_result = parse_csv_file_auto(path)?;
-    transform_parsed(parse_result, options, Some(path)).await
+    let file_name = path.file_name().and_then(|n| n.to_str());
+    let unpacked = unpack(std::fs::read(path)?, file_name)?;
+    let source_path = match (unpacked.compression, &unpacked.file_name) {
+        (Some(_), Some(inner)) => path.with_file_name(inner),
+        _ => path.to_path_buf(),
+    };
+    transform_input(&unpacked.bytes, options, Some(&source_path)).await
 }
 
 /// Transform CSV bytes to MIDDS format.
@@ -152,13 +413,94 @@ pub async fn transform_bytes(
     bytes: &[u8],
     options: TransformOptions,
 ) -> Result<PipelineResult, PipelineError> {
-    let parse_result = parse_bytes_auto(bytes)?;
-    transform_parsed(parse_result, options, None).await
+    transform_input(bytes, options, None).await
+}
+
+/// Internal: parse CSV, JSON, XLSX or CWR input, then transform it.
+///
+/// JSON records that are already flat MIDDS (and no matrix is forced) and
+/// CWR files are only grouped and validated.
+async fn transform_input(
+    bytes: &[u8],
+    options: TransformOptions,
+    source_path: Option<&Path>,
+) -> Result<PipelineResult, PipelineError> {
+    let sha256 = Some(checksum_sha256(bytes));
+
+    let format = options.input_format.resolve(bytes);
+    if format == InputFormat::Json {
+        let parse_result = parse_json_with_encoding(bytes, options.encoding.as_deref())?;
+        if options.matrix_path.is_none() && is_midds_flat(&parse_result.records) {
+            return group_flat(parse_result, options, sha256);
+        }
+        return transform_json(parse_result, options, source_path, sha256).await;
+    }
+    if format == InputFormat::Cwr {
+        return group_flat(parse_cwr(bytes, options.encoding.as_deref())?, options, sha256);
+    }
+
+    let parse_result = if format == InputFormat::Xlsx {
+        parse_xlsx(bytes, &parse_options(&options)?)?
+    } else {
+        parse_bytes_with_options(bytes, &parse_options(&options)?)?
+    };
+    transform_parsed(parse_result, options, source_path, sha256).await
+}
+
+/// Read CSV records lazily from `reader`, for catalogs too large to hold in
+/// memory, with the same parser options as [`transform_bytes`] (skipped
+/// lines, footer, ragged rows, encoding and delimiter).
+pub fn stream_csv<R: std::io::Read>(reader: R, options: &TransformOptions) -> Result<CsvStream<R>, PipelineError> {
+    Ok(parse_csv_stream(reader, &parse_options(options)?)?)
+}
+
+/// Whether JSON records are already flat MIDDS (the matrix output format)
+pub fn is_midds_flat(records: &[Value]) -> bool {
+    !records.is_empty()
+        && records.iter().all(|r| r.get("iswc").is_some() && r.get("creatorRole").is_some())
+}
+
+/// Parser options: skip rows from the provided matrix `source_format`,
+/// overridden by explicit options
+fn parse_options(options: &TransformOptions) -> Result<ParseOptions, PipelineError> {
+    let mut parse = ParseOptions::default();
+
+    if let Some(ref matrix_path) = options.matrix_path {
+        let content = std::fs::read_to_string(matrix_path)?;
+        let matrix = TransformationMatrix::from_json(&content)
+            .map_err(|e| PipelineError::MatrixError(e.to_string()))?;
+        if let Some(format) = matrix.source_format {
+            parse = format.parse_options();
+        }
+    }
+
+    if let Some(n) = options.skip_first {
+        parse.skip_first = n;
+    }
+    if let Some(line) = options.header_row {
+        parse = parse.with_header_row(line);
+    }
+    if let Some(n) = options.header_rows {
+        parse.header_rows = n;
+    }
+    if let Some(n) = options.skip_last {
+        parse.skip_last = n;
+    }
+    parse.ragged_rows = options.ragged_rows;
+    if let Some(ref columns) = options.fixed_width {
+        parse.fixed_width = Some(columns.clone());
+    }
+    parse.encoding = options.encoding.clone();
+    parse.delimiter = options.delimiter;
+
+    Ok(parse)
 }
 
-/// Transform already-parsed CSV data.
+/// Transform already-parsed records (CSV rows, or JSON records from
+/// `parse_json_auto` / `parse_jsonl_auto`).
 ///
-/// Useful when you've already parsed the CSV and want to transform it.
+/// Non-text values (numbers, booleans, arrays) are turned into text first,
+/// as CSV cells are, so matrices work the same on every input format.
 pub async fn transform_records(
     records: Vec<Value>,
     headers: Vec<String>,
@@ -166,32 +508,103 @@ pub async fn transform_records(
 ) -> Result<PipelineResult, PipelineError> {
     let parse_result = ParseResult {
         records,
+        lines: Vec::new(),
         encoding: "utf-8".to_string(),
+        encoding_confidence: 1.0,
         delimiter: ',',
         headers,
+        footer_lines: Vec::new(),
+        ragged_rows: Vec::new(),
+        diagnostics: ParseDiagnostics::default(),
+        renamed_columns: Vec::new(),
+        original_headers: BTreeMap::new(),
+        fixed_width: None,
     };
-    transform_parsed(parse_result, options, None).await
+    transform_json(parse_result, options, None, None).await
+}
+
+/// Internal: transform JSON records through the matrix pipeline, values as text
+async fn transform_json(
+    mut parse_result: ParseResult,
+    options: TransformOptions,
+    source_path: Option<&Path>,
+    sha256: Option<String>,
+) -> Result<PipelineResult, PipelineError> {
+    parse_result.stringify_values();
+    transform_parsed(parse_result, options, source_path, sha256).await
 }
 
 /// Internal: transform parsed CSV data
 async fn transform_parsed(
-    parse_result: ParseResult,
+    mut parse_result: ParseResult,
     options: TransformOptions,
     source_path: Option<&Path>,
+    sha256: Option<String>,
 ) -> Result<PipelineResult, PipelineError> {
+    let validator = ValidationContext::shared()?;
+
     // Step 1: CSV Info
+    set_log_stage(LogStage::Parse);
     log_info("📖 Reading CSV file...");
     log_info("Detecting encoding and separator...");
-    log_success(format!("Detected encoding: {}", parse_result.encoding));
-    log_success(format!("Detected separator: '{}'", format_delimiter(parse_result.delimiter)));
+    if options.encoding.is_some() {
+        log_success(format!("Encoding: {} (forced, detection skipped)", parse_result.encoding));
+    } else if parse_result.encoding_confidence < LOW_ENCODING_CONFIDENCE {
+        log_warning(format!(
+            "Detected encoding: {} (low confidence {:.0}%), override the encoding if accents look wrong",
+            parse_result.encoding, parse_result.encoding_confidence * 100.0
+        ));
+    } else {
+        log_success(format!("Detected encoding: {}", parse_result.encoding));
+    }
+    match parse_result.fixed_width {
+        None if parse_result.delimiter == JSON_DELIMITER => log_success("Read JSON records"),
+        Some(ref columns) => log_success(format!("Fixed-width layout: {}", columns.iter()
+            .map(|c| format!("{} [{}..{}]", c.name, c.start, c.start + c.len))
+            .collect::<Vec<_>>()
+            .join(", "))),
+        None => log_success(format!("Detected separator: '{}'", format_delimiter(parse_result.delimiter))),
+    }
     log_success(format!("Read {} rows", parse_result.records.len()));
+    if let Some(ref sha256) = sha256 {
+        log_info(format!("🔐 File SHA-256: {}", sha256));
+    }
+    if !parse_result.original_headers.is_empty() {
+        log_info(format!("Normalized {} column name(s): {}",
+            parse_result.original_headers.len(),
+            parse_result.original_headers.iter()
+                .map(|(normalized, original)| format!("\"{}\" → \"{}\"", original, normalized))
+                .collect::<Vec<_>>()
+                .join(", ")));
+    }
+    for (original, renamed) in &parse_result.renamed_columns {
+        log_warning(format!("Duplicate column \"{}\" renamed to \"{}\"", original, renamed));
+    }
+    if !parse_result.footer_lines.is_empty() {
+        log_warning(format!("Ignored {} footer line(s): {}",
+            parse_result.footer_lines.len(), parse_result.footer_lines.join(" | ")));
+    }
+    for note in &parse_result.diagnostics.notes {
+        log_warning(format!("Data quality: {}", note));
+    }
+    for row in parse_result.ragged_rows.iter().take(MAX_RAGGED_LOGS) {
+        log_warning(format!("Ragged row, {}", row));
+    }
+    if parse_result.ragged_rows.len() > MAX_RAGGED_LOGS {
+        log_warning(format!("... and {} more ragged rows", parse_result.ragged_rows.len() - MAX_RAGGED_LOGS));
+    }
+
+    // Strip excluded columns before anything can reach the AI or the registry
+    if !options.exclude_columns.is_empty() {
+        let removed = parse_result.exclude_columns(&options.exclude_columns);
+        if removed.is_empty() {
+            log_warning("None of the excluded columns were found in the CSV");
+        } else {
+            log_success(format!("🔒 Excluded {} column(s): {}", removed.len(), removed.join(", ")));
+        }
+    }
     
-    let csv_info = CsvInfo {
-        encoding: parse_result.encoding.clone(),
-        delimiter: parse_result.delimiter,
-        headers: parse_result.headers.clone(),
-        row_count: parse_result.records.len(),
-    };
+    let csv_info = CsvInfo::new(&parse_result, sha256);
 
     if parse_result.records.is_empty() {
         return Err(PipelineError::MatrixError("CSV file is empty".to_string()));
@@ -202,29 +615,168 @@ async fn transform_parsed(
     for (i, col) in parse_result.headers.iter().enumerate() {
         log_info(format!("[{:2}] {}", i + 1, col));
     }
+    let names = |columns: Vec<&ColumnProfile>| columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>().join(", ");
+    let empty: Vec<&ColumnProfile> = csv_info.report.empty_columns().collect();
+    if !empty.is_empty() {
+        log_warning(format!("{} empty column(s): {}", empty.len(), names(empty)));
+    }
+    let distinct: Vec<&ColumnProfile> = csv_info.report.high_cardinality_columns().collect();
+    if !distinct.is_empty() {
+        log_info(format!("Columns with a distinct value on nearly every row: {}", names(distinct)));
+    }
 
     // Step 2: Get or generate matrix (with fallback)
     log_info("🔄 Auto-detecting format and transforming...");
-    let (matrix, template_id, transform_result, valid_count, invalid_count, validation_errors) = 
-        get_matrix_with_fallback(&parse_result, &options, source_path).await?;
+    let MatrixSelection { outcome, template_columns, created_template, matrix_engine, ai_estimate } =
+        get_matrix_with_fallback(&parse_result, &csv_info.report, &options, source_path, validator).await?;
+    let (matrix, template_id, transform_result, valid_count, invalid_count, validation_errors, inferred_roles) = outcome;
+    drop(parse_result);
+    if let Some((ref plan, ref inferred)) = inferred_roles {
+        log_role_inference(plan, inferred);
+    }
+    options.emit(EventKind::MatrixSelected, json!({
+        "templateId": template_id,
+        "createdTemplate": created_template,
+        "engine": matrix_engine,
+    }));
+    options.emit(EventKind::TransformDone, json!({
+        "flatRecords": transform_result.records.len(),
+        "skippedRows": transform_result.skipped.len(),
+    }));
+
+    let flat_lines = transform_result.source_lines;
+    let lines_by_work = grouped_lines(&transform_result.records, &flat_lines, &options);
+    let (flat, grouped, flat_spill) = group_and_validate(transform_result.records, &options, validator)?;
+    let roles = role_report(&grouped, options.network);
+    log_role_report(&roles);
+    let mut validation_warnings = check_years(&grouped, &lines_by_work, &options);
+    if let Some((_, inferred)) = inferred_roles {
+        let mut inferred = inferred_role_warnings(&inferred, &grouped);
+        locate_warnings(&mut inferred, &lines_by_work);
+        validation_warnings.extend(inferred);
+    }
+    emit_validation_done(&options, &grouped, valid_count, invalid_count, &validation_warnings);
+
+    Ok(PipelineResult {
+        roles,
+        validation_warnings,
+        skipped: transform_result.skipped,
+        field_stats: transform_result.field_stats,
+        flat,
+        flat_spill,
+        flat_lines,
+        grouped,
+        valid_count,
+        invalid_count,
+        validation_errors,
+        matrix,
+        template_id,
+        template_columns,
+        created_template,
+        matrix_engine,
+        ai_estimate,
+        csv_info,
+        period: options.period.clone(),
+    })
+}
 
+/// `validation.done` event: grouped works and validation counts
+fn emit_validation_done(
+    options: &TransformOptions,
+    grouped: &[Value],
+    valid_count: usize,
+    invalid_count: usize,
+    warnings: &[ValidationWarning],
+) {
+    options.emit(EventKind::ValidationDone, json!({
+        "works": grouped.len(),
+        "valid": valid_count,
+        "invalid": invalid_count,
+        "warnings": warnings.len(),
+    }));
+}
+
+/// Group through the on-disk external sort, publishing each work to `live` as soon as it is built
+fn stream_grouped(
+    records: impl IntoIterator<Item = std::io::Result<Value>>,
+    dir: &Path,
+    live: Option<&LiveWorks>,
+) -> std::io::Result<Vec<Value>> {
+    flat_to_grouped_streaming(records, dir, DEFAULT_RUN_SIZE)?
+        .map(|work| {
+            let work = work?.to_value();
+            if let Some(live) = live {
+                live.push(work.clone());
+            }
+            Ok(work)
+        })
+        .collect()
+}
+
+/// Source lines of each grouped work, in the order [`group_and_validate`] builds the works
+fn grouped_lines(records: &[Value], flat_lines: &[usize], options: &TransformOptions) -> Vec<Vec<usize>> {
+    if flat_lines.is_empty() {
+        return Vec::new();
+    }
+    // The streaming grouper (spill or live download) yields works sorted by key
+    work_lines(records, flat_lines, options.spill_dir.is_some() || options.live.is_some())
+}
+
+/// Attach the source lines of the work each warning is about
+fn locate_warnings(warnings: &mut [ValidationWarning], lines_by_work: &[Vec<usize>]) {
+    for warning in warnings {
+        if let Some(lines) = lines_by_work.get(warning.record_index) {
+            warning.lines = lines.clone();
+        }
+    }
+}
+
+/// Group flat records (step 5) and validate the grouped works (step 6).
+///
+/// The flat records are returned empty when spilled to disk.
+fn group_and_validate(
+    mut records: Vec<Value>,
+    options: &TransformOptions,
+    validator: &ValidationContext,
+) -> Result<GroupOutcome, PipelineError> {
     // Step 5: Group by ISWC
+    set_log_stage(LogStage::Group);
     log_info("📦 Grouping by ISWC...");
-    let grouped = flat_to_grouped(transform_result.records.clone());
+    let progress = StageProgress::start(LogStage::Group, records.len() as u64);
+    let (grouped, flat_spill) = match options.spill_dir {
+        Some(ref dir) => {
+            let spill = SpillFile::create(dir, std::mem::take(&mut records))?;
+            log_info(format!("💾 Spilled {} flat records to {}", spill.len(), spill.path().display()));
+            let dir = spill.path().parent().unwrap_or(Path::new(".")).to_path_buf();
+            (stream_grouped(spill.records()?, &dir, options.live.as_deref())?, Some(Arc::new(spill)))
+        }
+        // A live download follows the job: emit works as the streaming grouper builds them
+        None if options.live.is_some() => {
+            let records = records.clone().into_iter().map(Ok);
+            let dir = options.temp_dir.as_ref().map(PathBuf::from).unwrap_or_else(std::env::temp_dir);
+            (stream_grouped(records, &dir, options.live.as_deref())?, None)
+        }
+        None => (to_values(&flat_to_grouped(records.clone())), None),
+    };
+    drop(progress);
     log_success(format!("{} musical works", grouped.len()));
 
     // Step 6: Validate grouped format against schema (before sending to blockchain)
     if !options.skip_validation {
+        set_log_stage(LogStage::Validate);
         log_info("✔️  Validating grouped MIDDS format...");
         let mut grouped_errors = 0;
+        let mut progress = StageProgress::start(LogStage::Validate, grouped.len() as u64);
         for (i, work) in grouped.iter().enumerate() {
-            if let Err(errs) = validate_musical_work_grouped(work) {
+            if let Err(errs) = validator.validate_grouped(work) {
                 grouped_errors += 1;
                 if grouped_errors <= 3 {
                     log_error(format!("Work {}: {}", i, errs.join(", ")));
                 }
             }
+            progress.set(i as u64 + 1);
         }
+        drop(progress);
         if grouped_errors > 0 {
             log_warning(format!("{} works failed grouped validation", grouped_errors));
         } else {
@@ -232,15 +784,66 @@ async fn transform_parsed(
         }
     }
 
+    Ok((records, grouped, flat_spill))
+}
+
+/// Group and validate records that are already flat MIDDS (JSON or CWR input, no matrix)
+fn group_flat(
+    parse_result: ParseResult,
+    options: TransformOptions,
+    sha256: Option<String>,
+) -> Result<PipelineResult, PipelineError> {
+    let validator = ValidationContext::shared()?;
+    register_pii_names(parse_result.records.iter().filter_map(|r| r.get("creatorName")?.as_str()));
+    set_log_stage(LogStage::Parse);
+    log_success(format!("Read {} flat MIDDS records, matrix step skipped", parse_result.records.len()));
+    let csv_info = CsvInfo::new(&parse_result, sha256);
+
+    if parse_result.records.is_empty() {
+        return Err(PipelineError::MatrixError("Input has no flat records".to_string()));
+    }
+
+    let (valid_count, invalid_count, validation_errors) = if options.skip_validation {
+        (parse_result.records.len(), 0, Vec::new())
+    } else {
+        set_log_stage(LogStage::Validate);
+        log_info("✔️  Validating flat records...");
+        validate_records(validator, &parse_result.records)
+    };
+
+    options.emit(EventKind::TransformDone, json!({
+        "flatRecords": parse_result.records.len(),
+        "skippedRows": 0,
+    }));
+
+    let flat_lines = parse_result.lines;
+    let lines_by_work = grouped_lines(&parse_result.records, &flat_lines, &options);
+    let (flat, grouped, flat_spill) = group_and_validate(parse_result.records, &options, validator)?;
+    let roles = role_report(&grouped, options.network);
+    log_role_report(&roles);
+    let validation_warnings = check_years(&grouped, &lines_by_work, &options);
+    emit_validation_done(&options, &grouped, valid_count, invalid_count, &validation_warnings);
+
     Ok(PipelineResult {
-        flat: transform_result.records,
+        roles,
+        validation_warnings,
+        skipped: Vec::new(),
+        field_stats: BTreeMap::new(),
+        flat,
+        flat_spill,
+        flat_lines,
         grouped,
         valid_count,
         invalid_count,
         validation_errors,
-        matrix,
-        template_id,
+        matrix: TransformationMatrix::new(),
+        template_id: None,
+        template_columns: None,
+        created_template: None,
+        matrix_engine: None,
+        ai_estimate: None,
         csv_info,
+        period: options.period.clone(),
     })
 }
 
@@ -251,22 +854,58 @@ fn format_delimiter(d: char) -> &'static str {
         ',' => ",",
         '\t' => "TAB",
         '|' => "|",
+        ' ' => "fixed-width",
+        JSON_DELIMITER => "none (JSON)",
         _ => "?",
     }
 }
 
+/// Outcome of grouping: (flat records, grouped works, spill file)
+type GroupOutcome = (Vec<Value>, Vec<Value>, Option<Arc<SpillFile>>);
+
+/// Outcome of trying a matrix: (matrix, template id, transform result, valid, invalid, validation errors,
+/// roles inferred when the matrix reads no role column)
+type MatrixOutcome = (
+    TransformationMatrix, Option<String>, super::dsl::TransformResult, usize, usize, Vec<(usize, Vec<String>)>,
+    Option<(RolePlan, Vec<InferredRole>)>,
+);
+
+/// Matrix kept by [`get_matrix_with_fallback`] and how it was obtained
+struct MatrixSelection {
+    outcome: MatrixOutcome,
+    /// Column coverage, when a cached template was kept
+    template_columns: Option<ColumnCoverage>,
+    /// Template name, when the generated matrix was saved
+    created_template: Option<String>,
+    /// Engine that generated the matrix
+    matrix_engine: Option<EngineReport>,
+    /// Estimated cost of the AI request
+    ai_estimate: Option<AiCostEstimate>,
+}
+
+impl MatrixSelection {
+    fn reused(outcome: MatrixOutcome, template_columns: Option<ColumnCoverage>) -> Self {
+        Self { outcome, template_columns, created_template: None, matrix_engine: None, ai_estimate: None }
+    }
+}
+
 /// Get matrix and execute transformation with fallback to AI if all cached templates fail
 /// 
-/// Algorithm (like massdrop's SmartTransformer):
+/// Algorithm:
 /// 1. Find ALL compatible cached templates
-/// 2. Try each one (sorted by success rate)
-/// 3. Stop at first one that produces valid results
-/// 4. If ALL fail → fallback to AI
+/// 2. Run each one and score it (valid ratio, then fewer skipped rows)
+/// 3. Keep the best one if its valid ratio reaches `min_valid_ratio`
+/// 4. Otherwise → fallback to AI
+///
+/// The AI step fails over to a secondary provider or the heuristic
+/// generator when the primary provider is down (see [`generate_with_failover`]).
 async fn get_matrix_with_fallback(
     parse_result: &ParseResult,
+    report: &ParseReport,
     options: &TransformOptions,
     source_path: Option<&Path>,
-) -> Result<(TransformationMatrix, Option<String>, super::dsl::TransformResult, usize, usize, Vec<(usize, Vec<String>)>), PipelineError> {
+    validator: &ValidationContext,
+) -> Result<MatrixSelection, PipelineError> {
     
     // Option 1: Use provided matrix file (no fallback)
     if let Some(ref matrix_path) = options.matrix_path {
@@ -274,79 +913,237 @@ async fn get_matrix_with_fallback(
         let content = std::fs::read_to_string(matrix_path)?;
         let matrix = TransformationMatrix::from_json(&content)
             .map_err(|e| PipelineError::MatrixError(e.to_string()))?;
-        return try_matrix(parse_result, matrix, None, options);
+        return Ok(MatrixSelection::reused(try_matrix(parse_result, matrix, None, options, validator)?, None));
     }
 
-    // Option 2: Try ALL compatible cached templates (sorted by success rate)
+    // Option 2: Score ALL compatible cached templates and keep the best one
     if !options.no_cache {
+        set_log_stage(LogStage::Template);
         log_info("Looking for compatible cached templates...");
-        let registry = MatrixRegistry::new();
-        let compatible = registry.find_compatible(&parse_result.headers);
+        let mut registry = options.registry();
+        let compatible: Vec<_> = registry.find_compatible(&parse_result.headers)
+            .into_iter()
+            .map(|(template, score)| (template.clone(), score))
+            .collect();
         
         if compatible.is_empty() {
             log_warning("No compatible templates found");
         } else {
             log_success(format!("Found {} compatible template(s)", compatible.len()));
             
-            // Try each template until one works
-            for (i, (template, score)) in compatible.iter().enumerate() {
-                log_info(format!("→ Trying template {}/{}: {} (score: {:.0}%, success rate: {:.0}%)", 
-                    i + 1, compatible.len(), template.name, score * 100.0, template.success_rate * 100.0));
-                
-                let result = try_matrix(parse_result, template.matrix.clone(), Some(template.id.clone()), options);
-                
-                if let Ok((ref _m, ref _tid, ref tr, valid, _invalid, ref _errs)) = result {
-                    // Update stats
-                    let mut registry_mut = MatrixRegistry::new();
-                    let success = valid > 0;
-                    registry_mut.update_stats(&template.id, success);
-                    
-                    if success {
-                        log_success(format!("✅ Template \"{}\" worked!", template.name));
-                        return result;
-                    } else {
-                        log_warning(format!("Template \"{}\" failed ({} records, 0 valid)", template.name, tr.records.len()));
-                    }
+            let mut best: Option<(MatrixScore, MatrixOutcome, String)> = None;
+            for (i, (template, compatibility)) in compatible.iter().enumerate() {
+                let outcome = run_matrix(parse_result, template.matrix.clone(), Some(template.id.clone()), options, validator);
+                let score = MatrixScore::new(parse_result.records.len(), &outcome.2, outcome.3);
+                log_info(format!("→ Template {}/{}: {} (columns: {:.0}%, valid: {:.0}%, skipped rows: {:.0}%)",
+                    i + 1, compatible.len(), template.name, compatibility * 100.0,
+                    score.valid_ratio * 100.0, score.skipped_ratio * 100.0));
+
+                registry.update_stats(&template.id, score.valid_ratio >= options.min_valid_ratio, options.period.as_ref());
+
+                if best.as_ref().is_none_or(|(b, _, _)| score.cmp_quality(b).is_gt()) {
+                    best = Some((score, outcome, template.name.clone()));
                 }
             }
-            
-            log_warning(format!("All {} cached templates failed", compatible.len()));
+
+            if let Some((score, outcome, name)) = best {
+                if score.valid_ratio >= options.min_valid_ratio {
+                    log_success(format!("✅ Using template \"{}\" ({:.0}% valid)", name, score.valid_ratio * 100.0));
+                    let coverage = column_coverage(&outcome.0, &parse_result.headers);
+                    log_column_coverage(&coverage);
+                    log_outcome(&outcome, options, validator);
+                    return Ok(MatrixSelection::reused(outcome, Some(coverage)));
+                }
+                log_warning(format!("Best template \"{}\" only reaches {:.0}% valid (minimum {:.0}%)",
+                    name, score.valid_ratio * 100.0, options.min_valid_ratio * 100.0));
+            }
         }
     }
 
     // Option 3: Fallback to AI
+    set_log_stage(LogStage::Ai);
+    if options.no_ai {
+        log_info("🧭 AI disabled: generating matrix from column names...");
+        let matrix = heuristic_matrix(&parse_result.headers);
+        log_info(format!("Fields mapped: {}", matrix.transforms.len()));
+        let outcome = try_matrix(parse_result, matrix, None, options, validator)?;
+        let engine = EngineReport { engine: Engine::Heuristic, model: None, failover_reason: None };
+        return Ok(MatrixSelection { matrix_engine: Some(engine), ..MatrixSelection::reused(outcome, None) });
+    }
     log_info("🤖 Fallback: Generating new matrix with AI...");
-    log_info("Using Claude API...");
-    let client = AiClient::from_env()?;
-    let preview_count = options.preview_rows.min(parse_result.records.len());
-    let preview = &parse_result.records[..preview_count];
-    log_info(format!("Sending {} preview rows + unique values from {} total rows to AI...", preview_count, parse_result.records.len()));
-    let matrix = client.generate_matrix_full(preview, &parse_result.records).await?;
-    log_success("AI matrix generated successfully");
-    log_info(format!("Fields mapped: {}", matrix.transforms.len()));
-    
+    let attempts = options.ai_attempts.max(1);
+    let target_ratio = options.ai_target_ratio.unwrap_or(options.min_valid_ratio);
+    let file_notes: Vec<String> = duplicate_columns_note(&parse_result.renamed_columns).into_iter()
+        .chain(column_stats_note(report))
+        .collect();
+    let preview_rows = options.preview_rows.min(MAX_PREVIEW_ROWS);
+    let preview = stratified_sample(&parse_result.records, preview_rows);
+    if options.structure_only {
+        log_warning("🔒 Structure-only mode: no cell values sent to AI, value mappings (roles, languages) may be less accurate");
+        log_info(format!("Sending structure of {} columns ({} rows summarized) to AI...", parse_result.headers.len(), parse_result.records.len()));
+    } else {
+        if options.preview_rows > MAX_PREVIEW_ROWS {
+            log_warning(format!("Preview rows capped at {} (requested {})", MAX_PREVIEW_ROWS, options.preview_rows));
+        }
+        log_info(format!("Sending {} sampled preview rows + unique values from {} total rows to AI...", preview.len(), parse_result.records.len()));
+    }
+
+    // Size and cost of the request, before anything is sent
+    let estimate = if options.structure_only {
+        AiCostEstimate::structure_only(&parse_result.headers, &parse_result.records, &file_notes)?
+    } else {
+        AiCostEstimate::full(&preview, &parse_result.records, &file_notes)?
+    };
+    log_info(format!("💰 Estimated AI request: ~{} input tokens ({} chars), up to {} output tokens, up to ${:.4} per attempt",
+        estimate.input_tokens, estimate.prompt_chars, estimate.max_output_tokens, estimate.cost_usd));
+    if attempts > 1 {
+        log_info(format!("🔁 Up to {} AI attempts (up to ${:.4}), stopping at {:.0}% valid",
+            attempts, estimate.cost_usd * attempts as f64, target_ratio * 100.0));
+    }
+    if let Some(limit) = options.ai_cost_limit {
+        // Every attempt may be needed: the worst case is what must be confirmed
+        if estimate.cost_usd * attempts as f64 > limit {
+            log_warning(format!("Estimated AI cost exceeds the ${:.4} threshold: confirmation required", limit));
+            return Err(PipelineError::AiCostNotConfirmed(estimate));
+        }
+    }
+
+    let mut best: Option<(MatrixScore, MatrixOutcome, EngineReport)> = None;
+    for attempt in 0..attempts {
+        let preview = if attempt == 0 { preview.clone() } else { varied_sample(&parse_result.records, preview_rows, attempt) };
+        if attempts > 1 {
+            set_log_stage(LogStage::Ai);
+            log_info(format!("🤖 AI attempt {}/{}", attempt + 1, attempts));
+        }
+        let generated = generate_with_failover(&parse_result.headers, |client| {
+            let client = client.with_file_notes(file_notes.clone());
+            let preview = &preview;
+            async move {
+                log_info(format!("Using Claude API (model {})...", client.model()));
+                if options.structure_only {
+                    client.generate_matrix_structure_only(&parse_result.headers, &parse_result.records).await
+                } else {
+                    client.generate_matrix_full(preview, &parse_result.records).await
+                }
+            }
+        }).await;
+        let (matrix, engine) = match generated {
+            Ok(generated) => generated,
+            // A later attempt failing keeps the best matrix so far
+            Err(e) if best.is_some() => {
+                log_warning(format!("AI attempt {} failed, keeping the best matrix so far: {}", attempt + 1, e));
+                break;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        log_engine(&engine);
+        log_info(format!("Fields mapped: {}", matrix.transforms.len()));
+
+        let outcome = run_matrix(parse_result, matrix, None, options, validator);
+        let score = MatrixScore::new(parse_result.records.len(), &outcome.2, outcome.3);
+        if attempts > 1 {
+            log_info(format!("→ Attempt {}: {:.0}% valid, skipped rows: {:.0}%",
+                attempt + 1, score.valid_ratio * 100.0, score.skipped_ratio * 100.0));
+        }
+        // The heuristic generator gives the same matrix every time
+        let done = score.valid_ratio >= target_ratio || engine.engine == Engine::Heuristic;
+        if best.as_ref().is_none_or(|(b, _, _)| score.cmp_quality(b).is_gt()) {
+            best = Some((score, outcome, engine));
+        }
+        if done {
+            break;
+        }
+    }
+    let Some((score, mut outcome, engine)) = best else {
+        return Err(PipelineError::MatrixError("No AI attempt was made".to_string()));
+    };
+    if attempts > 1 && score.valid_ratio < target_ratio {
+        log_warning(format!("No AI attempt reached {:.0}% valid, keeping the best one ({:.0}%)",
+            target_ratio * 100.0, score.valid_ratio * 100.0));
+    }
+
     // Save AI matrix to cache
-    let template_id = if !options.no_save {
-        let mut registry = MatrixRegistry::new();
-        let name = source_path
-            .and_then(|p| p.file_stem())
-            .and_then(|s| s.to_str())
-            .unwrap_or("auto-generated");
-        registry.save(matrix.clone(), name, parse_result.headers.clone()).ok()
+    let name = options.template_name.as_deref()
+        .or_else(|| source_path.and_then(|p| p.file_stem()).and_then(|s| s.to_str()))
+        .unwrap_or("auto-generated")
+        .to_string();
+    // A matrix guessed from column names is not worth reusing
+    let template_id = if !options.no_save && engine.engine != Engine::Heuristic {
+        let mut matrix = outcome.0.clone();
+        // Headerless fixed-width files need their layout again next time
+        if let Some(ref columns) = options.fixed_width {
+            matrix.source_format.get_or_insert_with(SourceFormat::default).fixed_width = Some(columns.clone());
+        }
+        let mut registry = options.registry();
+        registry.save(matrix, &name, parse_result.headers.clone()).ok()
     } else {
         None
     };
-    
-    let result = try_matrix(parse_result, matrix, template_id.clone(), options);
-    
+    outcome.1 = template_id.clone();
+    log_outcome(&outcome, options, validator);
+
     // Update AI template stats
-    if let (Some(ref tid), Ok((_, _, _, valid, _, _))) = (&template_id, &result) {
-        let mut registry = MatrixRegistry::new();
-        registry.update_stats(tid, *valid > 0);
+    if let Some(ref tid) = template_id {
+        let mut registry = options.registry();
+        registry.update_stats(tid, score.valid_ratio >= options.min_valid_ratio, options.period.as_ref());
+        set_log_stage(LogStage::Template);
         log_success(format!("→ Saved as: {}", tid));
     }
-    
-    result
+
+    let created_template = template_id.is_some().then_some(name);
+    Ok(MatrixSelection {
+        outcome,
+        template_columns: None,
+        created_template,
+        matrix_engine: Some(engine),
+        ai_estimate: Some(estimate),
+    })
+}
+
+/// Log which engine generated a matrix
+fn log_engine(engine: &EngineReport) {
+    if let Some(ref reason) = engine.failover_reason {
+        log_warning(format!("⚠️  AI failover: {}", reason));
+    }
+    match engine.engine {
+        Engine::Heuristic => {
+            log_warning("🧭 No AI provider available: matrix guessed from column names, please review the mapping");
+            log_success("Matrix generated from column names");
+        }
+        Engine::Secondary => {
+            log_warning(format!("↪️  Matrix generated by the secondary provider ({})", engine.model.as_deref().unwrap_or("?")));
+            log_success("AI matrix generated successfully");
+        }
+        Engine::Primary => log_success("AI matrix generated successfully"),
+    }
+}
+
+/// Log the mapping, transform and validation results of a matrix already run
+fn log_outcome(outcome: &MatrixOutcome, options: &TransformOptions, validator: &ValidationContext) {
+    set_log_stage(LogStage::Transform);
+    print_matrix_mapping(&outcome.0);
+    print_transform_result(&outcome.2);
+    if options.verbose {
+        log_row_details(&outcome.2, options.skip_validation, validator);
+    }
+    if !options.skip_validation {
+        set_log_stage(LogStage::Validate);
+        print_validation_result(&(outcome.3, outcome.4, outcome.5.clone()));
+    }
+}
+
+/// Log which columns a template reads, lacks and ignores
+fn log_column_coverage(coverage: &ColumnCoverage) {
+    log_info(format!("🧩 Columns matched ({}): {}", coverage.matched.len(), coverage.matched.join(", ")));
+    if !coverage.missing.is_empty() {
+        log_warning(format!("Template columns missing from the CSV ({}): {}", coverage.missing.len(), coverage.missing.join(", ")));
+    }
+    if !coverage.unused.is_empty() {
+        log_info(format!("CSV columns not used by the template ({}): {}", coverage.unused.len(), coverage.unused.join(", ")));
+    }
+    if coverage.used_ratio() < 0.5 {
+        log_warning(format!("Only {:.0}% of the CSV columns are used by this template", coverage.used_ratio() * 100.0));
+    }
 }
 
 /// Try a matrix and return results
@@ -355,24 +1152,68 @@ fn try_matrix(
     matrix: TransformationMatrix,
     template_id: Option<String>,
     options: &TransformOptions,
-) -> Result<(TransformationMatrix, Option<String>, super::dsl::TransformResult, usize, usize, Vec<(usize, Vec<String>)>), PipelineError> {
+    validator: &ValidationContext,
+) -> Result<MatrixOutcome, PipelineError> {
+    set_log_stage(LogStage::Transform);
     print_matrix_mapping(&matrix);
     
     log_info("⚙️  Executing transformation...");
-    let transform_result = execute(&parse_result.records, &matrix);
-    print_transform_result(&transform_result);
+    let outcome = run_matrix(parse_result, matrix, template_id, options, validator);
+    print_transform_result(&outcome.2);
+    if options.verbose {
+        log_row_details(&outcome.2, options.skip_validation, validator);
+    }
     
+    set_log_stage(LogStage::Validate);
     log_info("✔️  Validating records...");
-    let (valid_count, invalid_count, validation_errors) = if options.skip_validation {
+    if options.skip_validation {
         log_info("(validation skipped)");
+    } else {
+        print_validation_result(&(outcome.3, outcome.4, outcome.5.clone()));
+    }
+    
+    Ok(outcome)
+}
+
+/// Execute and validate a matrix without logging
+fn run_matrix(
+    parse_result: &ParseResult,
+    matrix: TransformationMatrix,
+    template_id: Option<String>,
+    options: &TransformOptions,
+    validator: &ValidationContext,
+) -> MatrixOutcome {
+    // The stored matrix stays as is: inference only changes what is executed
+    let plan = options.role_inference.plan(&matrix, &parse_result.headers);
+    let executed = plan.as_ref().map(|plan| apply_plan(&matrix, plan));
+    let mut progress = StageProgress::start(LogStage::Transform, parse_result.records.len() as u64);
+    let mut transform_result = execute_with_progress(&parse_result.records, executed.as_ref().unwrap_or(&matrix),
+        |done| progress.set(done as u64));
+    drop(progress);
+    transform_result.locate(&parse_result.lines);
+    let inferred_roles = plan.map(|plan| (plan, options.role_inference.fill_roles(&mut transform_result.records)));
+    register_creator_names(parse_result, &matrix, &transform_result);
+    let (valid_count, invalid_count, validation_errors) = if options.skip_validation {
         (transform_result.records.len(), 0, vec![])
     } else {
-        let result = validate_records(&transform_result.records);
-        print_validation_result(&result);
-        result
+        validate_records(validator, &transform_result.records)
     };
-    
-    Ok((matrix, template_id, transform_result, valid_count, invalid_count, validation_errors))
+    (matrix, template_id, transform_result, valid_count, invalid_count, validation_errors, inferred_roles)
+}
+
+/// Creator names of a run, source and transformed, masked in the logs with `MASSLOAD_MASK_PII`
+fn register_creator_names(parse_result: &ParseResult, matrix: &TransformationMatrix, result: &super::dsl::TransformResult) {
+    if !LOG_BROADCASTER.masks_pii() {
+        return;
+    }
+    let source = matrix.transforms.get("creatorName").and_then(|t| t.source.as_deref());
+    let raw = source.into_iter().flat_map(|column| {
+        parse_result.records.iter().filter_map(move |r| r.get(column)?.as_str())
+    });
+    let transformed = result.records.iter()
+        .filter_map(|r| r.get("creatorName")?.as_str())
+        .chain(result.skipped.iter().filter_map(|s| s.partial.get("creatorName")?.as_str()));
+    register_pii_names(raw.chain(transformed));
 }
 
 /// Print matrix mapping
@@ -389,6 +1230,35 @@ fn print_matrix_mapping(matrix: &TransformationMatrix) {
     }
 }
 
+/// Maximum number of per-row debug lines per job
+const MAX_DEBUG_ROWS: usize = 500;
+
+/// Verbose mode: one debug line per skipped row, transform error and invalid record
+fn log_row_details(result: &super::dsl::TransformResult, skip_validation: bool, validator: &ValidationContext) {
+    let skipped = result.skipped.iter().map(|skip| {
+        format!("Row {} ({}): skipped ({}), missing: {}", skip.row, skip.location(), skip.reason, skip.missing_fields.join(", "))
+    });
+    let errors = result.errors.iter().map(|err| {
+        format!("Row {}: {} → {}", err.row, err.field, err.message)
+    });
+    let invalid = result.records.iter().enumerate()
+        .filter(|_| !skip_validation)
+        .filter_map(|(i, record)| {
+            validator.validate_flat(record).err()
+                .map(|errs| format!("Record {}{}: invalid, {}", i,
+                    cite_lines(result.source_lines.get(i..=i).unwrap_or_default()), errs.join(", ")))
+        });
+
+    let mut lines = skipped.chain(errors).chain(invalid);
+    for line in lines.by_ref().take(MAX_DEBUG_ROWS) {
+        log_debug(line);
+    }
+    let remaining = lines.count();
+    if remaining > 0 {
+        log_debug(format!("… {} more row detail(s) not shown", remaining));
+    }
+}
+
 /// Print transformation result details
 fn print_transform_result(result: &super::dsl::TransformResult) {
     log_success(format!("Generated {} flat records", result.records.len()));
@@ -403,22 +1273,83 @@ fn print_transform_result(result: &super::dsl::TransformResult) {
         log_warning(format!("{} rows skipped (missing required fields)", result.skipped.len()));
         
         // Group by reason
-        let mut reasons: std::collections::HashMap<String, Vec<usize>> = std::collections::HashMap::new();
+        let mut reasons: std::collections::HashMap<String, Vec<String>> = std::collections::HashMap::new();
         for skip in &result.skipped {
             let key = if skip.missing_fields.is_empty() {
                 skip.reason.clone()
             } else {
                 format!("Missing: {}", skip.missing_fields.join(", "))
             };
-            reasons.entry(key).or_default().push(skip.row);
+            reasons.entry(key).or_default().push(skip.location());
         }
         
         for (reason, rows) in reasons.iter().take(5) {
-            let row_sample: Vec<String> = rows.iter().take(5).map(|r| r.to_string()).collect();
             let more = if rows.len() > 5 { format!("... +{}", rows.len() - 5) } else { String::new() };
-            log_warning(format!("• {} (rows: {}{})", reason, row_sample.join(", "), more));
+            log_warning(format!("• {} ({}{})", reason, rows[..rows.len().min(5)].join(", "), more));
         }
     }
+    for (field, stats) in result.failing_fields() {
+        let operations: Vec<String> = stats.by_operation.iter()
+            .map(|(op, count)| format!("{} ×{}", op, count))
+            .collect();
+        log_warning(format!("{}: {}/{} values failed to transform ({:.0}%, {}), kept as is or dropped",
+            field, stats.failures, stats.values, stats.failure_rate() * 100.0, operations.join(", ")));
+    }
+}
+
+/// Print creators per role and warn about works without composer
+fn log_role_report(report: &RoleReport) {
+    let roles: Vec<String> = report.roles.iter().map(|(role, count)| format!("{} {}", count, role)).collect();
+    log_info(format!("👥 Creators by role: {}", roles.join(", ")));
+    if report.works_without_composer > 0 {
+        let sample: Vec<&str> = report.without_composer.iter().take(3).map(|w| w.title.as_str()).collect();
+        log_warning(format!("{} work(s) without composer (e.g. {})", report.works_without_composer, sample.join(", ")));
+    }
+    for unsupported in &report.unsupported {
+        let advice = match &unsupported.suggestion {
+            Some(role) => format!("use {} instead", role),
+            None => "no substitute".to_string(),
+        };
+        log_warning(format!("{} creator(s) with role {}, not accepted on {:?} ({})",
+            unsupported.creators, unsupported.role, report.network, advice));
+    }
+}
+
+/// Log how roles were inferred for a catalog without role column
+fn log_role_inference(plan: &RolePlan, inferred: &[InferredRole]) {
+    if let RolePlan::Columns(groups) = plan {
+        let columns: Vec<String> = groups.iter()
+            .filter_map(|group| Some(format!("{} → {:?}", group.name.as_ref().or(group.ipi.as_ref())?, group.role)))
+            .collect();
+        log_warning(format!("🧭 No role column: roles read from column names ({})", columns.join(", ")));
+    }
+    if !inferred.is_empty() {
+        log_warning(format!("🧭 {} creator role(s) guessed from names and shares (low confidence), please review",
+            inferred.len()));
+    }
+}
+
+/// Check creation years of grouped works (unless validation is skipped) and log a summary
+fn check_years(grouped: &[Value], lines_by_work: &[Vec<usize>], options: &TransformOptions) -> Vec<ValidationWarning> {
+    if options.skip_validation {
+        return Vec::new();
+    }
+    let mut warnings = check_creation_years(grouped, &options.year_bounds);
+    locate_warnings(&mut warnings, lines_by_work);
+    if let Some(first) = warnings.first() {
+        log_warning(format!("{} work(s) with an implausible creation year (e.g. work {}{}: {})",
+            warnings.len(), first.record_index, cite_lines(&first.lines), first.message));
+    }
+    warnings
+}
+
+/// `, line 4` / `, lines 4, 5` for a log message (empty when the lines are unknown)
+fn cite_lines(lines: &[usize]) -> String {
+    match lines {
+        [] => String::new(),
+        [line] => format!(", line {}", line),
+        lines => format!(", lines {}", lines.iter().map(|l| l.to_string()).collect::<Vec<_>>().join(", ")),
+    }
 }
 
 /// Print validation result
@@ -432,13 +1363,14 @@ fn print_validation_result(result: &(usize, usize, Vec<(usize, Vec<String>)>)) {
 }
 
 /// Validate records and return statistics
-fn validate_records(records: &[Value]) -> (usize, usize, Vec<(usize, Vec<String>)>) {
+fn validate_records(validator: &ValidationContext, records: &[Value]) -> (usize, usize, Vec<(usize, Vec<String>)>) {
     let mut valid = 0;
     let mut invalid = 0;
     let mut errors = Vec::new();
+    let mut progress = StageProgress::start(LogStage::Validate, records.len() as u64);
 
     for (i, record) in records.iter().enumerate() {
-        match validate_musical_work_flat(record) {
+        match validator.validate_flat(record) {
             Ok(()) => valid += 1,
             Err(errs) => {
                 invalid += 1;
@@ -447,26 +1379,26 @@ fn validate_records(records: &[Value]) -> (usize, usize, Vec<(usize, Vec<String>
                 }
             }
         }
+        progress.set(i as u64 + 1);
     }
 
     (valid, invalid, errors)
 }
 
-/// Transform with a specific matrix (no AI, no cache)
+/// Transform with a specific matrix (no AI, no cache), validating when a context is given
 pub fn transform_with_matrix(
     records: &[Value],
     matrix: &TransformationMatrix,
-    validate: bool,
+    validator: Option<&ValidationContext>,
 ) -> TransformWithMatrixResult {
     let result = execute(records, matrix);
 
-    let (valid_count, invalid_count, validation_errors) = if validate {
-        validate_records(&result.records)
-    } else {
-        (result.records.len(), 0, vec![])
+    let (valid_count, invalid_count, validation_errors) = match validator {
+        Some(validator) => validate_records(validator, &result.records),
+        None => (result.records.len(), 0, vec![]),
     };
 
-    let grouped = flat_to_grouped(result.records.clone());
+    let grouped = to_values(&flat_to_grouped(result.records.clone()));
 
     TransformWithMatrixResult {
         flat: result.records,
@@ -491,9 +1423,215 @@ pub struct TransformWithMatrixResult {
     pub errors: usize,
 }
 
+/// Default number of rows sampled when comparing templates
+pub const DEFAULT_EVALUATION_ROWS: usize = 200;
+
+/// How well a matrix performed on a set of rows
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatrixScore {
+    /// Number of CSV rows transformed
+    pub rows: usize,
+    /// Valid flat records produced
+    pub valid: usize,
+    /// Distinct rows skipped for missing required fields
+    pub skipped_rows: usize,
+    /// Valid records / (records + skipped entries)
+    pub valid_ratio: f64,
+    /// Skipped rows / transformed rows
+    pub skipped_ratio: f64,
+}
+
+impl MatrixScore {
+    /// Score a transformation result given its number of valid records
+    pub fn new(rows: usize, result: &super::dsl::TransformResult, valid: usize) -> Self {
+        let attempts = result.records.len() + result.skipped.len();
+        let mut skipped: Vec<usize> = result.skipped.iter().map(|s| s.row).collect();
+        skipped.sort_unstable();
+        skipped.dedup();
+
+        Self {
+            rows,
+            valid,
+            skipped_rows: skipped.len(),
+            valid_ratio: if attempts > 0 { valid as f64 / attempts as f64 } else { 0.0 },
+            skipped_ratio: if rows > 0 { skipped.len() as f64 / rows as f64 } else { 0.0 },
+        }
+    }
+
+    /// Ordering used to rank matrices: higher valid ratio, then fewer skipped rows
+    pub fn cmp_quality(&self, other: &Self) -> std::cmp::Ordering {
+        self.valid_ratio.partial_cmp(&other.valid_ratio)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| other.skipped_ratio.partial_cmp(&self.skipped_ratio).unwrap_or(std::cmp::Ordering::Equal))
+    }
+}
+
+/// Trial report of one cached template on a sample of rows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateEvaluation {
+    pub template_id: String,
+    pub name: String,
+    /// Share of the template's columns found in the CSV (0.0 to 1.0)
+    pub compatibility: f64,
+    /// Historical success rate of the template
+    pub success_rate: f64,
+    #[serde(flatten)]
+    pub score: MatrixScore,
+    /// Target fields whose source column is missing from the CSV
+    pub unmapped_fields: Vec<String>,
+    /// Matched, missing and unused columns
+    pub columns: ColumnCoverage,
+}
+
+/// Run every compatible template of the registry on the first `sample_rows` rows.
+///
+/// Nothing is logged and template stats are left untouched. Results are sorted
+/// best first (valid ratio, then fewer skipped rows, then compatibility).
+pub fn evaluate_templates(
+    registry: &MatrixRegistry,
+    parse_result: &ParseResult,
+    sample_rows: usize,
+    validator: &ValidationContext,
+) -> Vec<TemplateEvaluation> {
+    let sample = &parse_result.records[..sample_rows.min(parse_result.records.len())];
+
+    let mut evaluations: Vec<TemplateEvaluation> = registry
+        .find_compatible(&parse_result.headers)
+        .into_iter()
+        .map(|(template, compatibility)| {
+            let result = execute(sample, &template.matrix);
+            let (valid, _, _) = validate_records(validator, &result.records);
+            TemplateEvaluation {
+                template_id: template.id.clone(),
+                name: template.name.clone(),
+                compatibility,
+                success_rate: template.success_rate,
+                score: MatrixScore::new(sample.len(), &result, valid),
+                unmapped_fields: unmapped_fields(&template.matrix, &parse_result.headers),
+                columns: column_coverage(&template.matrix, &parse_result.headers),
+            }
+        })
+        .collect();
+
+    evaluations.sort_by(|a, b| {
+        b.score.cmp_quality(&a.score)
+            .then_with(|| b.compatibility.partial_cmp(&a.compatibility).unwrap_or(std::cmp::Ordering::Equal))
+    });
+    evaluations
+}
+
+/// How the columns of a CSV line up with the source columns of a matrix
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnCoverage {
+    /// Matrix columns found in the CSV
+    pub matched: Vec<String>,
+    /// Matrix columns absent from the CSV (their fields stay empty)
+    pub missing: Vec<String>,
+    /// CSV columns the matrix never reads
+    pub unused: Vec<String>,
+}
+
+impl ColumnCoverage {
+    /// Share of the CSV columns read by the matrix (0.0 to 1.0)
+    pub fn used_ratio(&self) -> f64 {
+        let total = self.matched.len() + self.unused.len();
+        if total == 0 { 0.0 } else { self.matched.len() as f64 / total as f64 }
+    }
+}
+
+/// Compare the source columns of a matrix with the CSV headers (exact names, as the executor)
+pub fn column_coverage(matrix: &TransformationMatrix, headers: &[String]) -> ColumnCoverage {
+    let sources = matrix.source_columns();
+    let (matched, missing): (Vec<String>, Vec<String>) = sources.into_iter().partition(|col| has_column(headers, col));
+    let unused = headers.iter()
+        .filter(|h| !matched.iter().any(|col| col == *h || normalize_header(col) == **h))
+        .cloned()
+        .collect();
+    ColumnCoverage { matched, missing, unused }
+}
+
+/// Whether `column` (possibly named before header normalization) is among the headers
+fn has_column(headers: &[String], column: &String) -> bool {
+    headers.contains(column) || headers.contains(&normalize_header(column))
+}
+
+/// Target fields of a matrix whose source column(s) are missing from the headers
+pub fn unmapped_fields(matrix: &TransformationMatrix, headers: &[String]) -> Vec<String> {
+    let mut fields: Vec<String> = matrix
+        .transforms
+        .iter()
+        .filter(|(_, t)| {
+            t.source.iter()
+                .chain(t.sources.iter().flatten())
+                .any(|col| !has_column(headers, col))
+        })
+        .map(|(field, _)| field.clone())
+        .collect();
+    fields.sort();
+    fields
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parser::parse_json_auto;
+
+    #[test]
+    fn test_flat_json_skips_matrix() {
+        let json = r#"[
+            {"iswc": "T1234567890", "title": "Song", "creatorIpi": 123456789, "creatorRole": "Composer"},
+            {"iswc": "T1234567890", "title": "Song", "creatorIpi": 987654321, "creatorRole": "Author"}
+        ]"#;
+        let parse_result = parse_json_auto(json.as_bytes()).unwrap();
+        assert!(is_midds_flat(&parse_result.records));
+        assert!(!is_midds_flat(&[serde_json::json!({"Titre": "Song"})]));
+
+        let options = TransformOptions { skip_validation: true, ..TransformOptions::default() };
+        let result = group_flat(parse_result, options, None).unwrap();
+        assert_eq!(result.flat.len(), 2);
+        assert_eq!(result.grouped.len(), 1);
+        assert_eq!(result.grouped[0]["creators"].as_array().unwrap().len(), 2);
+        assert!(result.matrix.transforms.is_empty());
+    }
+
+    #[test]
+    fn test_stream_csv_applies_ragged_rows() {
+        let csv = "Titre;ISWC;IPI\nA;T1;1\nB;T2\nC;T3;3\n";
+        let options = TransformOptions { ragged_rows: RaggedRows::Reject, ..TransformOptions::default() };
+        let mut stream = stream_csv(csv.as_bytes(), &options).unwrap();
+        let records: Vec<Value> = stream.by_ref().collect::<Result<_, _>>().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1]["Titre"], "C");
+        assert_eq!(stream.ragged_rows, vec![RaggedRow { line: 3, cells: 2, expected: 3, rejected: true }]);
+    }
+
+    #[test]
+    fn test_source_lines() {
+        let csv = "Export du 01/01/2024\nCode ISWC;Titre;IPI;Role;Instrumental\nT-123.456.789-0;Song;123456789;CA;non\n\n\
+            T-999.999.999-9;;1;CA;non\nT-123.456.789-0;Song;987654321;A;non\n";
+        let options = ParseOptions { skip_first: 1, ..ParseOptions::default() };
+        let parsed = parse_bytes_with_options(csv.as_bytes(), &options).unwrap();
+        assert_eq!(parsed.lines, vec![3, 5, 6]);
+
+        let options = TransformOptions::default();
+        let validator = ValidationContext::shared().unwrap();
+        let outcome = run_matrix(&parsed, crate::transform::dsl::example_matrix(), None, &options, validator);
+        let result = outcome.2;
+        assert_eq!(result.source_lines, vec![3, 6]);
+        assert_eq!(result.skipped.len(), 1);
+        assert_eq!(result.skipped[0].location(), "line 5");
+
+        let lines_by_work = grouped_lines(&result.records, &result.source_lines, &options);
+        assert_eq!(lines_by_work, vec![vec![3, 6]]);
+        let mut grouped = to_values(&flat_to_grouped(result.records));
+        grouped[0]["creationYear"] = json!(1990);
+        let options = TransformOptions { year_bounds: YearBounds { min: 2000, max: None }, ..options };
+        let warnings = check_years(&grouped, &lines_by_work, &options);
+        assert_eq!(warnings[0].lines, vec![3, 6]);
+    }
 
     #[test]
     fn test_default_options() {
@@ -501,6 +1639,22 @@ mod tests {
         assert_eq!(opts.preview_rows, 10);
         assert!(!opts.skip_validation);
         assert!(!opts.no_cache);
+        assert!(opts.exclude_columns.is_empty());
+        assert_eq!(opts.min_valid_ratio, DEFAULT_MIN_VALID_RATIO);
+        assert_eq!(opts.ai_attempts, DEFAULT_AI_ATTEMPTS);
+        assert!(opts.ai_target_ratio.is_none());
+    }
+
+    #[test]
+    fn test_min_valid_ratio_defaults_when_missing() {
+        let opts: TransformOptions = serde_json::from_value(serde_json::json!({
+            "matrix_path": null,
+            "preview_rows": 10,
+            "skip_validation": false,
+            "no_cache": false,
+            "no_save": false
+        })).unwrap();
+        assert_eq!(opts.min_valid_ratio, DEFAULT_MIN_VALID_RATIO);
     }
 
     #[test]
@@ -517,12 +1671,59 @@ mod tests {
         ];
 
         let matrix = crate::transform::dsl::example_matrix();
-        let result = transform_with_matrix(&records, &matrix, false);
+        let result = transform_with_matrix(&records, &matrix, None);
 
         assert_eq!(result.flat.len(), 1);
         assert_eq!(result.grouped.len(), 1);
         assert_eq!(result.flat[0]["iswc"], "T1234567890");
         assert_eq!(result.flat[0]["title"], "Test Song");
     }
+
+    #[test]
+    fn test_evaluate_templates_ranks_best_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut registry = MatrixRegistry::with_dir(dir.path());
+        let headers: Vec<String> = ["Code ISWC", "Titre", "IPI", "Role", "Instrumental"]
+            .iter().map(|h| h.to_string()).collect();
+
+        // Same columns, but the title comes from a column the CSV doesn't have
+        let mut broken = crate::transform::dsl::example_matrix();
+        broken.transforms.get_mut("title").unwrap().source = Some("Title".to_string());
+        registry.save(broken, "broken", headers.clone()).unwrap();
+        registry.save(crate::transform::dsl::example_matrix(), "good", headers.clone()).unwrap();
+
+        let parse_result = ParseResult {
+            records: vec![serde_json::json!({
+                "Code ISWC": "T-123.456.789-0",
+                "Titre": "Test Song",
+                "IPI": "123456789",
+                "Role": "CA",
+                "Instrumental": "non"
+            })],
+            lines: vec![2],
+            encoding: "utf-8".to_string(),
+            encoding_confidence: 1.0,
+            delimiter: ';',
+            headers,
+            footer_lines: Vec::new(),
+            ragged_rows: Vec::new(),
+            diagnostics: ParseDiagnostics::default(),
+            renamed_columns: Vec::new(),
+            original_headers: BTreeMap::new(),
+            fixed_width: None,
+        };
+
+        let evaluations = evaluate_templates(&registry, &parse_result, DEFAULT_EVALUATION_ROWS, ValidationContext::shared().unwrap());
+
+        assert_eq!(evaluations.len(), 2);
+        assert_eq!(evaluations[0].name, "good");
+        assert_eq!(evaluations[0].score.valid_ratio, 1.0);
+        assert!(evaluations[0].unmapped_fields.is_empty());
+        assert!(evaluations[0].columns.missing.is_empty());
+        assert_eq!(evaluations[1].columns.missing, vec!["Title".to_string()]);
+        assert_eq!(evaluations[1].columns.unused, vec!["Titre".to_string()]);
+        assert_eq!(evaluations[1].score.skipped_ratio, 1.0);
+        assert_eq!(evaluations[1].unmapped_fields, vec!["title".to_string()]);
+    }
 }
 
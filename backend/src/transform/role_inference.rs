@@ -0,0 +1,341 @@
+//! Creator roles of catalogs without a role column.
+//!
+//! Off by default (`role_inference` in `massload.yaml`, `--infer-roles`).
+//! When the matrix reads no role column, roles are inferred instead of
+//! leaving every row without the required `creatorRole`:
+//!
+//! 1. Column grouping: creator columns named after a role (`Compositeur`,
+//!    `Auteur IPI`, `Éditeur %`) become one creator per role and row, through
+//!    a `multiple_columns` expansion of the matrix.
+//! 2. Otherwise each creator is guessed from its name (company forms such as
+//!    `Éditions` or `GmbH` are publishers) and its shares (publishers usually
+//!    hold more mechanical than performance rights), else `default_role`.
+//!
+//! Guessed roles are low-confidence: each work holding one gets a
+//! [`ValidationWarning`]. The stored template is never changed.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::ai::heuristic::field_transform;
+use crate::models::{title_text, CreatorRole};
+use crate::transform::dsl::matrix::{ColumnVariant, ExpandConfig, FieldTransform, TransformationMatrix};
+use crate::validation::ValidationWarning;
+
+/// Role words found in column names, checked in this order
+const ROLE_WORDS: &[(&str, CreatorRole)] = &[
+    ("compositeur", CreatorRole::Composer), ("composer", CreatorRole::Composer), ("komponist", CreatorRole::Composer),
+    ("auteur", CreatorRole::Author), ("author", CreatorRole::Author), ("parolier", CreatorRole::Author),
+    ("lyricist", CreatorRole::Author), ("textdichter", CreatorRole::Author),
+    ("arrangeur", CreatorRole::Arranger), ("arranger", CreatorRole::Arranger), ("bearbeiter", CreatorRole::Arranger),
+    ("adaptateur", CreatorRole::Adapter), ("adapter", CreatorRole::Adapter),
+    ("editeur", CreatorRole::Publisher), ("éditeur", CreatorRole::Publisher), ("publisher", CreatorRole::Publisher),
+    ("verlag", CreatorRole::Publisher),
+];
+
+/// Words of a share column (`%` in the header counts too)
+const SHARE_WORDS: &[&str] = &["share", "part", "quote", "pourcentage", "anteil", "pct"];
+
+/// Creator fields a role column group can provide
+const CREATOR_FIELDS: [&str; 3] = ["creatorName", "creatorIpi", "creatorShare"];
+
+/// Role inference settings (`role_inference` in `massload.yaml`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RoleInference {
+    /// Infer roles when the matrix reads no role column
+    pub enabled: bool,
+    /// Role of creators nothing points to
+    pub default_role: CreatorRole,
+    /// Words of a publisher name, compared case-insensitively with the name's words
+    pub publisher_words: Vec<String>,
+}
+
+impl Default for RoleInference {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            default_role: CreatorRole::Composer,
+            publisher_words: [
+                "editions", "éditions", "edition", "publishing", "publisher", "music", "musique", "musik",
+                "verlag", "records", "sarl", "sas", "gmbh", "ltd", "llc", "inc",
+            ].iter().map(|w| w.to_string()).collect(),
+        }
+    }
+}
+
+/// How the roles of a file are inferred
+#[derive(Debug, Clone, PartialEq)]
+pub enum RolePlan {
+    /// Creator columns named after a role, one group per role (and number)
+    Columns(Vec<RoleColumns>),
+    /// No role in the columns: guessed per creator
+    Guess,
+}
+
+/// Creator columns of one role (`Compositeur`, `Compositeur IPI`, `Compositeur %`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoleColumns {
+    pub role: CreatorRole,
+    pub name: Option<String>,
+    pub ipi: Option<String>,
+    pub share: Option<String>,
+}
+
+impl RoleColumns {
+    fn column(&self, field: &str) -> Option<&String> {
+        match field {
+            "creatorName" => self.name.as_ref(),
+            "creatorIpi" => self.ipi.as_ref(),
+            _ => self.share.as_ref(),
+        }
+    }
+}
+
+/// A creator whose role was guessed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InferredRole {
+    pub iswc: String,
+    pub title: String,
+    /// Creator name, else IPI
+    pub creator: String,
+    pub role: CreatorRole,
+    /// What the guess rests on
+    pub reason: &'static str,
+}
+
+impl RoleInference {
+    /// How to infer roles for `matrix` on a file with these headers (None:
+    /// disabled, or the matrix already gives the roles)
+    pub fn plan(&self, matrix: &TransformationMatrix, headers: &[String]) -> Option<RolePlan> {
+        if !self.enabled || !lacks_roles(matrix, headers) {
+            return None;
+        }
+        let groups = role_columns(headers);
+        Some(if groups.is_empty() { RolePlan::Guess } else { RolePlan::Columns(groups) })
+    }
+
+    /// Give a role to the flat records lacking one
+    pub fn fill_roles(&self, records: &mut [Value]) -> Vec<InferredRole> {
+        let mut inferred = Vec::new();
+        for record in records.iter_mut() {
+            if record.get("creatorRole").and_then(Value::as_str).is_some_and(|r| !r.trim().is_empty()) {
+                continue;
+            }
+            let (role, reason) = self.guess(record);
+            let text = |field: &str| record.get(field).and_then(title_text).unwrap_or_default().to_string();
+            let creator = match record.get("creatorIpi") {
+                Some(ipi) if text("creatorName").is_empty() => format!("IPI {}", ipi),
+                _ => text("creatorName"),
+            };
+            inferred.push(InferredRole { iswc: text("iswc"), title: text("title"), creator, role: role.clone(), reason });
+            if let (Some(record), Ok(role)) = (record.as_object_mut(), serde_json::to_value(&role)) {
+                record.insert("creatorRole".to_string(), role);
+            }
+        }
+        inferred
+    }
+
+    /// Role of one creator, from its name then its shares
+    fn guess(&self, record: &Value) -> (CreatorRole, &'static str) {
+        let name = record.get("creatorName").and_then(Value::as_str).unwrap_or_default().to_lowercase();
+        let company = name.split(|c: char| !c.is_alphanumeric())
+            .any(|word| self.publisher_words.iter().any(|w| w.to_lowercase() == word));
+        if company {
+            return (CreatorRole::Publisher, "company name");
+        }
+        let share = |field: &str| record.get(field).and_then(Value::as_f64);
+        match (share("creatorPerformanceShare"), share("creatorMechanicalShare")) {
+            (Some(performance), Some(mechanical)) if mechanical > performance => {
+                (CreatorRole::Publisher, "mechanical share above performance share")
+            }
+            (Some(performance), Some(mechanical)) if performance > mechanical => {
+                (self.default_role.clone(), "performance share above mechanical share")
+            }
+            _ => (self.default_role.clone(), "no role column"),
+        }
+    }
+}
+
+/// Whether `matrix` reads no role: no `creatorRole` constant, no expansion,
+/// and none of its role columns in the file
+fn lacks_roles(matrix: &TransformationMatrix, headers: &[String]) -> bool {
+    if matrix.expand.is_some() {
+        return false;
+    }
+    match matrix.transforms.get("creatorRole") {
+        None => true,
+        Some(transform) => transform.constant.is_none()
+            && !transform.get_sources().iter().any(|source| headers.contains(source)),
+    }
+}
+
+/// Group the columns named after a role (`Compositeur 2 IPI` is the IPI of the second composer).
+/// Groups without a name nor an IPI column are dropped.
+pub fn role_columns(headers: &[String]) -> Vec<RoleColumns> {
+    let mut groups: BTreeMap<(usize, String), RoleColumns> = BTreeMap::new();
+    for header in headers {
+        let normalized: String = header.to_lowercase().chars().filter(|c| c.is_alphanumeric()).collect();
+        let matched: Vec<&(&str, CreatorRole)> = ROLE_WORDS.iter()
+            .filter(|(word, _)| normalized.contains(word))
+            .collect();
+        // "Auteur-Compositeur" names two roles: left to the matrix
+        let [(word, role), ref others @ ..] = matched[..] else { continue };
+        if others.iter().any(|(_, other)| other != role) {
+            continue;
+        }
+        // Groups in role order, "Composer" and "Compositeur" columns together
+        let order = ROLE_WORDS.iter().position(|(_, r)| r == role).unwrap_or_default();
+        let rest = normalized.replacen(word, "", 1);
+        let number: String = rest.chars().filter(char::is_ascii_digit).collect();
+        let rest: String = rest.chars().filter(|c| !c.is_ascii_digit()).collect();
+        let group = groups.entry((order, number)).or_insert_with(|| RoleColumns {
+            role: role.clone(), name: None, ipi: None, share: None,
+        });
+        let slot = if header.contains('%') || SHARE_WORDS.iter().any(|w| rest.contains(w)) {
+            &mut group.share
+        } else if rest.contains("ipi") || rest.contains("cae") {
+            &mut group.ipi
+        } else if rest.is_empty() || rest.contains("nom") || rest.contains("name") {
+            &mut group.name
+        } else {
+            continue;
+        };
+        slot.get_or_insert_with(|| header.clone());
+    }
+    groups.into_values().filter(|g| g.name.is_some() || g.ipi.is_some()).collect()
+}
+
+/// Matrix to execute for `plan`: a `multiple_columns` expansion with one
+/// variant per role group, or the matrix without its unreadable `creatorRole`
+pub fn apply_plan(matrix: &TransformationMatrix, plan: &RolePlan) -> TransformationMatrix {
+    let mut matrix = matrix.clone();
+    matrix.transforms.remove("creatorRole");
+    if let RolePlan::Columns(groups) = plan {
+        let variants: Vec<ColumnVariant> = groups.iter().map(|group| {
+            let mut overrides: HashMap<String, FieldTransform> = CREATOR_FIELDS.iter()
+                .filter_map(|&field| {
+                    let transform = match (group.column(field), matrix.transforms.get(field)) {
+                        (Some(column), Some(base)) => FieldTransform {
+                            source: Some(column.clone()),
+                            sources: None,
+                            ..base.clone()
+                        },
+                        (Some(column), None) => field_transform(field, column),
+                        // Not the columns of another role: left empty
+                        (None, Some(_)) => FieldTransform::from_constant(Value::Null),
+                        (None, None) => return None,
+                    };
+                    Some((field.to_string(), transform))
+                })
+                .collect();
+            let role = serde_json::to_value(&group.role).unwrap_or(Value::Null);
+            overrides.insert("creatorRole".to_string(), FieldTransform::from_constant(role).required());
+            ColumnVariant { condition_column: group.name.clone().or_else(|| group.ipi.clone()), overrides }
+        }).collect();
+        // Overrides only replace fields of the matrix: the others get an empty base
+        let overridden: Vec<String> = variants.iter()
+            .flat_map(|v: &ColumnVariant| v.overrides.keys().cloned())
+            .collect();
+        for field in overridden {
+            matrix.transforms.entry(field).or_insert_with(|| FieldTransform::from_constant(Value::Null));
+        }
+        matrix.expand = Some(ExpandConfig::MultipleColumns { variants });
+    }
+    matrix
+}
+
+/// One warning per work holding guessed roles, at the work's index in `grouped`
+pub fn inferred_role_warnings(inferred: &[InferredRole], grouped: &[Value]) -> Vec<ValidationWarning> {
+    let mut by_work: HashMap<(&str, &str), Vec<&InferredRole>> = HashMap::new();
+    for guess in inferred {
+        by_work.entry((&guess.iswc, &guess.title)).or_default().push(guess);
+    }
+    grouped.iter().enumerate()
+        .filter_map(|(i, work)| {
+            let text = |field: &str| work.get(field).and_then(title_text).unwrap_or_default();
+            let guesses = by_work.get(&(text("iswc"), text("title")))?;
+            let listed: Vec<String> = guesses.iter()
+                .map(|g| format!("{} → {:?} ({})", g.creator, g.role, g.reason))
+                .collect();
+            Some(ValidationWarning {
+                record_index: i,
+                field: "creatorRole".to_string(),
+                message: format!("creator role guessed, low confidence: {}", listed.join("; ")),
+                lines: Vec::new(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::dsl::execute;
+    use serde_json::json;
+
+    fn headers(names: &[&str]) -> Vec<String> {
+        names.iter().map(|h| h.to_string()).collect()
+    }
+
+    #[test]
+    fn test_roles_from_column_groups() {
+        let headers = headers(&["ISWC", "Titre", "Compositeur", "Compositeur IPI", "Compositeur %", "Auteur", "Auteur IPI"]);
+        let groups = role_columns(&headers);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].role, CreatorRole::Composer);
+        assert_eq!(groups[0].share.as_deref(), Some("Compositeur %"));
+        assert_eq!(groups[1].ipi.as_deref(), Some("Auteur IPI"));
+
+        let settings = RoleInference { enabled: true, ..RoleInference::default() };
+        let mut matrix = TransformationMatrix::new();
+        matrix.transforms.insert("iswc".into(), FieldTransform::from_source("ISWC"));
+        matrix.transforms.insert("title".into(), FieldTransform::from_source("Titre"));
+        matrix.transforms.insert("creatorIpi".into(), field_transform("creatorIpi", "Compositeur IPI"));
+        let plan = settings.plan(&matrix, &headers).unwrap();
+        let rows = vec![json!({
+            "ISWC": "T1234567890", "Titre": "Song", "Compositeur": "Piaf", "Compositeur IPI": "111",
+            "Compositeur %": "50", "Auteur": "Monnot", "Auteur IPI": "222",
+        })];
+        let result = execute(&rows, &apply_plan(&matrix, &plan));
+        assert_eq!(result.records.len(), 2);
+        assert_eq!(result.records[0]["creatorRole"], "Composer");
+        assert_eq!(result.records[0]["creatorShare"], 50.0);
+        assert_eq!(result.records[1]["creatorRole"], "Author");
+        assert_eq!(result.records[1]["creatorIpi"], 222);
+        assert!(result.records[1].get("creatorShare").is_none());
+
+        // A matrix reading a role column needs nothing
+        matrix.transforms.insert("creatorRole".into(), FieldTransform::from_source("Compositeur"));
+        assert_eq!(settings.plan(&matrix, &headers), None);
+    }
+
+    #[test]
+    fn test_guessed_roles_are_warned() {
+        let settings = RoleInference { enabled: true, ..RoleInference::default() };
+        let matrix = TransformationMatrix::new();
+        assert_eq!(settings.plan(&matrix, &headers(&["ISWC", "Titre", "Nom", "IPI"])), Some(RolePlan::Guess));
+
+        let mut records = vec![
+            json!({"iswc": "T1234567890", "title": "Song", "creatorIpi": 1, "creatorName": "Éditions Rose"}),
+            json!({"iswc": "T1234567890", "title": "Song", "creatorIpi": 2,
+                "creatorPerformanceShare": 33.33, "creatorMechanicalShare": 50.0}),
+            json!({"iswc": "T1234567890", "title": "Song", "creatorIpi": 3, "creatorName": "Edith Piaf"}),
+            json!({"iswc": "T2222222222", "title": "Other", "creatorIpi": 4, "creatorRole": "Author"}),
+        ];
+        let inferred = settings.fill_roles(&mut records);
+        assert_eq!(inferred.len(), 3);
+        assert_eq!(records[0]["creatorRole"], "Publisher");
+        assert_eq!(records[1]["creatorRole"], "Publisher");
+        assert_eq!(records[2]["creatorRole"], "Composer");
+        assert_eq!(inferred[1].creator, "IPI 2");
+
+        let grouped = vec![json!({"iswc": "T2222222222", "title": "Other"}), json!({"iswc": "T1234567890", "title": "Song"})];
+        let warnings = inferred_role_warnings(&inferred, &grouped);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].record_index, 1);
+        assert!(warnings[0].message.contains("Éditions Rose → Publisher (company name)"));
+    }
+}
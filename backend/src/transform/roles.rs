@@ -0,0 +1,210 @@
+//! Creator role statistics of a batch.
+//!
+//! Counts the creators of each role over the grouped works and lists the
+//! works without any composer, a data-quality rule most societies enforce
+//! before accepting a registration.
+//!
+//! The chain runtime may accept fewer roles than [`CreatorRole`] models: the
+//! roles accepted by each [`ChainNetwork`] are embedded here, and creators
+//! with another role are reported with a suggested substitute instead of
+//! failing when the extrinsic is submitted.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::models::{title_text, CreatorRole};
+
+/// Works listed in [`RoleReport::without_composer`] (the count covers them all)
+const MAX_LISTED_WORKS: usize = 20;
+
+/// Roles that make a creator a composer of the work
+const COMPOSER_ROLES: [&str; 2] = ["Composer", "ComposerAuthor"];
+
+/// Every role of the MIDDS model
+const ALL_ROLES: [CreatorRole; 11] = [
+    CreatorRole::Composer, CreatorRole::Author, CreatorRole::ComposerAuthor, CreatorRole::Arranger,
+    CreatorRole::Adapter, CreatorRole::Translator, CreatorRole::SubAuthor, CreatorRole::SubArranger,
+    CreatorRole::Publisher, CreatorRole::OriginalPublisher, CreatorRole::SubPublisher,
+];
+
+/// Roles of the released runtime (testnet and mainnet)
+const RUNTIME_ROLES: [CreatorRole; 5] = [
+    CreatorRole::Composer, CreatorRole::Author, CreatorRole::Arranger, CreatorRole::Adapter, CreatorRole::Publisher,
+];
+
+/// Allfeat network the works are submitted to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChainNetwork {
+    /// Development chain, follows the MIDDS model (every role)
+    #[default]
+    Devnet,
+    Testnet,
+    Mainnet,
+}
+
+impl ChainNetwork {
+    /// Creator roles the runtime of this network accepts
+    pub fn accepted_roles(self) -> &'static [CreatorRole] {
+        match self {
+            Self::Devnet => &ALL_ROLES,
+            Self::Testnet | Self::Mainnet => &RUNTIME_ROLES,
+        }
+    }
+
+    /// Closest accepted role to `role` (`None` when no substitute is accepted)
+    pub fn substitute(self, role: &CreatorRole) -> Option<CreatorRole> {
+        let accepted = self.accepted_roles();
+        let mut candidate = role.clone();
+        loop {
+            if accepted.contains(&candidate) {
+                return Some(candidate);
+            }
+            candidate = match candidate {
+                CreatorRole::ComposerAuthor => CreatorRole::Composer,
+                CreatorRole::SubAuthor => CreatorRole::Author,
+                CreatorRole::SubArranger => CreatorRole::Arranger,
+                CreatorRole::Translator => CreatorRole::Adapter,
+                CreatorRole::OriginalPublisher | CreatorRole::SubPublisher => CreatorRole::Publisher,
+                _ => return None,
+            };
+        }
+    }
+}
+
+impl std::str::FromStr for ChainNetwork {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "devnet" | "dev" => Ok(Self::Devnet),
+            "testnet" | "melodie" => Ok(Self::Testnet),
+            "mainnet" => Ok(Self::Mainnet),
+            other => Err(format!("Unknown network \"{}\" (expected devnet, testnet or mainnet)", other)),
+        }
+    }
+}
+
+/// Creators per role and works lacking a composer
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleReport {
+    /// Creators per role (`Composer`, `Author`, `Publisher`...)
+    pub roles: BTreeMap<String, usize>,
+    /// Number of works without a `Composer` or `ComposerAuthor` creator
+    pub works_without_composer: usize,
+    /// First works without composer, in batch order
+    pub without_composer: Vec<WorkRef>,
+    /// Network the roles were checked against
+    #[serde(default)]
+    pub network: ChainNetwork,
+    /// Roles the network does not accept, in role order
+    #[serde(default)]
+    pub unsupported: Vec<UnsupportedRole>,
+}
+
+/// A role of the batch rejected by the chain runtime
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsupportedRole {
+    pub role: String,
+    /// Creators having this role
+    pub creators: usize,
+    /// Accepted role to use instead, if any
+    pub suggestion: Option<String>,
+}
+
+/// A work of the batch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkRef {
+    pub iswc: String,
+    pub title: String,
+}
+
+/// Role report of grouped works (SDK JSON), roles checked against `network`
+pub fn role_report(grouped: &[Value], network: ChainNetwork) -> RoleReport {
+    let mut report = RoleReport { network, ..RoleReport::default() };
+    for work in grouped {
+        let roles: Vec<&str> = work.get("creators")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|creator| creator.get("role").and_then(Value::as_str))
+            .collect();
+        for role in &roles {
+            *report.roles.entry(role.to_string()).or_default() += 1;
+        }
+
+        if !roles.iter().any(|role| COMPOSER_ROLES.contains(role)) {
+            report.works_without_composer += 1;
+            if report.without_composer.len() < MAX_LISTED_WORKS {
+                let text = |field: &str| work.get(field).and_then(title_text).unwrap_or_default().to_string();
+                report.without_composer.push(WorkRef { iswc: text("iswc"), title: text("title") });
+            }
+        }
+    }
+    report.unsupported = report.roles.iter()
+        .filter_map(|(name, &creators)| {
+            let role: CreatorRole = serde_json::from_value(Value::String(name.clone())).ok()?;
+            (!network.accepted_roles().contains(&role)).then(|| UnsupportedRole {
+                role: name.clone(),
+                creators,
+                suggestion: network.substitute(&role).map(|r| format!("{:?}", r)),
+            })
+        })
+        .collect();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_role_report() {
+        let grouped = vec![
+            json!({"iswc": "T1234567890", "title": "Avec compositeur", "creators": [
+                {"id": {"type": "Ipi", "value": 1}, "role": "Composer"},
+                {"id": {"type": "Ipi", "value": 2}, "role": "Author"},
+            ]}),
+            json!({"iswc": "T2222222222", "title": "Auteur-compositeur", "creators": [
+                {"id": {"type": "Ipi", "value": 3}, "role": "ComposerAuthor"},
+            ]}),
+            json!({"iswc": "T3333333333", "title": "Sans compositeur", "creators": [
+                {"id": {"type": "Ipi", "value": 4}, "role": "Author"},
+                {"id": {"type": "Ipi", "value": 5}, "role": "Publisher"},
+            ]}),
+        ];
+
+        let report = role_report(&grouped, ChainNetwork::Devnet);
+        assert_eq!(report.roles["Author"], 2);
+        assert_eq!(report.roles["Composer"], 1);
+        assert_eq!(report.roles.len(), 4);
+        assert_eq!(report.works_without_composer, 1);
+        assert_eq!(report.without_composer[0].title, "Sans compositeur");
+        assert!(report.unsupported.is_empty());
+    }
+
+    #[test]
+    fn test_unsupported_roles_get_substitutes() {
+        let grouped = vec![
+            json!({"iswc": "T1234567890", "title": "Sous-édition", "creators": [
+                {"id": {"type": "Ipi", "value": 1}, "role": "ComposerAuthor"},
+                {"id": {"type": "Ipi", "value": 2}, "role": "SubPublisher"},
+                {"id": {"type": "Ipi", "value": 3}, "role": "SubPublisher"},
+                {"id": {"type": "Ipi", "value": 4}, "role": "Author"},
+            ]}),
+        ];
+
+        let report = role_report(&grouped, ChainNetwork::Mainnet);
+        assert_eq!(report.unsupported, vec![
+            UnsupportedRole { role: "ComposerAuthor".into(), creators: 1, suggestion: Some("Composer".into()) },
+            UnsupportedRole { role: "SubPublisher".into(), creators: 2, suggestion: Some("Publisher".into()) },
+        ]);
+        assert_eq!("melodie".parse(), Ok(ChainNetwork::Testnet));
+        assert_eq!(ChainNetwork::Testnet.substitute(&CreatorRole::Translator), Some(CreatorRole::Adapter));
+    }
+}
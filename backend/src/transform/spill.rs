@@ -0,0 +1,139 @@
+//! Disk spill of flat records for very large catalogs.
+//!
+//! Flat records are written to a temporary NDJSON file (one record per line)
+//! instead of being kept in memory next to the grouped works. Grouping then
+//! streams over the file with an external sort (see [`super::streaming`]).
+
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use super::streaming::{flat_to_grouped_streaming, DEFAULT_RUN_SIZE};
+use crate::models::GroupedWork;
+
+/// Flat records spilled to an NDJSON file, removed when dropped
+#[derive(Debug)]
+pub struct SpillFile {
+    path: PathBuf,
+    len: usize,
+}
+
+impl SpillFile {
+    /// Write records to a new NDJSON file in `dir`
+    pub fn create(dir: impl AsRef<Path>, records: impl IntoIterator<Item = Value>) -> io::Result<Self> {
+        fs::create_dir_all(dir.as_ref())?;
+        let path = dir.as_ref().join(format!("massload-flat-{}.ndjson", uuid::Uuid::new_v4()));
+        let mut writer = BufWriter::new(File::create(&path)?);
+
+        let mut len = 0;
+        for record in records {
+            serde_json::to_writer(&mut writer, &record)?;
+            writer.write_all(b"\n")?;
+            len += 1;
+        }
+        writer.flush()?;
+
+        Ok(Self { path, len })
+    }
+
+    /// Path of the NDJSON file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of spilled records
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no record was spilled
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Stream the records back, in spill order
+    pub fn records(&self) -> io::Result<impl Iterator<Item = io::Result<Value>>> {
+        let reader = BufReader::new(File::open(&self.path)?);
+        Ok(reader.lines().map(|line| {
+            line.and_then(|l| serde_json::from_str(&l).map_err(io::Error::from))
+        }))
+    }
+
+    /// Write the records as a pretty JSON array without loading them all
+    pub fn write_json_array(&self, mut out: impl Write) -> io::Result<()> {
+        out.write_all(b"[")?;
+        for (i, record) in self.records()?.enumerate() {
+            out.write_all(if i == 0 { b"\n  " } else { b",\n  " })?;
+            let pretty = serde_json::to_string_pretty(&record?)?;
+            out.write_all(pretty.replace('\n', "\n  ").as_bytes())?;
+        }
+        out.write_all(if self.len == 0 { b"]\n" } else { b"\n]\n" })?;
+        out.flush()
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Group spilled flat records by ISWC (or `workKey`), streaming over the file.
+///
+/// Uses the external sort of [`flat_to_grouped_streaming`] with run files
+/// next to the spill file. Works come out sorted by grouping key; rows without
+/// a key are dropped, as in [`super::grouper::flat_to_grouped`].
+pub fn group_spilled(spill: &SpillFile) -> io::Result<Vec<GroupedWork>> {
+    let dir = spill.path().parent().unwrap_or(Path::new("."));
+    flat_to_grouped_streaming(spill.records()?, dir, DEFAULT_RUN_SIZE)?.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::grouper::flat_to_grouped;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn rows() -> Vec<Value> {
+        vec![
+            json!({"iswc": "T0000000002", "title": "B", "creatorIpi": 1, "creatorRole": "Composer"}),
+            json!({"iswc": "T0000000001", "title": "A", "creatorIpi": 2, "creatorRole": "Author"}),
+            json!({"iswc": "T0000000002", "title": "B", "creatorIpi": 3, "creatorRole": "Author"}),
+            json!({"title": "No key", "creatorIpi": 4, "creatorRole": "Author"}),
+        ]
+    }
+
+    #[test]
+    fn test_group_spilled_matches_in_memory_grouping() {
+        let dir = tempdir().unwrap();
+        let spill = SpillFile::create(dir.path(), rows()).unwrap();
+        assert_eq!(spill.len(), 4);
+
+        let streamed = group_spilled(&spill).unwrap();
+        let mut in_memory = flat_to_grouped(rows());
+        in_memory.sort_by(|a, b| a.iswc.cmp(&b.iswc));
+
+        assert_eq!(streamed, in_memory);
+        assert_eq!(streamed[1].creators.len(), 2);
+    }
+
+    #[test]
+    fn test_spill_file_round_trip_and_cleanup() {
+        let dir = tempdir().unwrap();
+        let spill = SpillFile::create(dir.path(), rows()).unwrap();
+        let path = spill.path().to_path_buf();
+
+        let records: Vec<Value> = spill.records().unwrap().map(Result::unwrap).collect();
+        assert_eq!(records, rows());
+
+        let mut out = Vec::new();
+        spill.write_json_array(&mut out).unwrap();
+        let array: Vec<Value> = serde_json::from_slice(&out).unwrap();
+        assert_eq!(array, rows());
+
+        drop(spill);
+        assert!(!path.exists());
+    }
+}
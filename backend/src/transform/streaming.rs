@@ -0,0 +1,293 @@
+//! External-sort grouping of flat records.
+//!
+//! Flat records are sorted by grouping key in bounded runs written to disk,
+//! then the runs are merged: rows of a work end up adjacent and each work is
+//! emitted as soon as its last row is read. Memory stays bounded by the run
+//! size, whatever the catalog size.
+//!
+//! ```text
+//! records ──▶ run 0 (sorted) ─┐
+//!         ──▶ run 1 (sorted) ─┼─▶ k-way merge ──▶ GroupedWork, GroupedWork, ...
+//!         ──▶ run 2 (sorted) ─┘
+//! ```
+
+use futures::stream::{self, Stream};
+use serde_json::Value;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+
+use super::grouper::{grouping_key, WorkBuilder};
+use crate::models::GroupedWork;
+
+/// Default number of records sorted in memory per run
+pub const DEFAULT_RUN_SIZE: usize = 50_000;
+
+/// Group flat records through an on-disk external sort.
+///
+/// Runs of `run_size` records are sorted by grouping key and written to
+/// `dir`; the returned stream merges them and yields works sorted by key.
+/// Rows without a key are dropped, as in [`super::grouper::flat_to_grouped`].
+/// Rows of a work keep their input order (creator order).
+pub fn flat_to_grouped_streaming(
+    records: impl IntoIterator<Item = io::Result<Value>>,
+    dir: impl AsRef<Path>,
+    run_size: usize,
+) -> io::Result<GroupedStream> {
+    fs::create_dir_all(dir.as_ref())?;
+    let prefix = format!("massload-run-{}", uuid::Uuid::new_v4());
+    let run_size = run_size.max(1);
+
+    let mut paths = Vec::new();
+    let mut chunk: Vec<(String, Value)> = Vec::with_capacity(run_size.min(DEFAULT_RUN_SIZE));
+    for record in records {
+        let record = record?;
+        if let Some(key) = grouping_key(&record) {
+            chunk.push((key, record));
+        }
+        if chunk.len() >= run_size {
+            paths.push(write_run(dir.as_ref(), &prefix, paths.len(), &mut chunk)?);
+        }
+    }
+    if !chunk.is_empty() {
+        paths.push(write_run(dir.as_ref(), &prefix, paths.len(), &mut chunk)?);
+    }
+
+    let mut stream = GroupedStream {
+        runs: Vec::new(),
+        heap: BinaryHeap::new(),
+        current: None,
+        paths,
+    };
+    for path in stream.paths.clone() {
+        let mut run = Run { lines: BufReader::new(File::open(&path)?).lines(), head: None };
+        run.advance()?;
+        if let Some((ref key, _)) = run.head {
+            stream.heap.push(Reverse((key.clone(), stream.runs.len())));
+        }
+        stream.runs.push(run);
+    }
+    Ok(stream)
+}
+
+/// Sort a chunk by key (stable) and write it as a run file
+fn write_run(dir: &Path, prefix: &str, index: usize, chunk: &mut Vec<(String, Value)>) -> io::Result<PathBuf> {
+    chunk.sort_by(|a, b| a.0.cmp(&b.0));
+    let path = dir.join(format!("{}-{}.ndjson", prefix, index));
+    let mut writer = BufWriter::new(File::create(&path)?);
+    for entry in chunk.drain(..) {
+        serde_json::to_writer(&mut writer, &entry)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(path)
+}
+
+/// A sorted run being merged
+struct Run {
+    lines: Lines<BufReader<File>>,
+    head: Option<(String, Value)>,
+}
+
+impl Run {
+    fn advance(&mut self) -> io::Result<()> {
+        self.head = match self.lines.next() {
+            Some(line) => Some(serde_json::from_str(&line?)?),
+            None => None,
+        };
+        Ok(())
+    }
+}
+
+/// Grouped works merged from sorted runs; run files are removed when dropped
+pub struct GroupedStream {
+    runs: Vec<Run>,
+    /// Smallest head key of each run (ties broken by run order)
+    heap: BinaryHeap<Reverse<(String, usize)>>,
+    /// Work being accumulated
+    current: Option<(String, WorkBuilder)>,
+    paths: Vec<PathBuf>,
+}
+
+impl GroupedStream {
+    /// Next row in key order
+    fn next_row(&mut self) -> io::Result<Option<(String, Value)>> {
+        let Some(Reverse((_, index))) = self.heap.pop() else {
+            return Ok(None);
+        };
+        let run = &mut self.runs[index];
+        let row = run.head.take();
+        run.advance()?;
+        if let Some((ref key, _)) = run.head {
+            self.heap.push(Reverse((key.clone(), index)));
+        }
+        Ok(row)
+    }
+}
+
+impl Iterator for GroupedStream {
+    type Item = io::Result<GroupedWork>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let row = match self.next_row() {
+                Ok(row) => row,
+                Err(e) => return Some(Err(e)),
+            };
+            match (row, self.current.as_mut()) {
+                (Some((key, record)), Some((current_key, builder))) if *current_key == key => {
+                    builder.add_creator(&record);
+                }
+                (Some((key, record)), _) => {
+                    let mut builder = WorkBuilder::new(&record);
+                    builder.add_creator(&record);
+                    if let Some((_, done)) = self.current.replace((key, builder)) {
+                        return Some(Ok(done.build()));
+                    }
+                }
+                (None, _) => return self.current.take().map(|(_, builder)| Ok(builder.build())),
+            }
+        }
+    }
+}
+
+impl Drop for GroupedStream {
+    fn drop(&mut self) {
+        self.runs.clear();
+        for path in &self.paths {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Grouped works of a running job, published as the grouper emits them
+/// so a download can start before the job completes
+#[derive(Debug)]
+pub struct LiveWorks {
+    works: Mutex<Vec<Value>>,
+    /// Works published so far, and the outcome once the job is over
+    state: watch::Sender<(usize, Option<Result<(), String>>)>,
+}
+
+impl LiveWorks {
+    pub fn new() -> Self {
+        Self { works: Mutex::new(Vec::new()), state: watch::channel((0, None)).0 }
+    }
+
+    /// Publish a grouped work
+    pub fn push(&self, work: Value) {
+        let mut works = self.works.lock().unwrap_or_else(|e| e.into_inner());
+        works.push(work);
+        let len = works.len();
+        self.state.send_modify(|state| state.0 = len);
+    }
+
+    /// Mark the job as over: readers end once they have every work, or get `error`
+    pub fn finish(&self, error: Option<String>) {
+        self.state.send_modify(|state| state.1 = Some(error.map_or(Ok(()), Err)));
+    }
+
+    /// Every work, already published or to come, until the job is over
+    pub fn stream(self: Arc<Self>) -> impl Stream<Item = io::Result<Value>> {
+        let state = self.state.subscribe();
+        stream::unfold(Some((self, state, 0)), |reader| async move {
+            let (live, mut state, next) = reader?;
+            loop {
+                // Outcome read before the works: a work published meanwhile wakes `changed`
+                let outcome = state.borrow_and_update().1.clone();
+                let work = live.works.lock().unwrap_or_else(|e| e.into_inner()).get(next).cloned();
+                match (work, outcome) {
+                    (Some(work), _) => return Some((Ok(work), Some((live, state, next + 1)))),
+                    (None, Some(Ok(()))) => return None,
+                    (None, Some(Err(e))) => return Some((Err(io::Error::other(e)), None)),
+                    (None, None) => {
+                        if state.changed().await.is_err() {
+                            return None;
+                        }
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl Default for LiveWorks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::grouper::flat_to_grouped;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn rows() -> Vec<Value> {
+        (0..50)
+            .map(|i| json!({
+                "iswc": format!("T{:010}", (i * 7) % 13),
+                "title": format!("Work {}", (i * 7) % 13),
+                "creatorIpi": i + 1,
+                "creatorRole": "Composer"
+            }))
+            .chain(std::iter::once(json!({"title": "No key", "creatorIpi": 1, "creatorRole": "Author"})))
+            .collect()
+    }
+
+    #[test]
+    fn test_streaming_matches_in_memory_grouping() {
+        let dir = tempdir().unwrap();
+        // Small runs to force a multi-way merge
+        let streamed: Vec<GroupedWork> = flat_to_grouped_streaming(rows().into_iter().map(Ok), dir.path(), 8)
+            .unwrap()
+            .collect::<io::Result<_>>()
+            .unwrap();
+
+        let mut in_memory = flat_to_grouped(rows());
+        in_memory.sort_by(|a, b| a.iswc.cmp(&b.iswc));
+
+        assert_eq!(streamed.len(), 13);
+        assert_eq!(streamed, in_memory);
+    }
+
+    #[test]
+    fn test_run_files_removed_on_drop() {
+        let dir = tempdir().unwrap();
+        let mut stream = flat_to_grouped_streaming(rows().into_iter().map(Ok), dir.path(), 8).unwrap();
+        assert!(fs::read_dir(dir.path()).unwrap().count() > 1);
+
+        stream.next().unwrap().unwrap();
+        drop(stream);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_live_works_stream_until_finished() {
+        use futures::StreamExt;
+
+        let live = Arc::new(LiveWorks::new());
+        live.push(json!({"iswc": "T0000000001"}));
+        let reader = live.clone().stream();
+
+        let writer = live.clone();
+        let handle = std::thread::spawn(move || {
+            writer.push(json!({"iswc": "T0000000002"}));
+            writer.finish(None);
+        });
+        let works: Vec<Value> = futures::executor::block_on(reader.map(Result::unwrap).collect());
+        handle.join().unwrap();
+        assert_eq!(works.len(), 2);
+
+        // A failed job ends the stream with an error
+        let failed = Arc::new(LiveWorks::new());
+        failed.finish(Some("boom".to_string()));
+        let outcome: Vec<io::Result<Value>> = futures::executor::block_on(failed.stream().collect());
+        assert!(outcome[0].is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! Grouped works back to flat rows.
+//!
+//! The inverse of [`flat_to_grouped`](super::grouper::flat_to_grouped): each
+//! work is expanded into one flat MIDDS record per creator, the work columns
+//! repeated on each line. Written as CSV, the grouped output can be edited in
+//! a spreadsheet and re-imported (the columns are the flat schema fields).
+//!
+//! The same CSV layout serves as the canonical export of flat records
+//! (`transform --flat-csv`, `GET /api/v1/jobs/{id}/flat?format=csv`).
+
+use serde_json::{Map, Value};
+use std::io::{self, Write};
+
+use crate::models::GroupedWork;
+
+/// Columns of the flat CSV, named after the flat schema fields, in schema order
+const FLAT_COLUMNS: [&str; 20] = [
+    "iswc", "title", "titleLanguage", "creationYear", "instrumental", "language", "bpm", "key", "workType",
+    "creatorIpi", "creatorIsni", "creatorRole", "creatorName", "creatorShare",
+    "creatorPerformanceShare", "creatorMechanicalShare",
+    "opus", "catalogNumber", "numberOfVoices", "workKey",
+];
+
+/// Flat records of grouped works, one per creator (one without creator
+/// fields for a work without creators), in work order.
+pub fn grouped_to_flat(works: &[GroupedWork]) -> Vec<Value> {
+    let mut records = Vec::new();
+    for work in works {
+        let mut base = Map::new();
+        base.insert("iswc".into(), work.iswc.clone().into());
+        base.insert("title".into(), work.title.text().into());
+        let mut set = |field: &str, value: Option<Value>| {
+            if let Some(value) = value {
+                base.insert(field.into(), value);
+            }
+        };
+        set("titleLanguage", work.title.language().map(Value::from));
+        set("creationYear", work.creation_year.map(Value::from));
+        set("instrumental", work.instrumental.map(Value::from));
+        set("language", work.language.clone().map(Value::from));
+        set("bpm", work.bpm.map(Value::from));
+        set("key", work.key.clone().map(Value::from));
+        // `{ "type": "Original" }` in grouped works, the bare name in flat records
+        set("workType", work.work_type.as_ref()
+            .and_then(|t| serde_json::to_value(t).ok()?.get("type").cloned()));
+        if let Some(info) = &work.classical_info {
+            set("opus", info.opus.clone().map(Value::from));
+            set("catalogNumber", info.catalog_number.clone().map(Value::from));
+            set("numberOfVoices", info.number_of_voices.map(Value::from));
+        }
+
+        if work.creators.is_empty() {
+            records.push(Value::Object(base));
+            continue;
+        }
+        for creator in &work.creators {
+            let mut record = base.clone();
+            if let Some(ipi) = creator.id.ipi() {
+                record.insert("creatorIpi".into(), ipi.into());
+            }
+            if let Some(isni) = creator.id.isni() {
+                record.insert("creatorIsni".into(), isni.into());
+            }
+            if let Ok(role) = serde_json::to_value(&creator.role) {
+                record.insert("creatorRole".into(), role);
+            }
+            if let Some(name) = &creator.name {
+                record.insert("creatorName".into(), name.clone().into());
+            }
+            if let Some(shares) = &creator.shares {
+                for (field, share) in [
+                    ("creatorShare", shares.ownership),
+                    ("creatorPerformanceShare", shares.performance),
+                    ("creatorMechanicalShare", shares.mechanical),
+                ] {
+                    if let Some(share) = share {
+                        record.insert(field.into(), share.into());
+                    }
+                }
+            }
+            records.push(Value::Object(record));
+        }
+    }
+    records
+}
+
+/// Write flat records as CSV, one column per flat schema field (empty when absent)
+pub fn flat_csv(records: &[Value]) -> Result<String, csv::Error> {
+    let mut bytes = Vec::new();
+    write_flat_csv(records.iter().cloned().map(Ok), &mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Stream flat records to `out` as CSV (see [`flat_csv`]), e.g. from a spill file
+pub fn write_flat_csv(records: impl IntoIterator<Item = io::Result<Value>>, out: impl Write) -> Result<(), csv::Error> {
+    let mut writer = csv::Writer::from_writer(out);
+    writer.write_record(FLAT_COLUMNS)?;
+    for record in records {
+        let record = record?;
+        writer.write_record(FLAT_COLUMNS.iter().map(|field| match record.get(field) {
+            None | Some(Value::Null) => String::new(),
+            Some(Value::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+        }))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::grouper::flat_to_grouped;
+    use serde_json::json;
+
+    #[test]
+    fn test_ungroup_round_trip() {
+        let works: Vec<GroupedWork> = serde_json::from_value(json!([
+            {
+                "iswc": "T1234567890", "title": "Song, the", "creationYear": 1999, "participants": [],
+                "workType": { "type": "Original" },
+                "creators": [
+                    { "id": { "type": "Ipi", "value": 123456789 }, "role": "Composer" },
+                    { "id": { "type": "Both", "value": { "ipi": 987654321, "isni": "0000000121032683" } }, "role": "Author",
+                      "shares": { "performance": 50.0, "mechanical": 25.0 } }
+                ]
+            },
+            { "iswc": "T0000000001", "title": "Solo", "creators": [], "participants": [] }
+        ])).unwrap();
+
+        let flat = grouped_to_flat(&works);
+        assert_eq!(flat.len(), 3);
+        assert_eq!(flat[1]["creatorIsni"], "0000000121032683");
+        assert_eq!(flat[1]["workType"], "Original");
+        assert_eq!(flat[1]["creatorMechanicalShare"], 25.0);
+        assert!(flat[1].get("creatorShare").is_none());
+
+        let csv = flat_csv(&flat).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], FLAT_COLUMNS.join(","));
+        assert_eq!(lines[1], "T1234567890,\"Song, the\",,1999,,,,,Original,123456789,,Composer,,,,,,,,");
+
+        assert_eq!(flat_to_grouped(flat), works);
+    }
+
+    #[test]
+    fn test_flat_columns_follow_flat_schema() {
+        let schema = crate::cache::flat_schema();
+        let mut properties: Vec<&str> = schema["properties"].as_object().unwrap().keys().map(String::as_str).collect();
+        let mut columns = FLAT_COLUMNS.to_vec();
+        properties.sort_unstable();
+        columns.sort_unstable();
+        assert_eq!(columns, properties);
+    }
+}
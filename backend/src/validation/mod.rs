@@ -21,6 +21,9 @@
 //! - `midds-musical-work-flat.json`
 //! - `midds-musical-work-grouped.json`
 //!
+//! They are compiled once into a [`ValidationContext`] shared by the
+//! pipeline; a broken schema is a [`ValidationError::InvalidSchema`].
+//!
 //! # Example
 //!
 //! ```rust,ignore
@@ -46,7 +49,12 @@
 //! assert!(is_valid_musical_work_grouped(&grouped));
 //! ```
 
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::sync::OnceLock;
+
+use crate::error::ValidationError;
 
 /// Valide un objet JSON contre un schéma JSON.
 ///
@@ -98,32 +106,157 @@ pub fn is_valid(schema: &Value, data: &Value) -> bool {
     jsonschema::draft7::is_valid(schema, data)
 }
 
+/// Embedded grouped schema (full work with creators array)
+const GROUPED_SCHEMA: &str = include_str!("../../schemas/midds-musical-work-grouped.json");
+
+/// Embedded flat schema (single row, one creator per row)
+pub(crate) const FLAT_SCHEMA: &str = include_str!("../../schemas/midds-musical-work-flat.json");
+
+static SHARED: OnceLock<Result<ValidationContext, ValidationError>> = OnceLock::new();
+
+/// MIDDS validators compiled once, reused for every record of a file.
+pub struct ValidationContext {
+    flat: jsonschema::Validator,
+    grouped: jsonschema::Validator,
+}
+
+impl ValidationContext {
+    /// Compile the embedded flat and grouped schemas
+    pub fn new() -> Result<Self, ValidationError> {
+        Self::from_schemas(FLAT_SCHEMA, GROUPED_SCHEMA)
+    }
+
+    /// Compile the given flat and grouped schemas (JSON text)
+    pub fn from_schemas(flat: &str, grouped: &str) -> Result<Self, ValidationError> {
+        Ok(Self {
+            flat: compile("flat", flat)?,
+            grouped: compile("grouped", grouped)?,
+        })
+    }
+
+    /// Context of the embedded schemas, compiled on first use
+    pub fn shared() -> Result<&'static Self, ValidationError> {
+        SHARED.get_or_init(Self::new).as_ref().map_err(Clone::clone)
+    }
+
+    /// Validate against the flat schema
+    pub fn validate_flat(&self, data: &Value) -> Result<(), Vec<String>> {
+        collect_errors(&self.flat, data)
+    }
+
+    /// Validate against the grouped schema
+    pub fn validate_grouped(&self, data: &Value) -> Result<(), Vec<String>> {
+        collect_errors(&self.grouped, data)
+    }
+
+    /// Quick check against the flat schema
+    pub fn is_valid_flat(&self, data: &Value) -> bool {
+        self.flat.is_valid(data)
+    }
+
+    /// Quick check against the grouped schema
+    pub fn is_valid_grouped(&self, data: &Value) -> bool {
+        self.grouped.is_valid(data)
+    }
+}
+
+fn compile(name: &str, schema: &str) -> Result<jsonschema::Validator, ValidationError> {
+    let invalid = |message: String| ValidationError::InvalidSchema { schema: name.to_string(), message };
+    let schema: Value = serde_json::from_str(schema).map_err(|e| invalid(e.to_string()))?;
+    jsonschema::draft7::new(&schema).map_err(|e| invalid(e.to_string()))
+}
+
+fn collect_errors(validator: &jsonschema::Validator, data: &Value) -> Result<(), Vec<String>> {
+    let errors: Vec<String> = validator.iter_errors(data).map(|e| e.to_string()).collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
 /// Validate against the grouped MIDDS schema (full work with creators array).
+///
+/// An invalid embedded schema is reported as the only error.
 pub fn validate_musical_work_grouped(data: &Value) -> Result<(), Vec<String>> {
-    let schema: Value = serde_json::from_str(include_str!("../../schemas/midds-musical-work-grouped.json"))
-        .expect("Invalid embedded schema");
-    validate(&schema, data)
+    ValidationContext::shared()
+        .map_err(|e| vec![e.to_string()])?
+        .validate_grouped(data)
 }
 
 /// Quick check against the grouped schema.
 pub fn is_valid_musical_work_grouped(data: &Value) -> bool {
-    let schema: Value = serde_json::from_str(include_str!("../../schemas/midds-musical-work-grouped.json"))
-        .expect("Invalid embedded schema");
-    is_valid(&schema, data)
+    ValidationContext::shared().is_ok_and(|context| context.is_valid_grouped(data))
 }
 
 /// Validate against the flat MIDDS schema (single row, one creator per row).
+///
+/// An invalid embedded schema is reported as the only error.
 pub fn validate_musical_work_flat(data: &Value) -> Result<(), Vec<String>> {
-    let schema: Value = serde_json::from_str(include_str!("../../schemas/midds-musical-work-flat.json"))
-        .expect("Invalid embedded schema");
-    validate(&schema, data)
+    ValidationContext::shared()
+        .map_err(|e| vec![e.to_string()])?
+        .validate_flat(data)
 }
 
 /// Quick check against the flat schema.
 pub fn is_valid_musical_work_flat(data: &Value) -> bool {
-    let schema: Value = serde_json::from_str(include_str!("../../schemas/midds-musical-work-flat.json"))
-        .expect("Invalid embedded schema");
-    is_valid(&schema, data)
+    ValidationContext::shared().is_ok_and(|context| context.is_valid_flat(data))
+}
+
+/// Earliest plausible creation year: older ones are usually date-parsing mistakes
+pub const DEFAULT_MIN_YEAR: i64 = 1700;
+
+/// Current year ([`crate::deterministic::now`], so pinned in deterministic mode)
+pub fn current_year() -> i64 {
+    crate::deterministic::now().year() as i64
+}
+
+/// Accepted range of `creationYear`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct YearBounds {
+    pub min: i64,
+    /// Latest accepted year (None: current year)
+    pub max: Option<i64>,
+}
+
+impl Default for YearBounds {
+    fn default() -> Self {
+        Self { min: DEFAULT_MIN_YEAR, max: None }
+    }
+}
+
+impl YearBounds {
+    pub fn contains(&self, year: i64) -> bool {
+        (self.min..=self.max.unwrap_or_else(current_year)).contains(&year)
+    }
+}
+
+/// A non-blocking finding on a grouped work: the work is still submitted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationWarning {
+    pub record_index: usize,
+    pub field: String,
+    pub message: String,
+    /// Source file lines of the work's rows, when known
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lines: Vec<usize>,
+}
+
+/// Warn about works whose `creationYear` is outside `bounds`
+pub fn check_creation_years(works: &[Value], bounds: &YearBounds) -> Vec<ValidationWarning> {
+    works.iter().enumerate()
+        .filter_map(|(i, work)| {
+            let year = work.get("creationYear")?.as_i64()?;
+            (!bounds.contains(year)).then(|| ValidationWarning {
+                record_index: i,
+                field: "creationYear".to_string(),
+                message: format!("implausible creation year {} (expected {} to {})", year, bounds.min,
+                    bounds.max.unwrap_or_else(current_year)),
+                lines: Vec::new(),
+            })
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -184,4 +317,27 @@ mod tests {
         assert!(!errors.is_empty());
         println!("Errors: {:?}", errors);
     }
+
+    #[test]
+    fn test_invalid_schema_is_an_error() {
+        let result = ValidationContext::from_schemas(FLAT_SCHEMA, "{ not json");
+        assert!(matches!(result, Err(ValidationError::InvalidSchema { ref schema, .. }) if schema == "grouped"));
+
+        let context = ValidationContext::new().unwrap();
+        assert!(context.validate_flat(&json!({ "iswc": "T1234567890" })).is_err());
+    }
+
+    #[test]
+    fn test_check_creation_years() {
+        let works = vec![
+            json!({"title": "Ok", "creationYear": 1985}),
+            json!({"title": "Parsed as 0201", "creationYear": 201}),
+            json!({"title": "No year"}),
+            json!({"title": "Future", "creationYear": 2301}),
+        ];
+        let warnings = check_creation_years(&works, &YearBounds { min: 1700, max: Some(2024) });
+        let indexes: Vec<usize> = warnings.iter().map(|w| w.record_index).collect();
+        assert_eq!(indexes, vec![1, 3]);
+        assert!(YearBounds::default().contains(current_year()));
+    }
 }
@@ -5,7 +5,7 @@ T1234567890,Test Song Alpha,120,C Major,222222222,Author,false`;
 const formData = new FormData();
 formData.append('file', new Blob([csv], { type: 'text/csv' }), 'test.csv');
 
-const res = await fetch('http://localhost:3000/api/upload', { method: 'POST', body: formData });
+const res = await fetch('http://localhost:3000/api/v1/upload', { method: 'POST', body: formData });
 const data = await res.json();
 
 console.log('Keys:', Object.keys(data));
@@ -0,0 +1,77 @@
+//! Réglages : URL du backend utilisée par ce navigateur.
+//!
+//! Permet de pointer le frontend déployé vers un autre backend (staging,
+//! prod) sans le recompiler ; le flux de logs est rouvert sur la nouvelle URL.
+
+use leptos::*;
+
+use super::logs::init_sse_logs;
+use crate::services::{backend_url, backend_url_override, set_backend_url};
+use crate::{LogEntry, BACKEND_URL};
+
+#[component]
+pub fn BackendSettings(set_logs: WriteSignal<Vec<LogEntry>>) -> impl IntoView {
+    let open = create_rw_signal(false);
+    let url = create_rw_signal(String::new());
+    let error = create_rw_signal(None::<String>);
+    // URL en cours, pour l'infobulle du bouton
+    let current = create_rw_signal(backend_url());
+
+    let on_open = move |_| {
+        url.set(backend_url());
+        error.set(None);
+        open.set(true);
+    };
+
+    let apply = move |raw: Option<String>| {
+        match set_backend_url(raw.as_deref()) {
+            Ok(new_url) => {
+                if new_url != current.get_untracked() {
+                    log::info!("🔌 Backend : {}", new_url);
+                    init_sse_logs(set_logs);
+                    current.set(new_url);
+                }
+                open.set(false);
+            }
+            Err(e) => error.set(Some(e)),
+        }
+    };
+
+    view! {
+        <button
+            class="settings-button"
+            class:custom-backend=move || current.get() != BACKEND_URL
+            title=move || format!("Backend : {}", current.get())
+            aria-label="Réglages du backend"
+            on:click=on_open
+        >
+            "⚙️"
+        </button>
+        {move || open.get().then(|| view! {
+            <div class="modal-backdrop">
+                <div class="modal" role="dialog" aria-modal="true" aria-labelledby="backend-settings-title">
+                    <div class="preview-title" id="backend-settings-title">"Réglages du backend"</div>
+                    <div class="upload-hint">
+                        "URL du backend utilisée par ce navigateur (par défaut : " {BACKEND_URL} ")."
+                        {backend_url_override().is_some().then_some(" Une URL personnalisée est active.")}
+                    </div>
+                    <input
+                        type="url"
+                        aria-label="URL du backend"
+                        placeholder=BACKEND_URL
+                        prop:value=move || url.get()
+                        on:input=move |ev| url.set(event_target_value(&ev))
+                    />
+                    {move || error.get().map(|e| view! { <div class="error-message">{e}</div> })}
+                    <div class="preflight-actions">
+                        <button class="btn btn-secondary" on:click=move |_| open.set(false)>"Annuler"</button>
+                        <button class="btn btn-secondary" on:click=move |_| apply(None)>"URL par défaut"</button>
+                        <button class="btn btn-primary" on:click=move |_| apply(Some(url.get_untracked()))>
+                            "Enregistrer"
+                        </button>
+                    </div>
+                </div>
+            </div>
+        })}
+    }
+}
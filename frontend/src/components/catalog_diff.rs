@@ -0,0 +1,104 @@
+//! Bandeau du mode révision : diff avec la version précédente du catalogue.
+//!
+//! Quand une société renvoie un catalogue mis à jour, seules les œuvres
+//! nouvelles ou modifiées sont proposées à la soumission par défaut.
+
+use leptos::*;
+use serde_json::Value;
+
+use crate::services::CatalogDiff;
+use crate::PreviewItem;
+
+/// Met à jour l'aperçu avec toutes les œuvres ou seulement les changements
+pub(crate) fn show_works(
+    diff: Option<&CatalogDiff>,
+    works: &[Value],
+    changes_only: bool,
+    set_preview_data: WriteSignal<Option<Vec<PreviewItem>>>,
+    set_musical_works_json: WriteSignal<Option<Value>>,
+) {
+    let shown = match diff {
+        Some(diff) if changes_only => diff.works_to_submit(works),
+        _ => works.to_vec(),
+    };
+    set_preview_data.set(Some(PreviewItem::from_works(&shown)));
+    set_musical_works_json.set(Some(Value::Array(shown)));
+}
+
+#[component]
+pub fn CatalogDiffBanner(
+    /// Diff renvoyé par le backend (None si premier envoi du catalogue)
+    catalog_diff: RwSignal<Option<CatalogDiff>>,
+    /// Toutes les œuvres de l'upload, avant filtrage
+    all_works: RwSignal<Vec<Value>>,
+    /// Ne soumettre que les œuvres nouvelles ou modifiées
+    changes_only: RwSignal<bool>,
+    set_preview_data: WriteSignal<Option<Vec<PreviewItem>>>,
+    set_musical_works_json: WriteSignal<Option<Value>>,
+) -> impl IntoView {
+    let on_toggle = move |ev| {
+        let checked = event_target_checked(&ev);
+        changes_only.set(checked);
+        catalog_diff.with_untracked(|diff| {
+            all_works.with_untracked(|works| {
+                show_works(diff.as_ref(), works, checked, set_preview_data, set_musical_works_json);
+            });
+        });
+    };
+
+    view! {
+        {move || catalog_diff.get().map(|diff| {
+            let since = diff.previous_created_at.as_deref()
+                .and_then(|d| d.get(..10))
+                .map(|d| format!(" (version du {})", d))
+                .unwrap_or_default();
+            let removed = diff.removed.clone();
+            let changed = diff.changed.clone();
+            let ambiguous = diff.ambiguous.clone();
+            view! {
+                <div class="catalog-diff" role="status">
+                    <div class="catalog-diff-title">
+                        "🔁 Catalogue déjà transformé" {since}
+                    </div>
+                    <div class="catalog-diff-summary">{diff.summary()}</div>
+                    <label class="catalog-diff-toggle">
+                        <input
+                            type="checkbox"
+                            prop:checked=move || changes_only.get()
+                            on:change=on_toggle
+                        />
+                        " Ne soumettre que les changements"
+                    </label>
+                    {(!changed.is_empty()).then(|| view! {
+                        <details class="catalog-diff-list">
+                            <summary>"Œuvres modifiées"</summary>
+                            <ul>
+                                {changed.iter().map(|c| view! {
+                                    <li>{c.title.clone()} " (" {c.iswc.clone()} ") : " {c.fields.join(", ")}</li>
+                                }).collect_view()}
+                            </ul>
+                        </details>
+                    })}
+                    {(!removed.is_empty()).then(|| view! {
+                        <details class="catalog-diff-list">
+                            <summary>"Œuvres absentes de cette version (non soumises)"</summary>
+                            <ul>
+                                {removed.iter().map(|key| view! { <li>{key.clone()}</li> }).collect_view()}
+                            </ul>
+                        </details>
+                    })}
+                    {(!ambiguous.is_empty()).then(|| view! {
+                        <details class="catalog-diff-list">
+                            <summary>"Titres partagés par plusieurs œuvres sans ISWC (non comparées, soumises)"</summary>
+                            <ul>
+                                {ambiguous.iter().map(|key| view! {
+                                    <li>{if key.is_empty() { "(sans ISWC ni titre)".to_string() } else { key.clone() }}</li>
+                                }).collect_view()}
+                            </ul>
+                        </details>
+                    })}
+                </div>
+            }
+        })}
+    }
+}
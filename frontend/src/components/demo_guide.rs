@@ -0,0 +1,64 @@
+//! Mode démo guidé : annotations de chaque étape du parcours.
+//!
+//! Activé par « Tester avec un fichier d'exemple » (fichier anonymisé servi
+//! par le backend), désactivé dès qu'un vrai fichier est choisi.
+
+use leptos::*;
+
+/// Étape du parcours annotée en mode démo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DemoStep {
+    Preflight,
+    Transform,
+    Preview,
+    Sign,
+}
+
+impl DemoStep {
+    const ALL: [DemoStep; 4] = [Self::Preflight, Self::Transform, Self::Preview, Self::Sign];
+
+    /// Numéro de l'étape (à partir de 1)
+    pub fn number(self) -> usize {
+        Self::ALL.iter().position(|s| *s == self).unwrap_or(0) + 1
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::Preflight => "Analyse du fichier",
+            Self::Transform => "Transformation",
+            Self::Preview => "Relecture",
+            Self::Sign => "Signature",
+        }
+    }
+
+    pub fn explanation(self) -> &'static str {
+        match self {
+            Self::Preflight => "Massload a détecté l'encodage, le séparateur et les colonnes du fichier, sans IA. \
+                Cochez les colonnes contenant des données personnelles pour les retirer, puis lancez la transformation.",
+            Self::Transform => "Les colonnes sont associées aux champs MIDDS (modèle enregistré, IA ou noms de colonnes), \
+                chaque ligne est validée puis les ayants droit sont regroupés par œuvre. Les logs suivent chaque étape.",
+            Self::Preview => "Chaque œuvre est listée avec ses ayants droit : ouvrez-en une pour vérifier le détail. \
+                Rien n'est encore envoyé.",
+            Self::Sign => "Connectez un wallet pour signer l'enregistrement sur la blockchain Allfeat, ou exportez le lot \
+                pour le faire signer ailleurs. Les ISWC et IPI de l'exemple sont fictifs : utilisez un réseau de test.",
+        }
+    }
+}
+
+#[component]
+pub fn DemoGuide(guided: RwSignal<bool>, step: DemoStep) -> impl IntoView {
+    view! {
+        <Show
+            when=move || guided.get()
+            fallback=|| view! { }
+        >
+            <div class="demo-guide" role="note">
+                <div class="demo-guide-title">
+                    {format!("🧪 Démo • Étape {}/{} : {}", step.number(), DemoStep::ALL.len(), step.title())}
+                </div>
+                <div>{step.explanation()}</div>
+                <button class="demo-guide-exit" on:click=move |_| guided.set(false)>"Quitter le mode guidé"</button>
+            </div>
+        </Show>
+    }
+}
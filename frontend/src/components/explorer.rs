@@ -0,0 +1,112 @@
+//! Onglet « Explorer le fichier » : profil des colonnes du fichier source.
+//!
+//! Aide à comprendre un export inconnu (colonnes vides, constantes,
+//! identifiants, formats de valeurs) via `GET /api/v1/jobs/{id}/columns`.
+
+use leptos::*;
+
+use crate::services::{fetch_job_columns, ColumnProfile, ColumnsResponse};
+use crate::backend_url;
+
+/// Remarque affichée à côté du nom de la colonne
+fn column_note(column: &ColumnProfile) -> Option<&'static str> {
+    if column.is_empty() {
+        Some("toujours vide")
+    } else if column.is_constant() {
+        Some("valeur unique")
+    } else if column.is_unique() {
+        Some("identifiant probable")
+    } else {
+        None
+    }
+}
+
+#[component]
+pub fn ColumnExplorer(
+    /// Job courant (None tant qu'aucun fichier n'a été transformé)
+    job_id: RwSignal<Option<String>>,
+) -> impl IntoView {
+    let open = create_rw_signal(false);
+    let profile = create_rw_signal(None::<ColumnsResponse>);
+    let error = create_rw_signal(None::<String>);
+    let loading = create_rw_signal(false);
+
+    // Nouveau job : refermer l'onglet et oublier le profil précédent
+    create_effect(move |_| {
+        job_id.track();
+        open.set(false);
+        profile.set(None);
+        error.set(None);
+    });
+
+    let on_toggle = move |_| {
+        let opening = !open.get_untracked();
+        open.set(opening);
+        if !opening || profile.get_untracked().is_some() || loading.get_untracked() {
+            return;
+        }
+        let Some(id) = job_id.get_untracked() else { return };
+        loading.set(true);
+        spawn_local(async move {
+            match fetch_job_columns(&id, &backend_url()).await {
+                Ok(response) => profile.set(Some(response)),
+                Err(e) => error.set(Some(e)),
+            }
+            loading.set(false);
+        });
+    };
+
+    view! {
+        <Show when=move || job_id.get().is_some() fallback=|| view! { }>
+            <div class="column-explorer">
+                <button class="btn btn-secondary" on:click=on_toggle>
+                    {move || if open.get() { "Masquer l'exploration" } else { "🔍 Explorer le fichier" }}
+                </button>
+                <Show when=move || open.get() fallback=|| view! { }>
+                    {move || loading.get().then(|| view! { <div class="explorer-status">"Analyse des colonnes..."</div> })}
+                    {move || error.get().map(|e| view! { <div class="error-message">{e}</div> })}
+                    {move || profile.get().map(|p| view! {
+                        <div class="explorer-status">
+                            {p.columns.len()} " colonne(s), " {p.row_count} " ligne(s)"
+                        </div>
+                        <table class="explorer-table">
+                            <thead>
+                                <tr>
+                                    <th>"Colonne"</th>
+                                    <th>"Vides"</th>
+                                    <th>"Valeurs distinctes"</th>
+                                    <th>"Longueur"</th>
+                                    <th>"Formats"</th>
+                                    <th>"Exemples"</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {p.columns.into_iter().map(|c| {
+                                    let note = column_note(&c);
+                                    view! {
+                                        <tr class:explorer-empty=c.is_empty()>
+                                            <td>
+                                                {c.name.clone()}
+                                                {note.map(|n| view! { <span class="explorer-note">{n}</span> })}
+                                            </td>
+                                            <td>{format!("{:.0} %", c.null_rate * 100.0)}</td>
+                                            <td>{c.distinct}</td>
+                                            <td>{if c.is_empty() { "—".to_string() } else { format!("{}–{}", c.min_length, c.max_length) }}</td>
+                                            <td>
+                                                {c.patterns.iter().map(|p| view! {
+                                                    <code>{p.pattern.clone()}</code>
+                                                    " ×" {p.count} " "
+                                                }).collect_view()}
+                                            </td>
+                                            <td>{c.samples.join(", ")}</td>
+                                        </tr>
+                                    }
+                                }).collect_view()}
+                            </tbody>
+                        </table>
+                    })}
+                </Show>
+            </div>
+        </Show>
+    }
+}
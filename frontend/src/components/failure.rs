@@ -0,0 +1,52 @@
+//! Écran d'échec quand aucune ligne du fichier n'est valide.
+//!
+//! Remplace l'aperçu vide par les causes d'erreur les plus fréquentes et
+//! les pistes de correction renvoyées par le backend (statut `all_invalid`).
+
+use leptos::*;
+
+use crate::services::FailureReport;
+use crate::LogEntry;
+
+#[component]
+pub fn FailureScreen(
+    /// Diagnostic du backend (None tant que l'upload n'a pas échoué)
+    failure: RwSignal<Option<FailureReport>>,
+    /// Set logs signal (vidé pour revenir à la zone de dépôt)
+    set_logs: WriteSignal<Vec<LogEntry>>,
+) -> impl IntoView {
+    let on_retry = move |_| {
+        failure.set(None);
+        set_logs.set(vec![]);
+    };
+
+    view! {
+        {move || failure.get().map(|report| view! {
+            <div class="failure-screen" role="alert">
+                <div class="failure-title">"❌ Aucune ligne valide dans ce fichier"</div>
+                {(!report.top_causes.is_empty()).then(|| view! {
+                    <div class="failure-section">
+                        <div class="failure-subtitle">"Erreurs les plus fréquentes"</div>
+                        <ul>
+                            {report.top_causes.iter().map(|c| view! {
+                                <li>
+                                    <code>{c.message.clone()}</code>
+                                    " — " {c.count} " ligne(s)"
+                                </li>
+                            }).collect_view()}
+                        </ul>
+                    </div>
+                })}
+                <div class="failure-section">
+                    <div class="failure-subtitle">"Que vérifier ?"</div>
+                    <ul>
+                        {report.suggestions.iter().map(|s| view! { <li>{s.label()}</li> }).collect_view()}
+                    </ul>
+                </div>
+                <button class="btn btn-secondary" on:click=on_retry>
+                    "Réessayer avec un autre fichier"
+                </button>
+            </div>
+        })}
+    }
+}
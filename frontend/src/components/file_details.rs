@@ -0,0 +1,99 @@
+//! Carte « Détails du fichier » au-dessus de l'aperçu.
+//!
+//! Encodage, séparateur, nombre de lignes et colonnes détectés par le
+//! backend, plus le modèle utilisé : une détection ratée (accents cassés,
+//! une seule colonne) se voit avant de parcourir les œuvres. Les colonnes
+//! vides ou aux valeurs presque toutes différentes sont signalées.
+
+use leptos::*;
+
+use crate::services::{CsvInfo, ResponseMetadata};
+
+/// Fiabilité d'encodage en dessous de laquelle la détection est signalée (comme le backend)
+const LOW_ENCODING_CONFIDENCE: f32 = 0.9;
+
+/// Métadonnées du fichier transformé
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileDetails {
+    pub csv_info: CsvInfo,
+    /// Modèle de transformation utilisé, en clair
+    pub template: String,
+}
+
+impl FileDetails {
+    pub fn from_metadata(metadata: &ResponseMetadata) -> Self {
+        let template = match (&metadata.matrix_id, &metadata.new_template, &metadata.matrix_engine) {
+            (Some(id), _, _) if metadata.cached => format!("Modèle en cache : {}", id),
+            (_, Some(new_template), _) => format!("Nouveau modèle : {}", new_template.suggested_name),
+            (_, _, Some(engine)) => format!("Généré par : {}", engine.label()),
+            (Some(id), _, _) => id.clone(),
+            (None, _, _) => "Aucun (enregistrements MIDDS déjà à plat)".to_string(),
+        };
+        Self { csv_info: metadata.csv_info.clone(), template }
+    }
+}
+
+/// Séparateur lisible : « Point-virgule (;) »
+fn delimiter_label(delimiter: &str) -> String {
+    match delimiter {
+        ";" => "Point-virgule (;)".to_string(),
+        "," => "Virgule (,)".to_string(),
+        "\t" => "Tabulation".to_string(),
+        "|" => "Barre verticale (|)".to_string(),
+        "\0" => "Aucun (JSON)".to_string(),
+        other => format!("« {} »", other),
+    }
+}
+
+#[component]
+pub fn FileDetailsCard(details: RwSignal<Option<FileDetails>>) -> impl IntoView {
+    move || details.get().map(|FileDetails { csv_info, template }| {
+        let low_confidence = csv_info.encoding_confidence
+            .filter(|c| *c < LOW_ENCODING_CONFIDENCE);
+        let encoding = match low_confidence {
+            Some(confidence) => format!("{} (fiabilité {:.0} %)", csv_info.encoding, confidence * 100.0),
+            None => csv_info.encoding.clone(),
+        };
+        let single_column = csv_info.columns.len() <= 1;
+        let empty = csv_info.report.empty_columns().join(", ");
+        let distinct = csv_info.report.high_cardinality_columns().join(", ");
+
+        view! {
+            <details class="file-details" open>
+                <summary>"📄 Détails du fichier"</summary>
+                <dl>
+                    <dt>"Encodage"</dt>
+                    <dd class:detail-warning=low_confidence.is_some()>{encoding}</dd>
+                    <dt>"Séparateur"</dt>
+                    <dd class:detail-warning=single_column>{delimiter_label(&csv_info.delimiter)}</dd>
+                    <dt>"Lignes"</dt>
+                    <dd>{csv_info.row_count}</dd>
+                    <dt>{format!("Colonnes ({})", csv_info.columns.len())}</dt>
+                    <dd>{csv_info.columns.join(", ")}</dd>
+                    {(!empty.is_empty()).then(|| view! {
+                        <dt>"Colonnes vides"</dt>
+                        <dd class="detail-warning">{empty}</dd>
+                    })}
+                    {(!distinct.is_empty()).then(|| view! {
+                        <dt>"Valeurs presque toutes différentes"</dt>
+                        <dd>{distinct}</dd>
+                    })}
+                    <dt>"Modèle"</dt>
+                    <dd>{template}</dd>
+                </dl>
+            </details>
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delimiter_label() {
+        assert_eq!(delimiter_label(";"), "Point-virgule (;)");
+        assert_eq!(delimiter_label("\t"), "Tabulation");
+        assert_eq!(delimiter_label("#"), "« # »");
+    }
+}
@@ -1,6 +1,8 @@
 //! Footer component
 
 use leptos::*;
+use leptos_router::A;
+use crate::services::download_fees_csv;
 
 #[component]
 pub fn Footer() -> impl IntoView {
@@ -8,6 +10,9 @@ pub fn Footer() -> impl IntoView {
         <footer>
             <div>"Copyright © 2025 Allfeat • Powered by " <span class="rust-badge">"🦀 Rust + Leptos"</span></div>
             <div class="footer-links">
+                <A href="/modeles" class="footer-link">
+                    "Modèles"
+                </A>
                 <a href="https://t.me/Allfeat_fndn" class="footer-link" target="_blank">
                     "Telegram"
                 </a>
@@ -17,6 +22,19 @@ pub fn Footer() -> impl IntoView {
                 <a href="https://github.com/allfeat" class="footer-link" target="_blank">
                     "GitHub"
                 </a>
+                <a
+                    href="#"
+                    class="footer-link"
+                    title="Frais payés par soumission, enregistrés dans ce navigateur"
+                    on:click=move |ev| {
+                        ev.prevent_default();
+                        if let Err(e) = download_fees_csv() {
+                            log::error!("❌ Export des frais : {}", e);
+                        }
+                    }
+                >
+                    "Export des frais"
+                </a>
             </div>
         </footer>
     }
@@ -1,6 +1,8 @@
 use leptos::*;
 use crate::services::wallet::PolkadotWallet;
 use crate::services::blockchain::get_wallet_balance;
+use crate::LogEntry;
+use super::backend_settings::BackendSettings;
 
 #[component]
 pub fn Header(
@@ -8,9 +10,20 @@ pub fn Header(
     wallet_address: ReadSignal<Option<String>>,
     set_wallet_connected: WriteSignal<bool>,
     set_wallet_address: WriteSignal<Option<String>>,
+    /// Wallet oublié après inactivité, à reconnecter
+    wallet_locked: RwSignal<bool>,
+    /// Pour rouvrir le flux de logs quand l'URL du backend change
+    set_logs: WriteSignal<Vec<LogEntry>>,
 ) -> impl IntoView {
     // Balance state
     let (balance, set_balance) = create_signal(None::<String>);
+
+    // Plus de solde affiché une fois le wallet oublié
+    create_effect(move |_| {
+        if !wallet_connected.get() {
+            set_balance.set(None);
+        }
+    });
     
     // Handler pour connexion wallet
     let on_wallet_click = move |_| {
@@ -23,6 +36,7 @@ pub fn Header(
                         log::info!("✅ Wallet connected: {}", account.address);
                         set_wallet_connected.set(true);
                         set_wallet_address.set(Some(account.address.clone()));
+                        wallet_locked.set(false);
                         
                         // Fetch balance
                         match get_wallet_balance(&account.address).await {
@@ -59,9 +73,12 @@ pub fn Header(
                 </span>
             </div>
             <div class="header-right">
+                <BackendSettings set_logs=set_logs/>
                 <div 
                     class="wallet-status" 
                     class:connected=move || wallet_connected.get()
+                    class:locked=move || wallet_locked.get()
+                    title=move || wallet_locked.get().then_some("Session verrouillée après inactivité")
                     on:click=on_wallet_click
                     style="cursor: pointer;"
                 >
@@ -69,6 +86,8 @@ pub fn Header(
                     <span id="walletText">
                         {move || if let Some(addr) = wallet_address.get() {
                             format!("{}...{}", &addr[0..6.min(addr.len())], &addr[addr.len().saturating_sub(4)..])
+                        } else if wallet_locked.get() {
+                            "🔒 Verrouillé • Reconnecter".to_string()
                         } else {
                             "Connect Wallet".to_string()
                         }}
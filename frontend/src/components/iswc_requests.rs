@@ -0,0 +1,47 @@
+//! Demande d'attribution d'ISWC pour les œuvres qui n'en ont pas.
+//!
+//! Ces œuvres ne peuvent pas être enregistrées : le fichier CSV généré par
+//! `GET /api/v1/jobs/{id}/iswc-requests` (titre, ayants droit, IPI, rôle, part)
+//! est à transmettre à la société de gestion pour obtenir les codes.
+
+use leptos::*;
+
+use crate::services::iswc_requests_url;
+use crate::backend_url;
+
+#[component]
+pub fn IswcRequests(
+    job_id: RwSignal<Option<String>>,
+    missing_iswc: RwSignal<usize>,
+) -> impl IntoView {
+    let href = move || {
+        job_id.get()
+            .map(|id| iswc_requests_url(&id, &backend_url()))
+            .unwrap_or_default()
+    };
+
+    let visible = move || job_id.get().is_some() && missing_iswc.get() > 0;
+
+    view! {
+        <Show
+            when=visible
+            fallback=|| view! { }
+        >
+            <section class="iswc-requests" aria-labelledby="iswc-requests-title">
+                <div class="preview-title" id="iswc-requests-title">
+                    "🏷️ Œuvres sans ISWC (" {move || missing_iswc.get()} ")"
+                </div>
+                <div class="upload-hint">
+                    "Ces œuvres ne peuvent pas être enregistrées sans ISWC. Téléchargez la demande "
+                    "d'attribution (titre, ayants droit, IPI, rôle, part) et envoyez-la à votre société "
+                    "de gestion, puis réimportez le catalogue une fois les codes attribués."
+                </div>
+                <div class="preflight-actions">
+                    <a class="btn btn-secondary" href=href download="">
+                        "Télécharger la demande d'attribution (CSV)"
+                    </a>
+                </div>
+            </section>
+        </Show>
+    }
+}
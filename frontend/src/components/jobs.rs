@@ -0,0 +1,166 @@
+//! Tableau de bord des imports : jobs en cours et récents de ce navigateur,
+//! chacun avec ses logs et son résultat.
+
+use leptos::*;
+use gloo_timers::future::TimeoutFuture;
+use crate::{backend_url, LogEntry, PreviewItem};
+use crate::services::{fetch_job_events, CatalogDiff, FailureReport, JobRecord, JobStatus, NewTemplate, RoleReport, SkippedRow};
+use super::file_details::FileDetails;
+
+/// Intervalle de relève des événements d'un job en cours
+const POLL_INTERVAL_MS: u32 = 1_000;
+
+/// Signaux d'affichage du job ouvert : panneau de logs, aperçu et encarts
+#[derive(Clone, Copy)]
+pub struct JobView {
+    pub set_preview_data: WriteSignal<Option<Vec<PreviewItem>>>,
+    pub set_musical_works_json: WriteSignal<Option<serde_json::Value>>,
+    pub set_file_sha256: WriteSignal<Option<String>>,
+    pub set_catalog_period: WriteSignal<Option<String>>,
+    pub set_logs: WriteSignal<Vec<LogEntry>>,
+    /// Job affiché et ses lignes ignorées (complétables ensuite)
+    pub job_id: RwSignal<Option<String>>,
+    pub skipped_rows: RwSignal<Vec<SkippedRow>>,
+    /// Mode révision : diff avec la version précédente et œuvres complètes
+    pub catalog_diff: RwSignal<Option<CatalogDiff>>,
+    pub all_works: RwSignal<Vec<serde_json::Value>>,
+    pub changes_only: RwSignal<bool>,
+    /// Diagnostic quand aucune ligne n'est valide
+    pub failure: RwSignal<Option<FailureReport>>,
+    /// Modèle créé par l'IA, à nommer
+    pub new_template: RwSignal<Option<NewTemplate>>,
+    /// Œuvres sans ISWC, exportables en demande d'attribution
+    pub missing_iswc: RwSignal<usize>,
+    /// Métadonnées du fichier affichées au-dessus de l'aperçu
+    pub file_details: RwSignal<Option<FileDetails>>,
+    /// Ayants droit par rôle et œuvres sans compositeur
+    pub role_report: RwSignal<Option<RoleReport>>,
+}
+
+impl JobView {
+    /// Vide l'affichage avant d'ouvrir un autre job (ou un nouvel import)
+    pub fn clear(&self) {
+        self.set_preview_data.set(None);
+        self.set_musical_works_json.set(None);
+        self.set_file_sha256.set(None);
+        self.set_catalog_period.set(None);
+        self.set_logs.set(Vec::new());
+        self.job_id.set(None);
+        self.skipped_rows.set(Vec::new());
+        self.catalog_diff.set(None);
+        self.all_works.set(Vec::new());
+        self.changes_only.set(true);
+        self.failure.set(None);
+        self.new_template.set(None);
+        self.missing_iswc.set(0);
+        self.file_details.set(None);
+        self.role_report.set(None);
+    }
+}
+
+/// Modifie l'entrée d'un job (sans effet si elle a été retirée du tableau de bord)
+pub(crate) fn update_job(jobs: RwSignal<Vec<JobRecord>>, id: &str, f: impl FnOnce(&mut JobRecord)) {
+    jobs.update(|jobs| {
+        if let Some(job) = jobs.iter_mut().find(|j| j.id == id) {
+            f(job);
+        }
+    });
+}
+
+/// Relève l'avancement d'un job tant qu'il tourne côté backend
+pub(crate) async fn watch_job(id: String, jobs: RwSignal<Vec<JobRecord>>) {
+    loop {
+        TimeoutFuture::new(POLL_INTERVAL_MS).await;
+        let running = jobs
+            .try_with_untracked(|jobs| jobs.iter().any(|j| j.id == id && j.status == JobStatus::Running))
+            .unwrap_or(false);
+        if !running {
+            break;
+        }
+        // Avant la lecture du fichier par le backend, le job n'existe pas encore (404)
+        if let Ok(events) = fetch_job_events(&id, &backend_url()).await {
+            update_job(jobs, &id, |job| job.apply_events(&events));
+        }
+    }
+}
+
+/// Imports en cours et récents : statut, avancement, ouverture des logs et du résultat
+#[component]
+pub fn JobDashboard(
+    jobs: RwSignal<Vec<JobRecord>>,
+    /// Job affiché dans le panneau de logs et l'aperçu
+    open_job: RwSignal<Option<String>>,
+    on_open: Callback<String>,
+    /// Revenir à la zone d'upload, les autres jobs continuent
+    on_new: Callback<()>,
+) -> impl IntoView {
+    // Filtre de l'historique par période comptable (`2025-Q1`, ou une année)
+    let period_filter = create_rw_signal(String::new());
+    let has_periods = move || jobs.with(|jobs| jobs.iter().any(|j| j.period.is_some()));
+
+    view! {
+        <Show when=move || !jobs.with(|jobs| jobs.is_empty()) fallback=|| view! { }>
+            <div class="job-dashboard">
+                <div class="logs-header">
+                    <span class="logs-title">
+                        "Imports "
+                        {move || {
+                            let running = jobs.with(|jobs| jobs.iter().filter(|j| j.status.is_active()).count());
+                            if running > 0 { format!("({} en cours)", running) } else { String::new() }
+                        }}
+                    </span>
+                    <Show when=has_periods fallback=|| view! { }>
+                        <input
+                            class="job-period-filter"
+                            type="search"
+                            placeholder="Période (2025-Q1, 2025)"
+                            aria-label="Filtrer par période comptable"
+                            prop:value=move || period_filter.get()
+                            on:input=move |ev| period_filter.set(event_target_value(&ev))
+                        />
+                    </Show>
+                    <button
+                        class="logs-clear"
+                        class:active=move || open_job.get().is_none()
+                        on:click=move |_| on_new.call(())
+                    >
+                        "＋ Nouvel import"
+                    </button>
+                </div>
+                <For
+                    each=move || {
+                        let filter = period_filter.get();
+                        jobs.get().into_iter().filter(|job| job.matches_period(&filter)).collect::<Vec<_>>()
+                    }
+                    key=|job| (job.id.clone(), job.status, job.steps_done, job.period.clone())
+                    children=move |job| {
+                        let id = job.id.clone();
+                        let id_for_open = job.id.clone();
+                        let progress = job.progress_percent();
+                        view! {
+                            <div class="job-row" class:open=move || open_job.get().as_deref() == Some(id.as_str())>
+                                <span class="job-file" title=job.id.clone()>
+                                    {job.file_name.clone()}
+                                    {job.period.clone().map(|period| view! { <span class="job-period">{period}</span> })}
+                                </span>
+                                <span
+                                    class=format!("job-status {}", job.status.css_class())
+                                    title=job.error.clone().unwrap_or_default()
+                                >
+                                    {job.status.label()}
+                                </span>
+                                <div class="progress-bar job-progress">
+                                    <div class="progress-fill" style=format!("width: {}%", progress)></div>
+                                </div>
+                                <span class="job-time">{job.started_at.clone()}</span>
+                                <button class="logs-clear" on:click=move |_| on_open.call(id_for_open.clone())>
+                                    "Ouvrir"
+                                </button>
+                            </div>
+                        }
+                    }
+                />
+            </div>
+        </Show>
+    }
+}
@@ -1,6 +1,6 @@
 //! Real-time log streaming using Server-Sent Events (SSE).
 //!
-//! Connects to the backend's `/api/logs` endpoint and displays
+//! Connects to the backend's `/api/v1/logs` endpoint and displays
 //! processing logs in real-time with auto-scroll support.
 
 use leptos::*;
@@ -8,7 +8,12 @@ use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use web_sys::{EventSource, MessageEvent};
 
-use crate::{LogEntry, LogLevel, BACKEND_URL, MAX_LOG_ENTRIES};
+use crate::{backend_url, LogEntry, LogLevel, LogStage, MAX_LOG_ENTRIES};
+
+thread_local! {
+    /// Current SSE connection, closed when the backend URL changes
+    static EVENT_SOURCE: std::cell::RefCell<Option<EventSource>> = const { std::cell::RefCell::new(None) };
+}
 
 /// Request animation frame helper for smooth scrolling
 fn request_animation_frame(f: impl FnOnce() + 'static) {
@@ -20,28 +25,44 @@ fn request_animation_frame(f: impl FnOnce() + 'static) {
     closure.forget();
 }
 
-/// Parse SSE log entry into our LogEntry format
+/// Parse SSE log entry into our LogEntry format.
+/// Older backends send neither `timestamp` nor `stage`: reception time is used.
 fn parse_sse_log(json: &str) -> Option<LogEntry> {
     let value: serde_json::Value = serde_json::from_str(json).ok()?;
     let level = match value.get("level")?.as_str()? {
         "success" => LogLevel::Success,
         "warning" => LogLevel::Warning,
         "error" => LogLevel::Error,
+        "debug" => LogLevel::Debug,
         _ => LogLevel::Info,
     };
     let message = value.get("message")?.as_str()?.to_string();
-    
-    Some(LogEntry {
-        level,
-        message,
-        timestamp: chrono::Local::now().format("%H:%M:%S").to_string(),
-    })
+
+    let mut entry = LogEntry::new(level, message);
+    if let Some(timestamp) = value.get("timestamp").and_then(|t| t.as_str()) {
+        if chrono::DateTime::parse_from_rfc3339(timestamp).is_ok() {
+            entry.timestamp = timestamp.to_string();
+        }
+    }
+    entry.stage = value.get("stage")
+        .and_then(|s| serde_json::from_value::<LogStage>(s.clone()).ok());
+    Some(entry)
+}
+
+/// Entries ordered by timestamp (stable: same-time entries keep arrival order)
+fn ordered_logs(mut logs: Vec<LogEntry>) -> Vec<LogEntry> {
+    logs.sort_by_key(|entry| entry.time());
+    logs
 }
 
-/// Start SSE connection to receive real-time logs
-/// Should be called ONCE at app startup
+/// Start SSE connection to receive real-time logs.
+/// Called at app startup, and again when the backend URL changes
+/// (the previous connection is closed).
 pub fn init_sse_logs(set_logs: WriteSignal<Vec<LogEntry>>) {
-    let sse_url = format!("{}/api/logs", BACKEND_URL);
+    if let Some(previous) = EVENT_SOURCE.with(|es| es.borrow_mut().take()) {
+        previous.close();
+    }
+    let sse_url = format!("{}/api/v1/logs", backend_url());
     
     let event_source = match EventSource::new(&sse_url) {
         Ok(es) => es,
@@ -85,11 +106,10 @@ pub fn init_sse_logs(set_logs: WriteSignal<Vec<LogEntry>>) {
     event_source.set_onerror(Some(onerror.as_ref().unchecked_ref()));
     onerror.forget();
     
-    // Store event_source to prevent it from being dropped
-    // We leak it intentionally as it should live for the app's lifetime
-    std::mem::forget(event_source);
+    // Keep the connection alive until the next backend change
+    EVENT_SOURCE.with(|es| *es.borrow_mut() = Some(event_source));
     
-    log::info!("📡 SSE log stream initialized");
+    log::info!("📡 SSE log stream initialized ({})", sse_url);
 }
 
 /// Real-time logs panel component (display only, SSE is initialized elsewhere)
@@ -99,6 +119,8 @@ pub fn LogsPanel(
     logs: ReadSignal<Vec<LogEntry>>,
     /// Set logs signal (for clearing)
     set_logs: WriteSignal<Vec<LogEntry>>,
+    /// Logs détaillés : affiche les entrées Debug et les demande aux prochains uploads
+    verbose_logs: RwSignal<bool>,
 ) -> impl IntoView {
     // Reference to the logs content div for auto-scroll
     let logs_container = create_node_ref::<leptos::html::Div>();
@@ -121,28 +143,63 @@ pub fn LogsPanel(
         <div class="logs-panel">
             <div class="logs-header">
                 <span class="logs-title">"📋 Processing Logs"</span>
-                <button 
-                    class="logs-clear"
-                    on:click=move |_| set_logs.set(vec![])
-                >
-                    "Clear"
-                </button>
+                <div class="logs-actions">
+                    <button
+                        class="logs-clear"
+                        class:active=move || verbose_logs.get()
+                        aria-pressed=move || verbose_logs.get().to_string()
+                        title="Afficher le détail ligne par ligne (demandé au backend pour les prochains uploads)"
+                        on:click=move |_| verbose_logs.update(|v| *v = !*v)
+                    >
+                        "🔍 Détails"
+                    </button>
+                    <button 
+                        class="logs-clear"
+                        on:click=move |_| set_logs.set(vec![])
+                    >
+                        "Clear"
+                    </button>
+                </div>
             </div>
             <div class="logs-content" node_ref=logs_container>
                 <For
-                    each=move || logs.get().into_iter().enumerate()
-                    key=|(i, _)| *i
-                    children=move |(_, entry)| {
+                    each=move || {
+                        // Mark the first entry of each stage to draw a group header
+                        let mut previous = None;
+                        let verbose = verbose_logs.get();
+                        ordered_logs(logs.get())
+                            .into_iter()
+                            .filter(|entry| verbose || entry.level != LogLevel::Debug)
+                            .map(|entry| {
+                                let starts_stage = entry.stage.is_some() && entry.stage != previous;
+                                previous = entry.stage;
+                                (starts_stage, entry)
+                            })
+                            .enumerate()
+                            .collect::<Vec<_>>()
+                    }
+                    key=|(i, (starts_stage, entry))| (*i, *starts_stage, entry.timestamp.clone())
+                    children=move |(_, (starts_stage, entry))| {
                         let level_class = match entry.level {
                             LogLevel::Success => "log-success",
                             LogLevel::Warning => "log-warning",
                             LogLevel::Error => "log-error",
+                            LogLevel::Debug => "log-debug",
                             _ => "log-info",
                         };
+                        let stage_class = entry.stage.map(|s| s.css_class()).unwrap_or("");
+                        let header = entry.stage
+                            .filter(|_| starts_stage)
+                            .map(|stage| view! {
+                                <div class=format!("log-stage-header {}", stage.css_class())>
+                                    {stage.label()}
+                                </div>
+                            });
                         
                         view! {
-                            <div class=format!("log-entry {}", level_class)>
-                                <span class="log-time">"[" {entry.timestamp.clone()} "] "</span>
+                            {header}
+                            <div class=format!("log-entry {} {}", level_class, stage_class)>
+                                <span class="log-time">"[" {entry.time_label()} "] "</span>
                                 {entry.message.clone()}
                             </div>
                         }
@@ -153,3 +210,34 @@ pub fn LogsPanel(
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sse_log_with_stage_and_timestamp() {
+        let entry = parse_sse_log(
+            r#"{"level":"success","message":"ok","indent":0,"timestamp":"2025-01-02T10:00:00.000Z","stage":"template"}"#,
+        ).unwrap();
+        assert_eq!(entry.level, LogLevel::Success);
+        assert_eq!(entry.timestamp, "2025-01-02T10:00:00.000Z");
+        assert_eq!(entry.stage, Some(LogStage::Template));
+
+        // Older backend: no timestamp nor stage
+        let legacy = parse_sse_log(r#"{"level":"info","message":"hello"}"#).unwrap();
+        assert!(legacy.time().is_some());
+        assert!(legacy.stage.is_none());
+    }
+
+    #[test]
+    fn test_ordered_logs_by_timestamp() {
+        let mut late = LogEntry::new(LogLevel::Info, "late");
+        late.timestamp = "2025-01-02T10:00:02.000Z".to_string();
+        let mut early = LogEntry::new(LogLevel::Info, "early");
+        early.timestamp = "2025-01-02T11:00:01.000+01:00".to_string();
+
+        let ordered = ordered_logs(vec![late, early]);
+        assert_eq!(ordered[0].message, "early");
+    }
+}
@@ -9,26 +9,65 @@
 //!
 //! # Feature Components
 //! - [`UploadSection`] - CSV file upload with drag & drop
+//! - [`JobDashboard`] - Active and recent uploads, each with its logs and result
+//! - [`FileDetailsCard`] - Detected encoding, delimiter, rows, columns and template
 //! - [`PreviewSection`] - Transaction preview before submission
 //! - `PreviewDetail` - Detailed view of a single musical work
+//! - [`PrintableView`] - Print layout of selected works or of the batch summary
+//! - [`CatalogDiffBanner`] - Review mode: changes since the previous catalog version
+//! - [`FailureScreen`] - Top error causes when no row is valid
+//! - [`SkippedRowsFix`] - Completion of rows skipped for one missing field
+//! - [`IswcRequests`] - ISWC allocation request export for works lacking a code
+//! - [`RoleStats`] - Creators per role and works without composer
+//! - [`ColumnExplorer`] - Source column statistics ("Explorer le fichier")
+//! - [`TemplateNameDialog`] - Naming prompt for a template created by the AI
+//! - [`TemplatesPage`] - Saved templates list with renaming (`/modeles`)
 //! - [`LogsPanel`] - Real-time processing logs (SSE)
 //! - [`ProgressSection`] - Transaction progress indicator
+//! - [`DemoGuide`] - Step annotations of the guided demo (example file)
+//! - [`ShortcutHelp`] - Keyboard shortcut overlay (`?`), fed by [`ShortcutRegistry`]
 
 mod header;
 mod hero;
 mod upload;
+mod jobs;
+mod file_details;
 mod preview;
 mod preview_detail;
+mod printable;
 mod progress;
 mod footer;
 mod logs;
+mod skipped_rows;
+mod iswc_requests;
+mod role_stats;
+mod catalog_diff;
+mod failure;
+mod explorer;
+mod templates;
+mod backend_settings;
+mod demo_guide;
+mod shortcuts;
 
 pub use header::*;
 pub use hero::*;
 pub use upload::*;
+pub use jobs::*;
+pub use file_details::*;
 pub use preview::*;
 pub use preview_detail::*;
+pub use printable::*;
 pub use progress::*;
 pub use footer::*;
 pub use logs::*;
+pub use skipped_rows::*;
+pub use iswc_requests::*;
+pub use role_stats::*;
+pub use catalog_diff::*;
+pub use failure::*;
+pub use explorer::*;
+pub use templates::*;
+pub use backend_settings::*;
+pub use demo_guide::*;
+pub use shortcuts::*;
 
@@ -1,8 +1,45 @@
 //! Composant Preview pour afficher et approuver les transactions
 
 use leptos::*;
-use crate::{PreviewItem, WorkDetail, LogEntry, LogLevel};
-use crate::services::BlockchainService;
+use wasm_bindgen::JsCast;
+use crate::{PreviewItem, PrintableView, WorkDetail, LogEntry, LogLevel};
+use crate::services::{
+    batch_remark, download_fees_csv, download_review_batch, load_work_index, record_fee, remember_registered, ReviewBatch,
+    BatchStrategy, BlockchainService, FeeRecord, SubmissionKind, SubmissionOutcome, SubmissionPlan, SubmissionResult,
+    ShortcutAction, TxStatus, DEFAULT_BATCH_SIZE, MAX_BATCH_LABEL_LEN, MAX_RETRY_ROUNDS,
+};
+use super::shortcuts::use_shortcut;
+
+/// Donne le focus au champ de recherche de l'aperçu (false s'il n'est pas affiché)
+fn focus_search() -> bool {
+    let input = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.get_element_by_id("preview-search"))
+        .and_then(|input| input.dyn_into::<web_sys::HtmlInputElement>().ok());
+    match input {
+        Some(input) => {
+            let _ = input.focus();
+            input.select();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Enregistre les frais d'un envoi arrivé on-chain (pour l'export comptable)
+fn record_submission_fee(result: &SubmissionResult, works_count: usize, wallet: Option<String>) {
+    if result.extrinsic_hashes.is_empty() && result.fee.is_none() {
+        return;
+    }
+    record_fee(FeeRecord {
+        date: chrono::Local::now().to_rfc3339(),
+        works_count,
+        updates_count: result.work_results.iter().filter(|r| r.kind == SubmissionKind::Update).count(),
+        fee: result.fee.clone(),
+        tx_hashes: result.extrinsic_hashes.clone(),
+        wallet: wallet.unwrap_or_default(),
+    });
+}
 
 #[component]
 pub fn PreviewSection(
@@ -14,9 +51,64 @@ pub fn PreviewSection(
     set_is_processing: WriteSignal<bool>,
     #[prop(optional)] set_preview_data: Option<WriteSignal<Option<Vec<PreviewItem>>>>,
     #[prop(optional)] set_musical_works_json: Option<WriteSignal<Option<serde_json::Value>>>,
+    #[prop(optional)] file_sha256: Option<ReadSignal<Option<String>>>,
+    /// Période comptable du catalogue, reprise dans le lot on-chain et les récapitulatifs
+    #[prop(optional)] catalog_period: Option<ReadSignal<Option<String>>>,
 ) -> impl IntoView {
     // État pour tracker quel item est expanded
     let (expanded_index, set_expanded_index) = create_signal(None::<usize>);
+    // Filtre de l'aperçu (titre ou ISWC)
+    let search = create_rw_signal(String::new());
+    // Œuvres cochées pour la version imprimable (aucune = récapitulatif du lot)
+    let selected = create_rw_signal(Vec::<usize>::new());
+    let printing = create_rw_signal(false);
+    create_effect(move |_| {
+        data.track();
+        selected.set(Vec::new());
+    });
+    // Fenêtre de confirmation avant signature, avec libellé de lot optionnel
+    let confirming = create_rw_signal(false);
+    let batch_label = create_rw_signal(String::new());
+    // Créations et mises à jour d'œuvres déjà enregistrées, calculées à l'ouverture de la confirmation
+    let plan = create_rw_signal(SubmissionPlan::default());
+    // Regroupement des œuvres en transactions (aussi utilisé pour les nouveaux essais)
+    let strategy = create_rw_signal(BatchStrategy::default());
+    // Résultat par œuvre du dernier envoi (pour réessayer les échecs)
+    let outcome = create_rw_signal(None::<SubmissionOutcome>);
+    let retrying = create_rw_signal(false);
+    // État on-chain du dernier envoi (bloc à revérifier si non finalisé)
+    let tx_status = create_rw_signal(None::<TxStatus>);
+    let tx_block = create_rw_signal(None::<String>);
+    let checking = create_rw_signal(false);
+
+    // Mémorise l'état du dernier envoi et prévient s'il n'est pas finalisé
+    let track_status = move |result: &SubmissionResult| {
+        tx_status.set(result.status);
+        tx_block.set(result.block_hash.clone());
+        if let Some(status) = result.status.filter(|s| *s != TxStatus::Finalized) {
+            set_logs.update(|logs| {
+                logs.push(LogEntry::new(LogLevel::Warning, format!("⏱️ {}", status.label())));
+            });
+        }
+    };
+
+    let on_check_again = move |_| {
+        let Some(block_hash) = tx_block.get_untracked() else { return };
+        checking.set(true);
+        spawn_local(async move {
+            match BlockchainService::new().check_status(&block_hash).await {
+                Ok(status) => {
+                    tx_status.set(Some(status));
+                    let level = if status == TxStatus::Finalized { LogLevel::Success } else { LogLevel::Warning };
+                    set_logs.update(|logs| logs.push(LogEntry::new(level, format!("🔎 {}", status.label()))));
+                }
+                Err(e) => {
+                    set_logs.update(|logs| logs.push(LogEntry::new(LogLevel::Error, format!("❌ {}", e))));
+                }
+            }
+            checking.set(false);
+        });
+    };
     
     // Handler pour annuler - reset tout et revient à la zone de drop
     let on_cancel = move |_| {
@@ -33,75 +125,130 @@ pub fn PreviewSection(
         set_is_processing.set(false);
     };
     
+    // Ouvre la confirmation (le wallet doit être connecté)
+    let on_sign_and_send = move || {
+        if !wallet_connected.get() {
+            log::warn!("⚠️ Wallet non connecté");
+            set_logs.update(|logs| {
+                logs.push(LogEntry::new(
+                    LogLevel::Warning,
+                    "Veuillez connecter votre wallet avant de signer",
+                ));
+            });
+            return;
+        }
+        let works = musical_works_json.get_untracked()
+            .and_then(|json| json.as_array().cloned())
+            .unwrap_or_default();
+        plan.set(SubmissionPlan::new(&works, &load_work_index()));
+        confirming.set(true);
+    };
+
     // Handler pour signer et envoyer
-    let on_sign_and_send = move |_| {
-        let works = musical_works_json.get();
+    let on_confirm = move || {
+        confirming.set(false);
+        let current_plan = plan.get_untracked();
+        let works = musical_works_json.get().map(|_| serde_json::Value::Array(current_plan.works.clone()));
+        let updates = current_plan.update_targets();
         let connected = wallet_connected.get();
         let address = wallet_address.get();
         
         if !connected {
             log::warn!("⚠️ Wallet non connecté");
             set_logs.update(|logs| {
-                logs.push(LogEntry {
-                    level: LogLevel::Warning,
-                    message: "Veuillez connecter votre wallet avant de signer".to_string(),
-                    timestamp: js_sys::Date::new_0().to_locale_time_string("fr-FR").as_string().unwrap_or_default(),
-                });
+                logs.push(LogEntry::new(
+                    LogLevel::Warning,
+                    "Veuillez connecter votre wallet avant de signer",
+                ));
             });
             return;
         }
         
+        if !current_plan.unchanged.is_empty() {
+            set_logs.update(|logs| {
+                logs.push(LogEntry::new(
+                    LogLevel::Info,
+                    format!("{} œuvre(s) déjà enregistrée(s) sans changement : non renvoyée(s)", current_plan.unchanged.len()),
+                ));
+            });
+        }
+        if current_plan.works.is_empty() {
+            return;
+        }
+
         if let Some(works_json) = works {
             log::info!("📤 Envoi des transactions...");
             set_is_processing.set(true);
+
+            let work_count = works_json.as_array().map(|a| a.len()).unwrap_or(0);
+            let sha = file_sha256.and_then(|s| s.get_untracked());
+            let period = catalog_period.and_then(|s| s.get_untracked());
+            let remark = batch_remark(&batch_label.get_untracked(), work_count, sha.as_deref(), period.as_deref());
+            if let Some(ref remark) = remark {
+                set_logs.update(|logs| {
+                    logs.push(LogEntry::new(LogLevel::Info, format!("🏷️ Lot : {}", remark)));
+                });
+            }
             
             set_logs.update(|logs| {
-                logs.push(LogEntry {
-                    level: LogLevel::Info,
-                    message: format!("Envoi de {} œuvres à la blockchain...", 
-                        works_json.as_array().map(|a| a.len()).unwrap_or(0)),
-                    timestamp: js_sys::Date::new_0().to_locale_time_string("fr-FR").as_string().unwrap_or_default(),
-                });
+                logs.push(LogEntry::new(
+                    LogLevel::Info,
+                    format!("Envoi de {} œuvres à la blockchain ({} mise(s) à jour)...", work_count, updates.len()),
+                ));
             });
             
             // Spawn async task pour envoyer à la blockchain
             spawn_local(async move {
                 let blockchain = BlockchainService::new();
                 
-                match blockchain.submit_works(works_json.clone(), address).await {
+                match blockchain.submit_works(works_json.clone(), &updates, address.clone(), remark, strategy.get_untracked()).await {
                     Ok(result) => {
+                        let works = works_json.as_array().cloned().unwrap_or_default();
+                        remember_registered(&works, &result.work_results);
+                        record_submission_fee(&result, works.len(), address);
+                        track_status(&result);
+                        outcome.set(Some(SubmissionOutcome::new(works, result.work_results.clone())));
                         if result.success {
                             let success_count = result.work_results.iter().filter(|w| w.success).count();
                             let total = result.work_results.len();
                             log::info!("✅ Transaction confirmée: {:?}", result.tx_hash);
                         set_logs.update(|logs| {
-                            logs.push(LogEntry {
-                                level: LogLevel::Success,
-                                    message: format!("✅ {}/{} œuvres enregistrées! Hash: {}", 
+                            logs.push(LogEntry::new(
+                                LogLevel::Success,
+                                format!("✅ {}/{} œuvres enregistrées! Hash: {}", 
                                         success_count, total,
                                         result.tx_hash.as_deref().unwrap_or("?")),
-                                timestamp: js_sys::Date::new_0().to_locale_time_string("fr-FR").as_string().unwrap_or_default(),
-                            });
+                            ));
                         });
+                        } else if result.work_results.iter().any(|w| w.success) {
+                            // Lots ou transactions individuelles : une partie est enregistrée
+                            let success_count = result.work_results.iter().filter(|w| w.success).count();
+                            set_logs.update(|logs| {
+                                logs.push(LogEntry::new(
+                                    LogLevel::Warning,
+                                    format!("⚠️ {}/{} œuvres enregistrées en {} transaction(s) ; premier échec : {}",
+                                            success_count, result.work_results.len(),
+                                            result.extrinsic_hashes.len(),
+                                            result.error.as_deref().unwrap_or("Erreur inconnue")),
+                                ));
+                            });
                         } else {
                             log::error!("❌ Transaction échouée: {:?}", result.error);
                             set_logs.update(|logs| {
-                                logs.push(LogEntry {
-                                    level: LogLevel::Error,
-                                    message: format!("❌ Échec: {}", result.error.as_deref().unwrap_or("Erreur inconnue")),
-                                    timestamp: js_sys::Date::new_0().to_locale_time_string("fr-FR").as_string().unwrap_or_default(),
-                                });
+                                logs.push(LogEntry::new(
+                                    LogLevel::Error,
+                                    format!("❌ Échec: {}", result.error.as_deref().unwrap_or("Erreur inconnue")),
+                                ));
                             });
                         }
                     }
                     Err(e) => {
                         log::error!("❌ Erreur blockchain: {}", e);
                         set_logs.update(|logs| {
-                            logs.push(LogEntry {
-                                level: LogLevel::Error,
-                                message: format!("❌ Erreur: {}", e),
-                                timestamp: js_sys::Date::new_0().to_locale_time_string("fr-FR").as_string().unwrap_or_default(),
-                            });
+                            logs.push(LogEntry::new(
+                                LogLevel::Error,
+                                format!("❌ Erreur: {}", e),
+                            ));
                         });
                     }
                 }
@@ -111,17 +258,143 @@ pub fn PreviewSection(
         }
     };
     
+    // Raccourcis : `/` recherche, `s` ouvre la confirmation, `v` la valide
+    use_shortcut(ShortcutAction::Search, focus_search);
+    use_shortcut(ShortcutAction::Sign, move || {
+        if confirming.get_untracked() || !wallet_connected.get_untracked() {
+            return false;
+        }
+        on_sign_and_send();
+        true
+    });
+    use_shortcut(ShortcutAction::Validate, move || {
+        if !confirming.get_untracked() {
+            return false;
+        }
+        on_confirm();
+        true
+    });
+
+    // Ré-envoie uniquement les œuvres en échec, les succès sont conservés
+    let on_retry = move |_| {
+        let Some(current) = outcome.get_untracked() else { return };
+        if !current.can_retry() || retrying.get_untracked() {
+            return;
+        }
+        let failed = current.failed_works();
+        let updates = plan.get_untracked().update_targets();
+        let address = wallet_address.get_untracked();
+        let sha = file_sha256.and_then(|s| s.get_untracked());
+        let period = catalog_period.and_then(|s| s.get_untracked());
+        let remark = batch_remark(&batch_label.get_untracked(), failed.len(), sha.as_deref(), period.as_deref());
+        let round = current.retry_rounds + 1;
+
+        set_logs.update(|logs| {
+            logs.push(LogEntry::new(
+                LogLevel::Info,
+                format!("🔁 Nouvel essai {}/{} : {} œuvre(s) en échec", round, MAX_RETRY_ROUNDS, failed.len()),
+            ));
+        });
+        retrying.set(true);
+        set_is_processing.set(true);
+
+        spawn_local(async move {
+            let blockchain = BlockchainService::new();
+            let count = failed.len();
+            match blockchain.submit_works(serde_json::Value::Array(failed.clone()), &updates, address.clone(), remark, strategy.get_untracked()).await {
+                Ok(result) => {
+                    remember_registered(&failed, &result.work_results);
+                    record_submission_fee(&result, count, address);
+                    track_status(&result);
+                    let registered = result.work_results.iter().filter(|w| w.success).count();
+                    outcome.update(|o| {
+                        if let Some(o) = o {
+                            o.apply_retry(result.work_results);
+                        }
+                    });
+                    let level = if registered == count { LogLevel::Success } else { LogLevel::Warning };
+                    set_logs.update(|logs| {
+                        logs.push(LogEntry::new(level, format!("🔁 {}/{} œuvres réessayées enregistrées", registered, count)));
+                    });
+                }
+                Err(e) => {
+                    // Le tour compte même si l'envoi n'a pas abouti
+                    outcome.update(|o| {
+                        if let Some(o) = o {
+                            o.apply_retry(Vec::new());
+                        }
+                    });
+                    set_logs.update(|logs| {
+                        logs.push(LogEntry::new(LogLevel::Error, format!("❌ Erreur: {}", e)));
+                    });
+                }
+            }
+            retrying.set(false);
+            set_is_processing.set(false);
+        });
+    };
+
+    // Mode revue : le lot relu part chez la personne qui signe
+    let on_export_batch = move |_| {
+        let works = musical_works_json.get_untracked()
+            .and_then(|json| json.as_array().cloned())
+            .unwrap_or_default();
+        let count = works.len();
+        let batch = ReviewBatch::new(works, file_sha256.and_then(|s| s.get_untracked()));
+        let entry = match download_review_batch(&batch) {
+            Ok(()) => LogEntry::new(LogLevel::Success, format!("📦 Lot exporté ({} œuvre(s)) : à déposer dans Mass Load par la personne qui signe", count)),
+            Err(e) => LogEntry::new(LogLevel::Error, format!("❌ Export du lot : {}", e)),
+        };
+        set_logs.update(|logs| logs.push(entry));
+    };
+
+    let on_export_fees = move |_| {
+        if let Err(e) = download_fees_csv() {
+            set_logs.update(|logs| logs.push(LogEntry::new(LogLevel::Error, format!("❌ Export des frais : {}", e))));
+        }
+    };
+
     view! {
         <div class="preview-section show" id="previewSection">
             <div class="preview-header">
                 <div class="preview-title">"📋 Aperçu des transactions"</div>
-                <button class="btn btn-secondary" id="cancelBtn" on:click=on_cancel>"Annuler"</button>
+                <div class="preview-actions">
+                    <button
+                        class="btn btn-secondary"
+                        on:click=move |_| printing.set(true)
+                        title="Œuvres cochées, ou récapitulatif du lot si aucune n'est cochée"
+                    >
+                        {move || match selected.get().len() {
+                            0 => "Version imprimable".to_string(),
+                            n => format!("Version imprimable ({})", n),
+                        }}
+                    </button>
+                    <button class="btn btn-secondary" id="cancelBtn" on:click=on_cancel>"Annuler"</button>
+                </div>
             </div>
             
             <div id="previewContent">
+                <input
+                    id="preview-search"
+                    class="preview-search"
+                    type="search"
+                    placeholder="Rechercher un titre ou un ISWC  ( / )"
+                    prop:value=move || search.get()
+                    on:input=move |ev| search.set(event_target_value(&ev))
+                    on:keydown=move |ev| if ev.key() == "Escape" {
+                        search.set(String::new());
+                        if let Some(input) = ev.target().and_then(|t| t.dyn_into::<web_sys::HtmlElement>().ok()) {
+                            let _ = input.blur();
+                        }
+                    }
+                />
                 <div class="preview-list" id="previewList">
                     <For
-                        each=move || data.get().unwrap_or_default().into_iter().enumerate()
+                        each=move || {
+                            let query = search.get();
+                            data.get().unwrap_or_default().into_iter().enumerate()
+                                .filter(move |(_, item)| item.matches(&query))
+                        }
                         key=|(idx, _)| *idx
                         children=move |(idx, item)| {
                             let is_expanded = move || expanded_index.get() == Some(idx);
@@ -143,9 +416,26 @@ pub fn PreviewSection(
                                     })
                             };
                             
+                            let is_selected = move || selected.get().contains(&idx);
+                            let toggle_selected = move |ev: ev::MouseEvent| {
+                                // Ne pas déplier l'œuvre en la cochant
+                                ev.stop_propagation();
+                                selected.update(|list| match list.iter().position(|&i| i == idx) {
+                                    Some(pos) => { list.remove(pos); }
+                                    None => list.push(idx),
+                                });
+                            };
+
                             view! {
                                 <div class="preview-item" class:expanded=is_expanded>
                                     <div class="preview-item-header" on:click=toggle_expand style="cursor: pointer;">
+                                        <input
+                                            type="checkbox"
+                                            class="print-select"
+                                            aria-label="Inclure dans la version imprimable"
+                                            prop:checked=is_selected
+                                            on:click=toggle_selected
+                                        />
                                         <div class="preview-item-title">
                                             {if is_expanded() { "▼ " } else { "▶ " }}
                                             {item.title.clone()}
@@ -184,16 +474,227 @@ pub fn PreviewSection(
                 <div class="preview-cost">
                     <strong>{move || data.get().map(|d| d.len()).unwrap_or(0)}</strong> " œuvres • "
                     "Coût estimé: " <strong>{move || format!("{:.2}", data.get().map(|d| d.len()).unwrap_or(0) as f32 * 0.05)}</strong> " AFT"
+                    {move || file_sha256.and_then(|s| s.get()).map(|sha| view! {
+                        <div class="file-checksum" title="Empreinte du fichier source">"SHA-256 : " {sha}</div>
+                    })}
                 </div>
+                <Show when=move || !wallet_connected.get() fallback=|| view! { }>
+                    <div class="upload-hint review-mode-hint">
+                        "Mode revue : sans wallet, exportez le lot pour le confier à la personne qui signe."
+                    </div>
+                </Show>
+                <button
+                    class="btn btn-secondary"
+                    on:click=on_export_batch
+                    title="Œuvres prêtes à signer, à déposer dans Mass Load depuis le poste qui détient le wallet"
+                >
+                    "Exporter le lot (JSON)"
+                </button>
                 <button 
                     class="btn btn-primary" 
                     id="signAndSendBtn"
-                    on:click=on_sign_and_send
+                    on:click=move |_| on_sign_and_send()
                     disabled=move || !wallet_connected.get()
                 >
                     {move || if wallet_connected.get() { "Signer & Envoyer" } else { "Connectez votre wallet" }}
                 </button>
             </div>
+
+            {move || outcome.get().map(|o| {
+                let total = o.works.len();
+                let registered = o.success_count();
+                let failed: Vec<_> = o.results.iter().filter(|r| !r.success).cloned().collect();
+                let failed_count = total - registered;
+                let updated = o.results.iter().filter(|r| r.success && r.kind == SubmissionKind::Update).count();
+                let cost = BlockchainService::new().estimate_cost(failed_count);
+                let can_retry = o.can_retry();
+                let rounds = o.retry_rounds;
+                view! {
+                    <div class="submission-results" role="status">
+                        <div class="preview-header">
+                            <div class="preview-title">"Résultat de l'envoi"</div>
+                            <button class="btn btn-secondary" on:click=on_export_fees title="Date, œuvres, frais en AFT, hashes, wallet">
+                                "Exporter les frais (CSV)"
+                            </button>
+                        </div>
+                        <div class="preview-cost">
+                            <strong>{registered}</strong> "/" {total} " œuvres enregistrées"
+                            {(updated > 0).then(|| view! { " dont " <strong>{updated}</strong> " mise(s) à jour" })}
+                            {(failed_count > 0).then(|| view! { " • " <strong>{failed_count}</strong> " en échec" })}
+                            {catalog_period.and_then(|s| s.get_untracked()).map(|period| format!(" • Période comptable : {}", period))}
+                        </div>
+                        {move || tx_status.get().filter(|s| *s != TxStatus::Finalized).map(|status| view! {
+                            <div class="tx-status" class:pending=status.is_pending()>
+                                <span>{status.label()}</span>
+                                {(status.is_pending() && tx_block.get().is_some()).then(|| view! {
+                                    <button
+                                        class="btn btn-secondary"
+                                        on:click=on_check_again
+                                        disabled=move || checking.get()
+                                    >
+                                        {move || if checking.get() { "Vérification..." } else { "Vérifier à nouveau" }}
+                                    </button>
+                                })}
+                            </div>
+                        })}
+                        {(!failed.is_empty()).then(|| view! {
+                            <details class="submission-failures">
+                                <summary>"Œuvres en échec"</summary>
+                                <ul>
+                                    {failed.iter().map(|r| view! {
+                                        <li>
+                                            {r.iswc.clone()}
+                                            {(r.kind == SubmissionKind::Update).then(|| format!(" ({})", r.kind.label()))}
+                                            " : " {r.error.clone().unwrap_or_else(|| "Erreur inconnue".to_string())}
+                                        </li>
+                                    }).collect_view()}
+                                </ul>
+                            </details>
+                        })}
+                        {(failed_count > 0).then(|| view! {
+                            <div class="preflight-actions">
+                                <span class="upload-hint">
+                                    {if can_retry {
+                                        format!("Coût estimé : {} • essai {}/{}", cost, rounds + 1, MAX_RETRY_ROUNDS)
+                                    } else {
+                                        format!("Nombre maximal d'essais atteint ({})", MAX_RETRY_ROUNDS)
+                                    }}
+                                </span>
+                                <button
+                                    class="btn btn-primary"
+                                    on:click=on_retry
+                                    disabled=move || !can_retry || retrying.get() || !wallet_connected.get()
+                                >
+                                    {move || if retrying.get() { "Envoi en cours..." } else { "Réessayer les échecs" }}
+                                </button>
+                            </div>
+                        })}
+                    </div>
+                }
+            })}
+
+            {move || printing.get().then(|| view! {
+                <PrintableView
+                    works=musical_works_json.get_untracked()
+                        .and_then(|json| json.as_array().cloned())
+                        .unwrap_or_default()
+                    selected=selected.get_untracked()
+                    file_sha256=file_sha256.and_then(|s| s.get_untracked())
+                    period=catalog_period.and_then(|s| s.get_untracked())
+                    on_close=Callback::new(move |_| printing.set(false))
+                />
+            })}
+
+            <Show when=move || confirming.get() fallback=|| view! { }>
+                <div class="modal-backdrop">
+                    <div class="modal" role="dialog" aria-modal="true" aria-labelledby="confirm-title">
+                        <div class="preview-title" id="confirm-title">"Confirmer l'envoi"</div>
+                        <p class="preview-cost">
+                            {move || plan.with(|p| {
+                                let n = p.works.len();
+                                match p.updates.len() {
+                                    0 => format!("{} œuvres • Coût estimé : {:.2} AFT", n, n as f32 * 0.05),
+                                    updates => format!(
+                                        "{} création(s) • {} mise(s) à jour • Coût estimé : {:.2} AFT",
+                                        p.creations(), updates, n as f32 * 0.05,
+                                    ),
+                                }
+                            })}
+                        </p>
+                        {move || plan.with(|p| (!p.updates.is_empty()).then(|| view! {
+                            <details class="update-plan" open>
+                                <summary>
+                                    {format!("Mises à jour d'œuvres déjà enregistrées ({})", p.updates.len())}
+                                </summary>
+                                <ul>
+                                    {p.updates.iter().map(|u| view! {
+                                        <li>
+                                            <strong>{u.iswc.clone()}</strong>
+                                            <ul>
+                                                {u.changes.iter().map(|c| view! { <li>{c.describe()}</li> }).collect_view()}
+                                            </ul>
+                                        </li>
+                                    }).collect_view()}
+                                </ul>
+                            </details>
+                        }))}
+                        {move || plan.with(|p| (!p.unchanged.is_empty()).then(|| view! {
+                            <div class="upload-hint">
+                                {format!("{} œuvre(s) déjà enregistrée(s) sans changement ne seront pas renvoyée(s).", p.unchanged.len())}
+                            </div>
+                        }))}
+                        <label class="batch-label" for="batch-label-input">
+                            "Libellé de lot (optionnel)"
+                        </label>
+                        <input
+                            id="batch-label-input"
+                            type="text"
+                            placeholder="ex. Campagne 2025-T1, réf. catalogue"
+                            maxlength=MAX_BATCH_LABEL_LEN.to_string()
+                            prop:value=move || batch_label.get()
+                            on:input=move |ev| batch_label.set(event_target_value(&ev))
+                        />
+                        <div class="upload-hint">
+                            "Enregistré on-chain avec les œuvres pour les retrouver plus tard par campagne."
+                        </div>
+                        <label class="batch-label" for="batch-strategy-select">
+                            "Regroupement des transactions"
+                        </label>
+                        <select
+                            id="batch-strategy-select"
+                            on:change=move |ev| strategy.set(match event_target_value(&ev).as_str() {
+                                "batch" => BatchStrategy::Batch { size: DEFAULT_BATCH_SIZE },
+                                "individual" => BatchStrategy::Individual,
+                                _ => BatchStrategy::BatchAll,
+                            })
+                        >
+                            <option value="batch_all" selected=move || strategy.get() == BatchStrategy::BatchAll>
+                                "Tout ou rien (batch_all)"
+                            </option>
+                            <option value="batch" selected=move || matches!(strategy.get(), BatchStrategy::Batch { .. })>
+                                "Par lots (utility.batch)"
+                            </option>
+                            <option value="individual" selected=move || strategy.get() == BatchStrategy::Individual>
+                                "Une transaction par œuvre"
+                            </option>
+                        </select>
+                        {move || match strategy.get() {
+                            BatchStrategy::Batch { size } => Some(view! {
+                                <label class="batch-size">
+                                    "Œuvres par lot "
+                                    <input
+                                        type="number"
+                                        min="1"
+                                        prop:value=size.to_string()
+                                        on:change=move |ev| {
+                                            let size = event_target_value(&ev).parse().unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+                                            strategy.set(BatchStrategy::Batch { size });
+                                        }
+                                    />
+                                </label>
+                            }),
+                            _ => None,
+                        }}
+                        <div class="upload-hint">
+                            {move || {
+                                let s = strategy.get();
+                                let n = plan.with(|p| p.works.len());
+                                format!("{} • {} transaction(s) à signer", s.description(), s.transactions(n))
+                            }}
+                        </div>
+                        <div class="preflight-actions">
+                            <button class="btn btn-secondary" on:click=move |_| confirming.set(false)>"Retour"</button>
+                            <button
+                                class="btn btn-primary"
+                                on:click=move |_| on_confirm()
+                                disabled=move || plan.with(|p| p.works.is_empty())
+                            >
+                                "Confirmer et signer"
+                            </button>
+                        </div>
+                    </div>
+                </div>
+            </Show>
         </div>
     }
 }
@@ -1,13 +1,27 @@
 //! Detailed view for a musical work - MIDDS Format
+//!
+//! Fields are rendered from the grouped MIDDS schema (the one the backend
+//! validates against): schema order, nested objects and arrays. Fields added
+//! to the schema show up without code changes; a few fields get a dedicated
+//! formatting hook (party ids, roles, work type). Fields of the work missing
+//! from the schema are shown last, typed from their value.
+
+use std::fmt;
+use std::sync::OnceLock;
 
 use leptos::*;
-use serde_json::Value;
+use serde::de::{Deserializer, MapAccess, Visitor};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Grouped MIDDS schema (SDK format)
+const GROUPED_SCHEMA: &str = include_str!("../../../backend/schemas/midds-musical-work-grouped.json");
 
 /// Extract ID display (IPI, ISNI, or Both)
 /// Supports both formats:
 /// - dedot: { "type": "Ipi", "value": 123 }
 /// - legacy: { "Ipi": 123 }
-fn format_party_id(id: &Value) -> String {
+pub(crate) fn format_party_id(id: &Value) -> String {
     // New dedot format: { "type": "Ipi", "value": 123 }
     if let Some(id_type) = id.get("type").and_then(|t| t.as_str()) {
         match id_type {
@@ -44,97 +58,243 @@ fn format_party_id(id: &Value) -> String {
     }
 }
 
-/// Creator data from MIDDS format
-struct MiddsCreator {
-    id_display: String,
-    role: String,
+/// Schema node: declared type, properties in file order, array items
+#[derive(Debug, Default, Deserialize)]
+struct SchemaNode {
+    /// `"string"`, `["string", "null"]`...
+    #[serde(default, rename = "type")]
+    kind: Value,
+    #[serde(default, deserialize_with = "ordered_properties")]
+    properties: Vec<(String, SchemaNode)>,
+    #[serde(default)]
+    items: Option<Box<SchemaNode>>,
+}
+
+impl SchemaNode {
+    /// First non-null declared type
+    fn kind(&self) -> Option<&str> {
+        match &self.kind {
+            Value::String(kind) => Some(kind),
+            Value::Array(kinds) => kinds.iter().filter_map(Value::as_str).find(|k| *k != "null"),
+            _ => None,
+        }
+    }
+}
+
+/// `properties` as a list, keeping the order of the schema file
+/// (a `serde_json::Map` would sort the keys)
+fn ordered_properties<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<(String, SchemaNode)>, D::Error> {
+    struct Ordered;
+
+    impl<'de> Visitor<'de> for Ordered {
+        type Value = Vec<(String, SchemaNode)>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a map of schema properties")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut properties = Vec::new();
+            while let Some(entry) = map.next_entry::<String, SchemaNode>()? {
+                properties.push(entry);
+            }
+            Ok(properties)
+        }
+    }
+
+    deserializer.deserialize_map(Ordered)
+}
+
+/// Grouped schema, parsed once
+fn grouped_schema() -> &'static SchemaNode {
+    static SCHEMA: OnceLock<SchemaNode> = OnceLock::new();
+    SCHEMA.get_or_init(|| serde_json::from_str(GROUPED_SCHEMA).unwrap_or_default())
+}
+
+/// Formatting hook of a field: CSS class and display text
+struct FieldHook {
+    class: &'static str,
+    format: fn(&Value) -> String,
+}
+
+/// Fields with a dedicated rendering, by name (at any depth)
+const FIELD_HOOKS: &[(&str, FieldHook)] = &[
+    ("iswc", FieldHook { class: "iswc-value", format: format_scalar }),
+    ("title", FieldHook { class: "", format: format_title }),
+    ("id", FieldHook { class: "ipi-code", format: format_party_id }),
+    ("role", FieldHook { class: "role-badge", format: format_scalar }),
+    ("workType", FieldHook { class: "type-value", format: format_work_type }),
+];
+
+fn field_hook(name: &str) -> Option<&'static FieldHook> {
+    FIELD_HOOKS.iter().find(|(field, _)| *field == name).map(|(_, hook)| hook)
+}
+
+/// CSS class of a scalar by declared type
+fn type_class(kind: Option<&str>) -> &'static str {
+    match kind {
+        Some("boolean") => "bool-value",
+        _ => "",
+    }
+}
+
+fn format_scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => "—".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// workType: { type: "Original" } | "Original"
+fn format_work_type(value: &Value) -> String {
+    value.get("type")
+        .and_then(|t| t.as_str())
+        .map(String::from)
+        .unwrap_or_else(|| format_scalar(value))
+}
+
+/// title: { title: "Song", language: "French" } | "Song"
+fn format_title(value: &Value) -> String {
+    match (value.get("title").and_then(|t| t.as_str()), value.get("language").and_then(|l| l.as_str())) {
+        (Some(title), Some(language)) => format!("{} ({})", title, language),
+        (Some(title), None) => title.to_string(),
+        _ => format_scalar(value),
+    }
+}
+
+/// How a field is laid out
+enum Layout {
+    Scalar { class: &'static str, text: String },
+    Object,
+    Array,
+}
+
+fn layout(name: &str, schema: Option<&SchemaNode>, value: &Value) -> Layout {
+    if let Some(hook) = field_hook(name) {
+        return Layout::Scalar { class: hook.class, text: (hook.format)(value) };
+    }
+    let kind = schema.and_then(SchemaNode::kind);
+    match (kind, value) {
+        (Some("object") | None, Value::Object(_)) => Layout::Object,
+        (Some("array") | None, Value::Array(_)) => Layout::Array,
+        _ => Layout::Scalar { class: type_class(kind.or(value.is_boolean().then_some("boolean"))), text: format_scalar(value) },
+    }
+}
+
+/// Fields of an object to display: schema order first, then unknown fields.
+/// Null values and empty arrays or objects are left out.
+fn ordered_fields<'a>(
+    schema: Option<&'a SchemaNode>,
+    object: &'a Map<String, Value>,
+) -> Vec<(&'a str, Option<&'a SchemaNode>, &'a Value)> {
+    let is_shown = |value: &Value| match value {
+        Value::Null => false,
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+        _ => true,
+    };
+    let declared = schema.map(|s| s.properties.as_slice()).unwrap_or_default();
+
+    let mut fields: Vec<_> = declared.iter()
+        .filter_map(|(name, node)| object.get(name).map(|value| (name.as_str(), Some(node), value)))
+        .collect();
+    fields.extend(
+        object.iter()
+            .filter(|(name, _)| !declared.iter().any(|(key, _)| key == *name))
+            .map(|(name, value)| (name.as_str(), None, value)),
+    );
+    fields.retain(|(_, _, value)| is_shown(value));
+    fields
+}
+
+/// Properties of a nested object, one line each
+fn render_props(schema: Option<&SchemaNode>, object: &Map<String, Value>) -> View {
+    ordered_fields(schema, object).into_iter().map(|(name, node, value)| {
+        let name = name.to_string();
+        match layout(&name, node, value) {
+            Layout::Scalar { class, text } => view! {
+                <div class="midds-prop">
+                    <span class="prop-key">{name}</span>
+                    <span class=format!("prop-value {}", class)>{text}</span>
+                </div>
+            }.into_view(),
+            Layout::Object => view! {
+                <div class="midds-prop">
+                    <span class="prop-key">{name}</span>
+                    <div class="midds-object">{render_props(node, value.as_object().unwrap_or(&Map::new()))}</div>
+                </div>
+            }.into_view(),
+            Layout::Array => view! {
+                <div class="midds-prop">
+                    <span class="prop-key">{name}</span>
+                    <div class="midds-array">{render_items(node, value)}</div>
+                </div>
+            }.into_view(),
+        }
+    }).collect_view()
+}
+
+/// Items of an array, with their index
+fn render_items(schema: Option<&SchemaNode>, value: &Value) -> View {
+    let item_schema = schema.and_then(|s| s.items.as_deref());
+    value.as_array().map(|items| items.iter().enumerate().map(|(idx, item)| {
+        let content = match item {
+            Value::Object(object) => view! {
+                <div class="midds-object">{render_props(item_schema, object)}</div>
+            }.into_view(),
+            other => view! { <span class="prop-value">{format_scalar(other)}</span> }.into_view(),
+        };
+        view! {
+            <div class="midds-array-item">
+                <span class="midds-index">{idx}</span>
+                {content}
+            </div>
+        }
+    }).collect_view()).unwrap_or_default()
+}
+
+/// Top-level field of the work
+fn render_field(name: &str, schema: Option<&SchemaNode>, value: &Value) -> View {
+    let label = name.to_string();
+    match layout(name, schema, value) {
+        Layout::Scalar { class, text } => view! {
+            <div class="midds-field">
+                <div class="midds-label">{label}</div>
+                <div class=format!("midds-value {}", class)>{text}</div>
+            </div>
+        }.into_view(),
+        Layout::Object => view! {
+            <div class="midds-field">
+                <div class="midds-label">{label}</div>
+                <div class="midds-object" style="margin-left: 1rem;">
+                    {render_props(schema, value.as_object().unwrap_or(&Map::new()))}
+                </div>
+            </div>
+        }.into_view(),
+        Layout::Array => view! {
+            <div class="midds-field">
+                <div class="midds-label">
+                    {label} <span class="midds-count">"[" {value.as_array().map_or(0, Vec::len)} "]"</span>
+                </div>
+                <div class="midds-array">{render_items(schema, value)}</div>
+            </div>
+        }.into_view(),
+    }
 }
 
-/// Extract and display work details in MIDDS format
+/// Display a work in MIDDS format, driven by the grouped schema
 #[component]
 pub fn WorkDetail(work: Value) -> impl IntoView {
-    // === MIDDS Field Extraction ===
-    
-    // iswc: String
-    let iswc = work.get("iswc")
-        .and_then(|v| v.as_str())
-        .unwrap_or("—")
-        .to_string();
-    
-    // title: String
-    let title = work.get("title")
-        .and_then(|v| v.as_str())
-        .unwrap_or("—")
-        .to_string();
-    
-    // creationYear: Number
-    let creation_year = work.get("creationYear")
-        .and_then(|y| y.as_i64());
-    
-    // instrumental: Boolean
-    let instrumental = work.get("instrumental")
-        .and_then(|i| i.as_bool());
-    
-    // language: String (enum)
-    let language = work.get("language")
-        .and_then(|l| l.as_str())
-        .map(|s| s.to_string());
-    
-    // bpm: Number
-    let bpm = work.get("bpm")
-        .and_then(|b| b.as_i64());
-    
-    // key: String (enum)
-    let key = work.get("key")
-        .and_then(|k| k.as_str())
-        .map(|s| s.to_string());
-    
-    // workType: { type: "Original" } | "Original" | null
-    let work_type = work.get("workType")
-        .and_then(|w| {
-            // New dedot format: { "type": "Original" }
-            if let Some(t) = w.get("type").and_then(|t| t.as_str()) {
-                Some(t.to_string())
-            } else {
-                // Legacy format: "Original"
-                w.as_str().map(|s| s.to_string())
-            }
-        });
-    
-    // creators: Array<{ id: PartyId, role: String }>
-    let creators: Vec<MiddsCreator> = work.get("creators")
-        .and_then(|c| c.as_array())
-        .map(|arr| {
-            arr.iter().map(|c| {
-                let id_display = c.get("id")
-                    .map(|id| format_party_id(id))
-                    .unwrap_or_else(|| "—".to_string());
-                let role = c.get("role")
-                    .and_then(|r| r.as_str())
-                    .unwrap_or("—")
-                    .to_string();
-                MiddsCreator { id_display, role }
-            }).collect()
+    let schema = grouped_schema();
+    let fields = work.as_object()
+        .map(|object| {
+            ordered_fields(Some(schema), object).into_iter()
+                .map(|(name, node, value)| render_field(name, node, value))
+                .collect_view()
         })
         .unwrap_or_default();
-    
-    // classicalInfo: { opus?, catalogNumber?, numberOfVoices? }
-    let classical_info = work.get("classicalInfo");
-    let opus = classical_info
-        .and_then(|ci| ci.get("opus"))
-        .and_then(|o| o.as_str())
-        .map(|s| s.to_string());
-    let catalog_number = classical_info
-        .and_then(|ci| ci.get("catalogNumber"))
-        .and_then(|c| c.as_str())
-        .map(|s| s.to_string());
-    let number_of_voices = classical_info
-        .and_then(|ci| ci.get("numberOfVoices"))
-        .and_then(|n| n.as_i64());
-    
-    let has_classical_info = opus.is_some() || catalog_number.is_some() || number_of_voices.is_some();
-    
-    // === MIDDS Display ===
+
     view! {
         <div class="midds-work">
             // Header with MIDDS badge
@@ -142,125 +302,35 @@ pub fn WorkDetail(work: Value) -> impl IntoView {
                 <span class="midds-badge">"MIDDS"</span>
                 <span class="midds-type">"MusicalWork"</span>
             </div>
-            
-            // iswc
-            <div class="midds-field">
-                <div class="midds-label">"iswc"</div>
-                <div class="midds-value iswc-value">{iswc}</div>
-            </div>
-            
-            // title
-            <div class="midds-field">
-                <div class="midds-label">"title"</div>
-                <div class="midds-value">{title}</div>
-            </div>
-            
-            // creationYear
-            {creation_year.map(|year| view! {
-                <div class="midds-field">
-                    <div class="midds-label">"creationYear"</div>
-                    <div class="midds-value">{year}</div>
-                </div>
-            })}
-            
-            // language
-            {language.map(|lang| view! {
-                <div class="midds-field">
-                    <div class="midds-label">"language"</div>
-                    <div class="midds-value">{lang}</div>
-                </div>
-            })}
-            
-            // instrumental
-            {instrumental.map(|is_inst| view! {
-                <div class="midds-field">
-                    <div class="midds-label">"instrumental"</div>
-                    <div class="midds-value bool-value">{if is_inst { "true" } else { "false" }}</div>
-                </div>
-            })}
-            
-            // bpm
-            {bpm.map(|b| view! {
-                <div class="midds-field">
-                    <div class="midds-label">"bpm"</div>
-                    <div class="midds-value">{b}</div>
-                </div>
-            })}
-            
-            // key
-            {key.map(|k| view! {
-                <div class="midds-field">
-                    <div class="midds-label">"key"</div>
-                    <div class="midds-value">{k}</div>
-                </div>
-            })}
-            
-            // workType
-            {work_type.map(|wtype| view! {
-                <div class="midds-field">
-                    <div class="midds-label">"workType"</div>
-                    <div class="midds-value type-value">{wtype}</div>
-                </div>
-            })}
-            
-            // creators[]
-            {if !creators.is_empty() {
-                view! {
-                    <div class="midds-field">
-                        <div class="midds-label">"creators" <span class="midds-count">"[" {creators.len()} "]"</span></div>
-                        <div class="midds-array">
-                            {creators.into_iter().enumerate().map(|(idx, c)| view! {
-                                <div class="midds-array-item">
-                                    <span class="midds-index">{idx}</span>
-                                    <div class="midds-object">
-                                        <div class="midds-prop">
-                                            <span class="prop-key">"id"</span>
-                                            <span class="prop-value ipi-code">{c.id_display}</span>
-                                        </div>
-                                        <div class="midds-prop">
-                                            <span class="prop-key">"role"</span>
-                                            <span class="prop-value role-badge">{c.role}</span>
-                                        </div>
-                                    </div>
-                                </div>
-                            }).collect_view()}
-                        </div>
-                    </div>
-                }.into_view()
-            } else {
-                view! {}.into_view()
-            }}
-            
-            // classicalInfo
-            {if has_classical_info {
-                view! {
-                    <div class="midds-field">
-                        <div class="midds-label">"classicalInfo"</div>
-                        <div class="midds-object" style="margin-left: 1rem;">
-                            {opus.map(|o| view! {
-                                        <div class="midds-prop">
-                                    <span class="prop-key">"opus"</span>
-                                    <span class="prop-value">{o}</span>
-                                        </div>
-                            })}
-                            {catalog_number.map(|cn| view! {
-                                            <div class="midds-prop">
-                                    <span class="prop-key">"catalogNumber"</span>
-                                    <span class="prop-value">{cn}</span>
-                                            </div>
-                                        })}
-                            {number_of_voices.map(|nv| view! {
-                                <div class="midds-prop">
-                                    <span class="prop-key">"numberOfVoices"</span>
-                                    <span class="prop-value">{nv}</span>
-                                </div>
-                            })}
-                        </div>
-                    </div>
-                }.into_view()
-            } else {
-                view! {}.into_view()
-            }}
+            {fields}
         </div>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_fields_follow_schema_order() {
+        let schema = grouped_schema();
+        assert_eq!(schema.properties[0].0, "iswc");
+        let creators = schema.properties.iter().find(|(name, _)| name == "creators").map(|(_, node)| node).unwrap();
+        assert_eq!(creators.kind(), Some("array"));
+        assert_eq!(creators.items.as_ref().unwrap().properties[1].0, "role");
+
+        let work = json!({
+            "title": "Song", "iswc": "T1234567890", "participants": [], "language": null,
+            "moodTags": ["calm"], "workType": {"type": "Original"},
+        });
+        let names: Vec<&str> = ordered_fields(Some(schema), work.as_object().unwrap())
+            .into_iter().map(|(name, _, _)| name).collect();
+        assert_eq!(names, vec!["iswc", "title", "workType", "moodTags"]);
+
+        assert_eq!(format_work_type(&work["workType"]), "Original");
+        assert_eq!(format_title(&json!({"title": "La Vie en rose", "language": "French"})), "La Vie en rose (French)");
+        assert_eq!(format_title(&work["title"]), "Song");
+        assert_eq!(format_party_id(&json!({"type": "Ipi", "value": 123})), "IPI: 123");
+    }
+}
@@ -0,0 +1,120 @@
+//! Version imprimable des œuvres sélectionnées ou du récapitulatif du lot.
+//!
+//! Les gestionnaires de droits archivent encore les enregistrements sur
+//! papier ou en PDF : la vue s'affiche par-dessus l'aperçu et la feuille
+//! `style/print.css` n'imprime qu'elle, en noir sur blanc.
+
+use leptos::*;
+use serde_json::Value;
+
+use super::preview_detail::format_party_id;
+use crate::{work_title, WorkDetail};
+
+/// Créateurs d'une œuvre sur une ligne : « IPI: 123 (Composer), ... »
+fn creators_summary(work: &Value) -> String {
+    work.get("creators")
+        .and_then(Value::as_array)
+        .map(|creators| {
+            creators.iter()
+                .map(|c| {
+                    let id = c.get("id").map(format_party_id).unwrap_or_else(|| "—".to_string());
+                    match c.get("role").and_then(Value::as_str) {
+                        Some(role) => format!("{} ({})", id, role),
+                        None => id,
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .unwrap_or_default()
+}
+
+fn text_field(work: &Value, field: &str) -> String {
+    let text = if field == "title" { work_title(work) } else { work.get(field).and_then(Value::as_str) };
+    text.unwrap_or("—").to_string()
+}
+
+#[component]
+pub fn PrintableView(
+    /// Œuvres du lot (format MIDDS)
+    works: Vec<Value>,
+    /// Index des œuvres sélectionnées ; vide = récapitulatif du lot
+    selected: Vec<usize>,
+    file_sha256: Option<String>,
+    /// Période comptable du catalogue
+    period: Option<String>,
+    on_close: Callback<()>,
+) -> impl IntoView {
+    let date = chrono::Local::now().format("%d/%m/%Y %H:%M").to_string();
+    let total = works.len();
+    let details: Vec<Value> = selected.iter().filter_map(|&i| works.get(i).cloned()).collect();
+
+    let on_print = move |_| {
+        if let Err(e) = window().print() {
+            log::warn!("Impression impossible : {:?}", e);
+        }
+    };
+
+    let content = if details.is_empty() {
+        view! {
+            <h1>"Récapitulatif du lot"</h1>
+            <table class="print-table">
+                <thead>
+                    <tr><th>"#"</th><th>"Titre"</th><th>"ISWC"</th><th>"Créateurs"</th></tr>
+                </thead>
+                <tbody>
+                    {works.iter().enumerate().map(|(i, work)| view! {
+                        <tr>
+                            <td>{i + 1}</td>
+                            <td>{text_field(work, "title")}</td>
+                            <td class="iswc-value">{text_field(work, "iswc")}</td>
+                            <td>{creators_summary(work)}</td>
+                        </tr>
+                    }).collect_view()}
+                </tbody>
+            </table>
+        }.into_view()
+    } else {
+        view! {
+            <h1>{format!("Détail de {} œuvre(s)", details.len())}</h1>
+            {details.into_iter().map(|work| view! {
+                <div class="print-work">
+                    <h2>{text_field(&work, "title")}</h2>
+                    <WorkDetail work=work/>
+                </div>
+            }).collect_view()}
+        }.into_view()
+    };
+
+    view! {
+        <div class="printable" role="dialog" aria-modal="true" aria-label="Version imprimable">
+            <div class="preflight-actions no-print">
+                <button class="btn btn-secondary" on:click=move |_| on_close.call(())>"Fermer"</button>
+                <button class="btn btn-primary" on:click=on_print>"Imprimer / PDF"</button>
+            </div>
+            <div class="print-meta">
+                <div>"Massload • Enregistrement MIDDS • " {date}</div>
+                <div>{format!("{} œuvre(s) • Coût estimé : {:.2} AFT", total, total as f32 * 0.05)}</div>
+                {file_sha256.map(|sha| view! { <div>"SHA-256 du fichier source : " {sha}</div> })}
+                {period.map(|period| view! { <div>"Période comptable : " {period}</div> })}
+            </div>
+            {content}
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_creators_summary() {
+        let work = json!({"creators": [
+            {"id": {"type": "Ipi", "value": 123}, "role": "Composer"},
+            {"id": {"type": "Isni", "value": "0000000121464388"}},
+        ]});
+        assert_eq!(creators_summary(&work), "IPI: 123 (Composer), ISNI: 0000000121464388");
+        assert_eq!(creators_summary(&json!({})), "");
+    }
+}
@@ -22,7 +22,7 @@ pub fn ProgressSection(logs: ReadSignal<Vec<LogEntry>>) -> impl IntoView {
                         };
                         view! {
                             <div class=class_name>
-                                "[" {entry.timestamp} "] " {entry.message}
+                                "[" {entry.time_label()} "] " {entry.message}
                             </div>
                         }
                     }
@@ -0,0 +1,89 @@
+//! Répartition des ayants droit par rôle sur le lot.
+//!
+//! Les sociétés de gestion refusent en général une œuvre sans compositeur :
+//! le panneau liste celles du lot pour les corriger avant la soumission. Il
+//! signale aussi les rôles que le runtime du réseau n'accepte pas, avec le
+//! rôle à utiliser à la place, avant que l'extrinsic échoue.
+
+use leptos::*;
+
+use crate::services::RoleReport;
+
+/// Libellé français d'un rôle CISAC (`Composer` → « Compositeur »)
+fn role_label(role: &str) -> &str {
+    match role {
+        "Composer" => "Compositeur",
+        "Author" => "Auteur",
+        "ComposerAuthor" => "Auteur-compositeur",
+        "Arranger" => "Arrangeur",
+        "Adapter" => "Adaptateur",
+        "Translator" => "Traducteur",
+        "SubAuthor" => "Sous-auteur",
+        "SubArranger" => "Sous-arrangeur",
+        "Publisher" => "Éditeur",
+        "OriginalPublisher" => "Éditeur original",
+        "SubPublisher" => "Sous-éditeur",
+        other => other,
+    }
+}
+
+/// Ligne d'un rôle refusé : nombre d'ayants droit et substitution proposée
+fn substitution_label(role: &str, creators: usize, suggestion: Option<&str>) -> String {
+    match suggestion {
+        Some(substitute) => format!("{} × {} → {}", creators, role_label(role), role_label(substitute)),
+        None => format!("{} × {} (aucun rôle équivalent)", creators, role_label(role)),
+    }
+}
+
+#[component]
+pub fn RoleStats(report: RwSignal<Option<RoleReport>>) -> impl IntoView {
+    move || report.get().filter(|r| !r.roles.is_empty()).map(|report| {
+        let hidden = report.works_without_composer.saturating_sub(report.without_composer.len());
+
+        view! {
+            <section class="role-stats" aria-labelledby="role-stats-title">
+                <div class="preview-title" id="role-stats-title">"👥 Ayants droit par rôle"</div>
+                <ul class="role-counts">
+                    {report.roles.iter().map(|(role, count)| view! {
+                        <li><strong>{*count}</strong> " " {role_label(role).to_string()}</li>
+                    }).collect_view()}
+                </ul>
+                {(report.works_without_composer > 0).then(|| view! {
+                    <div class="role-warning">
+                        {format!("⚠️ {} œuvre(s) sans compositeur, souvent refusées par les sociétés de gestion :",
+                            report.works_without_composer)}
+                        <ul>
+                            {report.without_composer.iter().map(|work| view! {
+                                <li>{work.title.clone()} " (" {work.iswc.clone()} ")"</li>
+                            }).collect_view()}
+                            {(hidden > 0).then(|| view! { <li>{format!("… et {} autre(s)", hidden)}</li> })}
+                        </ul>
+                    </div>
+                })}
+                {(!report.unsupported.is_empty()).then(|| view! {
+                    <div class="role-warning">
+                        {format!("⛓️ Rôles refusés par le réseau {} :", report.network)}
+                        <ul>
+                            {report.unsupported.iter().map(|unsupported| view! {
+                                <li>{substitution_label(&unsupported.role, unsupported.creators, unsupported.suggestion.as_deref())}</li>
+                            }).collect_view()}
+                        </ul>
+                    </div>
+                })}
+            </section>
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role_label() {
+        assert_eq!(role_label("ComposerAuthor"), "Auteur-compositeur");
+        assert_eq!(role_label("Publisher"), "Éditeur");
+        assert_eq!(role_label("Unknown"), "Unknown");
+        assert_eq!(substitution_label("SubPublisher", 2, Some("Publisher")), "2 × Sous-éditeur → Éditeur");
+    }
+}
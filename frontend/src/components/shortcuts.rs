@@ -0,0 +1,121 @@
+//! Registre central des raccourcis clavier et fenêtre d'aide (`?`).
+//!
+//! Un composant affiché enregistre ses actions avec [`use_shortcut`] ; elles
+//! sont retirées quand il disparaît. À chaque touche, les gestionnaires de
+//! l'action sont essayés du plus récent au plus ancien, jusqu'au premier qui
+//! la traite (un gestionnaire renvoie `false` quand son étape n'est pas affichée).
+
+use leptos::*;
+use wasm_bindgen::JsCast;
+use crate::services::{is_typing_target, ShortcutAction, SHORTCUTS};
+
+type Handler = (usize, ShortcutAction, Callback<(), bool>);
+
+/// Gestionnaires enregistrés par les composants affichés
+#[derive(Clone, Copy)]
+pub struct ShortcutRegistry {
+    handlers: RwSignal<Vec<Handler>>,
+    next_id: StoredValue<usize>,
+    /// Fenêtre d'aide ouverte
+    pub help_open: RwSignal<bool>,
+}
+
+impl ShortcutRegistry {
+    /// Crée le registre, le rend disponible aux composants enfants et écoute le clavier
+    pub fn provide() -> Self {
+        let registry = Self {
+            handlers: create_rw_signal(Vec::new()),
+            next_id: store_value(0),
+            help_open: create_rw_signal(false),
+        };
+        provide_context(registry);
+        // Retiré en quittant la page (le registre disparaît avec elle)
+        let listener = window_event_listener(ev::keydown, move |ev| registry.on_keydown(&ev));
+        on_cleanup(move || listener.remove());
+        registry
+    }
+
+    fn on_keydown(&self, ev: &ev::KeyboardEvent) {
+        if ev.key() == "Escape" && self.help_open.get_untracked() {
+            self.help_open.set(false);
+            return;
+        }
+        let typing = ev.target()
+            .and_then(|target| target.dyn_into::<web_sys::HtmlElement>().ok())
+            .is_some_and(|el| is_typing_target(&el.tag_name(), el.is_content_editable()));
+        let modified = ev.ctrl_key() || ev.alt_key() || ev.meta_key();
+        let Some(action) = ShortcutAction::from_key(&ev.key(), modified, typing) else {
+            return;
+        };
+        if action == ShortcutAction::Help {
+            self.help_open.update(|open| *open = !*open);
+            ev.prevent_default();
+            return;
+        }
+        let handlers: Vec<Callback<(), bool>> = self.handlers.with_untracked(|handlers| {
+            handlers.iter().rev().filter(|(_, a, _)| *a == action).map(|(_, _, h)| *h).collect()
+        });
+        if handlers.into_iter().any(|handler| handler.call(())) {
+            // `/` ne doit pas s'écrire dans le champ de recherche qui reçoit le focus
+            ev.prevent_default();
+            self.help_open.set(false);
+        }
+    }
+
+    /// Action traitée par au moins un composant affiché
+    pub fn is_available(&self, action: ShortcutAction) -> bool {
+        action == ShortcutAction::Help || self.handlers.with(|handlers| handlers.iter().any(|(_, a, _)| *a == action))
+    }
+}
+
+/// Associe `handler` à un raccourci tant que le composant appelant est affiché.
+/// Le gestionnaire renvoie `true` s'il a traité la touche.
+pub fn use_shortcut(action: ShortcutAction, handler: impl Fn() -> bool + 'static) {
+    let Some(registry) = use_context::<ShortcutRegistry>() else {
+        return;
+    };
+    let id = registry.next_id.get_value();
+    registry.next_id.set_value(id + 1);
+    registry.handlers.update(|handlers| handlers.push((id, action, Callback::new(move |_| handler()))));
+    on_cleanup(move || {
+        let _ = registry.handlers.try_update(|handlers| handlers.retain(|(h, _, _)| *h != id));
+    });
+}
+
+/// Liste des raccourcis, ceux sans effet sur l'écran courant estompés
+#[component]
+pub fn ShortcutHelp() -> impl IntoView {
+    let Some(registry) = use_context::<ShortcutRegistry>() else {
+        return view! { }.into_view();
+    };
+    let help_open = registry.help_open;
+
+    view! {
+        <Show when=move || help_open.get() fallback=|| view! { }>
+            <div class="modal-backdrop" on:click=move |_| help_open.set(false)>
+                <div
+                    class="modal shortcut-help"
+                    role="dialog"
+                    aria-modal="true"
+                    aria-labelledby="shortcut-help-title"
+                    on:click=|ev| ev.stop_propagation()
+                >
+                    <div class="preview-title" id="shortcut-help-title">"Raccourcis clavier"</div>
+                    <table class="shortcut-table">
+                        {SHORTCUTS.iter().map(|&action| view! {
+                            <tr class:shortcut-unavailable=move || !registry.is_available(action)>
+                                <td><kbd>{action.key()}</kbd></td>
+                                <td>{action.label()}</td>
+                            </tr>
+                        }).collect_view()}
+                    </table>
+                    <div class="upload-hint">"Sans effet pendant la saisie dans un champ. Échap pour fermer."</div>
+                    <div class="preflight-actions">
+                        <button class="btn btn-secondary" on:click=move |_| help_open.set(false)>"Fermer"</button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+    .into_view()
+}
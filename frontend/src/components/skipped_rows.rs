@@ -0,0 +1,321 @@
+//! Correction des lignes ignorées faute d'un champ obligatoire.
+//!
+//! Tableau compact utilisable au clavier : l'utilisateur colle une colonne
+//! de valeurs (ex. des IPI copiés depuis un autre document), réordonne les
+//! lignes pour les aligner, vérifie, puis fusionne via `POST /api/v1/jobs/{id}/amend`.
+
+use std::collections::HashMap;
+
+use leptos::*;
+use wasm_bindgen::JsCast;
+
+use super::upload::add_log;
+use crate::services::{amend_job, Amendment, SkippedRow};
+use crate::{backend_url, LogEntry, LogLevel, PreviewItem};
+
+/// Étapes du formulaire de correction
+#[derive(Clone, Copy, PartialEq)]
+enum FixStep {
+    /// Saisie / collage des valeurs
+    Edit,
+    /// Vérification avant envoi
+    Review,
+}
+
+/// Donne le focus au champ de saisie à la position donnée (s'il existe)
+fn focus_input(pos: usize) {
+    request_animation_frame(move || {
+        if let Some(el) = document()
+            .get_element_by_id(&format!("fix-input-{}", pos))
+            .and_then(|el| el.dyn_into::<web_sys::HtmlElement>().ok())
+        {
+            let _ = el.focus();
+        }
+    });
+}
+
+/// Déplace la ligne `pos` de `delta` positions, renvoie la nouvelle position
+fn move_row(order: RwSignal<Vec<usize>>, pos: usize, delta: isize) -> Option<usize> {
+    let mut new_pos = None;
+    order.update(|o| {
+        let target = pos as isize + delta;
+        if target >= 0 && (target as usize) < o.len() {
+            o.swap(pos, target as usize);
+            new_pos = Some(target as usize);
+        }
+    });
+    new_pos
+}
+
+#[component]
+pub fn SkippedRowsFix(
+    job_id: RwSignal<Option<String>>,
+    skipped_rows: RwSignal<Vec<SkippedRow>>,
+    set_preview_data: WriteSignal<Option<Vec<PreviewItem>>>,
+    set_musical_works_json: WriteSignal<Option<serde_json::Value>>,
+    set_logs: WriteSignal<Vec<LogEntry>>,
+) -> impl IntoView {
+    // Ordre d'affichage des lignes (index de ligne), réordonnable
+    let order = create_rw_signal(Vec::<usize>::new());
+    // Valeur saisie par index de ligne
+    let values = create_rw_signal(HashMap::<usize, String>::new());
+    // Erreurs renvoyées par le backend par index de ligne
+    let row_errors = create_rw_signal(HashMap::<usize, String>::new());
+    let paste = create_rw_signal(String::new());
+    let step = create_rw_signal(FixStep::Edit);
+    let is_sending = create_rw_signal(false);
+
+    // Garder l'ordre choisi pour les lignes encore présentes, ajouter les nouvelles à la fin
+    create_effect(move |_| {
+        let rows: Vec<usize> = skipped_rows.with(|rows| {
+            rows.iter()
+                .filter(|r| r.single_missing_field().is_some())
+                .map(|r| r.row)
+                .collect()
+        });
+        order.update(|o| {
+            o.retain(|r| rows.contains(r));
+            for r in rows {
+                if !o.contains(&r) {
+                    o.push(r);
+                }
+            }
+        });
+    });
+
+    let row_info = move |row: usize| {
+        skipped_rows.with(|rows| rows.iter().find(|r| r.row == row).cloned())
+    };
+
+    let other_skipped = move || {
+        skipped_rows.with(|rows| rows.iter().filter(|r| r.single_missing_field().is_none()).count())
+    };
+
+    let filled_count = move || {
+        values.with(|v| v.values().filter(|s| !s.trim().is_empty()).count())
+    };
+
+    // Répartit les lignes collées dans l'ordre affiché
+    let on_distribute = move |_| {
+        let pasted = paste.get_untracked();
+        let lines: Vec<String> = pasted.lines().map(|l| l.trim().to_string()).collect();
+        let rows = order.get_untracked();
+        values.update(|v| {
+            for (row, value) in rows.iter().zip(lines) {
+                if !value.is_empty() {
+                    v.insert(*row, value);
+                }
+            }
+        });
+        paste.set(String::new());
+        focus_input(0);
+    };
+
+    let on_submit = move |_| {
+        let Some(id) = job_id.get_untracked() else {
+            return;
+        };
+        let amendments: Vec<Amendment> = order.get_untracked().into_iter()
+            .filter_map(|row| {
+                let value = values.with_untracked(|v| v.get(&row).cloned())?;
+                let field = row_info(row)?.single_missing_field()?.to_string();
+                let mut map = serde_json::Map::new();
+                map.insert(field, serde_json::Value::String(value.trim().to_string()));
+                (!value.trim().is_empty()).then_some(Amendment { row, values: map })
+            })
+            .collect();
+
+        if amendments.is_empty() {
+            step.set(FixStep::Edit);
+            return;
+        }
+
+        is_sending.set(true);
+        spawn_local(async move {
+            match amend_job(&id, &amendments, &backend_url()).await {
+                Ok(response) => {
+                    set_preview_data.set(Some(PreviewItem::from_works(&response.musical_works)));
+                    set_musical_works_json.set(Some(serde_json::Value::Array(response.musical_works)));
+
+                    let errors: HashMap<usize, String> = response.errors.iter()
+                        .map(|e| (e.record_index, e.errors.join(", ")))
+                        .collect();
+                    values.update(|v| v.retain(|row, _| errors.contains_key(row)));
+                    row_errors.set(errors);
+                    skipped_rows.set(response.skipped);
+
+                    add_log(
+                        set_logs,
+                        LogLevel::Success,
+                        &format!("✏️  {} ligne(s) complétée(s) et fusionnée(s)", response.amended),
+                    );
+                    if !response.errors.is_empty() {
+                        add_log(
+                            set_logs,
+                            LogLevel::Warning,
+                            &format!("{} ligne(s) encore invalide(s), voir le tableau", response.errors.len()),
+                        );
+                    }
+                }
+                Err(e) => {
+                    add_log(set_logs, LogLevel::Error, &format!("❌ Correction impossible : {}", e));
+                }
+            }
+            is_sending.set(false);
+            step.set(FixStep::Edit);
+        });
+    };
+
+    view! {
+        <Show
+            when=move || !order.get().is_empty()
+            fallback=|| view! { }
+        >
+            <section class="skipped-fix" aria-labelledby="skipped-fix-title">
+                <div class="skipped-fix-header">
+                    <div class="preview-title" id="skipped-fix-title">
+                        "✏️ Lignes à compléter (" {move || order.get().len()} ")"
+                    </div>
+                    <div class="upload-hint">
+                        "Ces lignes ont été ignorées car un seul champ obligatoire manque. "
+                        "Collez les valeurs (une par ligne, dans l'ordre du tableau), ou saisissez-les. "
+                        "Entrée : ligne suivante • Alt+↑/↓ : déplacer la ligne."
+                        {move || match other_skipped() {
+                            0 => String::new(),
+                            n => format!(" {} autre(s) ligne(s) ignorée(s) ne sont pas complétables ici.", n),
+                        }}
+                    </div>
+                </div>
+
+                <Show
+                    when=move || step.get() == FixStep::Edit
+                    fallback=move || view! {
+                        <div class="skipped-fix-review" role="status">
+                            {move || format!("{} ligne(s) seront complétées puis revalidées.", filled_count())}
+                        </div>
+                        <div class="preflight-actions">
+                            <button class="btn btn-secondary" on:click=move |_| step.set(FixStep::Edit)>
+                                "Retour"
+                            </button>
+                            <button
+                                class="btn btn-primary"
+                                on:click=on_submit
+                                disabled=move || is_sending.get()
+                            >
+                                {move || if is_sending.get() { "⏳ Envoi..." } else { "Valider et fusionner" }}
+                            </button>
+                        </div>
+                    }
+                >
+                    <div class="skipped-fix-paste">
+                        <label for="fix-paste">"Coller une colonne de valeurs"</label>
+                        <textarea
+                            id="fix-paste"
+                            rows="3"
+                            prop:value=move || paste.get()
+                            on:input=move |ev| paste.set(event_target_value(&ev))
+                        />
+                        <button
+                            class="btn btn-secondary"
+                            on:click=on_distribute
+                            disabled=move || paste.get().trim().is_empty()
+                        >
+                            "Répartir dans le tableau"
+                        </button>
+                    </div>
+
+                    <table class="skipped-fix-table">
+                        <thead>
+                            <tr>
+                                <th scope="col">"Ligne"</th>
+                                <th scope="col">"Œuvre"</th>
+                                <th scope="col">"Champ manquant"</th>
+                                <th scope="col">"Valeur"</th>
+                                <th scope="col"><span class="sr-only">"Ordre"</span></th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            <For
+                                each=move || order.get().into_iter().enumerate()
+                                key=|(pos, row)| (*pos, *row)
+                                children=move |(pos, row)| {
+                                    let info = row_info(row);
+                                    let label = info.as_ref().map(|r| r.label()).unwrap_or_default();
+                                    let line = info.as_ref().map_or(row + 1, |r| r.display_line());
+                                    let field = info.as_ref()
+                                        .and_then(|r| r.single_missing_field().map(String::from))
+                                        .unwrap_or_default();
+                                    let error = move || row_errors.with(|e| e.get(&row).cloned());
+                                    let on_keydown = move |ev: ev::KeyboardEvent| {
+                                        match ev.key().as_str() {
+                                            "Enter" => {
+                                                ev.prevent_default();
+                                                focus_input(pos + 1);
+                                            }
+                                            "ArrowUp" if ev.alt_key() => {
+                                                ev.prevent_default();
+                                                if let Some(p) = move_row(order, pos, -1) {
+                                                    focus_input(p);
+                                                }
+                                            }
+                                            "ArrowDown" if ev.alt_key() => {
+                                                ev.prevent_default();
+                                                if let Some(p) = move_row(order, pos, 1) {
+                                                    focus_input(p);
+                                                }
+                                            }
+                                            _ => {}
+                                        }
+                                    };
+                                    view! {
+                                        <tr class:has-error=move || error().is_some()>
+                                            <td>{line}</td>
+                                            <td>{label}</td>
+                                            <td><code>{field.clone()}</code></td>
+                                            <td>
+                                                <input
+                                                    id=format!("fix-input-{}", pos)
+                                                    type="text"
+                                                    aria-label=format!("{} pour la ligne {}", field, line)
+                                                    prop:value=move || values.with(|v| v.get(&row).cloned().unwrap_or_default())
+                                                    on:input=move |ev| {
+                                                        let value = event_target_value(&ev);
+                                                        values.update(|v| { v.insert(row, value); });
+                                                    }
+                                                    on:keydown=on_keydown
+                                                />
+                                                {move || error().map(|e| view! { <div class="error-message">{e}</div> })}
+                                            </td>
+                                            <td class="skipped-fix-order">
+                                                <button
+                                                    class="btn-icon"
+                                                    aria-label=format!("Monter la ligne {}", line)
+                                                    on:click=move |_| { move_row(order, pos, -1); }
+                                                >"↑"</button>
+                                                <button
+                                                    class="btn-icon"
+                                                    aria-label=format!("Descendre la ligne {}", line)
+                                                    on:click=move |_| { move_row(order, pos, 1); }
+                                                >"↓"</button>
+                                            </td>
+                                        </tr>
+                                    }
+                                }
+                            />
+                        </tbody>
+                    </table>
+
+                    <div class="preflight-actions">
+                        <button
+                            class="btn btn-primary"
+                            on:click=move |_| step.set(FixStep::Review)
+                            disabled=move || filled_count() == 0
+                        >
+                            {move || format!("Vérifier ({} valeur(s))", filled_count())}
+                        </button>
+                    </div>
+                </Show>
+            </section>
+        </Show>
+    }
+}
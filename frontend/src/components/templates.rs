@@ -0,0 +1,214 @@
+//! Modèles de transformation : page de gestion et nommage après création.
+//!
+//! Les modèles créés par l'IA portent le nom du fichier
+//! (« export_sacem_mars »). La boîte de dialogue propose un nom lisible dès
+//! la création, la page `/modeles` permet de les renommer ensuite.
+
+use leptos::*;
+use leptos_router::A;
+
+use super::upload::add_log;
+use crate::services::{list_templates, rename_template, NewTemplate, TemplateSummary};
+use crate::{backend_url, LogEntry, LogLevel};
+
+/// Boîte de dialogue proposant un nom pour le modèle créé par l'IA
+#[component]
+pub fn TemplateNameDialog(
+    /// Modèle à nommer (None une fois nommé ou ignoré)
+    new_template: RwSignal<Option<NewTemplate>>,
+    set_logs: WriteSignal<Vec<LogEntry>>,
+) -> impl IntoView {
+    let name = create_rw_signal(String::new());
+    let saving = create_rw_signal(false);
+    let error = create_rw_signal(None::<String>);
+
+    // Pré-remplir avec le nom proposé
+    create_effect(move |_| {
+        if let Some(template) = new_template.get() {
+            name.set(template.suggested_name);
+            error.set(None);
+        }
+    });
+
+    let on_save = move |_| {
+        let Some(template) = new_template.get_untracked() else { return };
+        let new_name = name.get_untracked().trim().to_string();
+        if new_name.is_empty() {
+            error.set(Some("Le nom ne peut pas être vide".to_string()));
+            return;
+        }
+        saving.set(true);
+        spawn_local(async move {
+            match rename_template(&template.id, &new_name, &backend_url()).await {
+                Ok(renamed) => {
+                    add_log(set_logs, LogLevel::Success, &format!("🏷️  Modèle enregistré sous « {} »", renamed.name));
+                    new_template.set(None);
+                }
+                Err(e) => error.set(Some(e)),
+            }
+            saving.set(false);
+        });
+    };
+
+    view! {
+        {move || new_template.get().map(|template| view! {
+            <div class="modal-backdrop">
+                <div class="modal" role="dialog" aria-modal="true" aria-labelledby="template-name-title">
+                    <div class="preview-title" id="template-name-title">"Nouveau modèle créé"</div>
+                    <div class="upload-hint">
+                        "L'IA a créé un modèle pour ce format de fichier, enregistré sous « "
+                        {template.name.clone()}
+                        " ». Donnez-lui un nom pour le retrouver facilement."
+                    </div>
+                    <input
+                        type="text"
+                        aria-label="Nom du modèle"
+                        prop:value=move || name.get()
+                        on:input=move |ev| name.set(event_target_value(&ev))
+                    />
+                    {move || error.get().map(|e| view! { <div class="error-message">{e}</div> })}
+                    <div class="preflight-actions">
+                        <button class="btn btn-secondary" on:click=move |_| new_template.set(None)>
+                            "Plus tard"
+                        </button>
+                        <button class="btn btn-primary" disabled=move || saving.get() on:click=on_save>
+                            "Enregistrer le nom"
+                        </button>
+                    </div>
+                </div>
+            </div>
+        })}
+    }
+}
+
+/// Ligne de la liste des modèles, avec renommage sur place
+#[component]
+fn TemplateRow(template: TemplateSummary, templates: RwSignal<Vec<TemplateSummary>>) -> impl IntoView {
+    let editing = create_rw_signal(false);
+    let name = create_rw_signal(template.name.clone());
+    let error = create_rw_signal(None::<String>);
+    let id = template.id.clone();
+
+    let on_save = move |_| {
+        let id = id.clone();
+        let new_name = name.get_untracked().trim().to_string();
+        spawn_local(async move {
+            match rename_template(&id, &new_name, &backend_url()).await {
+                Ok(renamed) => {
+                    templates.update(|list| {
+                        if let Some(t) = list.iter_mut().find(|t| t.id == renamed.id) {
+                            *t = renamed;
+                        }
+                    });
+                    editing.set(false);
+                    error.set(None);
+                }
+                Err(e) => error.set(Some(e)),
+            }
+        });
+    };
+
+    let current_name = template.name.clone();
+    view! {
+        <tr>
+            <td>
+                <Show
+                    when=move || editing.get()
+                    fallback=move || view! { <span class="template-name">{current_name.clone()}</span> }
+                >
+                    <input
+                        type="text"
+                        aria-label="Nouveau nom"
+                        prop:value=move || name.get()
+                        on:input=move |ev| name.set(event_target_value(&ev))
+                    />
+                    <button class="btn btn-primary" on:click=on_save.clone()>"OK"</button>
+                    <button class="btn btn-secondary" on:click=move |_| editing.set(false)>"Annuler"</button>
+                </Show>
+                {move || error.get().map(|e| view! { <div class="error-message">{e}</div> })}
+                <div class="template-id">{template.id.clone()}</div>
+            </td>
+            <td>{template.csv_columns.len()}</td>
+            <td>{format!("{:.0} %", template.success_rate * 100.0)}</td>
+            <td>{template.use_count}</td>
+            <td>{template.periods_summary()}</td>
+            <td>{template.created_date().to_string()}</td>
+            <td>
+                <button class="btn btn-secondary" on:click=move |_| editing.set(true)>"Renommer"</button>
+            </td>
+        </tr>
+    }
+}
+
+/// Page `/modeles` : modèles enregistrés par le backend
+#[component]
+pub fn TemplatesPage() -> impl IntoView {
+    let templates = create_rw_signal(Vec::<TemplateSummary>::new());
+    let error = create_rw_signal(None::<String>);
+    let loading = create_rw_signal(true);
+    // Filtre par période comptable (`2025-Q1`, ou une année)
+    let period = create_rw_signal(String::new());
+
+    let load = move || {
+        loading.set(true);
+        spawn_local(async move {
+            let filter = period.get_untracked().trim().to_string();
+            match list_templates(&backend_url(), Some(filter.as_str()).filter(|p| !p.is_empty())).await {
+                Ok(list) => {
+                    templates.set(list);
+                    error.set(None);
+                }
+                Err(e) => error.set(Some(e)),
+            }
+            loading.set(false);
+        });
+    };
+    load();
+
+    view! {
+        <div class="container templates-page">
+            <A href="/" class="footer-link">"← Retour"</A>
+            <div class="preview-title">"Modèles de transformation"</div>
+            <div class="upload-hint">
+                "Un modèle est réutilisé automatiquement pour les fichiers aux colonnes compatibles."
+            </div>
+            <input
+                class="job-period-filter"
+                type="search"
+                placeholder="Période (2025-Q1, 2025)"
+                aria-label="Filtrer par période comptable"
+                prop:value=move || period.get()
+                on:input=move |ev| period.set(event_target_value(&ev))
+                on:change=move |_| load()
+            />
+            {move || loading.get().then(|| view! { <div class="explorer-status">"Chargement..."</div> })}
+            {move || error.get().map(|e| view! { <div class="error-message">{e}</div> })}
+            <Show
+                when=move || !loading.get() && templates.with(|t| t.is_empty()) && error.get().is_none()
+                fallback=|| view! { }
+            >
+                <div class="explorer-status">"Aucun modèle enregistré pour l'instant."</div>
+            </Show>
+            <table class="explorer-table">
+                <thead>
+                    <tr>
+                        <th>"Nom"</th>
+                        <th>"Colonnes"</th>
+                        <th>"Réussite"</th>
+                        <th>"Utilisations"</th>
+                        <th>"Périodes"</th>
+                        <th>"Créé le"</th>
+                        <th></th>
+                    </tr>
+                </thead>
+                <tbody>
+                    <For
+                        each=move || templates.get()
+                        key=|t| (t.id.clone(), t.name.clone())
+                        children=move |t| view! { <TemplateRow template=t templates=templates/> }
+                    />
+                </tbody>
+            </table>
+        </div>
+    }
+}
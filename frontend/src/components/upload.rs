@@ -1,209 +1,780 @@
 //! CSV upload component with drag & drop support.
 //!
-//! Handles file selection, upload to backend, and result parsing.
+//! Handles file selection, preflight (column checklist), upload to backend,
+//! and result parsing.
 
 use leptos::*;
-use web_sys::{Event, HtmlInputElement};
+use web_sys::{Event, File, HtmlInputElement};
 use wasm_bindgen::JsCast;
-use crate::{PreviewItem, LogEntry, LogLevel, BACKEND_URL};
-use crate::services::upload_csv;
+use crate::{backend_url, LogEntry, LogLevel, MAX_FILE_SIZE};
+use crate::services::{ShortcutAction, fetch_example, new_job_id, push_job, JobRecord, JobStatus, UploadResponse, preflight_csv, read_review_batch, DEFAULT_EXAMPLE, upload_csv, validate_csv_file, FailureReport, AiCostEstimate, FIELD_FAILURE_WARN_RATE, ParseReport, PreflightResponse, ragged_rows_summary, Suggestion, UploadError, UploadOptions};
+use super::catalog_diff::show_works;
+use super::file_details::FileDetails;
+use super::demo_guide::{DemoGuide, DemoStep};
+use super::jobs::{update_job, watch_job, JobView};
+use super::shortcuts::use_shortcut;
+
+/// Column name fragments that usually hold personal data.
+/// Matching columns are pre-checked in the exclusion checklist.
+const PII_HINTS: &[&str] = &["mail", "adresse", "address", "phone", "téléphone", "telephone", "tel."];
+
+/// Encodings the user can force when detection is uncertain
+const ENCODING_CHOICES: &[(&str, &str)] = &[
+    ("utf-8", "UTF-8"),
+    ("windows-1252", "Windows-1252 (Excel)"),
+    ("iso-8859-1", "ISO-8859-1 (Latin-1)"),
+    ("iso-8859-15", "ISO-8859-15 (Latin-9, €)"),
+];
+
+/// Open the file picker of the upload zone (false when it isn't displayed)
+fn open_file_picker() -> bool {
+    let input = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.get_element_by_id("fileInput"))
+        .and_then(|input| input.dyn_into::<HtmlInputElement>().ok());
+    match input {
+        Some(input) => {
+            input.click();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Whether a column name looks like personal data
+fn looks_like_pii(column: &str) -> bool {
+    let lower = column.to_lowercase();
+    PII_HINTS.iter().any(|hint| lower.contains(hint))
+}
 
 #[component]
 pub fn UploadSection(
-    set_preview_data: WriteSignal<Option<Vec<PreviewItem>>>,
-    set_musical_works_json: WriteSignal<Option<serde_json::Value>>,
+    /// Affichage du job ouvert (logs, aperçu, encarts)
+    view: JobView,
     set_is_processing: WriteSignal<bool>,
-    set_logs: WriteSignal<Vec<LogEntry>>,
+    /// Imports du tableau de bord et job affiché
+    jobs: RwSignal<Vec<JobRecord>>,
+    open_job: RwSignal<Option<String>>,
+    /// Ouvre un job (vide l'affichage et restaure ses logs)
+    on_open_job: Callback<String>,
+    /// Demander les logs détaillés (Debug) au backend
+    verbose_logs: RwSignal<bool>,
+    /// Démo guidée : fichier d'exemple, étapes annotées
+    guided_demo: RwSignal<bool>,
 ) -> impl IntoView {
+    let JobView { set_preview_data, set_musical_works_json, set_file_sha256, set_logs, all_works, failure, .. } = view;
     let (is_uploading, set_is_uploading) = create_signal(false);
     let (error, set_error) = create_signal(None::<String>);
+    // Fichier sélectionné, en attente de confirmation après le preflight
+    let pending_file = create_rw_signal(None::<File>);
+    let preflight = create_rw_signal(None::<PreflightResponse>);
+    // Colonnes cochées pour exclusion
+    let excluded = create_rw_signal(Vec::<String>::new());
+    // Mode confidentiel : aucune valeur brute envoyée à l'IA
+    let structure_only = create_rw_signal(false);
+
+    // Période comptable du catalogue (`2025-Q1`), gardée d'un import à l'autre
+    let period = create_rw_signal(String::new());
 
-    // Handler pour le changement de fichier
+    // Encodage forcé par l'utilisateur quand la détection est incertaine
+    let forced_encoding = create_rw_signal(None::<String>);
+    // Upload refusé en attente de confirmation du coût IA
+    let ai_confirmation = create_rw_signal(None::<PendingAiUpload>);
+
+    // Preflight : colonnes et encodage détectés, sans IA
+    let run_preflight = move |file: File, encoding: Option<String>| {
+        spawn_local(async move {
+            set_is_uploading.set(true);
+            // Lot relu et exporté sans wallet : directement dans l'aperçu, prêt à signer
+            if let Some(batch) = read_review_batch(&file).await {
+                pending_file.set(None);
+                add_log(
+                    set_logs,
+                    LogLevel::Success,
+                    &format!("📥 Lot relu importé : {} œuvre(s) exportée(s) le {}, prêtes à signer",
+                        batch.works.len(), batch.exported_at.get(..10).unwrap_or(&batch.exported_at)),
+                );
+                set_file_sha256.set(batch.file_sha256);
+                all_works.set(batch.works.clone());
+                show_works(None, &batch.works, false, set_preview_data, set_musical_works_json);
+                set_is_uploading.set(false);
+                return;
+            }
+            match preflight_csv(&file, &backend_url(), encoding.as_deref()).await {
+                Ok(response) => {
+                    excluded.set(
+                        response.csv_info.columns.iter()
+                            .filter(|c| looks_like_pii(c))
+                            .cloned()
+                            .collect(),
+                    );
+                    preflight.set(Some(response));
+                }
+                Err(e) => {
+                    preflight.set(None);
+                    pending_file.set(None);
+                    set_error.set(Some(e));
+                }
+            }
+            set_is_uploading.set(false);
+        });
+    };
+
+    // Changement d'encodage : relancer le preflight pour voir les colonnes corrigées
+    let on_encoding_change = move |ev: Event| {
+        let value = event_target_value(&ev);
+        let encoding = Some(value).filter(|v| !v.is_empty());
+        forced_encoding.set(encoding.clone());
+        if let Some(file) = pending_file.get_untracked() {
+            run_preflight(file, encoding);
+        }
+    };
+
+    // Handler pour le changement de fichier : lance le preflight
     let on_file_change = move |ev: Event| {
         let input: HtmlInputElement = event_target(&ev);
-        
+
         if let Some(files) = input.files() {
             if files.length() > 0 {
                 if let Some(file) = files.get(0) {
                     // Réinitialiser l'état
                     set_error.set(None);
                     set_preview_data.set(None);
-                    set_logs.set(Vec::new());
-                    
-                    // Lancer l'upload
-                    spawn_local(async move {
-                        set_is_uploading.set(true);
-                        set_is_processing.set(true);
-                        
-                        // Log de début
-                        add_log(set_logs, LogLevel::Info, "📤 Uploading CSV file...");
-                        
-                        // Upload
-                        match upload_csv(file, BACKEND_URL).await {
-                            Ok(response) => {
-                                add_log(
-                                    set_logs,
-                                    LogLevel::Success,
-                                    &format!("✅ Upload successful! {} works found", response.metadata.total_works),
-                                );
-                                
-                                if response.metadata.cached {
-                                    let id = response.metadata.matrix_id.as_deref().unwrap_or("unknown");
-                                    add_log(
-                                        set_logs,
-                                        LogLevel::Info,
-                                        &format!("♻️  Used cached transformation matrix: {}", id),
-                                    );
-                                } else {
-                                    let id = response.metadata.matrix_id.as_deref().unwrap_or("new");
-                                    add_log(
-                                        set_logs,
-                                        LogLevel::Info,
-                                        &format!("🤖 AI generated new transformation matrix: {}", id),
-                                    );
-                                }
-                                
-                                // Convertir en PreviewItems
-                                // Sauvegarder les musical works JSON complets
-                                set_musical_works_json.set(Some(serde_json::Value::Array(response.musical_works.clone())));
-                                
-                                // Convertir en PreviewItems
-                                let preview_items: Vec<PreviewItem> = response
-                                    .musical_works
-                                    .iter()
-                                    .filter_map(|work| {
-                                        // ISWC
-                                        let iswc = work.get("iswc")?.as_str()?.to_string();
-                                        
-                                        // Title peut être String ou Object {title: "...", language: "..."}
-                                        let title = if let Some(title_str) = work.get("title").and_then(|t| t.as_str()) {
-                                            title_str.to_string()
-                                        } else if let Some(title_obj) = work.get("title").and_then(|t| t.as_object()) {
-                                            title_obj.get("title")?.as_str()?.to_string()
-                                        } else {
-                                            return None;
-                                        };
-                                        
-                                        // Creators count
-                                        let creators_count = work.get("creators")
-                                            .and_then(|c| c.as_array())
-                                            .map(|arr| arr.len())
-                                            .unwrap_or(0);
-                                        
-                                        Some(PreviewItem {
-                                            title,
-                                            iswc,
-                                            creators_count,
-                                        })
-                                    })
-                                    .collect();
-                                
-                                set_preview_data.set(Some(preview_items));
-                                
-                                add_log(
-                                    set_logs,
-                                    LogLevel::Success,
-                                    &format!("🎵 Estimated cost: {}", response.metadata.estimated_cost),
-                                );
-                            }
-                            Err(e) => {
-                                add_log(set_logs, LogLevel::Error, &format!("❌ Upload failed: {}", e));
-                                set_error.set(Some(e));
-                            }
-                        }
-                        
-                        set_is_uploading.set(false);
-                        set_is_processing.set(false);
-                    });
+                    failure.set(None);
+                    // Permettre de resélectionner le même fichier après une erreur
+                    input.set_value("");
+                    guided_demo.set(false);
+
+                    if let Err(e) = validate_csv_file(&file.name(), &file.type_(), file.size()) {
+                        set_error.set(Some(e));
+                        return;
+                    }
+
+                    preflight.set(None);
+                    forced_encoding.set(None);
+                    pending_file.set(Some(file.clone()));
+                    run_preflight(file, None);
                 }
             }
         }
     };
 
-    // Handler pour cliquer sur la zone entière
-    let trigger_file_input = move |_| {
-        if let Some(window) = web_sys::window() {
-            if let Some(document) = window.document() {
-                if let Some(input) = document.get_element_by_id("fileInput") {
-                    if let Some(html_input) = input.dyn_ref::<HtmlInputElement>() {
-                        html_input.click();
-                    }
+    // Fichier d'exemple du backend : même parcours qu'un fichier choisi, étapes annotées
+    let on_try_example = move |ev: ev::MouseEvent| {
+        // Ne pas ouvrir le sélecteur de fichier de la zone d'upload
+        ev.stop_propagation();
+        set_error.set(None);
+        set_preview_data.set(None);
+        failure.set(None);
+        spawn_local(async move {
+            set_is_uploading.set(true);
+            match fetch_example(DEFAULT_EXAMPLE, &backend_url()).await {
+                Ok(file) => {
+                    guided_demo.set(true);
+                    preflight.set(None);
+                    forced_encoding.set(None);
+                    pending_file.set(Some(file.clone()));
+                    run_preflight(file, None);
+                }
+                Err(e) => {
+                    set_error.set(Some(format!("Fichier d'exemple indisponible : {}", e)));
+                    set_is_uploading.set(false);
                 }
             }
+        });
+    };
+
+    // Envoyer le fichier au backend comme un job du tableau de bord, ouvert à l'écran
+    let start_upload = move |file: File, options: UploadOptions| {
+        let id = options.job_id.clone().unwrap_or_else(new_job_id);
+        jobs.update(|jobs| match jobs.iter_mut().find(|j| j.id == id) {
+            // Relance après confirmation du coût IA
+            Some(job) => job.status = JobStatus::Running,
+            None => push_job(jobs, JobRecord::new(id.clone(), file.name()).with_period(options.period.clone())),
+        });
+        on_open_job.call(id.clone());
+        let options = UploadOptions { job_id: Some(id), ..options };
+        spawn_local(async move {
+            set_is_uploading.set(true);
+            set_is_processing.set(true);
+            let targets = UploadTargets { view, set_error, ai_confirmation, jobs, open_job };
+            run_upload(file, options, targets).await;
+            set_is_uploading.set(false);
+            set_is_processing.set(false);
+        });
+    };
+
+    // Lancer la transformation avec les colonnes exclues
+    let start_transform = move || {
+        let Some(file) = pending_file.get_untracked() else {
+            return;
+        };
+        let options = UploadOptions {
+            exclude_columns: excluded.get_untracked(),
+            structure_only: structure_only.get_untracked(),
+            encoding: forced_encoding.get_untracked(),
+            verbose: verbose_logs.get_untracked(),
+            confirm_ai: false,
+            job_id: None,
+            period: Some(period.get_untracked().trim().to_string()).filter(|p| !p.is_empty()),
+        };
+        preflight.set(None);
+        pending_file.set(None);
+        start_upload(file, options);
+    };
+
+    // Coût IA confirmé : renvoyer le même fichier avec la confirmation
+    let on_confirm_ai = Callback::new(move |_| {
+        if let Some(pending) = ai_confirmation.get_untracked() {
+            ai_confirmation.set(None);
+            start_upload(pending.file, UploadOptions { confirm_ai: true, ..pending.options });
         }
+    });
+
+    let on_cancel_preflight = move |_| {
+        preflight.set(None);
+        pending_file.set(None);
+        forced_encoding.set(None);
     };
 
+    // Handler pour cliquer sur la zone entière
+    let trigger_file_input = move |_| {
+        open_file_picker();
+    };
+
+    // Raccourcis : `u` choisit un fichier, `v` valide les colonnes ou le coût IA
+    use_shortcut(ShortcutAction::Upload, move || {
+        preflight.get_untracked().is_none() && ai_confirmation.get_untracked().is_none() && open_file_picker()
+    });
+    use_shortcut(ShortcutAction::Validate, move || {
+        if ai_confirmation.get_untracked().is_some() {
+            on_confirm_ai.call(());
+        } else if preflight.get_untracked().is_some() && pending_file.get_untracked().is_some() {
+            start_transform();
+        } else {
+            return false;
+        }
+        true
+    });
+
     view! {
-        <div 
-            class="upload-section" 
-            id="uploadZone"
-            on:click=trigger_file_input
+        <AiCostDialog pending=ai_confirmation on_confirm=on_confirm_ai/>
+        <Show
+            when=move || preflight.get().is_some()
+            fallback=move || view! {
+                <div
+                    class="upload-section"
+                    id="uploadZone"
+                    on:click=trigger_file_input
+                >
+                    <div class="upload-icon">"📤"</div>
+                    <div class="upload-text">
+                        {move || if is_uploading.get() {
+                            "⏳ Uploading and processing..."
+                        } else {
+                            "Glissez un fichier CSV ici"
+                        }}
+                    </div>
+
+                    <Show
+                        when=move || !is_uploading.get()
+                        fallback=|| view! { }
+                    >
+                        <div class="upload-hint">"ou cliquez pour sélectionner"</div>
+                        <div class="upload-hint mt-20">
+                            "Formats supportés : SACEM, ASCAP, GEMA, JASRAC, PRS, SGAE"
+                            <br/>
+                            "Transformation automatique par IA"
+                            <br/>
+                            {format!("Fichier .csv, .xlsx, .cwr ou .json (zippé ou non), {} Mo maximum", MAX_FILE_SIZE / (1024 * 1024))}
+                        </div>
+                    </Show>
+
+                    <Show
+                        when=move || error.get().is_some()
+                        fallback=|| view! { }
+                    >
+                        <div class="error-message">
+                            {move || error.get().unwrap_or_default()}
+                        </div>
+                    </Show>
+
+                    <input
+                        type="file"
+                        id="fileInput"
+                        accept=".csv,text/csv,.xlsx,.cwr,.v21,.v22,.json,.ndjson,.jsonl,application/json,.zip,.gz"
+                        style="display:none"
+                        on:change=on_file_change
+                    />
+
+                    <Show
+                        when=move || !is_uploading.get()
+                        fallback=|| view! { }
+                    >
+                        <label for="fileInput" class="upload-button">
+                            "Choisir un fichier CSV"
+                        </label>
+                        <button class="example-button" on:click=on_try_example>
+                            "Tester avec un fichier d'exemple"
+                        </button>
+                    </Show>
+                </div>
+            }
         >
-            <div class="upload-icon">"📤"</div>
-            <div class="upload-text">
-                {move || if is_uploading.get() {
-                    "⏳ Uploading and processing..."
-                } else {
-                    "Glissez un fichier CSV ici"
-                }}
-            </div>
-            
-            <Show
-                when=move || !is_uploading.get()
-                fallback=|| view! { }
-            >
-                <div class="upload-hint">"ou cliquez pour sélectionner"</div>
-                <div class="upload-hint mt-20">
-                    "Formats supportés : SACEM, ASCAP, GEMA, JASRAC, PRS, SGAE"
-                    <br/>
-                    "Transformation automatique par IA"
+            <div class="preflight-panel">
+                <DemoGuide guided=guided_demo step=DemoStep::Preflight/>
+                <div class="preflight-title">
+                    "📄 " {move || preflight.get().and_then(|p| p.file_name).unwrap_or_else(|| "Fichier".to_string())}
+                    " • " {move || preflight.get().map(|p| p.csv_info.row_count).unwrap_or(0)} " lignes"
                 </div>
-            </Show>
-            
-            <Show
-                when=move || error.get().is_some()
-                fallback=|| view! { }
-            >
-                <div class="error-message">
-                    {move || error.get().unwrap_or_default()}
+                <Show
+                    when=move || {
+                        forced_encoding.get().is_some()
+                            || preflight.get().is_some_and(|p| p.csv_info.encoding_uncertain())
+                    }
+                    fallback=|| view! { }
+                >
+                    <div class="encoding-warning">
+                        "⚠️ Encodage détecté : "
+                        {move || preflight.get().map(|p| p.csv_info.encoding).unwrap_or_default()}
+                        {move || if forced_encoding.get().is_some() { " (forcé)" } else { " (incertain)" }}
+                        ". Si les accents ou le signe € s'affichent mal, choisissez l'encodage :"
+                        <select on:change=on_encoding_change>
+                            <option value="" selected=move || forced_encoding.get().is_none()>"Automatique"</option>
+                            {ENCODING_CHOICES.iter().map(|(value, label)| view! {
+                                <option
+                                    value=*value
+                                    selected=move || forced_encoding.get().as_deref() == Some(*value)
+                                >
+                                    {*label}
+                                </option>
+                            }).collect_view()}
+                        </select>
+                    </div>
+                </Show>
+                <div class="file-checksum">
+                    "SHA-256 : " {move || preflight.get().and_then(|p| p.csv_info.sha256).unwrap_or_default()}
                 </div>
-            </Show>
-            
-            <input
-                type="file"
-                id="fileInput"
-                accept=".csv"
-                style="display:none"
-                on:change=on_file_change
-            />
-            
-            <Show
-                when=move || !is_uploading.get()
-                fallback=|| view! { }
-            >
-                <label for="fileInput" class="upload-button">
-                    "Choisir un fichier CSV"
+                <Show
+                    when=move || preflight.get().is_some_and(|p| !p.csv_info.footer_lines.is_empty())
+                    fallback=|| view! { }
+                >
+                    <div class="upload-hint">
+                        "Lignes de fin ignorées (totaux) : "
+                        {move || preflight.get().map(|p| p.csv_info.footer_lines.join(" | ")).unwrap_or_default()}
+                    </div>
+                </Show>
+                <Show
+                    when=move || preflight.get().is_some_and(|p| !p.csv_info.diagnostics.notes.is_empty())
+                    fallback=|| view! { }
+                >
+                    <div class="upload-hint">
+                        "Fichier réparé avant lecture : "
+                        {move || preflight.get().map(|p| p.csv_info.diagnostics.notes.join(" ; ")).unwrap_or_default()}
+                    </div>
+                </Show>
+                <Show
+                    when=move || preflight.get().is_some_and(|p| !p.csv_info.ragged_rows.is_empty())
+                    fallback=|| view! { }
+                >
+                    <div class="upload-hint">
+                        {move || preflight.get().map(|p| ragged_rows_summary(&p.csv_info.ragged_rows)).unwrap_or_default()}
+                    </div>
+                </Show>
+                <div class="upload-hint">
+                    "Cochez les colonnes contenant des données personnelles : elles seront retirées avant l'analyse IA et ne seront jamais enregistrées."
+                </div>
+                <ColumnChecklist
+                    columns=Signal::derive(move || preflight.get().map(|p| p.csv_info.columns).unwrap_or_default())
+                    report=Signal::derive(move || preflight.get().map(|p| p.csv_info.report).unwrap_or_default())
+                    excluded=excluded
+                />
+                <label class="structure-only-toggle" class:active=move || structure_only.get()>
+                    <input
+                        type="checkbox"
+                        prop:checked=move || structure_only.get()
+                        on:change=move |_| structure_only.update(|v| *v = !*v)
+                    />
+                    "Mode confidentiel (structure seule) : l'IA ne voit que les noms de colonnes et les formats des valeurs. Les correspondances de codes (rôles, langues) peuvent être moins précises."
+                </label>
+                <label class="structure-only-toggle" class:active=move || verbose_logs.get()>
+                    <input
+                        type="checkbox"
+                        prop:checked=move || verbose_logs.get()
+                        on:change=move |_| verbose_logs.update(|v| *v = !*v)
+                    />
+                    "Logs détaillés : une ligne de log par ligne ignorée ou invalide."
                 </label>
-            </Show>
+                <label class="batch-label" for="catalog-period-input">
+                    "Période comptable (optionnel)"
+                </label>
+                <input
+                    id="catalog-period-input"
+                    type="text"
+                    placeholder="ex. 2025-Q1, 2025-03, 2025-W07"
+                    prop:value=move || period.get()
+                    on:input=move |ev| period.set(event_target_value(&ev))
+                />
+                <div class="upload-hint">
+                    "Reprise dans le journal, les statistiques des modèles et les récapitulatifs ; filtre l'historique des imports."
+                </div>
+                <div class="preflight-actions">
+                    <button class="btn btn-secondary" on:click=on_cancel_preflight>"Annuler"</button>
+                    <button class="btn btn-primary" on:click=move |_| start_transform()>
+                        {move || match excluded.get().len() {
+                            0 => "Lancer la transformation".to_string(),
+                            n => format!("Lancer la transformation ({} colonne(s) exclue(s))", n),
+                        }}
+                    </button>
+                </div>
+            </div>
+        </Show>
+    }
+}
+
+/// Upload waiting for the user to accept the estimated AI cost
+#[derive(Clone)]
+struct PendingAiUpload {
+    file: File,
+    options: UploadOptions,
+    estimate: AiCostEstimate,
+}
+
+/// Estimated AI cost above the server threshold: confirm or cancel the upload
+#[component]
+fn AiCostDialog(pending: RwSignal<Option<PendingAiUpload>>, on_confirm: Callback<()>) -> impl IntoView {
+    view! {
+        {move || pending.get().map(|pending_upload| {
+            let estimate = pending_upload.estimate;
+            view! {
+                <div class="modal-backdrop">
+                    <div class="modal" role="dialog" aria-modal="true" aria-labelledby="ai-cost-title">
+                        <div class="preview-title" id="ai-cost-title">"Confirmer l'appel à l'IA"</div>
+                        <div class="upload-hint">
+                            "Aucun modèle existant ne correspond à « "
+                            {pending_upload.file.name()}
+                            " ». L'IA va générer un modèle de transformation :"
+                        </div>
+                        <ul class="ai-estimate">
+                            <li>{format!("Prompt : {} caractères, ~{} tokens en entrée", estimate.prompt_chars, estimate.input_tokens)}</li>
+                            <li>{format!("Réponse : {} tokens maximum", estimate.max_output_tokens)}</li>
+                            <li><strong>{format!("Coût estimé : {:.4} $ au plus", estimate.cost_usd)}</strong></li>
+                        </ul>
+                        <div class="preflight-actions">
+                            <button class="btn btn-secondary" on:click=move |_| pending.set(None)>"Annuler"</button>
+                            <button class="btn btn-primary" on:click=move |_| on_confirm.call(())>
+                                "Confirmer et lancer"
+                            </button>
+                        </div>
+                    </div>
+                </div>
+            }
+        })}
+    }
+}
+
+/// Checklist of detected columns; checked columns are excluded from processing.
+/// Hovering a column shows its fill rate and sample values; empty ones are dimmed.
+#[component]
+fn ColumnChecklist(
+    columns: Signal<Vec<String>>,
+    report: Signal<ParseReport>,
+    excluded: RwSignal<Vec<String>>,
+) -> impl IntoView {
+    view! {
+        <div class="column-checklist">
+            <For
+                each=move || columns.get()
+                key=|col| col.clone()
+                children=move |col| {
+                    let col_for_check = col.clone();
+                    let col_for_toggle = col.clone();
+                    let is_checked = move || excluded.get().contains(&col_for_check);
+                    let on_toggle = move |_| {
+                        excluded.update(|list| {
+                            if let Some(pos) = list.iter().position(|c| c == &col_for_toggle) {
+                                list.remove(pos);
+                            } else {
+                                list.push(col_for_toggle.clone());
+                            }
+                        });
+                    };
+                    let profile = report.with_untracked(|r| r.column(&col).cloned());
+                    let summary = profile.as_ref().map(|p| p.summary());
+                    let is_empty = profile.as_ref().is_some_and(|p| p.is_empty());
+                    view! {
+                        <label class="column-check" class:excluded=is_checked.clone() class:column-empty=is_empty title=summary>
+                            <input type="checkbox" prop:checked=is_checked on:change=on_toggle/>
+                            {col}
+                        </label>
+                    }
+                }
+            />
         </div>
     }
 }
 
-fn add_log(set_logs: WriteSignal<Vec<LogEntry>>, level: LogLevel, message: &str) {
-    // Utiliser Date JS pour le timestamp
-    let timestamp = js_sys::Date::new_0()
-        .to_locale_time_string("fr-FR")
-        .as_string()
-        .unwrap_or_else(|| "00:00:00".to_string());
-    
+/// Signals fed by the upload response
+#[derive(Clone, Copy)]
+struct UploadTargets {
+    view: JobView,
+    set_error: WriteSignal<Option<String>>,
+    ai_confirmation: RwSignal<Option<PendingAiUpload>>,
+    jobs: RwSignal<Vec<JobRecord>>,
+    open_job: RwSignal<Option<String>>,
+}
+
+/// Logs of one job: into the logs panel while the job is open, otherwise
+/// into its dashboard entry (shown again when the job is reopened)
+struct JobLog {
+    id: String,
+    jobs: RwSignal<Vec<JobRecord>>,
+    open_job: RwSignal<Option<String>>,
+    set_logs: WriteSignal<Vec<LogEntry>>,
+}
+
+impl JobLog {
+    fn is_open(&self) -> bool {
+        self.open_job.get_untracked().as_deref() == Some(self.id.as_str())
+    }
+
+    fn push(&self, entry: LogEntry) {
+        if self.is_open() {
+            self.set_logs.update(|logs| logs.push(entry));
+        } else {
+            log::info!("{}", entry.message);
+            update_job(self.jobs, &self.id, |job| job.logs.push(entry));
+        }
+    }
+
+    fn add(&self, level: LogLevel, message: &str) {
+        self.push(LogEntry::new(level, message));
+    }
+}
+
+/// Upload the file as the dashboard job `options.job_id` and record its outcome;
+/// the preview is fed only if the job is still the one on screen
+async fn run_upload(file: File, options: UploadOptions, targets: UploadTargets) {
+    let UploadTargets { view, set_error, ai_confirmation, jobs, open_job } = targets;
+    let id = options.job_id.clone().unwrap_or_default();
+    let log = JobLog { id: id.clone(), jobs, open_job, set_logs: view.set_logs };
+
+    // Log de début
+    log.add(LogLevel::Info, "📤 Uploading CSV file...");
+    if !options.exclude_columns.is_empty() {
+        log.add(
+            LogLevel::Info,
+            &format!("🔒 Colonnes exclues : {}", options.exclude_columns.join(", ")),
+        );
+    }
+    if let Some(ref encoding) = options.encoding {
+        log.add(LogLevel::Info, &format!("🔤 Encodage forcé : {}", encoding));
+    }
+    if let Some(ref period) = options.period {
+        log.add(LogLevel::Info, &format!("🗓️ Période comptable : {}", period));
+    }
+    if options.structure_only {
+        log.add(LogLevel::Warning, "🔒 Mode confidentiel : seule la structure est envoyée à l'IA");
+    }
+
+    // Upload, avancement relevé en parallèle pour le tableau de bord
+    spawn_local(watch_job(id.clone(), jobs));
+    match upload_csv(file.clone(), &backend_url(), &options).await {
+        Ok(response) => {
+            for entry in response_logs(&response) {
+                log.push(entry);
+            }
+            if log.is_open() {
+                show_response(&response, view);
+                if response.status != "all_invalid" {
+                    view.new_template.set(response.metadata.new_template.clone());
+                }
+            }
+            update_job(jobs, &id, |job| job.finish(response));
+        }
+        Err(UploadError::AiConfirmation(estimate)) => {
+            log.add(
+                LogLevel::Warning,
+                &format!("💰 Coût IA estimé à {:.4} $ : en attente de confirmation", estimate.cost_usd),
+            );
+            update_job(jobs, &id, |job| job.status = JobStatus::AwaitingConfirmation);
+            ai_confirmation.set(Some(PendingAiUpload { file, options, estimate }));
+        }
+        Err(UploadError::Failed(e)) => {
+            log.add(LogLevel::Error, &format!("❌ Upload failed: {}", e));
+            update_job(jobs, &id, |job| job.fail(e.clone()));
+            set_error.set(Some(e));
+        }
+    }
+}
+
+/// Log lines summarizing an upload response
+fn response_logs(response: &UploadResponse) -> Vec<LogEntry> {
+    let mut logs = Vec::new();
+    let mut add = |level: LogLevel, message: String| logs.push(LogEntry::new(level, message));
+
+    if response.status == "all_invalid" {
+        add(
+            LogLevel::Error,
+            format!("❌ Aucune ligne valide sur {} ligne(s)", response.metadata.csv_info.row_count),
+        );
+        return logs;
+    }
+
+    add(
+        LogLevel::Success,
+        format!("✅ Upload successful! {} works found", response.metadata.total_works),
+    );
+
+    if response.metadata.cached {
+        let id = response.metadata.matrix_id.as_deref().unwrap_or("unknown");
+        add(LogLevel::Info, format!("♻️  Used cached transformation matrix: {}", id));
+    } else {
+        let id = response.metadata.matrix_id.as_deref().unwrap_or("new");
+        add(LogLevel::Info, format!("🤖 AI generated new transformation matrix: {}", id));
+    }
+    if let Some(ref engine) = response.metadata.matrix_engine {
+        if let Some(ref reason) = engine.failover_reason {
+            add(LogLevel::Warning, format!("↪️  Basculement IA : {}", reason));
+        }
+        let level = if engine.engine == "primary" { LogLevel::Info } else { LogLevel::Warning };
+        add(level, format!("Modèle généré par : {}", engine.label()));
+    }
+    if let Some(ref estimate) = response.metadata.ai_estimate {
+        add(
+            LogLevel::Info,
+            format!("💰 Appel IA : ~{} tokens en entrée, {:.4} $ au plus", estimate.input_tokens, estimate.cost_usd),
+        );
+    }
+    for (field, stats) in &response.metadata.field_stats {
+        if stats.failure_rate() > FIELD_FAILURE_WARN_RATE {
+            add(
+                LogLevel::Warning,
+                format!(
+                    "🧮 {} : {}/{} valeur(s) non transformée(s) ({:.0} %), vérifiez le modèle",
+                    field, stats.failures, stats.values, stats.failure_rate() * 100.0
+                ),
+            );
+        }
+    }
+    if let Some(ref watermark) = response.metadata.watermark {
+        add(LogLevel::Warning, format!("🧪 {}", watermark));
+    }
+
+    // Colonnes du modèle en cache : lues, manquantes, ignorées
+    if let Some(ref columns) = response.metadata.template_columns {
+        add(
+            LogLevel::Info,
+            format!("🧩 Colonnes utilisées ({}) : {}", columns.matched.len(), columns.matched.join(", ")),
+        );
+        if !columns.missing.is_empty() {
+            add(
+                LogLevel::Warning,
+                format!("⚠️  Colonnes du modèle absentes du fichier : {}", columns.missing.join(", ")),
+            );
+        }
+        if !columns.unused.is_empty() {
+            let level = if columns.mostly_ignored() { LogLevel::Warning } else { LogLevel::Info };
+            add(
+                level,
+                format!("Colonnes du fichier ignorées par le modèle : {}", columns.unused.join(", ")),
+            );
+        }
+    }
+
+    if let Some(ref sha256) = response.metadata.csv_info.sha256 {
+        add(LogLevel::Info, format!("🔐 SHA-256 du fichier : {}", sha256));
+    }
+
+    // Lignes ignorées : complétables via le tableau de correction
+    let fixable = response.metadata.skipped.iter()
+        .filter(|r| r.single_missing_field().is_some())
+        .count();
+    if fixable > 0 {
+        add(
+            LogLevel::Warning,
+            format!("✏️  {} ligne(s) ignorée(s) pour un seul champ manquant : complétez-les sous l'aperçu", fixable),
+        );
+    }
+    let roles = &response.metadata.roles;
+    if roles.works_without_composer > 0 {
+        add(
+            LogLevel::Warning,
+            format!("🎼 {} œuvre(s) sans compositeur : voir la répartition des rôles sous l'aperçu", roles.works_without_composer),
+        );
+    }
+    if !roles.unsupported.is_empty() {
+        add(
+            LogLevel::Warning,
+            format!("⛓️ {} rôle(s) refusé(s) par {} : voir les substitutions proposées sous l'aperçu",
+                roles.unsupported.len(), roles.network),
+        );
+    }
+    let year_warnings = &response.metadata.validation.warnings;
+    if let Some(first) = year_warnings.first() {
+        add(
+            LogLevel::Warning,
+            format!("📅 {} œuvre(s) avec une année de création improbable (ex. œuvre {}{} : {})",
+                year_warnings.len(), first.record_index,
+                first.lines.first().map(|l| format!(", ligne {}", l)).unwrap_or_default(), first.message),
+        );
+    }
+    if response.metadata.missing_iswc > 0 {
+        add(
+            LogLevel::Warning,
+            format!(
+                "🏷️  {} œuvre(s) sans ISWC : téléchargez la demande d'attribution sous l'aperçu",
+                response.metadata.missing_iswc
+            ),
+        );
+    }
+
+    // Mode révision : catalogue déjà transformé, ne proposer que les changements
+    if let Some(ref diff) = response.metadata.diff {
+        add(LogLevel::Info, format!("🔁 Catalogue déjà transformé : {}", diff.summary()));
+    }
+
+    add(
+        LogLevel::Success,
+        format!("🎵 Estimated cost: {}", response.metadata.estimated_cost),
+    );
+    logs
+}
+
+/// Feed the preview and the panels below it with an upload response
+/// (on completion, or when the job is reopened from the dashboard)
+pub(crate) fn show_response(response: &UploadResponse, view: JobView) {
+    // Le fichier reste explorable, même pour comprendre un échec
+    view.job_id.set(Some(response.job_id.clone()));
+
+    if response.status == "all_invalid" {
+        // Aucune ligne valide : écran d'échec plutôt qu'un aperçu vide
+        view.failure.set(Some(response.metadata.failure.clone().unwrap_or(FailureReport {
+            top_causes: Vec::new(),
+            suggestions: vec![Suggestion::CheckTemplate],
+        })));
+        return;
+    }
+
+    view.set_file_sha256.set(response.metadata.csv_info.sha256.clone());
+    view.set_catalog_period.set(response.metadata.period.clone());
+    view.file_details.set(Some(FileDetails::from_metadata(&response.metadata)));
+    view.skipped_rows.set(response.metadata.skipped.clone());
+    view.missing_iswc.set(response.metadata.missing_iswc);
+    view.role_report.set(Some(response.metadata.roles.clone()));
+
+    let diff = response.metadata.diff.clone();
+    view.changes_only.set(diff.is_some());
+    show_works(diff.as_ref(), &response.musical_works, diff.is_some(), view.set_preview_data, view.set_musical_works_json);
+    view.catalog_diff.set(diff);
+    view.all_works.set(response.musical_works.clone());
+}
+
+pub(crate) fn add_log(set_logs: WriteSignal<Vec<LogEntry>>, level: LogLevel, message: &str) {
     set_logs.update(|logs| {
-        logs.push(LogEntry {
-            level,
-            message: message.to_string(),
-            timestamp,
-        });
+        logs.push(LogEntry::new(level, message));
     });
-    
+
     // Log aussi dans la console
     log::info!("{}", message);
 }
@@ -26,6 +26,51 @@ pub const BLOCKCHAIN_RPC: &str = match option_env!("BLOCKCHAIN_RPC") {
     None => "wss://node-dev.allfeat.io",
 };
 
+/// Allfeat network of `BLOCKCHAIN_RPC`: `devnet`, `testnet` or `mainnet`.
+///
+/// Set via `CHAIN_NETWORK` env var at build time.
+/// Creator roles the network does not accept are reported after the upload.
+pub const CHAIN_NETWORK: &str = match option_env!("CHAIN_NETWORK") {
+    Some(network) => network,
+    None => "devnet",
+};
+
+/// Time to wait for a submitted batch to be finalized (in seconds).
+///
+/// Set via `SUBMISSION_TIMEOUT_SECS` env var at build time.
+/// Past it, the batch is reported as in block (not finalized) or timed out.
+pub const SUBMISSION_TIMEOUT_SECS: &str = match option_env!("SUBMISSION_TIMEOUT_SECS") {
+    Some(secs) => secs,
+    None => "120",
+};
+
+/// Inactivity before the wallet state is cleared (in minutes).
+///
+/// Set via `IDLE_LOCK_MINUTES` env var at build time, `0` disables the lock.
+/// The wallet must then be reconnected before signing.
+pub const IDLE_LOCK_MINUTES: &str = match option_env!("IDLE_LOCK_MINUTES") {
+    Some(minutes) => minutes,
+    None => "15",
+};
+
+/// Blockchain RPC queries running at once (balance, status checks).
+///
+/// Set via `RPC_CONCURRENCY` env var at build time.
+/// Further queries wait in the RPC queue instead of hitting the node in parallel.
+pub const RPC_CONCURRENCY: &str = match option_env!("RPC_CONCURRENCY") {
+    Some(n) => n,
+    None => "4",
+};
+
+/// Retries of a failed RPC query before its error is reported.
+///
+/// Set via `RPC_RETRIES` env var at build time.
+/// Each retry waits a little longer than the previous one.
+pub const RPC_RETRIES: &str = match option_env!("RPC_RETRIES") {
+    Some(n) => n,
+    None => "2",
+};
+
 /// Application name for wallet connection.
 ///
 /// Displayed in wallet extension popups.
@@ -16,6 +16,49 @@ let web3Initialized = false;
 let cachedClient = null;
 let cachedRpcUrl = null;
 
+/**
+ * RPC queries running at once and retries, set from the app configuration
+ * (RPC_CONCURRENCY, RPC_RETRIES) by configureRpcQueue
+ */
+const rpcQueue = { concurrency: 4, retries: 2, retryDelayMs: 500, active: 0, waiting: [] };
+
+export function configureRpcQueue(concurrency, retries, retryDelayMs) {
+    rpcQueue.concurrency = Math.max(1, concurrency);
+    rpcQueue.retries = Math.max(0, retries);
+    rpcQueue.retryDelayMs = Math.max(0, retryDelayMs);
+}
+
+/**
+ * Run an RPC query once a slot is free, retrying it on error
+ * (delay × attempt between attempts); the last error is thrown
+ */
+async function withRpcSlot(query) {
+    if (rpcQueue.active >= rpcQueue.concurrency) {
+        // The slot is handed over by the query that releases it
+        await new Promise(resolve => rpcQueue.waiting.push(resolve));
+    } else {
+        rpcQueue.active++;
+    }
+    try {
+        for (let attempt = 0; ; attempt++) {
+            try {
+                return await query();
+            } catch (e) {
+                if (attempt >= rpcQueue.retries) throw e;
+                console.warn(`RPC query failed (${e.message}), retry ${attempt + 1}/${rpcQueue.retries}`);
+                await new Promise(resolve => setTimeout(resolve, rpcQueue.retryDelayMs * (attempt + 1)));
+            }
+        }
+    } finally {
+        const next = rpcQueue.waiting.shift();
+        if (next) {
+            next();
+        } else {
+            rpcQueue.active--;
+        }
+    }
+}
+
 /**
  * Convert IPI numbers to BigInt (SDK requirement)
  */
@@ -47,6 +90,17 @@ function convertPartyIdToBigInt(id) {
     return id;
 }
 
+/**
+ * Actual fee paid (planck, as a string) from the TransactionFeePaid event
+ */
+function actualFee(events) {
+    const paid = (events || []).find(({ event }) =>
+        event?.pallet === 'TransactionPayment' && event?.palletEvent?.name === 'TransactionFeePaid'
+    );
+    const fee = paid?.event?.palletEvent?.data?.actualFee;
+    return fee != null ? fee.toString() : null;
+}
+
 async function ensureWeb3Enabled() {
     if (!web3Initialized) {
         console.log('🔌 Initializing web3...');
@@ -87,7 +141,7 @@ async function getClient(rpcUrl) {
  */
 async function checkBalance(client, address) {
     try {
-        const balance = await client.getBalanceOf(address);
+        const balance = await withRpcSlot(() => client.getBalanceOf(address));
         const balanceFeat = Number(balance) / 10 ** 12;
         console.log(`💰 Balance: ${balanceFeat.toFixed(4)} FEAT`);
         
@@ -102,12 +156,224 @@ async function checkBalance(client, address) {
 }
 
 /**
- * Submit a batch of musical works with proper finalization waiting
+ * Sign and send a transaction, waiting for finalization
+ * @returns `{blockHash, txHash, fee, status, events}`, status being 'finalized' or
+ *   'in_block' (included, not finalized before the timeout)
+ * @throws an Error with `txStatus` ('timeout', 'dropped' or 'failed') and `txHash`
+ */
+function signAndWait(tx, walletAddress, signer, timeoutSecs) {
+    return new Promise((resolve, reject) => {
+        // Included in a block (no dispatch error) but not finalized yet
+        let inBlock = null;
+        let txHash = null;
+
+        // On timeout, an included transaction is reported as 'in_block'
+        // (it can be checked again later), otherwise as 'timeout'
+        const timeout = setTimeout(() => {
+            if (inBlock) {
+                console.warn(`⏱️ Not finalized after ${timeoutSecs}s, still in block`, inBlock.blockHash);
+                resolve({ ...inBlock, status: 'in_block' });
+            } else {
+                const err = new Error(`Transaction timeout after ${timeoutSecs}s (not included in a block)`);
+                err.txStatus = 'timeout';
+                err.txHash = txHash;
+                reject(err);
+            }
+        }, timeoutSecs * 1000);
+
+        tx.signAndSend(walletAddress, { signer }, (txResult) => {
+            const { status, dispatchError } = txResult;
+            txHash = txResult.txHash?.toString() || txHash;
+
+            console.log(`   → Status: ${status?.type || 'unknown'}`);
+
+            if (status?.type === 'BestChainBlockIncluded' || status?.type === 'Finalized') {
+                // Check for dispatch errors
+                if (dispatchError) {
+                    clearTimeout(timeout);
+                    const err = new Error(dispatchErrorMessage(dispatchError));
+                    err.txStatus = 'failed';
+                    err.txHash = txHash;
+                    reject(err);
+                    return;
+                }
+
+                inBlock = {
+                    blockHash: status.value?.blockHash || 'unknown',
+                    txHash,
+                    fee: actualFee(txResult.events),
+                    events: txResult.events || []
+                };
+                // Wait for finalization
+                if (status.type === 'Finalized') {
+                    clearTimeout(timeout);
+                    resolve({ ...inBlock, status: 'finalized' });
+                }
+            }
+
+            // Dropped from the pool or invalid: never included
+            if (status?.type === 'Invalid' || status?.type === 'Drop') {
+                clearTimeout(timeout);
+                const err = new Error(`Transaction ${status.type}`);
+                err.txStatus = 'dropped';
+                err.txHash = txHash;
+                reject(err);
+            }
+        }).catch((err) => {
+            clearTimeout(timeout);
+            reject(err);
+        });
+    });
+}
+
+function dispatchErrorMessage(dispatchError) {
+    if (dispatchError?.isModule) {
+        return `Module error: ${JSON.stringify(dispatchError.asModule)}`;
+    }
+    if (typeof dispatchError === 'object') {
+        return JSON.stringify(dispatchError, (_, v) => typeof v === 'bigint' ? v.toString() : v);
+    }
+    return 'Transaction dispatch error';
+}
+
+/**
+ * Index and error of the call that interrupted a `utility.batch`, if any
+ */
+function batchInterrupted(events) {
+    const interrupted = (events || []).find(({ event }) =>
+        event?.pallet === 'Utility' && event?.palletEvent?.name === 'BatchInterrupted'
+    );
+    if (!interrupted) return null;
+    const data = interrupted.event.palletEvent.data || {};
+    return { index: Number(data.index), error: dispatchErrorMessage(data.error) };
+}
+
+/** Hex string of a codec, byte array or hex value (lowercase), or null */
+function hexOf(value) {
+    if (value == null) return null;
+    const hex = typeof value.toHex === 'function' ? value.toHex() : value.toString();
+    return /^0x[0-9a-fA-F]+$/.test(hex) ? hex.toLowerCase() : null;
+}
+
+/** Text of an event field sent as a string, a codec or UTF-8 bytes in hex */
+function decodeText(value) {
+    if (value == null) return null;
+    if (typeof value.toUtf8 === 'function') return value.toUtf8();
+    const text = typeof value.toHuman === 'function' ? String(value.toHuman()) : value.toString();
+    if (/^0x([0-9a-fA-F]{2})+$/.test(text)) {
+        const bytes = text.slice(2).match(/../g).map(byte => parseInt(byte, 16));
+        return new TextDecoder().decode(new Uint8Array(bytes));
+    }
+    return text;
+}
+
+/** ISWC without separators (T-123.456.789-0 → T1234567890), or null */
+function normalizeIswc(iswc) {
+    const code = (iswc ?? '').toString().replace(/[^0-9A-Za-z]/g, '').toUpperCase();
+    return code || null;
+}
+
+/**
+ * MusicalWorks registration events of a transaction: the on-chain id with
+ * the identifiers of the registered work found in the payload (its ISWC
+ * and/or the hash of the encoded work)
  */
-export async function submitMusicalWorksBatch(rpcUrl, worksJson, walletAddress) {
+function registrationEvents(events) {
+    return (events || [])
+        .filter(({ event }) =>
+            event?.pallet === 'MusicalWorks' && /Registered$/.test(event?.palletEvent?.name || '')
+        )
+        .map(({ event }) => {
+            const data = event.palletEvent.data || {};
+            const id = data.middsId ?? data.midds_id ?? data.id ?? data.hash;
+            const work = data.work ?? data.midds ?? data.musicalWork ?? data;
+            return {
+                id: id != null ? id.toString() : null,
+                iswc: normalizeIswc(decodeText(work.iswc)),
+                hash: hexOf(data.hash ?? data.hashId ?? data.hash_id),
+            };
+        });
+}
+
+/**
+ * On-chain id of each work of a transaction: the known id for updates, the id
+ * of the registration event naming the work (same ISWC or same hash) for new
+ * works. Events are never paired by position: a work whose event cannot be
+ * identified gets no id.
+ * @param {Array<string|null>} hashes - Hash of each encoded work, in `works` order
+ * @param {Object} updates - ISWC → on-chain id of the works updated
+ */
+function workIds(works, hashes, events, updates) {
+    const registered = registrationEvents(events);
+    return works.map((work, i) => {
+        const target = updates[work?.iswc];
+        if (target != null) return target;
+        const iswc = normalizeIswc(work?.iswc);
+        const hash = hashes[i];
+        const index = registered.findIndex(e => (hash && e.hash === hash) || (iswc && e.iswc === iswc));
+        if (index < 0) {
+            console.warn(`No registration event found for work ${work?.iswc ?? i}`);
+            return null;
+        }
+        return registered.splice(index, 1)[0].id;
+    });
+}
+
+/**
+ * Per-work result of a transaction that went through
+ * @param {string|null} onchainId - Id from the registration event (successful works only)
+ */
+function included(work, result, error = null, onchainId = null) {
+    return {
+        iswc: work?.iswc ?? null,
+        onchainId: error ? null : (onchainId ?? null),
+        hash: result.blockHash,
+        txHash: result.txHash,
+        fee: result.fee,
+        status: result.status,
+        success: !error,
+        error
+    };
+}
+
+/** Per-work result of a transaction that did not go through */
+function rejected(work, error) {
+    return {
+        iswc: work?.iswc ?? null,
+        onchainId: null,
+        hash: null,
+        txHash: error.txHash || null,
+        fee: null,
+        status: error.txStatus || 'failed',
+        success: false,
+        error: error.message || 'Unknown error'
+    };
+}
+
+/**
+ * Submit musical works with proper finalization waiting
+ * @param {string} [batchRemark] - Optional remark (batch label) added via system.remarkWithEvent
+ * @param {number} [timeoutSecs=120] - How long to wait for finalization of each transaction
+ * @param {string} [strategy='batch_all'] - How works are grouped into transactions:
+ *   - 'batch_all': a single utility.batchAll, all works or none (rollback)
+ *   - 'batch': utility.batch of `batchSize` works; a failing work interrupts its
+ *     batch, the works before it are kept
+ *   - 'individual': one transaction per work
+ * @param {number} [batchSize=50] - Works per transaction for the 'batch' strategy
+ * @param {string} [updatesJson='{}'] - ISWC → on-chain id of works already registered:
+ *   those are sent with musicalWorks.update instead of musicalWorks.register
+ * @returns per work `{iswc, onchainId, hash, txHash, fee, status, success, error}`,
+ *   in the order of `worksJson` (matched on `iswc` by the caller), status being
+ *   'finalized', 'in_block' (included, not finalized before the timeout),
+ *   'timeout' (never included before the timeout), 'dropped' or 'failed'
+ */
+export async function submitMusicalWorksBatch(rpcUrl, worksJson, walletAddress, batchRemark, timeoutSecs = 120, strategy = 'batch_all', batchSize = 50, updatesJson = '{}') {
+    let works = [];
     try {
-        const works = JSON.parse(worksJson);
-        console.log(`📤 Submitting batch of ${works.length} musical works...`);
+        works = JSON.parse(worksJson);
+        const updates = JSON.parse(updatesJson || '{}');
+        const updateCount = works.filter(work => updates[work?.iswc] != null).length;
+        console.log(`📤 Submitting ${works.length} musical works, ${updateCount} update(s) (${strategy})...`);
         console.log('   RPC:', rpcUrl);
         console.log('   Wallet:', walletAddress);
 
@@ -125,91 +391,115 @@ export async function submitMusicalWorksBatch(rpcUrl, worksJson, walletAddress)
             throw new Error('Signer not available from wallet');
         }
         console.log('✅ Signer obtained');
+        const signer = injector.signer;
 
         // Convert IPI values to BigInt
         console.log('📦 Preparing works for SDK...');
         const sdkWorks = works.map(work => convertIpiToBigInt(work));
         
-        // Build calls
-        const calls = sdkWorks.map(work => 
-            client.tx.musicalWorks.register(work).call
-        );
-        console.log(`✅ ${calls.length} transactions prepared`);
-
-        // Create batch
-        console.log('📤 Creating batch transaction...');
-        const batchTx = client.tx.utility.batchAll(calls);
-        
-        // Sign and send with callback for finalization
-        console.log('✍️ Signing and submitting (waiting for finalization)...');
-        
-        const result = await new Promise((resolve, reject) => {
-            // Timeout after 60 seconds
-            const timeout = setTimeout(() => {
-                reject(new Error('Transaction timeout after 60s'));
-            }, 60000);
-            
-            batchTx.signAndSend(walletAddress, { signer: injector.signer }, (txResult) => {
-                const { status, dispatchError } = txResult;
-                
-                console.log(`   → Status: ${status?.type || 'unknown'}`);
-                
-                // Wait for finalization
-                if (status?.type === 'BestChainBlockIncluded' || status?.type === 'Finalized') {
-                    clearTimeout(timeout);
-                    
-                    // Check for dispatch errors
-                    if (dispatchError) {
-                        let errorMsg = 'Transaction dispatch error';
-                        if (dispatchError.isModule) {
-                            errorMsg = `Module error: ${JSON.stringify(dispatchError.asModule)}`;
-                        } else if (typeof dispatchError === 'object') {
-                            errorMsg = JSON.stringify(dispatchError);
-                        }
-                        reject(new Error(errorMsg));
-                    } else {
-                        resolve({
-                            blockHash: status.value?.blockHash || 'unknown',
-                            status: status.type
-                        });
-                    }
+        // Build calls: the whole new version replaces an existing work
+        const calls = sdkWorks.map(work => {
+            const target = updates[work.iswc];
+            return target != null
+                ? client.tx.musicalWorks.update(target, work).call
+                : client.tx.musicalWorks.register(work).call;
+        });
+        // Hash of each encoded work, to recognize it in the registration events
+        const hashes = calls.map(call => hexOf(call?.args?.[0]?.hash));
+        console.log(`✅ ${calls.length} calls prepared`);
+
+        // Tag the submission so it can be found later by campaign
+        const remarkCall = batchRemark ? client.tx.system.remarkWithEvent(batchRemark).call : null;
+        if (remarkCall) {
+            console.log('🏷️ Batch remark:', batchRemark);
+        }
+
+        if (strategy === 'individual') {
+            const results = [];
+            for (const [i, call] of calls.entries()) {
+                console.log(`✍️ Work ${i + 1}/${calls.length}...`);
+                try {
+                    const result = await signAndWait(client.tx.utility.batchAll([call]), walletAddress, signer, timeoutSecs);
+                    results.push(included(works[i], result, null, workIds([works[i]], [hashes[i]], result.events, updates)[0]));
+                } catch (error) {
+                    results.push(rejected(works[i], error));
                 }
-                
-                // Handle errors
-                if (status?.type === 'Invalid' || status?.type === 'Drop') {
-                    clearTimeout(timeout);
-                    reject(new Error(`Transaction ${status.type}`));
+            }
+            if (remarkCall && results.some(r => r.success)) {
+                try {
+                    await signAndWait(client.tx.utility.batchAll([remarkCall]), walletAddress, signer, timeoutSecs);
+                } catch (error) {
+                    console.warn('Batch remark not recorded:', error.message);
                 }
-            }).catch((err) => {
-                clearTimeout(timeout);
-                reject(err);
-            });
-        });
-        
-        console.log(`🎉 Batch finalized in block:`, result.blockHash);
+            }
+            return results;
+        }
+
+        if (strategy === 'batch') {
+            const size = Math.max(1, batchSize);
+            const results = [];
+            for (let start = 0; start < calls.length; start += size) {
+                const chunk = calls.slice(start, start + size);
+                // The remark goes last in the first chunk, after the works
+                const withRemark = start === 0 && remarkCall ? [...chunk, remarkCall] : chunk;
+                console.log(`✍️ Works ${start + 1}-${start + chunk.length}/${calls.length}...`);
+                try {
+                    const result = await signAndWait(client.tx.utility.batch(withRemark), walletAddress, signer, timeoutSecs);
+                    const interrupted = batchInterrupted(result.events);
+                    // Only the calls before the interruption emitted an event
+                    const ids = workIds(works.slice(start, start + chunk.length), hashes.slice(start, start + chunk.length), result.events, updates);
+                    chunk.forEach((_, i) => {
+                        const work = works[start + i];
+                        if (!interrupted || i < interrupted.index) {
+                            results.push(included(work, result, null, ids[i]));
+                        } else if (i === interrupted.index) {
+                            results.push(included(work, result, interrupted.error));
+                        } else {
+                            results.push(included(work, result, 'Not executed: batch interrupted by a previous work'));
+                        }
+                    });
+                } catch (error) {
+                    chunk.forEach((_, i) => results.push(rejected(works[start + i], error)));
+                }
+            }
+            return results;
+        }
 
-        return works.map(() => ({
-            hash: result.blockHash,
-            success: true,
-            error: null
-        }));
+        // batch_all: all works or none
+        if (remarkCall) {
+            calls.push(remarkCall);
+        }
+        console.log('✍️ Signing and submitting (waiting for finalization)...');
+        const result = await signAndWait(client.tx.utility.batchAll(calls), walletAddress, signer, timeoutSecs);
+        console.log(`🎉 Batch ${result.status} in block:`, result.blockHash);
+
+        const ids = workIds(works, hashes, result.events, updates);
+        return works.map((work, i) => included(work, result, null, ids[i]));
         
     } catch (error) {
         console.error('❌ Batch submission failed:', error);
-        
-        let works = [];
-        try {
-            works = JSON.parse(worksJson);
-        } catch (e) {
-            works = [{}];
-        }
-        
-        return works.map(() => ({
-            hash: null,
-            success: false,
-            error: error.message || 'Unknown error'
-        }));
+        return (works.length ? works : [{}]).map(work => rejected(work, error));
+    }
+}
+
+/**
+ * Check again a transaction included in `blockHash`
+ * @returns 'finalized', 'in_block' (not finalized yet) or 'dropped' (block retracted)
+ */
+export async function checkTransactionStatus(rpcUrl, blockHash) {
+    const client = await getClient(rpcUrl);
+    const header = await withRpcSlot(() => client.rpc.chain_getHeader(blockHash));
+    if (!header) {
+        return 'dropped';
+    }
+    const finalizedHead = await withRpcSlot(() => client.rpc.chain_getFinalizedHead());
+    const finalized = await withRpcSlot(() => client.rpc.chain_getHeader(finalizedHead));
+    if (Number(header.number) > Number(finalized.number)) {
+        return 'in_block';
     }
+    // Finalized height reached: the block must be the canonical one
+    const canonical = await withRpcSlot(() => client.rpc.chain_getBlockHash(header.number));
+    return canonical?.toString() === blockHash ? 'finalized' : 'dropped';
 }
 
 export async function submitMusicalWork(rpcUrl, workJson, walletAddress) {
@@ -226,7 +516,7 @@ export async function submitMusicalWork(rpcUrl, workJson, walletAddress) {
 export async function getWalletBalance(rpcUrl, walletAddress) {
     try {
         const client = await getClient(rpcUrl);
-        const balance = await client.getBalanceOf(walletAddress);
+        const balance = await withRpcSlot(() => client.getBalanceOf(walletAddress));
         const balanceNum = Number(balance) / 10 ** 12;
         
         // Format with appropriate precision
@@ -13,13 +13,24 @@
 //! ├─────────────────────────────────────────────────────────────┤
 //! │  MainContent                                                 │
 //! │  ├── Hero (title, description)                              │
+//! │  ├── JobDashboard (active and recent uploads)               │
 //! │  ├── UploadSection or LogsPanel                             │
-//! │  └── PreviewSection (when works loaded)                     │
+//! │  ├── FailureScreen (when no row is valid)                   │
+//! │  ├── ColumnExplorer (source column statistics)              │
+//! │  ├── FileDetailsCard (when works loaded)                    │
+//! │  ├── CatalogDiffBanner (when the catalog was sent before)   │
+//! │  ├── PreviewSection (when works loaded)                     │
+//! │  ├── SkippedRowsFix (when skipped rows can be completed)    │
+//! │  ├── RoleStats (creators per role, works without composer)  │
+//! │  ├── TemplateNameDialog (when the AI created a template)    │
+//! │  └── ShortcutHelp (keyboard shortcuts, `?`)                 │
 //! ├─────────────────────────────────────────────────────────────┤
 //! │  Footer                                                      │
 //! └─────────────────────────────────────────────────────────────┘
 //! ```
 //!
+//! `/modeles` renders [`components::TemplatesPage`] (saved templates).
+//!
 //! # Modules
 //!
 //! - [`types`] - Common types (LogEntry, PreviewItem, etc.)
@@ -49,9 +60,9 @@ pub use config::*;
 // Types
 pub use types::{
     // Preview
-    PreviewItem,
+    PreviewItem, work_title,
     // Logs
-    LogEntry, LogLevel,
+    LogEntry, LogLevel, LogStage,
     // API
     UploadResponse, ResponseMetadata,
     // Wallet
@@ -80,6 +91,9 @@ pub fn main() {
     _ = console_log::init_with_level(log::Level::Debug);
     
     log::info!("🦀 Mass Load Rust - Starting Leptos App");
+
+    // Limit the RPC queries sent by the JS bindings
+    configure_rpc_queue();
     
     // Mount the application
     mount_to_body(|| view! { <App/> });
@@ -92,6 +106,7 @@ pub fn App() -> impl IntoView {
             <main>
                 <Routes>
                     <Route path="/" view=MainContent/>
+                    <Route path="/modeles" view=TemplatesPage/>
                 </Routes>
             </main>
         </Router>
@@ -105,33 +120,137 @@ fn MainContent() -> impl IntoView {
     let (wallet_address, set_wallet_address) = create_signal(None::<String>);
     let (preview_data, set_preview_data) = create_signal(None::<Vec<PreviewItem>>);
     let (musical_works_json, set_musical_works_json) = create_signal(None::<serde_json::Value>);
+    // Empreinte SHA-256 du fichier transformé
+    let (file_sha256, set_file_sha256) = create_signal(None::<String>);
+    // Période comptable du catalogue transformé (`2025-Q1`)
+    let (catalog_period, set_catalog_period) = create_signal(None::<String>);
     let (_is_processing, set_is_processing) = create_signal(false);
     let (logs, set_logs) = create_signal(Vec::<LogEntry>::new());
+    // Job courant et lignes ignorées (pour la correction)
+    let job_id = create_rw_signal(None::<String>);
+    let skipped_rows = create_rw_signal(Vec::<SkippedRow>::new());
+    // Logs détaillés (niveau Debug) demandés au backend
+    let verbose_logs = create_rw_signal(false);
+    // Mode révision : diff avec la version précédente du catalogue
+    let catalog_diff = create_rw_signal(None::<CatalogDiff>);
+    let all_works = create_rw_signal(Vec::<serde_json::Value>::new());
+    let changes_only = create_rw_signal(true);
+    // Diagnostic quand aucune ligne n'est valide
+    let failure = create_rw_signal(None::<FailureReport>);
+    // Modèle créé par l'IA, en attente d'un nom
+    let new_template = create_rw_signal(None::<NewTemplate>);
+    // Œuvres sans ISWC (demande d'attribution)
+    let missing_iswc = create_rw_signal(0usize);
+    // Ayants droit par rôle (contrôle « au moins un compositeur »)
+    let role_report = create_rw_signal(None::<RoleReport>);
+    // Encodage, séparateur et modèle détectés pour le fichier transformé
+    let file_details = create_rw_signal(None::<FileDetails>);
+    // Démo guidée avec le fichier d'exemple
+    let guided_demo = create_rw_signal(false);
+    // Imports en cours et récents, et celui affiché à l'écran
+    let jobs = create_rw_signal(Vec::<JobRecord>::new());
+    let open_job = create_rw_signal(None::<String>);
+    let job_view = JobView {
+        set_preview_data,
+        set_musical_works_json,
+        set_file_sha256,
+        set_catalog_period,
+        set_logs,
+        job_id,
+        skipped_rows,
+        catalog_diff,
+        all_works,
+        changes_only,
+        failure,
+        new_template,
+        missing_iswc,
+        file_details,
+        role_report,
+    };
+
+    // Logs du job affiché gardés dans son entrée avant d'en afficher un autre
+    let keep_open_job_logs = move || {
+        if let Some(id) = open_job.get_untracked() {
+            let current = logs.get_untracked();
+            jobs.update(|jobs| {
+                if let Some(job) = jobs.iter_mut().find(|j| j.id == id) {
+                    job.logs = current;
+                }
+            });
+        }
+    };
+    // Afficher un job : ses logs et, s'il est terminé, son résultat
+    let on_open_job = Callback::new(move |id: String| {
+        keep_open_job_logs();
+        let Some(job) = jobs.with_untracked(|jobs| jobs.iter().find(|j| j.id == id).cloned()) else {
+            return;
+        };
+        job_view.clear();
+        open_job.set(Some(id));
+        job_view.set_logs.set(job.logs);
+        if let Some(ref response) = job.response {
+            show_response(response, job_view);
+        }
+    });
+    // Nouvel import : retour à la zone d'upload, les autres jobs continuent
+    let on_new_job = Callback::new(move |_| {
+        keep_open_job_logs();
+        open_job.set(None);
+        job_view.clear();
+    });
+    
+    // Raccourcis clavier : chaque composant affiché y enregistre ses actions
+    let _shortcuts = ShortcutRegistry::provide();
+
+    // Wallet oublié après inactivité (postes partagés)
+    let wallet_locked = create_rw_signal(false);
     
     // Initialize SSE connection ONCE at app startup
     init_sse_logs(set_logs);
 
+    watch_idle(move || {
+        if wallet_connected.get_untracked() {
+            set_wallet_connected.set(false);
+            set_wallet_address.set(None);
+            wallet_locked.set(true);
+            set_logs.update(|logs| {
+                logs.push(LogEntry::new(
+                    LogLevel::Warning,
+                    "🔒 Session verrouillée après inactivité : reconnectez le wallet avant de signer",
+                ));
+            });
+        }
+    });
+
     view! {
         <Header 
             wallet_connected=wallet_connected 
             wallet_address=wallet_address
             set_wallet_connected=set_wallet_connected
             set_wallet_address=set_wallet_address
+            wallet_locked=wallet_locked
+            set_logs=set_logs
         />
 
         <div class="container">
             <Hero/>
 
+            // Active and recent uploads, any of them can be opened below
+            <JobDashboard jobs=jobs open_job=open_job on_open=on_open_job on_new=on_new_job/>
+
             // Show UploadBox when no logs, hide when logs exist
             <Show
                 when=move || logs.get().is_empty()
                 fallback=|| view! { }
             >
-                <UploadSection 
-                    set_preview_data=set_preview_data
-                    set_musical_works_json=set_musical_works_json
-                    set_is_processing=set_is_processing 
-                    set_logs=set_logs
+                <UploadSection
+                    view=job_view
+                    set_is_processing=set_is_processing
+                    jobs=jobs
+                    open_job=open_job
+                    on_open_job=on_open_job
+                    verbose_logs=verbose_logs
+                    guided_demo=guided_demo
                 />
             </Show>
             
@@ -140,7 +259,25 @@ fn MainContent() -> impl IntoView {
                 when=move || !logs.get().is_empty()
                 fallback=|| view! { }
             >
-                <LogsPanel logs=logs set_logs=set_logs/>
+                <DemoGuide guided=guided_demo step=DemoStep::Transform/>
+                <LogsPanel logs=logs set_logs=set_logs verbose_logs=verbose_logs/>
+            </Show>
+
+            // Keyboard shortcut help (`?`)
+            <ShortcutHelp/>
+
+            // Naming prompt for a template created by the AI
+            <TemplateNameDialog new_template=new_template set_logs=set_logs/>
+
+            // Failure screen (when no row is valid)
+            <FailureScreen failure=failure set_logs=set_logs/>
+
+            // Column statistics of the transformed file
+            <Show
+                when=move || !logs.get().is_empty()
+                fallback=|| view! { }
+            >
+                <ColumnExplorer job_id=job_id/>
             </Show>
 
             // Preview section (appears after processing)
@@ -148,6 +285,15 @@ fn MainContent() -> impl IntoView {
                 when=move || preview_data.get().is_some()
                 fallback=|| view! { }
             >
+                <DemoGuide guided=guided_demo step=DemoStep::Preview/>
+                <FileDetailsCard details=file_details/>
+                <CatalogDiffBanner
+                    catalog_diff=catalog_diff
+                    all_works=all_works
+                    changes_only=changes_only
+                    set_preview_data=set_preview_data
+                    set_musical_works_json=set_musical_works_json
+                />
                 <PreviewSection 
                     data=preview_data
                     musical_works_json=musical_works_json
@@ -157,7 +303,19 @@ fn MainContent() -> impl IntoView {
                     set_is_processing=set_is_processing
                     set_preview_data=set_preview_data
                     set_musical_works_json=set_musical_works_json
+                    file_sha256=file_sha256
+                    catalog_period=catalog_period
+                />
+                <DemoGuide guided=guided_demo step=DemoStep::Sign/>
+                <SkippedRowsFix
+                    job_id=job_id
+                    skipped_rows=skipped_rows
+                    set_preview_data=set_preview_data
+                    set_musical_works_json=set_musical_works_json
+                    set_logs=set_logs
                 />
+                <IswcRequests job_id=job_id missing_iswc=missing_iswc/>
+                <RoleStats report=role_report/>
             </Show>
         </div>
 
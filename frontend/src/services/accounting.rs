@@ -0,0 +1,129 @@
+//! Comptabilité des frais payés par soumission.
+//!
+//! Chaque envoi enregistre les frais réellement payés (événement
+//! `TransactionFeePaid`) dans le `localStorage` du navigateur ; l'historique
+//! s'exporte en CSV pour le rapprochement administratif.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsCast;
+
+/// Clé de l'historique dans le `localStorage`
+const STORAGE_KEY: &str = "massload.fees";
+
+/// Décimales de l'AFT (1 AFT = 10^12 planck)
+const AFT_DECIMALS: u32 = 12;
+
+/// Frais payés pour une soumission (un extrinsic batch)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeRecord {
+    /// Date RFC 3339
+    pub date: String,
+    pub works_count: usize,
+    /// Œuvres existantes mises à jour parmi `works_count`
+    #[serde(default)]
+    pub updates_count: usize,
+    /// Frais en planck (None si l'événement n'a pas été reçu)
+    pub fee: Option<String>,
+    pub tx_hashes: Vec<String>,
+    pub wallet: String,
+}
+
+/// Montant en planck formaté en AFT (12 décimales)
+pub fn format_aft(planck: &str) -> String {
+    let Ok(value) = planck.parse::<u128>() else {
+        return String::new();
+    };
+    let unit = 10u128.pow(AFT_DECIMALS);
+    format!("{}.{:0width$}", value / unit, value % unit, width = AFT_DECIMALS as usize)
+}
+
+/// Historique au format CSV (date, œuvres, dont mises à jour, frais en AFT, hashes, wallet)
+pub fn fees_csv(records: &[FeeRecord]) -> String {
+    let mut csv = String::from("date,works_count,updates_count,fees_aft,tx_hashes,wallet\n");
+    for r in records {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            r.date,
+            r.works_count,
+            r.updates_count,
+            r.fee.as_deref().map(format_aft).unwrap_or_default(),
+            r.tx_hashes.join(" "),
+            r.wallet,
+        ));
+    }
+    csv
+}
+
+fn storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// Historique des frais enregistrés dans ce navigateur
+pub fn load_fee_records() -> Vec<FeeRecord> {
+    storage()
+        .and_then(|s| s.get_item(STORAGE_KEY).ok().flatten())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Ajoute une soumission à l'historique
+pub fn record_fee(record: FeeRecord) {
+    let mut records = load_fee_records();
+    records.push(record);
+    if let (Some(storage), Ok(json)) = (storage(), serde_json::to_string(&records)) {
+        if storage.set_item(STORAGE_KEY, &json).is_err() {
+            log::warn!("⚠️ Impossible d'enregistrer les frais (localStorage plein ?)");
+        }
+    }
+}
+
+/// Télécharge l'historique des frais en CSV
+pub fn download_fees_csv() -> Result<(), String> {
+    let csv = fees_csv(&load_fee_records());
+    let parts = js_sys::Array::of1(&csv.into());
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type("text/csv;charset=utf-8");
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options)
+        .map_err(|_| "Impossible de créer le fichier CSV".to_string())?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)
+        .map_err(|_| "Impossible de créer le lien de téléchargement".to_string())?;
+
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or("Document indisponible")?;
+    let link: web_sys::HtmlAnchorElement = document.create_element("a")
+        .map_err(|_| "Impossible de créer le lien".to_string())?
+        .unchecked_into();
+    link.set_href(&url);
+    link.set_download(&format!("massload-frais-{}.csv", chrono::Local::now().format("%Y-%m-%d")));
+    link.click();
+    let _ = web_sys::Url::revoke_object_url(&url);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fees_csv() {
+        assert_eq!(format_aft("1500000000000"), "1.500000000000");
+        assert_eq!(format_aft("42"), "0.000000000042");
+        assert_eq!(format_aft("abc"), "");
+
+        let records = vec![FeeRecord {
+            date: "2025-03-01T10:00:00+01:00".to_string(),
+            works_count: 12,
+            updates_count: 2,
+            fee: Some("2000000000000".to_string()),
+            tx_hashes: vec!["0xaa".to_string(), "0xbb".to_string()],
+            wallet: "5Grw".to_string(),
+        }];
+        let csv = fees_csv(&records);
+        assert_eq!(
+            csv,
+            "date,works_count,updates_count,fees_aft,tx_hashes,wallet\n2025-03-01T10:00:00+01:00,12,2,2.000000000000,0xaa 0xbb,5Grw\n",
+        );
+    }
+}
@@ -0,0 +1,60 @@
+//! URL du backend choisie à l'exécution.
+//!
+//! Un même build peut viser le staging ou la prod : l'URL saisie dans les
+//! réglages est enregistrée dans le `localStorage` et remplace `BACKEND_URL`
+//! (fixée à la compilation) jusqu'à sa réinitialisation.
+
+use crate::BACKEND_URL;
+
+/// Clé de l'URL choisie dans le `localStorage`
+const STORAGE_KEY: &str = "massload.backendUrl";
+
+fn storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// URL choisie dans les réglages, si elle remplace celle du build
+pub fn backend_url_override() -> Option<String> {
+    storage()?.get_item(STORAGE_KEY).ok().flatten()
+}
+
+/// URL du backend à utiliser pour les requêtes et le flux de logs
+pub fn backend_url() -> String {
+    backend_url_override().unwrap_or_else(|| BACKEND_URL.to_string())
+}
+
+/// URL saisie nettoyée : `http(s)://hôte[:port][/chemin]`, sans `/` final
+pub fn normalize_backend_url(raw: &str) -> Result<String, String> {
+    let url = raw.trim().trim_end_matches('/');
+    let host = url.strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .ok_or("L'URL doit commencer par http:// ou https://")?;
+    if host.is_empty() || host.contains(char::is_whitespace) {
+        return Err("URL du backend invalide".to_string());
+    }
+    Ok(url.to_string())
+}
+
+/// Enregistre l'URL choisie (None revient à celle du build) et renvoie l'URL désormais utilisée
+pub fn set_backend_url(raw: Option<&str>) -> Result<String, String> {
+    let storage = storage().ok_or("localStorage indisponible")?;
+    match raw.map(normalize_backend_url).transpose()? {
+        Some(url) if url != BACKEND_URL => storage.set_item(STORAGE_KEY, &url),
+        _ => storage.remove_item(STORAGE_KEY),
+    }
+    .map_err(|_| "Impossible d'enregistrer l'URL du backend".to_string())?;
+    Ok(backend_url())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_backend_url() {
+        assert_eq!(normalize_backend_url(" https://staging.example.com/ ").unwrap(), "https://staging.example.com");
+        assert_eq!(normalize_backend_url("http://localhost:3001").unwrap(), "http://localhost:3001");
+        assert!(normalize_backend_url("staging.example.com").is_err());
+        assert!(normalize_backend_url("https://").is_err());
+    }
+}
@@ -5,9 +5,97 @@
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::BTreeMap;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::JsFuture;
-use crate::config::BLOCKCHAIN_RPC;
+use crate::config::{BLOCKCHAIN_RPC, SUBMISSION_TIMEOUT_SECS};
+
+/// On-chain state of a submitted batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxStatus {
+    /// Included in a finalized block
+    Finalized,
+    /// Included in a block, not finalized before the timeout
+    InBlock,
+    /// Not included in a block before the timeout
+    Timeout,
+    /// Dropped from the pool, invalid, or its block was retracted
+    Dropped,
+    /// Rejected (dispatch error, signature refused, ...)
+    Failed,
+}
+
+impl TxStatus {
+    /// Message affiché à l'utilisateur
+    pub fn label(&self) -> &'static str {
+        match self {
+            TxStatus::Finalized => "Transaction finalisée",
+            TxStatus::InBlock => "Incluse dans un bloc mais pas encore finalisée : vérifiez à nouveau dans quelques instants",
+            TxStatus::Timeout => "Aucune inclusion dans un bloc avant le délai : vérifiez l'explorateur avant de réessayer",
+            TxStatus::Dropped => "Transaction abandonnée par le réseau : vous pouvez réessayer",
+            TxStatus::Failed => "Transaction rejetée",
+        }
+    }
+
+    /// The state may still change (worth checking again)
+    pub fn is_pending(&self) -> bool {
+        matches!(self, TxStatus::InBlock)
+    }
+}
+
+/// Works per batch for [`BatchStrategy::Batch`] by default
+pub const DEFAULT_BATCH_SIZE: u32 = 50;
+
+/// How works are grouped into transactions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum BatchStrategy {
+    /// One `utility.batchAll`: all works are registered or none (rollback)
+    #[default]
+    BatchAll,
+    /// `utility.batch` of `size` works: a failing work interrupts its batch,
+    /// the works before it are kept
+    Batch { size: u32 },
+    /// One transaction per work
+    Individual,
+}
+
+impl BatchStrategy {
+    /// Strategy name expected by `blockchain.js`
+    fn js_name(&self) -> &'static str {
+        match self {
+            BatchStrategy::BatchAll => "batch_all",
+            BatchStrategy::Batch { .. } => "batch",
+            BatchStrategy::Individual => "individual",
+        }
+    }
+
+    fn batch_size(&self) -> u32 {
+        match self {
+            BatchStrategy::Batch { size } => (*size).max(1),
+            _ => DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// Transactions (and wallet signatures) needed for `works` works
+    pub fn transactions(&self, works: usize) -> usize {
+        match self {
+            BatchStrategy::BatchAll => usize::from(works > 0),
+            BatchStrategy::Batch { .. } => works.div_ceil(self.batch_size() as usize),
+            BatchStrategy::Individual => works,
+        }
+    }
+
+    /// Comportement en cas d'échec, affiché dans la fenêtre de confirmation
+    pub fn description(&self) -> &'static str {
+        match self {
+            BatchStrategy::BatchAll => "Une seule transaction : si une œuvre échoue, aucune n'est enregistrée",
+            BatchStrategy::Batch { .. } => "Une œuvre en échec interrompt son lot ; les œuvres précédentes du lot sont conservées",
+            BatchStrategy::Individual => "Chaque œuvre est indépendante, mais une signature est demandée par œuvre",
+        }
+    }
+}
 
 /// Result of a transaction submission.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,35 +107,180 @@ pub struct SubmissionResult {
     pub error: Option<String>,
     #[serde(default)]
     pub work_results: Vec<WorkResult>,
+    /// Hashes of the extrinsics sent (one per transaction of the strategy)
+    #[serde(default)]
+    pub extrinsic_hashes: Vec<String>,
+    /// Actual fees paid over all transactions, in planck (from the `TransactionFeePaid` events)
+    #[serde(default)]
+    pub fee: Option<String>,
+    /// Least advanced state over all transactions
+    #[serde(default)]
+    pub status: Option<TxStatus>,
+    #[serde(default)]
+    pub strategy: BatchStrategy,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Whether a work was registered or an existing work was updated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubmissionKind {
+    /// `musicalWorks.register`
+    #[default]
+    Creation,
+    /// `musicalWorks.update` of a work already on chain
+    Update,
+}
+
+impl SubmissionKind {
+    /// Libellé des reçus
+    pub fn label(&self) -> &'static str {
+        match self {
+            SubmissionKind::Creation => "création",
+            SubmissionKind::Update => "mise à jour",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WorkResult {
     pub iswc: String,
     pub success: bool,
     pub error: Option<String>,
+    /// Extrinsic that carried this work
+    #[serde(default)]
+    pub tx_hash: Option<String>,
+    #[serde(default)]
+    pub status: Option<TxStatus>,
+    /// On-chain id of the registered work (from its MIDDS registration event)
+    /// or of the updated work
+    #[serde(default)]
+    pub onchain_id: Option<String>,
+    #[serde(default)]
+    pub kind: SubmissionKind,
+}
+
+/// Nombre maximal de tours de « Réessayer les échecs »
+pub const MAX_RETRY_ROUNDS: u32 = 3;
+
+/// Works of a submission with their latest per-work result.
+///
+/// Retries only re-submit the failed works; their new results replace the
+/// failed ones, successes are kept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubmissionOutcome {
+    pub works: Vec<Value>,
+    /// Same order as `works`
+    pub results: Vec<WorkResult>,
+    /// Retry rounds done so far
+    pub retry_rounds: u32,
+}
+
+impl SubmissionOutcome {
+    pub fn new(works: Vec<Value>, results: Vec<WorkResult>) -> Self {
+        Self { works, results, retry_rounds: 0 }
+    }
+
+    /// Indices of the works not registered (missing results count as failures)
+    fn failed_indices(&self) -> Vec<usize> {
+        (0..self.works.len())
+            .filter(|&i| !self.results.get(i).is_some_and(|r| r.success))
+            .collect()
+    }
+
+    /// Works to re-submit
+    pub fn failed_works(&self) -> Vec<Value> {
+        self.failed_indices().into_iter().map(|i| self.works[i].clone()).collect()
+    }
+
+    pub fn success_count(&self) -> usize {
+        self.results.iter().filter(|r| r.success).count()
+    }
+
+    /// Failed works remain and the retry rounds are not exhausted
+    pub fn can_retry(&self) -> bool {
+        self.retry_rounds < MAX_RETRY_ROUNDS && self.success_count() < self.works.len()
+    }
+
+    /// Merge the results of a retry of [`Self::failed_works`] (same order)
+    pub fn apply_retry(&mut self, retry: Vec<WorkResult>) {
+        let failed = self.failed_indices();
+        self.results.resize_with(self.works.len(), || WorkResult {
+            iswc: "unknown".to_string(),
+            success: false,
+            error: None,
+            tx_hash: None,
+            status: None,
+            onchain_id: None,
+            kind: SubmissionKind::Creation,
+        });
+        for (index, result) in failed.into_iter().zip(retry) {
+            self.results[index] = result;
+        }
+        self.retry_rounds += 1;
+    }
+}
+
+/// Longueur maximale du libellé de lot
+pub const MAX_BATCH_LABEL_LEN: usize = 64;
+
+/// Build the on-chain remark tagging a submission with a batch label.
+///
+/// Sent with `system.remarkWithEvent` in the same batch as the registrations,
+/// so all works of a campaign can be found later from the remark event.
+pub fn batch_remark(label: &str, work_count: usize, file_sha256: Option<&str>, period: Option<&str>) -> Option<String> {
+    let label: String = label.trim().chars().take(MAX_BATCH_LABEL_LEN).collect();
+    if label.is_empty() {
+        return None;
+    }
+    let mut remark = serde_json::json!({
+        "app": "massload",
+        "batch": label,
+        "works": work_count,
+    });
+    if let Some(sha) = file_sha256 {
+        remark["sha256"] = Value::String(sha.to_string());
+    }
+    if let Some(period) = period {
+        remark["period"] = Value::String(period.to_string());
+    }
+    Some(remark.to_string())
 }
 
 /// Blockchain service using @allfeat/client SDK.
 pub struct BlockchainService {
     rpc_url: String,
+    /// Time to wait for finalization of each submitted batch
+    timeout_secs: u32,
 }
 
 impl BlockchainService {
     /// Create a new service.
     pub fn new() -> Self {
         Self { 
-            rpc_url: BLOCKCHAIN_RPC.to_string() 
+            rpc_url: BLOCKCHAIN_RPC.to_string(),
+            timeout_secs: SUBMISSION_TIMEOUT_SECS.parse().unwrap_or(120),
         }
     }
+
+    /// Override the finalization timeout (in seconds)
+    pub fn with_timeout(mut self, secs: u32) -> Self {
+        self.timeout_secs = secs.max(1);
+        self
+    }
     
     /// Submit works using @allfeat/client SDK with wallet signer.
     /// The SDK handles signing and submission directly.
+    /// `remark` (see [`batch_remark`]) is appended to the batch when provided.
+    /// Works whose ISWC is in `updates` (ISWC → on-chain id) update the
+    /// existing work instead of registering a new one.
     pub async fn submit_works(
         &self,
         works_json: Value,
+        updates: &BTreeMap<String, String>,
         wallet_address: Option<String>,
+        remark: Option<String>,
+        strategy: BatchStrategy,
     ) -> Result<SubmissionResult, String> {
         let address = wallet_address.ok_or("No wallet address provided")?;
         
@@ -63,8 +296,19 @@ impl BlockchainService {
         // Call JavaScript SDK directly - it handles signing with the wallet
         let works_str = serde_json::to_string(&works_json)
             .map_err(|e| format!("Failed to serialize works: {}", e))?;
+        let updates_str = serde_json::to_string(updates)
+            .map_err(|e| format!("Failed to serialize updates: {}", e))?;
 
-        let promise = submit_batch_js(&self.rpc_url, &works_str, &address);
+        let promise = submit_batch_js(
+            &self.rpc_url,
+            &works_str,
+            &address,
+            remark,
+            self.timeout_secs,
+            strategy.js_name(),
+            strategy.batch_size(),
+            &updates_str,
+        );
         
         let js_result = JsFuture::from(promise)
             .await
@@ -81,37 +325,48 @@ impl BlockchainService {
         let results: Vec<JsSubmitResult> = serde_wasm_bindgen::from_value(js_result)
             .map_err(|e| format!("Failed to parse SDK result: {}", e))?;
 
+        let Transactions { hashes: extrinsic_hashes, fee, status, block_hash } = Transactions::of(&results);
+        let work_results = work_results(works_array, results, updates);
+
         // Check if all succeeded
-        let all_success = results.iter().all(|r| r.success);
-        let first_hash = results.first().and_then(|r| r.hash.clone());
-        let first_error = results.iter().find(|r| !r.success).and_then(|r| r.error.clone());
+        let all_success = work_results.iter().all(|r| r.success);
+        let first_error = work_results.iter().find(|r| !r.success).and_then(|r| r.error.clone());
         
         if all_success {
-            log::info!("✅ All {} works submitted successfully!", results.len());
+            log::info!("✅ All {} works submitted successfully!", work_results.len());
         } else {
             log::error!("❌ Some works failed: {:?}", first_error);
         }
 
         Ok(SubmissionResult {
             success: all_success,
-            tx_hash: first_hash.clone(),
-            block_hash: first_hash,
+            tx_hash: block_hash.clone(),
+            block_hash,
             error: first_error,
-            work_results: results.into_iter().enumerate().map(|(i, r)| {
-                let iswc = works_array.get(i)
-                    .and_then(|w| w.get("iswc"))
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("unknown")
-                    .to_string();
-                WorkResult {
-                    iswc,
-                    success: r.success,
-                    error: r.error,
-                }
-            }).collect(),
+            extrinsic_hashes,
+            fee,
+            status,
+            strategy,
+            work_results,
         })
     }
 
+    /// Check again a batch included in `block_hash` (in block, finalized or retracted)
+    pub async fn check_status(&self, block_hash: &str) -> Result<TxStatus, String> {
+        let js_result = JsFuture::from(check_status_js(&self.rpc_url, block_hash))
+            .await
+            .map_err(|e| {
+                let error_msg = js_sys::Reflect::get(&e, &"message".into())
+                    .ok()
+                    .and_then(|v| v.as_string())
+                    .or_else(|| e.as_string())
+                    .unwrap_or_else(|| "Unknown JS error".to_string());
+                format!("Status check failed: {}", error_msg)
+            })?;
+        serde_wasm_bindgen::from_value(js_result)
+            .map_err(|e| format!("Failed to parse status: {}", e))
+    }
+
     /// Estimate cost for a batch of works.
     pub fn estimate_cost(&self, work_count: usize) -> String {
         let cost = work_count as f32 * 0.05;
@@ -119,14 +374,115 @@ impl BlockchainService {
     }
 }
 
+impl Default for BlockchainService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// JavaScript result from SDK submission
 #[derive(Debug, Clone, Deserialize)]
 struct JsSubmitResult {
+    /// ISWC of the submitted work the result belongs to
+    #[serde(default)]
+    iswc: Option<String>,
+    #[serde(default, rename = "onchainId")]
+    onchain_id: Option<String>,
     hash: Option<String>,
+    #[serde(default, rename = "txHash")]
+    tx_hash: Option<String>,
+    #[serde(default)]
+    fee: Option<String>,
+    #[serde(default)]
+    status: Option<TxStatus>,
     success: bool,
     error: Option<String>,
 }
 
+/// Transactions behind per-work results (several works share one transaction)
+#[derive(Debug, PartialEq)]
+struct Transactions {
+    hashes: Vec<String>,
+    /// Sum of the fees of each transaction
+    fee: Option<String>,
+    /// First non-finalized status, finalized otherwise
+    status: Option<TxStatus>,
+    /// Block of that status (to check it again)
+    block_hash: Option<String>,
+}
+
+impl Transactions {
+    fn of(results: &[JsSubmitResult]) -> Self {
+        let mut hashes: Vec<String> = Vec::new();
+        let mut fee: Option<u128> = None;
+        for r in results {
+            let Some(ref hash) = r.tx_hash else { continue };
+            if hashes.contains(hash) {
+                continue;
+            }
+            hashes.push(hash.clone());
+            if let Some(paid) = r.fee.as_deref().and_then(|f| f.parse::<u128>().ok()) {
+                fee = Some(fee.unwrap_or(0) + paid);
+            }
+        }
+        let pending = results.iter()
+            .find(|r| r.status.is_some_and(|s| s != TxStatus::Finalized))
+            .or_else(|| results.first());
+        Self {
+            hashes,
+            fee: fee.map(|f| f.to_string()),
+            status: pending.and_then(|r| r.status),
+            block_hash: pending.and_then(|r| r.hash.clone()),
+        }
+    }
+}
+
+/// One result per work, in the order of `works`.
+///
+/// Results are matched on the ISWC returned by the SDK layer, so a reordered
+/// or shortened result list can't attribute a result to the wrong work; the
+/// index is only used for results without ISWC. A work left without result
+/// is reported as failed. Works of `updates` are marked as updates.
+fn work_results(works: &[Value], results: Vec<JsSubmitResult>, updates: &BTreeMap<String, String>) -> Vec<WorkResult> {
+    let mut pending: Vec<Option<JsSubmitResult>> = results.into_iter().map(Some).collect();
+    works.iter().enumerate().map(|(i, work)| {
+        let iswc = work.get("iswc").and_then(|v| v.as_str());
+        let position = iswc
+            .and_then(|iswc| pending.iter().position(|r| {
+                r.as_ref().is_some_and(|r| r.iswc.as_deref() == Some(iswc))
+            }))
+            .or_else(|| pending.get(i)
+                .is_some_and(|r| r.as_ref().is_some_and(|r| r.iswc.is_none()))
+                .then_some(i));
+        let kind = if iswc.is_some_and(|iswc| updates.contains_key(iswc)) {
+            SubmissionKind::Update
+        } else {
+            SubmissionKind::Creation
+        };
+        let iswc = iswc.unwrap_or("unknown").to_string();
+        match position.and_then(|p| pending[p].take()) {
+            Some(r) => WorkResult {
+                iswc,
+                success: r.success,
+                error: r.error,
+                tx_hash: r.tx_hash,
+                status: r.status,
+                onchain_id: r.onchain_id,
+                kind,
+            },
+            None => WorkResult {
+                iswc,
+                success: false,
+                error: Some("No result returned for this work".to_string()),
+                tx_hash: None,
+                status: None,
+                onchain_id: None,
+                kind,
+            },
+        }
+    }).collect()
+}
+
 /// Wallet balance info
 #[derive(Debug, Clone, Deserialize)]
 pub struct WalletBalance {
@@ -161,7 +517,15 @@ extern "C" {
         rpc_url: &str,
         works_json: &str,
         wallet_address: &str,
+        remark: Option<String>,
+        timeout_secs: u32,
+        strategy: &str,
+        batch_size: u32,
+        updates_json: &str,
     ) -> js_sys::Promise;
+
+    #[wasm_bindgen(js_name = "checkTransactionStatus")]
+    fn check_status_js(rpc_url: &str, block_hash: &str) -> js_sys::Promise;
     
     #[wasm_bindgen(js_name = "getWalletBalance")]
     fn get_balance_js(
@@ -169,3 +533,130 @@ extern "C" {
         wallet_address: &str,
     ) -> js_sys::Promise;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_remark() {
+        assert!(batch_remark("   ", 3, None, None).is_none());
+
+        let remark = batch_remark(" Campagne 2025-T1 ", 3, Some("abc"), Some("2025-Q1")).unwrap();
+        let value: Value = serde_json::from_str(&remark).unwrap();
+        assert_eq!(value["batch"], "Campagne 2025-T1");
+        assert_eq!(value["works"], 3);
+        assert_eq!(value["sha256"], "abc");
+        assert_eq!(value["period"], "2025-Q1");
+
+        let long = "x".repeat(MAX_BATCH_LABEL_LEN + 10);
+        let value: Value = serde_json::from_str(&batch_remark(&long, 1, None, None).unwrap()).unwrap();
+        assert_eq!(value["batch"].as_str().unwrap().len(), MAX_BATCH_LABEL_LEN);
+        assert!(value.get("period").is_none());
+    }
+
+    #[test]
+    fn test_tx_status_deserialization() {
+        let status: TxStatus = serde_json::from_str(r#""in_block""#).unwrap();
+        assert_eq!(status, TxStatus::InBlock);
+        assert!(status.is_pending());
+        assert!(!TxStatus::Dropped.is_pending());
+    }
+
+    #[test]
+    fn test_batch_strategy_transactions() {
+        assert_eq!(BatchStrategy::BatchAll.transactions(120), 1);
+        assert_eq!(BatchStrategy::Batch { size: 50 }.transactions(120), 3);
+        assert_eq!(BatchStrategy::Individual.transactions(120), 120);
+
+        let json = serde_json::to_string(&BatchStrategy::Batch { size: 10 }).unwrap();
+        assert_eq!(json, r#"{"kind":"batch","size":10}"#);
+    }
+
+    #[test]
+    fn test_transactions_of_chunked_results() {
+        let js = |tx: &str, block: &str, fee: &str, status: TxStatus| JsSubmitResult {
+            iswc: None,
+            onchain_id: None,
+            hash: Some(block.to_string()),
+            tx_hash: Some(tx.to_string()),
+            fee: Some(fee.to_string()),
+            status: Some(status),
+            success: true,
+            error: None,
+        };
+        let results = vec![
+            js("0x1", "0xb1", "100", TxStatus::Finalized),
+            js("0x1", "0xb1", "100", TxStatus::Finalized),
+            js("0x2", "0xb2", "50", TxStatus::InBlock),
+        ];
+
+        let txs = Transactions::of(&results);
+        assert_eq!(txs.hashes, vec!["0x1", "0x2"]);
+        assert_eq!(txs.fee.as_deref(), Some("150"));
+        assert_eq!(txs.status, Some(TxStatus::InBlock));
+        assert_eq!(txs.block_hash.as_deref(), Some("0xb2"));
+    }
+
+    fn result(iswc: &str, success: bool) -> WorkResult {
+        WorkResult {
+            iswc: iswc.to_string(),
+            success,
+            error: (!success).then(|| "Drop".to_string()),
+            tx_hash: None,
+            status: None,
+            onchain_id: None,
+            kind: SubmissionKind::Creation,
+        }
+    }
+
+    #[test]
+    fn test_work_results_matched_by_iswc() {
+        let works: Vec<Value> = ["T1", "T2", "T3"].iter()
+            .map(|iswc| serde_json::json!({"iswc": iswc}))
+            .collect();
+        let js = |iswc: &str, id: Option<&str>| JsSubmitResult {
+            iswc: Some(iswc.to_string()),
+            onchain_id: id.map(str::to_string),
+            hash: None,
+            tx_hash: None,
+            fee: None,
+            status: None,
+            success: id.is_some(),
+            error: id.is_none().then(|| "Drop".to_string()),
+        };
+
+        // Reordered and missing T2, T3 updates an existing work
+        let updates = BTreeMap::from([("T3".to_string(), "7".to_string())]);
+        let results = work_results(&works, vec![js("T3", None), js("T1", Some("42"))], &updates);
+        assert_eq!(results.iter().map(|r| r.iswc.as_str()).collect::<Vec<_>>(), ["T1", "T2", "T3"]);
+        assert_eq!(results[0].onchain_id.as_deref(), Some("42"));
+        assert!(results[0].success);
+        assert_eq!(results[1].error.as_deref(), Some("No result returned for this work"));
+        assert_eq!(results[2].error.as_deref(), Some("Drop"));
+        assert_eq!(results.iter().map(|r| r.kind).collect::<Vec<_>>(), [SubmissionKind::Creation, SubmissionKind::Creation, SubmissionKind::Update]);
+    }
+
+    #[test]
+    fn test_submission_outcome_retry_keeps_successes() {
+        let works: Vec<Value> = ["T1", "T2", "T3"].iter()
+            .map(|iswc| serde_json::json!({"iswc": iswc}))
+            .collect();
+        let mut outcome = SubmissionOutcome::new(works, vec![
+            result("T1", true),
+            result("T2", false),
+            result("T3", false),
+        ]);
+        assert_eq!(outcome.failed_works().len(), 2);
+        assert!(outcome.can_retry());
+
+        outcome.apply_retry(vec![result("T2", true), result("T3", false)]);
+        assert_eq!(outcome.success_count(), 2);
+        assert_eq!(outcome.failed_works(), vec![serde_json::json!({"iswc": "T3"})]);
+
+        outcome.apply_retry(vec![result("T3", false)]);
+        outcome.apply_retry(vec![result("T3", false)]);
+        assert_eq!(outcome.retry_rounds, MAX_RETRY_ROUNDS);
+        assert!(!outcome.can_retry());
+    }
+}
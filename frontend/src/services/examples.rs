@@ -0,0 +1,55 @@
+//! Fichiers d'exemple servis par le backend (`/api/v1/examples/{nom}`).
+//!
+//! Un fichier anonymisé permet d'essayer tout le parcours (preflight,
+//! transformation, aperçu, signature) sans exporter son propre catalogue.
+
+use gloo_net::http::Request;
+use web_sys::File;
+
+/// Exemple chargé par « Tester avec un fichier d'exemple »
+pub const DEFAULT_EXAMPLE: &str = "sacem";
+
+/// Télécharge un exemple et le renvoie comme un fichier choisi par l'utilisateur
+pub async fn fetch_example(name: &str, backend_url: &str) -> Result<File, String> {
+    let url = format!("{}/api/v1/examples/{}", backend_url, name);
+    let response = Request::get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("HTTP request failed: {}", e))?;
+
+    if !response.ok() {
+        return Err(format!("Server error ({})", response.status()));
+    }
+
+    let file_name = example_file_name(response.headers().get("content-disposition").as_deref(), name);
+    let content = response.text().await.map_err(|e| format!("Failed to read example: {}", e))?;
+
+    let parts = js_sys::Array::of1(&content.into());
+    let options = web_sys::FilePropertyBag::new();
+    options.set_type("text/csv");
+    File::new_with_str_sequence_and_options(&parts, &file_name, &options)
+        .map_err(|_| "Impossible de créer le fichier d'exemple".to_string())
+}
+
+/// Nom du fichier annoncé par le backend (`filename="…"`), sinon `<nom>.csv`
+fn example_file_name(disposition: Option<&str>, name: &str) -> String {
+    disposition
+        .and_then(|d| d.split("filename=").nth(1))
+        .map(|f| f.trim().trim_matches('"').to_string())
+        .filter(|f| !f.is_empty())
+        .unwrap_or_else(|| format!("{}.csv", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example_file_name() {
+        assert_eq!(
+            example_file_name(Some("attachment; filename=\"catalogue-sacem-exemple.csv\""), "sacem"),
+            "catalogue-sacem-exemple.csv"
+        );
+        assert_eq!(example_file_name(None, "sacem"), "sacem.csv");
+    }
+}
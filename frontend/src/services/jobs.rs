@@ -0,0 +1,254 @@
+//! Imports lancés depuis ce navigateur : statut, avancement (événements du
+//! job côté backend), logs et résultat de chacun, pour le tableau de bord.
+
+use gloo_net::http::Request;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::upload::UploadResponse;
+use crate::LogEntry;
+
+/// Jobs gardés dans le tableau de bord (les plus anciens terminés sont retirés)
+pub const MAX_DASHBOARD_JOBS: usize = 20;
+
+/// Étapes d'un job, dans l'ordre des événements du backend
+const JOB_STEPS: &[&str] = &["job.created", "matrix.selected", "transform.done", "validation.done", "job.completed"];
+
+/// Statut d'un import
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JobStatus {
+    Running,
+    /// Coût IA au-dessus du seuil du serveur, en attente de confirmation
+    AwaitingConfirmation,
+    Ready,
+    Warning,
+    AllInvalid,
+    Failed,
+}
+
+impl JobStatus {
+    /// Statut d'un import terminé (`status` de la réponse du backend)
+    pub fn from_response(status: &str) -> Self {
+        match status {
+            "ready" => JobStatus::Ready,
+            "warning" => JobStatus::Warning,
+            "all_invalid" => JobStatus::AllInvalid,
+            _ => JobStatus::Failed,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            JobStatus::Running => "En cours",
+            JobStatus::AwaitingConfirmation => "Coût IA à confirmer",
+            JobStatus::Ready => "Prêt",
+            JobStatus::Warning => "Avec erreurs",
+            JobStatus::AllInvalid => "Aucune ligne valide",
+            JobStatus::Failed => "Échec",
+        }
+    }
+
+    pub fn css_class(&self) -> &'static str {
+        match self {
+            JobStatus::Running | JobStatus::AwaitingConfirmation => "job-running",
+            JobStatus::Ready => "job-ready",
+            JobStatus::Warning => "job-warning",
+            JobStatus::AllInvalid | JobStatus::Failed => "job-failed",
+        }
+    }
+
+    /// Import pas encore terminé
+    pub fn is_active(&self) -> bool {
+        matches!(self, JobStatus::Running | JobStatus::AwaitingConfirmation)
+    }
+}
+
+/// Événement du cycle de vie d'un job (`GET /api/v1/jobs/{id}/events`)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct JobEvent {
+    pub sequence: usize,
+    /// `job.created`, `matrix.selected`, `transform.done`, `validation.done`, `job.completed` ou `job.failed`
+    pub event: String,
+    #[serde(default)]
+    pub data: Value,
+}
+
+#[derive(Deserialize)]
+struct JobEventsResponse {
+    events: Vec<JobEvent>,
+}
+
+/// Import suivi par le tableau de bord
+#[derive(Debug, Clone)]
+pub struct JobRecord {
+    /// Identifiant choisi par le navigateur et transmis au backend (`?jobId=`)
+    pub id: String,
+    pub file_name: String,
+    /// Heure de lancement (HH:MM)
+    pub started_at: String,
+    pub status: JobStatus,
+    /// Étapes franchies d'après les événements du backend
+    pub steps_done: usize,
+    /// Logs du job (quand il n'est pas ouvert ; sinon ceux du panneau)
+    pub logs: Vec<LogEntry>,
+    /// Réponse du backend, une fois le job terminé
+    pub response: Option<UploadResponse>,
+    pub error: Option<String>,
+    /// Période comptable saisie à l'envoi (normalisée par le backend à la fin du job)
+    pub period: Option<String>,
+}
+
+impl JobRecord {
+    pub fn new(id: impl Into<String>, file_name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            file_name: file_name.into(),
+            started_at: chrono::Local::now().format("%H:%M").to_string(),
+            status: JobStatus::Running,
+            steps_done: 0,
+            logs: Vec::new(),
+            response: None,
+            error: None,
+            period: None,
+        }
+    }
+
+    /// Période comptable du catalogue
+    pub fn with_period(mut self, period: Option<String>) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// Job de la période `filter` (`2025-Q1`) ou, pour une année (`2025`), d'une période
+    /// de cette année ; un filtre vide garde tous les jobs
+    pub fn matches_period(&self, filter: &str) -> bool {
+        let filter = filter.trim().to_uppercase();
+        if filter.is_empty() {
+            return true;
+        }
+        self.period.as_deref().map(str::to_uppercase).is_some_and(|period| {
+            period == filter || (filter.len() == 4 && period.starts_with(&filter))
+        })
+    }
+
+    /// Avancement en pourcentage (100 une fois terminé, même en échec)
+    pub fn progress_percent(&self) -> usize {
+        if self.status.is_active() {
+            self.steps_done.min(JOB_STEPS.len()) * 100 / JOB_STEPS.len()
+        } else {
+            100
+        }
+    }
+
+    /// Avancement d'après les événements du backend (jamais en arrière)
+    pub fn apply_events(&mut self, events: &[JobEvent]) {
+        self.steps_done = self.steps_done.max(steps_reached(events));
+    }
+
+    /// Termine le job avec la réponse du backend
+    pub fn finish(&mut self, response: UploadResponse) {
+        self.status = JobStatus::from_response(&response.status);
+        if let Some(ref period) = response.metadata.period {
+            self.period = Some(period.clone());
+        }
+        self.steps_done = JOB_STEPS.len();
+        self.response = Some(response);
+        self.error = None;
+    }
+
+    /// Termine le job en échec
+    pub fn fail(&mut self, error: impl Into<String>) {
+        self.status = JobStatus::Failed;
+        self.error = Some(error.into());
+    }
+}
+
+/// Étapes franchies : position de la dernière étape connue atteinte
+pub fn steps_reached(events: &[JobEvent]) -> usize {
+    events.iter()
+        .filter_map(|e| JOB_STEPS.iter().position(|step| *step == e.event))
+        .map(|i| i + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Ajoute un job en tête de liste ; au-delà de [`MAX_DASHBOARD_JOBS`], les
+/// plus anciens jobs terminés sont retirés (jamais ceux en cours)
+pub fn push_job(jobs: &mut Vec<JobRecord>, job: JobRecord) {
+    jobs.retain(|j| j.id != job.id);
+    jobs.insert(0, job);
+    while jobs.len() > MAX_DASHBOARD_JOBS {
+        match jobs.iter().rposition(|j| !j.status.is_active()) {
+            Some(oldest) => {
+                jobs.remove(oldest);
+            }
+            None => break,
+        }
+    }
+}
+
+/// Identifiant de job unique pour ce navigateur (1 à 64 lettres, chiffres ou `-`)
+pub fn new_job_id() -> String {
+    format!("web-{:x}-{:06x}", js_sys::Date::now() as u64, (js_sys::Math::random() * 16_777_216.0) as u64)
+}
+
+/// Événements d'un job, en cours ou terminé
+pub async fn fetch_job_events(job_id: &str, backend_url: &str) -> Result<Vec<JobEvent>, String> {
+    let url = format!("{}/api/v1/jobs/{}/events", backend_url, job_id);
+    let response = Request::get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("HTTP request failed: {}", e))?;
+
+    if !response.ok() {
+        return Err(format!("Server error ({})", response.status()));
+    }
+
+    response
+        .json::<JobEventsResponse>()
+        .await
+        .map(|r| r.events)
+        .map_err(|e| format!("Failed to parse response: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(sequence: usize, name: &str) -> JobEvent {
+        JobEvent { sequence, event: name.to_string(), data: Value::Null }
+    }
+
+    #[test]
+    fn test_job_progress_and_bounded_list() {
+        let mut job = JobRecord::new("web-1", "catalogue.csv");
+        assert_eq!(job.progress_percent(), 0);
+        job.apply_events(&[event(1, "job.created"), event(2, "matrix.selected")]);
+        assert_eq!(job.progress_percent(), 40);
+        // Événements partiels (liste tronquée) : l'avancement ne recule pas
+        job.apply_events(&[event(1, "job.created")]);
+        assert_eq!(job.steps_done, 2);
+        job.fail("Server error (500)");
+        assert_eq!(job.progress_percent(), 100);
+        assert!(!job.status.is_active());
+        assert_eq!(JobStatus::from_response("all_invalid"), JobStatus::AllInvalid);
+
+        let mut jobs = vec![JobRecord::new("running", "a.csv")];
+        for i in 0..MAX_DASHBOARD_JOBS {
+            let mut done = JobRecord::new(format!("done-{i}"), "b.csv");
+            done.fail("x");
+            push_job(&mut jobs, done);
+        }
+        assert_eq!(jobs.len(), MAX_DASHBOARD_JOBS);
+        assert_eq!(jobs[0].id, format!("done-{}", MAX_DASHBOARD_JOBS - 1));
+        assert!(jobs.iter().any(|j| j.id == "running"));
+        assert!(!jobs.iter().any(|j| j.id == "done-0"));
+
+        let job = JobRecord::new("web-2", "catalogue.csv").with_period(Some("2025-Q1".to_string()));
+        assert!(job.matches_period(" 2025-q1 "));
+        assert!(job.matches_period("2025"));
+        assert!(job.matches_period(""));
+        assert!(!job.matches_period("2025-Q2"));
+        assert!(!JobRecord::new("web-3", "a.csv").matches_period("2025"));
+    }
+}
@@ -5,8 +5,18 @@
 //! # Services
 //!
 //! - [`upload`] - CSV upload to massload backend
+//! - [`jobs`] - Uploads started from this browser (status, progress, logs, result)
 //! - [`wallet`] - Polkadot wallet extension integration (SubWallet, etc.)
 //! - [`blockchain`] - Allfeat blockchain transaction submission
+//! - [`accounting`] - Fees paid per submission and CSV export
+//! - [`updates`] - Works already on chain: detection, field delta, update plan
+//! - [`templates`] - Stored transformation templates (list, rename)
+//! - [`session`] - Wallet auto-lock after inactivity
+//! - [`backend`] - Backend URL chosen at runtime (overrides `BACKEND_URL`)
+//! - [`review`] - Reviewed batch export/import (wallet-free review, signing elsewhere)
+//! - [`examples`] - Bundled example CSVs for the guided demo
+//! - [`rpc_queue`] - Concurrency and retries of blockchain RPC queries
+//! - [`shortcuts`] - Keyboard shortcut keys and activation rules
 //!
 //! # JavaScript Bindings
 //!
@@ -15,9 +25,29 @@
 //! - `blockchain.js` - @allfeat/client SDK
 
 pub mod upload;
+pub mod jobs;
 pub mod wallet;
 pub mod blockchain;
+pub mod accounting;
+pub mod updates;
+pub mod templates;
+pub mod session;
+pub mod backend;
+pub mod review;
+pub mod examples;
+pub mod rpc_queue;
+pub mod shortcuts;
 
 pub use upload::*;
+pub use jobs::*;
 pub use wallet::*;
 pub use blockchain::*;
+pub use accounting::*;
+pub use updates::*;
+pub use templates::*;
+pub use session::*;
+pub use backend::*;
+pub use review::*;
+pub use examples::*;
+pub use rpc_queue::*;
+pub use shortcuts::*;
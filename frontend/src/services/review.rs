@@ -0,0 +1,93 @@
+//! Lot revu, exporté sans wallet pour être signé ailleurs.
+//!
+//! La personne qui gère le catalogue transforme et relit le fichier sans
+//! connecter de wallet, puis exporte le lot (œuvres MIDDS prêtes à signer,
+//! empreinte du fichier source). Celle qui détient le wallet du trésor
+//! dépose cet export dans la zone d'upload : il s'ouvre directement dans
+//! l'aperçu, sans nouvelle transformation, pour être signé.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::JsCast;
+
+/// Identifiant du format d'export, vérifié à l'import
+pub const REVIEW_FORMAT: &str = "massload-review/1";
+
+/// Œuvres relues, prêtes à signer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewBatch {
+    pub format: String,
+    /// Date de l'export (RFC 3339)
+    pub exported_at: String,
+    /// Empreinte SHA-256 du fichier source transformé
+    #[serde(default)]
+    pub file_sha256: Option<String>,
+    pub works: Vec<Value>,
+}
+
+impl ReviewBatch {
+    pub fn new(works: Vec<Value>, file_sha256: Option<String>) -> Self {
+        Self {
+            format: REVIEW_FORMAT.to_string(),
+            exported_at: chrono::Local::now().to_rfc3339(),
+            file_sha256,
+            works,
+        }
+    }
+
+    /// Lot exporté par Mass Load (None pour tout autre JSON, transformé normalement)
+    pub fn parse(json: &str) -> Option<Self> {
+        serde_json::from_str::<Self>(json).ok().filter(|batch| batch.format == REVIEW_FORMAT)
+    }
+}
+
+/// Télécharge le lot en JSON (`massload-lot-<date>.json`)
+pub fn download_review_batch(batch: &ReviewBatch) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(batch).map_err(|e| e.to_string())?;
+    let parts = js_sys::Array::of1(&json.into());
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type("application/json");
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options)
+        .map_err(|_| "Impossible de créer le fichier JSON".to_string())?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)
+        .map_err(|_| "Impossible de créer le lien de téléchargement".to_string())?;
+
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or("Document indisponible")?;
+    let link: web_sys::HtmlAnchorElement = document.create_element("a")
+        .map_err(|_| "Impossible de créer le lien".to_string())?
+        .unchecked_into();
+    link.set_href(&url);
+    link.set_download(&format!("massload-lot-{}.json", chrono::Local::now().format("%Y-%m-%d")));
+    link.click();
+    let _ = web_sys::Url::revoke_object_url(&url);
+    Ok(())
+}
+
+/// Lit un fichier JSON déposé et renvoie le lot s'il s'agit d'un export Mass Load
+pub async fn read_review_batch(file: &web_sys::File) -> Option<ReviewBatch> {
+    if !file.name().to_lowercase().ends_with(".json") {
+        return None;
+    }
+    let text = wasm_bindgen_futures::JsFuture::from(file.text()).await.ok()?.as_string()?;
+    ReviewBatch::parse(&text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_review_batch_round_trip() {
+        let batch = ReviewBatch::new(vec![json!({"iswc": "T1234567890", "title": "Song"})], Some("ab12".to_string()));
+        let json = serde_json::to_string(&batch).unwrap();
+        assert_eq!(ReviewBatch::parse(&json), Some(batch));
+
+        // Enregistrements MIDDS ordinaires : transformés par le backend
+        assert_eq!(ReviewBatch::parse(r#"[{"iswc": "T1234567890"}]"#), None);
+        assert_eq!(ReviewBatch::parse(r#"{"format": "other", "exportedAt": "", "works": []}"#), None);
+    }
+}
@@ -0,0 +1,121 @@
+//! Rate-limited queue for blockchain RPC queries.
+//!
+//! Checks over a whole batch (balances, duplicates, fee estimates) can mean
+//! thousands of queries: they run at most `concurrency` at a time and a
+//! failed query is retried with a growing delay before its error is kept.
+//! The JS bindings apply the same limits to the queries they send
+//! themselves (see [`configure_rpc_queue`]).
+
+use futures::stream::{self, StreamExt};
+use gloo_timers::future::TimeoutFuture;
+use std::future::Future;
+use wasm_bindgen::prelude::*;
+
+use crate::{RPC_CONCURRENCY, RPC_RETRIES};
+
+/// Delay before the first retry, multiplied by the attempt number for the next ones
+const RETRY_DELAY_MS: u32 = 500;
+
+/// Concurrency and retry settings of RPC queries
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RpcQueue {
+    /// Queries running at once
+    pub concurrency: usize,
+    /// Retries of a failed query
+    pub retries: u32,
+    /// Delay before the first retry (then 2×, 3×…), in milliseconds
+    pub retry_delay_ms: u32,
+}
+
+impl RpcQueue {
+    /// Settings from the build configuration (`RPC_CONCURRENCY`, `RPC_RETRIES`)
+    pub fn from_config() -> Self {
+        Self {
+            concurrency: RPC_CONCURRENCY.parse().unwrap_or(4).max(1),
+            retries: RPC_RETRIES.parse().unwrap_or(2),
+            retry_delay_ms: RETRY_DELAY_MS,
+        }
+    }
+
+    /// Run a query, retrying it on error
+    pub async fn call<T, F, Fut>(&self, query: F) -> Result<T, String>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, String>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match query().await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt >= self.retries => return Err(e),
+                Err(e) => {
+                    attempt += 1;
+                    log::warn!("RPC query failed ({}), retry {}/{}", e, attempt, self.retries);
+                    if self.retry_delay_ms > 0 {
+                        TimeoutFuture::new(self.retry_delay_ms * attempt).await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Run one query per item, at most `concurrency` at a time; results keep the item order
+    pub async fn run<I, T, F, Fut>(&self, items: Vec<I>, query: F) -> Vec<Result<T, String>>
+    where
+        F: Fn(&I) -> Fut,
+        Fut: Future<Output = Result<T, String>>,
+    {
+        let query = &query;
+        stream::iter(items.iter())
+            .map(|item| self.call(move || query(item)))
+            .buffered(self.concurrency.max(1))
+            .collect()
+            .await
+    }
+}
+
+impl Default for RpcQueue {
+    fn default() -> Self {
+        Self::from_config()
+    }
+}
+
+/// Apply the configured limits to the queries sent by the JS bindings
+pub fn configure_rpc_queue() {
+    let queue = RpcQueue::from_config();
+    configure_rpc_queue_js(queue.concurrency as u32, queue.retries, queue.retry_delay_ms);
+}
+
+#[wasm_bindgen(module = "/src/js/blockchain.js")]
+extern "C" {
+    #[wasm_bindgen(js_name = "configureRpcQueue")]
+    fn configure_rpc_queue_js(concurrency: u32, retries: u32, retry_delay_ms: u32);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_rpc_queue_retries_and_keeps_order() {
+        let queue = RpcQueue { concurrency: 2, retries: 2, retry_delay_ms: 0 };
+        // Attempts per item: "flaky" fails twice, "down" always fails
+        let attempts = RefCell::new(Vec::<&str>::new());
+        let results = futures::executor::block_on(queue.run(vec!["ok", "flaky", "down"], |item| {
+            attempts.borrow_mut().push(*item);
+            let count = attempts.borrow().iter().filter(|a| *a == item).count();
+            let item = *item;
+            async move {
+                match item {
+                    "flaky" if count <= 2 => Err("timeout".to_string()),
+                    "down" => Err("unreachable".to_string()),
+                    _ => Ok(item.to_string()),
+                }
+            }
+        }));
+
+        assert_eq!(results, vec![Ok("ok".to_string()), Ok("flaky".to_string()), Err("unreachable".to_string())]);
+        assert_eq!(attempts.borrow().iter().filter(|a| **a == "down").count(), 3);
+    }
+}
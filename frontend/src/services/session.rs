@@ -0,0 +1,67 @@
+//! Verrouillage de la session après inactivité.
+//!
+//! Sur les postes partagés des sociétés, le wallet connecté est oublié après
+//! [`IDLE_LOCK_MINUTES`](crate::config::IDLE_LOCK_MINUTES) sans clic, frappe
+//! ni défilement : il faut le reconnecter avant de signer.
+
+use std::time::Duration;
+
+use leptos::*;
+
+use crate::config::IDLE_LOCK_MINUTES;
+
+/// Fréquence de vérification de l'inactivité
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Délai d'inactivité avant verrouillage, None si désactivé (`0`)
+pub fn idle_timeout() -> Option<Duration> {
+    let minutes: u64 = IDLE_LOCK_MINUTES.parse().unwrap_or(15);
+    (minutes > 0).then(|| Duration::from_secs(minutes * 60))
+}
+
+/// Inactif depuis au moins `timeout` (horodatages en millisecondes)
+pub fn is_idle(last_activity_ms: f64, now_ms: f64, timeout: Duration) -> bool {
+    now_ms - last_activity_ms >= timeout.as_millis() as f64
+}
+
+/// Surveille l'activité de l'utilisateur et appelle `on_idle` une fois le
+/// délai dépassé (puis de nouveau après chaque reprise d'activité).
+pub fn watch_idle(on_idle: impl Fn() + 'static) {
+    let Some(timeout) = idle_timeout() else {
+        return;
+    };
+    let last_activity = store_value(js_sys::Date::now());
+    let locked = store_value(false);
+    let touch = move || {
+        last_activity.set_value(js_sys::Date::now());
+        locked.set_value(false);
+    };
+
+    // Les handles restent actifs pour toute la durée de l'application
+    let _ = window_event_listener(ev::mousedown, move |_| touch());
+    let _ = window_event_listener(ev::keydown, move |_| touch());
+    let _ = window_event_listener(ev::scroll, move |_| touch());
+    let _ = window_event_listener(ev::touchstart, move |_| touch());
+
+    let _ = set_interval_with_handle(
+        move || {
+            if !locked.get_value() && is_idle(last_activity.get_value(), js_sys::Date::now(), timeout) {
+                locked.set_value(true);
+                on_idle();
+            }
+        },
+        CHECK_INTERVAL,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_idle() {
+        let timeout = Duration::from_secs(15 * 60);
+        assert!(!is_idle(1_000.0, 1_000.0 + 60_000.0, timeout));
+        assert!(is_idle(1_000.0, 1_000.0 + 900_000.0, timeout));
+    }
+}
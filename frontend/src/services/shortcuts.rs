@@ -0,0 +1,86 @@
+//! Raccourcis clavier : touches, libellés et règles d'activation.
+//!
+//! Les composants y associent leurs actions via le registre
+//! (`components::shortcuts`) ; ici, uniquement ce qui ne dépend pas du DOM.
+
+/// Action déclenchable au clavier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShortcutAction {
+    /// Choisir un fichier à importer
+    Upload,
+    /// Rechercher dans l'aperçu
+    Search,
+    /// Valider l'étape en cours (colonnes, coût IA, envoi)
+    Validate,
+    /// Signer et envoyer les œuvres
+    Sign,
+    /// Afficher ou masquer l'aide
+    Help,
+}
+
+/// Raccourcis, dans l'ordre de l'aide
+pub const SHORTCUTS: &[ShortcutAction] = &[
+    ShortcutAction::Upload,
+    ShortcutAction::Search,
+    ShortcutAction::Validate,
+    ShortcutAction::Sign,
+    ShortcutAction::Help,
+];
+
+impl ShortcutAction {
+    /// Touche (valeur `KeyboardEvent.key`)
+    pub fn key(&self) -> &'static str {
+        match self {
+            ShortcutAction::Upload => "u",
+            ShortcutAction::Search => "/",
+            ShortcutAction::Validate => "v",
+            ShortcutAction::Sign => "s",
+            ShortcutAction::Help => "?",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ShortcutAction::Upload => "Importer un fichier",
+            ShortcutAction::Search => "Rechercher dans l'aperçu",
+            ShortcutAction::Validate => "Valider l'étape en cours (colonnes, coût IA, envoi)",
+            ShortcutAction::Sign => "Signer & envoyer",
+            ShortcutAction::Help => "Afficher ou masquer cette aide",
+        }
+    }
+
+    /// Action d'une touche pressée, hors saisie et sans Ctrl, Alt ni Méta
+    /// (les raccourcis du navigateur restent intacts)
+    pub fn from_key(key: &str, modified: bool, typing: bool) -> Option<Self> {
+        if modified || typing {
+            return None;
+        }
+        // Verrouillage des majuscules : `U` vaut `u`
+        let key = key.to_lowercase();
+        SHORTCUTS.iter().copied().find(|action| action.key() == key)
+    }
+}
+
+/// Élément de saisie : les touches y sont du texte, pas des raccourcis
+pub fn is_typing_target(tag_name: &str, content_editable: bool) -> bool {
+    content_editable || matches!(tag_name.to_uppercase().as_str(), "INPUT" | "TEXTAREA" | "SELECT")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shortcut_keys() {
+        assert_eq!(ShortcutAction::from_key("u", false, false), Some(ShortcutAction::Upload));
+        assert_eq!(ShortcutAction::from_key("S", false, false), Some(ShortcutAction::Sign));
+        assert_eq!(ShortcutAction::from_key("?", false, false), Some(ShortcutAction::Help));
+        assert_eq!(ShortcutAction::from_key("x", false, false), None);
+        // Ctrl+S (enregistrer) et les lettres tapées dans un champ ne déclenchent rien
+        assert_eq!(ShortcutAction::from_key("s", true, false), None);
+        assert_eq!(ShortcutAction::from_key("v", false, true), None);
+        assert!(is_typing_target("input", false));
+        assert!(is_typing_target("DIV", true));
+        assert!(!is_typing_target("BUTTON", false));
+    }
+}
@@ -0,0 +1,117 @@
+//! Modèles de transformation enregistrés par le backend : liste et renommage.
+
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Modèle enregistré (`GET /api/v1/templates`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateSummary {
+    pub id: String,
+    pub name: String,
+    pub csv_columns: Vec<String>,
+    pub created_at: String,
+    pub last_used: Option<String>,
+    pub success_rate: f64,
+    pub use_count: u32,
+    /// Utilisations par période comptable (`2025-Q1` → 3)
+    #[serde(default)]
+    pub periods: BTreeMap<String, u32>,
+}
+
+impl TemplateSummary {
+    /// Utilisations par période, ex. « 2025-Q1 (3), 2025-Q2 (1) »
+    pub fn periods_summary(&self) -> String {
+        self.periods.iter()
+            .map(|(period, count)| format!("{} ({})", period, count))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Date de création (AAAA-MM-JJ)
+    pub fn created_date(&self) -> &str {
+        self.created_at.get(..10).unwrap_or(&self.created_at)
+    }
+}
+
+/// Modèle créé par l'IA lors d'un upload, à nommer par l'utilisateur
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewTemplate {
+    pub id: String,
+    /// Nom d'enregistrement (nom du fichier)
+    pub name: String,
+    /// Nom lisible proposé par le backend
+    pub suggested_name: String,
+}
+
+/// Liste les modèles enregistrés, du plus récent au plus ancien
+pub async fn list_templates(backend_url: &str, period: Option<&str>) -> Result<Vec<TemplateSummary>, String> {
+    let url = format!("{}/api/v1/templates", backend_url);
+    let mut request = Request::get(&url);
+    if let Some(period) = period {
+        request = request.query([("period", period)]);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("HTTP request failed: {}", e))?;
+
+    if !response.ok() {
+        return Err(format!("Server error ({})", response.status()));
+    }
+
+    response
+        .json::<Vec<TemplateSummary>>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))
+}
+
+/// Renomme un modèle (son identifiant ne change pas)
+pub async fn rename_template(id: &str, name: &str, backend_url: &str) -> Result<TemplateSummary, String> {
+    let url = format!("{}/api/v1/templates/{}/rename", backend_url, id);
+    let response = Request::post(&url)
+        .json(&serde_json::json!({ "name": name }))
+        .map_err(|e| format!("Failed to build request: {}", e))?
+        .send()
+        .await
+        .map_err(|e| format!("HTTP request failed: {}", e))?;
+
+    if !response.ok() {
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Server error ({}): {}", response.status(), error_text));
+    }
+
+    response
+        .json::<TemplateSummary>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_summary_deserialization() {
+        let json = r#"[{
+            "id": "export-sacem-mars-1712345",
+            "name": "export_sacem_mars",
+            "csvColumns": ["Code ISWC", "Titre"],
+            "createdAt": "2025-03-01T10:00:00+00:00",
+            "lastUsed": null,
+            "successRate": 0.95,
+            "useCount": 3,
+            "periods": {"2025-Q2": 1, "2025-Q1": 2}
+        }]"#;
+
+        let templates: Vec<TemplateSummary> = serde_json::from_str(json).unwrap();
+        assert_eq!(templates[0].created_date(), "2025-03-01");
+        assert_eq!(templates[0].use_count, 3);
+        assert_eq!(templates[0].periods_summary(), "2025-Q1 (2), 2025-Q2 (1)");
+    }
+}
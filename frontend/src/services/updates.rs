@@ -0,0 +1,229 @@
+//! Mises à jour d'œuvres déjà enregistrées on-chain.
+//!
+//! Chaque œuvre enregistrée depuis ce navigateur est mémorisée dans le
+//! `localStorage` (ISWC → id on-chain et dernier JSON envoyé). Avant
+//! signature, les œuvres d'un nouveau fichier sont comparées à cet index :
+//! une œuvre connue dont des champs ont changé part en mise à jour
+//! (`musicalWorks.update`) au lieu d'un nouvel enregistrement, une œuvre
+//! connue sans changement n'est pas renvoyée.
+
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use super::blockchain::{SubmissionKind, WorkResult};
+
+/// Clé de l'index dans le `localStorage`
+const STORAGE_KEY: &str = "massload.registered_works";
+
+/// Œuvre enregistrée on-chain depuis ce navigateur
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisteredWork {
+    pub onchain_id: String,
+    /// Dernière version envoyée (enregistrement ou mise à jour)
+    pub work: Value,
+}
+
+/// Œuvres enregistrées, par ISWC
+pub type WorkIndex = BTreeMap<String, RegisteredWork>;
+
+/// Champ modifié par rapport à la version on-chain (`None` : absent)
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl FieldChange {
+    /// « champ : avant → après » affiché dans la confirmation
+    pub fn describe(&self) -> String {
+        let show = |value: &Option<Value>| match value {
+            None | Some(Value::Null) => "∅".to_string(),
+            Some(Value::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+        };
+        format!("{} : {} → {}", self.field, show(&self.before), show(&self.after))
+    }
+}
+
+/// Champs modifiés entre deux versions d'une œuvre.
+///
+/// Les listes d'objets (créateurs) sont comparées sans tenir compte de
+/// l'ordre : un changement par élément ajouté ou retiré.
+pub fn work_delta(before: &Value, after: &Value) -> Vec<FieldChange> {
+    let empty = serde_json::Map::new();
+    let old = before.as_object().unwrap_or(&empty);
+    let new = after.as_object().unwrap_or(&empty);
+    let mut fields: Vec<&String> = old.keys().chain(new.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    let mut changes = Vec::new();
+    for field in fields {
+        let (a, b) = (old.get(field).filter(|v| !v.is_null()), new.get(field).filter(|v| !v.is_null()));
+        match (a, b) {
+            (Some(Value::Array(a)), Some(Value::Array(b))) => {
+                for removed in a.iter().filter(|item| !b.contains(item)) {
+                    changes.push(FieldChange { field: field.clone(), before: Some(removed.clone()), after: None });
+                }
+                for added in b.iter().filter(|item| !a.contains(item)) {
+                    changes.push(FieldChange { field: field.clone(), before: None, after: Some(added.clone()) });
+                }
+            }
+            (a, b) if a != b => changes.push(FieldChange { field: field.clone(), before: a.cloned(), after: b.cloned() }),
+            _ => {}
+        }
+    }
+    changes
+}
+
+/// Mise à jour prévue d'une œuvre existante
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedUpdate {
+    pub iswc: String,
+    pub onchain_id: String,
+    pub changes: Vec<FieldChange>,
+}
+
+/// Répartition des œuvres d'un lot entre créations et mises à jour
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SubmissionPlan {
+    /// Œuvres à envoyer (créations et mises à jour), dans l'ordre du fichier
+    pub works: Vec<Value>,
+    /// Mises à jour parmi `works`
+    pub updates: Vec<PlannedUpdate>,
+    /// ISWC des œuvres déjà enregistrées sans changement (non envoyées)
+    pub unchanged: Vec<String>,
+}
+
+impl SubmissionPlan {
+    pub fn new(works: &[Value], index: &WorkIndex) -> Self {
+        let mut plan = Self::default();
+        for work in works {
+            let known = work.get("iswc").and_then(|v| v.as_str()).and_then(|iswc| Some((iswc, index.get(iswc)?)));
+            if let Some((iswc, registered)) = known {
+                let changes = work_delta(&registered.work, work);
+                if changes.is_empty() {
+                    plan.unchanged.push(iswc.to_string());
+                    continue;
+                }
+                plan.updates.push(PlannedUpdate {
+                    iswc: iswc.to_string(),
+                    onchain_id: registered.onchain_id.clone(),
+                    changes,
+                });
+            }
+            plan.works.push(work.clone());
+        }
+        plan
+    }
+
+    pub fn creations(&self) -> usize {
+        self.works.len() - self.updates.len()
+    }
+
+    /// ISWC → id on-chain des œuvres à mettre à jour
+    pub fn update_targets(&self) -> BTreeMap<String, String> {
+        self.updates.iter().map(|u| (u.iswc.clone(), u.onchain_id.clone())).collect()
+    }
+}
+
+/// Ajoute à l'index les œuvres enregistrées ou mises à jour avec succès
+/// (`results` dans l'ordre de `works`)
+pub fn index_results(index: &mut WorkIndex, works: &[Value], results: &[WorkResult]) {
+    for (work, result) in works.iter().zip(results) {
+        if !result.success {
+            continue;
+        }
+        let onchain_id = match (&result.onchain_id, result.kind) {
+            (Some(id), _) => id.clone(),
+            // Mise à jour sans événement lu : l'id ne change pas
+            (None, SubmissionKind::Update) => match index.get(&result.iswc) {
+                Some(known) => known.onchain_id.clone(),
+                None => continue,
+            },
+            // Sans id, une création ne pourra pas être mise à jour
+            (None, SubmissionKind::Creation) => continue,
+        };
+        index.insert(result.iswc.clone(), RegisteredWork { onchain_id, work: work.clone() });
+    }
+}
+
+fn storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// Index des œuvres enregistrées depuis ce navigateur
+pub fn load_work_index() -> WorkIndex {
+    storage()
+        .and_then(|s| s.get_item(STORAGE_KEY).ok().flatten())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Mémorise le résultat d'un envoi dans l'index
+pub fn remember_registered(works: &[Value], results: &[WorkResult]) {
+    let mut index = load_work_index();
+    index_results(&mut index, works, results);
+    if let (Some(storage), Ok(json)) = (storage(), serde_json::to_string(&index)) {
+        if storage.set_item(STORAGE_KEY, &json).is_err() {
+            log::warn!("⚠️ Impossible de mémoriser les œuvres enregistrées (localStorage plein ?)");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn result(iswc: &str, kind: SubmissionKind, onchain_id: Option<&str>) -> WorkResult {
+        WorkResult {
+            iswc: iswc.to_string(),
+            success: true,
+            error: None,
+            tx_hash: None,
+            status: None,
+            onchain_id: onchain_id.map(str::to_string),
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_plan_updates_from_index() {
+        let alice = json!({"id": {"type": "Ipi", "value": 1}, "role": "Composer"});
+        let bob = json!({"id": {"type": "Ipi", "value": 2}, "role": "Author"});
+        let first = vec![
+            json!({"iswc": "T1", "title": "Song", "creators": [alice.clone()]}),
+            json!({"iswc": "T2", "title": "Other", "creators": [alice.clone()]}),
+        ];
+        let mut index = WorkIndex::new();
+        index_results(&mut index, &first, &[
+            result("T1", SubmissionKind::Creation, Some("7")),
+            result("T2", SubmissionKind::Creation, None),
+        ]);
+        assert_eq!(index.len(), 1);
+
+        // T1 gagne un créateur et change de titre, T2 n'a jamais reçu d'id, T3 est nouvelle
+        let second = vec![
+            json!({"iswc": "T1", "title": "Song (remix)", "creators": [bob.clone(), alice.clone()]}),
+            first[1].clone(),
+            json!({"iswc": "T3", "title": "New", "creators": []}),
+        ];
+        let plan = SubmissionPlan::new(&second, &index);
+        assert_eq!((plan.works.len(), plan.creations()), (3, 2));
+        assert_eq!(plan.update_targets(), BTreeMap::from([("T1".to_string(), "7".to_string())]));
+        let changes: Vec<String> = plan.updates[0].changes.iter().map(FieldChange::describe).collect();
+        assert_eq!(changes, vec![
+            format!("creators : ∅ → {}", bob),
+            "title : Song → Song (remix)".to_string(),
+        ]);
+
+        // Une fois la mise à jour passée, le même fichier ne renvoie plus T1
+        index_results(&mut index, &second[..1], &[result("T1", SubmissionKind::Update, None)]);
+        let plan = SubmissionPlan::new(&second[..1], &index);
+        assert!(plan.works.is_empty());
+        assert_eq!(plan.unchanged, vec!["T1".to_string()]);
+    }
+}
@@ -3,8 +3,12 @@
 use gloo_net::http::Request;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::BTreeMap;
 use web_sys::{File, FormData};
 
+use super::templates::NewTemplate;
+use crate::work_title;
+
 /// Response du backend pour l'upload
 /// Les musical_works sont en format MIDDS natif
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,16 +31,397 @@ pub struct ResponseMetadata {
     pub cached: bool,
     pub csv_info: CsvInfo,
     pub validation: ValidationStats,
+    /// Lignes ignorées faute de champ obligatoire (complétables)
+    #[serde(default)]
+    pub skipped: Vec<SkippedRow>,
+    /// Changements depuis le précédent envoi du même catalogue
+    #[serde(default)]
+    pub diff: Option<CatalogDiff>,
+    /// Colonnes lues, manquantes et ignorées par le modèle en cache
+    #[serde(default)]
+    pub template_columns: Option<ColumnCoverage>,
+    /// Causes d'erreur quand aucune ligne n'est valide (statut `all_invalid`)
+    #[serde(default)]
+    pub failure: Option<FailureReport>,
+    /// Modèle créé par l'IA pour ce fichier, à nommer
+    #[serde(default)]
+    pub new_template: Option<NewTemplate>,
+    /// Moteur ayant généré le modèle (IA principale, secours ou heuristique)
+    #[serde(default)]
+    pub matrix_engine: Option<MatrixEngine>,
+    /// Taille et coût estimés de la requête IA (quand l'IA a été appelée)
+    #[serde(default)]
+    pub ai_estimate: Option<AiCostEstimate>,
+    /// Œuvres sans ISWC, à demander aux sociétés
+    #[serde(default)]
+    pub missing_iswc: usize,
+    /// Échecs des opérations par champ (regex invalide, nombre illisible, valeur non mappée)
+    #[serde(default)]
+    pub field_stats: BTreeMap<String, FieldStats>,
+    /// Ayants droit par rôle et œuvres sans compositeur
+    #[serde(default)]
+    pub roles: RoleReport,
+    /// Mention d'une instance de démonstration publique
+    #[serde(default)]
+    pub watermark: Option<String>,
+    /// Période comptable du catalogue (`2025-Q1`), normalisée par le backend
+    #[serde(default)]
+    pub period: Option<String>,
 }
 
-/// Info CSV
+/// Moteur de génération du modèle et raison du basculement éventuel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatrixEngine {
+    /// `primary`, `secondary` ou `heuristic`
+    pub engine: String,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub failover_reason: Option<String>,
+}
+
+impl MatrixEngine {
+    /// Libellé affiché dans les logs
+    pub fn label(&self) -> String {
+        match (self.engine.as_str(), self.model.as_deref()) {
+            ("heuristic", _) => "heuristique (noms de colonnes, sans IA)".to_string(),
+            ("secondary", Some(model)) => format!("IA de secours ({})", model),
+            ("secondary", None) => "IA de secours".to_string(),
+            (_, Some(model)) => format!("IA principale ({})", model),
+            _ => "IA principale".to_string(),
+        }
+    }
+}
+
+/// Correspondance entre les colonnes du fichier et celles du modèle
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnCoverage {
+    pub matched: Vec<String>,
+    pub missing: Vec<String>,
+    pub unused: Vec<String>,
+}
+
+impl ColumnCoverage {
+    /// Moins de la moitié des colonnes du fichier sont lues par le modèle
+    pub fn mostly_ignored(&self) -> bool {
+        self.unused.len() > self.matched.len()
+    }
+}
+
+/// Cause d'erreur et nombre de lignes concernées
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorCause {
+    pub message: String,
+    pub count: usize,
+}
+
+/// Piste de correction proposée par le backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Suggestion {
+    CheckTemplate,
+    CheckDelimiter,
+    CheckEncoding,
+    CheckRequiredColumns,
+}
+
+impl Suggestion {
+    /// Conseil affiché sur l'écran d'échec
+    pub fn label(&self) -> &'static str {
+        match self {
+            Suggestion::CheckTemplate => "Vérifiez que le fichier correspond au modèle de transformation (colonnes renommées ou déplacées ?)",
+            Suggestion::CheckDelimiter => "Une seule colonne détectée : vérifiez le séparateur (virgule, point-virgule, tabulation)",
+            Suggestion::CheckEncoding => "Encodage incertain : réessayez en forçant l'encodage du fichier",
+            Suggestion::CheckRequiredColumns => "Des champs obligatoires manquent : vérifiez les colonnes titre, ISWC et IPI",
+        }
+    }
+}
+
+/// Diagnostic renvoyé quand toutes les lignes sont invalides
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailureReport {
+    /// Causes les plus fréquentes en premier
+    pub top_causes: Vec<ErrorCause>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// Œuvre présente dans les deux versions avec un contenu différent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkChange {
+    pub iswc: String,
+    pub title: String,
+    /// Champs modifiés (ex. `creators`)
+    pub fields: Vec<String>,
+}
+
+/// Diff avec la version précédente du catalogue (mode révision)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogDiff {
+    #[serde(default)]
+    pub catalog: String,
+    #[serde(default)]
+    pub previous_job_id: Option<String>,
+    #[serde(default)]
+    pub previous_created_at: Option<String>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<WorkChange>,
+    pub unchanged: usize,
+    /// Clés partagées par plusieurs œuvres (ou vides) : œuvres non comparées, resoumises
+    #[serde(default)]
+    pub ambiguous: Vec<String>,
+}
+
+impl CatalogDiff {
+    /// Œuvres à soumettre : seulement les nouvelles, les modifiées et les ambiguës
+    pub fn works_to_submit(&self, works: &[Value]) -> Vec<Value> {
+        works.iter()
+            .filter(|w| {
+                let key = work_key(w);
+                self.added.contains(&key) || self.changed.iter().any(|c| c.iswc == key) || self.ambiguous.contains(&key)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Résumé affiché dans le bandeau de révision
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "{} nouvelle(s), {} modifiée(s), {} supprimée(s), {} inchangée(s)",
+            self.added.len(), self.changed.len(), self.removed.len(), self.unchanged,
+        );
+        if !self.ambiguous.is_empty() {
+            summary.push_str(&format!(", {} clé(s) ambiguë(s)", self.ambiguous.len()));
+        }
+        summary
+    }
+}
+
+/// Clé d'une œuvre entre deux versions (ISWC, sinon clé d'œuvre, sinon titre), comme côté backend
+pub fn work_key(work: &Value) -> String {
+    let field = |name: &str| {
+        work.get(name)
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+    };
+    field("iswc")
+        .map(String::from)
+        .or_else(|| field("workKey").map(|key| format!("key:{}", key)))
+        .or_else(|| {
+            work_title(work)
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|t| format!("title:{}", t.to_lowercase()))
+        })
+        .unwrap_or_default()
+}
+
+/// Ligne ignorée par le backend (champ obligatoire manquant)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedRow {
+    /// Index de la ligne de données (0 = première ligne après l'en-tête)
+    pub row: usize,
+    /// Ligne dans le fichier source (1 = première ligne), si connue
+    #[serde(default)]
+    pub line: Option<usize>,
+    pub reason: String,
+    pub missing_fields: Vec<String>,
+    /// Champs déjà transformés (titre, ISWC, ...)
+    #[serde(default)]
+    pub partial: serde_json::Map<String, Value>,
+}
+
+impl SkippedRow {
+    /// Un seul champ manque : la ligne peut être complétée dans le tableau
+    pub fn single_missing_field(&self) -> Option<&str> {
+        match self.missing_fields.as_slice() {
+            [field] => Some(field.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Numéro de ligne affiché : ligne du fichier, à défaut rang de la ligne de données
+    pub fn display_line(&self) -> usize {
+        self.line.unwrap_or(self.row + 1)
+    }
+
+    /// Libellé court pour identifier la ligne (titre ou ISWC)
+    pub fn label(&self) -> String {
+        ["title", "iswc"]
+            .iter()
+            .find_map(|f| self.partial.get(*f).and_then(|v| v.as_str()))
+            .unwrap_or("—")
+            .to_string()
+    }
+}
+
+/// Valeurs saisies pour compléter une ligne ignorée
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Amendment {
+    pub row: usize,
+    pub values: serde_json::Map<String, Value>,
+}
+
+/// Réponse après complétion des lignes ignorées
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+pub struct AmendResponse {
+    pub job_id: String,
+    /// Toutes les œuvres du job, regroupées avec les lignes complétées
+    pub musical_works: Vec<Value>,
+    pub amended: usize,
+    pub skipped: Vec<SkippedRow>,
+    pub errors: Vec<ValidationError>,
+}
+
+/// Profil d'une colonne du fichier source (onglet « Explorer le fichier »)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnProfile {
+    pub name: String,
+    /// Lignes avec une valeur
+    pub filled: usize,
+    /// Part des lignes vides (0 à 1)
+    pub null_rate: f64,
+    /// Valeurs distinctes
+    pub distinct: usize,
+    pub min_length: usize,
+    pub max_length: usize,
+    /// Motifs de valeurs les plus fréquents
+    pub patterns: Vec<PatternCount>,
+    /// Premières valeurs distinctes, dans l'ordre du fichier
+    #[serde(default)]
+    pub samples: Vec<String>,
+}
+
+impl ColumnProfile {
+    /// Colonne toujours vide
+    pub fn is_empty(&self) -> bool {
+        self.filled == 0
+    }
+
+    /// Une seule valeur répétée sur toutes les lignes remplies
+    pub fn is_constant(&self) -> bool {
+        self.distinct == 1 && self.filled > 1
+    }
+
+    /// Valeurs toutes différentes (identifiant probable : ISWC, code interne)
+    pub fn is_unique(&self) -> bool {
+        self.filled > 1 && self.distinct == self.filled
+    }
+
+    /// Valeurs presque toutes différentes (au moins 90 % sur 10 lignes ou plus, comme le backend)
+    pub fn is_high_cardinality(&self) -> bool {
+        self.filled >= 10 && self.distinct as f64 >= self.filled as f64 * 0.9
+    }
+
+    /// « 75 % remplie · ex. CA, A » affiché au survol d'une colonne
+    pub fn summary(&self) -> String {
+        let fill = format!("{:.0} % remplie", (1.0 - self.null_rate) * 100.0);
+        if self.samples.is_empty() {
+            fill
+        } else {
+            format!("{} · ex. {}", fill, self.samples.join(", "))
+        }
+    }
+}
+
+/// Statistiques des colonnes calculées à la lecture du fichier
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseReport {
+    pub rows: usize,
+    pub columns: Vec<ColumnProfile>,
+}
+
+impl ParseReport {
+    pub fn column(&self, name: &str) -> Option<&ColumnProfile> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+
+    /// Noms des colonnes toujours vides
+    pub fn empty_columns(&self) -> Vec<&str> {
+        self.columns.iter().filter(|c| c.is_empty()).map(|c| c.name.as_str()).collect()
+    }
+
+    /// Noms des colonnes aux valeurs presque toutes différentes
+    pub fn high_cardinality_columns(&self) -> Vec<&str> {
+        self.columns.iter().filter(|c| c.is_high_cardinality()).map(|c| c.name.as_str()).collect()
+    }
+}
+
+/// Motif de valeur et nombre de valeurs correspondantes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PatternCount {
+    pub pattern: String,
+    pub count: usize,
+}
+
+/// Réponse de `GET /api/v1/jobs/{id}/columns`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnsResponse {
+    pub job_id: String,
+    pub row_count: usize,
+    pub columns: Vec<ColumnProfile>,
+}
+
+/// Info CSV
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CsvInfo {
     pub encoding: String,
+    /// Fiabilité de la détection d'encodage (0 à 1)
+    #[serde(default)]
+    pub encoding_confidence: Option<f32>,
     pub delimiter: String,
     pub row_count: usize,
     pub columns: Vec<String>,
+    /// Empreinte SHA-256 du fichier envoyé
+    #[serde(default)]
+    pub sha256: Option<String>,
+    /// Lignes de fin écartées (totaux, marqueur de fin de fichier)
+    #[serde(default)]
+    pub footer_lines: Vec<String>,
+    /// Lignes dont le nombre de cellules diffère de l'en-tête
+    #[serde(default)]
+    pub ragged_rows: Vec<RaggedRow>,
+    /// Réparations faites avant la lecture (caractères NUL, fins de ligne mélangées)
+    #[serde(default)]
+    pub diagnostics: ParseDiagnostics,
+    /// Remplissage, valeurs distinctes et exemples par colonne
+    #[serde(default)]
+    pub report: ParseReport,
+}
+
+/// Réparations du texte avant la lecture, avec une note par réparation
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseDiagnostics {
+    #[serde(default)]
+    pub notes: Vec<String>,
+}
+
+/// Ligne au nombre de cellules différent de l'en-tête
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RaggedRow {
+    /// Numéro de ligne dans le fichier (à partir de 1)
+    pub line: usize,
+    pub cells: usize,
+    pub expected: usize,
+    /// Ligne écartée des données
+    #[serde(default)]
+    pub rejected: bool,
 }
 
 /// Stats validation
@@ -46,27 +431,316 @@ pub struct ValidationStats {
     pub valid: usize,
     pub invalid: usize,
     pub errors: Vec<ValidationError>,
+    /// Anomalies non bloquantes (année de création improbable) : les œuvres restent soumises
+    #[serde(default)]
+    pub warnings: Vec<ValidationWarning>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ValidationError {
     pub record_index: usize,
+    /// Ligne dans le fichier source, si connue
+    #[serde(default)]
+    pub line: Option<usize>,
     pub errors: Vec<String>,
 }
 
-/// Upload un fichier CSV vers le backend
-pub async fn upload_csv(file: File, backend_url: &str) -> Result<UploadResponse, String> {
-    // Créer FormData
+/// Anomalie non bloquante sur une œuvre regroupée
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationWarning {
+    pub record_index: usize,
+    pub field: String,
+    pub message: String,
+    /// Lignes du fichier source de l'œuvre, si connues
+    #[serde(default)]
+    pub lines: Vec<usize>,
+}
+
+/// Réponse du preflight (parsing seul, sans IA)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightResponse {
+    pub file_name: Option<String>,
+    pub csv_info: CsvInfo,
+}
+
+/// Options envoyées avec le fichier
+#[derive(Debug, Clone, Default)]
+pub struct UploadOptions {
+    /// Colonnes à retirer avant tout envoi à l'IA (données personnelles)
+    pub exclude_columns: Vec<String>,
+    /// Mode confidentiel : seule la structure des colonnes est envoyée à l'IA
+    pub structure_only: bool,
+    /// Encodage forcé (sinon détection automatique)
+    pub encoding: Option<String>,
+    /// Logs détaillés ligne par ligne (`?verbose=true`)
+    pub verbose: bool,
+    /// Coût IA estimé accepté par l'utilisateur (après une demande de confirmation)
+    pub confirm_ai: bool,
+    /// Identifiant du job, choisi pour suivre son avancement (`?jobId=`)
+    pub job_id: Option<String>,
+    /// Période comptable du catalogue (`2025`, `2025-Q1`, `2025-03`, `2025-W07`)
+    pub period: Option<String>,
+}
+
+/// Taille et coût estimés de la requête IA
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AiCostEstimate {
+    pub prompt_chars: usize,
+    pub input_tokens: u32,
+    pub max_output_tokens: u32,
+    /// Borne haute en USD pour une tentative
+    pub cost_usd: f64,
+}
+
+/// Ayants droit par rôle et œuvres sans compositeur
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleReport {
+    /// Nombre d'ayants droit par rôle (`Composer`, `Author`...)
+    #[serde(default)]
+    pub roles: BTreeMap<String, usize>,
+    #[serde(default)]
+    pub works_without_composer: usize,
+    /// Premières œuvres sans compositeur (20 au plus)
+    #[serde(default)]
+    pub without_composer: Vec<WorkRef>,
+    /// Réseau dont les rôles acceptés ont été vérifiés
+    #[serde(default)]
+    pub network: String,
+    /// Rôles refusés par le runtime du réseau
+    #[serde(default)]
+    pub unsupported: Vec<UnsupportedRole>,
+}
+
+/// Rôle du lot refusé par la chaîne, avec le rôle à utiliser à la place
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnsupportedRole {
+    pub role: String,
+    /// Nombre d'ayants droit ayant ce rôle
+    pub creators: usize,
+    pub suggestion: Option<String>,
+}
+
+/// Œuvre du lot (ISWC et titre)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkRef {
+    pub iswc: String,
+    pub title: String,
+}
+
+/// Part d'échecs au-delà de laquelle un champ est signalé (comme le backend)
+pub const FIELD_FAILURE_WARN_RATE: f64 = 0.1;
+
+/// Compteurs des opérations d'un champ cible
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldStats {
+    /// Valeurs non vides transformées
+    pub values: usize,
+    /// Valeurs dont au moins une opération a échoué
+    pub failures: usize,
+    /// Échecs par opération (`to_number`, `map`...)
+    #[serde(default)]
+    pub by_operation: BTreeMap<String, usize>,
+}
+
+impl FieldStats {
+    pub fn failure_rate(&self) -> f64 {
+        if self.values == 0 { 0.0 } else { self.failures as f64 / self.values as f64 }
+    }
+}
+
+/// Échec d'un upload
+#[derive(Debug, Clone, PartialEq)]
+pub enum UploadError {
+    /// Le coût IA estimé dépasse le seuil du serveur : confirmer puis renvoyer
+    AiConfirmation(AiCostEstimate),
+    Failed(String),
+}
+
+impl std::fmt::Display for UploadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UploadError::AiConfirmation(estimate) => {
+                write!(f, "Coût IA estimé à {:.4} $ : confirmation requise", estimate.cost_usd)
+            }
+            UploadError::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<String> for UploadError {
+    fn from(message: String) -> Self {
+        UploadError::Failed(message)
+    }
+}
+
+/// Estimation jointe à une réponse `409` de l'upload
+fn ai_confirmation(body: &str) -> Option<AiCostEstimate> {
+    let value: Value = serde_json::from_str(body).ok()?;
+    serde_json::from_value(value.get("aiEstimate")?.clone()).ok()
+}
+
+/// Seuil sous lequel la détection d'encodage est jugée incertaine
+pub const LOW_ENCODING_CONFIDENCE: f32 = 0.9;
+
+impl CsvInfo {
+    /// Détection d'encodage incertaine : proposer de le forcer
+    pub fn encoding_uncertain(&self) -> bool {
+        self.encoding_confidence.is_some_and(|c| c < LOW_ENCODING_CONFIDENCE)
+    }
+}
+
+/// Types MIME acceptés (les navigateurs Windows annoncent souvent les CSV en `vnd.ms-excel`)
+const ACCEPTED_MIME_TYPES: &[&str] = &[
+    "",
+    "text/csv",
+    "text/plain",
+    "text/x-csv",
+    "application/csv",
+    "application/vnd.ms-excel",
+    "application/json",
+    "application/x-ndjson",
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    "application/zip",
+    "application/x-zip-compressed",
+    "application/gzip",
+    "application/x-gzip",
+];
+
+/// Extensions acceptées : CSV, classeur Excel, fichier CWR, enregistrements JSON / NDJSON,
+/// ou l'un d'eux compressé (gzip, ou zip d'un seul fichier, décompressé par le backend)
+const ACCEPTED_EXTENSIONS: &[&str] = &[".csv", ".xlsx", ".cwr", ".v21", ".v22", ".json", ".ndjson", ".jsonl", ".zip", ".gz"];
+
+/// Numéros de ligne cités au plus dans le résumé des lignes irrégulières
+const MAX_RAGGED_LINES_SHOWN: usize = 10;
+
+/// Résumé des lignes au nombre de cellules différent de l'en-tête
+pub fn ragged_rows_summary(rows: &[RaggedRow]) -> String {
+    let mut lines: Vec<String> = rows.iter().take(MAX_RAGGED_LINES_SHOWN).map(|row| row.line.to_string()).collect();
+    if rows.len() > MAX_RAGGED_LINES_SHOWN {
+        lines.push("…".to_string());
+    }
+    let outcome = if rows.iter().all(|row| row.rejected) { "écartées" } else { "complétées ou tronquées" };
+    format!(
+        "{} ligne(s) avec un nombre de colonnes différent de l'en-tête, {} : ligne(s) {}",
+        rows.len(), outcome, lines.join(", ")
+    )
+}
+
+/// Vérifie extension, type MIME et taille avant tout envoi au backend
+pub fn validate_csv_file(name: &str, mime: &str, size: f64) -> Result<(), String> {
+    let lower = name.to_lowercase();
+    if !ACCEPTED_EXTENSIONS.iter().any(|ext| lower.ends_with(ext)) {
+        return Err(format!("« {} » n'est pas un fichier .csv, .xlsx, .cwr, .json, .zip ou .gz", name));
+    }
+    if !ACCEPTED_MIME_TYPES.contains(&mime.to_lowercase().as_str()) {
+        return Err(format!(
+            "« {} » n'est pas un fichier texte CSV (type {}). Exportez-le en CSV depuis votre tableur.",
+            name, mime
+        ));
+    }
+    if size > crate::MAX_FILE_SIZE as f64 {
+        return Err(format!(
+            "Fichier trop volumineux ({:.1} Mo, maximum {} Mo). Découpez le catalogue en plusieurs fichiers ou utilisez la CLI `massload transform`.",
+            size / (1024.0 * 1024.0),
+            crate::MAX_FILE_SIZE / (1024 * 1024)
+        ));
+    }
+    if size == 0.0 {
+        return Err(format!("« {} » est vide", name));
+    }
+    Ok(())
+}
+
+/// Crée le FormData avec le fichier
+fn file_form_data(file: &File) -> Result<FormData, String> {
     let form_data = FormData::new().map_err(|e| format!("Failed to create FormData: {:?}", e))?;
-    
-    // Ajouter le fichier
     form_data
-        .append_with_blob("file", &file)
+        .append_with_blob("file", file)
         .map_err(|e| format!("Failed to append file: {:?}", e))?;
+    Ok(form_data)
+}
+
+/// Analyse rapide du fichier (colonnes, encodage) avant l'upload réel
+pub async fn preflight_csv(file: &File, backend_url: &str, encoding: Option<&str>) -> Result<PreflightResponse, String> {
+    let form_data = file_form_data(file)?;
+    // Signale les lignes irrégulières sans les écarter
+    form_data
+        .append_with_str("ragged_rows", "warn")
+        .map_err(|e| format!("Failed to append field: {:?}", e))?;
+    if let Some(encoding) = encoding {
+        form_data
+            .append_with_str("encoding", encoding)
+            .map_err(|e| format!("Failed to append field: {:?}", e))?;
+    }
+
+    let url = format!("{}/api/v1/preflight", backend_url);
+    let response = Request::post(&url)
+        .body(form_data)
+        .map_err(|e| format!("Failed to build request: {}", e))?
+        .send()
+        .await
+        .map_err(|e| format!("HTTP request failed: {}", e))?;
+
+    if !response.ok() {
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Server error ({}): {}", response.status(), error_text));
+    }
+
+    response
+        .json::<PreflightResponse>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))
+}
+
+/// Upload un fichier CSV vers le backend
+pub async fn upload_csv(file: File, backend_url: &str, options: &UploadOptions) -> Result<UploadResponse, UploadError> {
+    // Créer FormData avec le fichier
+    let form_data = file_form_data(&file)?;
+
+    // Options
+    if !options.exclude_columns.is_empty() {
+        form_data
+            .append_with_str("exclude_columns", &options.exclude_columns.join(","))
+            .map_err(|e| format!("Failed to append field: {:?}", e))?;
+    }
+    if options.structure_only {
+        form_data
+            .append_with_str("structure_only", "true")
+            .map_err(|e| format!("Failed to append field: {:?}", e))?;
+    }
+    if let Some(ref encoding) = options.encoding {
+        form_data
+            .append_with_str("encoding", encoding)
+            .map_err(|e| format!("Failed to append field: {:?}", e))?;
+    }
+    if options.confirm_ai {
+        form_data
+            .append_with_str("confirm_ai", "true")
+            .map_err(|e| format!("Failed to append field: {:?}", e))?;
+    }
+    if let Some(ref period) = options.period {
+        form_data
+            .append_with_str("period", period)
+            .map_err(|e| format!("Failed to append field: {:?}", e))?;
+    }
 
     // Envoyer la requête
-    let url = format!("{}/api/upload", backend_url);
+    let mut url = format!("{}/api/v1/upload?network={}", backend_url, crate::CHAIN_NETWORK);
+    if options.verbose {
+        url.push_str("&verbose=true");
+    }
+    if let Some(ref job_id) = options.job_id {
+        url.push_str(&format!("&jobId={}", job_id));
+    }
     let request = Request::post(&url)
         .body(form_data)
         .map_err(|e| format!("Failed to build request: {}", e))?;
@@ -78,17 +752,72 @@ pub async fn upload_csv(file: File, backend_url: &str) -> Result<UploadResponse,
 
     // Vérifier le status
     if !response.ok() {
+        let status = response.status();
         let error_text = response
             .text()
             .await
             .unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Server error ({}): {}", response.status(), error_text));
+        if let Some(estimate) = (status == 409).then(|| ai_confirmation(&error_text)).flatten() {
+            return Err(UploadError::AiConfirmation(estimate));
+        }
+        return Err(format!("Server error ({}): {}", status, error_text).into());
     }
 
     // Parser la réponse JSON
-    response
+    Ok(response
         .json::<UploadResponse>()
         .await
+        .map_err(|e| format!("Failed to parse response: {}", e))?)
+}
+
+/// Complète les lignes ignorées d'un job puis récupère les œuvres regroupées
+pub async fn amend_job(job_id: &str, amendments: &[Amendment], backend_url: &str) -> Result<AmendResponse, String> {
+    let url = format!("{}/api/v1/jobs/{}/amend", backend_url, job_id);
+    let response = Request::post(&url)
+        .json(&serde_json::json!({ "amendments": amendments }))
+        .map_err(|e| format!("Failed to build request: {}", e))?
+        .send()
+        .await
+        .map_err(|e| format!("HTTP request failed: {}", e))?;
+
+    if !response.ok() {
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Server error ({}): {}", response.status(), error_text));
+    }
+
+    response
+        .json::<AmendResponse>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))
+}
+
+/// URL du fichier de demandes d'attribution d'ISWC d'un job (CSV)
+pub fn iswc_requests_url(job_id: &str, backend_url: &str) -> String {
+    format!("{}/api/v1/jobs/{}/iswc-requests", backend_url, job_id)
+}
+
+/// Récupère le profil des colonnes du fichier d'un job
+pub async fn fetch_job_columns(job_id: &str, backend_url: &str) -> Result<ColumnsResponse, String> {
+    let url = format!("{}/api/v1/jobs/{}/columns", backend_url, job_id);
+    let response = Request::get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("HTTP request failed: {}", e))?;
+
+    if !response.ok() {
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Server error ({}): {}", response.status(), error_text));
+    }
+
+    response
+        .json::<ColumnsResponse>()
+        .await
         .map_err(|e| format!("Failed to parse response: {}", e))
 }
 
@@ -96,6 +825,124 @@ pub async fn upload_csv(file: File, backend_url: &str) -> Result<UploadResponse,
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_validate_csv_file() {
+        assert!(validate_csv_file("catalogue.CSV", "text/csv", 1024.0).is_ok());
+        assert!(validate_csv_file("catalogue.csv", "application/vnd.ms-excel", 1024.0).is_ok());
+        assert!(validate_csv_file("catalogue.xlsx", "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet", 1024.0).is_ok());
+        assert!(validate_csv_file("catalogue.xls", "", 1024.0).is_err());
+        assert!(validate_csv_file("CW240001PUB_000.V21", "", 1024.0).is_ok());
+        assert!(validate_csv_file("oeuvres.ndjson", "application/x-ndjson", 1024.0).is_ok());
+        assert!(validate_csv_file("catalogue.csv.gz", "application/gzip", 1024.0).is_ok());
+        assert!(validate_csv_file("export.zip", "application/x-zip-compressed", 1024.0).is_ok());
+        assert!(validate_csv_file("photo.csv", "image/png", 1024.0).is_err());
+        assert!(validate_csv_file("vide.csv", "text/csv", 0.0).is_err());
+
+        let too_big = (crate::MAX_FILE_SIZE + 1) as f64;
+        let err = validate_csv_file("gros.csv", "text/csv", too_big).unwrap_err();
+        assert!(err.contains("trop volumineux"));
+    }
+
+    #[test]
+    fn test_ragged_rows_summary() {
+        let rows: Vec<RaggedRow> = (1..=12)
+            .map(|i| RaggedRow { line: i * 2, cells: 2, expected: 3, rejected: false })
+            .collect();
+        let summary = ragged_rows_summary(&rows);
+        assert!(summary.starts_with("12 ligne(s)"));
+        assert!(summary.ends_with("2, 4, 6, 8, 10, 12, 14, 16, 18, 20, …"));
+        assert!(summary.contains("complétées ou tronquées"));
+    }
+
+    #[test]
+    fn test_ai_confirmation_body() {
+        let body = r#"{"status": "error", "error": "Estimated AI cost", "aiEstimate":
+            {"promptChars": 26287, "inputTokens": 6572, "maxOutputTokens": 2048, "costUsd": 0.0504}}"#;
+        let estimate = ai_confirmation(body).unwrap();
+        assert_eq!(estimate.input_tokens, 6572);
+        assert!(UploadError::AiConfirmation(estimate).to_string().contains("0.0504"));
+        assert_eq!(ai_confirmation(r#"{"error": "boom"}"#), None);
+    }
+
+    #[test]
+    fn test_matrix_engine_label() {
+        let json = r#"{"engine": "heuristic", "failoverReason": "primary provider unavailable (HTTP 529)"}"#;
+        let engine: MatrixEngine = serde_json::from_str(json).unwrap();
+        assert!(engine.label().starts_with("heuristique"));
+        assert!(engine.failover_reason.is_some());
+
+        let engine = MatrixEngine { engine: "secondary".to_string(), model: Some("claude-haiku".to_string()), failover_reason: None };
+        assert_eq!(engine.label(), "IA de secours (claude-haiku)");
+    }
+
+    #[test]
+    fn test_skipped_row_deserialization() {
+        let json = r#"{
+            "row": 4,
+            "reason": "Missing required fields",
+            "missingFields": ["creatorIpi"],
+            "partial": {"iswc": "T1234567890", "title": "My Song"}
+        }"#;
+
+        let row: SkippedRow = serde_json::from_str(json).unwrap();
+        assert_eq!(row.single_missing_field(), Some("creatorIpi"));
+        assert_eq!(row.label(), "My Song");
+    }
+
+    #[test]
+    fn test_catalog_diff_works_to_submit() {
+        let json = r#"{
+            "catalog": "sacem",
+            "added": ["T0000000002", "title:sans iswc"],
+            "removed": ["T0000000009"],
+            "changed": [{"iswc": "T0000000003", "title": "Modifiée", "fields": ["creators"]}],
+            "unchanged": 1
+        }"#;
+        let diff: CatalogDiff = serde_json::from_str(json).unwrap();
+        let works = vec![
+            serde_json::json!({"iswc": "T0000000001", "title": "Inchangée"}),
+            serde_json::json!({"iswc": "T0000000002", "title": "Nouvelle"}),
+            serde_json::json!({"iswc": "T0000000003", "title": "Modifiée"}),
+            serde_json::json!({"title": "Sans ISWC"}),
+        ];
+
+        let submitted = diff.works_to_submit(&works);
+        let titles: Vec<&str> = submitted.iter()
+            .map(|w| w["title"].as_str().unwrap())
+            .collect();
+        assert_eq!(titles, vec!["Nouvelle", "Modifiée", "Sans ISWC"]);
+        assert_eq!(diff.summary(), "2 nouvelle(s), 1 modifiée(s), 1 supprimée(s), 1 inchangée(s)");
+    }
+
+    #[test]
+    fn test_preflight_deserialization() {
+        let json = r#"{
+            "fileName": "catalog.csv",
+            "csvInfo": {
+                "encoding": "utf-8",
+                "delimiter": ";",
+                "rowCount": 12,
+                "columns": ["Titre", "Email"],
+                "sha256": "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+                "report": {"rows": 12, "columns": [
+                    {"name": "Titre", "filled": 12, "nullRate": 0.0, "distinct": 12, "minLength": 3, "maxLength": 20,
+                     "patterns": [], "samples": ["Song", "Other"]},
+                    {"name": "Email", "filled": 0, "nullRate": 1.0, "distinct": 0, "minLength": 0, "maxLength": 0, "patterns": []}
+                ]}
+            }
+        }"#;
+
+        let response: PreflightResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.file_name.as_deref(), Some("catalog.csv"));
+        assert_eq!(response.csv_info.columns, vec!["Titre", "Email"]);
+        assert!(!response.csv_info.encoding_uncertain());
+        assert!(response.csv_info.sha256.unwrap().starts_with("ba7816bf"));
+        let report = &response.csv_info.report;
+        assert_eq!(report.empty_columns(), vec!["Email"]);
+        assert_eq!(report.high_cardinality_columns(), vec!["Titre"]);
+        assert_eq!(report.column("Titre").unwrap().summary(), "100 % remplie · ex. Song, Other");
+    }
+
     #[test]
     fn test_response_deserialization() {
         // Format MIDDS natif retourné par massload
@@ -140,7 +987,39 @@ mod tests {
         let response = result.unwrap();
         assert_eq!(response.status, "ready");
         assert_eq!(response.metadata.total_works, 1);
-        assert_eq!(response.metadata.cached, false);
+        assert!(!response.metadata.cached);
         assert_eq!(response.metadata.csv_info.encoding, "utf-8");
     }
+
+    #[test]
+    fn test_failure_report_deserialization() {
+        let json = r#"{
+            "topCauses": [{"message": "value is not of type \"integer\"", "count": 12}],
+            "suggestions": ["check_delimiter", "check_template"]
+        }"#;
+
+        let report: FailureReport = serde_json::from_str(json).unwrap();
+        assert_eq!(report.top_causes[0].count, 12);
+        assert_eq!(report.suggestions, vec![Suggestion::CheckDelimiter, Suggestion::CheckTemplate]);
+        assert!(report.suggestions[0].label().contains("séparateur"));
+    }
+
+    #[test]
+    fn test_columns_response_deserialization() {
+        let json = r#"{
+            "jobId": "abc",
+            "rowCount": 3,
+            "columns": [
+                {"name": "ISWC", "filled": 3, "nullRate": 0.0, "distinct": 3, "minLength": 11, "maxLength": 15,
+                 "patterns": [{"pattern": "[A-Z]\\d{10}", "count": 2}]},
+                {"name": "Pays", "filled": 3, "nullRate": 0.0, "distinct": 1, "minLength": 2, "maxLength": 2, "patterns": []}
+            ]
+        }"#;
+
+        let response: ColumnsResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.row_count, 3);
+        assert!(response.columns[0].is_unique());
+        assert!(response.columns[1].is_constant());
+        assert!(!response.columns[1].is_empty());
+    }
 }
@@ -31,6 +31,45 @@ pub struct PreviewItem {
     pub creators_count: usize,
 }
 
+impl PreviewItem {
+    /// Build a preview item from a MIDDS musical work.
+    ///
+    /// Returns `None` when the work has no ISWC or title.
+    pub fn from_work(work: &serde_json::Value) -> Option<Self> {
+        let iswc = work.get("iswc")?.as_str()?.to_string();
+
+        let title = work_title(work)?.to_string();
+
+        let creators_count = work.get("creators")
+            .and_then(|c| c.as_array())
+            .map(|arr| arr.len())
+            .unwrap_or(0);
+
+        Some(Self { title, iswc, creators_count })
+    }
+
+    /// Whether the title or ISWC contains `query` (case-insensitive; empty matches all)
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        query.is_empty() || self.title.to_lowercase().contains(&query) || self.iswc.to_lowercase().contains(&query)
+    }
+
+    /// Build preview items for a list of works (skipping incomplete ones)
+    pub fn from_works(works: &[serde_json::Value]) -> Vec<Self> {
+        works.iter().filter_map(Self::from_work).collect()
+    }
+}
+
+/// Title text of a MIDDS work.
+///
+/// The title can be a string or an object `{title: "...", language: "..."}`.
+pub fn work_title(work: &serde_json::Value) -> Option<&str> {
+    match work.get("title")? {
+        serde_json::Value::Object(obj) => obj.get("title")?.as_str(),
+        title => title.as_str(),
+    }
+}
+
 // =============================================================================
 // Log Types
 // =============================================================================
@@ -76,17 +115,81 @@ impl LogLevel {
     }
 }
 
-/// A single log entry from the backend.
-///
-/// Received via SSE from `/api/logs` endpoint.
+/// Pipeline stage of a backend log entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStage {
+    Parse,
+    Template,
+    Ai,
+    Transform,
+    Validate,
+    Group,
+}
+
+impl LogStage {
+    /// Libellé affiché en tête de groupe.
+    pub fn label(&self) -> &'static str {
+        match self {
+            LogStage::Parse => "Lecture du fichier",
+            LogStage::Template => "Templates",
+            LogStage::Ai => "Analyse IA",
+            LogStage::Transform => "Transformation",
+            LogStage::Validate => "Validation",
+            LogStage::Group => "Regroupement",
+        }
+    }
+
+    /// Get CSS class for styling.
+    pub fn css_class(&self) -> &'static str {
+        match self {
+            LogStage::Parse => "stage-parse",
+            LogStage::Template => "stage-template",
+            LogStage::Ai => "stage-ai",
+            LogStage::Transform => "stage-transform",
+            LogStage::Validate => "stage-validate",
+            LogStage::Group => "stage-group",
+        }
+    }
+}
+
+/// A single log entry, from the backend (SSE `/api/v1/logs`) or the frontend.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct LogEntry {
     /// Severity level
     pub level: LogLevel,
     /// Log message
     pub message: String,
-    /// Timestamp string (HH:MM:SS)
+    /// Emission time (RFC3339)
     pub timestamp: String,
+    /// Pipeline stage (backend entries only)
+    #[serde(default)]
+    pub stage: Option<LogStage>,
+}
+
+impl LogEntry {
+    /// Create a frontend entry stamped with the current time.
+    pub fn new(level: LogLevel, message: impl Into<String>) -> Self {
+        Self {
+            level,
+            message: message.into(),
+            timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            stage: None,
+        }
+    }
+
+    /// Parsed timestamp, used to order entries.
+    pub fn time(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        chrono::DateTime::parse_from_rfc3339(&self.timestamp).ok()
+    }
+
+    /// Local time (HH:MM:SS) for display.
+    pub fn time_label(&self) -> String {
+        match self.time() {
+            Some(t) => t.with_timezone(&chrono::Local).format("%H:%M:%S").to_string(),
+            None => self.timestamp.clone(),
+        }
+    }
 }
 
 // =============================================================================